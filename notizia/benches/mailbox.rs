@@ -0,0 +1,73 @@
+//! Throughput of the regular multi-producer mailbox compared to the
+//! single-producer ring buffer.
+//!
+//! Run with `cargo bench --features spsc`.
+
+use std::hint::black_box;
+use std::time::Duration;
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use notizia::feed;
+use notizia::prelude::*;
+use tokio::runtime::Runtime;
+
+const MESSAGES: u64 = 10_000;
+const RING_CAPACITY: usize = 1024;
+
+#[derive(Task)]
+#[task(message = u64)]
+struct Sink;
+
+impl Runnable<u64> for Sink {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            black_box(msg);
+        }
+    }
+}
+
+/// Feed `MESSAGES` messages to `handle` and wait until all were received.
+async fn drain(handle: TaskHandle<u64>) {
+    for i in 0..MESSAGES {
+        feed!(handle, i).await.unwrap();
+    }
+    handle.shutdown(Duration::from_secs(10)).await.unwrap();
+}
+
+fn mailbox(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mailbox");
+    group.throughput(Throughput::Elements(MESSAGES));
+
+    for (name, runtime) in [
+        (
+            "current_thread",
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build(),
+        ),
+        (
+            "multi_thread",
+            tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build(),
+        ),
+    ] {
+        let runtime: Runtime = runtime.unwrap();
+
+        group.bench_function(BenchmarkId::new("mpsc", name), |b| {
+            b.iter(|| runtime.block_on(async { drain(Sink.run()).await }));
+        });
+        group.bench_function(BenchmarkId::new("spsc", name), |b| {
+            b.iter(|| {
+                runtime.block_on(async {
+                    drain(Sink.builder().single_producer(RING_CAPACITY).spawn()).await
+                })
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, mailbox);
+criterion_main!(benches);
@@ -105,7 +105,7 @@ impl Runnable<CounterMsg> for Counter {
 
     async fn terminate(&self, reason: TerminateReason) {
         match reason {
-            TerminateReason::Normal => {
+            TerminateReason::Normal | TerminateReason::Idle | TerminateReason::DeadlineExceeded => {
                 let final_count = self.count.load(Ordering::SeqCst);
                 let total_ops = self.operations.load(Ordering::SeqCst);
                 println!(
@@ -238,9 +238,12 @@ async fn main() {
     // This will succeed
     match call!(handle_arc, |tx| CounterMsg::GetCount { reply_to: tx }).await {
         Ok(count) => println!("   ✓ Retrieved count: {}", count),
-        Err(CallError::Timeout) => println!("   ✗ Request timed out"),
+        Err(CallError::Timeout { name, elapsed, .. }) => {
+            println!("   ✗ Request to {} timed out after {:?}", name, elapsed)
+        }
         Err(CallError::ChannelClosed) => println!("   ✗ Service dropped the response channel"),
-        Err(CallError::SendError) => println!("   ✗ Service is not running"),
+        Err(CallError::SendError { .. }) => println!("   ✗ Service is not running"),
+        Err(e) => println!("   ✗ Call failed: {}", e),
     }
     println!();
 
@@ -258,8 +261,11 @@ async fn main() {
         panic!("Failed to unwrap Arc - still has references");
     });
     match handle.join().await {
-        Ok(TerminateReason::Normal) => println!("   ✓ Service stopped gracefully\n"),
+        Ok(TerminateReason::Normal | TerminateReason::Idle) => {
+            println!("   ✓ Service stopped gracefully\n")
+        }
         Ok(TerminateReason::Panic(msg)) => println!("   ✗ Service panicked: {}\n", msg),
+        Ok(TerminateReason::DeadlineExceeded) => println!("   ✗ Service ran out of time\n"),
         Err(e) => println!("   ✗ Join error: {:?}\n", e),
     }
 
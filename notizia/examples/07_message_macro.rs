@@ -19,7 +19,7 @@ struct CounterStats {
 
 // Message enum using the #[message] macro
 // The #[request(reply = T)] attribute automatically adds:
-//   reply_to: tokio::sync::oneshot::Sender<T>
+//   reply_to: notizia::Reply<T>
 #[message]
 #[derive(Debug)]
 #[allow(dead_code)]
@@ -57,7 +57,7 @@ impl Runnable<CounterMsg> for Counter {
                 Ok(CounterMsg::GetCount { reply_to }) => {
                     let count = self.count.load(Ordering::SeqCst);
                     println!("GetCount request - responding with: {}", count);
-                    if reply_to.send(count).is_err() {
+                    if reply_to.reply(count).is_err() {
                         eprintln!("Failed to send count response");
                     }
                 }
@@ -68,7 +68,7 @@ impl Runnable<CounterMsg> for Counter {
                         total_operations: self.operations.load(Ordering::SeqCst) as u64,
                     };
                     println!("GetStats request - responding with: {:?}", stats);
-                    if reply_to.send(stats).is_err() {
+                    if reply_to.reply(stats).is_err() {
                         eprintln!("Failed to send stats response");
                     }
                 }
@@ -151,8 +151,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let stats = call!(handle, CounterMsg::GetStats, timeout = 1000).await?;
     println!("Statistics: {:?}\n", stats);
 
-    // Get final count
-    let final_count = call!(handle, CounterMsg::GetCount).await?;
+    // Get final count through the generated `CounterMsgCalls` trait
+    let final_count = handle.get_count().await?;
     println!("Final count: {}", final_count);
 
     // Stop the counter
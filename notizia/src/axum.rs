@@ -0,0 +1,157 @@
+//! Serving HTTP from tasks with axum.
+//!
+//! Handlers reach tasks through axum state: a [`TaskRef`](crate::TaskRef)
+//! is cheap to clone, so it can be the state itself, or a field of a larger
+//! state extracted with [`FromRef`](::axum::extract::FromRef). With this
+//! module, [`CallError`] implements
+//! [`IntoResponse`], so handlers can `call!` a task and return the error
+//! with `?`:
+//!
+//! | Error | Status |
+//! |---|---|
+//! | [`CallError::Timeout`] | `504 Gateway Timeout` |
+//! | [`CallError::SendError`], [`CallError::CircuitOpen`] | `503 Service Unavailable` |
+//! | others | `500 Internal Server Error` |
+//!
+//! [`serve`] runs the server next to a [`TaskSet`] of the tasks it talks
+//! to, and shuts both down together.
+//!
+//! This module requires the `axum` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//!
+//! use axum::Router;
+//! use axum::extract::State;
+//! use axum::routing::get;
+//! use notizia::core::errors::CallError;
+//! use notizia::{call, message};
+//! use notizia::prelude::*;
+//! use notizia::task::TaskSet;
+//!
+//! #[message]
+//! enum CounterMsg {
+//!     #[request(reply = u64)]
+//!     Get,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = CounterMsg)]
+//! struct Counter;
+//!
+//! impl Runnable<CounterMsg> for Counter {
+//!     async fn start(&self) {
+//!         // ...
+//!     }
+//! }
+//!
+//! async fn count(State(counter): State<TaskRef<CounterMsg>>) -> Result<String, CallError> {
+//!     let count = call!(counter, CounterMsg::Get).await?;
+//!     Ok(count.to_string())
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! let counter = spawn!(Counter);
+//! let app = Router::new()
+//!     .route("/count", get(count))
+//!     .with_state(counter.this());
+//!
+//! let mut tasks = TaskSet::new();
+//! tasks.insert(counter);
+//!
+//! let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await?;
+//! let shutdown = async {
+//!     let _ = tokio::signal::ctrl_c().await;
+//! };
+//! notizia::axum::serve(listener, app, tasks, shutdown, Duration::from_secs(5)).await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+
+use ::axum::Router;
+use ::axum::http::StatusCode;
+use ::axum::response::{IntoResponse, Response};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+use crate::core::errors::CallError;
+use crate::core::lifecycle::{ShutdownError, ShutdownResult};
+use crate::task::{TaskId, TaskSet};
+
+impl CallError {
+    /// The HTTP status a handler answers with when the call fails.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            CallError::Timeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            CallError::SendError { .. } | CallError::CircuitOpen => StatusCode::SERVICE_UNAVAILABLE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+impl IntoResponse for CallError {
+    fn into_response(self) -> Response {
+        (self.status_code(), self.to_string()).into_response()
+    }
+}
+
+/// Serve `router` on `listener` until `signal` resolves or one of `tasks`
+/// terminates, then shut the tasks down.
+///
+/// The server shuts down gracefully first, so requests in flight can still
+/// call the tasks. Afterwards the remaining tasks are shut down like with
+/// [`TaskSet::shutdown_all`] within `timeout`. A task terminating on its
+/// own, e.g. after a panic, brings the server down as well, instead of
+/// leaving it to answer every request with an error.
+///
+/// Returns how each task terminated.
+///
+/// # Errors
+///
+/// Returns an error if the server fails to accept connections. The tasks
+/// are shut down nonetheless.
+pub async fn serve<F>(
+    listener: TcpListener,
+    router: Router,
+    mut tasks: TaskSet,
+    signal: F,
+    timeout: Duration,
+) -> io::Result<Vec<(TaskId, ShutdownResult)>>
+where
+    F: Future<Output = ()>,
+{
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = ::axum::serve(listener, router).with_graceful_shutdown(async move {
+        let _ = stopped.await;
+    });
+    let mut server = tokio::spawn(server.into_future());
+
+    let mut results = Vec::new();
+    let served = tokio::select! {
+        served = &mut server => Some(served),
+        () = signal => None,
+        Some((id, result)) = tasks.join_next() => {
+            results.push((id, result.map_err(ShutdownError::from)));
+            None
+        }
+    };
+
+    let served = match served {
+        Some(served) => served,
+        None => {
+            let _ = stop.send(());
+            server.await
+        }
+    };
+
+    results.extend(tasks.shutdown_all(timeout).await);
+    served.map_err(io::Error::other)??;
+    Ok(results)
+}
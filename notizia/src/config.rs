@@ -0,0 +1,190 @@
+//! Loading configuration and publishing its changes.
+//!
+//! A [`ConfigTask`] owns the configuration of an application. It loads the
+//! configuration with a loader function, reloads it on request and publishes
+//! every change on a [`watch`] channel. Tasks
+//! [subscribe](ConfigMsg::Subscribe) to the channel and receive the changes
+//! as messages of their own with [`Context::watch`](crate::task::Context::watch).
+//!
+//! Since a watch channel only holds the latest configuration, changes made
+//! in quick succession reach a busy subscriber as one, and a reload that
+//! leaves the configuration unchanged is not published at all.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::call;
+//! use notizia::config::{ConfigMsg, ConfigTask};
+//! use notizia::prelude::*;
+//! use tokio::sync::watch;
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Config {
+//!     workers: usize,
+//! }
+//!
+//! fn load() -> std::io::Result<Config> {
+//!     let workers = std::fs::read_to_string("workers.conf")?;
+//!     Ok(Config {
+//!         workers: workers.trim().parse().unwrap_or(4),
+//!     })
+//! }
+//!
+//! enum PoolMsg {
+//!     Watch(watch::Receiver<Config>),
+//!     ConfigChanged(Config),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = PoolMsg, handler)]
+//! struct Pool {
+//!     workers: usize,
+//! }
+//!
+//! impl Handler<PoolMsg> for Pool {
+//!     async fn handle(&mut self, msg: PoolMsg, ctx: &mut Context<PoolMsg>) {
+//!         match msg {
+//!             PoolMsg::Watch(config) => {
+//!                 ctx.watch(config, PoolMsg::ConfigChanged);
+//!             }
+//!             PoolMsg::ConfigChanged(config) => self.workers = config.workers,
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = ConfigTask::new(load)?.run();
+//! let pool = Pool { workers: 0 }.run();
+//! pool.send(PoolMsg::Watch(call!(config, ConfigMsg::Subscribe).await?))?;
+//!
+//! // Later, e.g. on SIGHUP
+//! call!(config, ConfigMsg::Reload).await??;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::error::Error;
+
+use tokio::sync::watch;
+
+use crate::core::reply::Reply;
+use crate::task::{Context, Handler};
+
+/// Loads the configuration of a [`ConfigTask`].
+type Loader<C> = Box<dyn FnMut() -> Result<C, ConfigError> + Send>;
+
+/// A configuration that could not be loaded.
+#[derive(Debug, thiserror::Error)]
+#[error("failed to load configuration: {0}")]
+pub struct ConfigError(#[source] pub Box<dyn Error + Send + Sync>);
+
+/// Requests answered by a [`ConfigTask`].
+///
+/// Like messages defined with `#[message]`, requests carry a `reply_to`
+/// field and are sent with [`call!`](crate::call!).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ConfigMsg<C> {
+    /// Get the current configuration
+    Get { reply_to: Reply<C> },
+    /// Get a receiver of the current configuration and its changes
+    Subscribe { reply_to: Reply<watch::Receiver<C>> },
+    /// Load the configuration again and publish it if it changed
+    ///
+    /// If it cannot be loaded, the current configuration is kept and the
+    /// error is returned.
+    Reload {
+        reply_to: Reply<Result<(), ConfigError>>,
+    },
+    /// Replace the configuration and publish it if it changed
+    Set(C),
+}
+
+/// A task owning the configuration of an application.
+///
+/// See the [module documentation](self) for an example.
+#[derive(crate::Task)]
+#[task(message = ConfigMsg<C>, handler)]
+pub struct ConfigTask<C>
+where
+    C: Clone + PartialEq + Send + Sync + 'static,
+{
+    loader: Option<Loader<C>>,
+    config: watch::Sender<C>,
+}
+
+impl<C> ConfigTask<C>
+where
+    C: Clone + PartialEq + Send + Sync + 'static,
+{
+    /// Load the configuration with `loader`, which is called again on every
+    /// [reload](ConfigMsg::Reload).
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of `loader` if the configuration cannot be loaded.
+    pub fn new<L, E>(mut loader: L) -> Result<Self, ConfigError>
+    where
+        L: FnMut() -> Result<C, E> + Send + 'static,
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        let mut loader: Loader<C> =
+            Box::new(move || loader().map_err(|err| ConfigError(err.into())));
+        let config = loader()?;
+        Ok(ConfigTask {
+            loader: Some(loader),
+            config: watch::Sender::new(config),
+        })
+    }
+
+    /// Start with the given configuration, which changes only when
+    /// [set](ConfigMsg::Set). Reloading keeps it as it is.
+    pub fn with_config(config: C) -> Self {
+        ConfigTask {
+            loader: None,
+            config: watch::Sender::new(config),
+        }
+    }
+
+    /// A receiver of the current configuration and its changes.
+    pub fn subscribe(&self) -> watch::Receiver<C> {
+        self.config.subscribe()
+    }
+
+    /// Publish `config` if it differs from the current configuration.
+    fn publish(&self, config: C) {
+        self.config.send_if_modified(|current| {
+            let changed = *current != config;
+            if changed {
+                *current = config;
+            }
+            changed
+        });
+    }
+}
+
+impl<C> Handler<ConfigMsg<C>> for ConfigTask<C>
+where
+    C: Clone + PartialEq + Send + Sync + 'static,
+{
+    async fn handle(&mut self, msg: ConfigMsg<C>, _ctx: &mut Context<ConfigMsg<C>>) {
+        // Callers that gave up waiting are of no concern
+        match msg {
+            ConfigMsg::Get { reply_to } => {
+                let _ = reply_to.reply(self.config.borrow().clone());
+            }
+            ConfigMsg::Subscribe { reply_to } => {
+                let _ = reply_to.reply(self.config.subscribe());
+            }
+            ConfigMsg::Reload { reply_to } => {
+                let result = match &mut self.loader {
+                    Some(loader) => loader().map(|config| self.publish(config)),
+                    None => Ok(()),
+                };
+                let _ = reply_to.reply(result);
+            }
+            ConfigMsg::Set(config) => self.publish(config),
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! Correlation ids for request chains.
+//!
+//! Every [`call!`](crate::call!) is tagged with a [`CorrelationId`]. The id
+//! travels with the request's [`Reply`](crate::Reply), shows up in timeouts
+//! and in the warning for unanswered requests, and can be read by the
+//! handler with [`Reply::correlation_id`](crate::Reply::correlation_id).
+//!
+//! While a task handles a request received through [`recv!`](crate::recv!),
+//! the request's id is the task's [current](CorrelationId::current) one.
+//! Calls the task makes to other tasks in the meantime reuse it instead of
+//! allocating a fresh id, so a chain of requests across several tasks can be
+//! stitched together in logs.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::message;
+//!
+//! #[message]
+//! #[derive(Debug)]
+//! enum FrontMsg {
+//!     #[request(reply = u32)]
+//!     Lookup,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = FrontMsg)]
+//! struct Front;
+//!
+//! impl Runnable<FrontMsg> for Front {
+//!     async fn start(&self) {
+//!         while let Ok(FrontMsg::Lookup { reply_to }) = recv!(self) {
+//!             // Same id as `reply_to.correlation_id()`
+//!             println!("handling {:?}", CorrelationId::current());
+//!             let _ = reply_to.reply(42);
+//!         }
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+
+use std::cell::Cell;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+tokio::task_local! {
+    static CURRENT: Cell<Option<CorrelationId>>;
+}
+
+/// Identifier shared by all requests belonging to one request chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CorrelationId(u64);
+
+impl CorrelationId {
+    /// Allocate a fresh identifier.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        CorrelationId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The correlation id of the request the current task is handling.
+    ///
+    /// Returns `None` outside of tasks and while the task handles a message
+    /// that is not a request.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(Cell::get).ok().flatten()
+    }
+
+    /// The current correlation id, or a fresh one if there is none.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn current_or_next() -> Self {
+        Self::current().unwrap_or_else(Self::next)
+    }
+
+    /// The numeric value of the identifier.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Messages that can carry a correlation id.
+///
+/// `#[message]` implements this for enums with request variants.
+pub trait Correlated {
+    /// The correlation id of the message, if it is a request.
+    fn correlation_id(&self) -> Option<CorrelationId>;
+}
+
+/// Run `future` with its own current correlation id.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn scope<F>(future: F) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    CURRENT.scope(Cell::new(None), future)
+}
+
+/// Make `id` the current correlation id of the running task.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn enter(id: Option<CorrelationId>) {
+    let _ = CURRENT.try_with(|current| current.set(id));
+}
+
+/// Wrapper used by [`recv!`](crate::recv!) to look up the correlation id of
+/// messages that may or may not implement [`Correlated`].
+#[doc(hidden)]
+pub struct Probe<'a, T>(pub &'a T);
+
+#[doc(hidden)]
+pub trait ViaCorrelated {
+    fn correlation_id(&self) -> Option<CorrelationId>;
+}
+
+impl<T> ViaCorrelated for Probe<'_, T>
+where
+    T: Correlated,
+{
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        self.0.correlation_id()
+    }
+}
+
+#[doc(hidden)]
+pub trait ViaAny {
+    fn correlation_id(&self) -> Option<CorrelationId>;
+}
+
+impl<T> ViaAny for &Probe<'_, T> {
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn current_id_is_scoped_per_task() {
+        assert_eq!(CorrelationId::current(), None);
+
+        let id = CorrelationId::next();
+        scope(async move {
+            assert_eq!(CorrelationId::current(), None);
+            enter(Some(id));
+            assert_eq!(CorrelationId::current(), Some(id));
+            assert_eq!(CorrelationId::current_or_next(), id);
+        })
+        .await;
+
+        assert_eq!(CorrelationId::current(), None);
+        assert_ne!(CorrelationId::current_or_next(), id);
+    }
+}
@@ -0,0 +1,162 @@
+//! Messages that could not be delivered.
+//!
+//! Messages sent with [`send_reliable!`](crate::send_reliable!) that were
+//! never acknowledged end up in a process-wide dead-letter queue, so they can
+//! be inspected, logged or sent again later. The queue holds the latest
+//! [`DEAD_LETTER_CAPACITY`] letters; older ones are discarded.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::core::dead_letter;
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! loop {
+//!     let letter = dead_letter::recv().await;
+//!     eprintln!("{} (task {}) lost a message: {}", letter.name(), letter.task(), letter.reason());
+//!
+//!     if let Ok(amount) = letter.downcast::<u64>() {
+//!         eprintln!("undelivered amount: {amount}");
+//!     }
+//! }
+//! # }
+//! ```
+
+use std::any::Any;
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::{LazyLock, Mutex};
+
+use tokio::sync::Notify;
+
+use super::trace;
+use crate::task::TaskId;
+
+/// Number of letters the dead-letter queue holds.
+pub const DEAD_LETTER_CAPACITY: usize = 1024;
+
+static QUEUE: LazyLock<Queue> = LazyLock::new(|| Queue {
+    letters: Mutex::new(VecDeque::new()),
+    pushed: Notify::new(),
+});
+
+struct Queue {
+    letters: Mutex<VecDeque<DeadLetter>>,
+    pushed: Notify,
+}
+
+/// A message that could not be delivered to a task.
+pub struct DeadLetter {
+    task: TaskId,
+    name: &'static str,
+    attempts: u32,
+    reason: String,
+    message: Box<dyn Any + Send>,
+}
+
+impl DeadLetter {
+    pub(crate) fn new<T>(
+        task: TaskId,
+        name: &'static str,
+        attempts: u32,
+        reason: &dyn fmt::Display,
+        message: T,
+    ) -> Self
+    where
+        T: Send + 'static,
+    {
+        DeadLetter {
+            task,
+            name,
+            attempts,
+            reason: reason.to_string(),
+            message: Box::new(message),
+        }
+    }
+
+    /// The task the message was sent to.
+    pub fn task(&self) -> TaskId {
+        self.task
+    }
+
+    /// Name of the task the message was sent to.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Number of times the message was sent.
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    /// Why the last attempt failed.
+    pub fn reason(&self) -> &str {
+        &self.reason
+    }
+
+    /// Check whether the message is of type `M`.
+    pub fn is<M>(&self) -> bool
+    where
+        M: 'static,
+    {
+        self.message.is::<M>()
+    }
+
+    /// Take the message out if it is of type `M`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the letter itself if the message has a different type.
+    pub fn downcast<M>(self) -> Result<M, Self>
+    where
+        M: 'static,
+    {
+        match self.message.downcast() {
+            Ok(message) => Ok(*message),
+            Err(message) => Err(DeadLetter { message, ..self }),
+        }
+    }
+}
+
+impl fmt::Debug for DeadLetter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeadLetter")
+            .field("task", &self.task)
+            .field("name", &self.name)
+            .field("attempts", &self.attempts)
+            .field("reason", &self.reason)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Put a letter into the queue, discarding the oldest one if it is full.
+pub(crate) fn push(letter: DeadLetter) {
+    trace::dead_lettered(letter.name, letter.attempts, &letter.reason);
+
+    let mut letters = QUEUE.letters.lock().unwrap();
+    if letters.len() == DEAD_LETTER_CAPACITY {
+        letters.pop_front();
+    }
+    letters.push_back(letter);
+    drop(letters);
+
+    QUEUE.pushed.notify_waiters();
+}
+
+/// Take all letters from the queue, oldest first.
+pub fn drain() -> Vec<DeadLetter> {
+    QUEUE.letters.lock().unwrap().drain(..).collect()
+}
+
+/// Wait for the next letter and take it from the queue.
+pub async fn recv() -> DeadLetter {
+    loop {
+        // Register before checking, so no letter is missed
+        let pushed = QUEUE.pushed.notified();
+        if let Some(letter) = QUEUE.letters.lock().unwrap().pop_front() {
+            return letter;
+        }
+        pushed.await;
+    }
+}
@@ -0,0 +1,190 @@
+//! Debouncing and conflation of incoming messages.
+//!
+//! Tasks that mirror fast-changing state (prices, positions, UI models) often
+//! only care about the latest update per key. [`Debounced`] wraps a task's
+//! [`Mailbox`] and collapses bursts of messages sharing the same key into the
+//! most recent one before the task sees them.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::core::Debounced;
+//! use std::time::Duration;
+//!
+//! #[derive(Debug, Clone)]
+//! enum QuoteMsg {
+//!     Price { symbol: String, price: f64 },
+//!     Flush,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = QuoteMsg)]
+//! struct Quotes;
+//!
+//! impl Runnable<QuoteMsg> for Quotes {
+//!     async fn start(&self) {
+//!         let mut updates = Debounced::new(self.mailbox(), |msg: &QuoteMsg| match msg {
+//!             QuoteMsg::Price { symbol, .. } => Some(symbol.clone()),
+//!             QuoteMsg::Flush => None,
+//!         })
+//!         .window(Duration::from_millis(50));
+//!
+//!         while let Ok(msg) = updates.recv().await {
+//!             // Only the latest price per symbol arrives here
+//!             println!("{msg:?}");
+//!         }
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use super::Mailbox;
+use super::errors::{RecvError, RecvResult};
+use crate::runtime::Instant;
+
+/// A view on a [`Mailbox`] that conflates messages by key.
+///
+/// Every call to [`recv`](Self::recv) first drains all messages that are
+/// already queued. Messages for which the key function returns the same
+/// `Some(key)` replace each other, so only the latest value is delivered, at
+/// the position where the key first appeared. Messages without a key are
+/// never conflated and keep their relative order.
+///
+/// With a [debounce window](Self::window), `recv` additionally keeps
+/// collecting messages for that long after the first one of a burst
+/// arrives, so rapid updates are merged even if the task is idle.
+pub struct Debounced<T, K, F> {
+    mailbox: Mailbox<T>,
+    key: F,
+    window: Duration,
+    pending: VecDeque<(Option<K>, T)>,
+    closed: bool,
+}
+
+impl<T, K, F> Debounced<T, K, F>
+where
+    K: PartialEq,
+    F: Fn(&T) -> Option<K>,
+{
+    /// Conflate messages from `mailbox` by the key returned from `key`.
+    pub fn new(mailbox: Mailbox<T>, key: F) -> Self {
+        Debounced {
+            mailbox,
+            key,
+            window: Duration::ZERO,
+            pending: VecDeque::new(),
+            closed: false,
+        }
+    }
+
+    /// Collect messages for `window` after the first message of a burst.
+    pub fn window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Receive the next conflated message.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`] once the mailbox is closed and all
+    /// pending messages have been delivered, and [`RecvError::Poisoned`] if
+    /// the mailbox has no receiver.
+    pub async fn recv(&mut self) -> RecvResult<T> {
+        if self.pending.is_empty() && !self.closed {
+            let first = self.mailbox.recv().await?;
+            self.push(first);
+
+            if !self.window.is_zero() {
+                let deadline = Instant::now() + self.window;
+                loop {
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    match self.mailbox.recv_timeout(remaining).await {
+                        Ok(msg) => self.push(msg),
+                        Err(RecvError::Timeout) => break,
+                        Err(RecvError::Closed) => {
+                            self.closed = true;
+                            break;
+                        }
+                        Err(err) => return Err(err),
+                    }
+                }
+            }
+        }
+
+        self.drain().await?;
+
+        self.pending
+            .pop_front()
+            .map(|(_, msg)| msg)
+            .ok_or(RecvError::Closed)
+    }
+
+    /// Number of conflated messages waiting to be delivered.
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Move all immediately available messages into the pending queue.
+    async fn drain(&mut self) -> RecvResult<()> {
+        while !self.closed {
+            match self.mailbox.try_recv().await {
+                Ok(Some(msg)) => self.push(msg),
+                Ok(None) => break,
+                Err(RecvError::Closed) => self.closed = true,
+                Err(err) => return Err(err),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn push(&mut self, msg: T) {
+        let key = (self.key)(&msg);
+
+        if key.is_some()
+            && let Some(slot) = self.pending.iter_mut().find(|(k, _)| *k == key)
+        {
+            slot.1 = msg;
+            return;
+        }
+
+        self.pending.push_back((key, msg));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Envelope;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[tokio::test]
+    async fn queued_updates_are_conflated_by_key() {
+        let (sender, receiver) = unbounded_channel();
+        let mailbox = Mailbox::from_receiver(receiver);
+        let mut debounced = Debounced::new(mailbox, |(key, _): &(Option<u8>, u32)| *key);
+
+        for msg in [
+            (Some(1), 1),
+            (None, 2),
+            (Some(1), 3),
+            (Some(2), 4),
+            (None, 5),
+        ] {
+            sender.send(Envelope::User(msg)).unwrap();
+        }
+        drop(sender);
+
+        let mut received = Vec::new();
+        while let Ok((_, value)) = debounced.recv().await {
+            received.push(value);
+        }
+
+        assert_eq!(received, vec![3, 2, 4, 5]);
+    }
+}
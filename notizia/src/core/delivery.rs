@@ -0,0 +1,115 @@
+//! Acknowledged delivery of messages.
+//!
+//! [`send_reliable!`](crate::send_reliable!) sends a message and waits until
+//! the receiving task has processed it. The task's mailbox acknowledges the
+//! message once the task asks for its next message, after the handler of a
+//! handler task returned, or when [`Mailbox::ack`](super::Mailbox::ack) is
+//! called. A message that is not acknowledged in time, or that is dropped
+//! unprocessed because the task stopped or panicked, is sent again according
+//! to a [`RetryPolicy`]. Once the attempts are used up, the message is put
+//! into the [dead-letter queue](super::dead_letter).
+//!
+//! This gives at-least-once semantics: a message processed by a task that
+//! failed to acknowledge it in time is delivered again, so handlers of
+//! reliable messages should be idempotent.
+
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::oneshot;
+
+use super::correlation::CorrelationId;
+use super::dead_letter::{self, DeadLetter};
+use super::errors::{CallError, CallResult, SendResult};
+use super::retry::{DEFAULT_MAX_ATTEMPTS, RetryPolicy};
+use super::time::IntoTimeout;
+use crate::runtime::{self, Instant};
+use crate::task::TaskId;
+
+/// Acknowledgement travelling with a message sent by
+/// [`send_reliable!`](crate::send_reliable!).
+///
+/// This is an implementation detail of the generated code.
+#[doc(hidden)]
+#[derive(Debug, Clone)]
+pub struct Ack(Arc<Mutex<Option<oneshot::Sender<()>>>>);
+
+impl Ack {
+    pub(crate) fn channel() -> (Self, oneshot::Receiver<()>) {
+        let (sender, receiver) = oneshot::channel();
+        (Ack(Arc::new(Mutex::new(Some(sender)))), receiver)
+    }
+
+    /// Report the message as processed.
+    pub(crate) fn ack(self) {
+        if let Some(sender) = self.0.lock().unwrap().take() {
+            let _ = sender.send(());
+        }
+    }
+}
+
+impl PartialEq for Ack {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Ack {}
+
+/// The policy of [`send_reliable!`](crate::send_reliable!) calls that do not
+/// specify one: [`DEFAULT_MAX_ATTEMPTS`] attempts, retrying every error.
+pub fn default_policy() -> RetryPolicy {
+    RetryPolicy::new(DEFAULT_MAX_ATTEMPTS).retry_on(|_| true)
+}
+
+/// Send `msg` with `send` until it is acknowledged.
+///
+/// This is typically called by the generated code and not by user code directly.
+///
+/// # Errors
+///
+/// Returns [`CallError::WouldDeadlock`] if a task sends to itself, and the
+/// error of the last attempt once the message was put into the dead-letter
+/// queue.
+#[doc(hidden)]
+pub async fn deliver<T, F>(
+    task: TaskId,
+    name: &'static str,
+    msg: T,
+    policy: &RetryPolicy,
+    timeout: impl IntoTimeout,
+    send: F,
+) -> CallResult<()>
+where
+    T: Clone + Send + 'static,
+    F: Fn(T, Ack) -> SendResult<T>,
+{
+    CallError::check_self_call(task, name)?;
+
+    let timeout = timeout.into_timeout();
+    let correlation = CorrelationId::current_or_next();
+    let mut attempts = 0;
+
+    let result = policy
+        .run(|| {
+            attempts += 1;
+            let (ack, acked) = Ack::channel();
+            let started = Instant::now();
+            let sent =
+                send(msg.clone(), ack).map_err(|err| CallError::send_failed(task, name, err));
+
+            async move {
+                sent?;
+                match runtime::timeout(timeout, acked).await {
+                    Ok(Ok(())) => Ok(()),
+                    Ok(Err(_)) => Err(CallError::NoReply),
+                    Err(_) => Err(CallError::timed_out(task, name, correlation, started)),
+                }
+            }
+        })
+        .await;
+
+    if let Err(error) = &result {
+        dead_letter::push(DeadLetter::new(task, name, attempts, error, msg));
+    }
+    result
+}
@@ -0,0 +1,673 @@
+//! System signals travelling alongside user messages.
+//!
+//! A task's channel does not carry its messages directly but wraps them in
+//! an [`Envelope`]. Besides [`Envelope::User`] messages, it carries
+//! [`SystemSignal`]s that the task's [`Mailbox`](super::Mailbox) handles
+//! itself, before the task's own code sees any message. User protocols stay
+//! unchanged: [`recv!`](crate::recv!) only ever returns user messages.
+//!
+//! Signals are ordered with respect to messages: a signal takes effect once
+//! all messages sent before it have been received.
+//!
+//! Liveness checks travel the same way. [`TaskRef::ping`](crate::TaskRef::ping)
+//! sends an [`Envelope::Ping`] that the mailbox answers as soon as the task
+//! receives again, so every task answers pings without its protocol defining
+//! a health message.
+//!
+//! Messages sent from within a task carry their [`Sender`]. While handling
+//! such a message, the receiving task looks it up with
+//! [`Context::sender`](crate::task::Context::sender) or
+//! [`Mailbox::sender`](super::Mailbox::sender), so it can answer without
+//! every message variant carrying a [`TaskRef`] of its own. A message
+//! [forwarded](crate::forward!) to another task keeps the sender of the
+//! message being handled, so the other task answers the original sender.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::core::SystemSignal;
+//!
+//! #[derive(Task)]
+//! #[task(message = u32)]
+//! struct Worker;
+//!
+//! impl Runnable<u32> for Worker {
+//!     async fn start(&self) {
+//!         while let Ok(job) = recv!(self) {
+//!             println!("job {job}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let handle = spawn!(Worker);
+//! handle.signal(SystemSignal::Suspend).unwrap();
+//!
+//! // Held until the worker is resumed
+//! handle.send(1).unwrap();
+//! handle.signal(SystemSignal::Resume).unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::error::TryRecvError;
+
+use super::correlation::CorrelationId;
+use super::delivery::Ack;
+use super::mailbox::Capacity;
+#[cfg(feature = "spill")]
+use super::spill::Spill;
+#[cfg(feature = "spsc")]
+use super::spsc::RingReader;
+use crate::task::middleware::Chain;
+use crate::task::reference::WeakTaskRef;
+use crate::task::{TaskId, TaskRef};
+#[cfg(feature = "testing")]
+use crate::testing::scheduler::Gate;
+
+/// A message on a task's channel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Envelope<T> {
+    /// A message of the task's own protocol
+    User(T),
+    /// A message sent with [`send_reliable!`](crate::send_reliable!),
+    /// acknowledged once the task has processed it
+    Reliable(T, Ack),
+    /// A signal handled by the task's mailbox
+    System(SystemSignal),
+    /// A liveness check sent with [`TaskRef::ping`](crate::TaskRef::ping),
+    /// answered by the task's mailbox, with the correlation id of the ping
+    Ping(Ack, CorrelationId),
+    /// A message of the task's own protocol sent from within another task,
+    /// acknowledged once processed if it was sent reliably
+    Sent(T, Sender, Option<Ack>),
+}
+
+tokio::task_local! {
+    static SENDER: Origin;
+}
+
+/// Where the messages sent by the running task come from.
+struct Origin {
+    /// The running task
+    task: Sender,
+    /// Sender of the message the task is handling
+    handling: RefCell<Option<Sender>>,
+    /// Whether messages are [forwarded](forwarding) on behalf of `handling`
+    forwarding: Cell<bool>,
+}
+
+/// The task that sent a message.
+///
+/// Returned by [`Context::sender`](crate::task::Context::sender) for
+/// messages sent from within a task. Displayed as `name#id`, e.g.
+/// `Worker#7`.
+#[derive(Clone)]
+pub struct Sender {
+    id: TaskId,
+    name: &'static str,
+    /// The [`WeakTaskRef`] of the task, of any message type
+    task: Arc<dyn Any + Send + Sync>,
+}
+
+impl Sender {
+    /// Identifier of the sending task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Name of the sending task.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// A reference to the sending task, if its message type is `M`.
+    ///
+    /// Returns `None` if the task has a different message type or has
+    /// stopped receiving messages. Messages do not keep their sender's
+    /// mailbox open.
+    pub fn task_ref<M>(&self) -> Option<TaskRef<M>>
+    where
+        M: Send + 'static,
+    {
+        self.task.downcast_ref::<WeakTaskRef<M>>()?.upgrade()
+    }
+
+    /// The sender of messages sent by the caller: the task running it, or
+    /// the sender of the handled message while [forwarding]. `None` outside
+    /// of tasks.
+    pub(crate) fn current() -> Option<Self> {
+        SENDER
+            .try_with(|origin| match origin.forwarding.get() {
+                true => origin.handling.borrow().clone(),
+                false => Some(origin.task.clone()),
+            })
+            .ok()
+            .flatten()
+    }
+}
+
+impl fmt::Debug for Sender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Sender")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for Sender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.name, self.id)
+    }
+}
+
+impl PartialEq for Sender {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for Sender {}
+
+/// Run `future` as `task`, which becomes the [`Sender`] of every message
+/// sent from within.
+pub(crate) fn sending_as<T, F>(
+    task: &TaskRef<T>,
+    future: F,
+) -> impl Future<Output = F::Output> + use<T, F>
+where
+    T: Send + 'static,
+    F: Future,
+{
+    let origin = Origin {
+        task: Sender {
+            id: task.id(),
+            name: task.name(),
+            task: Arc::new(task.weak()),
+        },
+        handling: RefCell::new(None),
+        forwarding: Cell::new(false),
+    };
+    SENDER.scope(origin, future)
+}
+
+/// Call `send`, with the messages it sends keeping the sender of the
+/// message the running task is handling.
+pub(crate) fn forwarding<R>(send: impl FnOnce() -> R) -> R {
+    let _ = SENDER.try_with(|origin| origin.forwarding.set(true));
+    let result = send();
+    let _ = SENDER.try_with(|origin| origin.forwarding.set(false));
+    result
+}
+
+/// Signals handled by a task's mailbox instead of the task.
+///
+/// Sent with [`TaskRef::signal`](crate::TaskRef::signal).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SystemSignal {
+    /// Stop accepting messages; the task receives
+    /// [`RecvError::Closed`](crate::RecvError::Closed) once the messages
+    /// sent before the signal have been received.
+    Stop,
+    /// Hold back messages sent after the signal until [`Resume`](Self::Resume).
+    Suspend,
+    /// Deliver held back messages again.
+    Resume,
+}
+
+/// The receiving end of a task's channel.
+///
+/// Handles system signals and the capacity of bounded mailboxes, so the
+/// mailbox only sees user messages.
+pub(crate) struct Inbox<T> {
+    receiver: UnboundedReceiver<Envelope<T>>,
+    capacity: Option<Arc<Capacity>>,
+    /// Disk queue taking over once too many messages are in memory
+    #[cfg(feature = "spill")]
+    spill: Option<Arc<Spill<T>>>,
+    /// Ring buffer of a single-producer mailbox, taking precedence over the
+    /// channel
+    #[cfg(feature = "spsc")]
+    ring: Option<RingReader<T>>,
+    middleware: Chain<T>,
+    /// Messages received while suspended, in order, with their sender
+    held: VecDeque<(T, Option<Ack>, Option<Sender>)>,
+    suspended: bool,
+    /// Acknowledgement of the last delivered message
+    unacked: Option<Ack>,
+    /// Sender of the last delivered message
+    sender: Option<Sender>,
+    /// Messages left before the task yields to the executor
+    budget: Option<Budget>,
+    /// Connection to the deterministic scheduler, if the task is scheduled
+    #[cfg(feature = "testing")]
+    gate: Option<Arc<Gate>>,
+    /// Message taken from the channel, waiting for the scheduler
+    #[cfg(feature = "testing")]
+    pending: Option<T>,
+}
+
+/// Number of messages a task receives before yielding, so a task with a
+/// large backlog does not starve other tasks on its worker thread.
+struct Budget {
+    limit: usize,
+    left: usize,
+}
+
+impl Budget {
+    /// Check whether the budget is used up, refilling it if so.
+    fn exhausted(&mut self) -> bool {
+        let exhausted = self.left == 0;
+        if exhausted {
+            self.left = self.limit;
+        }
+        exhausted
+    }
+
+    fn spend(&mut self) {
+        self.left = self.left.saturating_sub(1);
+    }
+}
+
+impl<T> Inbox<T> {
+    pub(crate) fn new(
+        receiver: UnboundedReceiver<Envelope<T>>,
+        capacity: Option<Arc<Capacity>>,
+        middleware: Chain<T>,
+    ) -> Self {
+        Inbox {
+            receiver,
+            capacity,
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "spsc")]
+            ring: None,
+            middleware,
+            held: VecDeque::new(),
+            suspended: false,
+            unacked: None,
+            sender: None,
+            budget: None,
+            #[cfg(feature = "testing")]
+            gate: None,
+            #[cfg(feature = "testing")]
+            pending: None,
+        }
+    }
+
+    /// Let the deterministic scheduler behind `gate` decide when messages
+    /// are delivered.
+    #[cfg(feature = "testing")]
+    pub(crate) fn gated(self, gate: Option<Arc<Gate>>) -> Self {
+        Inbox { gate, ..self }
+    }
+
+    /// Receive from the ring buffer of a single-producer mailbox.
+    #[cfg(feature = "spsc")]
+    pub(crate) fn ringed(self, ring: Option<RingReader<T>>) -> Self {
+        Inbox { ring, ..self }
+    }
+
+    /// Yield to the executor after every `budget` received messages.
+    pub(crate) fn budgeted(self, budget: Option<usize>) -> Self {
+        let budget = budget.map(|limit| Budget { limit, left: limit });
+        Inbox { budget, ..self }
+    }
+
+    /// Receive spilled messages once the channel is empty.
+    #[cfg(feature = "spill")]
+    pub(crate) fn spilling(self, spill: Option<Arc<Spill<T>>>) -> Self {
+        Inbox { spill, ..self }
+    }
+
+    /// Receive the next user message that passes the task's middleware.
+    ///
+    /// Cancel-safe: every envelope taken from the channel is processed before
+    /// the next await point.
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        // Asking for the next message means the previous one was handled
+        self.middleware.finish();
+        self.ack();
+        #[cfg(feature = "testing")]
+        if let Some(gate) = &self.gate {
+            gate.idle();
+        }
+        if let Some(budget) = &mut self.budget
+            && budget.exhausted()
+        {
+            tokio::task::yield_now().await;
+        }
+
+        loop {
+            let msg = self.recv_next().await?;
+            if self.admit(&msg) {
+                if let Some(budget) = &mut self.budget {
+                    budget.spend();
+                }
+                return Some(msg);
+            }
+            self.ack();
+        }
+    }
+
+    /// Receive the next user message if one is immediately available.
+    pub(crate) fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        self.middleware.finish();
+        self.ack();
+
+        loop {
+            let msg = self.try_recv_next()?;
+            if self.admit(&msg) {
+                return Ok(msg);
+            }
+            self.ack();
+        }
+    }
+
+    /// Receive the next user message once the scheduler delivers it, if the
+    /// task is scheduled.
+    async fn recv_next(&mut self) -> Option<T> {
+        #[cfg(feature = "testing")]
+        if let Some(gate) = self.gate.clone() {
+            if self.pending.is_none() {
+                self.pending = Some(self.recv_any().await?);
+            }
+            gate.acquire().await;
+            return self.pending.take();
+        }
+
+        self.recv_any().await
+    }
+
+    fn try_recv_next(&mut self) -> Result<T, TryRecvError> {
+        #[cfg(feature = "testing")]
+        if let Some(gate) = self.gate.clone() {
+            if self.pending.is_none() {
+                self.pending = Some(self.try_recv_any()?);
+            }
+            return match gate.try_acquire() {
+                true => self.pending.take().ok_or(TryRecvError::Empty),
+                false => Err(TryRecvError::Empty),
+            };
+        }
+
+        self.try_recv_any()
+    }
+
+    async fn recv_any(&mut self) -> Option<T> {
+        loop {
+            if let Some(msg) = self.next_held() {
+                return Some(msg);
+            }
+
+            match self.next_envelope().await {
+                Some(envelope) => {
+                    if let Some(msg) = self.accept(envelope) {
+                        return Some(msg);
+                    }
+                }
+                // Nobody can resume a closed channel, so deliver what is held
+                None if self.suspended => self.suspended = false,
+                None => return None,
+            }
+        }
+    }
+
+    fn try_recv_any(&mut self) -> Result<T, TryRecvError> {
+        loop {
+            if let Some(msg) = self.next_held() {
+                return Ok(msg);
+            }
+
+            match self.try_next_envelope() {
+                Ok(envelope) => {
+                    if let Some(msg) = self.accept(envelope) {
+                        return Ok(msg);
+                    }
+                }
+                Err(TryRecvError::Disconnected) if self.suspended => self.suspended = false,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Take the next envelope from the channel, or a spilled message once
+    /// the channel is empty.
+    async fn next_envelope(&mut self) -> Option<Envelope<T>> {
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            loop {
+                match self.receiver.try_recv() {
+                    Ok(envelope) => return Some(spill.receive(envelope)),
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => return spill.pop().map(Envelope::User),
+                }
+                if let Some(msg) = spill.pop() {
+                    return Some(Envelope::User(msg));
+                }
+
+                tokio::select! {
+                    envelope = self.receiver.recv() => match envelope {
+                        Some(envelope) => return Some(spill.receive(envelope)),
+                        None => continue,
+                    },
+                    _ = spill.spilled() => {}
+                }
+            }
+        }
+
+        #[cfg(feature = "spsc")]
+        if let Some(ring) = &mut self.ring {
+            loop {
+                if let Some(envelope) = ring.pop() {
+                    return Some(envelope);
+                }
+                // Signals and the end of the mailbox arrive on the channel
+                match self.receiver.try_recv() {
+                    Ok(envelope) => return Some(envelope),
+                    Err(TryRecvError::Empty) => {}
+                    Err(TryRecvError::Disconnected) => return ring.pop(),
+                }
+
+                tokio::select! {
+                    envelope = self.receiver.recv() => if envelope.is_some() {
+                        return envelope;
+                    },
+                    () = ring.ready() => {}
+                }
+            }
+        }
+
+        self.receiver.recv().await
+    }
+
+    fn try_next_envelope(&mut self) -> Result<Envelope<T>, TryRecvError> {
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            return match self.receiver.try_recv() {
+                Ok(envelope) => Ok(spill.receive(envelope)),
+                Err(err) => spill.pop().map(Envelope::User).ok_or(err),
+            };
+        }
+
+        #[cfg(feature = "spsc")]
+        if let Some(ring) = &mut self.ring {
+            return match ring.pop() {
+                Some(envelope) => Ok(envelope),
+                None => self.receiver.try_recv(),
+            };
+        }
+
+        self.receiver.try_recv()
+    }
+
+    /// Number of messages waiting to be received, including held ones.
+    ///
+    /// Envelopes carrying signals are counted as well.
+    pub(crate) fn len(&self) -> usize {
+        #[cfg(feature = "spsc")]
+        let ring = self.ring.as_ref().map_or(0, RingReader::len);
+        #[cfg(not(feature = "spsc"))]
+        let ring = 0;
+
+        self.receiver.len() + self.held.len() + ring
+    }
+
+    /// The task that sent the last delivered message, if it was sent from
+    /// within a task.
+    pub(crate) fn sender(&self) -> Option<&Sender> {
+        self.sender.as_ref()
+    }
+
+    /// Acknowledge the last delivered message if it was sent reliably.
+    pub(crate) fn ack(&mut self) {
+        if let Some(ack) = self.unacked.take() {
+            ack.ack();
+        }
+    }
+
+    /// Stop accepting messages, keeping the queued ones.
+    pub(crate) fn close(&mut self) {
+        self.receiver.close();
+    }
+
+    /// Check whether the mailbox stopped accepting messages.
+    pub(crate) fn is_closed(&self) -> bool {
+        self.receiver.is_closed()
+    }
+
+    /// Wait for the first user message, keeping it queued for delivery.
+    ///
+    /// Signals arriving before are processed. Returns `false` if the
+    /// mailbox closed first.
+    pub(crate) async fn first(&mut self) -> bool {
+        while self.held.is_empty() {
+            match self.next_envelope().await {
+                // Held messages keep their slot until they are delivered
+                Some(Envelope::User(msg)) => self.held.push_back((msg, None, None)),
+                Some(Envelope::Reliable(msg, ack)) => self.held.push_back((msg, Some(ack), None)),
+                Some(Envelope::Sent(msg, sender, ack)) => {
+                    self.held.push_back((msg, ack, Some(sender)));
+                }
+                Some(envelope) => {
+                    self.accept(envelope);
+                }
+                None => return false,
+            }
+        }
+        true
+    }
+
+    /// Forget the message that was being handled, without acknowledging it.
+    pub(crate) fn abandon(&mut self) {
+        self.unacked = None;
+    }
+
+    /// Take all queued messages out without delivering them.
+    ///
+    /// Held messages are included, and none of the messages is
+    /// acknowledged.
+    pub(crate) fn drain(&mut self) -> Vec<T> {
+        self.abandon();
+        self.suspended = false;
+
+        let mut messages = Vec::new();
+        #[cfg(feature = "testing")]
+        messages.extend(self.pending.take());
+        while let Ok(msg) = self.try_recv_any() {
+            self.unacked = None;
+            messages.push(msg);
+        }
+        messages
+    }
+
+    /// Run the middleware on `msg`, returning whether to deliver it.
+    fn admit(&mut self, msg: &T) -> bool {
+        if !self.middleware.admit(msg) {
+            return false;
+        }
+
+        #[cfg(feature = "metrics")]
+        crate::metrics::delivered(
+            self.middleware.task(),
+            self.receiver.len() + self.held.len(),
+        );
+        true
+    }
+
+    fn next_held(&mut self) -> Option<T> {
+        while !self.suspended {
+            let (msg, ack, sender) = self.held.pop_front()?;
+            if let Some(msg) = self.deliver(msg, ack, sender) {
+                return Some(msg);
+            }
+        }
+
+        None
+    }
+
+    /// Process an envelope, returning the message to deliver if there is one.
+    fn accept(&mut self, envelope: Envelope<T>) -> Option<T> {
+        match envelope {
+            // Held messages keep their slot until they are delivered
+            Envelope::User(msg) if self.suspended => self.held.push_back((msg, None, None)),
+            Envelope::User(msg) => return self.deliver(msg, None, None),
+            Envelope::Reliable(msg, ack) if self.suspended => {
+                self.held.push_back((msg, Some(ack), None));
+            }
+            Envelope::Reliable(msg, ack) => return self.deliver(msg, Some(ack), None),
+            Envelope::Sent(msg, sender, ack) if self.suspended => {
+                self.held.push_back((msg, ack, Some(sender)));
+            }
+            Envelope::Sent(msg, sender, ack) => return self.deliver(msg, ack, Some(sender)),
+            Envelope::System(SystemSignal::Stop) => {
+                self.receiver.close();
+                self.suspended = false;
+            }
+            Envelope::System(SystemSignal::Suspend) => self.suspended = true,
+            Envelope::System(SystemSignal::Resume) => self.suspended = false,
+            // Answered even while suspended, since the task is still receiving
+            Envelope::Ping(pong, _) => pong.ack(),
+        }
+
+        None
+    }
+
+    /// Deliver `msg` unless it was displaced, remembering to acknowledge it
+    /// and who sent it.
+    ///
+    /// A displaced message is never acknowledged, so a reliable sender
+    /// sends it again.
+    fn deliver(&mut self, msg: T, ack: Option<Ack>, sender: Option<Sender>) -> Option<T> {
+        if !self.release() {
+            return None;
+        }
+
+        self.unacked = ack;
+        // Remembered for forwarding, if the task is receiving itself
+        let _ = SENDER.try_with(|origin| origin.handling.replace(sender.clone()));
+        self.sender = sender;
+        Some(msg)
+    }
+
+    /// Free the slot of a delivered message in a bounded mailbox.
+    ///
+    /// Returns `false` if the message was displaced and must be discarded.
+    fn release(&self) -> bool {
+        match &self.capacity {
+            Some(capacity) => capacity.release(),
+            None => true,
+        }
+    }
+}
@@ -1,5 +1,13 @@
+use std::any::Any;
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+
 pub use tokio::sync::mpsc::error::SendError;
 
+use super::correlation::CorrelationId;
+use crate::task::TaskId;
+
 #[derive(Debug, thiserror::Error)]
 pub enum RecvError {
     #[error("channel closed")]
@@ -16,16 +24,166 @@ pub type SendResult<T> = Result<(), SendError<T>>;
 
 #[derive(Debug, thiserror::Error)]
 pub enum CallError {
-    #[error("call timeout")]
-    Timeout,
+    /// No reply arrived in time
+    #[error("call {correlation} to {name} (task {task}) timed out after {elapsed:?}")]
+    Timeout {
+        /// The called task
+        task: TaskId,
+        /// Name of the called task
+        name: &'static str,
+        /// Correlation id of the call
+        correlation: CorrelationId,
+        /// Time between sending the request and giving up
+        elapsed: Duration,
+    },
+    /// The task dropped the reply channel without answering
     #[error("reply channel closed")]
     ChannelClosed,
-    #[error("send failed")]
-    SendError,
+    /// The task dropped the [`Reply`](crate::Reply) without answering
+    #[error("request dropped without a reply")]
+    NoReply,
+    /// The request could not be delivered because the mailbox is closed
+    #[error("send to {name} (task {task}) failed: mailbox closed")]
+    SendError {
+        /// The called task
+        task: TaskId,
+        /// Name of the called task
+        name: &'static str,
+        /// The request that could not be delivered
+        message: UndeliveredMessage,
+    },
+    /// The [`CircuitBreaker`](crate::task::CircuitBreaker) is open
+    #[error("circuit breaker open")]
+    CircuitOpen,
+    /// A call to any of a set of replicas was given none
+    #[error("no replicas to call")]
+    NoReplicas,
+    /// A task called itself, which would block until the call times out
+    #[error("call to {name} (task {task}) from the task itself would deadlock")]
+    WouldDeadlock {
+        /// The calling and called task
+        task: TaskId,
+        /// Name of the task
+        name: &'static str,
+    },
+}
+
+impl CallError {
+    /// Build a [`CallError::Timeout`] for a call started at `started`.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn timed_out(
+        task: TaskId,
+        name: &'static str,
+        correlation: CorrelationId,
+        started: crate::runtime::Instant,
+    ) -> Self {
+        CallError::Timeout {
+            task,
+            name,
+            correlation,
+            elapsed: started.elapsed(),
+        }
+    }
+
+    /// Fail with [`CallError::WouldDeadlock`] if `task` is the current task.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn check_self_call(task: TaskId, name: &'static str) -> CallResult<()> {
+        if TaskId::current() == Some(task) {
+            Err(CallError::WouldDeadlock { task, name })
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Build a [`CallError::SendError`] from a failed send.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn send_failed<T>(task: TaskId, name: &'static str, err: SendError<T>) -> Self
+    where
+        T: Send + 'static,
+    {
+        CallError::SendError {
+            task,
+            name,
+            message: UndeliveredMessage::new(err.0),
+        }
+    }
+
+    /// Recover the request of a failed send.
+    ///
+    /// Returns `None` for other errors or if the request is not of type `M`.
+    /// The reply channel inside the recovered request is already closed, so
+    /// a retry has to build a fresh request from its data.
+    pub fn into_message<M>(self) -> Option<M>
+    where
+        M: 'static,
+    {
+        match self {
+            CallError::SendError { message, .. } => message.downcast(),
+            _ => None,
+        }
+    }
+}
+
+/// A request that could not be delivered, with its type erased.
+pub struct UndeliveredMessage(Mutex<Box<dyn Any + Send>>);
+
+impl UndeliveredMessage {
+    fn new<T>(message: T) -> Self
+    where
+        T: Send + 'static,
+    {
+        UndeliveredMessage(Mutex::new(Box::new(message)))
+    }
+
+    /// Check whether the request is of type `M`.
+    pub fn is<M>(&self) -> bool
+    where
+        M: 'static,
+    {
+        self.0.lock().is_ok_and(|message| message.is::<M>())
+    }
+
+    /// Take the request out if it is of type `M`.
+    pub fn downcast<M>(self) -> Option<M>
+    where
+        M: 'static,
+    {
+        let message = self.0.into_inner().ok()?;
+        message.downcast().ok().map(|message| *message)
+    }
+}
+
+impl fmt::Debug for UndeliveredMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("UndeliveredMessage { .. }")
+    }
 }
 
 pub type CallResult<T> = Result<T, CallError>;
 
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ThrottleError<T> {
+    #[error("rate limit exceeded")]
+    RateLimited(T),
+    #[error("channel closed")]
+    Closed(T),
+}
+
+impl<T> ThrottleError<T> {
+    /// Recover the message that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            ThrottleError::RateLimited(msg) | ThrottleError::Closed(msg) => msg,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,12 +208,46 @@ mod tests {
 
         assert_eq!(format!("{}", SendError(42)), "channel closed");
 
-        assert_eq!(format!("{}", CallError::Timeout), "call timeout");
+        let task = TaskId::next();
+        let correlation = CorrelationId::next();
+        assert_eq!(
+            format!(
+                "{}",
+                CallError::Timeout {
+                    task,
+                    name: "Worker",
+                    correlation,
+                    elapsed: Duration::from_millis(5),
+                }
+            ),
+            format!("call {correlation} to Worker (task {task}) timed out after 5ms")
+        );
         assert_eq!(
             format!("{}", CallError::ChannelClosed),
             "reply channel closed"
         );
-        assert_eq!(format!("{}", CallError::SendError), "send failed");
+        assert_eq!(
+            format!("{}", CallError::NoReply),
+            "request dropped without a reply"
+        );
+        assert_eq!(
+            format!("{}", CallError::send_failed(task, "Worker", SendError(1))),
+            format!("send to Worker (task {task}) failed: mailbox closed")
+        );
+        assert_eq!(
+            format!("{}", CallError::CircuitOpen),
+            "circuit breaker open"
+        );
+        assert_eq!(
+            format!(
+                "{}",
+                CallError::WouldDeadlock {
+                    task,
+                    name: "Worker"
+                }
+            ),
+            format!("call to Worker (task {task}) from the task itself would deadlock")
+        );
     }
 
     #[test]
@@ -65,9 +257,30 @@ mod tests {
         assert_eq!(format!("{:?}", RecvError::Timeout), "Timeout");
     }
 
+    #[test]
+    fn throttle_error_returns_message() {
+        assert_eq!(ThrottleError::RateLimited(1).into_inner(), 1);
+        assert_eq!(ThrottleError::Closed(2).into_inner(), 2);
+        assert_eq!(
+            format!("{}", ThrottleError::RateLimited(0)),
+            "rate limit exceeded"
+        );
+    }
+
     #[test]
     fn call_error_implements_std_error() {
-        fn assert_is_error<E: std::error::Error + 'static>() {}
+        fn assert_is_error<E: std::error::Error + Send + Sync + 'static>() {}
         assert_is_error::<CallError>();
     }
+
+    #[test]
+    fn failed_send_returns_message() {
+        let err = CallError::send_failed(TaskId::next(), "Worker", SendError(42u32));
+
+        assert!(matches!(&err, CallError::SendError { message, .. } if message.is::<u32>()));
+        assert_eq!(err.into_message::<u32>(), Some(42));
+
+        let err = CallError::send_failed(TaskId::next(), "Worker", SendError(42u32));
+        assert_eq!(err.into_message::<String>(), None);
+    }
 }
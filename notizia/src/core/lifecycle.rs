@@ -3,7 +3,14 @@
 //! This module contains types related to task lifecycle management,
 //! including graceful shutdown and termination handling.
 
+use std::any::Any;
 use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+/// Time [`TaskHandle::stop`](crate::TaskHandle::stop) gives a task to
+/// terminate unless configured otherwise.
+pub const DEFAULT_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 
 /// Reason why a task's terminate() hook is being called.
 ///
@@ -15,6 +22,12 @@ pub enum TerminateReason {
     Normal,
     /// Task panicked during execution
     Panic(String),
+    /// Task was passivated after its mailbox stayed idle for the configured
+    /// [idle timeout](crate::Mailbox::set_idle_timeout)
+    Idle,
+    /// `start()` was aborted because it ran past the task's
+    /// [deadline](crate::task::SpawnBuilder::deadline)
+    DeadlineExceeded,
 }
 
 impl fmt::Display for TerminateReason {
@@ -22,6 +35,8 @@ impl fmt::Display for TerminateReason {
         match self {
             TerminateReason::Normal => write!(f, "normal termination"),
             TerminateReason::Panic(msg) => write!(f, "panicked: {}", msg),
+            TerminateReason::Idle => write!(f, "passivated after being idle"),
+            TerminateReason::DeadlineExceeded => write!(f, "deadline exceeded"),
         }
     }
 }
@@ -34,12 +49,38 @@ pub enum ShutdownError {
     Timeout,
     /// Unexpected join error
     #[error("task join error: {0}")]
-    JoinError(#[from] tokio::task::JoinError),
+    JoinError(#[from] crate::runtime::JoinError),
 }
 
 /// Result type for shutdown operations.
 pub type ShutdownResult = Result<TerminateReason, ShutdownError>;
 
+/// Run `future`, giving up once `deadline` has elapsed.
+///
+/// Returns `None` if the deadline elapsed first. This is typically called by
+/// the generated code and not by user code directly.
+#[doc(hidden)]
+pub async fn with_deadline<F>(deadline: Option<Duration>, future: F) -> Option<F::Output>
+where
+    F: Future,
+{
+    match deadline {
+        Some(deadline) => crate::runtime::timeout(deadline, future).await.ok(),
+        None => Some(future.await),
+    }
+}
+
+/// Extract a human-readable message from a panic payload.
+pub(crate) fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = payload.downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,28 +1,326 @@
 //! Mailbox for receiving messages.
 
-use std::sync::Arc;
+use std::convert::Infallible;
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
-use tokio::sync::Mutex;
 use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::{Mutex, Notify};
 
+use super::envelope::{Envelope, Inbox, Sender};
 use super::errors::{RecvError, RecvResult};
+#[cfg(feature = "spill")]
+use super::spill::Spill;
+#[cfg(feature = "spsc")]
+use super::spsc::Ring;
+use crate::runtime::{self, Instant};
+use crate::task::audit::Auditor;
+use crate::task::middleware::Chain;
+use crate::task::watchdog::Watchdog;
+#[cfg(feature = "testing")]
+use crate::testing::scheduler::Gate;
+
+/// Outcome of [`Mailbox::recv_or`].
+#[derive(Debug)]
+pub enum Received<T, X> {
+    /// A message arrived
+    Message(T),
+    /// The interrupting future completed first
+    Interrupted(X),
+}
+
+impl<T> Received<T, Infallible> {
+    fn into_message(self) -> T {
+        match self {
+            Received::Message(msg) => msg,
+            Received::Interrupted(never) => match never {},
+        }
+    }
+}
 
 /// A thread-safe mailbox for receiving messages.
 ///
 /// The mailbox provides a safe way to receive messages from other tasks.
 /// It wraps an `UnboundedReceiver` and manages its lifecycle using Arc and Mutex
 /// to enable the take-recv-put pattern required for async receiving without
-/// holding locks.
+/// holding locks. [System signals](super::envelope) on the channel are
+/// handled by the mailbox, so only user messages are returned.
+///
+/// # Idle Passivation
+///
+/// A mailbox can be configured with an [idle timeout](Self::set_idle_timeout).
+/// If no message arrives within that duration, the mailbox is *passivated*:
+/// the channel is closed for new messages, messages that are already queued
+/// are still delivered, and afterwards [`recv`](Self::recv) returns
+/// [`RecvError::Closed`]. A task looping on `recv` therefore stops gracefully
+/// and its `terminate()` hook is called with
+/// [`TerminateReason::Idle`](crate::TerminateReason::Idle).
+///
+/// # Capacity
+///
+/// Mailboxes are unbounded by default. A task spawned with a
+/// [`bounded`] mailbox holds at most `capacity` messages; what happens to
+/// messages sent while it is full is decided by its [`Overflow`] policy.
+/// Alternatively, a mailbox can [spill](super::spill) messages beyond a
+/// threshold to disk, or receive from a single producer through a
+/// [ring buffer](super::spsc).
 pub struct Mailbox<T> {
-    pub(crate) receiver: Arc<Mutex<Option<UnboundedReceiver<T>>>>,
+    pub(crate) receiver: Arc<Mutex<Option<Inbox<T>>>>,
+    pub(crate) passivation: Passivation,
+    pub(crate) activity: Activity,
+    pub(crate) capacity: Option<Arc<Capacity>>,
+    #[cfg(feature = "spill")]
+    pub(crate) spill: Option<Arc<Spill<T>>>,
+    #[cfg(feature = "spsc")]
+    pub(crate) ring: Option<Arc<Ring<T>>>,
+    pub(crate) middleware: Chain<T>,
+    pub(crate) audit: Option<Auditor>,
+    pub(crate) watchdog: Option<Watchdog>,
+    pub(crate) budget: Option<usize>,
+    /// Whether the inbox was taken over from a previous incarnation of the
+    /// task, see [`TaskHandle::respawn`](crate::TaskHandle::respawn)
+    pub(crate) reattached: bool,
+    #[cfg(feature = "testing")]
+    pub(crate) gate: Option<Arc<Gate>>,
+}
+
+/// Configuration of a task's mailbox.
+///
+/// Created with [`bounded`] or [`unbounded`] and passed to
+/// [`SpawnBuilder::mailbox`](crate::task::SpawnBuilder::mailbox).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MailboxConfig {
+    /// Accept any number of queued messages
+    #[default]
+    Unbounded,
+    /// Hold at most this many queued messages
+    Bounded(usize),
+}
+
+/// What a [bounded](bounded) mailbox does with a message sent while it is full.
+///
+/// Passed to [`SpawnBuilder::overflow`](crate::task::SpawnBuilder::overflow)
+/// or set with `#[task(message = T, mailbox = bounded(n), overflow = drop_oldest)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Fail the send, returning the message to the sender
+    #[default]
+    Reject,
+    /// Discard the new message, reporting the send as successful
+    DropNewest,
+    /// Discard the oldest queued message to make room for the new one
+    DropOldest,
+}
+
+/// A mailbox holding at most `capacity` messages.
+///
+/// # Panics
+///
+/// Panics if `capacity` is zero.
+pub fn bounded(capacity: usize) -> MailboxConfig {
+    assert!(capacity > 0, "mailbox capacity must be greater than zero");
+    MailboxConfig::Bounded(capacity)
+}
+
+/// A mailbox without a limit on queued messages.
+pub fn unbounded() -> MailboxConfig {
+    MailboxConfig::Unbounded
+}
+
+impl MailboxConfig {
+    pub(crate) fn capacity(&self, overflow: Overflow) -> Option<Arc<Capacity>> {
+        match self {
+            MailboxConfig::Unbounded => None,
+            MailboxConfig::Bounded(limit) => Some(Arc::new(Capacity {
+                limit: *limit,
+                overflow,
+                queued: AtomicUsize::new(0),
+                displaced: AtomicUsize::new(0),
+                freed: Notify::new(),
+            })),
+        }
+    }
+}
+
+/// Outcome of [`Capacity::acquire`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Admission {
+    /// A slot was reserved for the message
+    Accepted,
+    /// The message takes the slot of the oldest queued message
+    Displacing,
+    /// The mailbox is full and the message is not sent
+    Full(Overflow),
+}
+
+/// Number of messages queued in a bounded mailbox.
+///
+/// Senders reserve a slot before sending and the mailbox releases it when the
+/// message is received. With [`Overflow::DropOldest`], a sender finding the
+/// mailbox full sends anyway and marks the oldest queued message as
+/// displaced; the mailbox then discards it instead of delivering it.
+/// Senders [waiting](Self::reserve) for a slot are woken whenever one is
+/// released.
+#[derive(Debug)]
+pub(crate) struct Capacity {
+    limit: usize,
+    overflow: Overflow,
+    queued: AtomicUsize,
+    displaced: AtomicUsize,
+    freed: Notify,
+}
+
+impl Capacity {
+    /// Reserve a slot for a message about to be sent.
+    pub(crate) fn acquire(&self) -> Admission {
+        match self.overflow {
+            _ if self.try_reserve() => Admission::Accepted,
+            Overflow::DropOldest => {
+                self.displaced.fetch_add(1, Ordering::SeqCst);
+                Admission::Displacing
+            }
+            overflow => Admission::Full(overflow),
+        }
+    }
+
+    /// Wait until a slot is free and reserve it, regardless of the overflow
+    /// policy.
+    ///
+    /// Cancel-safe: no slot is reserved if the future is dropped early.
+    pub(crate) async fn reserve(&self) {
+        loop {
+            // Register before checking, so no released slot is missed
+            let freed = self.freed.notified();
+            if self.try_reserve() {
+                return;
+            }
+            freed.await;
+        }
+    }
+
+    fn try_reserve(&self) -> bool {
+        self.queued
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |queued| {
+                (queued < self.limit).then_some(queued + 1)
+            })
+            .is_ok()
+    }
+
+    /// Undo [`acquire`](Self::acquire) after the message could not be sent.
+    pub(crate) fn cancel(&self, admission: Admission) {
+        match admission {
+            Admission::Accepted => {
+                self.queued.fetch_sub(1, Ordering::SeqCst);
+                self.freed.notify_waiters();
+            }
+            Admission::Displacing => {
+                self.displaced.fetch_sub(1, Ordering::SeqCst);
+            }
+            Admission::Full(_) => {}
+        }
+    }
+
+    /// Release the slot of a received message.
+    ///
+    /// Returns `false` if the message was displaced by a newer one and must
+    /// be discarded.
+    pub(crate) fn release(&self) -> bool {
+        let displaced = self
+            .displaced
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |displaced| {
+                displaced.checked_sub(1)
+            })
+            .is_ok();
+
+        if !displaced {
+            self.queued.fetch_sub(1, Ordering::SeqCst);
+            self.freed.notify_waiters();
+        }
+
+        !displaced
+    }
+}
+
+/// Idle passivation state shared between a mailbox and its task handle.
+///
+/// Kept separate from the receiver, so holding it does not keep the channel
+/// open after the task has terminated.
+#[derive(Clone, Default)]
+pub(crate) struct Passivation {
+    idle_timeout: Arc<std::sync::Mutex<Option<Duration>>>,
+    changed: Arc<Notify>,
+    passivated: Arc<AtomicBool>,
+}
+
+impl Passivation {
+    pub(crate) fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        *self.idle_timeout.lock().unwrap() = timeout;
+        self.changed.notify_waiters();
+    }
+
+    pub(crate) fn idle_timeout(&self) -> Option<Duration> {
+        *self.idle_timeout.lock().unwrap()
+    }
+
+    pub(crate) fn is_passivated(&self) -> bool {
+        self.passivated.load(Ordering::SeqCst)
+    }
+}
+
+/// When the task last received a message, shared with its handle.
+#[derive(Clone, Default)]
+pub(crate) struct Activity(Arc<std::sync::Mutex<Option<Instant>>>);
+
+impl Activity {
+    fn touch(&self) {
+        *self.0.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub(crate) fn last(&self) -> Option<Instant> {
+        *self.0.lock().unwrap()
+    }
+}
+
+/// The depth of a mailbox, observed without keeping its channel open.
+pub(crate) struct Gauge<T>(Weak<Mutex<Option<Inbox<T>>>>);
+
+impl<T> Gauge<T> {
+    pub(crate) fn depth(&self) -> usize {
+        self.0.upgrade().map_or(0, |receiver| depth(&receiver))
+    }
+}
+
+/// Approximate number of messages waiting behind `receiver`.
+fn depth<T>(receiver: &Mutex<Option<Inbox<T>>>) -> usize {
+    match receiver.try_lock() {
+        Ok(slot) => slot.as_ref().map_or(0, Inbox::len),
+        Err(_) => 0,
+    }
 }
 
 // Manual Clone implementation to avoid requiring T: Clone
-// Arc<Mutex<Option<UnboundedReceiver<T>>>> is Clone regardless of T
+// Arc<Mutex<Option<Inbox<T>>>> is Clone regardless of T
 impl<T> Clone for Mailbox<T> {
     fn clone(&self) -> Self {
         Mailbox {
             receiver: self.receiver.clone(),
+            passivation: self.passivation.clone(),
+            activity: self.activity.clone(),
+            capacity: self.capacity.clone(),
+            #[cfg(feature = "spill")]
+            spill: self.spill.clone(),
+            #[cfg(feature = "spsc")]
+            ring: self.ring.clone(),
+            middleware: self.middleware.clone(),
+            audit: self.audit.clone(),
+            watchdog: self.watchdog.clone(),
+            budget: self.budget,
+            reattached: self.reattached,
+            #[cfg(feature = "testing")]
+            gate: self.gate.clone(),
         }
     }
 }
@@ -41,14 +339,122 @@ impl<T> Mailbox<T> {
     pub fn new() -> Self {
         Mailbox {
             receiver: Arc::new(Mutex::new(None)),
+            passivation: Passivation::default(),
+            activity: Activity::default(),
+            capacity: None,
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "spsc")]
+            ring: None,
+            middleware: Chain::default(),
+            audit: None,
+            watchdog: None,
+            budget: None,
+            reattached: false,
+            #[cfg(feature = "testing")]
+            gate: None,
+        }
+    }
+
+    /// Create a new empty mailbox with the given configuration.
+    pub(crate) fn with_config(
+        config: MailboxConfig,
+        overflow: Overflow,
+        middleware: Chain<T>,
+    ) -> Self {
+        Mailbox {
+            capacity: config.capacity(overflow),
+            middleware,
+            ..Self::new()
+        }
+    }
+
+    /// Create a mailbox that is already connected to a receiver.
+    pub(crate) fn from_receiver(receiver: UnboundedReceiver<Envelope<T>>) -> Self {
+        Mailbox {
+            receiver: Arc::new(Mutex::new(Some(Inbox::new(
+                receiver,
+                None,
+                Chain::default(),
+            )))),
+            ..Self::new()
         }
     }
 
     /// Set the receiver for this mailbox.
     ///
     /// This is typically called during task setup by the generated code.
-    pub async fn set_receiver(&self, receiver: UnboundedReceiver<T>) {
-        *self.receiver.lock().await = Some(receiver);
+    pub async fn set_receiver(&self, receiver: UnboundedReceiver<Envelope<T>>) {
+        // A respawned task keeps the inbox of its previous incarnation
+        if self.reattached {
+            return;
+        }
+
+        let inbox = Inbox::new(receiver, self.capacity.clone(), self.middleware.clone())
+            .budgeted(self.budget);
+        #[cfg(feature = "spill")]
+        let inbox = inbox.spilling(self.spill.clone());
+        #[cfg(feature = "spsc")]
+        let inbox = inbox.ringed(self.ring.as_ref().and_then(Ring::reader));
+        #[cfg(feature = "testing")]
+        let inbox = inbox.gated(self.gate.clone());
+
+        *self.receiver.lock().await = Some(inbox);
+    }
+
+    /// Set the idle timeout after which the mailbox is passivated.
+    ///
+    /// `None` disables passivation. A pending [`recv`](Self::recv) picks up
+    /// the new timeout immediately and restarts its idle period.
+    pub fn set_idle_timeout(&self, timeout: Option<Duration>) {
+        self.passivation.set_idle_timeout(timeout);
+    }
+
+    /// The configured idle timeout, if any.
+    pub fn idle_timeout(&self) -> Option<Duration> {
+        self.passivation.idle_timeout()
+    }
+
+    /// Check whether the mailbox was passivated because it was idle.
+    pub fn is_passivated(&self) -> bool {
+        self.passivation.is_passivated()
+    }
+
+    /// Acknowledge the last received message.
+    ///
+    /// Messages sent with [`send_reliable!`](crate::send_reliable!) are
+    /// acknowledged once the task asks for its next message, which handler
+    /// tasks do after each handler. Call this to acknowledge a message
+    /// earlier, e.g. before starting long-running work.
+    pub fn ack(&self) {
+        if let Ok(mut slot) = self.receiver.try_lock()
+            && let Some(inbox) = slot.as_mut()
+        {
+            inbox.ack();
+        }
+    }
+
+    /// The task that sent the last received message.
+    ///
+    /// Returns `None` if the message was not sent from within a task, or
+    /// while the task waits for a message.
+    pub fn sender(&self) -> Option<Sender> {
+        let slot = self.receiver.try_lock().ok()?;
+        slot.as_ref()?.sender().cloned()
+    }
+
+    /// Approximate number of messages waiting in the mailbox.
+    ///
+    /// While the task waits for a message, the mailbox is empty and this
+    /// returns 0.
+    #[cfg(feature = "inspector")]
+    pub(crate) fn depth(&self) -> usize {
+        depth(&self.receiver)
+    }
+
+    /// Observe the depth of this mailbox without keeping it open.
+    pub(crate) fn gauge(&self) -> Gauge<T> {
+        Gauge(Arc::downgrade(&self.receiver))
     }
 
     /// Receive a message from the mailbox.
@@ -56,12 +462,64 @@ impl<T> Mailbox<T> {
     /// This method will await until a message is available. It uses a take-recv-put
     /// pattern to avoid holding the Mutex lock while awaiting.
     ///
+    /// If an [idle timeout](Self::set_idle_timeout) is configured and expires
+    /// before a message arrives, the mailbox is passivated and this method
+    /// returns [`RecvError::Closed`] once all queued messages are drained.
+    ///
     /// # Errors
     ///
-    /// Returns [`RecvError::Closed`] if the channel has been closed.
+    /// Returns [`RecvError::Closed`] if the channel has been closed or the
+    /// mailbox was passivated.
     /// Returns [`RecvError::Poisoned`] if the receiver has not been set or was
     /// taken and not returned.
     pub async fn recv(&self) -> RecvResult<T> {
+        self.recv_until(None, std::future::pending::<Infallible>())
+            .await
+            .map(Received::into_message)
+    }
+
+    /// Receive a message, waiting at most `timeout`.
+    ///
+    /// Unlike wrapping [`recv`](Self::recv) in [`runtime::timeout`], the
+    /// mailbox stays usable when the timeout expires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Timeout`] if no message arrived in time, and the
+    /// same errors as [`recv`](Self::recv) otherwise.
+    pub async fn recv_timeout(&self, timeout: Duration) -> RecvResult<T> {
+        self.recv_until(
+            Instant::now().checked_add(timeout),
+            std::future::pending::<Infallible>(),
+        )
+        .await
+        .map(Received::into_message)
+    }
+
+    /// Receive a message, unless `interrupt` completes first.
+    ///
+    /// `interrupt` is polled before the mailbox, so it wins if both are
+    /// ready. Unlike racing [`recv`](Self::recv) against another future,
+    /// the mailbox stays usable when `interrupt` wins. This lets a task wait
+    /// for messages and, say, data on a socket at the same time.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`recv`](Self::recv).
+    pub async fn recv_or<X>(
+        &self,
+        interrupt: impl Future<Output = X>,
+    ) -> RecvResult<Received<T, X>> {
+        self.recv_until(None, interrupt).await
+    }
+
+    async fn recv_until<X>(
+        &self,
+        deadline: Option<Instant>,
+        interrupt: impl Future<Output = X>,
+    ) -> RecvResult<Received<T, X>> {
+        tokio::pin!(interrupt);
+
         // Take the receiver out
         let mut receiver = {
             let mut slot = self.receiver.lock().await;
@@ -69,11 +527,77 @@ impl<T> Mailbox<T> {
         };
 
         // Await without holding the Mutex lock
-        let value = receiver.recv().await.ok_or(RecvError::Closed)?;
+        let value = loop {
+            // Register before reading the timeout, so no change is missed
+            let changed = self.passivation.changed.notified();
+
+            let expired = async {
+                match deadline {
+                    Some(deadline) => runtime::sleep_until(deadline).await,
+                    None => std::future::pending().await,
+                }
+            };
+
+            let idle = match self.idle_timeout() {
+                Some(idle) if !self.is_passivated() => idle,
+                _ => {
+                    tokio::select! {
+                        biased;
+                        interrupted = &mut interrupt => break Ok(Received::Interrupted(interrupted)),
+                        value = receiver.recv() => break value.map(Received::Message).ok_or(RecvError::Closed),
+                        _ = changed => continue,
+                        _ = expired => break Err(RecvError::Timeout),
+                    }
+                }
+            };
+
+            tokio::select! {
+                biased;
+                interrupted = &mut interrupt => break Ok(Received::Interrupted(interrupted)),
+                value = receiver.recv() => break value.map(Received::Message).ok_or(RecvError::Closed),
+                _ = changed => continue,
+                _ = expired => break Err(RecvError::Timeout),
+                _ = runtime::sleep(idle) => {
+                    // Reject new messages, but still deliver queued ones
+                    self.passivation.passivated.store(true, Ordering::SeqCst);
+                    receiver.close();
+                    break receiver.recv().await.map(Received::Message).ok_or(RecvError::Closed);
+                }
+            }
+        };
 
         // Put it back
         *self.receiver.lock().await = Some(receiver);
 
-        Ok(value)
+        if let Ok(Received::Message(_)) = &value {
+            self.activity.touch();
+        }
+        value
+    }
+
+    /// Receive a message if one is immediately available.
+    ///
+    /// Returns `Ok(None)` if the mailbox is currently empty. Unlike
+    /// [`recv`](Self::recv), this never waits for a message and does not
+    /// count towards the idle timeout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`] if the channel has been closed and no
+    /// messages are left.
+    /// Returns [`RecvError::Poisoned`] if the receiver has not been set or is
+    /// currently in use by a pending [`recv`](Self::recv).
+    pub async fn try_recv(&self) -> RecvResult<Option<T>> {
+        let mut slot = self.receiver.lock().await;
+        let receiver = slot.as_mut().ok_or(RecvError::Poisoned)?;
+
+        match receiver.try_recv() {
+            Ok(value) => {
+                self.activity.touch();
+                Ok(Some(value))
+            }
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(RecvError::Closed),
+        }
     }
 }
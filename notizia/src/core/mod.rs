@@ -2,13 +2,58 @@
 //!
 //! This module contains the fundamental types used for message passing:
 //! - [`Mailbox`] - Thread-safe message receiver
+//! - [`ArcMessage`] - Payloads shared between many receivers
+//! - [`correlation`] - Correlation ids for request chains
+//! - [`dead_letter`] - Messages that could not be delivered
+//! - [`Debounced`] - Conflation of message bursts by key
+//! - [`delivery`] - Acknowledged delivery of messages
+//! - [`envelope`] - System signals travelling alongside user messages
 //! - [`errors`] - Error types for send and receive operations
+//! - [`Reply`] - Reply side of a request
+//! - [`RetryPolicy`] - Retries with backoff for failed calls
+//! - [`schema`] - Machine-readable descriptions of message enums
+//! - [`spill`] - Mailboxes spilling overflow to disk (`spill` feature)
+//! - [`spsc`] - Single-producer mailboxes (`spsc` feature)
+//! - [`stream`] - Streaming replies to requests
+//! - [`time`] - Timeouts and deadlines accepted by `call!`
+//! - [`trace`] - Task spans, structured warnings and the task context of log output
+//! - [`version`] - Schema versions of serialized messages (`serde` feature)
 //! - [`state`] - Internal task-local state (hidden from docs)
 
+pub mod correlation;
+pub mod dead_letter;
+pub mod debounce;
+pub mod delivery;
+pub mod envelope;
 pub mod errors;
 pub mod lifecycle;
 pub mod mailbox;
+pub mod reply;
+pub mod retry;
+pub mod schema;
+pub mod shared;
+#[cfg(feature = "spill")]
+pub mod spill;
+#[cfg(feature = "spsc")]
+pub mod spsc;
 pub(crate) mod state;
+pub mod stream;
+pub mod time;
+pub mod trace;
+#[cfg(feature = "serde")]
+pub mod version;
 
-pub use mailbox::Mailbox;
+pub use correlation::{Correlated, CorrelationId};
+pub use dead_letter::DeadLetter;
+pub use debounce::Debounced;
+pub use envelope::{Envelope, Sender, SystemSignal};
+pub use mailbox::{Mailbox, MailboxConfig, Overflow, Received, bounded, unbounded};
+pub use reply::{CallFuture, Reply, ReplyReceiver};
+pub use retry::RetryPolicy;
+pub use schema::Describe;
+pub use shared::ArcMessage;
 pub use state::TaskState;
+pub use stream::{ReplyStream, StreamEnd, StreamReply};
+pub use time::{DEFAULT_CALL_TIMEOUT, IntoDeadline, IntoTimeout};
+#[cfg(feature = "serde")]
+pub use version::Versioned;
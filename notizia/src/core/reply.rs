@@ -0,0 +1,458 @@
+//! Replies to requests.
+//!
+//! Request variants declared with `#[request(reply = T)]` carry a
+//! [`Reply<T>`] in their `reply_to` field. The task answers the request by
+//! calling [`Reply::reply`].
+//!
+//! A [`Reply`] that is dropped without an answer, because the handler
+//! returned early or panicked, completes the call immediately with
+//! [`CallError::NoReply`] and reports the unanswered request.
+//!
+//! Each reply carries the [`CorrelationId`] of the call that created it.
+//!
+//! A request need not be answered while it is handled. [`Reply`] is `Send`
+//! and `'static` for any `Send` answer, so a task can keep it in its state
+//! and answer from a later message, e.g. once a job started with
+//! [`Context::pipe_to`](crate::task::Context::pipe_to) completes. Until then,
+//! the task goes on handling other messages; if it stops first, the stashed
+//! replies are dropped and their calls fail with [`CallError::NoReply`].
+//!
+//! [`call_detached!`](crate::call_detached!) sends a request right away and
+//! returns a [`CallFuture`] to await its answer later.
+//! [`call_hedged!`](crate::call_hedged!) builds on it to call the next of a
+//! set of replicas when the previous ones are slow to answer.
+//!
+//! [`reply!`](crate::reply!) answers either a [`Reply`] or, given a handler's
+//! [`Context`](crate::task::Context), the [sender](super::envelope::Sender)
+//! of the current message, and reports answers that could not be delivered.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::message;
+//!
+//! #[message]
+//! #[derive(Debug)]
+//! enum CounterMsg {
+//!     #[request(reply = u32)]
+//!     GetCount,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = CounterMsg)]
+//! struct Counter;
+//!
+//! impl Runnable<CounterMsg> for Counter {
+//!     async fn start(&self) {
+//!         while let Ok(CounterMsg::GetCount { reply_to }) = recv!(self) {
+//!             if !reply_to.is_canceled() {
+//!                 let _ = reply_to.reply(42);
+//!             }
+//!         }
+//!     }
+//! }
+//! # fn main() {}
+//! ```
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, ready};
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+use tokio::sync::oneshot;
+
+use super::correlation::{Correlated, CorrelationId};
+use super::envelope::Sender;
+use super::errors::{CallError, CallResult};
+use crate::runtime::{self, Instant};
+use crate::task::TaskId;
+
+/// The reply side of a request.
+///
+/// A `Reply` can be stored and answered later, from another message or
+/// another task. Dropping it without answering completes the call with
+/// [`CallError::NoReply`]. If the caller was still waiting, a warning naming
+/// the expected reply type is printed as well, so forgotten answers do not go
+/// unnoticed.
+pub struct Reply<T> {
+    sender: Option<oneshot::Sender<Option<T>>>,
+    correlation: CorrelationId,
+}
+
+impl<T> Reply<T> {
+    /// Create a reply together with the receiver awaiting its answer.
+    ///
+    /// The reply is tagged with the [current](CorrelationId::current)
+    /// correlation id, or a fresh one outside of a request chain.
+    pub fn channel() -> (Self, ReplyReceiver<T>) {
+        Self::correlated(CorrelationId::current_or_next())
+    }
+
+    fn correlated(correlation: CorrelationId) -> (Self, ReplyReceiver<T>) {
+        let (sender, receiver) = oneshot::channel();
+        let reply = Reply {
+            sender: Some(sender),
+            correlation,
+        };
+        (reply, ReplyReceiver::Reply(receiver))
+    }
+
+    /// The correlation id of the call this reply answers.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation
+    }
+
+    /// Answer the request.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value if the caller is no longer waiting for it, e.g.
+    /// because its call timed out.
+    pub fn reply(mut self, value: T) -> Result<(), T> {
+        match self.sender.take() {
+            Some(sender) => sender.send(Some(value)).map_err(|value| value.unwrap()),
+            None => Err(value),
+        }
+    }
+
+    /// Check whether the caller has stopped waiting for the answer.
+    ///
+    /// Tasks can use this to skip expensive work for requests that already
+    /// timed out.
+    pub fn is_canceled(&self) -> bool {
+        self.sender.as_ref().is_none_or(|sender| sender.is_closed())
+    }
+}
+
+impl<T> fmt::Debug for Reply<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reply")
+            .field("correlation", &self.correlation)
+            .field("canceled", &self.is_canceled())
+            .finish()
+    }
+}
+
+impl<T> Drop for Reply<T> {
+    fn drop(&mut self) {
+        let Some(sender) = self.sender.take() else {
+            return;
+        };
+
+        if sender.send(None).is_ok() {
+            super::trace::reply_dropped(self.correlation, std::any::type_name::<T>());
+        }
+    }
+}
+
+impl<T> Correlated for Reply<T> {
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        Some(self.correlation)
+    }
+}
+
+/// The caller's side of a request, resolving to its answer.
+pub enum ReplyReceiver<T> {
+    /// Awaits a [`Reply`]
+    #[doc(hidden)]
+    Reply(oneshot::Receiver<Option<T>>),
+    /// Awaits a raw oneshot sender
+    #[doc(hidden)]
+    Sender(oneshot::Receiver<T>),
+}
+
+impl<T> Future for ReplyReceiver<T> {
+    type Output = CallResult<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Both variants only hold `Unpin` receivers
+        let result = match self.get_mut() {
+            ReplyReceiver::Reply(receiver) => match ready!(Pin::new(receiver).poll(cx)) {
+                Ok(Some(value)) => Ok(value),
+                Ok(None) | Err(_) => Err(CallError::NoReply),
+            },
+            ReplyReceiver::Sender(receiver) => {
+                ready!(Pin::new(receiver).poll(cx)).map_err(|_| CallError::ChannelClosed)
+            }
+        };
+
+        Poll::Ready(result)
+    }
+}
+
+/// A request already sent by [`call_detached!`](crate::call_detached!),
+/// resolving to its answer.
+///
+/// Unlike [`call!`](crate::call!), which sends when awaited, the request is
+/// sent when the future is created, so it can be stored, awaited later or
+/// raced against other work in the meantime. The call's timeout runs from
+/// the moment it was sent.
+///
+/// Dropping the future, or calling [`cancel`](Self::cancel), abandons the
+/// call: the task sees the request as [canceled](Reply::is_canceled) and
+/// its answer is discarded.
+#[must_use = "dropping a `CallFuture` cancels the call"]
+pub struct CallFuture<T> {
+    answer: BoxFuture<'static, CallResult<T>>,
+    correlation: CorrelationId,
+}
+
+impl<T> CallFuture<T>
+where
+    T: Send + 'static,
+{
+    /// Await the answer to a sent request until `deadline`, if any.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn pending(
+        receiver: ReplyReceiver<T>,
+        deadline: Option<Instant>,
+        task: TaskId,
+        name: &'static str,
+        correlation: CorrelationId,
+        started: Instant,
+    ) -> Self {
+        let answer = Box::pin(async move {
+            match deadline {
+                Some(deadline) => runtime::timeout_at(deadline, receiver)
+                    .await
+                    .map_err(|_| CallError::timed_out(task, name, correlation, started))?,
+                None => receiver.await,
+            }
+        });
+        CallFuture {
+            answer,
+            correlation,
+        }
+    }
+
+    /// Resolve to `err` right away, for a request that could not be sent.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn failed(err: CallError, correlation: CorrelationId) -> Self {
+        CallFuture {
+            answer: Box::pin(std::future::ready(Err(err))),
+            correlation,
+        }
+    }
+}
+
+impl<T> CallFuture<T> {
+    /// The correlation id of the call.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation
+    }
+
+    /// Abandon the call, discarding its answer.
+    ///
+    /// Equivalent to dropping the future; named for readability.
+    pub fn cancel(self) {}
+}
+
+impl<T> Future for CallFuture<T> {
+    type Output = CallResult<T>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.answer.as_mut().poll(cx)
+    }
+}
+
+impl<T> fmt::Debug for CallFuture<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallFuture")
+            .field("correlation", &self.correlation)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Call `replicas` in turn until one answers, calling the next one whenever
+/// the calls in flight fail or take longer than `hedge_after`.
+///
+/// Returns the first answer, canceling the other calls, or the last error
+/// if every call failed. This is an implementation detail of
+/// [`call_hedged!`](crate::call_hedged!).
+#[doc(hidden)]
+pub async fn hedged<I, F, T>(replicas: I, mut call: F, hedge_after: Duration) -> CallResult<T>
+where
+    I: IntoIterator,
+    F: FnMut(I::Item) -> CallFuture<T>,
+{
+    let mut replicas = replicas.into_iter().peekable();
+    let mut calls = FuturesUnordered::new();
+    let mut error = CallError::NoReplicas;
+
+    loop {
+        if let Some(replica) = replicas.next() {
+            calls.push(call(replica));
+        }
+        if calls.is_empty() {
+            return Err(error);
+        }
+
+        let more = replicas.peek().is_some();
+        let hedge = async move {
+            match more {
+                true => runtime::sleep(hedge_after).await,
+                false => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            biased;
+            Some(answer) = calls.next() => match answer {
+                Ok(value) => return Ok(value),
+                // Call the next replica right away
+                Err(err) => error = err,
+            },
+            () = hedge => {}
+        }
+    }
+}
+
+/// A sender that [`call!`](crate::call!) can hand to a request.
+///
+/// This lets `call!` fill both [`Reply`] fields and raw oneshot sender
+/// fields. It is an implementation detail of the macros.
+#[doc(hidden)]
+pub trait ReplySender: Sized {
+    /// The type of the answer.
+    type Output;
+
+    /// Create the sender for a call with the given correlation id, together
+    /// with the receiver awaiting its answer.
+    fn pair(correlation: CorrelationId) -> (Self, ReplyReceiver<Self::Output>);
+}
+
+impl<T> ReplySender for Reply<T> {
+    type Output = T;
+
+    fn pair(correlation: CorrelationId) -> (Self, ReplyReceiver<T>) {
+        Reply::correlated(correlation)
+    }
+}
+
+impl<T> ReplySender for oneshot::Sender<T> {
+    type Output = T;
+
+    fn pair(_correlation: CorrelationId) -> (Self, ReplyReceiver<T>) {
+        let (sender, receiver) = oneshot::channel();
+        (sender, ReplyReceiver::Sender(receiver))
+    }
+}
+
+/// Create a reply sender of the type the request expects.
+#[doc(hidden)]
+pub fn channel<S>(correlation: CorrelationId) -> (S, ReplyReceiver<S::Output>)
+where
+    S: ReplySender,
+{
+    S::pair(correlation)
+}
+
+/// Where [`reply!`](crate::reply!) can send an answer of type `T`.
+///
+/// Implemented for [`Reply`], raw oneshot senders, the [`Sender`] of a
+/// message and a handler's [`Context`](crate::task::Context), which answers
+/// the sender of the message being handled.
+pub trait Respond<T> {
+    /// Send `value`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the value if it could not be delivered.
+    fn respond(self, value: T) -> Result<(), T>;
+}
+
+impl<T> Respond<T> for Reply<T> {
+    fn respond(self, value: T) -> Result<(), T> {
+        self.reply(value)
+    }
+}
+
+impl<T> Respond<T> for oneshot::Sender<T> {
+    fn respond(self, value: T) -> Result<(), T> {
+        self.send(value)
+    }
+}
+
+impl<T> Respond<T> for &Sender
+where
+    T: Send + 'static,
+{
+    fn respond(self, value: T) -> Result<(), T> {
+        match self.task_ref::<T>() {
+            Some(task) => task.send(value).map_err(|err| err.0),
+            None => Err(value),
+        }
+    }
+}
+
+impl<T, M> Respond<T> for &crate::task::Context<M>
+where
+    T: Send + 'static,
+{
+    fn respond(self, value: T) -> Result<(), T> {
+        match self.sender() {
+            Some(sender) => sender.respond(value),
+            None => Err(value),
+        }
+    }
+}
+
+/// Report an answer of [`reply!`](crate::reply!) that could not be
+/// delivered, returning whether it was.
+#[doc(hidden)]
+pub fn delivered<T>(result: Result<(), T>) -> bool {
+    if result.is_err() {
+        super::trace::reply_undelivered(std::any::type_name::<T>());
+    }
+    result.is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn reply_reaches_the_caller() {
+        let (reply, receiver) = Reply::channel();
+
+        assert!(!reply.is_canceled());
+        reply.reply(7).unwrap();
+
+        assert_eq!(receiver.await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn dropped_reply_completes_with_no_reply() {
+        let (reply, receiver) = Reply::<u32>::channel();
+        drop(reply);
+
+        assert!(matches!(receiver.await, Err(CallError::NoReply)));
+    }
+
+    #[test]
+    fn reply_to_canceled_request_returns_value() {
+        let (reply, receiver) = Reply::channel();
+        drop(receiver);
+
+        assert!(reply.is_canceled());
+        assert_eq!(reply.reply(7), Err(7));
+    }
+
+    #[tokio::test]
+    async fn reply_carries_correlation_id() {
+        let correlation = CorrelationId::next();
+        let (reply, _receiver) = channel::<Reply<u32>>(correlation);
+
+        assert_eq!(reply.correlation_id(), correlation);
+        assert_eq!(Correlated::correlation_id(&reply), Some(correlation));
+    }
+}
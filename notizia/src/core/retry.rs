@@ -0,0 +1,156 @@
+//! Retry policies for request-response calls.
+
+use std::future::Future;
+use std::time::Duration;
+
+use super::errors::{CallError, CallResult};
+
+/// Default number of attempts of a [`RetryPolicy`].
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// Default delay before the first retry.
+pub const DEFAULT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Default upper bound for the delay between retries.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(10);
+
+/// Describes how failed calls are retried.
+///
+/// A policy allows a number of attempts in total and waits between them with
+/// exponential backoff: the first retry waits for the initial backoff, every
+/// further retry waits [`multiplier`](Self::multiplier) times longer, up to
+/// the [maximum backoff](Self::max_backoff).
+///
+/// By default, [`CallError::Timeout`] and [`CallError::SendError`] are
+/// retried. Use [`retry_on`](Self::retry_on) to decide per error.
+///
+/// Policies are usually applied through [`call_with_retry!`](crate::call_with_retry!).
+///
+/// # Example
+///
+/// ```
+/// use notizia::core::RetryPolicy;
+/// use notizia::CallError;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(5)
+///     .backoff(Duration::from_millis(50))
+///     .max_backoff(Duration::from_secs(1))
+///     .retry_on(|err| matches!(err, CallError::Timeout { .. }));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    backoff: Duration,
+    multiplier: u32,
+    max_backoff: Duration,
+    retry_on: fn(&CallError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_ATTEMPTS)
+    }
+}
+
+impl RetryPolicy {
+    /// Create a policy allowing `max_attempts` attempts in total.
+    ///
+    /// A value of zero is treated as one, i.e. no retries.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: DEFAULT_BACKOFF,
+            multiplier: 2,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            retry_on: |err| matches!(err, CallError::Timeout { .. } | CallError::SendError { .. }),
+        }
+    }
+
+    /// Set the delay before the first retry.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set the factor the delay grows by after every retry.
+    ///
+    /// A multiplier of one gives a constant delay.
+    pub fn multiplier(mut self, multiplier: u32) -> Self {
+        self.multiplier = multiplier.max(1);
+        self
+    }
+
+    /// Set the upper bound for the delay between retries.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Set the predicate deciding which errors are retried.
+    pub fn retry_on(mut self, retry_on: fn(&CallError) -> bool) -> Self {
+        self.retry_on = retry_on;
+        self
+    }
+
+    /// Number of attempts allowed in total.
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// Delay before the given retry, starting at one for the first retry.
+    pub fn delay(&self, retry: u32) -> Duration {
+        let factor = self.multiplier.saturating_pow(retry.saturating_sub(1));
+        self.backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Run `attempt` until it succeeds, fails with an error that is not
+    /// retried, or the attempts are used up.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last attempt.
+    pub async fn run<R, F, Fut>(&self, mut attempt: F) -> CallResult<R>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = CallResult<R>>,
+    {
+        let mut attempts = 1;
+
+        loop {
+            match attempt().await {
+                Err(err) if attempts < self.max_attempts && (self.retry_on)(&err) => {
+                    crate::runtime::sleep(self.delay(attempts)).await;
+                    attempts += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn delay_grows_exponentially_up_to_maximum() {
+        let policy = RetryPolicy::new(10)
+            .backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500));
+
+        assert_eq!(policy.delay(1), Duration::from_millis(100));
+        assert_eq!(policy.delay(2), Duration::from_millis(200));
+        assert_eq!(policy.delay(3), Duration::from_millis(400));
+        assert_eq!(policy.delay(4), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn constant_backoff_with_multiplier_of_one() {
+        let policy = RetryPolicy::new(3)
+            .backoff(Duration::from_millis(10))
+            .multiplier(1);
+
+        assert_eq!(policy.delay(1), policy.delay(5));
+    }
+}
@@ -0,0 +1,184 @@
+//! Machine-readable descriptions of message enums.
+//!
+//! `#[message]` implements [`Describe`] for every message enum: its
+//! [`SCHEMA`](Describe::SCHEMA) lists the variants with their field types
+//! and, for requests, the reply type. [`Schema::to_json`] renders it for
+//! tooling outside of Rust, such as generators for clients in other
+//! languages or protocol documentation.
+//!
+//! ```
+//! use notizia::core::schema::{Describe, ReplySchema};
+//! use notizia::message;
+//!
+//! #[message]
+//! enum CounterMsg {
+//!     Add { amount: u32 },
+//!     #[request(reply = u32)]
+//!     GetCount,
+//! }
+//!
+//! let schema = CounterMsg::SCHEMA;
+//! assert_eq!(schema.name, "CounterMsg");
+//! assert_eq!(schema.variants[0].fields[0].ty, "u32");
+//! assert_eq!(schema.variants[1].reply, Some(ReplySchema::Single("u32")));
+//! println!("{}", schema.to_json());
+//! ```
+//!
+//! Types are recorded as written in the enum, so they are not resolved to
+//! full paths.
+
+use std::fmt::Write;
+
+/// Message enums with a description of their protocol.
+///
+/// Implemented by `#[message]`.
+pub trait Describe {
+    /// Description of the enum.
+    const SCHEMA: Schema;
+}
+
+/// Description of a message enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Schema {
+    /// Name of the enum
+    pub name: &'static str,
+    /// Schema version, see [`version`](super::version)
+    pub version: Option<u32>,
+    /// Whether the enum is serializable with `#[message(serde)]`
+    pub serde: bool,
+    /// Variants in declaration order
+    pub variants: &'static [VariantSchema],
+}
+
+/// Description of a variant of a message enum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantSchema {
+    /// Name of the variant
+    pub name: &'static str,
+    /// How the fields are declared
+    pub shape: Shape,
+    /// Fields in declaration order, without the reply channel of requests
+    pub fields: &'static [FieldSchema],
+    /// The reply of a request variant, `None` for casts
+    pub reply: Option<ReplySchema>,
+}
+
+/// How the fields of a variant are declared.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    /// `Variant`
+    Unit,
+    /// `Variant(A, B)`
+    Tuple,
+    /// `Variant { a: A, b: B }`
+    Struct,
+}
+
+/// Description of a field of a variant.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSchema {
+    /// Name of the field, `None` in tuple variants
+    pub name: Option<&'static str>,
+    /// The type as written in the enum
+    pub ty: &'static str,
+}
+
+/// The reply a request variant expects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplySchema {
+    /// `#[request(reply = T)]`, with the type as written
+    Single(&'static str),
+    /// `#[request(stream = T)]`, with the item type as written
+    Stream(&'static str),
+}
+
+impl Schema {
+    /// The variant named `name`.
+    pub fn variant(&self, name: &str) -> Option<&VariantSchema> {
+        self.variants.iter().find(|variant| variant.name == name)
+    }
+
+    /// Render the schema as JSON.
+    ///
+    /// ```json
+    /// {"name":"CounterMsg","version":null,"serde":false,"variants":[
+    ///   {"name":"GetCount","shape":"unit","fields":[],"reply":{"kind":"single","type":"u32"}}
+    /// ]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let mut json = String::new();
+        json.push_str("{\"name\":");
+        push_str(&mut json, self.name);
+        match self.version {
+            Some(version) => write!(json, ",\"version\":{version}").unwrap(),
+            None => json.push_str(",\"version\":null"),
+        }
+        write!(json, ",\"serde\":{},\"variants\":[", self.serde).unwrap();
+
+        for (i, variant) in self.variants.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            variant.push_json(&mut json);
+        }
+        json.push_str("]}");
+        json
+    }
+}
+
+impl VariantSchema {
+    fn push_json(&self, json: &mut String) {
+        json.push_str("{\"name\":");
+        push_str(json, self.name);
+        let shape = match self.shape {
+            Shape::Unit => "unit",
+            Shape::Tuple => "tuple",
+            Shape::Struct => "struct",
+        };
+        write!(json, ",\"shape\":\"{shape}\",\"fields\":[").unwrap();
+
+        for (i, field) in self.fields.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str("{\"name\":");
+            match field.name {
+                Some(name) => push_str(json, name),
+                None => json.push_str("null"),
+            }
+            json.push_str(",\"type\":");
+            push_str(json, field.ty);
+            json.push('}');
+        }
+
+        json.push_str("],\"reply\":");
+        match self.reply {
+            Some(ReplySchema::Single(ty)) => {
+                json.push_str("{\"kind\":\"single\",\"type\":");
+                push_str(json, ty);
+                json.push('}');
+            }
+            Some(ReplySchema::Stream(ty)) => {
+                json.push_str("{\"kind\":\"stream\",\"type\":");
+                push_str(json, ty);
+                json.push('}');
+            }
+            None => json.push_str("null"),
+        }
+        json.push('}');
+    }
+}
+
+/// Push `value` as a JSON string literal.
+fn push_str(json: &mut String, value: &str) {
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => write!(json, "\\u{:04x}", c as u32).unwrap(),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
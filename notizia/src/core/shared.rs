@@ -0,0 +1,181 @@
+//! Shared payloads for broadcasting large messages.
+//!
+//! Sending the same message to many tasks, with [`cast_all!`](crate::cast_all!)
+//! or [`ShardRegion::broadcast`](crate::sharding::ShardRegion::broadcast),
+//! clones it once per receiver. For multi-megabyte blobs this multiplies
+//! memory with the number of receivers. [`ArcMessage`] wraps a payload in an
+//! [`Arc`], so cloning it only bumps a reference count and every receiver
+//! reads the same allocation.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::cast_all;
+//! use notizia::core::ArcMessage;
+//!
+//! #[derive(Debug, Clone)]
+//! enum IndexMsg {
+//!     Snapshot(ArcMessage<Vec<u8>>),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = IndexMsg)]
+//! struct Index;
+//!
+//! impl Runnable<IndexMsg> for Index {
+//!     async fn start(&self) {
+//!         while let Ok(IndexMsg::Snapshot(snapshot)) = recv!(self) {
+//!             println!("{} bytes", snapshot.len());
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let indexes: Vec<_> = (0..8).map(|_| Index.run().this()).collect();
+//!
+//! // One 64 MiB allocation, shared by all eight tasks
+//! let snapshot = ArcMessage::new(vec![0u8; 64 << 20]);
+//! cast_all!(&indexes, IndexMsg::Snapshot(snapshot));
+//! # }
+//! ```
+
+use std::borrow::Borrow;
+use std::fmt;
+use std::ops::Deref;
+use std::sync::Arc;
+
+/// A message payload shared between all its receivers.
+///
+/// `ArcMessage<T>` is [`Clone`] for any `T`, and cloning it never copies the
+/// payload. Receivers get shared access through [`Deref`]; a receiver that
+/// needs ownership can [`try_unwrap`](Self::try_unwrap) the payload once
+/// all other copies are dropped, or [`unwrap_or_clone`](Self::unwrap_or_clone)
+/// it.
+pub struct ArcMessage<T: ?Sized>(Arc<T>);
+
+impl<T> ArcMessage<T> {
+    /// Wrap a payload for sharing.
+    pub fn new(value: T) -> Self {
+        ArcMessage(Arc::new(value))
+    }
+
+    /// Take the payload out if this is the only copy left.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message unchanged if other copies are still alive.
+    pub fn try_unwrap(this: Self) -> Result<T, Self> {
+        Arc::try_unwrap(this.0).map_err(ArcMessage)
+    }
+
+    /// Take the payload out, cloning it if other copies are still alive.
+    pub fn unwrap_or_clone(this: Self) -> T
+    where
+        T: Clone,
+    {
+        Arc::unwrap_or_clone(this.0)
+    }
+}
+
+impl<T: ?Sized> ArcMessage<T> {
+    /// The shared payload as an [`Arc`].
+    pub fn into_arc(this: Self) -> Arc<T> {
+        this.0
+    }
+
+    /// Check whether two messages share the same payload.
+    pub fn ptr_eq(this: &Self, other: &Self) -> bool {
+        Arc::ptr_eq(&this.0, &other.0)
+    }
+
+    /// Number of copies sharing the payload.
+    pub fn copies(this: &Self) -> usize {
+        Arc::strong_count(&this.0)
+    }
+}
+
+impl<T: ?Sized> Clone for ArcMessage<T> {
+    fn clone(&self) -> Self {
+        ArcMessage(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Deref for ArcMessage<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> AsRef<T> for ArcMessage<T> {
+    fn as_ref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: ?Sized> Borrow<T> for ArcMessage<T> {
+    fn borrow(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> From<T> for ArcMessage<T> {
+    fn from(value: T) -> Self {
+        ArcMessage::new(value)
+    }
+}
+
+impl<T: ?Sized> From<Arc<T>> for ArcMessage<T> {
+    fn from(value: Arc<T>) -> Self {
+        ArcMessage(value)
+    }
+}
+
+impl<T: ?Sized> From<ArcMessage<T>> for Arc<T> {
+    fn from(value: ArcMessage<T>) -> Self {
+        value.0
+    }
+}
+
+impl<T: ?Sized + fmt::Debug> fmt::Debug for ArcMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&*self.0, f)
+    }
+}
+
+impl<T: ?Sized + fmt::Display> fmt::Display for ArcMessage<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&*self.0, f)
+    }
+}
+
+impl<T: ?Sized + PartialEq> PartialEq for ArcMessage<T> {
+    fn eq(&self, other: &Self) -> bool {
+        *self.0 == *other.0
+    }
+}
+
+impl<T: ?Sized + Eq> Eq for ArcMessage<T> {}
+
+impl<T: ?Sized + std::hash::Hash> std::hash::Hash for ArcMessage<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.0.hash(state)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<T: ?Sized + serde::Serialize> serde::Serialize for ArcMessage<T> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        (*self.0).serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: serde::Deserialize<'de>> serde::Deserialize<'de> for ArcMessage<T> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(ArcMessage::new)
+    }
+}
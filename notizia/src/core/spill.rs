@@ -0,0 +1,318 @@
+//! Mailboxes spilling overflow to disk.
+//!
+//! A task spawned with [`SpawnBuilder::spill`](crate::task::SpawnBuilder::spill)
+//! keeps up to `threshold` messages in memory. Messages sent beyond that are
+//! serialized to a [`SpillFile`] and delivered from there, in the order they
+//! were sent, once the messages in memory have been received. Bursty
+//! producers therefore neither grow memory without bound nor lose messages.
+//!
+//! Spilled messages are one JSON document per line. A spill file that still
+//! holds messages when it is opened, e.g. after a crash, is replayed before
+//! anything sent later. Messages are only removed from the file once all of
+//! them have been delivered, so after a crash while the file was being
+//! drained, some spilled messages may be delivered again.
+//!
+//! Requires the `spill` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::core::spill::SpillFile;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct Sample(f64);
+//!
+//! #[derive(Task)]
+//! #[task(message = Sample)]
+//! struct Recorder;
+//!
+//! impl Runnable<Sample> for Recorder {
+//!     async fn start(&self) {
+//!         while let Ok(Sample(value)) = recv!(self) {
+//!             println!("{value}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! let recorder = Recorder
+//!     .builder()
+//!     .spill(SpillFile::open("recorder.spill")?, 1024)
+//!     .spawn();
+//!
+//! for i in 0..100_000 {
+//!     recorder.send(Sample(i as f64)).unwrap();
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use tokio::sync::Notify;
+use tokio::sync::futures::Notified;
+use tokio::sync::mpsc::error::SendError;
+
+use super::envelope::Envelope;
+use super::errors::SendResult;
+use super::trace;
+
+/// An on-disk queue of spilled messages.
+#[derive(Debug)]
+pub struct SpillFile {
+    path: PathBuf,
+    writer: File,
+    reader: BufReader<File>,
+    pending: usize,
+}
+
+impl SpillFile {
+    /// Open the spill file at `path`, creating it if it does not exist.
+    ///
+    /// Messages left in the file are delivered first. A message that was
+    /// only partially written is discarded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be opened or read.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut writer = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)?;
+
+        let mut contents = Vec::new();
+        writer.read_to_end(&mut contents)?;
+        let complete = contents
+            .iter()
+            .rposition(|byte| *byte == b'\n')
+            .map_or(0, |last| last + 1);
+        if complete < contents.len() {
+            writer.set_len(complete as u64)?;
+        }
+
+        Ok(SpillFile {
+            reader: BufReader::new(File::open(&path)?),
+            pending: contents[..complete]
+                .iter()
+                .filter(|byte| **byte == b'\n')
+                .count(),
+            path,
+            writer,
+        })
+    }
+
+    /// The path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Number of messages waiting in the file.
+    pub fn len(&self) -> usize {
+        self.pending
+    }
+
+    /// Check whether no messages are waiting in the file.
+    pub fn is_empty(&self) -> bool {
+        self.pending == 0
+    }
+
+    fn push(&mut self, mut record: Vec<u8>) -> io::Result<()> {
+        record.push(b'\n');
+        self.writer.write_all(&record)?;
+        self.pending += 1;
+        Ok(())
+    }
+
+    fn pop(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.pending == 0 {
+            return Ok(None);
+        }
+
+        let mut record = Vec::new();
+        self.reader.read_until(b'\n', &mut record)?;
+        if record.pop() != Some(b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "spill file ended in the middle of a message",
+            ));
+        }
+
+        self.pending -= 1;
+        if self.pending == 0 {
+            self.clear()?;
+        }
+        Ok(Some(record))
+    }
+
+    /// Remove all messages, so the file does not grow across bursts.
+    fn clear(&mut self) -> io::Result<()> {
+        self.pending = 0;
+        self.writer.set_len(0)?;
+        self.reader.seek(SeekFrom::Start(0))?;
+        Ok(())
+    }
+}
+
+/// Spilling state shared between the references and the mailbox of a task.
+///
+/// Senders put a message on the channel while the spill file is empty and
+/// fewer than `threshold` messages are in memory, and append it to the file
+/// otherwise. The mailbox only reads the file once the channel is empty, so
+/// messages are delivered in the order they were sent.
+pub(crate) struct Spill<T> {
+    file: Mutex<SpillFile>,
+    threshold: usize,
+    /// User messages on the channel
+    memory: AtomicUsize,
+    spilled: Notify,
+    encode: fn(&T) -> serde_json::Result<Vec<u8>>,
+    decode: fn(&[u8]) -> serde_json::Result<T>,
+}
+
+impl<T> Spill<T> {
+    pub(crate) fn new(file: SpillFile, threshold: usize) -> Self
+    where
+        T: Serialize + DeserializeOwned,
+    {
+        Spill {
+            file: Mutex::new(file),
+            threshold,
+            memory: AtomicUsize::new(0),
+            spilled: Notify::new(),
+            encode: |msg| serde_json::to_vec(msg),
+            decode: |record| serde_json::from_slice(record),
+        }
+    }
+
+    /// Send `msg` through `channel`, or spill it if the mailbox is over its
+    /// threshold.
+    pub(crate) fn send(&self, msg: T, channel: impl FnOnce(T) -> SendResult<T>) -> SendResult<T> {
+        let mut file = self.file.lock().unwrap();
+
+        if file.is_empty() && self.memory.load(Ordering::SeqCst) < self.threshold {
+            // Count first, so the mailbox never sees more messages than counted
+            self.memory.fetch_add(1, Ordering::SeqCst);
+            return channel(msg).inspect_err(|_| self.received());
+        }
+
+        let spilled = (self.encode)(&msg)
+            .map_err(io::Error::from)
+            .and_then(|record| file.push(record));
+        match spilled {
+            Ok(()) => {
+                self.spilled.notify_one();
+                Ok(())
+            }
+            Err(error) => {
+                trace::spill_failed(&file.path, &error);
+                Err(SendError(msg))
+            }
+        }
+    }
+
+    /// Send `msg` through `channel`, even if the mailbox is over its
+    /// threshold.
+    ///
+    /// Used for reliable messages, whose acknowledgement cannot be spilled.
+    pub(crate) fn send_unspilled(
+        &self,
+        msg: T,
+        channel: impl FnOnce(T) -> SendResult<T>,
+    ) -> SendResult<T> {
+        let _file = self.file.lock().unwrap();
+
+        self.memory.fetch_add(1, Ordering::SeqCst);
+        channel(msg).inspect_err(|_| self.received())
+    }
+
+    /// Account for an envelope received from the channel.
+    pub(crate) fn receive(&self, envelope: Envelope<T>) -> Envelope<T> {
+        if let Envelope::User(_) | Envelope::Reliable(..) | Envelope::Sent(..) = envelope {
+            self.received();
+        }
+        envelope
+    }
+
+    fn received(&self) {
+        let _ = self
+            .memory
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |memory| {
+                memory.checked_sub(1)
+            });
+    }
+
+    /// Take the oldest spilled message.
+    ///
+    /// Messages that cannot be read are reported and skipped. If the file
+    /// itself cannot be read, the remaining spilled messages are discarded.
+    pub(crate) fn pop(&self) -> Option<T> {
+        let mut file = self.file.lock().unwrap();
+
+        loop {
+            let record = match file.pop() {
+                Ok(record) => record?,
+                Err(error) => {
+                    trace::spill_failed(&file.path, &error);
+                    if let Err(error) = file.clear() {
+                        trace::spill_failed(&file.path, &error);
+                    }
+                    return None;
+                }
+            };
+
+            match (self.decode)(&record) {
+                Ok(msg) => return Some(msg),
+                Err(error) => trace::spill_failed(&file.path, &error),
+            }
+        }
+    }
+
+    /// Wait until a message was spilled.
+    pub(crate) fn spilled(&self) -> Notified<'_> {
+        self.spilled.notified()
+    }
+}
+
+impl<T> fmt::Debug for Spill<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Spill")
+            .field("file", &self.file)
+            .field("threshold", &self.threshold)
+            .field("memory", &self.memory)
+            .finish()
+    }
+}
+
+/// A [`Spill`] of any message type, carried by spawn options.
+#[derive(Clone)]
+pub(crate) struct AnySpill(Arc<dyn Any + Send + Sync>);
+
+impl AnySpill {
+    pub(crate) fn new<T: 'static>(spill: Spill<T>) -> Self {
+        AnySpill(Arc::new(spill))
+    }
+
+    pub(crate) fn downcast<T: 'static>(&self) -> Option<Arc<Spill<T>>> {
+        self.0.clone().downcast().ok()
+    }
+}
+
+impl fmt::Debug for AnySpill {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnySpill").finish_non_exhaustive()
+    }
+}
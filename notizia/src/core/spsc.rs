@@ -0,0 +1,199 @@
+//! Single-producer mailboxes.
+//!
+//! A task spawned with
+//! [`SpawnBuilder::single_producer`](crate::task::SpawnBuilder::single_producer)
+//! receives its messages through a fixed-size ring buffer instead of the
+//! multi-producer channel behind regular mailboxes. With a single sender,
+//! e.g. the stage of a pipeline feeding the next one, this skips the
+//! coordination between producers the channel needs;
+//! `cargo bench --features spsc` compares both paths.
+//!
+//! The ring holds at most `capacity` messages. Like a
+//! [bounded](super::bounded) mailbox that rejects overflowing messages,
+//! sending to a full ring fails, while [`feed!`](crate::feed!) waits for
+//! room. Several references may still send to the task: their sends are
+//! serialized, which stays correct but gives up the fast path.
+//!
+//! [System signals](super::SystemSignal) travel on the regular channel and
+//! are handled once the ring is empty. Messages sent before a signal are
+//! still received before it, but messages sent after it may be received
+//! before it as well.
+//!
+//! Requires the `spsc` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::feed;
+//!
+//! #[derive(Debug)]
+//! struct Frame(Vec<u8>);
+//!
+//! #[derive(Task)]
+//! #[task(message = Frame)]
+//! struct Encoder;
+//!
+//! impl Runnable<Frame> for Encoder {
+//!     async fn start(&self) {
+//!         while let Ok(Frame(bytes)) = recv!(self) {
+//!             println!("{} bytes", bytes.len());
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let encoder = Encoder.builder().single_producer(1024).spawn();
+//!
+//! for _ in 0..100_000 {
+//!     feed!(encoder, Frame(vec![0; 64])).await?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::sync::atomic::{AtomicUsize, Ordering, fence};
+use std::sync::{Arc, Mutex};
+use std::task::Poll;
+
+use futures::task::AtomicWaker;
+use rtrb::{Consumer, Producer, RingBuffer};
+use tokio::sync::Notify;
+
+use super::envelope::Envelope;
+
+/// Ring buffer shared between the references and the mailbox of a task.
+pub(crate) struct Ring<T> {
+    producer: Mutex<Producer<Envelope<T>>>,
+    /// Taken by the mailbox once it starts receiving
+    consumer: Mutex<Option<Consumer<Envelope<T>>>>,
+    /// Wakes the mailbox once a message was pushed
+    pushed: AtomicWaker,
+    /// Wakes senders waiting for room
+    popped: Notify,
+    /// Number of senders waiting for room
+    waiting: AtomicUsize,
+}
+
+impl<T> Ring<T> {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let (producer, consumer) = RingBuffer::new(capacity);
+
+        Ring {
+            producer: Mutex::new(producer),
+            consumer: Mutex::new(Some(consumer)),
+            pushed: AtomicWaker::new(),
+            popped: Notify::new(),
+            waiting: AtomicUsize::new(0),
+        }
+    }
+
+    /// Push an envelope, returning it if the ring is full.
+    pub(crate) fn push(&self, envelope: Envelope<T>) -> Result<(), Envelope<T>> {
+        let pushed = self.producer.lock().unwrap().push(envelope);
+
+        match pushed {
+            Ok(()) => {
+                self.pushed.wake();
+                Ok(())
+            }
+            Err(rtrb::PushError::Full(envelope)) => Err(envelope),
+        }
+    }
+
+    /// Wait until the mailbox has taken a message out of the ring.
+    ///
+    /// Room made after this was called but before it is awaited is not
+    /// missed.
+    pub(crate) fn space(&self) -> impl Future<Output = ()> + '_ {
+        let waiting = Waiting::new(&self.waiting);
+        let popped = self.popped.notified();
+
+        async move {
+            let _waiting = waiting;
+            popped.await;
+        }
+    }
+
+    /// The receiving end of the ring, unless it was already taken.
+    pub(crate) fn reader(self: &Arc<Self>) -> Option<RingReader<T>> {
+        let consumer = self.consumer.lock().unwrap().take()?;
+
+        Some(RingReader {
+            ring: self.clone(),
+            consumer,
+        })
+    }
+}
+
+impl<T> fmt::Debug for Ring<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Ring")
+            .field("waiting", &self.waiting)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Registration of a sender waiting for room.
+struct Waiting<'a>(&'a AtomicUsize);
+
+impl<'a> Waiting<'a> {
+    fn new(waiting: &'a AtomicUsize) -> Self {
+        waiting.fetch_add(1, Ordering::SeqCst);
+        // Pair with the fence in `pop`, so either the mailbox sees this
+        // sender or the sender sees the room made by the mailbox
+        fence(Ordering::SeqCst);
+        Waiting(waiting)
+    }
+}
+
+impl Drop for Waiting<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// The receiving end of a [`Ring`], owned by the mailbox.
+pub(crate) struct RingReader<T> {
+    ring: Arc<Ring<T>>,
+    consumer: Consumer<Envelope<T>>,
+}
+
+impl<T> RingReader<T> {
+    /// Take the oldest envelope out of the ring.
+    pub(crate) fn pop(&mut self) -> Option<Envelope<T>> {
+        let envelope = self.consumer.pop().ok()?;
+
+        // Wake waiting senders once half of the ring is free, rather than
+        // after every message
+        if self.consumer.slots() <= self.consumer.buffer().capacity() / 2 {
+            fence(Ordering::SeqCst);
+            if self.ring.waiting.load(Ordering::SeqCst) > 0 {
+                self.ring.popped.notify_waiters();
+            }
+        }
+        Some(envelope)
+    }
+
+    /// Wait until the ring holds an envelope.
+    pub(crate) async fn ready(&mut self) {
+        // Capture the exclusive borrow, since the consumer is not `Sync`
+        poll_fn(move |cx| {
+            // Register before checking, so no push is missed
+            self.ring.pushed.register(cx.waker());
+            match self.consumer.is_empty() {
+                true => Poll::Pending,
+                false => Poll::Ready(()),
+            }
+        })
+        .await
+    }
+
+    /// Number of envelopes in the ring.
+    pub(crate) fn len(&self) -> usize {
+        self.consumer.slots()
+    }
+}
@@ -1,8 +1,27 @@
 //! Task-local state (internal use only).
 
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::mpsc::{WeakUnboundedSender, unbounded_channel};
+
+use std::any::Any;
+use std::future::Future;
+use std::sync::Arc;
 
 use super::Mailbox;
+use super::envelope::Envelope;
+use super::mailbox::Capacity;
+#[cfg(feature = "spill")]
+use super::spill::Spill;
+#[cfg(feature = "spsc")]
+use super::spsc::Ring;
+use crate::task::{Control, TaskId, TaskRef};
+
+tokio::task_local! {
+    /// State of the running task if it is a generic task.
+    ///
+    /// `task_local!` statics cannot depend on generic parameters, so the
+    /// generated code of generic tasks stores their state here, type-erased.
+    static GENERIC: Box<dyn Any + Send>;
+}
 
 /// Internal state stored in task-local storage.
 ///
@@ -10,19 +29,124 @@ use super::Mailbox;
 /// state including the mailbox and sender. It is stored using Tokio's
 /// `task_local!` macro.
 ///
+/// The sender is held weakly, so that the task itself does not keep its own
+/// mailbox open. Once every [`TaskHandle`](crate::TaskHandle) and
+/// [`TaskRef`] is gone, the task receives
+/// [`RecvError::Closed`](crate::RecvError::Closed).
+///
 /// This type is hidden from documentation as it's an implementation detail.
 pub struct TaskState<T> {
     pub mailbox: Mailbox<T>,
-    pub sender: UnboundedSender<T>,
+    pub sender: WeakUnboundedSender<Envelope<T>>,
+    pub id: TaskId,
+    pub name: &'static str,
+    pub(crate) path: &'static str,
+    pub(crate) capacity: Option<Arc<Capacity>>,
+    #[cfg(feature = "spill")]
+    pub(crate) spill: Option<Arc<Spill<T>>>,
+    #[cfg(feature = "spsc")]
+    pub(crate) ring: Option<Arc<Ring<T>>>,
+    pub(crate) control: Option<Control>,
+}
+
+impl<T> TaskState<T> {
+    /// Create the state for the task referenced by `task`.
+    pub fn new(mailbox: Mailbox<T>, task: &TaskRef<T>) -> Self {
+        TaskState {
+            mailbox,
+            sender: task.downgrade(),
+            id: task.id(),
+            name: task.name(),
+            path: task.path(),
+            capacity: task.capacity().cloned(),
+            #[cfg(feature = "spill")]
+            spill: task.spill().cloned(),
+            #[cfg(feature = "spsc")]
+            ring: task.ring().cloned(),
+            control: task.control().cloned(),
+        }
+    }
+
+    /// Get a reference to the task owning this state.
+    ///
+    /// If the mailbox has already been closed, the returned reference is
+    /// disconnected and every send through it fails.
+    pub fn task_ref(&self) -> TaskRef<T> {
+        let sender = self
+            .sender
+            .upgrade()
+            .unwrap_or_else(|| unbounded_channel().0);
+        let task = TaskRef::with_identity(sender, self.id, self.name)
+            .with_path(self.path)
+            .with_capacity(self.capacity.clone())
+            .with_control(self.control.clone());
+        #[cfg(feature = "spill")]
+        let task = task.with_spill(self.spill.clone());
+        #[cfg(feature = "spsc")]
+        let task = task.with_ring(self.ring.clone());
+
+        task
+    }
+
+    /// The control mailbox of a multi-protocol task.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task has no control mailbox.
+    pub fn control(&self) -> Control {
+        self.control
+            .clone()
+            .expect("task was spawned without a control mailbox")
+    }
+}
+
+impl<T> TaskState<T>
+where
+    T: Send + 'static,
+{
+    /// Run `future` with `self` as the state of a generic task.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    pub fn scope_generic<F>(self, future: F) -> impl Future<Output = F::Output>
+    where
+        F: Future,
+    {
+        GENERIC.scope(Box::new(self), future)
+    }
+
+    /// The state of the running generic task.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a generic task with message type `T`.
+    pub fn generic() -> Self {
+        GENERIC.with(|state| {
+            state
+                .downcast_ref::<Self>()
+                .expect("task state has a different message type")
+                .clone()
+        })
+    }
 }
 
 // Manual Clone implementation to avoid requiring T: Clone
-// Both Mailbox<T> and UnboundedSender<T> are Clone regardless of T
+// Both Mailbox<T> and WeakUnboundedSender<T> are Clone regardless of T
 impl<T> Clone for TaskState<T> {
     fn clone(&self) -> Self {
         TaskState {
             mailbox: self.mailbox.clone(),
             sender: self.sender.clone(),
+            id: self.id,
+            name: self.name,
+            path: self.path,
+            capacity: self.capacity.clone(),
+            #[cfg(feature = "spill")]
+            spill: self.spill.clone(),
+            #[cfg(feature = "spsc")]
+            ring: self.ring.clone(),
+            control: self.control.clone(),
         }
     }
 }
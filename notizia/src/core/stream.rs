@@ -0,0 +1,251 @@
+//! Streaming replies to requests.
+//!
+//! Some requests are answered with many values over time, e.g. progress
+//! updates or pages of a result set. Request variants declared with
+//! `#[request(stream = Item)]` carry a [`StreamReply<Item>`] in their
+//! `reply_to` field, and [`call_stream!`](crate::call_stream!) returns the
+//! matching [`ReplyStream<Item>`].
+//!
+//! The task sends items with [`StreamReply::send`] and ends the stream with
+//! [`StreamReply::finish`] or [`StreamReply::fail`]. After the stream is
+//! exhausted, [`ReplyStream::end`] tells the caller how it ended.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::{call_stream, message};
+//! use notizia::futures::StreamExt;
+//!
+//! #[message]
+//! #[derive(Debug)]
+//! enum JobMsg {
+//!     #[request(stream = u8)]
+//!     Progress,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = JobMsg)]
+//! struct Job;
+//!
+//! impl Runnable<JobMsg> for Job {
+//!     async fn start(&self) {
+//!         while let Ok(JobMsg::Progress { reply_to }) = recv!(self) {
+//!             for percent in [25, 50, 75, 100] {
+//!                 if reply_to.send(percent).await.is_err() {
+//!                     break;
+//!                 }
+//!             }
+//!             reply_to.finish().await;
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), CallError> {
+//! let job = spawn!(Job);
+//!
+//! let mut progress = call_stream!(job, JobMsg::Progress)?;
+//! while let Some(percent) = progress.next().await {
+//!     println!("{percent}%");
+//! }
+//! assert_eq!(progress.end(), Some(&StreamEnd::Finished));
+//! # Ok(())
+//! # }
+//! ```
+
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::Stream;
+
+use super::correlation::{Correlated, CorrelationId};
+use tokio::sync::mpsc;
+
+/// Number of items buffered between the task and the caller by default.
+pub const DEFAULT_STREAM_BUFFER: usize = 32;
+
+/// How a [`ReplyStream`] ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamEnd {
+    /// The task finished the stream
+    Finished,
+    /// The task failed the stream with a reason
+    Failed(String),
+    /// The task dropped the [`StreamReply`] without finishing the stream
+    Dropped,
+}
+
+enum Frame<T> {
+    Item(T),
+    End(StreamEnd),
+}
+
+/// The task's side of a streaming request.
+pub struct StreamReply<T> {
+    sender: mpsc::Sender<Frame<T>>,
+    correlation: CorrelationId,
+}
+
+/// The caller's side of a streaming request.
+///
+/// Yields the items sent by the task, in order. Once it returns `None`,
+/// [`end`](Self::end) reports how the stream ended.
+pub struct ReplyStream<T> {
+    receiver: mpsc::Receiver<Frame<T>>,
+    end: Option<StreamEnd>,
+}
+
+/// Create a connected [`StreamReply`] and [`ReplyStream`] buffering up to
+/// `buffer` items.
+///
+/// The reply is tagged with the [current](CorrelationId::current)
+/// correlation id, or a fresh one outside of a request chain.
+///
+/// # Panics
+///
+/// Panics if `buffer` is zero.
+pub fn channel<T>(buffer: usize) -> (StreamReply<T>, ReplyStream<T>) {
+    let (sender, receiver) = mpsc::channel(buffer);
+    (
+        StreamReply {
+            sender,
+            correlation: CorrelationId::current_or_next(),
+        },
+        ReplyStream {
+            receiver,
+            end: None,
+        },
+    )
+}
+
+impl<T> StreamReply<T> {
+    /// Send the next item, waiting while the caller's buffer is full.
+    ///
+    /// # Errors
+    ///
+    /// Returns the item if the caller dropped the stream.
+    pub async fn send(&self, item: T) -> Result<(), T> {
+        match self.sender.reserve().await {
+            Ok(permit) => {
+                permit.send(Frame::Item(item));
+                Ok(())
+            }
+            Err(_) => Err(item),
+        }
+    }
+
+    /// End the stream successfully.
+    pub async fn finish(self) {
+        let _ = self.sender.send(Frame::End(StreamEnd::Finished)).await;
+    }
+
+    /// End the stream with an error.
+    pub async fn fail(self, reason: impl Into<String>) {
+        let _ = self
+            .sender
+            .send(Frame::End(StreamEnd::Failed(reason.into())))
+            .await;
+    }
+
+    /// Check whether the caller dropped the stream.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// The correlation id of the call this stream answers.
+    pub fn correlation_id(&self) -> CorrelationId {
+        self.correlation
+    }
+}
+
+impl<T> Correlated for StreamReply<T> {
+    fn correlation_id(&self) -> Option<CorrelationId> {
+        Some(self.correlation)
+    }
+}
+
+impl<T> std::fmt::Debug for StreamReply<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamReply")
+            .field("correlation", &self.correlation)
+            .field("closed", &self.is_closed())
+            .finish()
+    }
+}
+
+impl<T> ReplyStream<T> {
+    /// How the stream ended, or `None` while it is still open.
+    pub fn end(&self) -> Option<&StreamEnd> {
+        self.end.as_ref()
+    }
+}
+
+impl<T> Stream for ReplyStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        let this = self.get_mut();
+
+        if this.end.is_some() {
+            return Poll::Ready(None);
+        }
+
+        match this.receiver.poll_recv(cx) {
+            Poll::Ready(Some(Frame::Item(item))) => Poll::Ready(Some(item)),
+            Poll::Ready(Some(Frame::End(end))) => {
+                this.end = Some(end);
+                this.receiver.close();
+                Poll::Ready(None)
+            }
+            Poll::Ready(None) => {
+                this.end = Some(StreamEnd::Dropped);
+                Poll::Ready(None)
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn items_arrive_in_order_until_finished() {
+        let (reply, mut stream) = channel(2);
+
+        tokio::spawn(async move {
+            for i in 0..5 {
+                reply.send(i).await.unwrap();
+            }
+            reply.finish().await;
+        });
+
+        assert_eq!(stream.end(), None);
+        assert_eq!(
+            stream.by_ref().collect::<Vec<_>>().await,
+            vec![0, 1, 2, 3, 4]
+        );
+        assert_eq!(stream.end(), Some(&StreamEnd::Finished));
+    }
+
+    #[tokio::test]
+    async fn dropped_reply_ends_stream() {
+        let (reply, mut stream) = channel::<u8>(1);
+        drop(reply);
+
+        assert_eq!(stream.next().await, None);
+        assert_eq!(stream.end(), Some(&StreamEnd::Dropped));
+    }
+
+    #[tokio::test]
+    async fn send_to_dropped_stream_returns_item() {
+        let (reply, stream) = channel(1);
+        drop(stream);
+
+        assert!(reply.is_closed());
+        assert_eq!(reply.send(7).await, Err(7));
+    }
+}
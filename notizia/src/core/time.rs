@@ -0,0 +1,80 @@
+//! Timeouts and deadlines for calls.
+//!
+//! The `timeout = ...` argument of [`call!`](crate::call!) and related macros
+//! accepts anything implementing [`IntoTimeout`]: a [`Duration`], or an
+//! integer number of milliseconds for backward compatibility. The
+//! `deadline = ...` argument accepts anything implementing [`IntoDeadline`].
+
+use std::time::Duration;
+
+use crate::runtime::Instant;
+
+/// Timeout of calls that do not specify one.
+pub const DEFAULT_CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A value that can be used as a call timeout.
+///
+/// Integers are interpreted as milliseconds; negative values are treated as
+/// zero.
+pub trait IntoTimeout {
+    /// Convert into a [`Duration`].
+    fn into_timeout(self) -> Duration;
+}
+
+impl IntoTimeout for Duration {
+    fn into_timeout(self) -> Duration {
+        self
+    }
+}
+
+macro_rules! impl_into_timeout_millis {
+    ($($ty:ty),*) => {
+        $(
+            impl IntoTimeout for $ty {
+                fn into_timeout(self) -> Duration {
+                    Duration::from_millis(u64::try_from(self).unwrap_or(0))
+                }
+            }
+        )*
+    };
+}
+
+impl_into_timeout_millis!(u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+/// A value that can be used as an absolute call deadline.
+pub trait IntoDeadline {
+    /// Convert into an [`Instant`].
+    fn into_deadline(self) -> Instant;
+}
+
+impl IntoDeadline for Instant {
+    fn into_deadline(self) -> Instant {
+        self
+    }
+}
+
+// `std::time::Instant` is unsupported in browsers
+#[cfg(not(target_arch = "wasm32"))]
+impl IntoDeadline for std::time::Instant {
+    fn into_deadline(self) -> Instant {
+        Instant::from_std(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers_are_milliseconds() {
+        assert_eq!(1000.into_timeout(), Duration::from_secs(1));
+        assert_eq!(250u64.into_timeout(), Duration::from_millis(250));
+        assert_eq!((-5i32).into_timeout(), Duration::ZERO);
+    }
+
+    #[test]
+    fn durations_are_used_as_is() {
+        let timeout = Duration::from_micros(1500);
+        assert_eq!(timeout.into_timeout(), timeout);
+    }
+}
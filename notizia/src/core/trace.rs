@@ -0,0 +1,288 @@
+//! Structured diagnostics for tasks.
+//!
+//! With the `tracing` feature enabled, every task derived with
+//! `#[derive(Task)]` runs inside a `task` span carrying its `id`, `name` and
+//! `path` (the module path of the task type). Each message a handler task
+//! dispatches is processed in a child `message` span, and warnings such as a
+//! panicking `terminate()` hook are emitted as `tracing` events.
+//!
+//! Without the feature, spans are not created and warnings are printed to
+//! stderr.
+//!
+//! # Example
+//!
+//! ```toml
+//! [dependencies]
+//! notizia = { version = "0.3", features = ["tracing"] }
+//! ```
+//!
+//! # Task context
+//!
+//! Independent of the `tracing` feature, code running inside a task can
+//! look up the task's id, name and path with [`TaskContext::current`], e.g.
+//! to prefix its own output. With the `log` feature, wrapping the
+//! application's logger in a [`TaskLogger`] prefixes every record emitted
+//! inside a task with the task's name and id, so interleaved logs of many
+//! workers stay attributable:
+//!
+//! ```ignore
+//! TaskLogger::new(env_logger::Logger::from_default_env()).install()?;
+//!
+//! // Inside a task: "[Worker#7] processing job 42"
+//! log::info!("processing job {}", job);
+//! ```
+//!
+//! The context does not carry over to futures the task spawns itself.
+
+use std::fmt;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::core::correlation::CorrelationId;
+use crate::core::envelope;
+use crate::task::{TaskId, TaskRef, timer};
+
+tokio::task_local! {
+    static CONTEXT: TaskContext;
+}
+
+/// The identity of the task running the caller.
+///
+/// Displayed as `name#id`, e.g. `Worker#7`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskContext {
+    /// Identifier of the task
+    pub id: TaskId,
+    /// Name of the task
+    pub name: &'static str,
+    /// Module path of the task type
+    pub path: &'static str,
+}
+
+impl TaskContext {
+    /// The context of the task running the caller, or `None` outside of
+    /// tasks.
+    pub fn current() -> Option<Self> {
+        CONTEXT.try_with(|context| *context).ok()
+    }
+}
+
+impl fmt::Display for TaskContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}#{}", self.name, self.id)
+    }
+}
+
+/// A [`log::Log`] prefixing records emitted inside a task with its
+/// [`TaskContext`].
+///
+/// Records emitted outside of tasks are passed on unchanged.
+///
+/// Requires the `log` feature.
+#[cfg(feature = "log")]
+#[derive(Debug)]
+pub struct TaskLogger<L> {
+    inner: L,
+}
+
+#[cfg(feature = "log")]
+impl<L> TaskLogger<L>
+where
+    L: log::Log + 'static,
+{
+    /// Wrap `inner`, which writes the prefixed records.
+    pub fn new(inner: L) -> Self {
+        TaskLogger { inner }
+    }
+
+    /// Install as the global logger, keeping the current maximum level.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`log::SetLoggerError`] if a logger was already installed.
+    pub fn install(self) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(Box::new(self))
+    }
+}
+
+#[cfg(feature = "log")]
+impl<L> log::Log for TaskLogger<L>
+where
+    L: log::Log,
+{
+    fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record<'_>) {
+        match TaskContext::current() {
+            Some(context) => self.inner.log(
+                &record
+                    .to_builder()
+                    .args(format_args!("[{}] {}", context, record.args()))
+                    .build(),
+            ),
+            None => self.inner.log(record),
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Run `future` as `task`, inside its span.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn instrument<T, F>(
+    task: &TaskRef<T>,
+    path: &'static str,
+    future: F,
+) -> impl Future<Output = F::Output> + use<T, F>
+where
+    T: Send + 'static,
+    F: Future,
+{
+    let context = TaskContext {
+        id: task.id(),
+        name: task.name(),
+        path,
+    };
+    let future = timer::owning_timers(future);
+    let future = CONTEXT.scope(context, envelope::sending_as(task, future));
+
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::info_span!("task", id = %task.id(), name = task.name(), path);
+        tracing::Instrument::instrument(future, span)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    future
+}
+
+/// Run `future`, handling a single message of type `M`, inside a `message`
+/// span.
+#[cfg_attr(not(feature = "tracing"), allow(clippy::extra_unused_type_parameters))]
+pub(crate) fn dispatch<M, F>(future: F) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    #[cfg(feature = "tracing")]
+    {
+        let span = tracing::debug_span!("message", message = std::any::type_name::<M>());
+        tracing::Instrument::instrument(future, span)
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    future
+}
+
+/// Report a panic of a task's `terminate()` hook.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn terminate_panicked(message: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(panic = message, "terminate() hook panicked");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("Warning: terminate() hook panicked: {}", message);
+}
+
+/// Report a request whose [`Reply`](super::Reply) was dropped without an
+/// answer.
+pub(crate) fn reply_dropped(correlation: CorrelationId, reply: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%correlation, reply, "request was dropped without an answer");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!(
+        "Warning: request {} expecting a `{}` reply was dropped without an answer",
+        correlation, reply
+    );
+}
+
+/// Report an answer of [`reply!`](crate::reply!) that could not be delivered.
+pub(crate) fn reply_undelivered(reply: &'static str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(reply, "reply could not be delivered");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("Warning: `{}` reply could not be delivered", reply);
+}
+
+/// Report a frame to or from a remote node that could not be delivered.
+///
+/// `peer` is the address the frame was sent to, or empty for received frames.
+#[cfg(feature = "remote")]
+pub(crate) fn frame_dropped(peer: &str, error: &dyn std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(peer, %error, "remote frame was dropped");
+
+    #[cfg(not(feature = "tracing"))]
+    match peer {
+        "" => eprintln!("Warning: remote frame was dropped: {}", error),
+        peer => eprintln!("Warning: remote frame to {} was dropped: {}", peer, error),
+    }
+}
+
+/// Report a spill file that could not be written or read.
+#[cfg(feature = "spill")]
+pub(crate) fn spill_failed(file: &std::path::Path, error: &dyn std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(file = %file.display(), %error, "spill file failed");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!("Warning: spill file `{}` failed: {}", file.display(), error);
+}
+
+/// Report a snapshot of a persistent task that could not be saved.
+#[cfg(feature = "persistence")]
+pub(crate) fn snapshot_failed(journal: &str, error: &dyn std::fmt::Display) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(journal, %error, "snapshot could not be saved");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!(
+        "Warning: snapshot of journal `{}` could not be saved: {}",
+        journal, error
+    );
+}
+
+/// Report a handler that has been processing a single message for longer
+/// than its watchdog allows.
+pub(crate) fn handler_stalled(
+    id: TaskId,
+    task: &'static str,
+    message: &'static str,
+    elapsed: Duration,
+    state: Option<&str>,
+) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(%id, task, message, ?elapsed, state, "handler stalled");
+
+    #[cfg(not(feature = "tracing"))]
+    {
+        eprintln!(
+            "Warning: {} ({}) has been handling a `{}` for {:?}",
+            task, id, message, elapsed
+        );
+        if let Some(state) = state {
+            eprintln!("Last known state: {}", state);
+        }
+    }
+}
+
+/// Report a message put into the dead-letter queue.
+pub(crate) fn dead_lettered(task: &'static str, attempts: u32, reason: &str) {
+    #[cfg(feature = "tracing")]
+    tracing::warn!(task, attempts, reason, "message was dead-lettered");
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!(
+        "Warning: message to {} was dead-lettered after {} attempts: {}",
+        task, attempts, reason
+    );
+}
@@ -0,0 +1,91 @@
+//! Schema versions of serialized messages.
+//!
+//! Nodes of a cluster are upgraded one by one, so for a while a message
+//! enum may be serialized by a node running an older version of the
+//! protocol than the node deserializing it. `#[message(serde)]` implements
+//! [`Versioned`] for the enum; with `#[message(version = 2)]`, remote frames
+//! carrying the message are tagged with version 2, and payloads tagged with
+//! any other version are rejected instead of being decoded into the wrong
+//! shape. A `compat` hook decodes payloads of older versions:
+//!
+//! ```
+//! use notizia::message;
+//! use notizia::serde::{Deserialize, Deserializer};
+//!
+//! #[message(version = 2, compat = upcast)]
+//! enum OrderMsg {
+//!     Place { id: u32, quantity: u32 },
+//! }
+//!
+//! /// Version 1 had no quantity
+//! #[derive(Deserialize)]
+//! #[serde(crate = "notizia::serde")]
+//! enum OrderMsgV1 {
+//!     Place { id: u32 },
+//! }
+//!
+//! fn upcast<'de, D>(version: Option<u32>, payload: D) -> Result<OrderMsg, D::Error>
+//! where
+//!     D: Deserializer<'de>,
+//! {
+//!     match version {
+//!         Some(1) | None => match OrderMsgV1::deserialize(payload)? {
+//!             OrderMsgV1::Place { id } => Ok(OrderMsg::Place { id, quantity: 1 }),
+//!         },
+//!         Some(version) => Err(notizia::core::version::unsupported(version, 2)),
+//!     }
+//! }
+//! ```
+//!
+//! Payloads from newer versions never reach the hook: an old node cannot
+//! know what changed, so it rejects them.
+//!
+//! Requires the `serde` feature.
+
+use serde::Deserializer;
+use serde::de::Error;
+
+/// Messages whose serialized form carries a schema version.
+///
+/// Implemented by `#[message(serde)]`; see the [module documentation](self).
+pub trait Versioned: Sized {
+    /// The version of the schema, `None` for unversioned messages.
+    const VERSION: Option<u32>;
+
+    /// Deserialize a message serialized with schema `version`.
+    ///
+    /// `None` means the sender did not tag the message with a version.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the payload cannot be deserialized, or its
+    /// version is not supported.
+    fn upcast<'de, D>(version: Option<u32>, payload: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>;
+}
+
+/// The error for a payload of a `version` that a message of schema
+/// `supported` cannot decode.
+pub fn unsupported<E>(version: u32, supported: u32) -> E
+where
+    E: Error,
+{
+    E::custom(format_args!(
+        "unsupported message version {version}, expected {supported}"
+    ))
+}
+
+/// The error for an untagged payload reaching a versioned message without
+/// `compat` hook.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn untagged<E>(supported: u32) -> E
+where
+    E: Error,
+{
+    E::custom(format_args!(
+        "message without version, expected {supported}"
+    ))
+}
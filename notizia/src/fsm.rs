@@ -0,0 +1,230 @@
+//! Tasks driven by a finite state machine.
+//!
+//! Instead of hand-rolling a mode flag in a `start()` loop, a
+//! [`StateMachine`] declares its states as a type and answers every event
+//! with a typed [`Transition`]. The runner spawned by [`spawn`] takes care
+//! of the rest:
+//!
+//! - [`on_enter`](StateMachine::on_enter) and
+//!   [`on_exit`](StateMachine::on_exit) run whenever the state changes.
+//! - A state with a [`timeout`](StateMachine::timeout) receives
+//!   [`Event::Timeout`] once if it is not left in time. The timeout is
+//!   measured from entering the state, so messages handled with
+//!   [`Transition::Stay`] do not restart it.
+//! - The task stops with [`Transition::Stop`] or once its mailbox is
+//!   closed. [`on_exit`](StateMachine::on_exit) is called for the final
+//!   state in both cases.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use notizia::fsm::{self, Event, StateMachine, Transition};
+//!
+//! #[derive(Debug, Clone, PartialEq, Eq)]
+//! enum Door {
+//!     Locked,
+//!     Open,
+//! }
+//!
+//! enum DoorMsg {
+//!     Code(u32),
+//!     Close,
+//! }
+//!
+//! struct Lock {
+//!     code: u32,
+//! }
+//!
+//! impl StateMachine for Lock {
+//!     type State = Door;
+//!     type Message = DoorMsg;
+//!
+//!     fn initial_state(&self) -> Door {
+//!         Door::Locked
+//!     }
+//!
+//!     fn timeout(&self, state: &Door) -> Option<Duration> {
+//!         // Lock again automatically
+//!         (*state == Door::Open).then(|| Duration::from_secs(10))
+//!     }
+//!
+//!     async fn handle(&mut self, state: &Door, event: Event<DoorMsg>) -> Transition<Door> {
+//!         match (state, event) {
+//!             (Door::Locked, Event::Message(DoorMsg::Code(code))) if code == self.code => {
+//!                 Transition::Goto(Door::Open)
+//!             }
+//!             (Door::Open, Event::Message(DoorMsg::Close) | Event::Timeout) => {
+//!                 Transition::Goto(Door::Locked)
+//!             }
+//!             _ => Transition::Stay,
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let door = fsm::spawn(Lock { code: 1234 });
+//! door.send(DoorMsg::Code(1234)).unwrap();
+//! # }
+//! ```
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use futures::FutureExt;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::TerminateReason;
+use crate::core::errors::RecvError;
+use crate::core::lifecycle::panic_message;
+use crate::core::mailbox::Mailbox;
+use crate::runtime::{self, Instant};
+use crate::task::{TaskHandle, TaskId, TaskRef};
+
+/// An event handled by a [`StateMachine`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event<M> {
+    /// A message was received
+    Message(M),
+    /// The [timeout](StateMachine::timeout) of the current state expired
+    Timeout,
+}
+
+/// The outcome of handling an [`Event`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Transition<S> {
+    /// Remain in the current state
+    Stay,
+    /// Leave the current state for the given one
+    ///
+    /// Going to the current state leaves and enters it again, restarting
+    /// its timeout.
+    Goto(S),
+    /// Leave the current state and stop the task
+    Stop,
+}
+
+/// A task driven by a finite state machine.
+///
+/// See the [module documentation](self) for how events and transitions are
+/// processed.
+pub trait StateMachine: Send + Sized + 'static {
+    /// The states of the machine, usually an enum.
+    type State: Debug + Send + Sync + 'static;
+
+    /// Message type handled by the machine.
+    type Message: Send + 'static;
+
+    /// The state the machine starts in.
+    fn initial_state(&self) -> Self::State;
+
+    /// Handle an event in `state`, deciding on the next state.
+    fn handle(
+        &mut self,
+        state: &Self::State,
+        event: Event<Self::Message>,
+    ) -> impl Future<Output = Transition<Self::State>> + Send;
+
+    /// Time the machine may spend in `state` before it receives
+    /// [`Event::Timeout`].
+    ///
+    /// The default implementation returns `None`, i.e. states never time out.
+    fn timeout(&self, state: &Self::State) -> Option<Duration> {
+        let _ = state;
+        None
+    }
+
+    /// Hook called when `state` is entered, including the initial state.
+    ///
+    /// The default implementation does nothing.
+    fn on_enter(&mut self, state: &Self::State) -> impl Future<Output = ()> + Send {
+        let _ = state;
+        async {}
+    }
+
+    /// Hook called when `state` is left, including when the task stops.
+    ///
+    /// The default implementation does nothing.
+    fn on_exit(&mut self, state: &Self::State) -> impl Future<Output = ()> + Send {
+        let _ = state;
+        async {}
+    }
+}
+
+/// Spawn a task running `machine`.
+///
+/// The task is named after the machine's type.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime.
+pub fn spawn<S>(machine: S) -> TaskHandle<S::Message>
+where
+    S: StateMachine,
+{
+    let (sender, receiver) = unbounded_channel();
+    let mailbox = Mailbox::from_receiver(receiver);
+    let mb = mailbox.clone();
+
+    let name = std::any::type_name::<S>();
+    let handle = runtime::spawn(name, async move {
+        match AssertUnwindSafe(run(machine, &mb)).catch_unwind().await {
+            Ok(()) => TerminateReason::Normal,
+            Err(payload) => TerminateReason::Panic(panic_message(&*payload)),
+        }
+    });
+
+    let task = TaskRef::with_identity(sender, TaskId::next(), name);
+    TaskHandle::new(task, &mailbox, handle)
+}
+
+/// Drive `machine` with the messages of `mailbox` until it stops.
+async fn run<S>(mut machine: S, mailbox: &Mailbox<S::Message>)
+where
+    S: StateMachine,
+{
+    let mut state = machine.initial_state();
+    machine.on_enter(&state).await;
+    let mut deadline = machine
+        .timeout(&state)
+        .map(|timeout| Instant::now() + timeout);
+
+    loop {
+        let received = match deadline {
+            Some(deadline) => {
+                mailbox
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    .await
+            }
+            None => mailbox.recv().await,
+        };
+
+        let event = match received {
+            Ok(msg) => Event::Message(msg),
+            Err(RecvError::Timeout) => {
+                // A state times out once per visit
+                deadline = None;
+                Event::Timeout
+            }
+            Err(_) => break,
+        };
+
+        match machine.handle(&state, event).await {
+            Transition::Stay => {}
+            Transition::Goto(next) => {
+                machine.on_exit(&state).await;
+                state = next;
+                machine.on_enter(&state).await;
+                deadline = machine
+                    .timeout(&state)
+                    .map(|timeout| Instant::now() + timeout);
+            }
+            Transition::Stop => break,
+        }
+    }
+
+    machine.on_exit(&state).await;
+}
@@ -0,0 +1,453 @@
+//! Exposing tasks as gRPC services.
+//!
+//! A [`GrpcGateway`] maps the methods of a gRPC service onto the messages of
+//! a task, so clients in other languages can talk to it. Each method names
+//! the message its request becomes:
+//!
+//! - [unary](GrpcGateway::unary) methods are sent as requests with
+//!   [`call!`](crate::call!), and the reply is the response;
+//! - [server-streaming](GrpcGateway::server_streaming) methods are sent
+//!   with [`call_stream!`](crate::call_stream!), and the items of the
+//!   [`ReplyStream`](crate::ReplyStream) are streamed back;
+//! - [cast](GrpcGateway::cast) methods are sent without waiting for the
+//!   task, and answered with [`Empty`].
+//!
+//! Requests and responses are encoded as JSON with [`JsonCodec`] instead of
+//! protobuf, so no code generation is needed; gRPC clients send them with
+//! the `application/grpc+json` content type. Failed calls map to gRPC
+//! status codes, e.g. a timed out call to `DEADLINE_EXCEEDED`.
+//!
+//! The gateway turns into [`tonic::service::Routes`] to serve with tonic, or
+//! into an [`axum::Router`] to combine with other routes.
+//!
+//! This module requires the `grpc` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::grpc::GrpcGateway;
+//! use notizia::message;
+//! use notizia::prelude::*;
+//! use serde::Deserialize;
+//!
+//! #[message]
+//! enum CounterMsg {
+//!     Add { amount: u64 },
+//!     #[request(reply = u64)]
+//!     Get,
+//!     #[request(stream = u64)]
+//!     Watch,
+//! }
+//!
+//! #[derive(Deserialize)]
+//! struct AddRequest {
+//!     amount: u64,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = CounterMsg)]
+//! struct Counter;
+//!
+//! impl Runnable<CounterMsg> for Counter {
+//!     async fn start(&self) {
+//!         // ...
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let counter = spawn!(Counter);
+//!
+//! let routes = GrpcGateway::new("counter.Counter", counter.this())
+//!     .cast("Add", |req: AddRequest| CounterMsg::Add { amount: req.amount })
+//!     .unary("Get", |(): (), reply_to| CounterMsg::Get { reply_to })
+//!     .server_streaming("Watch", |(): (), reply_to| CounterMsg::Watch { reply_to })
+//!     .into_routes();
+//!
+//! // Serve with `tonic::transport::Server::builder().add_routes(routes)`
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io::Write;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::body::Body as AxumBody;
+use bytes::{Buf, BufMut};
+use futures::future::BoxFuture;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::server::{Grpc, ServerStreamingService, UnaryService};
+use tonic::{Request, Response, Status};
+
+use crate::core::errors::CallError;
+use crate::core::stream::StreamEnd;
+use crate::core::{DEFAULT_CALL_TIMEOUT, IntoTimeout, Reply, StreamReply};
+use crate::task::TaskRef;
+
+/// A tonic codec encoding messages as JSON.
+///
+/// `T` is the type of outgoing messages, `U` that of incoming ones: a
+/// server encodes responses and decodes requests, a client the other way
+/// around.
+#[derive(Debug)]
+pub struct JsonCodec<T, U>(PhantomData<fn(T) -> U>);
+
+impl<T, U> Default for JsonCodec<T, U> {
+    fn default() -> Self {
+        JsonCodec(PhantomData)
+    }
+}
+
+impl<T, U> Codec for JsonCodec<T, U>
+where
+    T: Serialize + Send + 'static,
+    U: DeserializeOwned + Send + 'static,
+{
+    type Encode = T;
+    type Decode = U;
+    type Encoder = JsonEncoder<T>;
+    type Decoder = JsonDecoder<U>;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        JsonEncoder(PhantomData)
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        JsonDecoder(PhantomData)
+    }
+}
+
+/// The encoder of a [`JsonCodec`].
+#[derive(Debug)]
+pub struct JsonEncoder<T>(PhantomData<fn(T)>);
+
+impl<T> Encoder for JsonEncoder<T>
+where
+    T: Serialize,
+{
+    type Item = T;
+    type Error = Status;
+
+    fn encode(&mut self, item: T, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        let mut writer = dst.writer();
+        serde_json::to_writer(&mut writer, &item)
+            .map_err(|err| Status::internal(format!("failed to encode message: {err}")))?;
+        writer
+            .flush()
+            .map_err(|err| Status::internal(err.to_string()))
+    }
+}
+
+/// The decoder of a [`JsonCodec`].
+#[derive(Debug)]
+pub struct JsonDecoder<U>(PhantomData<fn() -> U>);
+
+impl<U> Decoder for JsonDecoder<U>
+where
+    U: DeserializeOwned,
+{
+    type Item = U;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<U>, Status> {
+        if !src.has_remaining() {
+            return Ok(None);
+        }
+        serde_json::from_reader(src.reader())
+            .map(Some)
+            .map_err(|err| Status::invalid_argument(format!("failed to decode message: {err}")))
+    }
+}
+
+/// The response of [cast](GrpcGateway::cast) methods, encoded as `{}`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Empty {}
+
+/// Serves a gRPC method, given the task's call timeout and the HTTP request.
+type Method = Arc<
+    dyn Fn(
+            Duration,
+            http::Request<AxumBody>,
+        ) -> BoxFuture<'static, http::Response<tonic::body::Body>>
+        + Send
+        + Sync,
+>;
+
+/// A gRPC service forwarding its methods to a task.
+///
+/// See the [module documentation](self) for an example.
+pub struct GrpcGateway<M> {
+    service: String,
+    task: TaskRef<M>,
+    timeout: Duration,
+    methods: Vec<(String, Method)>,
+}
+
+impl<M> GrpcGateway<M>
+where
+    M: Send + 'static,
+{
+    /// Create a gateway serving the gRPC service `service`, e.g.
+    /// `"shop.Orders"`, from `task`.
+    pub fn new(service: impl Into<String>, task: TaskRef<M>) -> Self {
+        GrpcGateway {
+            service: service.into(),
+            task,
+            timeout: DEFAULT_CALL_TIMEOUT,
+            methods: Vec::new(),
+        }
+    }
+
+    /// Set the timeout of calls to the task.
+    ///
+    /// Defaults to [`DEFAULT_CALL_TIMEOUT`].
+    pub fn timeout(mut self, timeout: impl IntoTimeout) -> Self {
+        self.timeout = timeout.into_timeout();
+        self
+    }
+
+    /// Serve the unary method `method` by calling the task with the message
+    /// `make` creates from the request and the reply channel.
+    pub fn unary<Req, Res, F>(self, method: &str, make: F) -> Self
+    where
+        Req: DeserializeOwned + Send + 'static,
+        Res: Serialize + Send + 'static,
+        F: Fn(Req, Reply<Res>) -> M + Send + Sync + 'static,
+    {
+        let call = Call {
+            task: self.task.clone(),
+            make: Arc::new(make),
+            timeout: DEFAULT_CALL_TIMEOUT,
+            _reply: PhantomData,
+        };
+        self.method(method, move |timeout, req| {
+            let call = Call {
+                timeout,
+                ..call.clone()
+            };
+            Box::pin(async move {
+                Grpc::new(JsonCodec::<Res, Req>::default())
+                    .unary(call, req)
+                    .await
+            })
+        })
+    }
+
+    /// Serve the server-streaming method `method` by calling the task with
+    /// the message `make` creates from the request and the stream's reply
+    /// channel.
+    ///
+    /// A stream the task [fails](StreamReply::fail) ends with status
+    /// `ABORTED`, one it drops without finishing with `INTERNAL`.
+    pub fn server_streaming<Req, Item, F>(self, method: &str, make: F) -> Self
+    where
+        Req: DeserializeOwned + Send + 'static,
+        Item: Serialize + Send + 'static,
+        F: Fn(Req, StreamReply<Item>) -> M + Send + Sync + 'static,
+    {
+        let call = Call {
+            task: self.task.clone(),
+            make: Arc::new(make),
+            timeout: DEFAULT_CALL_TIMEOUT,
+            _reply: PhantomData,
+        };
+        self.method(method, move |_, req| {
+            let call = call.clone();
+            Box::pin(async move {
+                Grpc::new(JsonCodec::<Item, Req>::default())
+                    .server_streaming(call, req)
+                    .await
+            })
+        })
+    }
+
+    /// Serve the method `method` by sending the message `make` creates from
+    /// the request, answering with [`Empty`] once it is in the task's
+    /// mailbox.
+    pub fn cast<Req, F>(self, method: &str, make: F) -> Self
+    where
+        Req: DeserializeOwned + Send + 'static,
+        F: Fn(Req) -> M + Send + Sync + 'static,
+    {
+        let cast = Cast {
+            task: self.task.clone(),
+            make: Arc::new(make),
+        };
+        self.method(method, move |_, req| {
+            let cast = cast.clone();
+            Box::pin(async move {
+                Grpc::new(JsonCodec::<Empty, Req>::default())
+                    .unary(cast, req)
+                    .await
+            })
+        })
+    }
+
+    fn method<F>(mut self, method: &str, serve: F) -> Self
+    where
+        F: Fn(
+                Duration,
+                http::Request<AxumBody>,
+            ) -> BoxFuture<'static, http::Response<tonic::body::Body>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.methods.push((method.to_string(), Arc::new(serve)));
+        self
+    }
+
+    /// An axum router serving the methods under `/{service}/{method}`.
+    pub fn into_router(self) -> axum::Router {
+        let timeout = self.timeout;
+        self.methods
+            .into_iter()
+            .fold(axum::Router::new(), |router, (method, serve)| {
+                let path = format!("/{}/{method}", self.service);
+                router.route(
+                    &path,
+                    axum::routing::post(move |req: http::Request<AxumBody>| serve(timeout, req)),
+                )
+            })
+    }
+
+    /// Routes to serve the service with tonic, e.g. with
+    /// `tonic::transport::Server::builder().add_routes(routes)`.
+    pub fn into_routes(self) -> tonic::service::Routes {
+        tonic::service::Routes::from(self.into_router())
+    }
+}
+
+/// Calls the task for a method replying with `R`, or streaming `R`s.
+struct Call<M, F, R> {
+    task: TaskRef<M>,
+    make: Arc<F>,
+    timeout: Duration,
+    _reply: PhantomData<fn() -> R>,
+}
+
+impl<M, F, R> Clone for Call<M, F, R> {
+    fn clone(&self) -> Self {
+        Call {
+            task: self.task.clone(),
+            make: self.make.clone(),
+            timeout: self.timeout,
+            _reply: PhantomData,
+        }
+    }
+}
+
+impl<M, Req, Res, F> UnaryService<Req> for Call<M, F, Res>
+where
+    M: Send + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+    F: Fn(Req, Reply<Res>) -> M + Send + Sync + 'static,
+{
+    type Response = Res;
+    type Future = BoxFuture<'static, Result<Response<Res>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let Call {
+            task,
+            make,
+            timeout,
+            ..
+        } = self.clone();
+        let req = request.into_inner();
+
+        Box::pin(async move {
+            crate::call!(task, |reply_to| make(req, reply_to), timeout = timeout)
+                .await
+                .map(Response::new)
+                .map_err(status)
+        })
+    }
+}
+
+impl<M, Req, Item, F> ServerStreamingService<Req> for Call<M, F, Item>
+where
+    M: Send + 'static,
+    Req: Send + 'static,
+    Item: Send + 'static,
+    F: Fn(Req, StreamReply<Item>) -> M + Send + Sync + 'static,
+{
+    type Response = Item;
+    type ResponseStream = BoxStream<'static, Result<Item, Status>>;
+    type Future = BoxFuture<'static, Result<Response<Self::ResponseStream>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let req = request.into_inner();
+        let items = crate::call_stream!(self.task, |reply_to| (self.make)(req, reply_to));
+
+        Box::pin(async move {
+            let items = items.map_err(status)?;
+            let items = stream::unfold(Some(items), |items| async move {
+                let mut items = items?;
+                match items.next().await {
+                    Some(item) => Some((Ok(item), Some(items))),
+                    None => match items.end() {
+                        Some(StreamEnd::Failed(reason)) => {
+                            Some((Err(Status::aborted(reason.clone())), None))
+                        }
+                        Some(StreamEnd::Dropped) => Some((
+                            Err(Status::internal("stream dropped without finishing")),
+                            None,
+                        )),
+                        _ => None,
+                    },
+                }
+            });
+            Ok(Response::new(items.boxed()))
+        })
+    }
+}
+
+/// Sends the messages of a cast method to the task.
+struct Cast<M, F> {
+    task: TaskRef<M>,
+    make: Arc<F>,
+}
+
+impl<M, F> Clone for Cast<M, F> {
+    fn clone(&self) -> Self {
+        Cast {
+            task: self.task.clone(),
+            make: self.make.clone(),
+        }
+    }
+}
+
+impl<M, Req, F> UnaryService<Req> for Cast<M, F>
+where
+    M: Send + 'static,
+    Req: Send + 'static,
+    F: Fn(Req) -> M + Send + Sync + 'static,
+{
+    type Response = Empty;
+    type Future = BoxFuture<'static, Result<Response<Empty>, Status>>;
+
+    fn call(&mut self, request: Request<Req>) -> Self::Future {
+        let sent = self.task.send((self.make)(request.into_inner()));
+        let result = sent
+            .map(|()| Response::new(Empty {}))
+            .map_err(|_| Status::unavailable("task is not running"));
+        Box::pin(async move { result })
+    }
+}
+
+/// The gRPC status of a failed call.
+fn status(err: CallError) -> Status {
+    match err {
+        CallError::Timeout { .. } => Status::deadline_exceeded(err.to_string()),
+        CallError::SendError { .. } | CallError::CircuitOpen => {
+            Status::unavailable(err.to_string())
+        }
+        _ => Status::internal(err.to_string()),
+    }
+}
@@ -0,0 +1,312 @@
+//! Built-in inspection of running tasks.
+//!
+//! With the `inspector` feature enabled, every task spawned through
+//! `#[derive(Task)]` is recorded in a process-wide directory while it runs.
+//! Spawning an [`InspectorTask`] gives an application a control surface over
+//! that directory: it answers the standard [`InspectorMsg`] protocol to list
+//! tasks, inspect or kill a single task, and dump mailbox depths.
+//!
+//! For tasks declared with `#[task(message = M, handler)]`, the generated
+//! dispatch also times every call to [`Handler::handle`]. The
+//! [`HandlerStats`](InspectorMsg::HandlerStats) request reports latency
+//! percentiles over the last [`LATENCY_SAMPLES`] messages and the throughput
+//! averaged over the last [`THROUGHPUT_WINDOW`].
+//!
+//! This module requires the `inspector` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::call;
+//! use notizia::inspector::{InspectorMsg, InspectorTask};
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let inspector = spawn!(InspectorTask);
+//!
+//! for task in call!(inspector, InspectorMsg::ListTasks).await.unwrap() {
+//!     println!("{} {} ({} queued)", task.id, task.name, task.mailbox_depth);
+//! }
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use crate::core::mailbox::Mailbox;
+use crate::core::reply::Reply;
+use crate::runtime::{AbortHandle, Instant};
+use crate::task::{Context, Handler, TaskId};
+
+/// Number of handler durations latency percentiles are computed from.
+pub const LATENCY_SAMPLES: usize = 1024;
+
+/// Time span handler throughput is averaged over.
+pub const THROUGHPUT_WINDOW: Duration = Duration::from_secs(10);
+
+/// Throughput is counted in one bucket per second of the window.
+const BUCKETS: usize = THROUGHPUT_WINDOW.as_secs() as usize;
+
+/// A running task, as recorded in the directory.
+struct Entry {
+    name: &'static str,
+    spawned: Instant,
+    abort: Option<AbortHandle>,
+    depth: Box<dyn Fn() -> usize + Send>,
+    /// Timings of a handler task, attached once its context is created
+    samples: Option<Arc<Mutex<Samples>>>,
+}
+
+impl Entry {
+    fn info(&self, id: TaskId) -> TaskInfo {
+        TaskInfo {
+            id,
+            name: self.name,
+            mailbox_depth: (self.depth)(),
+            uptime: self.spawned.elapsed(),
+        }
+    }
+}
+
+static DIRECTORY: LazyLock<Mutex<BTreeMap<TaskId, Entry>>> = LazyLock::new(Default::default);
+
+/// Record a task that is about to be spawned.
+pub(crate) fn enter<T>(id: TaskId, name: &'static str, mailbox: &Mailbox<T>)
+where
+    T: Send + 'static,
+{
+    let mailbox = mailbox.clone();
+    DIRECTORY.lock().unwrap().insert(
+        id,
+        Entry {
+            name,
+            spawned: Instant::now(),
+            abort: None,
+            depth: Box::new(move || mailbox.depth()),
+            samples: None,
+        },
+    );
+}
+
+/// Timings of the handler task `id`, recorded in the directory if it is
+/// running.
+pub(crate) fn samples(id: TaskId) -> Arc<Mutex<Samples>> {
+    let samples = Arc::new(Mutex::new(Samples::new()));
+    if let Some(entry) = DIRECTORY.lock().unwrap().get_mut(&id) {
+        entry.samples = Some(samples.clone());
+    }
+    samples
+}
+
+/// Attach the abort handle of a spawned task, unless it already finished.
+pub(crate) fn attach(id: TaskId, abort: AbortHandle) {
+    if let Some(entry) = DIRECTORY.lock().unwrap().get_mut(&id) {
+        entry.abort = Some(abort);
+    }
+}
+
+/// Removes a task from the directory when dropped, i.e. when its future
+/// completes or is aborted.
+pub(crate) struct Exit(pub(crate) TaskId);
+
+impl Drop for Exit {
+    fn drop(&mut self) {
+        // Never panic while the task is being dropped
+        if let Ok(mut directory) = DIRECTORY.lock() {
+            directory.remove(&self.0);
+        }
+    }
+}
+
+/// Handler durations and throughput of a task.
+pub(crate) struct Samples {
+    handled: u64,
+    /// The most recent durations, oldest first
+    durations: VecDeque<Duration>,
+    /// Start of the first throughput bucket
+    since: Instant,
+    /// Messages finished per second, indexed by second modulo `BUCKETS`
+    buckets: [u64; BUCKETS],
+    /// Second of the most recently filled bucket
+    current: u64,
+}
+
+impl Samples {
+    fn new() -> Self {
+        Samples {
+            handled: 0,
+            durations: VecDeque::with_capacity(LATENCY_SAMPLES),
+            since: Instant::now(),
+            buckets: [0; BUCKETS],
+            current: 0,
+        }
+    }
+
+    /// Record a message the handler spent `elapsed` on.
+    pub(crate) fn record(&mut self, elapsed: Duration) {
+        if self.durations.len() == LATENCY_SAMPLES {
+            self.durations.pop_front();
+        }
+        self.durations.push_back(elapsed);
+        self.handled += 1;
+
+        let second = self.since.elapsed().as_secs();
+        // Clear the buckets of the seconds without messages
+        for skipped in (self.current + 1..=second).take(BUCKETS) {
+            self.buckets[skipped as usize % BUCKETS] = 0;
+        }
+        self.current = self.current.max(second);
+        self.buckets[second as usize % BUCKETS] += 1;
+    }
+
+    fn stats(&self) -> HandlerStats {
+        let mut sorted: Vec<_> = self.durations.iter().copied().collect();
+        sorted.sort_unstable();
+        // Nearest-rank percentile
+        let percentile = |p: usize| match sorted.len() {
+            0 => Duration::ZERO,
+            n => sorted[(n * p).div_ceil(100).max(1) - 1],
+        };
+
+        let elapsed = self.since.elapsed();
+        let second = elapsed.as_secs();
+        let first = (second + 1).saturating_sub(BUCKETS as u64);
+        let finished: u64 = (first..=second.min(self.current))
+            .map(|second| self.buckets[second as usize % BUCKETS])
+            .sum();
+        let window = elapsed - Duration::from_secs(first);
+
+        HandlerStats {
+            handled: self.handled,
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+            per_second: match window.is_zero() {
+                true => 0.0,
+                false => finished as f64 / window.as_secs_f64(),
+            },
+        }
+    }
+}
+
+/// Latency and throughput of a handler task.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HandlerStats {
+    /// Messages handled since the task was spawned
+    pub handled: u64,
+    /// Median time spent in [`Handler::handle`]
+    pub p50: Duration,
+    /// 95th percentile of the time spent in [`Handler::handle`]
+    pub p95: Duration,
+    /// 99th percentile of the time spent in [`Handler::handle`]
+    pub p99: Duration,
+    /// Messages handled per second, averaged over the
+    /// [`THROUGHPUT_WINDOW`]
+    pub per_second: f64,
+}
+
+/// A snapshot of a running task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskInfo {
+    /// Identifier of the task
+    pub id: TaskId,
+    /// Name of the task
+    pub name: &'static str,
+    /// Approximate number of messages waiting in the task's mailbox
+    pub mailbox_depth: usize,
+    /// Time since the task was spawned
+    pub uptime: Duration,
+}
+
+/// Requests answered by an [`InspectorTask`].
+///
+/// Like messages defined with `#[message]`, requests carry a `reply_to`
+/// field and are sent with [`call!`](crate::call!).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum InspectorMsg {
+    /// List all running tasks, ordered by id
+    ListTasks { reply_to: Reply<Vec<TaskInfo>> },
+    /// Describe the task with the given id, if it is running
+    TaskDetail {
+        id: TaskId,
+        reply_to: Reply<Option<TaskInfo>>,
+    },
+    /// Abort the task with the given id
+    ///
+    /// Replies whether a running task was found. Like
+    /// [`TaskHandle::kill`](crate::TaskHandle::kill), this does not give the
+    /// task a chance to clean up.
+    KillTask { id: TaskId, reply_to: Reply<bool> },
+    /// Mailbox depths of all running tasks, ordered by id
+    DumpMailboxDepths {
+        reply_to: Reply<Vec<(TaskId, usize)>>,
+    },
+    /// Handler latency and throughput of the task with the given id
+    ///
+    /// Replies `None` unless the task is running and declared with
+    /// `#[task(message = M, handler)]`.
+    HandlerStats {
+        id: TaskId,
+        reply_to: Reply<Option<HandlerStats>>,
+    },
+}
+
+/// A task answering [`InspectorMsg`] requests about all running tasks.
+///
+/// See the [module documentation](self) for an example.
+#[derive(crate::Task, Debug, Default)]
+#[task(message = InspectorMsg, handler)]
+pub struct InspectorTask;
+
+impl Handler<InspectorMsg> for InspectorTask {
+    async fn handle(&mut self, msg: InspectorMsg, _ctx: &mut Context<InspectorMsg>) {
+        // Callers that gave up waiting are of no concern
+        match msg {
+            InspectorMsg::ListTasks { reply_to } => {
+                let tasks = DIRECTORY
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(id, entry)| entry.info(*id))
+                    .collect();
+                let _ = reply_to.reply(tasks);
+            }
+            InspectorMsg::TaskDetail { id, reply_to } => {
+                let task = DIRECTORY
+                    .lock()
+                    .unwrap()
+                    .get(&id)
+                    .map(|entry| entry.info(id));
+                let _ = reply_to.reply(task);
+            }
+            InspectorMsg::KillTask { id, reply_to } => {
+                let abort = DIRECTORY
+                    .lock()
+                    .unwrap()
+                    .get(&id)
+                    .and_then(|entry| entry.abort.clone());
+                let _ = reply_to.reply(abort.inspect(AbortHandle::abort).is_some());
+            }
+            InspectorMsg::DumpMailboxDepths { reply_to } => {
+                let depths = DIRECTORY
+                    .lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(id, entry)| (*id, (entry.depth)()))
+                    .collect();
+                let _ = reply_to.reply(depths);
+            }
+            InspectorMsg::HandlerStats { id, reply_to } => {
+                let samples = DIRECTORY
+                    .lock()
+                    .unwrap()
+                    .get(&id)
+                    .and_then(|entry| entry.samples.clone());
+                let _ = reply_to.reply(samples.map(|samples| samples.lock().unwrap().stats()));
+            }
+        }
+    }
+}
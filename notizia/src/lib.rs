@@ -70,8 +70,7 @@
 //! A **task** is an independent unit of work that processes messages. Tasks are defined
 //! by deriving the [`Task`] trait and implementing [`Runnable`]:
 //!
-//! ```rust,ignore
-//! # TODO: Re-enable once derive macro hygiene is fixed
+//! ```rust
 //! # use notizia::prelude::*;
 //! # #[derive(Clone)] enum MyMessage {}
 //! #[derive(Task)]
@@ -156,8 +155,7 @@
 //!
 //! ### Pattern 1: Unwrap for Prototypes
 //!
-//! ```rust,ignore
-//! # TODO: Re-enable once derive macro hygiene is fixed
+//! ```rust
 //! # use notizia::prelude::*;
 //! # #[derive(Clone)] enum Signal {}
 //! # #[derive(Task)]
@@ -175,8 +173,7 @@
 //!
 //! ### Pattern 2: Error Propagation with `?`
 //!
-//! ```rust,ignore
-//! # TODO: Re-enable once derive macro hygiene is fixed
+//! ```rust
 //! # use notizia::prelude::*;
 //! # use notizia::core::errors::RecvError;
 //! # #[derive(Clone)] enum Signal {}
@@ -200,8 +197,7 @@
 //!
 //! ### Pattern 3: Explicit Handling
 //!
-//! ```rust,ignore
-//! # TODO: Re-enable once derive macro hygiene is fixed
+//! ```rust
 //! # use notizia::prelude::*;
 //! # #[derive(Clone)] enum Signal {}
 //! # #[derive(Task)]
@@ -261,8 +257,32 @@
 //! ```
 //!
 //! The `#[request(reply = T)]` attribute automatically adds a
-//! `reply_to: tokio::sync::oneshot::Sender<T>` field to the variant,
-//! reducing boilerplate and making the intent clearer.
+//! `reply_to: notizia::Reply<T>` field to the variant,
+//! reducing boilerplate and making the intent clearer. The task answers with
+//! [`Reply::reply`]; a [`Reply`] dropped without an answer prints a warning
+//! instead of silently leaving the caller waiting.
+//!
+//! Requests answered with many items over time, such as progress updates or
+//! pages, are declared with `#[request(stream = T)]`. They carry a
+//! [`StreamReply`] and are sent with [`call_stream!`](crate::call_stream!),
+//! which returns a [`ReplyStream`] of the items.
+//!
+//! For enums with request variants, `#[message]` also generates a
+//! `{Enum}Calls` extension trait, implemented for [`TaskHandle`] and
+//! [`TaskRef`], with one method per request variant. The variant's fields
+//! become the method's parameters, so `handle.get_count().await?` and
+//! `handle.get_stats_with_timeout(Duration::from_secs(1)).await?` replace
+//! hand-written `call!`s.
+//!
+//! With `#[message(client = CounterClient)]`, the macro also generates a
+//! `CounterClient` struct wrapping a [`TaskRef`], with one method per variant:
+//! casts return a [`SendResult`], requests return a future of the reply. The
+//! client can be passed around as the task's API instead of a raw reference.
+//!
+//! Since request variants hold a [`Reply`], the message enum cannot derive
+//! `Clone`. `#[message(casts = CounterCast)]` generates a cloneable
+//! `CounterCast` enum with only the cast variants, convertible into the
+//! message enum with `.into()`, for messages that are broadcast to many tasks.
 //!
 //! ## Request-Response Patterns
 //!
@@ -329,6 +349,14 @@
 //! # }
 //! ```
 //!
+//! Transient failures can be retried with [`call_with_retry!`](crate::call_with_retry!)
+//! and a [`RetryPolicy`](crate::core::RetryPolicy):
+//!
+//! ```rust,ignore
+//! let policy = RetryPolicy::new(3).backoff(Duration::from_millis(100));
+//! let status = call_with_retry!(handle, Msg::GetStatus, retry = policy).await?;
+//! ```
+//!
 //! ### Asynchronous: `cast!`
 //!
 //! Use [`cast!`](crate::cast!) for fire-and-forget messages that don't require a response.
@@ -353,9 +381,32 @@
 //! # }
 //! ```
 //!
+//! [`cast_all!`](crate::cast_all!) sends a clone of one message to every
+//! task in a collection and reports the result per task. Large payloads
+//! wrapped in an [`ArcMessage`](crate::core::ArcMessage) are shared between
+//! the receivers instead of being copied for each of them.
+//!
+//! Producers feeding a task with a [bounded](crate::core::bounded) mailbox
+//! can use [`feed!`](crate::feed!) instead, which waits for room rather than
+//! failing, so they slow down to the pace of the task:
+//!
+//! ```rust,ignore
+//! feed!(handle, Chunk(bytes)).await?;
+//! ```
+//!
+//! Critical commands can be sent with [`send_reliable!`](crate::send_reliable!),
+//! which waits until the task has processed the message, sends it again if
+//! it was lost, and puts it into the [dead-letter queue](crate::core::dead_letter)
+//! once the attempts are used up:
+//!
+//! ```rust,ignore
+//! send_reliable!(handle, Command::Charge(100), retry = policy).await?;
+//! ```
+//!
 //! ### Handling Call Messages
 //!
-//! Tasks respond to call messages by sending a value through the oneshot channel:
+//! Tasks respond to call messages by sending a value through the oneshot channel
+//! (or through [`Reply::reply`] for variants declared with `#[request]`):
 //!
 //! ```rust,ignore
 //! # use notizia::prelude::*;
@@ -395,24 +446,69 @@
 //!
 //! - [`core`] - Core types (mailbox, errors, internal state)
 //! - [`task`] - Task traits and handles
+//! - [`config`] - Loading configuration and publishing its changes
+//! - [`fsm`] - Tasks driven by a finite state machine
+//! - `inspector` - Built-in inspection of running tasks (requires the `inspector` feature)
+//! - `metrics` - Per-task metrics (requires the `metrics` feature)
+//! - [`pipeline`] - Staged processing pipelines
+//! - `record` - Recording and replaying message traces (requires the `record` feature)
+//! - [`registry`] - Named task registry
+//! - `remote` - Messaging between processes over TCP (requires the `remote` feature)
+//! - [`session`] - Bidirectional sessions between two tasks
+//! - `scheduler` - Cron-style scheduling of messages (requires the `scheduler` feature)
+//! - [`sharding`] - One task per entity key
+//! - `testing` - Utilities for testing tasks (requires the `testing` feature)
+//! - [`virtual_actors`] - Grain-style actors activated on demand
 //! - [`prelude`] - Common imports for convenience
 //!
 //! ## Re-exports
 //!
 //! Notizia re-exports key types at the crate root for convenience:
 
+// Lets the derive macros, which refer to `::notizia`, be used in this crate
+extern crate self as notizia;
+
+#[cfg(feature = "axum")]
+pub mod axum;
+pub mod config;
 pub mod core;
+pub mod fsm;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+#[cfg(feature = "inspector")]
+pub mod inspector;
 #[doc(hidden)]
 pub mod macros;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod net;
+#[cfg(feature = "persistence")]
+pub mod persistence;
+pub mod pipeline;
 pub mod prelude;
+#[cfg(feature = "record")]
+pub mod record;
+pub mod registry;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod runtime;
+#[cfg(feature = "scheduler")]
+pub mod scheduler;
+pub mod session;
+pub mod sharding;
+pub mod system;
 pub mod task;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod virtual_actors;
 
 // Re-export core types at crate root
-pub use crate::core::Mailbox;
 pub use crate::core::errors::{CallError, CallResult, RecvError, RecvResult, SendResult};
+pub use crate::core::{CorrelationId, Mailbox, Reply, ReplyStream, StreamEnd, StreamReply};
 
 // Re-export task types at crate root
-pub use crate::task::{Runnable, Task, TaskHandle, TaskRef};
+pub use crate::task::{Runnable, Task, TaskHandle, TaskId, TaskRef, TaskSet};
 
 // Re-export lifecycle types at crate root
 pub use crate::core::lifecycle::{ShutdownError, ShutdownResult, TerminateReason};
@@ -430,6 +526,10 @@ pub use notizia_gen::Task;
 #[doc(inline)]
 pub use notizia_gen::message;
 
+// Re-export handlers macro from notizia_gen
+#[doc(inline)]
+pub use notizia_gen::handlers;
+
 // Re-export Tokio for macro usage (hidden from docs)
 #[doc(hidden)]
 pub use tokio;
@@ -437,6 +537,11 @@ pub use tokio;
 #[doc(hidden)]
 pub use futures;
 
+// Re-export serde for the derives of `#[message(serde)]` (hidden from docs)
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub use serde;
+
 // Internal types (hidden from docs)
 #[doc(hidden)]
 pub use crate::core::state::TaskState;
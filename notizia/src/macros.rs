@@ -3,7 +3,15 @@
 //! This module provides ergonomic macros for common task operations:
 //! - [`spawn!`] - Spawn a task
 //! - [`send!`] / [`cast!`] - Send a message to a task (fire-and-forget)
+//! - [`feed!`] - Send a message, waiting for room in a bounded mailbox
+//! - [`cast_all!`] - Send the same message to many tasks
 //! - [`call!`] - Call a task and wait for response (request-response)
+//! - [`call_detached!`] - Send a request now, await its response later
+//! - [`call_hedged!`] - Call replicas in turn until one answers
+//! - [`call_with_retry!`] - Call a task, retrying transient failures
+//! - [`scatter!`] - Call many tasks and gather their responses
+//! - [`reply!`] - Answer a request or the sender of a message
+//! - [`forward!`] - Pass on a request, leaving the answer to another task
 //! - [`recv!`] - Receive a message (must be awaited)
 //!
 //! These macros are provided for convenience and consistency with the
@@ -42,7 +50,46 @@
 #[macro_export]
 macro_rules! spawn {
     ($ident:ident) => {
-        $ident.run()
+        $crate::Task::run($ident)
+    };
+}
+
+/// Spawn a task whose `start()` must finish within a time budget.
+///
+/// This is shorthand for
+/// [`SpawnBuilder::deadline`](crate::task::SpawnBuilder::deadline): if
+/// `start()` is still running when the deadline passes, it is aborted and
+/// `terminate()` is called with
+/// [`TerminateReason::DeadlineExceeded`](crate::TerminateReason::DeadlineExceeded).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::spawn_with_deadline;
+/// # use std::time::Duration;
+/// # #[derive(Task)]
+/// # #[task(message = Signal)]
+/// # struct Batch;
+/// # impl Runnable<Signal> for Batch {
+/// #     async fn start(&self) {}
+/// # }
+/// # #[derive(Clone)]
+/// # enum Signal {}
+/// # #[tokio::main]
+/// # async fn main() {
+/// let handle = spawn_with_deadline!(Batch, Duration::from_secs(60));
+///
+/// // Equivalent to:
+/// // let handle = Batch.builder().deadline(Duration::from_secs(60)).spawn();
+/// # }
+/// ```
+#[macro_export]
+macro_rules! spawn_with_deadline {
+    ($task:expr, $deadline:expr) => {
+        $crate::task::Task::builder($task)
+            .deadline($deadline)
+            .spawn()
     };
 }
 
@@ -81,6 +128,63 @@ macro_rules! send {
         $task.send($msg)
     };
 }
+/// Send a message to a task, waiting for room in its mailbox.
+///
+/// This macro is a convenient wrapper around the `feed()` method on
+/// [`TaskHandle`](crate::task::TaskHandle) or [`TaskRef`](crate::task::TaskRef)
+/// and must be awaited. Where [`send!`] fails or discards a message sent to a
+/// full [bounded](crate::core::bounded) mailbox, `feed!` waits until the task
+/// has made room, so producers slow down to the pace of the task. For
+/// unbounded mailboxes, it sends right away.
+///
+/// Returns a [`SendResult`](crate::core::errors::SendResult).
+///
+/// # Interaction with `call!`
+///
+/// [`call!`] never waits for room: a request sent to a full mailbox fails
+/// right away (or is dropped, depending on the
+/// [`Overflow`](crate::core::Overflow) policy), and its timeout or deadline
+/// only covers the wait for the reply. Conversely, `feed!` waits without a
+/// limit. When requests and fed messages share a bounded mailbox, requests
+/// compete with producers for slots; give requests their own
+/// [control mailbox](crate::task::control) or bound the wait of `feed!`
+/// with a timeout, as in the example.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::core::bounded;
+/// # use notizia::feed;
+/// # use std::time::Duration;
+/// # #[derive(Task)]
+/// # #[task(message = Chunk)]
+/// # struct Writer;
+/// # impl Runnable<Chunk> for Writer {
+/// #     async fn start(&self) {}
+/// # }
+/// # struct Chunk(Vec<u8>);
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let handle = Writer.builder().mailbox(bounded(16)).spawn();
+///
+/// for chunk in [b"hello".to_vec(), b"world".to_vec()] {
+///     // Waits while 16 chunks are queued
+///     feed!(handle, Chunk(chunk)).await?;
+/// }
+///
+/// // Give up if the writer cannot catch up within a second
+/// tokio::time::timeout(Duration::from_secs(1), feed!(handle, Chunk(Vec::new()))).await??;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! feed {
+    ($task:expr, $msg:expr) => {
+        $task.feed($msg)
+    };
+}
+
 /// Call a task and wait for synchronous response with timeout.
 ///
 /// This macro performs a synchronous request-response interaction with a task,
@@ -90,13 +194,26 @@ macro_rules! send {
 /// # Timeout
 ///
 /// The timeout parameter is optional and defaults to 5000ms (5 seconds).
-/// Specify a custom timeout with `timeout = <millis>`.
+/// Specify a custom timeout with `timeout = <duration>`, either as a
+/// [`Duration`](std::time::Duration) or as an integer number of milliseconds.
+/// Alternatively, `deadline = <instant>` waits for the reply until an
+/// absolute [`Instant`](tokio::time::Instant), which is useful when several
+/// calls share one time budget.
 ///
 /// # Errors
 ///
 /// Returns [`CallError::Timeout`] if no response within deadline.
-/// Returns [`CallError::ChannelClosed`] if task drops reply channel.
-/// Returns [`CallError::SendError`] if task mailbox is closed.
+/// Returns [`CallError::NoReply`] if the task drops the [`Reply`](crate::Reply)
+/// without answering, and [`CallError::ChannelClosed`] if it drops a raw
+/// oneshot sender.
+/// Returns [`CallError::SendError`] if task mailbox is closed; the request
+/// can be recovered with [`CallError::into_message`].
+/// Returns [`CallError::WouldDeadlock`] without sending anything if a task
+/// calls itself, since it could never answer while waiting.
+///
+/// The request is sent like with [`send!`], so it is not queued if the
+/// task's [bounded](crate::core::bounded) mailbox is full; see
+/// [`feed!`](crate::feed!#interaction-with-call) for waiting on room.
 ///
 /// # Example
 ///
@@ -104,6 +221,7 @@ macro_rules! send {
 /// # use notizia::prelude::*;
 /// # use notizia::{call, message};
 /// # use tokio::sync::oneshot;
+/// # use tokio::time::{Duration, Instant};
 /// # #[message]
 /// # #[derive(Debug)]
 /// # enum Msg {
@@ -123,8 +241,16 @@ macro_rules! send {
 /// let status = call!(handle, Msg::GetStatus).await?;
 ///
 /// // With custom timeout (1 second)
+/// let status = call!(handle, Msg::GetStatus, timeout = Duration::from_secs(1)).await?;
+///
+/// // Integer timeouts are milliseconds
 /// let status = call!(handle, Msg::GetStatus, timeout = 1000).await?;
 ///
+/// // Share one deadline between calls
+/// let deadline = Instant::now() + Duration::from_secs(2);
+/// let first = call!(handle, Msg::GetStatus, deadline = deadline).await?;
+/// let second = call!(handle, Msg::GetStatus, deadline = deadline).await?;
+///
 /// // For variants with additional data, use closure syntax:
 /// // call!(handle, |tx| Msg::Echo { id: 42, reply_to: tx }).await?;
 /// # Ok(())
@@ -135,36 +261,616 @@ macro_rules! call {
     // e.g., call!(handle, |tx| Msg::Echo { id: 42, reply_to: tx }, timeout = 1000)
     ($task:expr, |$tx:ident| $msg:expr, timeout = $timeout:expr) => {{
         async {
-            let ($tx, rx) = $crate::tokio::sync::oneshot::channel();
+            let __notizia_task = &$task;
+            $crate::core::errors::CallError::check_self_call(__notizia_task.id(), __notizia_task.name())?;
+            let correlation = $crate::core::CorrelationId::current_or_next();
+            let ($tx, rx) = $crate::core::reply::channel(correlation);
             let msg = $msg;
-            $task
-                .send(msg)
-                .map_err(|_| $crate::core::errors::CallError::SendError)?;
+            let started = $crate::runtime::Instant::now();
+            __notizia_task.send(msg).map_err(|err| {
+                $crate::core::errors::CallError::send_failed(
+                    __notizia_task.id(),
+                    __notizia_task.name(),
+                    err,
+                )
+            })?;
 
-            $crate::tokio::time::timeout(std::time::Duration::from_millis($timeout), rx)
+            $crate::runtime::timeout($crate::core::IntoTimeout::into_timeout($timeout), rx)
                 .await
-                .map_err(|_| $crate::core::errors::CallError::Timeout)?
-                .map_err(|_| $crate::core::errors::CallError::ChannelClosed)
+                .map_err(|_| {
+                    $crate::core::errors::CallError::timed_out(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                        correlation,
+                        started,
+                    )
+                })?
         }
     }};
 
-    // Pattern 2: Closure syntax without timeout
+    // Pattern 2: Closure syntax with deadline
+    // e.g., call!(handle, |tx| Msg::Echo { id: 42, reply_to: tx }, deadline = at)
+    ($task:expr, |$tx:ident| $msg:expr, deadline = $deadline:expr) => {{
+        async {
+            let __notizia_task = &$task;
+            $crate::core::errors::CallError::check_self_call(__notizia_task.id(), __notizia_task.name())?;
+            let correlation = $crate::core::CorrelationId::current_or_next();
+            let ($tx, rx) = $crate::core::reply::channel(correlation);
+            let msg = $msg;
+            let started = $crate::runtime::Instant::now();
+            __notizia_task.send(msg).map_err(|err| {
+                $crate::core::errors::CallError::send_failed(
+                    __notizia_task.id(),
+                    __notizia_task.name(),
+                    err,
+                )
+            })?;
+
+            $crate::runtime::timeout_at($crate::core::IntoDeadline::into_deadline($deadline), rx)
+                .await
+                .map_err(|_| {
+                    $crate::core::errors::CallError::timed_out(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                        correlation,
+                        started,
+                    )
+                })?
+        }
+    }};
+
+    // Pattern 3: Closure syntax without timeout
     // e.g., call!(handle, |tx| Msg::Echo { id: 42, reply_to: tx })
     ($task:expr, |$tx:ident| $msg:expr) => {
-        call!($task, |$tx| $msg, timeout = 5000)
+        $crate::call!($task, |$tx| $msg, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
     };
 
-    // Pattern 3: Simple variant path with timeout (new ergonomic syntax)
+    // Pattern 4: Simple variant path with timeout (new ergonomic syntax)
     // Match using token trees to detect :: pattern
     // e.g., call!(handle, CounterMsg::GetStatus, timeout = 1000)
     ($task:expr, $first:ident :: $($rest:tt)::+, timeout = $timeout:expr) => {
-        call!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, timeout = $timeout)
+        $crate::call!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, timeout = $timeout)
     };
 
-    // Pattern 4: Simple variant path without timeout
+    // Pattern 5: Simple variant path with deadline
+    // e.g., call!(handle, CounterMsg::GetStatus, deadline = at)
+    ($task:expr, $first:ident :: $($rest:tt)::+, deadline = $deadline:expr) => {
+        $crate::call!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, deadline = $deadline)
+    };
+
+    // Pattern 6: Simple variant path without timeout
     // e.g., call!(handle, CounterMsg::GetStatus)
     ($task:expr, $first:ident :: $($rest:tt)::+) => {
-        call!($task, $first :: $($rest)::+, timeout = 5000)
+        $crate::call!($task, $first :: $($rest)::+, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+}
+
+/// Send a request to a task now and await its response later.
+///
+/// Takes the same arguments as [`call!`], but sends the request immediately
+/// instead of when awaited, and returns a
+/// [`CallFuture`](crate::core::CallFuture) resolving to the response. The
+/// future can be stored, raced against other work or
+/// [canceled](crate::core::CallFuture::cancel); the timeout runs from the
+/// moment the request was sent.
+///
+/// # Errors
+///
+/// The future resolves to the same errors as [`call!`]. Errors raised while
+/// sending, such as [`CallError::SendError`](crate::CallError::SendError),
+/// are returned when it is awaited.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{call_detached, message};
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = u32)]
+/// #     GetLoad,
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Worker;
+/// # impl Runnable<Msg> for Worker { async fn start(&self) {} }
+/// # async fn index_files() {}
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// # let handle = spawn!(Worker);
+/// // The worker starts on the request while we index
+/// let load = call_detached!(handle, Msg::GetLoad, timeout = 1000);
+/// index_files().await;
+/// let load = load.await?;
+///
+/// // Give up on the answer if the user quits first
+/// let (quit_tx, quit) = tokio::sync::oneshot::channel::<()>();
+/// # drop(quit_tx);
+/// let load = call_detached!(handle, Msg::GetLoad);
+/// tokio::select! {
+///     load = load => println!("load: {}", load?),
+///     _ = quit => println!("quit"),
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_detached {
+    // Pattern 1: Closure syntax with deadline (implementation)
+    ($task:expr, |$tx:ident| $msg:expr, deadline = $deadline:expr) => {
+        $crate::call_detached!(@send $task, |$tx| $msg, ::std::option::Option::Some(
+            $crate::core::IntoDeadline::into_deadline($deadline)
+        ))
+    };
+
+    // Pattern 2: Closure syntax with timeout
+    ($task:expr, |$tx:ident| $msg:expr, timeout = $timeout:expr) => {
+        $crate::call_detached!(@send $task, |$tx| $msg, $crate::runtime::Instant::now()
+            .checked_add($crate::core::IntoTimeout::into_timeout($timeout)))
+    };
+
+    // Pattern 3: Closure syntax without timeout
+    ($task:expr, |$tx:ident| $msg:expr) => {
+        $crate::call_detached!($task, |$tx| $msg, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    // Pattern 4: Simple variant path with timeout
+    ($task:expr, $first:ident :: $($rest:tt)::+, timeout = $timeout:expr) => {
+        $crate::call_detached!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, timeout = $timeout)
+    };
+
+    // Pattern 5: Simple variant path with deadline
+    ($task:expr, $first:ident :: $($rest:tt)::+, deadline = $deadline:expr) => {
+        $crate::call_detached!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, deadline = $deadline)
+    };
+
+    // Pattern 6: Simple variant path without timeout
+    ($task:expr, $first:ident :: $($rest:tt)::+) => {
+        $crate::call_detached!($task, $first :: $($rest)::+, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    (@send $task:expr, |$tx:ident| $msg:expr, $deadline:expr) => {{
+        let __notizia_task = &$task;
+        let correlation = $crate::core::CorrelationId::current_or_next();
+        let started = $crate::runtime::Instant::now();
+        let deadline = $deadline;
+        match $crate::core::errors::CallError::check_self_call(__notizia_task.id(), __notizia_task.name()) {
+            ::std::result::Result::Err(err) => $crate::core::CallFuture::failed(err, correlation),
+            ::std::result::Result::Ok(()) => {
+                let ($tx, rx) = $crate::core::reply::channel(correlation);
+                match __notizia_task.send($msg) {
+                    ::std::result::Result::Ok(()) => $crate::core::CallFuture::pending(
+                        rx,
+                        deadline,
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                        correlation,
+                        started,
+                    ),
+                    ::std::result::Result::Err(err) => $crate::core::CallFuture::failed(
+                        $crate::core::errors::CallError::send_failed(
+                            __notizia_task.id(),
+                            __notizia_task.name(),
+                            err,
+                        ),
+                        correlation,
+                    ),
+                }
+            }
+        }
+    }};
+}
+
+/// Call one of several replicas, hedging slow calls with the next replica.
+///
+/// Sends the request to the first task of `refs`. If it has not answered
+/// within `hedge_after`, the request is also sent to the second task, and
+/// so on; a replica that fails is followed by the next one right away. The
+/// first answer wins and the calls still in flight are
+/// [canceled](crate::core::CallFuture::cancel). This trades a little extra
+/// load for lower tail latency when replicas are occasionally slow.
+///
+/// `hedge_after` and the optional `timeout` accept the same values as the
+/// timeout of [`call!`]. The timeout applies to the whole call, across all
+/// replicas, and defaults to 5000ms (5 seconds).
+///
+/// Since the message is built once per replica, values moved into it must
+/// be cloned inside the closure.
+///
+/// # Errors
+///
+/// Returns the error of the last failed call if no replica answered, e.g.
+/// [`CallError::Timeout`](crate::CallError::Timeout), or
+/// [`CallError::NoReplicas`](crate::CallError::NoReplicas) if `refs` is
+/// empty.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{call_hedged, message};
+/// # use std::time::Duration;
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = String)]
+/// #     Lookup { key: String },
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Replica;
+/// # impl Runnable<Msg> for Replica { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// # let replicas: Vec<TaskHandle<Msg>> = vec![spawn!(Replica), spawn!(Replica)];
+/// let key = String::from("user:7");
+/// let value = call_hedged!(
+///     &replicas,
+///     |reply_to| Msg::Lookup { key: key.clone(), reply_to },
+///     hedge_after = Duration::from_millis(20)
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_hedged {
+    // Pattern 1: Closure syntax with timeout (implementation)
+    ($refs:expr, |$tx:ident| $msg:expr, hedge_after = $hedge:expr, timeout = $timeout:expr) => {{
+        async {
+            let deadline = $crate::runtime::Instant::now()
+                .checked_add($crate::core::IntoTimeout::into_timeout($timeout));
+            $crate::core::reply::hedged(
+                $refs,
+                |__notizia_task| $crate::call_detached!(@send __notizia_task, |$tx| $msg, deadline),
+                $crate::core::IntoTimeout::into_timeout($hedge),
+            )
+            .await
+        }
+    }};
+
+    // Pattern 2: Closure syntax without timeout
+    ($refs:expr, |$tx:ident| $msg:expr, hedge_after = $hedge:expr) => {
+        $crate::call_hedged!($refs, |$tx| $msg, hedge_after = $hedge, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    // Pattern 3: Simple variant path with timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+, hedge_after = $hedge:expr, timeout = $timeout:expr) => {
+        $crate::call_hedged!($refs, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, hedge_after = $hedge, timeout = $timeout)
+    };
+
+    // Pattern 4: Simple variant path without timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+, hedge_after = $hedge:expr) => {
+        $crate::call_hedged!($refs, $first :: $($rest)::+, hedge_after = $hedge, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+}
+
+/// Call a task like [`call!`], retrying failed attempts.
+///
+/// Every attempt sends a fresh request and waits for the reply with the
+/// given timeout. Failed attempts are retried according to a
+/// [`RetryPolicy`](crate::core::RetryPolicy), which controls the number of
+/// attempts, the backoff between them, and which errors are retried.
+///
+/// Since the message is built once per attempt, values moved into it must
+/// be cloned inside the closure.
+///
+/// # Timeout
+///
+/// The timeout applies to each attempt individually. It is optional and
+/// defaults to 5000ms (5 seconds).
+///
+/// # Errors
+///
+/// Returns the [`CallError`](crate::CallError) of the last attempt.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::core::RetryPolicy;
+/// # use notizia::{call_with_retry, message};
+/// # use std::time::Duration;
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = u32)]
+/// #     GetStatus,
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Worker;
+/// # impl Runnable<Msg> for Worker { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// # let handle = spawn!(Worker);
+/// let policy = RetryPolicy::new(3).backoff(Duration::from_millis(50));
+///
+/// // Up to three attempts of 500ms each
+/// let status = call_with_retry!(handle, Msg::GetStatus, retry = policy, timeout = 500).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_with_retry {
+    // Pattern 1: Closure syntax with timeout (implementation)
+    ($task:expr, |$tx:ident| $msg:expr, retry = $policy:expr, timeout = $timeout:expr) => {{
+        async {
+            $crate::core::RetryPolicy::run(&$policy, || {
+                $crate::call!($task, |$tx| $msg, timeout = $timeout)
+            })
+            .await
+        }
+    }};
+
+    // Pattern 2: Closure syntax without timeout
+    ($task:expr, |$tx:ident| $msg:expr, retry = $policy:expr) => {
+        $crate::call_with_retry!($task, |$tx| $msg, retry = $policy, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    // Pattern 3: Simple variant path with timeout
+    ($task:expr, $first:ident :: $($rest:tt)::+, retry = $policy:expr, timeout = $timeout:expr) => {
+        $crate::call_with_retry!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, retry = $policy, timeout = $timeout)
+    };
+
+    // Pattern 4: Simple variant path without timeout
+    ($task:expr, $first:ident :: $($rest:tt)::+, retry = $policy:expr) => {
+        $crate::call_with_retry!($task, $first :: $($rest)::+, retry = $policy, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+}
+
+/// Send a request to many tasks and gather all responses.
+///
+/// This macro performs a [`call!`] against every task in an iterable of
+/// handles or references concurrently. The returned future resolves to a
+/// `Vec<CallResult<R>>` with one entry per callee, in iteration order.
+///
+/// The message is built once per callee, so the closure body is evaluated
+/// for every task.
+///
+/// # Timeout
+///
+/// The timeout applies to each callee individually. It is optional and
+/// defaults to 5000ms (5 seconds). A slow callee only fails its own entry
+/// with [`CallError::Timeout`](crate::CallError::Timeout).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{message, scatter};
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = u32)]
+/// #     GetLoad,
+/// #     #[request(reply = u32)]
+/// #     Lookup { key: u32 },
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Worker;
+/// # impl Runnable<Msg> for Worker { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let workers: Vec<_> = (0..4).map(|_| Worker.run().this()).collect();
+///
+/// // Ask every worker for its load
+/// let loads: Vec<CallResult<u32>> = scatter!(&workers, Msg::GetLoad).await;
+///
+/// // Closure syntax with a per-callee timeout of 100ms
+/// let key = 7;
+/// let hits = scatter!(&workers, |tx| Msg::Lookup { key, reply_to: tx }, timeout = 100).await;
+/// # }
+/// ```
+#[macro_export]
+macro_rules! scatter {
+    // Pattern 1: Closure syntax with timeout (implementation)
+    ($refs:expr, |$tx:ident| $msg:expr, timeout = $timeout:expr) => {{
+        $crate::futures::future::join_all(::std::iter::IntoIterator::into_iter($refs).map(
+            |__notizia_task| async move {
+                $crate::core::errors::CallError::check_self_call(__notizia_task.id(), __notizia_task.name())?;
+                let correlation = $crate::core::CorrelationId::current_or_next();
+                let ($tx, rx) = $crate::core::reply::channel(correlation);
+                let started = $crate::runtime::Instant::now();
+                __notizia_task.send($msg).map_err(|err| {
+                    $crate::core::errors::CallError::send_failed(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                        err,
+                    )
+                })?;
+
+                $crate::runtime::timeout($crate::core::IntoTimeout::into_timeout($timeout), rx)
+                    .await
+                    .map_err(|_| {
+                        $crate::core::errors::CallError::timed_out(
+                            __notizia_task.id(),
+                            __notizia_task.name(),
+                            correlation,
+                            started,
+                        )
+                    })?
+            },
+        ))
+    }};
+
+    // Pattern 2: Closure syntax without timeout
+    ($refs:expr, |$tx:ident| $msg:expr) => {
+        $crate::scatter!($refs, |$tx| $msg, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    // Pattern 3: Simple variant path with timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+, timeout = $timeout:expr) => {
+        $crate::scatter!($refs, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, timeout = $timeout)
+    };
+
+    // Pattern 4: Simple variant path without timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+) => {
+        $crate::scatter!($refs, $first :: $($rest)::+, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+}
+
+/// Send a streaming request to a task.
+///
+/// For request variants declared with `#[request(stream = Item)]`, this
+/// macro creates a [`StreamReply`](crate::StreamReply) for the `reply_to`
+/// field, sends the request, and returns the caller's
+/// [`ReplyStream`](crate::ReplyStream), which yields the items sent by the
+/// task. After the stream is exhausted,
+/// [`ReplyStream::end`](crate::ReplyStream::end) reports whether the task
+/// finished it, failed it, or dropped it.
+///
+/// # Buffer
+///
+/// Up to `buffer` items are queued before the task's
+/// [`send`](crate::StreamReply::send) waits for the caller. The buffer is
+/// optional and defaults to
+/// [`DEFAULT_STREAM_BUFFER`](crate::core::stream::DEFAULT_STREAM_BUFFER).
+///
+/// # Errors
+///
+/// Returns [`CallError::SendError`](crate::CallError::SendError) if the
+/// task's mailbox is closed, and
+/// [`CallError::WouldDeadlock`](crate::CallError::WouldDeadlock) if a task
+/// streams from itself.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{call_stream, message};
+/// # use notizia::futures::StreamExt;
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(stream = String)]
+/// #     Tail,
+/// #     #[request(stream = Vec<u32>)]
+/// #     Pages { size: usize },
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Store;
+/// # impl Runnable<Msg> for Store { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// # let handle = spawn!(Store);
+/// let mut lines = call_stream!(handle, Msg::Tail)?;
+/// while let Some(line) = lines.next().await {
+///     println!("{line}");
+/// }
+///
+/// // Closure syntax with a buffer of one page
+/// let pages = call_stream!(handle, |tx| Msg::Pages { size: 100, reply_to: tx }, buffer = 1)?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_stream {
+    // Pattern 1: Closure syntax with buffer (implementation)
+    ($task:expr, |$tx:ident| $msg:expr, buffer = $buffer:expr) => {{
+        let __notizia_task = &$task;
+        $crate::core::errors::CallError::check_self_call(
+            __notizia_task.id(),
+            __notizia_task.name(),
+        )
+        .and_then(|()| {
+            let ($tx, stream) = $crate::core::stream::channel($buffer);
+            __notizia_task
+                .send($msg)
+                .map(|()| stream)
+                .map_err(|err| {
+                    $crate::core::errors::CallError::send_failed(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                        err,
+                    )
+                })
+        })
+    }};
+
+    // Pattern 2: Closure syntax without buffer
+    ($task:expr, |$tx:ident| $msg:expr) => {
+        $crate::call_stream!($task, |$tx| $msg, buffer = $crate::core::stream::DEFAULT_STREAM_BUFFER)
+    };
+
+    // Pattern 3: Simple variant path with buffer
+    ($task:expr, $first:ident :: $($rest:tt)::+, buffer = $buffer:expr) => {
+        $crate::call_stream!($task, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, buffer = $buffer)
+    };
+
+    // Pattern 4: Simple variant path without buffer
+    ($task:expr, $first:ident :: $($rest:tt)::+) => {
+        $crate::call_stream!($task, $first :: $($rest)::+, buffer = $crate::core::stream::DEFAULT_STREAM_BUFFER)
+    };
+}
+
+/// Send a request to many tasks and gather all responses by task.
+///
+/// Like [`scatter!`], but every result is paired with the
+/// [`TaskId`](crate::TaskId) of its callee. The returned future resolves to a
+/// `Vec<(TaskId, CallResult<R>)>` in iteration order, which suits health
+/// checks and quorum queries across a pool.
+///
+/// # Timeout
+///
+/// The timeout applies to each callee individually. It is optional and
+/// defaults to 5000ms (5 seconds).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{call_all, message};
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = bool)]
+/// #     IsHealthy,
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Worker;
+/// # impl Runnable<Msg> for Worker { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let workers: Vec<_> = (0..4).map(|_| Worker.run().this()).collect();
+///
+/// for (task, health) in call_all!(&workers, Msg::IsHealthy, timeout = 1000).await {
+///     if !matches!(health, Ok(true)) {
+///         println!("task {task} is unhealthy");
+///     }
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! call_all {
+    // Pattern 1: Closure syntax with timeout (implementation)
+    ($refs:expr, |$tx:ident| $msg:expr, timeout = $timeout:expr) => {{
+        $crate::futures::future::join_all(::std::iter::IntoIterator::into_iter($refs).map(
+            |__notizia_callee| async move {
+                let id = __notizia_callee.id();
+                let result = $crate::call!(__notizia_callee, |$tx| $msg, timeout = $timeout).await;
+                (id, result)
+            },
+        ))
+    }};
+
+    // Pattern 2: Closure syntax without timeout
+    ($refs:expr, |$tx:ident| $msg:expr) => {
+        $crate::call_all!($refs, |$tx| $msg, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
+    };
+
+    // Pattern 3: Simple variant path with timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+, timeout = $timeout:expr) => {
+        $crate::call_all!($refs, |__notizia_tx| $first :: $($rest)::+ { reply_to: __notizia_tx }, timeout = $timeout)
+    };
+
+    // Pattern 4: Simple variant path without timeout
+    ($refs:expr, $first:ident :: $($rest:tt)::+) => {
+        $crate::call_all!($refs, $first :: $($rest)::+, timeout = $crate::core::DEFAULT_CALL_TIMEOUT)
     };
 }
 
@@ -197,6 +903,264 @@ macro_rules! cast {
     };
 }
 
+/// Cast the same message to many tasks (fire-and-forget).
+///
+/// Sends a clone of the message to every [`TaskRef`](crate::task::TaskRef) or
+/// [`TaskHandle`](crate::task::TaskHandle) yielded by `refs`, e.g. a slice or
+/// a `Vec`, and returns a `Vec<(TaskId, SendResult<T>)>` in iteration order.
+/// A failed send does not stop the others, so callers can prune terminated
+/// tasks from their list afterwards. The message type must implement
+/// [`Clone`]; wrap large payloads in an
+/// [`ArcMessage`](crate::core::ArcMessage) so the clones share them.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::cast_all;
+/// # #[derive(Clone)]
+/// # enum Signal { Reload }
+/// # #[derive(Task)]
+/// # #[task(message = Signal)]
+/// # struct Worker;
+/// # impl Runnable<Signal> for Worker { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut workers: Vec<_> = (0..4).map(|_| Worker.run().this()).collect();
+///
+/// for (task, result) in cast_all!(&workers, Signal::Reload) {
+///     if result.is_err() {
+///         println!("task {task} has terminated");
+///     }
+/// }
+///
+/// // Forget terminated workers
+/// workers.retain(|worker| !worker.is_closed());
+/// # }
+/// ```
+#[macro_export]
+macro_rules! cast_all {
+    ($refs:expr, $msg:expr) => {{
+        let __notizia_msg = $msg;
+        ::std::iter::IntoIterator::into_iter($refs)
+            .map(|__notizia_target| {
+                let id = __notizia_target.id();
+                (
+                    id,
+                    __notizia_target.send(::std::clone::Clone::clone(&__notizia_msg)),
+                )
+            })
+            .collect::<::std::vec::Vec<_>>()
+    }};
+}
+
+/// Send a message and wait until the task has processed it.
+///
+/// The message is acknowledged by the receiving task's mailbox once the task
+/// asks for its next message; handler tasks acknowledge it after their
+/// handler returned. Messages that are not acknowledged within the timeout,
+/// or that are dropped unprocessed, are sent again with the backoff of a
+/// [`RetryPolicy`](crate::core::RetryPolicy). Once the attempts are used up,
+/// the message is put into the [dead-letter queue](crate::core::dead_letter)
+/// and the error of the last attempt is returned. See
+/// [`core::delivery`](crate::core::delivery).
+///
+/// The message must implement `Clone`, so it can be sent more than once.
+/// Delivery is at-least-once, so the task may process a message twice.
+///
+/// # Timeout and Retries
+///
+/// `timeout = <duration>` limits how long each attempt waits for the
+/// acknowledgement and defaults to 5000ms (5 seconds). `retry = <policy>`
+/// defaults to [`default_policy`](crate::core::delivery::default_policy),
+/// retrying every error.
+///
+/// # Errors
+///
+/// Returns [`CallError::Timeout`](crate::CallError::Timeout) if the message
+/// was not acknowledged in time, [`CallError::NoReply`](crate::CallError::NoReply)
+/// if it was dropped unprocessed, and [`CallError::SendError`](crate::CallError::SendError)
+/// if the mailbox is closed.
+/// Returns [`CallError::WouldDeadlock`](crate::CallError::WouldDeadlock)
+/// without sending anything if a task sends to itself.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::core::RetryPolicy;
+/// # use notizia::send_reliable;
+/// # use std::time::Duration;
+/// # #[derive(Debug, Clone)]
+/// # enum Command { Charge(u64) }
+/// # #[derive(Task)]
+/// # #[task(message = Command)]
+/// # struct Billing;
+/// # impl Runnable<Command> for Billing { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// let handle = spawn!(Billing);
+///
+/// send_reliable!(handle, Command::Charge(100)).await?;
+///
+/// // Up to five attempts, waiting one second for each acknowledgement
+/// let policy = RetryPolicy::new(5).backoff(Duration::from_millis(50));
+/// send_reliable!(handle, Command::Charge(100), retry = policy, timeout = 1000).await?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! send_reliable {
+    // Pattern 1: Retry policy and timeout (implementation)
+    ($task:expr, $msg:expr, retry = $policy:expr, timeout = $timeout:expr) => {{
+        async {
+            let __notizia_task = &$task;
+            $crate::core::delivery::deliver(
+                __notizia_task.id(),
+                __notizia_task.name(),
+                $msg,
+                &$policy,
+                $timeout,
+                |msg, ack| __notizia_task.send_acked(msg, ack),
+            )
+            .await
+        }
+    }};
+
+    // Pattern 2: Retry policy without timeout
+    ($task:expr, $msg:expr, retry = $policy:expr) => {
+        $crate::send_reliable!(
+            $task,
+            $msg,
+            retry = $policy,
+            timeout = $crate::core::DEFAULT_CALL_TIMEOUT
+        )
+    };
+
+    // Pattern 3: Timeout without retry policy
+    ($task:expr, $msg:expr, timeout = $timeout:expr) => {
+        $crate::send_reliable!(
+            $task,
+            $msg,
+            retry = $crate::core::delivery::default_policy(),
+            timeout = $timeout
+        )
+    };
+
+    // Pattern 4: Neither
+    ($task:expr, $msg:expr) => {
+        $crate::send_reliable!(
+            $task,
+            $msg,
+            retry = $crate::core::delivery::default_policy(),
+            timeout = $crate::core::DEFAULT_CALL_TIMEOUT
+        )
+    };
+}
+
+/// Answer a request or the sender of the current message.
+///
+/// `reply!(reply_to, value)` answers a [`Reply`](crate::Reply) or raw
+/// oneshot sender, like `let _ = reply_to.reply(value);`.
+/// `reply!(ctx, value)` sends `value` to the
+/// [sender](crate::task::Context::sender) of the message a handler is
+/// handling, provided the sender's message type is the type of `value`.
+///
+/// Returns whether the answer was delivered. An answer that could not be
+/// delivered, because the caller stopped waiting, the message was not sent
+/// from within a task or the sender has a different message type, is
+/// reported as a warning (a `tracing` event with the `tracing` feature).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{message, reply};
+/// #[message]
+/// #[derive(Debug)]
+/// enum Msg {
+///     Ping,
+///     #[request(reply = u32)]
+///     GetCount,
+/// }
+///
+/// #[derive(Task)]
+/// #[task(message = Msg, handler)]
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// impl Handler<Msg> for Counter {
+///     async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+///         self.count += 1;
+///         match msg {
+///             // Answers a sender with message type `u32`
+///             Msg::Ping => {
+///                 reply!(ctx, self.count);
+///             }
+///             Msg::GetCount { reply_to } => {
+///                 reply!(reply_to, self.count);
+///             }
+///         }
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! reply {
+    ($to:expr, $value:expr) => {{
+        use $crate::core::reply::Respond as _;
+        $crate::core::reply::delivered(($to).respond($value))
+    }};
+}
+
+/// Pass on a message to another task, which answers in place of the
+/// running task.
+///
+/// This macro is a convenient wrapper around the `forward()` method on
+/// [`TaskHandle`](crate::task::TaskHandle) or [`TaskRef`](crate::task::TaskRef).
+/// A request keeps its `reply_to`, so routers and proxies delegate calls
+/// without awaiting the answer and sending it back themselves. The message
+/// also keeps the [sender](crate::task::Context::sender) of the message
+/// being handled.
+///
+/// Returns a [`SendResult`](crate::core::errors::SendResult) containing
+/// the message if it could not be forwarded.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{forward, message};
+/// #[message]
+/// #[derive(Debug)]
+/// enum Query {
+///     #[request(reply = String)]
+///     Get { key: String },
+/// }
+///
+/// #[derive(Task)]
+/// #[task(message = Query, handler)]
+/// struct Router {
+///     shards: Vec<TaskRef<Query>>,
+/// }
+///
+/// impl Handler<Query> for Router {
+///     async fn handle(&mut self, msg: Query, _ctx: &mut Context<Query>) {
+///         let Query::Get { key, .. } = &msg;
+///         let shard = &self.shards[key.len() % self.shards.len()];
+///
+///         // The shard answers the caller
+///         let _ = forward!(shard, msg);
+///     }
+/// }
+/// ```
+#[macro_export]
+macro_rules! forward {
+    ($task:expr, $msg:expr) => {
+        $task.forward($msg)
+    };
+}
+
 /// Receive a message from a task's mailbox.
 ///
 /// This macro must be used with `.await` as it performs an asynchronous operation.
@@ -207,8 +1171,7 @@ macro_rules! cast {
 ///
 /// # Example
 ///
-/// ```ignore
-/// # TODO: Re-enable once derive macro hygiene is fixed
+/// ```
 /// # use notizia::prelude::*;
 /// # #[derive(Clone)]
 /// # enum Signal { Ping }
@@ -224,9 +1187,115 @@ macro_rules! cast {
 ///     }
 /// }
 /// ```
+///
+/// # Correlation
+///
+/// If the received message is a request of a `#[message]` enum, its
+/// correlation id becomes the task's
+/// [current](crate::core::CorrelationId::current) one until the next
+/// message is received. Calling `self.recv()` directly skips this.
 #[macro_export]
 macro_rules! recv {
+    ($ident:ident) => {{
+        let __notizia_msg = $crate::Task::recv($ident).await;
+        if let ::core::result::Result::Ok(msg) = &__notizia_msg {
+            #[allow(unused_imports)]
+            use $crate::core::correlation::{ViaAny as _, ViaCorrelated as _};
+            $crate::core::correlation::enter(
+                (&$crate::core::correlation::Probe(msg)).correlation_id(),
+            );
+        }
+        __notizia_msg
+    }};
+}
+
+/// Receive a message from a task's data mailbox.
+///
+/// For tasks with a [control mailbox](crate::task::control), this makes the
+/// distinction to [`recv_ctrl!`] explicit. It is the same as [`recv!`].
+#[macro_export]
+macro_rules! recv_data {
+    ($ident:ident) => {
+        $crate::recv!($ident)
+    };
+}
+
+/// Receive a message from a task's control mailbox.
+///
+/// Only available for tasks declared with
+/// `#[task(message = T, control = C)]`. Returns a
+/// [`RecvResult<C>`](crate::core::errors::RecvResult). See
+/// [`task::control`](crate::task::control).
+#[macro_export]
+macro_rules! recv_ctrl {
     ($ident:ident) => {
-        $ident.recv().await
+        $crate::task::control::recv_control($ident).await
+    };
+}
+
+/// Receive the next message from either of a task's mailboxes.
+///
+/// Returns an [`Incoming`](crate::task::Incoming) with either a control or
+/// a data message. If both are available, the control message is returned
+/// first, so control traffic is never stuck behind a data backlog. Fails
+/// once the data mailbox is closed.
+///
+/// Only available for tasks declared with
+/// `#[task(message = T, control = C)]`. See
+/// [`task::control`](crate::task::control).
+#[macro_export]
+macro_rules! recv_any {
+    ($ident:ident) => {{
+        let __notizia_msg = $crate::task::control::recv_any($ident).await;
+        if let Ok($crate::task::Incoming::Data(msg)) = &__notizia_msg {
+            #[allow(unused_imports)]
+            use $crate::core::correlation::{ViaAny as _, ViaCorrelated as _};
+            $crate::core::correlation::enter(
+                (&$crate::core::correlation::Probe(msg)).correlation_id(),
+            );
+        }
+        __notizia_msg
+    }};
+}
+
+/// Expect the next message of a [`TestProbe`](crate::testing::TestProbe) to
+/// match a pattern.
+///
+/// Evaluates to the matched message. Without `within`, waits up to
+/// [`DEFAULT_CALL_TIMEOUT`](crate::core::DEFAULT_CALL_TIMEOUT).
+///
+/// Requires the `testing` feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::expect_msg;
+/// # use notizia::testing::TestProbe;
+/// #[derive(Debug)]
+/// enum Event {
+///     Saved { id: u32 },
+/// }
+///
+/// # #[tokio::main]
+/// # async fn main() {
+/// let probe = TestProbe::new();
+/// probe.this().send(Event::Saved { id: 7 }).unwrap();
+///
+/// let saved = expect_msg!(probe, Event::Saved { id } if *id > 0, within = 100);
+/// # }
+/// ```
+#[cfg(feature = "testing")]
+#[macro_export]
+macro_rules! expect_msg {
+    ($probe:expr, $pattern:pat $(if $guard:expr)?, within = $within:expr) => {
+        $probe
+            .expect_msg_where($within, |__notizia_msg| {
+                ::core::matches!(__notizia_msg, $pattern $(if $guard)?)
+            })
+            .await
+    };
+
+    ($probe:expr, $pattern:pat $(if $guard:expr)?) => {
+        $crate::expect_msg!($probe, $pattern $(if $guard)?, within = $crate::core::DEFAULT_CALL_TIMEOUT)
     };
 }
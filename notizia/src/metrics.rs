@@ -0,0 +1,74 @@
+//! Per-task metrics.
+//!
+//! With the `metrics` feature enabled, notizia reports the following metrics
+//! through the [`metrics`](::metrics) facade. Every metric carries a `task`
+//! label with the name of the task.
+//!
+//! | Metric | Type | Description |
+//! |---|---|---|
+//! | `notizia_mailbox_depth` | gauge | Messages waiting in the mailbox, sampled whenever the task receives one |
+//! | `notizia_messages_processed_total` | counter | Messages delivered to the task |
+//! | `notizia_handler_duration_seconds` | histogram | Time the task spent on a message, until it asked for the next one |
+//! | `notizia_task_restarts_total` | counter | Tasks registering under a [registry](crate::registry) name another task held before |
+//! | `notizia_task_panics_total` | counter | Tasks terminating with [`TerminateReason::Panic`] |
+//!
+//! Metrics only cover tasks spawned through `#[derive(Task)]`. Where they go
+//! is decided by the installed recorder; to expose them to Prometheus,
+//! install e.g. the `metrics-exporter-prometheus` recorder.
+//!
+//! This module requires the `metrics` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! // Install a recorder of your choice first, then
+//! notizia::metrics::describe();
+//! ```
+
+use std::time::Duration;
+
+use crate::TerminateReason;
+
+const MAILBOX_DEPTH: &str = "notizia_mailbox_depth";
+const MESSAGES_PROCESSED: &str = "notizia_messages_processed_total";
+const HANDLER_DURATION: &str = "notizia_handler_duration_seconds";
+const TASK_RESTARTS: &str = "notizia_task_restarts_total";
+const TASK_PANICS: &str = "notizia_task_panics_total";
+
+/// Register descriptions of notizia's metrics with the installed recorder.
+///
+/// Optional; recorders use the descriptions as help texts.
+pub fn describe() {
+    metrics::describe_gauge!(MAILBOX_DEPTH, "Messages waiting in the mailbox");
+    metrics::describe_counter!(MESSAGES_PROCESSED, "Messages delivered to the task");
+    metrics::describe_histogram!(
+        HANDLER_DURATION,
+        metrics::Unit::Seconds,
+        "Time the task spent on a message"
+    );
+    metrics::describe_counter!(TASK_RESTARTS, "Tasks registered again under a name");
+    metrics::describe_counter!(TASK_PANICS, "Tasks terminated by a panic");
+}
+
+/// Record the delivery of a message, with `depth` messages left behind it.
+pub(crate) fn delivered(task: &'static str, depth: usize) {
+    metrics::gauge!(MAILBOX_DEPTH, "task" => task).set(depth as f64);
+    metrics::counter!(MESSAGES_PROCESSED, "task" => task).increment(1);
+}
+
+/// Record the time a task spent on a message.
+pub(crate) fn handled(task: &'static str, elapsed: Duration) {
+    metrics::histogram!(HANDLER_DURATION, "task" => task).record(elapsed);
+}
+
+/// Record a task taking over the registration of another one.
+pub(crate) fn restarted(task: &'static str) {
+    metrics::counter!(TASK_RESTARTS, "task" => task).increment(1);
+}
+
+/// Record the termination of a task.
+pub(crate) fn terminated(task: &'static str, reason: &TerminateReason) {
+    if let TerminateReason::Panic(_) = reason {
+        metrics::counter!(TASK_PANICS, "task" => task).increment(1);
+    }
+}
@@ -0,0 +1,221 @@
+//! Serving TCP connections with a task per connection.
+//!
+//! A [`TcpServerTask`] accepts connections and spawns a connection task for
+//! each of them, built by a factory from the socket, the peer's address and
+//! a [`TaskRef`] back to the server. Every connection is isolated in its own
+//! task: a connection task that panics only drops its own connection.
+//!
+//! The server tracks its connection tasks, can
+//! [limit](TcpServerTask::max_connections) how many are open at once, and
+//! shuts them all down when it stops.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::core::Received;
+//! use notizia::net::{ServerMsg, TcpServerTask};
+//! use notizia::prelude::*;
+//! use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+//! use tokio::net::TcpStream;
+//! use tokio::sync::Mutex;
+//!
+//! enum EchoMsg {}
+//!
+//! #[derive(Task)]
+//! #[task(message = EchoMsg)]
+//! struct Echo {
+//!     stream: Mutex<TcpStream>,
+//! }
+//!
+//! impl Runnable<EchoMsg> for Echo {
+//!     async fn start(&self) {
+//!         let mailbox = self.mailbox();
+//!         let mut stream = self.stream.lock().await;
+//!         let (read, mut write) = stream.split();
+//!         let mut lines = BufReader::new(read).lines();
+//!
+//!         // Until the peer disconnects or the server shuts down
+//!         while let Ok(Received::Interrupted(Ok(Some(line)))) =
+//!             mailbox.recv_or(lines.next_line()).await
+//!         {
+//!             let _ = write.write_all(format!("{line}\n").as_bytes()).await;
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! let server = TcpServerTask::bind("0.0.0.0:7000", |stream, _addr, _server| Echo {
+//!     stream: Mutex::new(stream),
+//! })
+//! .await?
+//! .max_connections(1024);
+//! let server = spawn!(server);
+//!
+//! // Later: stop accepting and close all connections
+//! server.send(ServerMsg::Shutdown).unwrap();
+//! server.join().await.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+
+use crate::core::Reply;
+use crate::core::lifecycle::DEFAULT_SHUTDOWN_TIMEOUT;
+use crate::core::mailbox::Received;
+use crate::task::{Runnable, Task, TaskRef, TaskSet};
+
+/// Spawns the task for an accepted connection into the set.
+type Spawn = Box<dyn Fn(TcpStream, SocketAddr, TaskRef<ServerMsg>, &mut TaskSet) + Send + Sync>;
+
+/// Messages understood by a [`TcpServerTask`].
+///
+/// Like messages defined with `#[message]`, requests carry a `reply_to`
+/// field and are sent with [`call!`](crate::call!).
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ServerMsg {
+    /// Number of open connections
+    Connections { reply_to: Reply<usize> },
+    /// Stop accepting connections and shut down all connection tasks
+    Shutdown,
+}
+
+/// What interrupted waiting for messages.
+enum Event {
+    Accepted(io::Result<(TcpStream, SocketAddr)>),
+    Closed,
+}
+
+/// A task accepting TCP connections, running a task per connection.
+///
+/// The server runs until it receives [`ServerMsg::Shutdown`] or its mailbox
+/// closes. Since connection tasks hold a reference to the server, its
+/// mailbox only closes once they all dropped theirs, so stop a server with
+/// `Shutdown`. Either way, it stops accepting connections and gracefully
+/// shuts down the open ones within the
+/// [shutdown timeout](Self::shutdown_timeout), closing their mailboxes like
+/// [`TaskSet::shutdown_all`]. Connection tasks waiting on their socket
+/// notice by racing reads against their mailbox with
+/// [`Mailbox::recv_or`](crate::core::Mailbox::recv_or).
+///
+/// See the [module documentation](self) for an example.
+#[derive(crate::Task)]
+#[task(message = ServerMsg)]
+pub struct TcpServerTask {
+    listener: TcpListener,
+    spawn: Spawn,
+    max_connections: Option<usize>,
+    shutdown_timeout: Duration,
+}
+
+impl TcpServerTask {
+    /// Listen on `addr`, running the task `connection` builds for every
+    /// accepted connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind<A, M, F>(addr: impl ToSocketAddrs, connection: F) -> io::Result<Self>
+    where
+        A: Task<M> + 'static,
+        M: Send + 'static,
+        F: Fn(TcpStream, SocketAddr, TaskRef<ServerMsg>) -> A + Send + Sync + 'static,
+    {
+        Ok(Self::from_listener(
+            TcpListener::bind(addr).await?,
+            connection,
+        ))
+    }
+
+    /// Accept connections on a bound `listener`, running the task
+    /// `connection` builds for each of them.
+    pub fn from_listener<A, M, F>(listener: TcpListener, connection: F) -> Self
+    where
+        A: Task<M> + 'static,
+        M: Send + 'static,
+        F: Fn(TcpStream, SocketAddr, TaskRef<ServerMsg>) -> A + Send + Sync + 'static,
+    {
+        TcpServerTask {
+            listener,
+            spawn: Box::new(move |stream, addr, server, connections| {
+                connections.insert(connection(stream, addr, server).run());
+            }),
+            max_connections: None,
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+        }
+    }
+
+    /// Keep at most `max` connections open at once.
+    ///
+    /// Once the limit is reached, the server stops accepting until a
+    /// connection task terminates. Further connections wait in the
+    /// listener's backlog meanwhile. Unlimited by default.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max` is zero.
+    pub fn max_connections(mut self, max: usize) -> Self {
+        assert!(max > 0, "a server must allow at least one connection");
+        self.max_connections = Some(max);
+        self
+    }
+
+    /// Set how long connection tasks get to terminate once the server stops.
+    ///
+    /// Defaults to [`DEFAULT_SHUTDOWN_TIMEOUT`].
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.shutdown_timeout = timeout;
+        self
+    }
+
+    /// The address the server is bound to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be determined.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+}
+
+impl Runnable<ServerMsg> for TcpServerTask {
+    async fn start(&self) {
+        let mailbox = self.mailbox();
+        let this = self.this();
+        let mut connections = TaskSet::new();
+
+        loop {
+            let full = self
+                .max_connections
+                .is_some_and(|max| connections.len() >= max);
+            let event = async {
+                tokio::select! {
+                    accepted = self.listener.accept(), if !full => Event::Accepted(accepted),
+                    Some(_) = connections.join_next() => Event::Closed,
+                }
+            };
+
+            let received = mailbox.recv_or(event).await;
+            match received {
+                Ok(Received::Interrupted(Event::Accepted(Ok((stream, addr))))) => {
+                    (self.spawn)(stream, addr, this.clone(), &mut connections);
+                }
+                // Accepting fails transiently, e.g. when out of file descriptors
+                Ok(Received::Interrupted(Event::Accepted(Err(_)))) => {}
+                Ok(Received::Interrupted(Event::Closed)) => {}
+                Ok(Received::Message(ServerMsg::Connections { reply_to })) => {
+                    let _ = reply_to.reply(connections.len());
+                }
+                Ok(Received::Message(ServerMsg::Shutdown)) | Err(_) => break,
+            }
+        }
+
+        connections.shutdown_all(self.shutdown_timeout).await;
+    }
+}
@@ -0,0 +1,754 @@
+//! Event-sourced tasks.
+//!
+//! A [`Persistent`] task does not store its state directly. Its handlers
+//! describe every change as an event and [persist](Context::persist) it to a
+//! [`Journal`] before [applying](Persistent::apply) it. When the task starts,
+//! for example after a supervisor restarted it, all events in its journal
+//! are replayed into [`apply`](Persistent::apply), rebuilding the state it
+//! had before.
+//!
+//! Journals are stored by a pluggable [`JournalBackend`]. A
+//! [`MemoryJournal`] keeps events for the lifetime of the process, which
+//! suits tests and tasks that only need to survive restarts; a
+//! [`FileJournal`] appends them to one file per journal.
+//!
+//! Event sourcing is enabled with `persistent`, as in
+//! `#[task(message = M, handler, persistent)]`, on tasks implementing
+//! [`Handler`](crate::task::Handler). If recovering the state fails, the
+//! task panics, so supervisors can restart it like any other crash.
+//!
+//! Replaying a long history gets slow. Tasks implementing [`Snapshots`] and
+//! declared with `snapshots`, as in
+//! `#[task(message = M, handler, persistent, snapshots)]`, save their state
+//! every few events, as configured with [`Journal::snapshot_every`], and
+//! only replay the events persisted after the latest snapshot.
+//!
+//! This module requires the `persistence` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::persistence::{FileJournal, Journal, Persistent};
+//! use notizia::prelude::*;
+//! use notizia::{call, message};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[message]
+//! enum AccountMsg {
+//!     Deposit(u64),
+//!     #[request(reply = u64)]
+//!     Balance,
+//! }
+//!
+//! #[derive(Serialize, Deserialize)]
+//! enum AccountEvent {
+//!     Deposited(u64),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = AccountMsg, handler, persistent)]
+//! struct Account {
+//!     journal: Journal<AccountEvent>,
+//!     balance: u64,
+//! }
+//!
+//! impl Persistent for Account {
+//!     type Event = AccountEvent;
+//!
+//!     fn journal(&self) -> Journal<AccountEvent> {
+//!         self.journal.clone()
+//!     }
+//!
+//!     fn apply(&mut self, event: AccountEvent) {
+//!         match event {
+//!             AccountEvent::Deposited(amount) => self.balance += amount,
+//!         }
+//!     }
+//! }
+//!
+//! impl Handler<AccountMsg> for Account {
+//!     async fn handle(&mut self, msg: AccountMsg, ctx: &mut Context<AccountMsg>) {
+//!         match msg {
+//!             AccountMsg::Deposit(amount) => {
+//!                 let event = AccountEvent::Deposited(amount);
+//!                 match ctx.persist(&event).await {
+//!                     Ok(_) => self.apply(event),
+//!                     // The state can no longer be recovered; restart
+//!                     Err(_) => ctx.stop(),
+//!                 }
+//!             }
+//!             AccountMsg::Balance { reply_to } => {
+//!                 let _ = reply_to.reply(self.balance);
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let backend = FileJournal::open("journals")?;
+//! let account = Account {
+//!     journal: Journal::new(backend, "account-42"),
+//!     balance: 0,
+//! }
+//! .run();
+//!
+//! account.send(AccountMsg::Deposit(100))?;
+//! let balance = call!(account, AccountMsg::Balance).await?;
+//! # let _ = balance;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Write};
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use futures::future::BoxFuture;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+
+use crate::core::trace;
+use crate::task::Context;
+
+/// Errors of reading and writing journals.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum JournalError {
+    /// The backend failed to store or load events
+    #[error("journal I/O failed: {0}")]
+    Io(#[from] io::Error),
+
+    /// An event could not be serialized or deserialized
+    #[error("invalid event: {0}")]
+    Serde(#[from] serde_json::Error),
+
+    /// An event was appended at a sequence number that is already taken,
+    /// e.g. because two tasks write to the same journal
+    #[error("event {seq} of journal `{id}` was already written")]
+    Conflict { id: String, seq: u64 },
+
+    /// A stored event or snapshot cannot be read back
+    #[error("journal `{id}` is corrupted at line {line}")]
+    Corrupted { id: String, line: usize },
+}
+
+/// Serialized events read from a backend, along with their numbers.
+pub type StoredEvents = Vec<(u64, Vec<u8>)>;
+
+/// A serialized snapshot, along with the number of events it covers.
+pub type StoredSnapshot = (u64, Vec<u8>);
+
+/// Storage for the events of journals.
+///
+/// A backend stores the events of many journals, each identified by its
+/// id. Events are opaque bytes, numbered from zero without gaps.
+pub trait JournalBackend: Send + Sync + 'static {
+    /// Append `event` as event number `seq` of the journal `id`.
+    ///
+    /// Implementations must fail with [`JournalError::Conflict`] unless
+    /// `seq` is the number of events stored so far, so that a journal has a
+    /// single writer.
+    fn append<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        event: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>>;
+
+    /// Read the events of the journal `id`, starting at event number `from`.
+    fn read<'a>(
+        &'a self,
+        id: &'a str,
+        from: u64,
+    ) -> BoxFuture<'a, Result<StoredEvents, JournalError>>;
+
+    /// Store `snapshot` as the state of the journal `id` after its first
+    /// `seq` events, replacing older snapshots.
+    ///
+    /// The default implementation stores nothing, so tasks recover from
+    /// their events alone.
+    fn save_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        snapshot: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>> {
+        let _ = (id, seq, snapshot);
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    /// Load the latest snapshot of the journal `id`, if any.
+    ///
+    /// The default implementation never finds one.
+    fn load_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSnapshot>, JournalError>> {
+        let _ = id;
+        Box::pin(std::future::ready(Ok(None)))
+    }
+}
+
+/// The journal of a [`Persistent`] task, holding events of type `E`.
+///
+/// A journal is a named log in a [`JournalBackend`]. Cloning it is cheap;
+/// clones refer to the same log.
+pub struct Journal<E> {
+    backend: Arc<dyn JournalBackend>,
+    id: Arc<str>,
+    snapshot_every: Option<u64>,
+    _event: PhantomData<fn(E) -> E>,
+}
+
+impl<E> Journal<E> {
+    /// The journal `id` in `backend`.
+    pub fn new(backend: impl JournalBackend, id: impl Into<String>) -> Self {
+        Journal {
+            backend: Arc::new(backend),
+            id: id.into().into(),
+            snapshot_every: None,
+            _event: PhantomData,
+        }
+    }
+
+    /// Snapshot the state of a task implementing [`Snapshots`] after every
+    /// `events` persisted events.
+    ///
+    /// A restarted task then loads the latest snapshot and only replays the
+    /// events persisted after it. Without this, tasks never take snapshots,
+    /// but still recover from existing ones.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `events` is zero.
+    pub fn snapshot_every(mut self, events: u64) -> Self {
+        assert!(events > 0, "snapshots must cover at least one event");
+        self.snapshot_every = Some(events);
+        self
+    }
+
+    /// The id of the journal.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Read the events starting at event number `from`, along with their
+    /// numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the backend fails or an event cannot be
+    /// deserialized.
+    pub async fn read(&self, from: u64) -> Result<Vec<(u64, E)>, JournalError>
+    where
+        E: DeserializeOwned,
+    {
+        self.backend
+            .read(&self.id, from)
+            .await?
+            .into_iter()
+            .map(|(seq, event)| Ok((seq, serde_json::from_slice(&event)?)))
+            .collect()
+    }
+
+    /// Append `event` as event number `seq`.
+    async fn append(&self, seq: u64, event: &E) -> Result<(), JournalError>
+    where
+        E: Serialize,
+    {
+        let event = serde_json::to_vec(event)?;
+        self.backend.append(&self.id, seq, event).await
+    }
+}
+
+impl<E> Clone for Journal<E> {
+    fn clone(&self) -> Self {
+        Journal {
+            backend: self.backend.clone(),
+            id: self.id.clone(),
+            snapshot_every: self.snapshot_every,
+            _event: PhantomData,
+        }
+    }
+}
+
+impl<E> fmt::Debug for Journal<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Journal").field("id", &self.id).finish()
+    }
+}
+
+/// A task whose state is rebuilt from its events.
+///
+/// See the [module documentation](self) for an example.
+pub trait Persistent: Send {
+    /// The events describing changes to the task's state.
+    type Event: Serialize + DeserializeOwned + Send + 'static;
+
+    /// The journal the task's events are stored in.
+    fn journal(&self) -> Journal<Self::Event>;
+
+    /// Apply `event` to the task's state.
+    ///
+    /// Called for every stored event when the task starts, and by the
+    /// handlers for the events they persist.
+    fn apply(&mut self, event: Self::Event);
+}
+
+/// A [`Persistent`] task that can save its state in snapshots.
+///
+/// Tasks declared with `snapshots`, as in
+/// `#[task(message = M, handler, persistent, snapshots)]`, take a snapshot
+/// as configured with [`Journal::snapshot_every`]. On start, they
+/// [`restore`](Self::restore) the latest snapshot and replay only the events
+/// persisted after it, so long-lived tasks do not replay their whole
+/// history.
+///
+/// Snapshots are taken between messages, so handlers must apply the events
+/// they persist before returning. They are an optimization: if one cannot
+/// be saved, the task keeps running and recovers from its events instead.
+pub trait Snapshots: Persistent {
+    /// The serialized state of the task.
+    type Snapshot: Serialize + DeserializeOwned + Send + 'static;
+
+    /// Capture the task's state.
+    fn snapshot(&self) -> Self::Snapshot;
+
+    /// Replace the task's state with `snapshot`.
+    fn restore(&mut self, snapshot: Self::Snapshot);
+}
+
+/// The journal of a running task and the number of its next event.
+struct Writer<E> {
+    journal: Journal<E>,
+    next: u64,
+    /// Number of events covered by the latest snapshot
+    snapshot: u64,
+}
+
+/// Replay the events of `journal` from event number `from` into `task`, and
+/// let `ctx` persist further events.
+///
+/// # Panics
+///
+/// Panics if the journal cannot be read.
+async fn replay<S, M>(task: &mut S, ctx: &mut Context<M>, journal: Journal<S::Event>, from: u64)
+where
+    S: Persistent,
+{
+    let events = match journal.read(from).await {
+        Ok(events) => events,
+        Err(err) => panic!("failed to recover from journal `{}`: {err}", journal.id()),
+    };
+
+    let mut next = from;
+    for (seq, event) in events {
+        task.apply(event);
+        next = seq + 1;
+    }
+
+    ctx.journal = Some(Box::new(Writer {
+        journal,
+        next,
+        snapshot: from,
+    }));
+}
+
+/// Rebuild `task` from its journal and let `ctx` persist further events.
+///
+/// This is typically called by the generated code and not by user code directly.
+///
+/// # Panics
+///
+/// Panics if the journal cannot be read.
+#[doc(hidden)]
+pub async fn __recover<S, M>(task: &mut S, ctx: &mut Context<M>)
+where
+    S: Persistent,
+{
+    let journal = task.journal();
+    replay(task, ctx, journal, 0).await;
+}
+
+/// Rebuild `task` from its latest snapshot and the events after it, and
+/// let `ctx` persist further events.
+///
+/// This is typically called by the generated code and not by user code directly.
+///
+/// # Panics
+///
+/// Panics if the journal or the snapshot cannot be read.
+#[doc(hidden)]
+pub async fn __recover_snapshot<S, M>(task: &mut S, ctx: &mut Context<M>)
+where
+    S: Snapshots,
+{
+    let journal = task.journal();
+    let snapshot = journal
+        .backend
+        .load_snapshot(&journal.id)
+        .await
+        .and_then(|snapshot| {
+            snapshot
+                .map(|(seq, snapshot)| Ok((seq, serde_json::from_slice(&snapshot)?)))
+                .transpose()
+        });
+
+    let from = match snapshot {
+        Ok(Some((seq, snapshot))) => {
+            task.restore(snapshot);
+            seq
+        }
+        Ok(None) => 0,
+        Err(err) => panic!(
+            "failed to load snapshot of journal `{}`: {err}",
+            journal.id()
+        ),
+    };
+
+    replay(task, ctx, journal, from).await;
+}
+
+/// Snapshot `task` if enough events were persisted since the last snapshot.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub async fn __snapshot<S, M>(task: &S, ctx: &mut Context<M>)
+where
+    S: Snapshots,
+{
+    let Some(writer) = ctx
+        .journal
+        .as_mut()
+        .and_then(|writer| writer.downcast_mut::<Writer<S::Event>>())
+    else {
+        return;
+    };
+    let Some(every) = writer.journal.snapshot_every else {
+        return;
+    };
+    if writer.next - writer.snapshot < every {
+        return;
+    }
+
+    let saved = match serde_json::to_vec(&task.snapshot()) {
+        Ok(snapshot) => {
+            let journal = &writer.journal;
+            journal
+                .backend
+                .save_snapshot(&journal.id, writer.next, snapshot)
+                .await
+        }
+        Err(err) => Err(err.into()),
+    };
+
+    match saved {
+        Ok(()) => writer.snapshot = writer.next,
+        Err(err) => trace::snapshot_failed(writer.journal.id(), &err),
+    }
+}
+
+impl<M> Context<M> {
+    /// Append `event` to the task's journal, returning its event number.
+    ///
+    /// The event is stored, but not applied: apply it with
+    /// [`Persistent::apply`] once this succeeded, so the state never holds
+    /// changes that would be lost on a restart.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the event could not be stored. The task's state
+    /// and its journal then may have diverged; stopping the task lets a
+    /// supervisor restart it from the journal.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task is not declared with `persistent`, or `E` is not
+    /// its event type.
+    pub async fn persist<E>(&mut self, event: &E) -> Result<u64, JournalError>
+    where
+        E: Serialize + 'static,
+    {
+        let writer = self
+            .journal
+            .as_mut()
+            .expect("`persist` requires a task declared with `#[task(..., persistent)]`")
+            .downcast_mut::<Writer<E>>()
+            .expect("event is not of the task's event type");
+
+        let seq = writer.next;
+        writer.journal.append(seq, event).await?;
+        writer.next += 1;
+
+        Ok(seq)
+    }
+}
+
+/// A [`JournalBackend`] keeping events in memory.
+///
+/// Events survive restarts of a task, but not of the process. Clones share
+/// their events.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryJournal {
+    journals: Arc<Mutex<HashMap<String, Vec<Vec<u8>>>>>,
+    snapshots: Arc<Mutex<HashMap<String, StoredSnapshot>>>,
+}
+
+impl MemoryJournal {
+    /// Create an empty backend.
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl JournalBackend for MemoryJournal {
+    fn append<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        event: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>> {
+        let mut journals = self.journals.lock().unwrap();
+        let events = journals.entry(id.to_string()).or_default();
+
+        let result = if seq == events.len() as u64 {
+            events.push(event);
+            Ok(())
+        } else {
+            Err(JournalError::Conflict {
+                id: id.to_string(),
+                seq,
+            })
+        };
+
+        Box::pin(std::future::ready(result))
+    }
+
+    fn read<'a>(
+        &'a self,
+        id: &'a str,
+        from: u64,
+    ) -> BoxFuture<'a, Result<StoredEvents, JournalError>> {
+        let journals = self.journals.lock().unwrap();
+        let events = journals
+            .get(id)
+            .into_iter()
+            .flatten()
+            .enumerate()
+            .map(|(seq, event)| (seq as u64, event.clone()))
+            .skip_while(|(seq, _)| *seq < from)
+            .collect();
+
+        Box::pin(std::future::ready(Ok(events)))
+    }
+
+    fn save_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        snapshot: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>> {
+        let mut snapshots = self.snapshots.lock().unwrap();
+        snapshots.insert(id.to_string(), (seq, snapshot));
+        Box::pin(std::future::ready(Ok(())))
+    }
+
+    fn load_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSnapshot>, JournalError>> {
+        let snapshot = self.snapshots.lock().unwrap().get(id).cloned();
+        Box::pin(std::future::ready(Ok(snapshot)))
+    }
+}
+
+/// A [`JournalBackend`] appending events to files.
+///
+/// Every journal is a file in the backend's directory, named after the
+/// journal's id, with one event per line: its number and its JSON encoding.
+/// Appended events are synced to disk before [`Context::persist`] returns.
+/// The latest snapshot of a journal is kept in a file next to it, in the
+/// same format.
+///
+/// Since the number of events is tracked in memory, open a directory with
+/// a single backend at a time. Clones share their state.
+#[derive(Debug, Clone)]
+pub struct FileJournal {
+    inner: Arc<FileInner>,
+}
+
+#[derive(Debug)]
+struct FileInner {
+    dir: PathBuf,
+    /// Number of events per journal, counted on first access
+    lengths: Mutex<HashMap<String, u64>>,
+}
+
+impl FileJournal {
+    /// Store journals in `dir`, creating it if necessary.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created.
+    pub fn open(dir: impl AsRef<Path>) -> io::Result<Self> {
+        std::fs::create_dir_all(dir.as_ref())?;
+        Ok(FileJournal {
+            inner: Arc::new(FileInner {
+                dir: dir.as_ref().to_path_buf(),
+                lengths: Mutex::new(HashMap::new()),
+            }),
+        })
+    }
+
+    /// The file holding the journal `id`, or its snapshot.
+    fn path(&self, id: &str, extension: &str) -> PathBuf {
+        // Keep ids from escaping the directory
+        let name: String = id
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' | '.' => c,
+                _ => '_',
+            })
+            .collect();
+        self.inner.dir.join(format!("{name}.{extension}"))
+    }
+
+    /// Read the events of `id`, starting at `from`.
+    fn read_events(&self, id: &str, from: u64) -> Result<StoredEvents, JournalError> {
+        let file = match std::fs::File::open(self.path(id, "journal")) {
+            Ok(file) => file,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut events = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            let corrupted = || JournalError::Corrupted {
+                id: id.to_string(),
+                line: index + 1,
+            };
+
+            let (seq, event) = line.split_once(' ').ok_or_else(corrupted)?;
+            let seq: u64 = seq.parse().map_err(|_| corrupted())?;
+            if seq != index as u64 {
+                return Err(corrupted());
+            }
+            if seq >= from {
+                events.push((seq, event.as_bytes().to_vec()));
+            }
+        }
+
+        Ok(events)
+    }
+
+    /// Append `event` to `id`, as event number `seq`.
+    fn append_event(&self, id: &str, seq: u64, event: &[u8]) -> Result<(), JournalError> {
+        let mut lengths = self.inner.lengths.lock().unwrap();
+        let length = match lengths.get(id) {
+            Some(length) => *length,
+            None => self.read_events(id, 0)?.len() as u64,
+        };
+        if seq != length {
+            return Err(JournalError::Conflict {
+                id: id.to_string(),
+                seq,
+            });
+        }
+
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.path(id, "journal"))?;
+        let mut line = format!("{seq} ").into_bytes();
+        line.extend_from_slice(event);
+        line.push(b'\n');
+        file.write_all(&line)?;
+        file.sync_data()?;
+
+        lengths.insert(id.to_string(), seq + 1);
+        Ok(())
+    }
+}
+
+impl FileJournal {
+    /// Replace the snapshot of `id`.
+    fn save_snapshot_file(&self, id: &str, seq: u64, snapshot: &[u8]) -> Result<(), JournalError> {
+        // Write a new file and move it into place, so a crash never leaves
+        // a partial snapshot behind
+        let path = self.path(id, "snapshot");
+        let partial = self.path(id, "snapshot.partial");
+
+        let mut file = std::fs::File::create(&partial)?;
+        let mut contents = format!("{seq} ").into_bytes();
+        contents.extend_from_slice(snapshot);
+        file.write_all(&contents)?;
+        file.sync_data()?;
+        std::fs::rename(partial, path)?;
+
+        Ok(())
+    }
+
+    /// Load the snapshot of `id`.
+    fn load_snapshot_file(&self, id: &str) -> Result<Option<StoredSnapshot>, JournalError> {
+        let contents = match std::fs::read(self.path(id, "snapshot")) {
+            Ok(contents) => contents,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+
+        let corrupted = || JournalError::Corrupted {
+            id: id.to_string(),
+            line: 1,
+        };
+        let space = contents.iter().position(|byte| *byte == b' ');
+        let (seq, snapshot) = contents.split_at(space.ok_or_else(corrupted)?);
+        let seq = std::str::from_utf8(seq)
+            .ok()
+            .and_then(|seq| seq.parse().ok())
+            .ok_or_else(corrupted)?;
+
+        Ok(Some((seq, snapshot[1..].to_vec())))
+    }
+}
+
+impl JournalBackend for FileJournal {
+    fn append<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        event: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>> {
+        Box::pin(std::future::ready(self.append_event(id, seq, &event)))
+    }
+
+    fn read<'a>(
+        &'a self,
+        id: &'a str,
+        from: u64,
+    ) -> BoxFuture<'a, Result<StoredEvents, JournalError>> {
+        Box::pin(std::future::ready(self.read_events(id, from)))
+    }
+
+    fn save_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+        seq: u64,
+        snapshot: Vec<u8>,
+    ) -> BoxFuture<'a, Result<(), JournalError>> {
+        Box::pin(std::future::ready(
+            self.save_snapshot_file(id, seq, &snapshot),
+        ))
+    }
+
+    fn load_snapshot<'a>(
+        &'a self,
+        id: &'a str,
+    ) -> BoxFuture<'a, Result<Option<StoredSnapshot>, JournalError>> {
+        Box::pin(std::future::ready(self.load_snapshot_file(id)))
+    }
+}
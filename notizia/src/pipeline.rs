@@ -0,0 +1,274 @@
+//! Staged processing pipelines.
+//!
+//! A [`Pipeline`] wires a sequence of [`Stage`]s together so that the output of
+//! one stage becomes the input of the next. Every stage runs on its own pool of
+//! worker tasks, and the message type changes from stage to stage as each one
+//! converts its input into the next stage's input.
+//!
+//! Shutdown propagates downstream in order: closing the pipeline input lets the
+//! first stage drain its backlog, after which the second stage's input closes,
+//! and so on until the last stage has finished.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::pipeline::{Pipeline, Stage};
+//! use std::time::Duration;
+//!
+//! struct Parse;
+//!
+//! impl Stage for Parse {
+//!     type In = String;
+//!     type Out = u32;
+//!
+//!     async fn process(&self, input: String) -> u32 {
+//!         input.trim().parse().unwrap_or_default()
+//!     }
+//! }
+//!
+//! struct Double;
+//!
+//! impl Stage for Double {
+//!     type In = u32;
+//!     type Out = u32;
+//!
+//!     async fn process(&self, input: u32) -> u32 {
+//!         input * 2
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let pipeline = Pipeline::new().stage(Parse, 4).stage(Double, 2).build();
+//!
+//! pipeline.send("21".to_string()).unwrap();
+//! assert_eq!(pipeline.recv().await.unwrap(), 42);
+//!
+//! pipeline.shutdown(Duration::from_secs(1)).await.unwrap();
+//! # }
+//! ```
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Mutex;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::core::errors::{RecvError, RecvResult, SendResult};
+use crate::core::lifecycle::panic_message;
+use crate::runtime::{self, JoinHandle};
+use crate::{ShutdownError, ShutdownResult, TerminateReason};
+
+/// A single processing step of a [`Pipeline`].
+///
+/// A stage converts values of type [`In`](Self::In) into values of type
+/// [`Out`](Self::Out). The same stage instance is shared between all workers
+/// of the stage, so any mutable state must be synchronized.
+pub trait Stage: Send + Sync + 'static {
+    /// Type of the values this stage consumes.
+    type In: Send + 'static;
+
+    /// Type of the values this stage produces for the next stage.
+    type Out: Send + 'static;
+
+    /// Process a single value.
+    fn process(&self, input: Self::In) -> impl Future<Output = Self::Out> + Send;
+}
+
+type Wiring<In, Out> =
+    Box<dyn FnOnce(UnboundedReceiver<In>, &mut Vec<Vec<JoinHandle<()>>>) -> UnboundedReceiver<Out>>;
+
+/// Builder for a staged processing pipeline.
+///
+/// Stages are added with [`stage`](Self::stage) and the pipeline is spawned
+/// with [`build`](Self::build). The type parameters track the input type of
+/// the first stage and the output type of the last stage, so mismatched stages
+/// are rejected at compile time.
+pub struct Pipeline<In, Out> {
+    wiring: Wiring<In, Out>,
+}
+
+impl<T> Pipeline<T, T>
+where
+    T: Send + 'static,
+{
+    /// Create an empty pipeline.
+    ///
+    /// An empty pipeline passes its input straight through to its output.
+    pub fn new() -> Self {
+        Pipeline {
+            wiring: Box::new(|receiver, _| receiver),
+        }
+    }
+}
+
+impl<T> Default for Pipeline<T, T>
+where
+    T: Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<In, Out> Pipeline<In, Out>
+where
+    In: Send + 'static,
+    Out: Send + 'static,
+{
+    /// Append a stage running on `workers` concurrent worker tasks.
+    ///
+    /// The stage consumes the output of the previous stage. A worker count of
+    /// zero is treated as one.
+    pub fn stage<S>(self, stage: S, workers: usize) -> Pipeline<In, S::Out>
+    where
+        S: Stage<In = Out>,
+    {
+        let previous = self.wiring;
+        let stage = Arc::new(stage);
+
+        Pipeline {
+            wiring: Box::new(move |receiver, stages| {
+                let input = Arc::new(Mutex::new(previous(receiver, stages)));
+                let (sender, output) = unbounded_channel();
+
+                let workers = (0..workers.max(1))
+                    .map(|_| spawn_worker(stage.clone(), input.clone(), sender.clone()))
+                    .collect();
+                stages.push(workers);
+
+                output
+            }),
+        }
+    }
+
+    /// Spawn all stages and return a handle to the running pipeline.
+    pub fn build(self) -> PipelineHandle<In, Out> {
+        let (sender, receiver) = unbounded_channel();
+        let mut stages = Vec::new();
+        let output = (self.wiring)(receiver, &mut stages);
+
+        PipelineHandle {
+            sender,
+            output: Mutex::new(output),
+            stages,
+        }
+    }
+}
+
+fn spawn_worker<S>(
+    stage: Arc<S>,
+    input: Arc<Mutex<UnboundedReceiver<S::In>>>,
+    output: UnboundedSender<S::Out>,
+) -> JoinHandle<()>
+where
+    S: Stage,
+{
+    runtime::spawn("pipeline-stage", async move {
+        loop {
+            // Only hold the lock while waiting for the next value so that
+            // other workers of this stage can process concurrently
+            let next = input.lock().await.recv().await;
+            let Some(value) = next else {
+                break;
+            };
+
+            if output.send(stage.process(value).await).is_err() {
+                break;
+            }
+        }
+    })
+}
+
+/// Handle for a running [`Pipeline`].
+///
+/// Values sent into the handle flow through all stages in order. Values
+/// produced by the last stage can be received with [`recv`](Self::recv).
+pub struct PipelineHandle<In, Out> {
+    sender: UnboundedSender<In>,
+    output: Mutex<UnboundedReceiver<Out>>,
+    stages: Vec<Vec<JoinHandle<()>>>,
+}
+
+impl<In, Out> PipelineHandle<In, Out> {
+    /// Send a value into the first stage of the pipeline.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the workers of
+    /// the first stage have terminated.
+    pub fn send(&self, input: In) -> SendResult<In> {
+        self.sender.send(input)
+    }
+
+    /// Receive the next value produced by the last stage.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`](crate::RecvError::Closed) once all stages
+    /// have terminated and every output has been received.
+    pub async fn recv(&self) -> RecvResult<Out> {
+        self.output
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(RecvError::Closed)
+    }
+
+    /// Number of stages in this pipeline.
+    pub fn stages(&self) -> usize {
+        self.stages.len()
+    }
+
+    /// Gracefully shut down the pipeline with a timeout.
+    ///
+    /// This closes the pipeline input and waits for each stage to drain its
+    /// backlog, starting with the first stage. A stage only observes its input
+    /// closing once every worker of the previous stage has finished.
+    ///
+    /// Outputs that have not been received are discarded.
+    ///
+    /// Returns [`TerminateReason::Panic`] if any worker panicked while
+    /// processing a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ShutdownError::Timeout`] if the stages did not drain within
+    /// the timeout. In this case, all remaining workers are aborted.
+    pub async fn shutdown(self, timeout: Duration) -> ShutdownResult {
+        drop(self.sender);
+
+        let stages = self.stages;
+        let aborts: Vec<_> = stages.iter().flatten().map(|h| h.abort_handle()).collect();
+
+        let drain = async move {
+            let mut reason = TerminateReason::Normal;
+
+            for workers in stages {
+                for worker in workers {
+                    match worker.await {
+                        Ok(()) => {}
+                        Err(err) if err.is_panic() => {
+                            if reason == TerminateReason::Normal {
+                                reason = TerminateReason::Panic(panic_message(&*err.into_panic()));
+                            }
+                        }
+                        Err(err) => return Err(ShutdownError::JoinError(err)),
+                    }
+                }
+            }
+
+            Ok(reason)
+        };
+
+        match runtime::timeout(timeout, drain).await {
+            Ok(result) => result,
+            Err(_elapsed) => {
+                aborts.iter().for_each(|h| h.abort());
+                Err(ShutdownError::Timeout)
+            }
+        }
+    }
+}
@@ -8,8 +8,8 @@
 //! ```
 //!
 //! This brings into scope:
-//! - Core types: [`Mailbox`], error types ([`RecvError`], [`RecvResult`], [`SendResult`], [`CallError`], [`CallResult`])
-//! - Task types: [`Task`], [`Runnable`], [`TaskHandle`], [`TaskRef`]
+//! - Core types: [`Mailbox`], [`Reply`], [`CallFuture`], [`StreamReply`], [`ReplyStream`], [`StreamEnd`], error types ([`RecvError`], [`RecvResult`], [`SendResult`], [`CallError`], [`CallResult`])
+//! - Task types: [`Task`], [`Runnable`], [`Handler`], [`Context`], [`TaskHandle`], [`TaskRef`], [`TaskId`]
 //! - Macros: [`spawn!`], [`send!`], [`recv!`]
 //! - Derive macro: [`Task`] (for `#[derive(Task)]`)
 //!
@@ -19,10 +19,12 @@
 //! use notizia::{call, cast};
 //! ```
 
-pub use crate::core::Mailbox;
 pub use crate::core::errors::{CallError, CallResult, RecvError, RecvResult, SendResult};
 pub use crate::core::lifecycle::{ShutdownError, ShutdownResult, TerminateReason};
-pub use crate::task::{Runnable, Task, TaskHandle, TaskRef};
+pub use crate::core::{
+    CallFuture, CorrelationId, Mailbox, Reply, ReplyStream, StreamEnd, StreamReply,
+};
+pub use crate::task::{Context, Handler, Runnable, Task, TaskHandle, TaskId, TaskRef, TaskSet};
 
 // Macros are already exported at crate root via #[macro_export]
 // They're automatically available when you use notizia::prelude::*
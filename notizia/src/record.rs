@@ -0,0 +1,261 @@
+//! Recording and replaying message traces.
+//!
+//! A [`Recorder`] is a [`Middleware`] that appends every message delivered to
+//! a task to a trace file, one JSON object per line:
+//!
+//! ```json
+//! {"seq":0,"type":"app::OrderMsg","timestamp_micros":1760000000000000,"payload":{"Place":{"id":7}}}
+//! ```
+//!
+//! The payload is recorded when the message type implements
+//! [`Serialize`](serde::Serialize) and the recorder was created with
+//! [`Recorder::serialized`]; otherwise it is `null`. A [`Trace`] loads a
+//! recorded file and [replays](Trace::replay) its messages into a task, so a
+//! bug observed in production can be reproduced deterministically in a test.
+//!
+//! This module requires the `record` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::record::{Recorder, Trace};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! enum OrderMsg {
+//!     Place { id: u32 },
+//!     Cancel { id: u32 },
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = OrderMsg)]
+//! struct Orders;
+//!
+//! impl Runnable<OrderMsg> for Orders {
+//!     async fn start(&self) {
+//!         while let Ok(msg) = recv!(self) {
+//!             println!("{msg:?}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // In production
+//! let orders = Orders
+//!     .builder()
+//!     .middleware(Recorder::serialized("orders.trace")?)
+//!     .spawn();
+//!
+//! // In a test
+//! let orders = spawn!(Orders);
+//! Trace::load("orders.trace")?.replay(&orders.this())?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::task::{Middleware, TaskRef};
+
+/// A recorded message.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Entry {
+    /// Position of the message in the trace, starting at 0
+    pub seq: u64,
+    /// Name of the message type
+    #[serde(rename = "type")]
+    pub message_type: String,
+    /// Time the message was delivered, in microseconds since the Unix epoch
+    pub timestamp_micros: u64,
+    /// The serialized message, if it could be serialized
+    pub payload: Option<Value>,
+}
+
+/// Middleware appending every delivered message to a trace file.
+///
+/// Write errors are ignored, so a full disk never stops the recorded task.
+pub struct Recorder<M> {
+    state: Mutex<State>,
+    encode: fn(&M) -> Option<Value>,
+}
+
+struct State {
+    writer: BufWriter<File>,
+    seq: u64,
+}
+
+impl<M> Recorder<M> {
+    /// Record the type and delivery time of every message to the file at
+    /// `path`, without payloads.
+    ///
+    /// An existing file is truncated.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn new(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::create(path.as_ref(), |_| None)
+    }
+
+    /// Like [`new`](Self::new), but also record the serialized messages, so
+    /// the trace can be [replayed](Trace::replay).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn serialized(path: impl AsRef<Path>) -> io::Result<Self>
+    where
+        M: Serialize,
+    {
+        Self::create(path.as_ref(), |msg| serde_json::to_value(msg).ok())
+    }
+
+    fn create(path: &Path, encode: fn(&M) -> Option<Value>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+
+        Ok(Recorder {
+            state: Mutex::new(State {
+                writer: BufWriter::new(file),
+                seq: 0,
+            }),
+            encode,
+        })
+    }
+}
+
+impl<M> Middleware<M> for Recorder<M> {
+    fn before_handle(&self, msg: &M) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let entry = Entry {
+            seq: state.seq,
+            message_type: std::any::type_name::<M>().to_string(),
+            timestamp_micros: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |now| now.as_micros() as u64),
+            payload: (self.encode)(msg),
+        };
+        state.seq += 1;
+
+        // Flush every entry, so the trace survives a crash of the process
+        if serde_json::to_writer(&mut state.writer, &entry).is_ok() {
+            let _ = writeln!(state.writer);
+            let _ = state.writer.flush();
+        }
+        true
+    }
+}
+
+/// Errors that can occur while loading or replaying a trace.
+#[derive(Debug, thiserror::Error)]
+pub enum ReplayError {
+    /// The trace file could not be read
+    #[error("failed to read trace: {0}")]
+    Io(#[from] io::Error),
+    /// A line of the trace is not a valid entry, or its payload does not
+    /// match the message type
+    #[error("invalid trace entry {seq}: {source}")]
+    Decode {
+        /// Position of the entry in the trace
+        seq: u64,
+        /// The underlying JSON error
+        source: serde_json::Error,
+    },
+    /// A message was recorded without its payload
+    #[error("trace entry {0} has no payload")]
+    MissingPayload(u64),
+    /// The task's mailbox is closed
+    #[error("task stopped before the trace was replayed")]
+    Closed,
+}
+
+/// A recorded message trace.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Trace {
+    entries: Vec<Entry>,
+}
+
+impl Trace {
+    /// Load the trace recorded to the file at `path`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or contains invalid
+    /// entries.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReplayError> {
+        let reader = BufReader::new(File::open(path)?);
+        let mut entries = Vec::new();
+
+        for (line, seq) in reader.lines().zip(0..) {
+            let entry = serde_json::from_str(&line?)
+                .map_err(|source| ReplayError::Decode { seq, source })?;
+            entries.push(entry);
+        }
+
+        Ok(Trace { entries })
+    }
+
+    /// The recorded entries, in delivery order.
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// Decode the recorded messages.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a message was recorded without payload or its
+    /// payload is not a valid `M`.
+    pub fn messages<M>(&self) -> Result<Vec<M>, ReplayError>
+    where
+        M: DeserializeOwned,
+    {
+        self.entries
+            .iter()
+            .map(|entry| {
+                let payload = entry
+                    .payload
+                    .clone()
+                    .ok_or(ReplayError::MissingPayload(entry.seq))?;
+                serde_json::from_value(payload).map_err(|source| ReplayError::Decode {
+                    seq: entry.seq,
+                    source,
+                })
+            })
+            .collect()
+    }
+
+    /// Send the recorded messages to `task`, in the order they were
+    /// delivered.
+    ///
+    /// All messages are decoded before the first one is sent, so a broken
+    /// trace never replays partially.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the messages cannot be [decoded](Self::messages)
+    /// or the task's mailbox is closed.
+    pub fn replay<M>(&self, task: &TaskRef<M>) -> Result<(), ReplayError>
+    where
+        M: DeserializeOwned + Send + 'static,
+    {
+        for msg in self.messages::<M>()? {
+            task.send(msg).map_err(|_| ReplayError::Closed)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,528 @@
+//! Named task registry.
+//!
+//! A [`Registry`] maps names to running tasks, so tasks can be looked up by
+//! a well-known name instead of passing [`TaskRef`]s around. A task that is
+//! restarted simply registers itself again under the same name; everyone
+//! resolving the name afterwards reaches the new instance.
+//!
+//! Registrations are weak: they neither keep a task's mailbox open nor
+//! prevent graceful shutdown. Once a task has terminated, looking up its name
+//! returns `None`.
+//!
+//! [`register_with`](Registry::register_with) lets callers decide what
+//! happens if another running task holds the name: fail, replace and stop
+//! the other task, or queue behind it until it has terminated. Queued tasks
+//! take the name over one after another, in the order they were queued.
+//! Every registration and conflict is reported to
+//! [subscribers](Registry::subscribe).
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::registry::Registry;
+//!
+//! #[derive(Debug, Clone)]
+//! enum LogMsg {
+//!     Line(String),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = LogMsg)]
+//! struct Logger;
+//!
+//! impl Runnable<LogMsg> for Logger {
+//!     async fn start(&self) {
+//!         while let Ok(LogMsg::Line(line)) = recv!(self) {
+//!             println!("{line}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let registry = Registry::new();
+//! let logger = spawn!(Logger);
+//! registry.register("logger", &logger.this());
+//!
+//! if let Some(logger) = registry.whereis::<LogMsg>("logger") {
+//!     logger.send(LogMsg::Line("hello".into())).unwrap();
+//! }
+//! # }
+//! ```
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, RwLock};
+
+use futures::FutureExt;
+use futures::future::{self, BoxFuture};
+use tokio::sync::mpsc::WeakUnboundedSender;
+use tokio::sync::{Notify, broadcast};
+
+use crate::core::mailbox::Capacity;
+#[cfg(feature = "spill")]
+use crate::core::spill::Spill;
+#[cfg(feature = "spsc")]
+use crate::core::spsc::Ring;
+use crate::core::{Envelope, SystemSignal};
+use crate::runtime;
+use crate::task::{Control, TaskId, TaskRef, id};
+
+type Entry = Box<dyn Registered>;
+
+/// A registration of any message type.
+trait Registered: Send + Sync {
+    fn id(&self) -> TaskId;
+
+    #[cfg(feature = "metrics")]
+    fn name(&self) -> &'static str;
+
+    /// Whether the task still accepts messages.
+    fn is_alive(&self) -> bool;
+
+    /// Wait for the task to stop accepting messages.
+    fn terminated(&self) -> BoxFuture<'static, ()>;
+
+    /// Signal the task to stop.
+    fn stop(&self);
+
+    fn as_any(&self) -> &dyn Any;
+}
+
+/// A weak registration of a task with message type `T`.
+struct Registration<T> {
+    sender: WeakUnboundedSender<Envelope<T>>,
+    id: TaskId,
+    name: &'static str,
+    path: &'static str,
+    capacity: Option<Arc<Capacity>>,
+    #[cfg(feature = "spill")]
+    spill: Option<Arc<Spill<T>>>,
+    #[cfg(feature = "spsc")]
+    ring: Option<Arc<Ring<T>>>,
+    control: Option<Control>,
+}
+
+impl<T> Registration<T>
+where
+    T: Send + 'static,
+{
+    fn new(task: &TaskRef<T>) -> Self {
+        Registration {
+            sender: task.downgrade(),
+            id: task.id(),
+            name: task.name(),
+            path: task.path(),
+            capacity: task.capacity().cloned(),
+            #[cfg(feature = "spill")]
+            spill: task.spill().cloned(),
+            #[cfg(feature = "spsc")]
+            ring: task.ring().cloned(),
+            control: task.control().cloned(),
+        }
+    }
+
+    /// The registered task, unless it has terminated.
+    fn task(&self) -> Option<TaskRef<T>> {
+        let sender = self.sender.upgrade()?;
+
+        let task = TaskRef::with_identity(sender, self.id, self.name)
+            .with_path(self.path)
+            .with_capacity(self.capacity.clone())
+            .with_control(self.control.clone());
+        #[cfg(feature = "spill")]
+        let task = task.with_spill(self.spill.clone());
+        #[cfg(feature = "spsc")]
+        let task = task.with_ring(self.ring.clone());
+
+        Some(task).filter(|task| !task.is_closed())
+    }
+}
+
+impl<T> Registered for Registration<T>
+where
+    T: Send + 'static,
+{
+    fn id(&self) -> TaskId {
+        self.id
+    }
+
+    #[cfg(feature = "metrics")]
+    fn name(&self) -> &'static str {
+        self.name
+    }
+
+    fn is_alive(&self) -> bool {
+        self.sender
+            .upgrade()
+            .is_some_and(|sender| !sender.is_closed())
+    }
+
+    fn terminated(&self) -> BoxFuture<'static, ()> {
+        if let Some(terminated) = id::terminated(self.id) {
+            return terminated.boxed();
+        }
+
+        // Not spawned, or terminated with its mailbox about to close. Waiting
+        // with a sender keeps the mailbox open, so this is the fallback only.
+        match self.sender.upgrade() {
+            Some(sender) => async move { sender.closed().await }.boxed(),
+            None => future::ready(()).boxed(),
+        }
+    }
+
+    fn stop(&self) {
+        if let Some(sender) = self.sender.upgrade() {
+            let _ = sender.send(Envelope::System(SystemSignal::Stop));
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// What [`Registry::register_with`] does if the name is held by another
+/// running task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Conflict {
+    /// Fail with [`NameTaken`]
+    Error,
+    /// Replace the registration and stop the task holding the name with
+    /// [`SystemSignal::Stop`]
+    Replace,
+    /// Register the task once the task holding the name and all tasks
+    /// queued before it have terminated
+    QueueBehind,
+}
+
+/// The name passed to [`Registry::register_with`] is held by another task.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("name `{name}` is already registered by task {holder}")]
+pub struct NameTaken {
+    /// The requested name
+    pub name: String,
+    /// The task holding the name
+    pub holder: TaskId,
+}
+
+/// A change of the registrations, see [`Registry::subscribe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RegistryEvent {
+    /// `task` was registered under `name`
+    Registered {
+        /// The name
+        name: String,
+        /// The registered task
+        task: TaskId,
+    },
+    /// `task` was not registered under `name`, since `holder` holds it
+    Rejected {
+        /// The name
+        name: String,
+        /// The rejected task
+        task: TaskId,
+        /// The task holding the name
+        holder: TaskId,
+    },
+    /// `task` replaced `previous` under `name`, which was signaled to stop
+    Replaced {
+        /// The name
+        name: String,
+        /// The new task
+        task: TaskId,
+        /// The replaced task
+        previous: TaskId,
+    },
+    /// `task` is registered under `name` once `holder` has terminated
+    Queued {
+        /// The name
+        name: String,
+        /// The waiting task
+        task: TaskId,
+        /// The task holding the name
+        holder: TaskId,
+    },
+}
+
+/// A shared map from names to running tasks.
+///
+/// Cloning a registry is cheap; all clones share the same registrations.
+#[derive(Clone)]
+pub struct Registry {
+    entries: Arc<RwLock<HashMap<String, Entry>>>,
+    /// Registrations queued behind the holder of a name, oldest first
+    queued: Arc<Mutex<HashMap<String, VecDeque<Entry>>>>,
+    /// Notified whenever a registration is added or removed
+    changed: Arc<Notify>,
+    events: broadcast::Sender<RegistryEvent>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Registry {
+            entries: Arc::default(),
+            queued: Arc::default(),
+            changed: Arc::default(),
+            events: broadcast::channel(64).0,
+        }
+    }
+}
+
+impl Registry {
+    /// Create an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a task under `name`.
+    ///
+    /// An existing registration for the same name is replaced, without
+    /// stopping the task it belongs to. Use [`register_with`](Self::register_with)
+    /// to decide what happens if another task holds the name.
+    pub fn register<T>(&self, name: impl Into<String>, task: &TaskRef<T>)
+    where
+        T: Send + 'static,
+    {
+        let mut entries = self.entries.write().unwrap();
+        self.insert(&mut entries, name.into(), Box::new(Registration::new(task)));
+    }
+
+    /// Register a task under `name`, resolving a conflict with another
+    /// running task holding the name as `conflict` says.
+    ///
+    /// Registrations of terminated tasks and of the task itself never
+    /// conflict. Every outcome is reported to [subscribers](Self::subscribe).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`NameTaken`] if the name is held by another task and
+    /// `conflict` is [`Conflict::Error`].
+    ///
+    /// # Panics
+    ///
+    /// With [`Conflict::QueueBehind`], panics if called outside of a Tokio
+    /// runtime and no other [runtime](crate::runtime) backend is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use notizia::registry::{Conflict, Registry};
+    /// # #[derive(Task)]
+    /// # #[task(message = String)]
+    /// # struct Logger;
+    /// # impl Runnable<String> for Logger {
+    /// #     async fn start(&self) {}
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let registry = Registry::new();
+    /// let first = spawn!(Logger);
+    /// let second = spawn!(Logger);
+    ///
+    /// registry.register_with("logger", &first.this(), Conflict::Error).unwrap();
+    /// assert!(registry.register_with("logger", &second.this(), Conflict::Error).is_err());
+    ///
+    /// // Takes over once `first` has terminated
+    /// registry.register_with("logger", &second.this(), Conflict::QueueBehind).unwrap();
+    /// # }
+    /// ```
+    pub fn register_with<T>(
+        &self,
+        name: impl Into<String>,
+        task: &TaskRef<T>,
+        conflict: Conflict,
+    ) -> Result<(), NameTaken>
+    where
+        T: Send + 'static,
+    {
+        let name = name.into();
+        let mut entries = self.entries.write().unwrap();
+
+        // Keep the order of the tasks already queued for the name
+        if conflict == Conflict::QueueBehind
+            && let Some(queue) = self.queued.lock().unwrap().get_mut(&name)
+        {
+            let holder = entries
+                .get(&name)
+                .or(queue.front())
+                .expect("queues are removed once empty");
+            self.emit(RegistryEvent::Queued {
+                name,
+                task: task.id(),
+                holder: holder.id(),
+            });
+            queue.push_back(Box::new(Registration::new(task)));
+            return Ok(());
+        }
+
+        let Some(holder) = entries
+            .get(&name)
+            .filter(|entry| entry.is_alive() && entry.id() != task.id())
+        else {
+            self.insert(&mut entries, name, Box::new(Registration::new(task)));
+            return Ok(());
+        };
+
+        let holder_id = holder.id();
+        match conflict {
+            Conflict::Error => {
+                self.emit(RegistryEvent::Rejected {
+                    name: name.clone(),
+                    task: task.id(),
+                    holder: holder_id,
+                });
+                Err(NameTaken {
+                    name,
+                    holder: holder_id,
+                })
+            }
+            Conflict::Replace => {
+                holder.stop();
+                self.emit(RegistryEvent::Replaced {
+                    name: name.clone(),
+                    task: task.id(),
+                    previous: holder_id,
+                });
+                self.insert(&mut entries, name, Box::new(Registration::new(task)));
+                Ok(())
+            }
+            Conflict::QueueBehind => {
+                self.emit(RegistryEvent::Queued {
+                    name: name.clone(),
+                    task: task.id(),
+                    holder: holder_id,
+                });
+                let queue = VecDeque::from([Box::new(Registration::new(task)) as Entry]);
+                self.queued.lock().unwrap().insert(name.clone(), queue);
+                self.hand_over(name);
+                Ok(())
+            }
+        }
+    }
+
+    /// Hand `name` to the registrations queued for it, one after another,
+    /// each once the task holding the name has terminated.
+    fn hand_over(&self, name: String) {
+        let registry = self.clone();
+        runtime::spawn("registry", async move {
+            loop {
+                let changed = registry.changed.notified();
+                let mut changed = std::pin::pin!(changed);
+
+                let terminated = {
+                    let mut entries = registry.entries.write().unwrap();
+                    let mut queued = registry.queued.lock().unwrap();
+                    let Some(queue) = queued.get_mut(&name) else {
+                        return;
+                    };
+
+                    let holder = entries
+                        .get(&name)
+                        .filter(|entry| entry.is_alive())
+                        .map(|entry| entry.id());
+                    match holder {
+                        // Registered directly while queued
+                        Some(holder) if queue.front().is_some_and(|next| next.id() == holder) => {
+                            queue.pop_front();
+                        }
+                        Some(_) => {}
+                        None => {
+                            // Skip the tasks that terminated while queued
+                            while let Some(next) = queue.pop_front() {
+                                if next.is_alive() {
+                                    registry.insert(&mut entries, name.clone(), next);
+                                    break;
+                                }
+                            }
+                        }
+                    }
+
+                    if queue.is_empty() {
+                        queued.remove(&name);
+                        return;
+                    }
+
+                    // Also wake up if the name is unregistered or taken over
+                    changed.as_mut().enable();
+                    match entries.get(&name) {
+                        Some(holder) => holder.terminated(),
+                        None => future::ready(()).boxed(),
+                    }
+                };
+
+                tokio::select! {
+                    () = terminated => {}
+                    () = changed => {}
+                }
+            }
+        });
+    }
+
+    fn insert(&self, entries: &mut HashMap<String, Entry>, name: String, entry: Entry) {
+        let id = entry.id();
+        #[cfg(feature = "metrics")]
+        let task = entry.name();
+
+        let previous = entries.insert(name.clone(), entry);
+
+        #[cfg(feature = "metrics")]
+        if previous.is_some_and(|previous| previous.id() != id) {
+            crate::metrics::restarted(task);
+        }
+        #[cfg(not(feature = "metrics"))]
+        let _ = previous;
+
+        self.changed.notify_waiters();
+        self.emit(RegistryEvent::Registered { name, task: id });
+    }
+
+    fn emit(&self, event: RegistryEvent) {
+        // Nobody may be subscribed
+        let _ = self.events.send(event);
+    }
+
+    /// Subscribe to registrations and name conflicts.
+    ///
+    /// Subscribers that fall behind by more than 64 events miss the oldest
+    /// ones.
+    pub fn subscribe(&self) -> broadcast::Receiver<RegistryEvent> {
+        self.events.subscribe()
+    }
+
+    /// Look up the task registered under `name`.
+    ///
+    /// Returns `None` if nothing is registered under the name, the registered
+    /// task has a different message type, or the task has terminated.
+    pub fn whereis<T>(&self, name: &str) -> Option<TaskRef<T>>
+    where
+        T: Send + 'static,
+    {
+        let entries = self.entries.read().unwrap();
+        entries
+            .get(name)?
+            .as_any()
+            .downcast_ref::<Registration<T>>()?
+            .task()
+    }
+
+    /// Remove the registration for `name`.
+    ///
+    /// Returns `true` if a registration existed.
+    pub fn unregister(&self, name: &str) -> bool {
+        let removed = self.entries.write().unwrap().remove(name).is_some();
+        self.changed.notify_waiters();
+        removed
+    }
+
+    /// Names of all current registrations.
+    ///
+    /// Registrations of terminated tasks are included until they are
+    /// replaced or [unregistered](Self::unregister).
+    pub fn names(&self) -> Vec<String> {
+        self.entries.read().unwrap().keys().cloned().collect()
+    }
+}
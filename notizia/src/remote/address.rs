@@ -0,0 +1,174 @@
+//! Serializable, location-transparent references to tasks.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::sync::{LazyLock, RwLock};
+
+use serde::{Deserialize, Serialize};
+
+use super::membership::NodeId;
+use super::{RemoteError, RemoteRef};
+use crate::core::Versioned;
+use crate::registry::Registry;
+
+/// Registries of the nodes running in this process.
+static HOSTED: LazyLock<RwLock<HashMap<NodeId, Registry>>> = LazyLock::new(Default::default);
+
+/// Resolve addresses of `node` to tasks registered in `registry`.
+pub(crate) fn host(node: &NodeId, registry: &Registry) {
+    HOSTED
+        .write()
+        .unwrap()
+        .insert(node.clone(), registry.clone());
+}
+
+/// Stop resolving addresses of `node` locally.
+pub(crate) fn unhost(node: &NodeId) {
+    HOSTED.write().unwrap().remove(node);
+}
+
+/// The address of a task on some node of the cluster.
+///
+/// An address names the node and the name the task is registered as there,
+/// along with where the node's listener is reached. Unlike a
+/// [`TaskRef`](crate::TaskRef), it can be serialized, so tasks can put
+/// addresses into messages to remote tasks, for example to tell them where
+/// to send their answer.
+///
+/// Sending resolves the address where it is used: on the node it points
+/// to, the message goes straight to the task's mailbox; everywhere else it
+/// is shipped to the node's listener like with a [`RemoteRef`]. A node
+/// resolves addresses locally while its
+/// [`Membership`](super::Membership) task runs.
+///
+/// # Example
+///
+/// ```
+/// use notizia::message;
+/// use notizia::remote::Address;
+///
+/// #[message(serde)]
+/// enum PriceMsg {
+///     Quote { item: String, reply_to: Address<QuoteMsg> },
+/// }
+///
+/// #[message(serde)]
+/// enum QuoteMsg {
+///     Price { cents: u64 },
+/// }
+///
+/// let reply_to = Address::<QuoteMsg>::new("node-a", "10.0.0.1:7400".parse().unwrap(), "quotes");
+/// let msg = PriceMsg::Quote { item: "tea".into(), reply_to };
+/// ```
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct Address<T> {
+    node: NodeId,
+    addr: SocketAddr,
+    name: String,
+    #[serde(skip)]
+    _message: PhantomData<fn(T)>,
+}
+
+impl<T> Clone for Address<T> {
+    fn clone(&self) -> Self {
+        Address {
+            node: self.node.clone(),
+            addr: self.addr,
+            name: self.name.clone(),
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T> PartialEq for Address<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node && self.addr == other.addr && self.name == other.name
+    }
+}
+
+impl<T> Eq for Address<T> {}
+
+impl<T> fmt::Debug for Address<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Address")
+            .field("node", &self.node)
+            .field("addr", &self.addr)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for Address<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.node, self.name)
+    }
+}
+
+impl<T> Address<T> {
+    /// The address of the task registered as `name` on `node`, whose
+    /// listener is reached at `addr`.
+    pub fn new(node: impl Into<NodeId>, addr: SocketAddr, name: impl Into<String>) -> Self {
+        Address {
+            node: node.into(),
+            addr,
+            name: name.into(),
+            _message: PhantomData,
+        }
+    }
+
+    /// The node the task runs on.
+    pub fn node(&self) -> &NodeId {
+        &self.node
+    }
+
+    /// Where the node's listener is reached.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The name the task is registered as on its node.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the task runs on a node in this process.
+    pub fn is_local(&self) -> bool {
+        HOSTED.read().unwrap().contains_key(&self.node)
+    }
+
+    /// A reference sending to the task through the node's listener, even if
+    /// the task is local.
+    pub fn remote(&self) -> RemoteRef<T> {
+        RemoteRef::new(self.addr, self.name.as_str())
+    }
+
+    /// Send a message to the task (fire-and-forget).
+    ///
+    /// # Errors
+    ///
+    /// For a local task, returns [`RemoteError::NotRunning`] if no running
+    /// task is registered under the name. For a remote task, returns the
+    /// errors of [`RemoteRef::send`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task is remote and this is called outside of a Tokio
+    /// runtime.
+    pub fn send(&self, msg: T) -> Result<(), RemoteError>
+    where
+        T: Serialize + Versioned + Send + 'static,
+    {
+        let registry = HOSTED.read().unwrap().get(&self.node).cloned();
+        match registry {
+            Some(registry) => registry
+                .whereis::<T>(&self.name)
+                .ok_or_else(|| RemoteError::NotRunning(self.name.clone()))?
+                .send(msg)
+                .map_err(|_| RemoteError::NotRunning(self.name.clone())),
+            None => self.remote().send(msg),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Length-prefixed frames carrying messages to named tasks.
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use super::RemoteError;
+use crate::core::Versioned;
+
+/// Maximum length of a frame, excluding its length prefix.
+pub const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+/// A message addressed to a named task.
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Frame {
+    /// Name the receiving task is exposed as
+    pub(crate) to: String,
+    /// Schema version of the message, see [`Versioned`]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) version: Option<u32>,
+    /// The serialized message
+    pub(crate) payload: Value,
+}
+
+impl Frame {
+    /// Serialize `msg` into a frame for the task exposed as `to`.
+    ///
+    /// The length prefix is added by the transport, see [`prefixed`].
+    pub(crate) fn encode<T>(to: &str, msg: &T) -> Result<Vec<u8>, RemoteError>
+    where
+        T: Serialize + Versioned,
+    {
+        let frame = Frame {
+            to: to.to_string(),
+            version: T::VERSION,
+            payload: serde_json::to_value(msg).map_err(RemoteError::Encode)?,
+        };
+
+        let bytes = serde_json::to_vec(&frame).map_err(RemoteError::Encode)?;
+        if bytes.len() > MAX_FRAME_LEN {
+            return Err(RemoteError::FrameTooLarge(bytes.len()));
+        }
+        Ok(bytes)
+    }
+
+    /// Deserialize an encoded frame.
+    pub(crate) fn decode(bytes: &[u8]) -> Result<Self, RemoteError> {
+        serde_json::from_slice(bytes).map_err(RemoteError::Decode)
+    }
+}
+
+/// Prepend the length prefix to an [encoded](Frame::encode) frame.
+pub(crate) fn prefixed(frame: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(4 + frame.len());
+    bytes.extend_from_slice(&(frame.len() as u32).to_be_bytes());
+    bytes.extend_from_slice(frame);
+    bytes
+}
+
+/// Read the next length-prefixed frame, without its prefix.
+///
+/// Returns `None` if the connection was closed between frames.
+pub(crate) async fn read<R>(reader: &mut R) -> Result<Option<Vec<u8>>, RemoteError>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut prefix = [0; 4];
+    match reader.read_exact(&mut prefix).await {
+        Ok(_) => {}
+        Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(err) => return Err(err.into()),
+    }
+
+    let len = u32::from_be_bytes(prefix) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(RemoteError::FrameTooLarge(len));
+    }
+
+    let mut bytes = vec![0; len];
+    reader.read_exact(&mut bytes).await?;
+    Ok(Some(bytes))
+}
@@ -0,0 +1,258 @@
+//! Accepting messages from other processes.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use serde_json::Value;
+use tokio::io::BufReader;
+use tokio::net::{TcpListener, TcpStream, ToSocketAddrs};
+use tokio::task::JoinSet;
+
+use super::RemoteError;
+use super::frame::{self, Frame};
+use crate::core::Versioned;
+use crate::core::mailbox::Received;
+use crate::core::trace;
+use crate::registry::Registry;
+use crate::task::{Runnable, Task};
+
+/// Decodes a payload and sends it to the task registered under a name.
+type Route =
+    Arc<dyn Fn(&Registry, &str, Option<u32>, Value) -> Result<(), RemoteError> + Send + Sync>;
+
+/// Delivers received frames to the tasks their names were exposed for.
+#[derive(Clone)]
+pub(crate) struct Router {
+    registry: Registry,
+    routes: Arc<HashMap<String, Route>>,
+}
+
+impl Router {
+    /// Deliver an encoded frame, dropping it with a warning if that fails.
+    pub(crate) fn deliver(&self, bytes: &[u8]) {
+        let delivered = Frame::decode(bytes).and_then(|frame| {
+            let route = self
+                .routes
+                .get(&frame.to)
+                .ok_or_else(|| RemoteError::NotExposed(frame.to.clone()))?;
+            route(&self.registry, &frame.to, frame.version, frame.payload)
+        });
+
+        if let Err(err) = delivered {
+            trace::frame_dropped("", &err);
+        }
+    }
+}
+
+/// Accepts connections, and determines how frames are carried on them.
+enum Acceptor {
+    /// Length-prefixed frames on plain TCP streams
+    Tcp(TcpListener),
+    /// One frame per WebSocket message
+    #[cfg(feature = "remote-ws")]
+    WebSocket(TcpListener),
+    /// Length-prefixed frames on a QUIC stream per task
+    #[cfg(feature = "remote-quic")]
+    Quic(quinn::Endpoint),
+}
+
+impl Acceptor {
+    /// Accept the next connection, returning the future serving it.
+    ///
+    /// Cancel safe: no connection is lost if the future is dropped.
+    async fn accept(&self, router: Router) -> io::Result<BoxFuture<'static, ()>> {
+        match self {
+            Acceptor::Tcp(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let _ = stream.set_nodelay(true);
+                Ok(Box::pin(serve(stream, router)))
+            }
+            #[cfg(feature = "remote-ws")]
+            Acceptor::WebSocket(listener) => {
+                let (stream, _) = listener.accept().await?;
+                let _ = stream.set_nodelay(true);
+                Ok(Box::pin(super::ws::serve(stream, router)))
+            }
+            #[cfg(feature = "remote-quic")]
+            Acceptor::Quic(endpoint) => match endpoint.accept().await {
+                Some(incoming) => Ok(Box::pin(super::quic::serve(incoming, router))),
+                // Only happens once the endpoint is closed
+                None => std::future::pending().await,
+            },
+        }
+    }
+}
+
+/// A task accepting connections and routing the frames received on them to
+/// local tasks.
+///
+/// The task runs until it is shut down, which also closes all accepted
+/// connections. See the [module documentation](super) for an example.
+#[derive(crate::Task)]
+#[task(message = Infallible)]
+pub struct RemoteListener {
+    acceptor: Acceptor,
+    router: Router,
+}
+
+impl RemoteListener {
+    /// Listen on `addr`, delivering to tasks registered in `registry`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    pub async fn bind(addr: impl ToSocketAddrs, registry: &Registry) -> io::Result<Self> {
+        Ok(Self::with_acceptor(
+            Acceptor::Tcp(TcpListener::bind(addr).await?),
+            registry,
+        ))
+    }
+
+    /// Listen for WebSocket connections on `addr`, delivering to tasks
+    /// registered in `registry`.
+    ///
+    /// Every binary or text message on a connection carries one frame,
+    /// without length prefix. This lets browsers and peers behind proxies
+    /// that only pass HTTP reach local tasks.
+    ///
+    /// Requires the `remote-ws` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    #[cfg(feature = "remote-ws")]
+    pub async fn bind_ws(addr: impl ToSocketAddrs, registry: &Registry) -> io::Result<Self> {
+        Ok(Self::with_acceptor(
+            Acceptor::WebSocket(TcpListener::bind(addr).await?),
+            registry,
+        ))
+    }
+
+    /// Listen for QUIC connections on the UDP address `addr`, delivering to
+    /// tasks registered in `registry`.
+    ///
+    /// Peers open a bidirectional stream per task and send length-prefixed
+    /// frames on it, so messages to one task never wait behind those to
+    /// another. Send to such a listener through a
+    /// [`QuicClient`](super::QuicClient).
+    ///
+    /// Requires the `remote-quic` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    #[cfg(feature = "remote-quic")]
+    pub fn bind_quic(
+        addr: SocketAddr,
+        config: super::quic::ServerConfig,
+        registry: &Registry,
+    ) -> io::Result<Self> {
+        Ok(Self::with_acceptor(
+            Acceptor::Quic(quinn::Endpoint::server(config, addr)?),
+            registry,
+        ))
+    }
+
+    fn with_acceptor(acceptor: Acceptor, registry: &Registry) -> Self {
+        RemoteListener {
+            acceptor,
+            router: Router {
+                registry: registry.clone(),
+                routes: Arc::default(),
+            },
+        }
+    }
+
+    /// The address the listener is bound to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be determined.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match &self.acceptor {
+            Acceptor::Tcp(listener) => listener.local_addr(),
+            #[cfg(feature = "remote-ws")]
+            Acceptor::WebSocket(listener) => listener.local_addr(),
+            #[cfg(feature = "remote-quic")]
+            Acceptor::Quic(endpoint) => endpoint.local_addr(),
+        }
+    }
+
+    /// Accept messages of type `T` for the task registered as `name`.
+    ///
+    /// The task is looked up whenever a message arrives, so a restarted task
+    /// that registers again keeps receiving messages. Messages are decoded
+    /// with [`Versioned::upcast`], so frames from nodes running another
+    /// version of the message schema are upcast or dropped, never decoded
+    /// into the wrong shape.
+    pub fn expose<T>(mut self, name: impl Into<String>) -> Self
+    where
+        T: Versioned + Send + 'static,
+    {
+        let route: Route = Arc::new(|registry, name, version, payload| {
+            let msg = T::upcast(version, payload).map_err(RemoteError::Decode)?;
+            let task = registry
+                .whereis::<T>(name)
+                .ok_or_else(|| RemoteError::NotRunning(name.to_string()))?;
+            task.send(msg)
+                .map_err(|_| RemoteError::NotRunning(name.to_string()))
+        });
+
+        Arc::make_mut(&mut self.router.routes).insert(name.into(), route);
+        self
+    }
+}
+
+impl Runnable<Infallible> for RemoteListener {
+    async fn start(&self) {
+        let mailbox = self.mailbox();
+        // Dropping the set closes the connections
+        let mut connections = JoinSet::new();
+
+        loop {
+            let accept = self.acceptor.accept(self.router.clone());
+            match mailbox.recv_or(accept).await {
+                Ok(Received::Interrupted(Ok(connection))) => {
+                    connections.spawn(connection);
+                    while connections.try_join_next().is_some() {}
+                }
+                // Accepting fails transiently, e.g. when out of file descriptors
+                Ok(Received::Interrupted(Err(_))) => {}
+                Ok(Received::Message(never)) => match never {},
+                // Shut down
+                Err(_) => break,
+            }
+        }
+
+        #[cfg(feature = "remote-quic")]
+        if let Acceptor::Quic(endpoint) = &self.acceptor {
+            endpoint.close(0u32.into(), b"shutdown");
+        }
+    }
+}
+
+/// Deliver the length-prefixed frames received on `stream` until it is
+/// closed.
+async fn serve(stream: TcpStream, router: Router) {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        match frame::read(&mut reader).await {
+            Ok(Some(bytes)) => router.deliver(&bytes),
+            Ok(None) => break,
+            Err(err) => {
+                // The stream is out of sync after a failed read
+                trace::frame_dropped("", &err);
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,443 @@
+//! Node membership through gossip.
+//!
+//! Every node runs a [`Membership`] task, exposed on its
+//! [`RemoteListener`](super::RemoteListener) under [`MEMBERSHIP`]. At every
+//! heartbeat the task sends a [`Gossip`] message to all nodes it knows of,
+//! announcing the names registered in its [`Registry`] and the peers it can
+//! reach. Nodes learn about each other from the gossip of the seeds they
+//! were started with and of every peer they hear from.
+//!
+//! A peer that has not been heard from for a while is marked
+//! [unreachable](Reachability::Unreachable). Its names can no longer be
+//! looked up in the [`RemoteRegistry`], and the references previously looked
+//! up become [stale](super::RemoteRef::is_stale): sending through them fails
+//! with [`RemoteError::Unreachable`](super::RemoteError::Unreachable). Once
+//! the peer is heard from again, its names can be looked up anew.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::message;
+//! use notizia::registry::Registry;
+//! use notizia::remote::RemoteListener;
+//! use notizia::remote::membership::{Gossip, MEMBERSHIP, Membership};
+//!
+//! #[message(serde)]
+//! enum OrderMsg {
+//!     Place { id: u32 },
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let registry = Registry::new();
+//! let listener = RemoteListener::bind("0.0.0.0:7400", &registry)
+//!     .await?
+//!     .expose::<Gossip>(MEMBERSHIP);
+//!
+//! let membership = Membership::new("node-b", "10.0.0.2:7400".parse()?, &registry)
+//!     .seed("10.0.0.1:7400".parse()?);
+//! let peers = membership.remote_registry();
+//! let _membership = membership.spawn();
+//! let _listener = listener.spawn();
+//!
+//! // Once the nodes have gossiped
+//! if let Some(orders) = peers.lookup::<OrderMsg>("orders") {
+//!     orders.send(OrderMsg::Place { id: 7 })?;
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use tokio::time::{Instant, MissedTickBehavior};
+
+use super::RemoteRef;
+use super::address::{self, Address};
+use super::reference::Endpoint;
+use crate::core::Versioned;
+use crate::core::mailbox::Received;
+use crate::registry::Registry;
+use crate::task::{Runnable, Task};
+
+/// Name the [`Membership`] task registers and is exposed as.
+pub const MEMBERSHIP: &str = "$membership";
+
+/// Interval between heartbeats unless configured otherwise.
+pub const DEFAULT_HEARTBEAT: Duration = Duration::from_secs(1);
+
+/// Time without gossip after which a peer is considered unreachable, unless
+/// configured otherwise.
+pub const DEFAULT_UNREACHABLE_AFTER: Duration = Duration::from_secs(5);
+
+/// Identifies a node in the cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NodeId(String);
+
+impl NodeId {
+    /// Create a node id.
+    pub fn new(id: impl Into<String>) -> Self {
+        NodeId(id.into())
+    }
+
+    /// The id as a string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for NodeId {
+    fn from(id: &str) -> Self {
+        NodeId::new(id)
+    }
+}
+
+impl From<String> for NodeId {
+    fn from(id: String) -> Self {
+        NodeId(id)
+    }
+}
+
+/// Whether a peer has been heard from recently.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reachability {
+    /// Gossip arrived within the configured time
+    Reachable,
+    /// No gossip arrived within the configured time
+    Unreachable,
+}
+
+/// A peer as last heard from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Member {
+    /// Id of the node
+    pub id: NodeId,
+    /// Address of the node's listener
+    pub addr: SocketAddr,
+    /// Names registered on the node
+    pub names: Vec<String>,
+    /// Whether the node is reachable
+    pub reachability: Reachability,
+}
+
+/// The heartbeat nodes send each other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Gossip {
+    /// Id of the sending node
+    pub from: NodeId,
+    /// Address of the sender's listener
+    pub addr: SocketAddr,
+    /// Names registered on the sending node
+    pub names: Vec<String>,
+    /// Peers the sender can reach
+    pub peers: Vec<(NodeId, SocketAddr)>,
+}
+
+impl Versioned for Gossip {
+    const VERSION: Option<u32> = None;
+
+    fn upcast<'de, D>(_version: Option<u32>, payload: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Gossip::deserialize(payload)
+    }
+}
+
+/// Marks the references looked up while a peer was reachable.
+///
+/// Invalidated when the peer becomes unreachable or moves to another
+/// address, making all those references stale.
+#[derive(Debug, Clone)]
+pub(crate) struct Lease {
+    node: NodeId,
+    valid: Arc<AtomicBool>,
+}
+
+impl Lease {
+    fn new(node: NodeId) -> Self {
+        Lease {
+            node,
+            valid: Arc::new(AtomicBool::new(true)),
+        }
+    }
+
+    fn revoke(&self) {
+        self.valid.store(false, Ordering::Relaxed);
+    }
+
+    /// Whether the references holding the lease may still be used.
+    pub(crate) fn is_valid(&self) -> bool {
+        self.valid.load(Ordering::Relaxed)
+    }
+
+    /// The node the lease was granted for.
+    pub(crate) fn node(&self) -> &NodeId {
+        &self.node
+    }
+}
+
+struct Peer {
+    member: Member,
+    last_seen: Instant,
+    lease: Lease,
+}
+
+/// The peers known to a [`Membership`] task and the names registered on
+/// them.
+///
+/// Cloning is cheap; all clones share the same view.
+#[derive(Clone, Default)]
+pub struct RemoteRegistry {
+    peers: Arc<RwLock<HashMap<NodeId, Peer>>>,
+}
+
+impl fmt::Debug for RemoteRegistry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.members()).finish()
+    }
+}
+
+impl RemoteRegistry {
+    /// All known peers, sorted by id.
+    pub fn members(&self) -> Vec<Member> {
+        let mut members: Vec<_> = self
+            .peers
+            .read()
+            .unwrap()
+            .values()
+            .map(|peer| peer.member.clone())
+            .collect();
+        members.sort_by(|a, b| a.id.cmp(&b.id));
+        members
+    }
+
+    /// The peer with id `node`, if it is known.
+    pub fn member(&self, node: &NodeId) -> Option<Member> {
+        let peers = self.peers.read().unwrap();
+        peers.get(node).map(|peer| peer.member.clone())
+    }
+
+    /// Reference the task registered as `name` on a reachable peer.
+    ///
+    /// If several peers registered the name, the one with the lowest id is
+    /// used. The reference becomes stale once the peer is unreachable.
+    pub fn lookup<T>(&self, name: &str) -> Option<RemoteRef<T>> {
+        let peers = self.peers.read().unwrap();
+        let peer = peers
+            .values()
+            .filter(|peer| peer.member.reachability == Reachability::Reachable)
+            .filter(|peer| peer.member.names.iter().any(|n| n == name))
+            .min_by(|a, b| a.member.id.cmp(&b.member.id))?;
+
+        Some(RemoteRef::at(Endpoint::Tcp(peer.member.addr), name).with_lease(peer.lease.clone()))
+    }
+
+    /// Record gossip received from a peer.
+    fn heard(&self, gossip: &Gossip) {
+        let mut peers = self.peers.write().unwrap();
+        let peer = peers.entry(gossip.from.clone()).or_insert_with(|| Peer {
+            member: Member {
+                id: gossip.from.clone(),
+                addr: gossip.addr,
+                names: Vec::new(),
+                reachability: Reachability::Reachable,
+            },
+            last_seen: Instant::now(),
+            lease: Lease::new(gossip.from.clone()),
+        });
+
+        if peer.member.reachability == Reachability::Unreachable || peer.member.addr != gossip.addr
+        {
+            peer.lease.revoke();
+            peer.lease = Lease::new(gossip.from.clone());
+        }
+        peer.member.addr = gossip.addr;
+        peer.member.names.clone_from(&gossip.names);
+        peer.member.reachability = Reachability::Reachable;
+        peer.last_seen = Instant::now();
+    }
+
+    /// Mark the peers not heard from within `timeout` as unreachable.
+    fn expire(&self, timeout: Duration) {
+        let mut peers = self.peers.write().unwrap();
+        for peer in peers.values_mut() {
+            if peer.member.reachability == Reachability::Reachable
+                && peer.last_seen.elapsed() > timeout
+            {
+                peer.member.reachability = Reachability::Unreachable;
+                peer.lease.revoke();
+            }
+        }
+    }
+
+    /// Ids and addresses of the reachable peers.
+    fn reachable(&self) -> Vec<(NodeId, SocketAddr)> {
+        let peers = self.peers.read().unwrap();
+        peers
+            .values()
+            .filter(|peer| peer.member.reachability == Reachability::Reachable)
+            .map(|peer| (peer.member.id.clone(), peer.member.addr))
+            .collect()
+    }
+
+    /// Addresses of all known peers, reachable or not.
+    fn addrs(&self) -> Vec<SocketAddr> {
+        let peers = self.peers.read().unwrap();
+        peers.values().map(|peer| peer.member.addr).collect()
+    }
+}
+
+/// A task gossiping with the other nodes of a cluster.
+///
+/// The task registers itself as [`MEMBERSHIP`] in the registry it was
+/// created with; the node's listener must
+/// [expose](super::RemoteListener::expose) [`Gossip`] under that name. See
+/// the [module documentation](self) for an example.
+#[derive(crate::Task)]
+#[task(message = Gossip)]
+pub struct Membership {
+    id: NodeId,
+    addr: SocketAddr,
+    registry: Registry,
+    seeds: Vec<SocketAddr>,
+    heartbeat: Duration,
+    unreachable_after: Duration,
+    peers: RemoteRegistry,
+}
+
+impl Membership {
+    /// Create the membership of node `id`, whose listener other nodes reach
+    /// at `addr`, announcing the names registered in `registry`.
+    pub fn new(id: impl Into<NodeId>, addr: SocketAddr, registry: &Registry) -> Self {
+        Membership {
+            id: id.into(),
+            addr,
+            registry: registry.clone(),
+            seeds: Vec::new(),
+            heartbeat: DEFAULT_HEARTBEAT,
+            unreachable_after: DEFAULT_UNREACHABLE_AFTER,
+            peers: RemoteRegistry::default(),
+        }
+    }
+
+    /// Gossip with the listener at `addr` to join its cluster.
+    pub fn seed(mut self, addr: SocketAddr) -> Self {
+        self.seeds.push(addr);
+        self
+    }
+
+    /// Set the interval between heartbeats.
+    ///
+    /// Defaults to [`DEFAULT_HEARTBEAT`].
+    pub fn heartbeat(mut self, interval: Duration) -> Self {
+        self.heartbeat = interval;
+        self
+    }
+
+    /// Set the time without gossip after which a peer is unreachable.
+    ///
+    /// Defaults to [`DEFAULT_UNREACHABLE_AFTER`].
+    pub fn unreachable_after(mut self, timeout: Duration) -> Self {
+        self.unreachable_after = timeout;
+        self
+    }
+
+    /// The id of this node.
+    pub fn id(&self) -> &NodeId {
+        &self.id
+    }
+
+    /// The [address](Address) of the task registered as `name` on this
+    /// node.
+    pub fn address<T>(&self, name: impl Into<String>) -> Address<T> {
+        Address::new(self.id.clone(), self.addr, name)
+    }
+
+    /// The peers known to this node, updated while the task runs.
+    pub fn remote_registry(&self) -> RemoteRegistry {
+        self.peers.clone()
+    }
+
+    /// Send a heartbeat to every seed and known peer.
+    fn gossip(&self, contacts: &HashSet<SocketAddr>) {
+        let gossip = Gossip {
+            from: self.id.clone(),
+            addr: self.addr,
+            names: self
+                .registry
+                .names()
+                .into_iter()
+                .filter(|name| name != MEMBERSHIP)
+                .collect(),
+            peers: self.peers.reachable(),
+        };
+
+        let targets: HashSet<_> = self
+            .seeds
+            .iter()
+            .chain(contacts)
+            .copied()
+            .chain(self.peers.addrs())
+            .filter(|addr| *addr != self.addr)
+            .collect();
+        for addr in targets {
+            // Failed deliveries show up as missing heartbeats
+            let _ = RemoteRef::<Gossip>::new(addr, MEMBERSHIP).send(gossip.clone());
+        }
+    }
+}
+
+impl Runnable<Gossip> for Membership {
+    async fn start(&self) {
+        self.registry.register(MEMBERSHIP, &self.this());
+        address::host(&self.id, &self.registry);
+
+        let mailbox = self.mailbox();
+        let mut ticks = tokio::time::interval(self.heartbeat);
+        ticks.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // Peers learned of through gossip, but not heard from yet
+        let mut contacts = HashSet::new();
+
+        loop {
+            match mailbox.recv_or(ticks.tick()).await {
+                Ok(Received::Message(gossip)) => {
+                    if gossip.from == self.id {
+                        continue;
+                    }
+                    self.peers.heard(&gossip);
+                    contacts.extend(
+                        gossip
+                            .peers
+                            .into_iter()
+                            .filter(|(id, _)| *id != self.id)
+                            .map(|(_, addr)| addr),
+                    );
+                }
+                Ok(Received::Interrupted(_)) => {
+                    self.peers.expire(self.unreachable_after);
+                    self.gossip(&contacts);
+                }
+                // Shut down
+                Err(_) => break,
+            }
+        }
+
+        address::unhost(&self.id);
+    }
+}
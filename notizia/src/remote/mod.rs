@@ -0,0 +1,173 @@
+//! Messaging between processes.
+//!
+//! A [`RemoteListener`] is a task accepting connections from other
+//! processes. Every frame it receives names a task and carries a serialized
+//! message; the listener decodes the message into the type the name was
+//! [exposed](RemoteListener::expose) with and sends it to the task
+//! registered under that name in a [`Registry`](crate::registry::Registry).
+//!
+//! On the sending side, a [`RemoteRef`] serializes messages and ships them to
+//! a remote task over a connection shared by all references to the same
+//! address. Sends are fire-and-forget: like [`TaskRef::send`](crate::TaskRef::send),
+//! a successful send only means the message is on its way. Frames that
+//! cannot be delivered, because the connection broke or the name is
+//! unknown on the remote side, are dropped with a warning.
+//!
+//! Messages are serialized as JSON. Message enums derive the required
+//! traits with [`#[message(serde)]`](crate::message); request variants
+//! cannot be sent to remote tasks.
+//!
+//! # Wire format
+//!
+//! A frame is a 4-byte big-endian length followed by that many bytes of
+//! JSON:
+//!
+//! ```json
+//! {"to":"orders","payload":{"Place":{"id":7}}}
+//! ```
+//!
+//! Messages with a [schema version](crate::core::version) carry it in a
+//! `version` field next to the payload, e.g. `"version":2`. Listeners
+//! decode payloads of other versions through the message's `compat` hook,
+//! or drop them.
+//!
+//! Frames larger than [`MAX_FRAME_LEN`] are rejected.
+//!
+//! # WebSocket transport
+//!
+//! With the `remote-ws` feature, [`RemoteListener::bind_ws`] accepts
+//! WebSocket connections instead, and [`RemoteRef::websocket`] sends to
+//! such a listener. Every WebSocket message carries one frame in the same
+//! JSON format, without the length prefix, so browsers, WASM peers and
+//! peers behind HTTP-only firewalls can take part.
+//!
+//! # Membership
+//!
+//! Instead of configuring the address of every remote task, nodes can run
+//! a [`Membership`] task gossiping with their peers. It learns the names
+//! registered on other nodes, tracks which nodes are reachable, and hands
+//! out references through a [`RemoteRegistry`]. See [`membership`] for
+//! details.
+//!
+//! Local [`TaskRef`](crate::TaskRef)s cannot leave the process. To pass a
+//! reference to a task inside a message to another node, use an
+//! [`Address`]: it is serializable, and sending through it reaches the task
+//! directly on its own node and through the transport everywhere else.
+//!
+//! # QUIC transport
+//!
+//! With the `remote-quic` feature, [`RemoteListener::bind_quic`] accepts
+//! QUIC connections, and references created with [`QuicClient::remote`]
+//! send to such a listener. A client keeps one connection per listener, and
+//! the frames for every remote task travel on their own bidirectional
+//! stream in the length-prefixed format above. Each task thus gets its own
+//! flow control, and a stalled task does not block messages to unrelated
+//! tasks behind it. Frames to the same task stay in order.
+//!
+//! This module requires the `remote` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::message;
+//! use notizia::registry::Registry;
+//! use notizia::remote::{RemoteListener, RemoteRef};
+//!
+//! #[message(serde)]
+//! #[derive(Debug)]
+//! enum OrderMsg {
+//!     Place { id: u32 },
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = OrderMsg)]
+//! struct Orders;
+//!
+//! impl Runnable<OrderMsg> for Orders {
+//!     async fn start(&self) {
+//!         while let Ok(msg) = recv!(self) {
+//!             println!("{msg:?}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! // On the receiving node
+//! let registry = Registry::new();
+//! let orders = spawn!(Orders);
+//! registry.register("orders", &orders.this());
+//!
+//! let listener = RemoteListener::bind("0.0.0.0:7400", &registry)
+//!     .await?
+//!     .expose::<OrderMsg>("orders")
+//!     .spawn();
+//!
+//! // On the sending node
+//! let orders = RemoteRef::<OrderMsg>::new("10.0.0.1:7400".parse()?, "orders");
+//! orders.send(OrderMsg::Place { id: 7 })?;
+//! # Ok(())
+//! # }
+//! ```
+
+mod address;
+mod frame;
+pub mod listener;
+pub mod membership;
+mod pool;
+#[cfg(feature = "remote-quic")]
+pub mod quic;
+pub mod reference;
+#[cfg(feature = "remote-ws")]
+mod ws;
+
+pub use address::Address;
+pub use frame::MAX_FRAME_LEN;
+pub use listener::RemoteListener;
+pub use membership::{Membership, NodeId, RemoteRegistry};
+pub use reference::{Endpoint, RemoteRef};
+
+#[cfg(feature = "remote-quic")]
+pub use quic::QuicClient;
+
+use std::io;
+
+/// Errors of remote messaging.
+#[derive(Debug, thiserror::Error)]
+pub enum RemoteError {
+    /// The message could not be serialized
+    #[error("failed to encode message: {0}")]
+    Encode(#[source] serde_json::Error),
+    /// A received frame or its message could not be deserialized
+    #[error("failed to decode frame: {0}")]
+    Decode(#[source] serde_json::Error),
+    /// The frame exceeds [`MAX_FRAME_LEN`]
+    #[error("frame of {0} bytes exceeds the maximum frame length")]
+    FrameTooLarge(usize),
+    /// No message type was exposed under the name
+    #[error("no task is exposed as `{0}`")]
+    NotExposed(String),
+    /// No running task is registered under the name
+    #[error("task `{0}` is not running")]
+    NotRunning(String),
+    /// The node of the remote task is unreachable, see
+    /// [`RemoteRef::is_stale`]
+    #[error("node `{0}` is unreachable")]
+    Unreachable(NodeId),
+    /// The connection failed
+    #[error("connection failed: {0}")]
+    Io(#[from] io::Error),
+    /// The WebSocket connection failed
+    #[cfg(feature = "remote-ws")]
+    #[error("websocket connection failed: {0}")]
+    WebSocket(#[from] tokio_tungstenite::tungstenite::Error),
+    /// A QUIC connection could not be started
+    #[cfg(feature = "remote-quic")]
+    #[error("failed to connect: {0}")]
+    QuicConnect(#[from] quinn::ConnectError),
+    /// The QUIC connection failed
+    #[cfg(feature = "remote-quic")]
+    #[error("quic connection failed: {0}")]
+    Quic(#[from] quinn::ConnectionError),
+}
@@ -0,0 +1,95 @@
+//! Connections shared by all remote references to the same endpoint.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, LazyLock, Mutex};
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use super::frame;
+use super::reference::Endpoint;
+use crate::core::trace;
+
+/// Number of times a frame is written before it is dropped.
+pub(crate) const ATTEMPTS: usize = 2;
+
+/// Identifies a writer: the endpoint, and for transports with a stream per
+/// task, the name of the task.
+type Key = (Endpoint, Option<Arc<str>>);
+
+/// Queues of encoded frames, one per writer.
+static POOL: LazyLock<Mutex<HashMap<Key, UnboundedSender<Vec<u8>>>>> =
+    LazyLock::new(Default::default);
+
+/// Queue an encoded frame for the task exposed as `name` at `endpoint`,
+/// starting its writer if needed.
+///
+/// Frames to the same task are written in the order they were queued.
+pub(crate) fn ship(endpoint: &Endpoint, name: &Arc<str>, frame: Vec<u8>) {
+    let key = (
+        endpoint.clone(),
+        endpoint.stream_per_task().then(|| name.clone()),
+    );
+    let mut pool = POOL.lock().unwrap();
+
+    // A writer stops with the runtime it ran on
+    let frame = match pool.get(&key) {
+        Some(writer) => match writer.send(frame) {
+            Ok(()) => return,
+            Err(err) => err.0,
+        },
+        None => frame,
+    };
+
+    let (writer, frames) = unbounded_channel();
+    let _ = writer.send(frame);
+    match endpoint {
+        Endpoint::Tcp(addr) => tokio::spawn(write(*addr, frames)),
+        #[cfg(feature = "remote-ws")]
+        Endpoint::WebSocket(url) => tokio::spawn(super::ws::write(url.clone(), frames)),
+        #[cfg(feature = "remote-quic")]
+        Endpoint::Quic(target) => tokio::spawn(super::quic::write(target.clone(), frames)),
+    };
+    pool.insert(key, writer);
+}
+
+/// Write the queued frames to `addr`, reconnecting when the connection
+/// breaks.
+async fn write(addr: SocketAddr, mut frames: UnboundedReceiver<Vec<u8>>) {
+    let mut connection: Option<TcpStream> = None;
+
+    while let Some(frame) = frames.recv().await {
+        let frame = frame::prefixed(&frame);
+        let mut result = Ok(());
+
+        for _ in 0..ATTEMPTS {
+            if connection.is_none() {
+                match TcpStream::connect(addr).await {
+                    Ok(stream) => {
+                        let _ = stream.set_nodelay(true);
+                        connection = Some(stream);
+                    }
+                    Err(err) => {
+                        result = Err(err);
+                        continue;
+                    }
+                }
+            }
+
+            let Some(stream) = connection.as_mut() else {
+                continue;
+            };
+            result = stream.write_all(&frame).await;
+            if result.is_ok() {
+                break;
+            }
+            connection = None;
+        }
+
+        if let Err(err) = result {
+            trace::frame_dropped(&addr.to_string(), &err);
+        }
+    }
+}
@@ -0,0 +1,250 @@
+//! QUIC transport, carrying the frames for each task on their own stream.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tokio::io::BufReader;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::task::JoinSet;
+
+use super::RemoteError;
+use super::frame;
+use super::listener::Router;
+use super::pool::ATTEMPTS;
+use super::reference::{Endpoint, RemoteRef};
+use crate::core::trace;
+
+pub use quinn::{ClientConfig, ServerConfig};
+
+static NEXT_CLIENT: AtomicU64 = AtomicU64::new(0);
+
+/// A local QUIC endpoint for sending to remote listeners bound with
+/// [`RemoteListener::bind_quic`](super::RemoteListener::bind_quic).
+///
+/// The client keeps one connection per listener. Every remote task gets its
+/// own stream on that connection, so a slow or stalled task does not hold
+/// up messages to other tasks on the same node. Clones share the endpoint
+/// and its connections.
+///
+/// Requires the `remote-quic` feature.
+#[derive(Clone)]
+pub struct QuicClient {
+    endpoint: quinn::Endpoint,
+    connections: Arc<Mutex<HashMap<(SocketAddr, String), quinn::Connection>>>,
+    id: u64,
+}
+
+impl fmt::Debug for QuicClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicClient")
+            .field("local_addr", &self.endpoint.local_addr().ok())
+            .finish_non_exhaustive()
+    }
+}
+
+impl QuicClient {
+    /// Bind a client endpoint to an ephemeral UDP port, connecting with
+    /// `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the socket cannot be bound.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn new(config: ClientConfig) -> io::Result<Self> {
+        let mut endpoint = quinn::Endpoint::client((Ipv4Addr::UNSPECIFIED, 0).into())?;
+        endpoint.set_default_client_config(config);
+
+        Ok(QuicClient {
+            endpoint,
+            connections: Arc::default(),
+            id: NEXT_CLIENT.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    /// Reference the task exposed as `name` by the QUIC listener at `addr`,
+    /// whose certificate is valid for `server_name`.
+    pub fn remote<T>(
+        &self,
+        addr: SocketAddr,
+        server_name: impl Into<String>,
+        name: impl Into<Arc<str>>,
+    ) -> RemoteRef<T> {
+        let target = QuicTarget {
+            client: self.clone(),
+            addr,
+            server_name: server_name.into(),
+        };
+        RemoteRef::at(Endpoint::Quic(target), name)
+    }
+
+    /// The open connection to `addr`, establishing it if needed.
+    async fn connection(
+        &self,
+        addr: SocketAddr,
+        server_name: &str,
+    ) -> Result<quinn::Connection, RemoteError> {
+        let mut connections = self.connections.lock().await;
+        let key = (addr, server_name.to_string());
+
+        if let Some(connection) = connections.get(&key)
+            && connection.close_reason().is_none()
+        {
+            return Ok(connection.clone());
+        }
+
+        let connection = self.endpoint.connect(addr, server_name)?.await?;
+        connections.insert(key, connection.clone());
+        Ok(connection)
+    }
+}
+
+/// A QUIC listener as reached through a [`QuicClient`].
+#[derive(Clone)]
+pub struct QuicTarget {
+    client: QuicClient,
+    addr: SocketAddr,
+    server_name: String,
+}
+
+impl QuicTarget {
+    /// The address of the listener.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// The name the listener's certificate is checked against.
+    pub fn server_name(&self) -> &str {
+        &self.server_name
+    }
+}
+
+impl PartialEq for QuicTarget {
+    fn eq(&self, other: &Self) -> bool {
+        self.client.id == other.client.id
+            && self.addr == other.addr
+            && self.server_name == other.server_name
+    }
+}
+
+impl Eq for QuicTarget {}
+
+impl Hash for QuicTarget {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.client.id.hash(state);
+        self.addr.hash(state);
+        self.server_name.hash(state);
+    }
+}
+
+impl fmt::Debug for QuicTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("QuicTarget")
+            .field("addr", &self.addr)
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl fmt::Display for QuicTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "quic://{}@{}", self.server_name, self.addr)
+    }
+}
+
+/// Deliver the frames received on the streams of an incoming connection
+/// until it is closed.
+pub(crate) async fn serve(incoming: quinn::Incoming, router: Router) {
+    let connection = match incoming.await {
+        Ok(connection) => connection,
+        Err(err) => return trace::frame_dropped("", &RemoteError::from(err)),
+    };
+    // Dropping the set closes the streams
+    let mut streams = JoinSet::new();
+
+    // Fails once the peer closes the connection
+    while let Ok((_, recv)) = connection.accept_bi().await {
+        streams.spawn(read(recv, router.clone()));
+        while streams.try_join_next().is_some() {}
+    }
+}
+
+/// Deliver the length-prefixed frames received on `stream` until it is
+/// finished.
+async fn read(stream: quinn::RecvStream, router: Router) {
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        match frame::read(&mut reader).await {
+            Ok(Some(bytes)) => router.deliver(&bytes),
+            Ok(None) => break,
+            Err(err) => {
+                // The stream is out of sync after a failed read
+                trace::frame_dropped("", &err);
+                break;
+            }
+        }
+    }
+}
+
+/// Write the queued frames for one task to the listener at `target`, on a
+/// stream of their own, reopening it when it breaks.
+pub(crate) async fn write(target: QuicTarget, mut frames: UnboundedReceiver<Vec<u8>>) {
+    let mut stream: Option<quinn::SendStream> = None;
+
+    while let Some(frame) = frames.recv().await {
+        let frame = frame::prefixed(&frame);
+        let mut result = Ok(());
+
+        for _ in 0..ATTEMPTS {
+            if stream.is_none() {
+                match open(&target).await {
+                    Ok(send) => stream = Some(send),
+                    Err(err) => {
+                        result = Err(err);
+                        continue;
+                    }
+                }
+            }
+
+            let Some(send) = stream.as_mut() else {
+                continue;
+            };
+            result = send
+                .write_all(&frame)
+                .await
+                .map_err(|err| RemoteError::Io(err.into()));
+            if result.is_ok() {
+                break;
+            }
+            stream = None;
+        }
+
+        if let Err(err) = result {
+            trace::frame_dropped(&target.to_string(), &err);
+        }
+    }
+
+    if let Some(mut send) = stream {
+        let _ = send.finish();
+    }
+}
+
+/// Open a stream to `target`, on the shared connection of its client.
+async fn open(target: &QuicTarget) -> Result<quinn::SendStream, RemoteError> {
+    let connection = target
+        .client
+        .connection(target.addr, &target.server_name)
+        .await?;
+    // The listener never writes back
+    let (send, _) = connection.open_bi().await?;
+    Ok(send)
+}
@@ -0,0 +1,166 @@
+//! References to tasks in other processes.
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use serde::Serialize;
+
+use super::RemoteError;
+use super::frame::Frame;
+use super::membership::Lease;
+use super::pool;
+use crate::core::Versioned;
+
+/// Where a remote listener accepts connections.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Endpoint {
+    /// A listener bound with [`RemoteListener::bind`](super::RemoteListener::bind)
+    Tcp(SocketAddr),
+    /// The URL of a listener bound with
+    /// [`RemoteListener::bind_ws`](super::RemoteListener::bind_ws), e.g.
+    /// `ws://10.0.0.1:7401`
+    #[cfg(feature = "remote-ws")]
+    WebSocket(String),
+    /// A listener bound with
+    /// [`RemoteListener::bind_quic`](super::RemoteListener::bind_quic),
+    /// reached through a [`QuicClient`](super::QuicClient)
+    #[cfg(feature = "remote-quic")]
+    Quic(super::quic::QuicTarget),
+}
+
+impl Endpoint {
+    /// Whether the frames for each task travel on a stream of their own.
+    pub(crate) fn stream_per_task(&self) -> bool {
+        match self {
+            Endpoint::Tcp(_) => false,
+            #[cfg(feature = "remote-ws")]
+            Endpoint::WebSocket(_) => false,
+            #[cfg(feature = "remote-quic")]
+            Endpoint::Quic(_) => true,
+        }
+    }
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Endpoint::Tcp(addr) => write!(f, "{addr}"),
+            #[cfg(feature = "remote-ws")]
+            Endpoint::WebSocket(url) => write!(f, "{url}"),
+            #[cfg(feature = "remote-quic")]
+            Endpoint::Quic(target) => write!(f, "{target}"),
+        }
+    }
+}
+
+/// A reference for sending messages to a task in another process.
+///
+/// The remote process must run a [`RemoteListener`](super::RemoteListener)
+/// exposing the task under the name. References are cheap to clone, and all
+/// references to the same endpoint share one connection.
+///
+/// References to QUIC listeners are created with
+/// [`QuicClient::remote`](super::QuicClient::remote).
+pub struct RemoteRef<T> {
+    endpoint: Endpoint,
+    name: Arc<str>,
+    lease: Option<Lease>,
+    _message: PhantomData<fn(T)>,
+}
+
+impl<T> Clone for RemoteRef<T> {
+    fn clone(&self) -> Self {
+        RemoteRef {
+            endpoint: self.endpoint.clone(),
+            name: self.name.clone(),
+            lease: self.lease.clone(),
+            _message: PhantomData,
+        }
+    }
+}
+
+impl<T> fmt::Debug for RemoteRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RemoteRef")
+            .field("endpoint", &self.endpoint)
+            .field("name", &self.name)
+            .finish()
+    }
+}
+
+impl<T> RemoteRef<T> {
+    /// Reference the task exposed as `name` by the TCP listener at `addr`.
+    pub fn new(addr: SocketAddr, name: impl Into<Arc<str>>) -> Self {
+        Self::at(Endpoint::Tcp(addr), name)
+    }
+
+    /// Reference the task exposed as `name` by the WebSocket listener at
+    /// `url`.
+    ///
+    /// Requires the `remote-ws` feature.
+    #[cfg(feature = "remote-ws")]
+    pub fn websocket(url: impl Into<String>, name: impl Into<Arc<str>>) -> Self {
+        Self::at(Endpoint::WebSocket(url.into()), name)
+    }
+
+    /// Reference the task exposed as `name` by the listener at `endpoint`.
+    pub fn at(endpoint: Endpoint, name: impl Into<Arc<str>>) -> Self {
+        RemoteRef {
+            endpoint,
+            name: name.into(),
+            lease: None,
+            _message: PhantomData,
+        }
+    }
+
+    /// Make the reference stale once `lease` is revoked.
+    pub(crate) fn with_lease(mut self, lease: Lease) -> Self {
+        self.lease = Some(lease);
+        self
+    }
+
+    /// Where the remote listener accepts connections.
+    pub fn endpoint(&self) -> &Endpoint {
+        &self.endpoint
+    }
+
+    /// The name the remote task is exposed as.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether the node of the remote task became unreachable since the
+    /// reference was [looked up](super::membership::RemoteRegistry::lookup).
+    ///
+    /// References not looked up through a membership are never stale.
+    pub fn is_stale(&self) -> bool {
+        self.lease.as_ref().is_some_and(|lease| !lease.is_valid())
+    }
+
+    /// Send a message to the remote task (fire-and-forget).
+    ///
+    /// The connection is established in the background; messages that
+    /// cannot be delivered are dropped with a warning.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reference [is stale](Self::is_stale), or if
+    /// the message cannot be serialized or is too large for a frame.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime.
+    pub fn send(&self, msg: T) -> Result<(), RemoteError>
+    where
+        T: Serialize + Versioned,
+    {
+        if let Some(lease) = self.lease.as_ref().filter(|lease| !lease.is_valid()) {
+            return Err(RemoteError::Unreachable(lease.node().clone()));
+        }
+        pool::ship(&self.endpoint, &self.name, Frame::encode(&self.name, &msg)?);
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+//! WebSocket transport, carrying one frame per message.
+
+use futures::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use super::RemoteError;
+use super::listener::Router;
+use super::pool::ATTEMPTS;
+use crate::core::trace;
+
+/// Deliver the frames received on the WebSocket connection `stream` until
+/// it is closed.
+pub(crate) async fn serve(stream: TcpStream, router: Router) {
+    let mut socket = match tokio_tungstenite::accept_async(stream).await {
+        Ok(socket) => socket,
+        Err(err) => return trace::frame_dropped("", &RemoteError::from(err)),
+    };
+
+    while let Some(message) = socket.next().await {
+        match message {
+            Ok(Message::Binary(bytes)) => router.deliver(&bytes),
+            Ok(Message::Text(text)) => router.deliver(text.as_bytes()),
+            Ok(Message::Close(_)) => break,
+            // Pings are answered by the socket itself
+            Ok(_) => {}
+            Err(err) => {
+                trace::frame_dropped("", &RemoteError::from(err));
+                break;
+            }
+        }
+    }
+}
+
+/// Write the queued frames to the listener at `url`, reconnecting when the
+/// connection breaks.
+pub(crate) async fn write(url: String, mut frames: UnboundedReceiver<Vec<u8>>) {
+    let mut connection: Option<WebSocketStream<MaybeTlsStream<TcpStream>>> = None;
+
+    while let Some(frame) = frames.recv().await {
+        let mut result = Ok(());
+
+        for _ in 0..ATTEMPTS {
+            if connection.is_none() {
+                match tokio_tungstenite::connect_async(url.as_str()).await {
+                    Ok((socket, _)) => connection = Some(socket),
+                    Err(err) => {
+                        result = Err(err);
+                        continue;
+                    }
+                }
+            }
+
+            let Some(socket) = connection.as_mut() else {
+                continue;
+            };
+            result = socket.send(Message::binary(frame.clone())).await;
+            if result.is_ok() {
+                break;
+            }
+            connection = None;
+        }
+
+        if let Err(err) = result {
+            trace::frame_dropped(&url, &RemoteError::from(err));
+        }
+    }
+}
@@ -0,0 +1,286 @@
+//! Joining and aborting tasks, whichever backend runs them.
+
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use futures::FutureExt;
+use futures::channel::oneshot;
+
+/// An owned permission to join a spawned task.
+///
+/// Awaiting the handle waits for the task to complete. Dropping it detaches
+/// the task, which keeps running.
+pub struct JoinHandle<T> {
+    inner: JoinInner<T>,
+}
+
+// Only the backends besides Tokio spawn remote tasks
+#[cfg_attr(
+    not(any(
+        feature = "runtime-smol",
+        feature = "runtime-async-std",
+        target_arch = "wasm32"
+    )),
+    allow(dead_code)
+)]
+enum JoinInner<T> {
+    Tokio(tokio::task::JoinHandle<T>),
+    Remote {
+        result: oneshot::Receiver<Result<T, JoinError>>,
+        abort: AbortHandle,
+    },
+}
+
+impl<T> JoinHandle<T> {
+    /// Wrap `future` so its output can be joined from the returned handle.
+    ///
+    /// Spawning the returned future on any executor runs the task.
+    #[cfg_attr(
+        not(any(
+            feature = "runtime-smol",
+            feature = "runtime-async-std",
+            target_arch = "wasm32"
+        )),
+        allow(dead_code)
+    )]
+    pub(crate) fn remote<F>(future: F) -> (impl Future<Output = ()> + Send + 'static, Self)
+    where
+        F: Future<Output = T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let (abort, registration) = futures::future::AbortHandle::new_pair();
+        let (sender, result) = oneshot::channel();
+        let finished = Arc::new(AtomicBool::new(false));
+
+        let abort = AbortHandle {
+            inner: AbortInner::Remote {
+                abort,
+                finished: finished.clone(),
+            },
+        };
+        let task = async move {
+            let future = std::panic::AssertUnwindSafe(future).catch_unwind();
+            let output = match futures::future::Abortable::new(future, registration).await {
+                Ok(Ok(output)) => Ok(output),
+                Ok(Err(panic)) => Err(JoinError::panic(panic)),
+                Err(_) => Err(JoinError::cancelled()),
+            };
+            finished.store(true, Ordering::Release);
+            let _ = sender.send(output);
+        };
+
+        (
+            task,
+            JoinHandle {
+                inner: JoinInner::Remote { result, abort },
+            },
+        )
+    }
+
+    /// Abort the task.
+    ///
+    /// Awaiting the handle afterwards fails with a cancelled
+    /// [`JoinError`], unless the task completed before.
+    pub fn abort(&self) {
+        match &self.inner {
+            JoinInner::Tokio(handle) => handle.abort(),
+            JoinInner::Remote { abort, .. } => abort.abort(),
+        }
+    }
+
+    /// Check whether the task has finished.
+    pub fn is_finished(&self) -> bool {
+        match &self.inner {
+            JoinInner::Tokio(handle) => handle.is_finished(),
+            JoinInner::Remote { abort, .. } => abort.is_finished(),
+        }
+    }
+
+    /// A handle that aborts the task without consuming this handle.
+    pub fn abort_handle(&self) -> AbortHandle {
+        match &self.inner {
+            JoinInner::Tokio(handle) => handle.abort_handle().into(),
+            JoinInner::Remote { abort, .. } => abort.clone(),
+        }
+    }
+}
+
+impl<T> From<tokio::task::JoinHandle<T>> for JoinHandle<T> {
+    fn from(handle: tokio::task::JoinHandle<T>) -> Self {
+        JoinHandle {
+            inner: JoinInner::Tokio(handle),
+        }
+    }
+}
+
+impl<T> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match &mut self.get_mut().inner {
+            JoinInner::Tokio(handle) => Pin::new(handle).poll(cx).map_err(JoinError::from),
+            JoinInner::Remote { result, .. } => Pin::new(result)
+                .poll(cx)
+                // The executor dropped the task without running it to the end
+                .map(|result| result.unwrap_or_else(|_| Err(JoinError::cancelled()))),
+        }
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// Aborts a spawned task without owning its [`JoinHandle`].
+#[derive(Clone)]
+pub struct AbortHandle {
+    inner: AbortInner,
+}
+
+#[cfg_attr(
+    not(any(
+        feature = "runtime-smol",
+        feature = "runtime-async-std",
+        target_arch = "wasm32"
+    )),
+    allow(dead_code)
+)]
+#[derive(Clone)]
+enum AbortInner {
+    Tokio(tokio::task::AbortHandle),
+    Remote {
+        abort: futures::future::AbortHandle,
+        finished: Arc<AtomicBool>,
+    },
+}
+
+impl AbortHandle {
+    /// Abort the task.
+    pub fn abort(&self) {
+        match &self.inner {
+            AbortInner::Tokio(handle) => handle.abort(),
+            AbortInner::Remote { abort, .. } => abort.abort(),
+        }
+    }
+
+    /// Check whether the task has finished.
+    pub fn is_finished(&self) -> bool {
+        match &self.inner {
+            AbortInner::Tokio(handle) => handle.is_finished(),
+            AbortInner::Remote { finished, .. } => finished.load(Ordering::Acquire),
+        }
+    }
+}
+
+impl From<tokio::task::AbortHandle> for AbortHandle {
+    fn from(handle: tokio::task::AbortHandle) -> Self {
+        AbortHandle {
+            inner: AbortInner::Tokio(handle),
+        }
+    }
+}
+
+impl fmt::Debug for AbortHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AbortHandle")
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// A task failed to run to completion.
+pub struct JoinError {
+    repr: Repr,
+}
+
+enum Repr {
+    Cancelled,
+    // The mutex makes the error `Sync`
+    Panic(Mutex<Box<dyn Any + Send + 'static>>),
+}
+
+impl JoinError {
+    pub(crate) fn cancelled() -> Self {
+        JoinError {
+            repr: Repr::Cancelled,
+        }
+    }
+
+    pub(crate) fn panic(payload: Box<dyn Any + Send + 'static>) -> Self {
+        JoinError {
+            repr: Repr::Panic(Mutex::new(payload)),
+        }
+    }
+
+    /// Whether the task was aborted.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.repr, Repr::Cancelled)
+    }
+
+    /// Whether the task panicked.
+    pub fn is_panic(&self) -> bool {
+        matches!(self.repr, Repr::Panic(_))
+    }
+
+    /// The payload of the panic.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the task did not panic.
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic.")
+    }
+
+    /// The payload of the panic, or the error if the task did not panic.
+    ///
+    /// # Errors
+    ///
+    /// Returns `self` if the task was cancelled.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.repr {
+            Repr::Panic(payload) => Ok(payload
+                .into_inner()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())),
+            Repr::Cancelled => Err(self),
+        }
+    }
+}
+
+impl From<tokio::task::JoinError> for JoinError {
+    fn from(err: tokio::task::JoinError) -> Self {
+        match err.try_into_panic() {
+            Ok(payload) => JoinError::panic(payload),
+            Err(_) => JoinError::cancelled(),
+        }
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => f.write_str("task was cancelled"),
+            Repr::Panic(_) => f.write_str("task panicked"),
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => f.write_str("JoinError::Cancelled"),
+            Repr::Panic(_) => f.write_str("JoinError::Panic(..)"),
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
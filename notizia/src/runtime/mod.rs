@@ -0,0 +1,406 @@
+//! The async runtime tasks run on.
+//!
+//! Tasks, their mailboxes and their timers reach the runtime only through
+//! this module, so notizia is not tied to Tokio's executor. Tokio is the
+//! default; the `runtime-smol` and `runtime-async-std` features add
+//! backends for [smol](https://docs.rs/smol) and
+//! [async-std](https://docs.rs/async-std).
+//!
+//! The backend is picked whenever a task is spawned or a timer is started:
+//! inside a Tokio runtime, notizia uses Tokio, so the Tokio backend keeps
+//! working with the other features enabled. Elsewhere, it uses smol if
+//! enabled, then async-std. Without any of them, spawning outside of a
+//! Tokio runtime panics, as it always did.
+//!
+//! On `wasm32` targets, tasks are spawned onto the browser's event loop with
+//! [`wasm_bindgen_futures::spawn_local`] and timers are driven by the
+//! browser, so the same task protocols run in UI code. Features that need
+//! threads are not available there: aborting tasks with
+//! [`TaskHandle::kill`](crate::task::TaskHandle::kill), the
+//! [`KillSwitch`](crate::task::KillSwitch), blocking tasks and the blocking
+//! bridges such as `TaskHandle::blocking_call`.
+//!
+//! Channels, locks and task-local values come from `tokio::sync` and
+//! `tokio::task_local!`, which do not depend on Tokio's executor and work
+//! with every backend. Modules built on Tokio's networking, such as
+//! [`net`](crate::net) or `remote`, still require a Tokio runtime, and so do
+//! the `console` and `tokio-metrics` features.
+//!
+//! # Example
+//!
+//! With the `runtime-smol` feature:
+//!
+//! ```ignore
+//! use notizia::prelude::*;
+//!
+//! #[derive(Task)]
+//! #[task(message = Ping)]
+//! struct Pong;
+//!
+//! impl Runnable<Ping> for Pong {
+//!     async fn start(&self) {
+//!         while let Ok(Ping) = recv!(self) {}
+//!     }
+//! }
+//!
+//! struct Ping;
+//!
+//! fn main() {
+//!     smol::block_on(async {
+//!         let pong = spawn!(Pong);
+//!         pong.send(Ping).unwrap();
+//!         pong.shutdown(std::time::Duration::from_secs(1)).await.unwrap();
+//!     })
+//! }
+//! ```
+
+mod join;
+
+use std::future::Future;
+use std::time::Duration;
+
+use futures::future::{BoxFuture, Either};
+
+pub use join::{AbortHandle, JoinError, JoinHandle};
+#[cfg(not(target_arch = "wasm32"))]
+pub use tokio::time::Instant;
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+#[doc(hidden)]
+pub use tokio::task_local;
+
+/// The receiving end of a task's mailbox.
+///
+/// This is typically used by the generated code and not by user code directly.
+#[doc(hidden)]
+pub type Receiver<T> = tokio::sync::mpsc::UnboundedReceiver<crate::core::Envelope<T>>;
+
+/// An executor notizia runs tasks and timers on.
+pub(crate) trait Runtime {
+    /// Spawn `future`, named `name` where the runtime supports it.
+    fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static;
+
+    /// Run the future on a thread that may block, named `name` where the
+    /// runtime supports it.
+    fn spawn_blocking<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static;
+
+    /// Wait until `deadline`.
+    fn sleep_until(deadline: Instant) -> BoxFuture<'static, ()>;
+
+    /// Create an unbounded channel.
+    fn channel<T>() -> (
+        tokio::sync::mpsc::UnboundedSender<T>,
+        tokio::sync::mpsc::UnboundedReceiver<T>,
+    ) {
+        tokio::sync::mpsc::unbounded_channel()
+    }
+}
+
+/// The Tokio backend.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) struct Tokio;
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Runtime for Tokio {
+    fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        #[cfg(all(tokio_unstable, feature = "console"))]
+        let handle = tokio::task::Builder::new()
+            .name(name)
+            .spawn(future)
+            .expect("failed to spawn task");
+
+        #[cfg(not(all(tokio_unstable, feature = "console")))]
+        let handle = {
+            let _ = name;
+            tokio::spawn(future)
+        };
+
+        handle.into()
+    }
+
+    fn spawn_blocking<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let runtime = tokio::runtime::Handle::current();
+        let f = move || runtime.block_on(future);
+
+        #[cfg(all(tokio_unstable, feature = "console"))]
+        let handle = tokio::task::Builder::new()
+            .name(name)
+            .spawn_blocking(f)
+            .expect("failed to spawn task");
+
+        #[cfg(not(all(tokio_unstable, feature = "console")))]
+        let handle = {
+            let _ = name;
+            tokio::task::spawn_blocking(f)
+        };
+
+        handle.into()
+    }
+
+    fn sleep_until(deadline: Instant) -> BoxFuture<'static, ()> {
+        Box::pin(tokio::time::sleep_until(deadline))
+    }
+}
+
+/// The smol backend.
+#[cfg(feature = "runtime-smol")]
+pub(crate) struct Smol;
+
+#[cfg(feature = "runtime-smol")]
+impl Runtime for Smol {
+    fn spawn<F>(_name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (task, handle) = JoinHandle::remote(future);
+        smol::spawn(task).detach();
+        handle
+    }
+
+    fn spawn_blocking<F>(_name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (task, handle) = JoinHandle::remote(smol::unblock(move || smol::block_on(future)));
+        smol::spawn(task).detach();
+        handle
+    }
+
+    fn sleep_until(deadline: Instant) -> BoxFuture<'static, ()> {
+        let timer = smol::Timer::at(deadline.into_std());
+        Box::pin(async move {
+            timer.await;
+        })
+    }
+}
+
+/// The async-std backend.
+#[cfg(feature = "runtime-async-std")]
+pub(crate) struct AsyncStd;
+
+#[cfg(feature = "runtime-async-std")]
+impl Runtime for AsyncStd {
+    fn spawn<F>(_name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (task, handle) = JoinHandle::remote(future);
+        // Dropping the join handle detaches the task
+        drop(async_std::task::spawn(task));
+        handle
+    }
+
+    fn spawn_blocking<F>(_name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (task, handle) = JoinHandle::remote(async_std::task::spawn_blocking(move || {
+            async_std::task::block_on(future)
+        }));
+        drop(async_std::task::spawn(task));
+        handle
+    }
+
+    fn sleep_until(deadline: Instant) -> BoxFuture<'static, ()> {
+        let duration = deadline.saturating_duration_since(Instant::now());
+        Box::pin(async_std::task::sleep(duration))
+    }
+}
+
+/// The browser backend.
+#[cfg(target_arch = "wasm32")]
+pub(crate) struct Wasm;
+
+#[cfg(target_arch = "wasm32")]
+impl Runtime for Wasm {
+    fn spawn<F>(_name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let (task, handle) = JoinHandle::remote(future);
+        wasm_bindgen_futures::spawn_local(task);
+        handle
+    }
+
+    fn spawn_blocking<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        // There is no thread to block; blocking tasks cannot be requested here
+        Self::spawn(name, future)
+    }
+
+    fn sleep_until(deadline: Instant) -> BoxFuture<'static, ()> {
+        let duration = deadline.saturating_duration_since(Instant::now());
+        Box::pin(futures_timer::Delay::new(duration))
+    }
+}
+
+/// The backends notizia can run on.
+enum Backend {
+    #[cfg(not(target_arch = "wasm32"))]
+    Tokio,
+    #[cfg(target_arch = "wasm32")]
+    Wasm,
+    #[cfg(feature = "runtime-smol")]
+    Smol,
+    // smol takes precedence when both are enabled
+    #[cfg(feature = "runtime-async-std")]
+    #[cfg_attr(feature = "runtime-smol", allow(dead_code))]
+    AsyncStd,
+}
+
+/// The backend used outside of a Tokio runtime.
+#[cfg(feature = "runtime-smol")]
+const FALLBACK: Backend = Backend::Smol;
+#[cfg(all(feature = "runtime-async-std", not(feature = "runtime-smol")))]
+const FALLBACK: Backend = Backend::AsyncStd;
+#[cfg(not(any(
+    feature = "runtime-smol",
+    feature = "runtime-async-std",
+    target_arch = "wasm32"
+)))]
+const FALLBACK: Backend = Backend::Tokio;
+
+/// The backend for the current context.
+#[cfg(not(target_arch = "wasm32"))]
+fn backend() -> Backend {
+    if tokio::runtime::Handle::try_current().is_ok() {
+        Backend::Tokio
+    } else {
+        FALLBACK
+    }
+}
+
+/// The backend for the current context.
+#[cfg(target_arch = "wasm32")]
+fn backend() -> Backend {
+    Backend::Wasm
+}
+
+/// Dispatch a call to the [`Runtime`] of the current backend.
+macro_rules! dispatch {
+    ($method:ident($($arg:expr),*)) => {
+        match backend() {
+            #[cfg(not(target_arch = "wasm32"))]
+            Backend::Tokio => Tokio::$method($($arg),*),
+            #[cfg(target_arch = "wasm32")]
+            Backend::Wasm => Wasm::$method($($arg),*),
+            #[cfg(feature = "runtime-smol")]
+            Backend::Smol => Smol::$method($($arg),*),
+            #[cfg(feature = "runtime-async-std")]
+            Backend::AsyncStd => AsyncStd::$method($($arg),*),
+        }
+    };
+}
+
+/// Spawn `future` as a task named `name`.
+///
+/// Names show up in `tokio-console` when built with the `console` feature
+/// and `--cfg tokio_unstable`; otherwise they are ignored.
+///
+/// # Panics
+///
+/// Panics if called outside of a Tokio runtime and no other backend is
+/// enabled.
+pub(crate) fn spawn<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    dispatch!(spawn(name, future))
+}
+
+/// Run `future` on a thread that may block, as a task named `name`.
+///
+/// See [`spawn`].
+pub(crate) fn spawn_blocking<F>(name: &'static str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    dispatch!(spawn_blocking(name, future))
+}
+
+/// Create the channel behind a mailbox.
+pub(crate) fn channel<T>() -> (
+    tokio::sync::mpsc::UnboundedSender<T>,
+    tokio::sync::mpsc::UnboundedReceiver<T>,
+) {
+    dispatch!(channel())
+}
+
+/// Wait until `deadline`.
+pub async fn sleep_until(deadline: Instant) {
+    dispatch!(sleep_until(deadline)).await;
+}
+
+/// Wait for `duration`.
+pub async fn sleep(duration: Duration) {
+    match Instant::now().checked_add(duration) {
+        Some(deadline) => sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+/// The error of a [`timeout`] that elapsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, thiserror::Error)]
+#[error("deadline has elapsed")]
+pub struct Elapsed;
+
+/// Run `future`, giving up once `deadline` has passed.
+///
+/// Like [`tokio::time::timeout_at`], the future is polled first, so it wins
+/// if both are ready.
+///
+/// # Errors
+///
+/// Returns [`Elapsed`] if the deadline passed first.
+pub async fn timeout_at<F>(deadline: Instant, future: F) -> Result<F::Output, Elapsed>
+where
+    F: Future,
+{
+    let future = std::pin::pin!(future);
+    let expired = std::pin::pin!(sleep_until(deadline));
+
+    match futures::future::select(future, expired).await {
+        Either::Left((output, _)) => Ok(output),
+        Either::Right(_) => Err(Elapsed),
+    }
+}
+
+/// Run `future`, giving up after `duration`.
+///
+/// # Errors
+///
+/// Returns [`Elapsed`] if the time ran out first.
+pub async fn timeout<F>(duration: Duration, future: F) -> Result<F::Output, Elapsed>
+where
+    F: Future,
+{
+    match Instant::now().checked_add(duration) {
+        Some(deadline) => timeout_at(deadline, future).await,
+        None => Ok(future.await),
+    }
+}
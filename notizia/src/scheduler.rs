@@ -0,0 +1,218 @@
+//! Cron-style scheduling of messages.
+//!
+//! A [`Scheduler`] sends messages to named tasks on a cron schedule. All jobs
+//! are driven by a single background task, so scheduling many jobs costs no
+//! more than one timer.
+//!
+//! Jobs address their target by name through a [`Registry`] rather than by a
+//! [`TaskRef`](crate::TaskRef). The name is resolved every time the job
+//! fires, so a target that is restarted and registers itself again keeps
+//! receiving its messages. Ticks that fire while no live task is registered
+//! under the name are skipped.
+//!
+//! Expressions use the six-field format of the [`cron`] crate, with seconds
+//! first (`sec min hour day-of-month month day-of-week`), and are evaluated
+//! in UTC.
+//!
+//! This module requires the `scheduler` feature.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::registry::Registry;
+//! use notizia::scheduler::Scheduler;
+//!
+//! #[derive(Debug, Clone)]
+//! enum CacheMsg {
+//!     Evict,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = CacheMsg)]
+//! struct Cache;
+//!
+//! impl Runnable<CacheMsg> for Cache {
+//!     async fn start(&self) {
+//!         while let Ok(CacheMsg::Evict) = recv!(self) {
+//!             println!("evicting stale entries");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let registry = Registry::new();
+//! let cache = spawn!(Cache);
+//! registry.register("cache", &cache.this());
+//!
+//! let scheduler = Scheduler::new(registry.clone());
+//!
+//! // Every five minutes
+//! scheduler
+//!     .schedule("0 */5 * * * *", "cache", || CacheMsg::Evict)
+//!     .unwrap();
+//! # }
+//! ```
+
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use chrono::{DateTime, Utc};
+use cron::Schedule;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+use tokio::task::JoinHandle;
+
+use crate::registry::Registry;
+
+/// Identifier of a scheduled job.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct JobId(u64);
+
+/// Errors that can occur when scheduling a job.
+#[derive(Debug, thiserror::Error)]
+pub enum ScheduleError {
+    /// The cron expression could not be parsed
+    #[error("invalid cron expression `{expression}`: {reason}")]
+    InvalidExpression {
+        /// The rejected expression
+        expression: String,
+        /// Why the expression was rejected
+        reason: String,
+    },
+    /// The scheduler task is no longer running
+    #[error("scheduler has stopped")]
+    Stopped,
+}
+
+type Fire = Box<dyn Fn(&Registry) + Send>;
+
+struct Job {
+    id: JobId,
+    schedule: Schedule,
+    next: Option<DateTime<Utc>>,
+    fire: Fire,
+}
+
+enum Command {
+    Add(Box<Job>),
+    Cancel(JobId),
+}
+
+/// Sends messages to named tasks on cron schedules.
+///
+/// Dropping the scheduler stops all of its jobs.
+pub struct Scheduler {
+    commands: UnboundedSender<Command>,
+    next_id: AtomicU64,
+    handle: JoinHandle<()>,
+}
+
+impl Scheduler {
+    /// Start a scheduler resolving job targets in `registry`.
+    pub fn new(registry: Registry) -> Self {
+        let (commands, receiver) = unbounded_channel();
+
+        Scheduler {
+            commands,
+            next_id: AtomicU64::new(0),
+            handle: tokio::spawn(drive(registry, receiver)),
+        }
+    }
+
+    /// Send the message produced by `message` to the task registered as
+    /// `target` whenever `expression` fires.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ScheduleError::InvalidExpression`] if the expression cannot
+    /// be parsed and [`ScheduleError::Stopped`] if the scheduler task is no
+    /// longer running.
+    pub fn schedule<T, F>(
+        &self,
+        expression: &str,
+        target: impl Into<String>,
+        message: F,
+    ) -> Result<JobId, ScheduleError>
+    where
+        T: Send + 'static,
+        F: Fn() -> T + Send + 'static,
+    {
+        let schedule =
+            Schedule::from_str(expression).map_err(|err| ScheduleError::InvalidExpression {
+                expression: expression.to_string(),
+                reason: err.to_string(),
+            })?;
+
+        let id = JobId(self.next_id.fetch_add(1, Ordering::Relaxed));
+        let target = target.into();
+
+        let job = Job {
+            id,
+            next: schedule.upcoming(Utc).next(),
+            schedule,
+            fire: Box::new(move |registry| {
+                if let Some(task) = registry.whereis::<T>(&target) {
+                    let _ = task.send(message());
+                }
+            }),
+        };
+
+        self.commands
+            .send(Command::Add(Box::new(job)))
+            .map_err(|_| ScheduleError::Stopped)?;
+
+        Ok(id)
+    }
+
+    /// Stop a scheduled job.
+    ///
+    /// Cancelling a job that does not exist (anymore) has no effect.
+    pub fn cancel(&self, job: JobId) {
+        let _ = self.commands.send(Command::Cancel(job));
+    }
+
+    /// Stop the scheduler and wait for its task to finish.
+    pub async fn shutdown(self) {
+        drop(self.commands);
+        let _ = self.handle.await;
+    }
+}
+
+/// Drive all jobs of a scheduler until it is dropped.
+async fn drive(registry: Registry, mut commands: UnboundedReceiver<Command>) {
+    let mut jobs: Vec<Job> = Vec::new();
+
+    loop {
+        let next = jobs.iter().filter_map(|job| job.next).min();
+        let wait = async move {
+            match next {
+                Some(at) => {
+                    tokio::time::sleep((at - Utc::now()).to_std().unwrap_or_default()).await
+                }
+                None => std::future::pending().await,
+            }
+        };
+
+        tokio::select! {
+            command = commands.recv() => match command {
+                Some(Command::Add(job)) => jobs.push(*job),
+                Some(Command::Cancel(id)) => jobs.retain(|job| job.id != id),
+                None => break,
+            },
+            _ = wait => {
+                let now = Utc::now();
+
+                for job in jobs.iter_mut() {
+                    if job.next.is_some_and(|at| at <= now) {
+                        (job.fire)(&registry);
+                        job.next = job.schedule.after(&now).next();
+                    }
+                }
+
+                // Schedules without future occurrences are done
+                jobs.retain(|job| job.next.is_some());
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Bidirectional sessions between two tasks.
+//!
+//! A task's mailbox mixes messages from everyone talking to it. A
+//! [`Session`] is a private, typed conversation between exactly two parties,
+//! separate from both mailboxes. One side opens it with [`Session::open`],
+//! which hands the other half to the peer inside a handshake message. Both
+//! sides can then [`send`](Session::send) and [`recv`](Session::recv)
+//! independently.
+//!
+//! Sessions tear themselves down: once either side drops its half, e.g.
+//! because its task terminated, the other side receives
+//! [`RecvError::Closed`] after the remaining messages and its sends fail.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::session::Session;
+//!
+//! #[derive(Debug)]
+//! enum Question {
+//!     Ask(String),
+//! }
+//!
+//! #[derive(Debug)]
+//! enum Answer {
+//!     Tell(String),
+//! }
+//!
+//! enum OracleMsg {
+//!     Consult(Session<Answer, Question>),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = OracleMsg)]
+//! struct Oracle;
+//!
+//! impl Runnable<OracleMsg> for Oracle {
+//!     async fn start(&self) {
+//!         while let Ok(OracleMsg::Consult(mut session)) = recv!(self) {
+//!             while let Ok(Question::Ask(question)) = session.recv().await {
+//!                 let _ = session.send(Answer::Tell(format!("yes, {question}")));
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), CallError> {
+//! let oracle = spawn!(Oracle);
+//!
+//! let mut session = Session::open(&oracle.this(), OracleMsg::Consult)?;
+//! session.send(Question::Ask("will it work?".into())).unwrap();
+//! let Answer::Tell(answer) = session.recv().await.unwrap();
+//! # Ok(())
+//! # }
+//! ```
+
+use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use crate::core::errors::{CallError, CallResult, RecvError, RecvResult, SendResult};
+use crate::task::TaskRef;
+
+/// One side of a conversation, sending `Out` and receiving `In`.
+///
+/// The other side is a `Session<In, Out>`.
+#[derive(Debug)]
+pub struct Session<Out, In> {
+    sender: UnboundedSender<Out>,
+    receiver: UnboundedReceiver<In>,
+}
+
+impl<Out, In> Session<Out, In> {
+    /// Create both sides of a session.
+    pub fn pair() -> (Session<Out, In>, Session<In, Out>) {
+        let (out_sender, out_receiver) = unbounded_channel();
+        let (in_sender, in_receiver) = unbounded_channel();
+
+        (
+            Session {
+                sender: out_sender,
+                receiver: in_receiver,
+            },
+            Session {
+                sender: in_sender,
+                receiver: out_receiver,
+            },
+        )
+    }
+
+    /// Open a session with `task`.
+    ///
+    /// The peer's side of the session is passed to `handshake`, and the
+    /// resulting message is sent to the task. The task takes the session out
+    /// of the message to join the conversation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CallError::SendError`] if the task's mailbox is closed.
+    pub fn open<T, F>(task: &TaskRef<T>, handshake: F) -> CallResult<Self>
+    where
+        T: Send + 'static,
+        F: FnOnce(Session<In, Out>) -> T,
+    {
+        let (session, peer) = Self::pair();
+
+        task.send(handshake(peer))
+            .map_err(|err| CallError::send_failed(task.id(), task.name(), err))?;
+
+        Ok(session)
+    }
+
+    /// Send a message to the other side.
+    ///
+    /// # Errors
+    ///
+    /// Returns the message if the other side has been dropped.
+    pub fn send(&self, msg: Out) -> SendResult<Out> {
+        self.sender.send(msg)
+    }
+
+    /// Receive the next message from the other side.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`] once the other side has been dropped
+    /// and all of its messages have been received.
+    pub async fn recv(&mut self) -> RecvResult<In> {
+        self.receiver.recv().await.ok_or(RecvError::Closed)
+    }
+
+    /// Receive a message if one is immediately available.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RecvError::Closed`] once the other side has been dropped
+    /// and all of its messages have been received.
+    pub fn try_recv(&mut self) -> RecvResult<Option<In>> {
+        match self.receiver.try_recv() {
+            Ok(msg) => Ok(Some(msg)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(RecvError::Closed),
+        }
+    }
+
+    /// Check whether the other side has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+}
@@ -0,0 +1,450 @@
+//! Entity sharding by key.
+//!
+//! A [`ShardRegion`] manages one task per entity key (one task per user,
+//! session, device, ...). Entities are spawned lazily when the first message
+//! for their key arrives and are distributed over a number of shards, each
+//! guarded by its own lock, so unrelated keys don't contend with each other.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::sharding::ShardRegion;
+//!
+//! #[derive(Debug, Clone)]
+//! enum SessionMsg {
+//!     Touch,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = SessionMsg)]
+//! struct Session {
+//!     user: String,
+//! }
+//!
+//! impl Runnable<SessionMsg> for Session {
+//!     async fn start(&self) {
+//!         while let Ok(SessionMsg::Touch) = recv!(self) {
+//!             println!("{} is active", self.user);
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let sessions = ShardRegion::new(|user: &String| Session { user: user.clone() });
+//!
+//! // Spawns the session for "alice" on first use
+//! sessions.send("alice".to_string(), SessionMsg::Touch).unwrap();
+//! sessions.send("alice".to_string(), SessionMsg::Touch).unwrap();
+//!
+//! assert_eq!(sessions.len(), 1);
+//! # }
+//! ```
+
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use tokio::sync::mpsc::error::SendError;
+
+use crate::ShutdownResult;
+use crate::core::errors::SendResult;
+use crate::runtime::Instant;
+use crate::task::{Task, TaskHandle, TaskRef};
+
+/// Default number of shards of a [`ShardRegion`].
+pub const DEFAULT_SHARDS: usize = 16;
+
+/// Default timeout for gracefully stopping evicted entities.
+pub const DEFAULT_EVICTION_TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Entity<T>
+where
+    T: 'static,
+{
+    handle: TaskHandle<T>,
+    last_used: Instant,
+}
+
+type Shard<K, T> = Mutex<HashMap<K, Entity<T>>>;
+
+type Factory<K, T> = Box<dyn Fn(&K) -> TaskHandle<T> + Send + Sync>;
+
+/// Strategy deciding which shard an entity is placed on.
+///
+/// The returned index is reduced modulo the number of shards, so
+/// implementations don't have to care about the exact shard count.
+pub trait Placement<K>: Send + Sync + 'static {
+    /// Choose the shard for `key` out of `shards` shards.
+    fn place(&self, key: &K, shards: usize) -> usize;
+}
+
+/// Default [`Placement`] distributing keys by their hash.
+#[derive(Debug, Clone, Default)]
+pub struct HashPlacement {
+    hasher: RandomState,
+}
+
+impl<K> Placement<K> for HashPlacement
+where
+    K: Hash,
+{
+    fn place(&self, key: &K, shards: usize) -> usize {
+        (self.hasher.hash_one(key) % shards as u64) as usize
+    }
+}
+
+/// A set of entity tasks addressed by key.
+///
+/// Each key maps to at most one running task. The task for a key is created
+/// by the factory passed to [`new`](Self::new) the first time a message is
+/// routed to that key, and is re-created if it has terminated in the
+/// meantime.
+///
+/// When a [maximum number of entities](Self::max_entities) is configured,
+/// the least recently used entity is gracefully shut down to make room for
+/// new ones. Entities can also be [passivated](Self::passivate_after) once
+/// they stayed idle for a while; they are re-activated on their next message.
+pub struct ShardRegion<K, T>
+where
+    T: 'static,
+{
+    factory: Factory<K, T>,
+    shards: RwLock<Vec<Shard<K, T>>>,
+    placement: Box<dyn Placement<K>>,
+    max_entities: Option<usize>,
+    eviction_timeout: Duration,
+    idle_timeout: Option<Duration>,
+}
+
+impl<K, T> ShardRegion<K, T>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    T: Send + 'static,
+{
+    /// Create a new region that spawns entities with the given factory.
+    ///
+    /// The factory receives the key of the entity and returns the task state
+    /// for it. It is called whenever a key without a running entity receives
+    /// a message.
+    pub fn new<S, F>(factory: F) -> Self
+    where
+        S: Task<T> + 'static,
+        F: Fn(&K) -> S + Send + Sync + 'static,
+    {
+        Self::with_spawner(move |key| factory(key).run())
+    }
+
+    /// Create a new region that spawns entities with a custom spawner.
+    ///
+    /// Unlike [`new`](Self::new), the spawner is responsible for spawning the
+    /// entity itself and returns the handle of the running task.
+    pub fn with_spawner<F>(spawner: F) -> Self
+    where
+        F: Fn(&K) -> TaskHandle<T> + Send + Sync + 'static,
+    {
+        ShardRegion {
+            factory: Box::new(spawner),
+            shards: RwLock::new(Self::empty_shards(DEFAULT_SHARDS)),
+            placement: Box::new(HashPlacement::default()),
+            max_entities: None,
+            eviction_timeout: DEFAULT_EVICTION_TIMEOUT,
+            idle_timeout: None,
+        }
+    }
+
+    /// Set the number of shards the entities are distributed over.
+    ///
+    /// A shard count of zero is treated as one.
+    pub fn with_shards(self, shards: usize) -> Self {
+        *self.shards.write().unwrap() = Self::empty_shards(shards);
+        self
+    }
+
+    /// Set the strategy deciding which shard an entity is placed on.
+    ///
+    /// Changing the placement redistributes all existing entities.
+    pub fn placement<P>(mut self, placement: P) -> Self
+    where
+        P: Placement<K>,
+    {
+        self.placement = Box::new(placement);
+        let shards = self.shard_count();
+        self.rebalance(shards);
+        self
+    }
+
+    /// Limit the number of concurrently running entities.
+    ///
+    /// Once the limit is exceeded, the least recently used entity is evicted
+    /// and gracefully shut down in the background.
+    pub fn max_entities(mut self, max: usize) -> Self {
+        self.max_entities = Some(max);
+        self
+    }
+
+    /// Set the timeout for gracefully stopping evicted entities.
+    pub fn eviction_timeout(mut self, timeout: Duration) -> Self {
+        self.eviction_timeout = timeout;
+        self
+    }
+
+    /// Passivate entities that received nothing for `idle`.
+    ///
+    /// Passivated entities stop gracefully with
+    /// [`TerminateReason::Idle`](crate::TerminateReason::Idle). The next
+    /// message for their key spawns a fresh entity.
+    pub fn passivate_after(mut self, idle: Duration) -> Self {
+        self.idle_timeout = Some(idle);
+        self
+    }
+
+    fn empty_shards(count: usize) -> Vec<Shard<K, T>> {
+        (0..count.max(1))
+            .map(|_| Mutex::new(HashMap::new()))
+            .collect()
+    }
+
+    fn shard_index(&self, key: &K, shards: usize) -> usize {
+        self.placement.place(key, shards) % shards
+    }
+
+    /// Index of the shard the given key is assigned to.
+    pub fn shard_of(&self, key: &K) -> usize {
+        let shards = self.shards.read().unwrap();
+        self.shard_index(key, shards.len())
+    }
+
+    /// Number of shards in this region.
+    pub fn shard_count(&self) -> usize {
+        self.shards.read().unwrap().len()
+    }
+
+    /// Route a message to the entity for `key`, spawning it if necessary.
+    ///
+    /// If the entity has terminated since it was last used, a fresh entity is
+    /// spawned and receives the message instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the freshly
+    /// spawned entity terminated before it could receive the message.
+    pub fn send(&self, key: K, msg: T) -> SendResult<T> {
+        let result = {
+            let shards = self.shards.read().unwrap();
+            let mut shard = shards[self.shard_index(&key, shards.len())].lock().unwrap();
+
+            let msg = match shard.get_mut(&key) {
+                Some(entity) => {
+                    entity.last_used = Instant::now();
+                    match entity.handle.send(msg) {
+                        Ok(()) => return Ok(()),
+                        Err(SendError(msg)) => msg,
+                    }
+                }
+                None => msg,
+            };
+
+            let entity = self.spawn_entity(&key);
+            let result = entity.handle.send(msg);
+            shard.insert(key.clone(), entity);
+            result
+        };
+
+        self.enforce_limit(&key);
+        result
+    }
+
+    /// Get a reference to the entity for `key`, spawning it if necessary.
+    pub fn entity(&self, key: K) -> TaskRef<T> {
+        let task_ref = {
+            let shards = self.shards.read().unwrap();
+            let mut shard = shards[self.shard_index(&key, shards.len())].lock().unwrap();
+
+            match shard.get_mut(&key) {
+                Some(entity) if !entity.handle.is_finished() => {
+                    entity.last_used = Instant::now();
+                    return entity.handle.this();
+                }
+                _ => {
+                    let entity = self.spawn_entity(&key);
+                    let task_ref = entity.handle.this();
+                    shard.insert(key.clone(), entity);
+                    task_ref
+                }
+            }
+        };
+
+        self.enforce_limit(&key);
+        task_ref
+    }
+
+    /// Send a copy of a message to every entity currently known to the region.
+    ///
+    /// Unlike [`send`](Self::send), this never spawns entities, and it doesn't
+    /// count as use for [evicting](Self::max_entities) the least recently
+    /// used entity. Entities that terminated on their own report an error.
+    ///
+    /// The message is cloned once per entity; wrap large payloads in an
+    /// [`ArcMessage`](crate::core::ArcMessage) to share them instead.
+    pub fn broadcast(&self, msg: T) -> Vec<(K, SendResult<T>)>
+    where
+        T: Clone,
+    {
+        let shards = self.shards.read().unwrap();
+        shards
+            .iter()
+            .flat_map(|s| {
+                s.lock()
+                    .unwrap()
+                    .iter()
+                    .map(|(key, entity)| (key.clone(), entity.handle.send(msg.clone())))
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    fn spawn_entity(&self, key: &K) -> Entity<T> {
+        let handle = (self.factory)(key);
+        if let Some(idle) = self.idle_timeout {
+            handle.passivate_after(idle);
+        }
+
+        Entity {
+            handle,
+            last_used: Instant::now(),
+        }
+    }
+
+    /// Check whether an entity for `key` is currently known to the region.
+    pub fn contains_key(&self, key: &K) -> bool {
+        let shards = self.shards.read().unwrap();
+        shards[self.shard_index(key, shards.len())]
+            .lock()
+            .unwrap()
+            .contains_key(key)
+    }
+
+    /// Number of entities currently known to the region.
+    ///
+    /// Entities that terminated on their own are counted until they are
+    /// [pruned](Self::prune) or receive their next message.
+    pub fn len(&self) -> usize {
+        let shards = self.shards.read().unwrap();
+        shards.iter().map(|s| s.lock().unwrap().len()).sum()
+    }
+
+    /// Check whether the region has no entities.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Keys of all entities currently known to the region.
+    pub fn keys(&self) -> Vec<K> {
+        let shards = self.shards.read().unwrap();
+        shards
+            .iter()
+            .flat_map(|s| s.lock().unwrap().keys().cloned().collect::<Vec<_>>())
+            .collect()
+    }
+
+    /// Remove entities whose tasks have terminated on their own.
+    ///
+    /// Returns the number of removed entities.
+    pub fn prune(&self) -> usize {
+        let shards = self.shards.read().unwrap();
+        shards
+            .iter()
+            .map(|s| {
+                let mut shard = s.lock().unwrap();
+                let before = shard.len();
+                shard.retain(|_, entity| !entity.handle.is_finished());
+                before - shard.len()
+            })
+            .sum()
+    }
+
+    /// Redistribute all entities over a new number of shards.
+    ///
+    /// Running entities are kept; only their shard assignment changes.
+    pub fn rebalance(&self, shards: usize) {
+        let mut current = self.shards.write().unwrap();
+        let next = Self::empty_shards(shards);
+
+        for shard in current.drain(..) {
+            for (key, entity) in shard.into_inner().unwrap() {
+                let index = self.shard_index(&key, next.len());
+                next[index].lock().unwrap().insert(key, entity);
+            }
+        }
+
+        *current = next;
+    }
+
+    /// Gracefully stop the entity for `key` and remove it from the region.
+    ///
+    /// Returns `None` if no entity exists for the key. A later message for the
+    /// key spawns a fresh entity.
+    pub async fn evict(&self, key: &K, timeout: Duration) -> Option<ShutdownResult> {
+        let entity = {
+            let shards = self.shards.read().unwrap();
+            shards[self.shard_index(key, shards.len())]
+                .lock()
+                .unwrap()
+                .remove(key)
+        }?;
+
+        Some(entity.handle.shutdown(timeout).await)
+    }
+
+    /// Gracefully stop all entities, returning each entity's result.
+    pub async fn shutdown(self, timeout: Duration) -> Vec<(K, ShutdownResult)> {
+        let entities: Vec<_> = self
+            .shards
+            .into_inner()
+            .unwrap()
+            .into_iter()
+            .flat_map(|s| s.into_inner().unwrap())
+            .collect();
+
+        crate::futures::future::join_all(
+            entities
+                .into_iter()
+                .map(|(key, entity)| async move { (key, entity.handle.shutdown(timeout).await) }),
+        )
+        .await
+    }
+
+    fn enforce_limit(&self, keep: &K) {
+        let Some(max) = self.max_entities else {
+            return;
+        };
+
+        while self.len() > max {
+            let shards = self.shards.read().unwrap();
+
+            let victim = shards
+                .iter()
+                .enumerate()
+                .flat_map(|(index, s)| {
+                    s.lock()
+                        .unwrap()
+                        .iter()
+                        .filter(|(key, _)| *key != keep)
+                        .map(|(key, entity)| (entity.last_used, index, key.clone()))
+                        .collect::<Vec<_>>()
+                })
+                .min_by_key(|(last_used, _, _)| *last_used);
+
+            let Some((_, index, key)) = victim else {
+                return;
+            };
+
+            if let Some(entity) = shards[index].lock().unwrap().remove(&key) {
+                crate::runtime::spawn("eviction", entity.handle.shutdown(self.eviction_timeout));
+            }
+        }
+    }
+}
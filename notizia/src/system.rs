@@ -0,0 +1,658 @@
+//! Named task hierarchies.
+//!
+//! A [`System`] keeps track of the tasks added to it under a name. It
+//! [registers](crate::registry) each task, remembers which task of the
+//! system spawned it and counts how often a name was taken over by a new
+//! task, i.e. how often the task was restarted.
+//!
+//! [`export_dot`](System::export_dot) renders the current hierarchy for
+//! Graphviz, [`export_json`](System::export_json) for other tools, which
+//! helps debugging and documenting the running topology.
+//!
+//! # Startup order
+//!
+//! Tasks that depend on each other can be [declared](System::declare) as a
+//! [`Spec`] naming the tasks they start [after](Spec::after).
+//! [`start`](System::start) then spawns them in dependency order, starting
+//! each task only once the tasks it depends on are ready, i.e. answer a
+//! [heartbeat](crate::task::TaskRef::ping). Since a task answers heartbeats
+//! while it waits for messages, a task is ready once it has initialized
+//! itself in `start()` and waits on its mailbox for the first time.
+//!
+//! ```no_run
+//! # use notizia::prelude::*;
+//! # use notizia::system::{Spec, System};
+//! # #[derive(Task)]
+//! # #[task(message = String)]
+//! # struct Db;
+//! # impl Runnable<String> for Db {
+//! #     async fn start(&self) {}
+//! # }
+//! # #[derive(Task)]
+//! # #[task(message = String)]
+//! # struct Api;
+//! # impl Runnable<String> for Api {
+//! #     async fn start(&self) {}
+//! # }
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), notizia::system::StartError> {
+//! let system = System::new();
+//! system.declare(Spec::new("api", Api).after("db"));
+//! system.declare(Spec::new("db", Db));
+//!
+//! // Starts `db`, waits until it is ready, then starts `api`
+//! system.start().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Lazy tasks
+//!
+//! [`register_lazy`](System::register_lazy) registers a task without
+//! spawning it. The returned [`TaskRef`] can be handed out right away; the
+//! task is only spawned once its first message arrives, so rarely used
+//! services cost nothing at startup.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::system::System;
+//!
+//! #[derive(Task)]
+//! #[task(message = String)]
+//! struct Db;
+//!
+//! impl Runnable<String> for Db {
+//!     async fn start(&self) {
+//!         while let Ok(query) = recv!(self) {
+//!             println!("{query}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let system = System::new();
+//! let db = system.spawn("db", Db);
+//!
+//! assert!(system.registry().whereis::<String>("db").is_some());
+//!
+//! // Render with `dot -Tsvg system.dot -o system.svg`
+//! std::fs::write("system.dot", system.export_dot()).unwrap();
+//! # }
+//! ```
+
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+
+use crate::core::errors::{CallError, CallResult};
+use crate::core::{DEFAULT_CALL_TIMEOUT, TaskState};
+use crate::registry::Registry;
+use crate::runtime;
+use crate::task::respawn::AnyIncarnation;
+use crate::task::{SpawnOptions, Task, TaskHandle, TaskId, TaskRef};
+
+/// Reports the status of a task.
+type Status = Box<dyn Fn() -> TaskStatus + Send + Sync>;
+
+/// Spawns a declared task under the given name, resolving once it answered
+/// a heartbeat within the given timeout.
+type Starter =
+    Box<dyn FnOnce(&System, String, Duration) -> BoxFuture<'static, CallResult<Duration>> + Send>;
+
+/// A handle of a task started by a system.
+trait Started: Send {
+    fn is_finished(&self) -> bool;
+}
+
+impl<T> Started for TaskHandle<T>
+where
+    T: Send + 'static,
+{
+    fn is_finished(&self) -> bool {
+        TaskHandle::is_finished(self)
+    }
+}
+
+/// A task added to a system.
+struct Node {
+    id: TaskId,
+    task: &'static str,
+    parent: Option<String>,
+    restarts: u32,
+    status: Status,
+}
+
+/// A set of named tasks and the hierarchy between them.
+///
+/// Cloning a system is cheap; all clones share the same tasks.
+#[derive(Clone, Default)]
+pub struct System {
+    registry: Registry,
+    nodes: Arc<Mutex<BTreeMap<String, Node>>>,
+    specs: Arc<Mutex<Vec<Spec>>>,
+    /// Handles of the tasks started by the system, keeping them alive
+    started: Arc<Mutex<Vec<Box<dyn Started>>>>,
+    /// Handles of the singletons, by the type of their task
+    singletons: Arc<Mutex<HashMap<TypeId, Box<dyn Any + Send>>>>,
+}
+
+impl System {
+    /// Create an empty system.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The registry the system's tasks are registered in.
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// Spawn `task` and add it to the system under `name`.
+    ///
+    /// See [`add`](Self::add).
+    pub fn spawn<S, T>(&self, name: impl Into<String>, task: S) -> TaskHandle<T>
+    where
+        S: Task<T>,
+        T: Send + 'static,
+    {
+        self.add(name, task.run())
+    }
+
+    /// Add a spawned task to the system under `name`, e.g. one spawned with
+    /// a [`SpawnBuilder`](crate::task::SpawnBuilder).
+    ///
+    /// The task is registered in the system's [registry](Self::registry).
+    /// When called from within another task of the system, that task becomes
+    /// its parent. A task replacing an earlier one under the same name counts
+    /// as a restart.
+    pub fn add<T>(&self, name: impl Into<String>, handle: TaskHandle<T>) -> TaskHandle<T>
+    where
+        T: Send + 'static,
+    {
+        let name = name.into();
+        self.registry.register(name.clone(), &handle.this());
+
+        let mut nodes = self.nodes.lock().unwrap();
+        let parent = TaskId::current().and_then(|current| {
+            nodes
+                .iter()
+                .find(|(_, node)| node.id == current)
+                .map(|(name, _)| name.clone())
+        });
+        let restarts = match nodes.get(&name) {
+            Some(previous) if previous.id != handle.id() => previous.restarts + 1,
+            Some(previous) => previous.restarts,
+            None => 0,
+        };
+
+        nodes.insert(
+            name,
+            Node {
+                id: handle.id(),
+                task: handle.name(),
+                parent,
+                restarts,
+                status: Box::new(handle.status()),
+            },
+        );
+        handle
+    }
+
+    /// Register a task under `name` that is only spawned once it receives
+    /// its first message.
+    ///
+    /// The returned reference is valid right away. Messages sent through it
+    /// are queued, and the first one spawns the task built by `factory` and
+    /// adds it to the system, which keeps it alive from then on.
+    /// [Heartbeats](TaskRef::ping) are answered without spawning the task.
+    /// If every reference to the task is dropped before it receives a
+    /// message, it is never spawned.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use notizia::system::System;
+    /// # #[derive(Task, Default)]
+    /// # #[task(message = String)]
+    /// # struct Cache;
+    /// # impl Runnable<String> for Cache {
+    /// #     async fn start(&self) {}
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let system = System::new();
+    /// let cache = system.register_lazy("cache", Cache::default);
+    ///
+    /// // Spawns the cache
+    /// cache.send("warm up".to_string()).unwrap();
+    /// # }
+    /// ```
+    pub fn register_lazy<S, T, F>(&self, name: impl Into<String>, factory: F) -> TaskRef<T>
+    where
+        S: Task<T> + 'static,
+        T: Send + 'static,
+        F: FnOnce() -> S + Send + 'static,
+    {
+        let name = name.into();
+        let (task, mailbox, receiver) = SpawnOptions::default().channel::<T>(short_name::<S>());
+        let task = task.with_path(std::any::type_name::<S>());
+        self.registry.register(name.clone(), &task);
+
+        // Waiting for the first message must not keep the mailbox open
+        let state = TaskState::new(mailbox, &task);
+        let system = self.clone();
+        runtime::spawn(task.name(), async move {
+            state.mailbox.set_receiver(receiver).await;
+            let activated = match state.mailbox.receiver.lock().await.as_mut() {
+                Some(inbox) => inbox.first().await,
+                None => false,
+            };
+            if !activated {
+                return;
+            }
+
+            let mut options = SpawnOptions::default();
+            options.incarnation = Some(AnyIncarnation::new(
+                state.task_ref(),
+                state.mailbox.receiver.clone(),
+            ));
+            let handle = system.add(name, factory().__spawn(options));
+            system.keep(handle);
+        });
+
+        task
+    }
+
+    /// The instance of the task type `S`, spawning one built by `factory`
+    /// if none is running.
+    ///
+    /// Concurrent callers all get the same task: `factory` may be called by
+    /// several of them, but only one instance is spawned. Since `factory`
+    /// runs without locking the system, it may use the system, e.g. to get
+    /// other singletons. The instance is added to the system under the name
+    /// of its type and kept alive by it. Once it has terminated, the next
+    /// call spawns a new one.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use notizia::system::System;
+    /// # #[derive(Task, Default)]
+    /// # #[task(message = String)]
+    /// # struct Cache;
+    /// # impl Runnable<String> for Cache {
+    /// #     async fn start(&self) {}
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let system = System::new();
+    /// let cache = system.singleton(Cache::default);
+    /// let again = system.singleton(Cache::default);
+    ///
+    /// assert_eq!(cache.id(), again.id());
+    /// # }
+    /// ```
+    pub fn singleton<S, T, F>(&self, factory: F) -> TaskRef<T>
+    where
+        S: Task<T> + 'static,
+        T: Send + 'static,
+        F: FnOnce() -> S,
+    {
+        let running = |singletons: &HashMap<TypeId, Box<dyn Any + Send>>| {
+            singletons
+                .get(&TypeId::of::<S>())
+                .and_then(|handle| handle.downcast_ref::<TaskHandle<T>>())
+                .filter(|handle| !handle.is_finished())
+                .map(|handle| handle.this())
+        };
+        if let Some(task) = running(&self.singletons.lock().unwrap()) {
+            return task;
+        }
+
+        let instance = factory();
+
+        // Another caller may have spawned an instance meanwhile, in which
+        // case this one is dropped without being spawned
+        let mut singletons = self.singletons.lock().unwrap();
+        if let Some(task) = running(&singletons) {
+            return task;
+        }
+        let handle = self.add(short_name::<S>(), instance.run());
+        let task = handle.this();
+        singletons.insert(TypeId::of::<S>(), Box::new(handle));
+        task
+    }
+
+    /// Keep `handle` alive as long as the system, forgetting the handles of
+    /// tasks that have terminated.
+    fn keep<T>(&self, handle: TaskHandle<T>)
+    where
+        T: Send + 'static,
+    {
+        let mut started = self.started.lock().unwrap();
+        started.retain(|handle| !handle.is_finished());
+        started.push(Box::new(handle));
+    }
+
+    /// Declare a task for [`start`](Self::start) to spawn.
+    pub fn declare(&self, spec: Spec) {
+        self.specs.lock().unwrap().push(spec);
+    }
+
+    /// Spawn all declared tasks in dependency order.
+    ///
+    /// A task is only spawned once every task it was declared to start
+    /// [after](Spec::after) is ready, i.e. answered a heartbeat. Tasks that
+    /// were already added to the system count as ready. The started tasks
+    /// are added to the system, which keeps them alive as long as it exists.
+    ///
+    /// The declarations are consumed, even if starting fails.
+    ///
+    /// # Errors
+    ///
+    /// Fails before spawning any task with [`StartError::Cycle`] if the
+    /// dependencies form a cycle and with [`StartError::UnknownDependency`]
+    /// if a task depends on a name that is neither declared nor added. Stops
+    /// at the first task that does not become ready in time with
+    /// [`StartError::NotReady`].
+    pub async fn start(&self) -> Result<(), StartError> {
+        let specs = std::mem::take(&mut *self.specs.lock().unwrap());
+
+        for spec in self.order(specs)? {
+            let ready = (spec.start)(self, spec.name.clone(), spec.ready_timeout);
+            ready.await.map_err(|source| StartError::NotReady {
+                task: spec.name,
+                source,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Sort `specs` so that every task comes after its dependencies.
+    fn order(&self, specs: Vec<Spec>) -> Result<Vec<Spec>, StartError> {
+        let declared: HashSet<String> = specs.iter().map(|spec| spec.name.clone()).collect();
+        {
+            let nodes = self.nodes.lock().unwrap();
+            for spec in &specs {
+                if let Some(dependency) = spec.after.iter().find(|dependency| {
+                    !declared.contains(*dependency) && !nodes.contains_key(*dependency)
+                }) {
+                    return Err(StartError::UnknownDependency {
+                        task: spec.name.clone(),
+                        dependency: dependency.clone(),
+                    });
+                }
+            }
+        }
+
+        let mut ordered = Vec::with_capacity(specs.len());
+        let mut done = HashSet::new();
+        let mut pending = specs;
+        while !pending.is_empty() {
+            let (ready, blocked): (Vec<_>, Vec<_>) = pending.into_iter().partition(|spec| {
+                spec.after
+                    .iter()
+                    .all(|dependency| done.contains(dependency) || !declared.contains(dependency))
+            });
+            if ready.is_empty() {
+                return Err(StartError::Cycle(cycle(&blocked)));
+            }
+
+            done.extend(ready.iter().map(|spec| spec.name.clone()));
+            ordered.extend(ready);
+            pending = blocked;
+        }
+        Ok(ordered)
+    }
+
+    /// Snapshot of all tasks in the system, ordered by name.
+    pub fn tasks(&self) -> Vec<TaskNode> {
+        self.nodes
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, node)| TaskNode {
+                name: name.clone(),
+                id: node.id,
+                task: node.task,
+                parent: node.parent.clone(),
+                status: (node.status)(),
+                restarts: node.restarts,
+            })
+            .collect()
+    }
+
+    /// Render the task hierarchy in the Graphviz DOT language.
+    ///
+    /// Every task is a node labelled with its name, task type, id, status
+    /// and restart count; edges point from parents to the tasks they
+    /// spawned.
+    pub fn export_dot(&self) -> String {
+        let tasks = self.tasks();
+        let mut dot = String::from("digraph system {\n    node [shape=box];\n");
+
+        for task in &tasks {
+            let label = format!(
+                "{}\n{} #{}\n{}, {} restarts",
+                task.name, task.task, task.id, task.status, task.restarts
+            );
+            let _ = writeln!(dot, "    {} [label={}];", quote(&task.name), quote(&label));
+        }
+        for task in &tasks {
+            if let Some(parent) = &task.parent {
+                let _ = writeln!(dot, "    {} -> {};", quote(parent), quote(&task.name));
+            }
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Render the task hierarchy as JSON.
+    ///
+    /// The result is an array of objects with the fields of [`TaskNode`],
+    /// ordered by name, e.g.
+    /// `[{"name":"db","task":"Db","id":1,"parent":null,"status":"running","restarts":0}]`.
+    pub fn export_json(&self) -> String {
+        let tasks: Vec<_> = self
+            .tasks()
+            .iter()
+            .map(|task| {
+                format!(
+                    r#"{{"name":{},"task":{},"id":{},"parent":{},"status":{},"restarts":{}}}"#,
+                    quote(&task.name),
+                    quote(task.task),
+                    task.id,
+                    task.parent.as_deref().map_or("null".into(), quote),
+                    quote(&task.status.to_string()),
+                    task.restarts,
+                )
+            })
+            .collect();
+
+        format!("[{}]", tasks.join(","))
+    }
+}
+
+/// A task declared for [`System::start`].
+pub struct Spec {
+    name: String,
+    after: Vec<String>,
+    ready_timeout: Duration,
+    start: Starter,
+}
+
+impl Spec {
+    /// Declare `task`, to be added to the system under `name`.
+    pub fn new<S, T>(name: impl Into<String>, task: S) -> Self
+    where
+        S: Task<T> + 'static,
+        T: Send + 'static,
+    {
+        Spec {
+            name: name.into(),
+            after: Vec::new(),
+            ready_timeout: DEFAULT_CALL_TIMEOUT,
+            start: Box::new(move |system, name, timeout| {
+                let handle = system.spawn(name, task);
+                let task = handle.this();
+                system.keep(handle);
+                Box::pin(async move { task.ping(timeout).await })
+            }),
+        }
+    }
+
+    /// Start the task only once the task named `name` is ready.
+    ///
+    /// Can be called several times.
+    pub fn after(mut self, name: impl Into<String>) -> Self {
+        self.after.push(name.into());
+        self
+    }
+
+    /// Time the task has to become ready, [`DEFAULT_CALL_TIMEOUT`] by
+    /// default.
+    pub fn ready_timeout(mut self, timeout: Duration) -> Self {
+        self.ready_timeout = timeout;
+        self
+    }
+}
+
+impl std::fmt::Debug for Spec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Spec")
+            .field("name", &self.name)
+            .field("after", &self.after)
+            .field("ready_timeout", &self.ready_timeout)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Errors of [`System::start`].
+#[derive(Debug, thiserror::Error)]
+pub enum StartError {
+    /// The dependencies between the declared tasks form a cycle, given as
+    /// the names along it
+    #[error("dependency cycle: {}", .0.join(" -> "))]
+    Cycle(Vec<String>),
+    /// A task depends on a name that is neither declared nor added
+    #[error("{task} depends on unknown task {dependency}")]
+    UnknownDependency {
+        /// Name of the declared task
+        task: String,
+        /// Name of the missing dependency
+        dependency: String,
+    },
+    /// A task did not answer its heartbeat in time
+    #[error("{task} did not become ready: {source}")]
+    NotReady {
+        /// Name of the task
+        task: String,
+        /// Why the heartbeat failed
+        source: CallError,
+    },
+}
+
+/// Find a cycle among `blocked`, where every task depends on another
+/// blocked task.
+fn cycle(blocked: &[Spec]) -> Vec<String> {
+    let specs: HashMap<&str, &Spec> = blocked
+        .iter()
+        .map(|spec| (spec.name.as_str(), spec))
+        .collect();
+
+    let mut path = vec![blocked[0].name.as_str()];
+    loop {
+        let next = specs[path[path.len() - 1]]
+            .after
+            .iter()
+            .map(String::as_str)
+            .find(|dependency| specs.contains_key(dependency))
+            .expect("blocked task without blocked dependency");
+
+        if let Some(start) = path.iter().position(|name| *name == next) {
+            let mut cycle: Vec<String> = path[start..].iter().map(ToString::to_string).collect();
+            cycle.push(next.to_string());
+            return cycle;
+        }
+        path.push(next);
+    }
+}
+
+/// A task of a [`System`], as returned by [`System::tasks`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TaskNode {
+    /// Name the task was added under
+    pub name: String,
+    /// Identifier of the task
+    pub id: TaskId,
+    /// Name of the task type
+    pub task: &'static str,
+    /// Name of the task that added this one, if it belongs to the system
+    pub parent: Option<String>,
+    /// Current status of the task
+    pub status: TaskStatus,
+    /// Number of tasks this one replaced under its name
+    pub restarts: u32,
+}
+
+/// The status of a task in a [`System`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TaskStatus {
+    /// The task is running
+    Running,
+    /// A message has been processing for longer than the task's
+    /// [watchdog](crate::task::watchdog) allows
+    Stalled,
+    /// The task was passivated because it was idle
+    Passivated,
+    /// The task has terminated
+    Terminated,
+}
+
+impl std::fmt::Display for TaskStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            TaskStatus::Running => "running",
+            TaskStatus::Stalled => "stalled",
+            TaskStatus::Passivated => "passivated",
+            TaskStatus::Terminated => "terminated",
+        })
+    }
+}
+
+/// Name of the task type `S`, without its path and generic parameters.
+fn short_name<S>() -> &'static str {
+    let name = std::any::type_name::<S>();
+    let name = name.split('<').next().unwrap_or(name);
+    name.rsplit("::").next().unwrap_or(name)
+}
+
+/// Quote `text` as a string in both DOT and JSON.
+fn quote(text: &str) -> String {
+    let mut quoted = String::with_capacity(text.len() + 2);
+    quoted.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            c if c.is_control() => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
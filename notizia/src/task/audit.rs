@@ -0,0 +1,174 @@
+//! Audit log of the messages a task processed.
+//!
+//! A handler task spawned with [`SpawnBuilder::audit`](super::SpawnBuilder::audit)
+//! reports every message it dispatched to an [`AuditSink`] once its handler
+//! completed: which message type was handled, with which [`Outcome`], when
+//! and for how long, and under which correlation id. This answers "what did
+//! this service do and when" without touching the handlers.
+//!
+//! Sinks are called synchronously on the task, so slow sinks should hand
+//! records off, e.g. to a channel or another task. Closures taking an
+//! [`AuditRecord`] are sinks.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::task::AuditRecord;
+//!
+//! #[derive(Debug)]
+//! enum Command {
+//!     Transfer(u64),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = Command, handler)]
+//! struct Ledger;
+//!
+//! impl Handler<Command> for Ledger {
+//!     async fn handle(&mut self, msg: Command, _ctx: &mut Context<Command>) {
+//!         println!("{msg:?}");
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let handle = Ledger
+//!     .builder()
+//!     .audit(|record: &AuditRecord| {
+//!         println!(
+//!             "{} handled {} at {:?}: {:?} in {:?}",
+//!             record.name, record.message, record.at, record.outcome, record.duration
+//!         );
+//!     })
+//!     .spawn();
+//! handle.send(Command::Transfer(100)).unwrap();
+//! # }
+//! ```
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::SystemTime;
+
+#[cfg(target_arch = "wasm32")]
+use web_time::SystemTime;
+
+use super::TaskId;
+use crate::core::correlation::CorrelationId;
+use crate::runtime::Instant;
+
+/// How handling a message ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Outcome {
+    /// The handler returned
+    Handled,
+    /// The handler returned and [stopped](super::Context::stop) the task
+    Stopped,
+    /// The handler panicked
+    Panicked,
+    /// The handler was interrupted, e.g. because the task was killed
+    Cancelled,
+}
+
+/// A message processed by a task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditRecord {
+    /// The task that processed the message
+    pub task: TaskId,
+    /// Name of the task
+    pub name: &'static str,
+    /// Type name of the message
+    pub message: &'static str,
+    /// How handling the message ended
+    pub outcome: Outcome,
+    /// When the handler started
+    pub at: SystemTime,
+    /// How long the handler ran
+    pub duration: Duration,
+    /// Correlation id of the message, if it is part of a request chain
+    pub correlation: Option<CorrelationId>,
+}
+
+/// Destination of a task's [`AuditRecord`]s.
+pub trait AuditSink: Send + Sync + 'static {
+    /// Record a processed message.
+    fn record(&self, record: &AuditRecord);
+}
+
+impl<F> AuditSink for F
+where
+    F: Fn(&AuditRecord) + Send + Sync + 'static,
+{
+    fn record(&self, record: &AuditRecord) {
+        self(record)
+    }
+}
+
+/// The audit sink of a task.
+#[derive(Clone)]
+pub(crate) struct Auditor(Arc<dyn AuditSink>);
+
+impl Auditor {
+    pub(crate) fn new(sink: impl AuditSink) -> Self {
+        Auditor(Arc::new(sink))
+    }
+
+    /// Start auditing a message of type `M`.
+    ///
+    /// The message is recorded as [`Outcome::Panicked`] or
+    /// [`Outcome::Cancelled`] unless the audit is [finished](Audit::finish).
+    pub(crate) fn start<M>(&self, task: TaskId, name: &'static str) -> Audit {
+        Audit {
+            sink: self.0.clone(),
+            record: Some(AuditRecord {
+                task,
+                name,
+                message: std::any::type_name::<M>(),
+                outcome: Outcome::Cancelled,
+                at: SystemTime::now(),
+                duration: Duration::ZERO,
+                correlation: CorrelationId::current(),
+            }),
+            started: Instant::now(),
+        }
+    }
+}
+
+impl fmt::Debug for Auditor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Auditor").finish_non_exhaustive()
+    }
+}
+
+/// A message being audited, recorded when dropped.
+pub(crate) struct Audit {
+    sink: Arc<dyn AuditSink>,
+    record: Option<AuditRecord>,
+    started: Instant,
+}
+
+impl Audit {
+    /// Record the message with `outcome`.
+    pub(crate) fn finish(mut self, outcome: Outcome) {
+        if let Some(record) = &mut self.record {
+            record.outcome = outcome;
+        }
+    }
+}
+
+impl Drop for Audit {
+    fn drop(&mut self) {
+        let Some(mut record) = self.record.take() else {
+            return;
+        };
+
+        if record.outcome == Outcome::Cancelled && std::thread::panicking() {
+            record.outcome = Outcome::Panicked;
+        }
+        record.duration = self.started.elapsed();
+        self.sink.record(&record);
+    }
+}
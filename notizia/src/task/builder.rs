@@ -0,0 +1,463 @@
+//! Spawning tasks with per-spawn options.
+
+use std::future::Future;
+use std::marker::PhantomData;
+#[cfg(feature = "spsc")]
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::FutureExt;
+#[cfg(feature = "spill")]
+use serde::{Serialize, de::DeserializeOwned};
+use tokio::sync::mpsc::UnboundedReceiver;
+
+use super::audit::{AuditSink, Auditor};
+use super::middleware::{Layers, Middleware};
+use super::respawn::{AnyIncarnation, MailboxPolicy, Restart};
+use super::watchdog::Watchdog;
+use super::{Control, Task, TaskHandle, TaskId, TaskRef};
+use crate::TerminateReason;
+use crate::core::Envelope;
+use crate::core::correlation;
+use crate::core::mailbox::{Mailbox, MailboxConfig, Overflow};
+#[cfg(feature = "spill")]
+use crate::core::spill::{AnySpill, Spill, SpillFile};
+#[cfg(feature = "spsc")]
+use crate::core::spsc::Ring;
+use crate::runtime;
+
+/// Builder for spawning a task with custom options.
+///
+/// Created with [`Task::builder`]. Options that are not set keep the
+/// defaults used by [`Task::run`] and [`spawn!`](crate::spawn!).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::core::bounded;
+/// # use std::time::Duration;
+/// # #[derive(Task)]
+/// # #[task(message = Signal)]
+/// # struct Worker { id: usize }
+/// # impl Runnable<Signal> for Worker {
+/// #     async fn start(&self) {}
+/// # }
+/// # #[derive(Clone)]
+/// # enum Signal {}
+/// # #[tokio::main]
+/// # async fn main() {
+/// let handle = Worker { id: 1 }
+///     .builder()
+///     .name("w1")
+///     .mailbox(bounded(64))
+///     .shutdown_timeout(Duration::from_secs(5))
+///     .spawn();
+///
+/// assert_eq!(handle.name(), "w1");
+/// # }
+/// ```
+pub struct SpawnBuilder<S, T> {
+    task: S,
+    options: SpawnOptions,
+    _message: PhantomData<fn() -> T>,
+}
+
+impl<S, T> SpawnBuilder<S, T>
+where
+    S: Task<T>,
+    T: Send,
+{
+    /// Start building a spawn of `task`.
+    pub fn new(task: S) -> Self {
+        SpawnBuilder {
+            task,
+            options: SpawnOptions::default(),
+            _message: PhantomData,
+        }
+    }
+
+    /// Name the task instead of using the name of its type.
+    pub fn name(mut self, name: &'static str) -> Self {
+        self.options.name = Some(name);
+        self
+    }
+
+    /// Configure the task's mailbox.
+    ///
+    /// Overrides `mailbox = ...` of the task's `#[task(...)]` attribute.
+    pub fn mailbox(mut self, config: MailboxConfig) -> Self {
+        self.options.mailbox = Some(config);
+        self
+    }
+
+    /// Decide what a full [bounded](crate::core::bounded) mailbox does with
+    /// new messages.
+    ///
+    /// Overrides `overflow = ...` of the task's `#[task(...)]` attribute.
+    pub fn overflow(mut self, overflow: Overflow) -> Self {
+        self.options.overflow = Some(overflow);
+        self
+    }
+
+    /// Spill messages to `file` once `threshold` messages are queued in
+    /// memory.
+    ///
+    /// Spilled messages are delivered in order once the messages in memory
+    /// have been received; see [`core::spill`](crate::core::spill). The task
+    /// is no longer limited by a [bounded](crate::core::bounded) mailbox.
+    #[cfg(feature = "spill")]
+    pub fn spill(mut self, file: SpillFile, threshold: usize) -> Self
+    where
+        T: Serialize + DeserializeOwned + 'static,
+    {
+        self.options.spill = Some(AnySpill::new(Spill::<T>::new(file, threshold)));
+        self
+    }
+
+    /// Receive messages through a ring buffer holding up to `capacity`
+    /// messages, for tasks with a single producer.
+    ///
+    /// Sending to a full ring fails; see [`core::spsc`](crate::core::spsc).
+    /// The ring replaces a [bounded](crate::core::bounded) mailbox and is not
+    /// used if the mailbox [spills](Self::spill) to disk.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    #[cfg(feature = "spsc")]
+    pub fn single_producer(mut self, capacity: usize) -> Self {
+        assert!(capacity > 0, "ring capacity must be greater than zero");
+        self.options.single_producer = Some(capacity);
+        self
+    }
+
+    /// Run `layer` around every message the task receives.
+    ///
+    /// Can be called several times; see [`Middleware`] for the order in
+    /// which the layers run.
+    pub fn middleware(mut self, layer: impl Middleware<T> + 'static) -> Self
+    where
+        T: 'static,
+    {
+        self.options.middleware.push(layer);
+        self
+    }
+
+    /// Record every message a handler task processed to `sink`.
+    ///
+    /// Only tasks declared with `#[task(message = T, handler)]` are
+    /// audited, since other tasks receive messages themselves. See [`task::audit`](crate::task::audit).
+    pub fn audit(mut self, sink: impl AuditSink) -> Self {
+        self.options.audit = Some(Auditor::new(sink));
+        self
+    }
+
+    /// Yield to the runtime after every `messages` received messages.
+    ///
+    /// A task that always finds its next message waiting never yields on
+    /// its own, starving other tasks on the same worker thread while it
+    /// works through a large backlog. With a budget, it lets them run in
+    /// between. Only messages received by awaiting
+    /// [`recv`](crate::core::Mailbox::recv) count towards the budget.
+    ///
+    /// Overrides `budget = ...` of the task's `#[task(...)]` attribute.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `messages` is zero.
+    pub fn budget(mut self, messages: usize) -> Self {
+        assert!(messages > 0, "budget must be greater than zero");
+        self.options.budget = Some(messages);
+        self
+    }
+
+    /// Flag the task as stalled while a single message has been processing
+    /// for longer than `bound`, and emit a warning naming the message type.
+    ///
+    /// Only tasks declared with `#[task(message = T, handler)]` are
+    /// watched. See [`task::watchdog`](crate::task::watchdog).
+    pub fn watchdog(mut self, bound: Duration) -> Self {
+        self.options.watchdog = Some(Watchdog::new(bound));
+        self
+    }
+
+    /// Like [`watchdog`](Self::watchdog), but the warning also contains the
+    /// [`Debug`](std::fmt::Debug) output of the task before the stalled
+    /// message.
+    ///
+    /// The state is captured before every message, which costs as much as
+    /// formatting the task.
+    pub fn watchdog_with_state(mut self, bound: Duration) -> Self
+    where
+        S: std::fmt::Debug + 'static,
+    {
+        self.options.watchdog = Some(Watchdog::new(bound).dumping::<S>());
+        self
+    }
+
+    /// Keep the task's mailbox open after it terminates, so it can be
+    /// [respawned](TaskHandle::respawn) in place.
+    ///
+    /// Until the task is respawned, messages sent to it are queued instead
+    /// of failing. On respawn, they are put into the
+    /// [dead-letter queue](crate::core::dead_letter). See [`task::respawn`](crate::task::respawn).
+    pub fn restartable(self) -> Self {
+        self.restartable_with(MailboxPolicy::default())
+    }
+
+    /// Like [`restartable`](Self::restartable), with `policy` deciding
+    /// whether the respawned task receives the messages queued for its
+    /// previous incarnation.
+    pub fn restartable_with(mut self, policy: MailboxPolicy) -> Self {
+        self.options.restartable = Some(policy);
+        self
+    }
+
+    /// Time [`TaskHandle::stop`] gives the task to terminate.
+    pub fn shutdown_timeout(mut self, timeout: Duration) -> Self {
+        self.options.shutdown_timeout = Some(timeout);
+        self
+    }
+
+    /// Passivate the task once it has received nothing for `idle`.
+    ///
+    /// See [`TaskHandle::passivate_after`].
+    pub fn passivate_after(mut self, idle: Duration) -> Self {
+        self.options.idle_timeout = Some(idle);
+        self
+    }
+
+    /// Abort `start()` if it runs longer than `deadline`.
+    ///
+    /// Once the deadline has passed, `start()` is dropped at its current
+    /// await point and `terminate()` is called with
+    /// [`TerminateReason::DeadlineExceeded`]. Useful for batch jobs that must
+    /// not run forever.
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.options.deadline = Some(deadline);
+        self
+    }
+
+    /// Run the task's `start()` on Tokio's blocking pool.
+    ///
+    /// The task keeps its mailbox and can await as usual, but runs on a
+    /// thread of its own, so CPU-heavy work does not starve the async
+    /// runtime. This is what `#[task(message = T, blocking)]` enables by
+    /// default.
+    ///
+    /// Blocking tasks cannot be interrupted by [`TaskHandle::kill`]; stop
+    /// them by closing their mailbox, e.g. with [`TaskHandle::shutdown`].
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking(mut self) -> Self {
+        self.options = self.options.blocking();
+        self
+    }
+
+    /// Spawn the task on the current [runtime](crate::runtime).
+    pub fn spawn(self) -> TaskHandle<T> {
+        self.task.__spawn(self.options)
+    }
+}
+
+/// Options collected by a [`SpawnBuilder`].
+///
+/// This is an implementation detail of the generated code.
+#[doc(hidden)]
+#[derive(Debug, Clone, Default)]
+pub struct SpawnOptions {
+    name: Option<&'static str>,
+    mailbox: Option<MailboxConfig>,
+    overflow: Option<Overflow>,
+    shutdown_timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    deadline: Option<Duration>,
+    blocking: bool,
+    control: Option<Control>,
+    #[cfg(feature = "spill")]
+    spill: Option<AnySpill>,
+    #[cfg(feature = "spsc")]
+    single_producer: Option<usize>,
+    middleware: Layers,
+    audit: Option<Auditor>,
+    watchdog: Option<Watchdog>,
+    budget: Option<usize>,
+    restartable: Option<MailboxPolicy>,
+    pub(crate) incarnation: Option<AnyIncarnation>,
+}
+
+impl SpawnOptions {
+    /// Run the task on the blocking pool.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking(mut self) -> Self {
+        self.blocking = true;
+        self
+    }
+
+    /// Use `config` unless a mailbox was configured explicitly.
+    pub fn default_mailbox(mut self, config: MailboxConfig) -> Self {
+        self.mailbox.get_or_insert(config);
+        self
+    }
+
+    /// Use `overflow` unless an overflow policy was set explicitly.
+    pub fn default_overflow(mut self, overflow: Overflow) -> Self {
+        self.overflow.get_or_insert(overflow);
+        self
+    }
+
+    /// Use `budget` unless a budget was set explicitly.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `budget` is zero.
+    pub fn default_budget(mut self, budget: usize) -> Self {
+        assert!(budget > 0, "budget must be greater than zero");
+        self.budget.get_or_insert(budget);
+        self
+    }
+
+    /// Give the task a control mailbox for messages of type `C`.
+    pub fn control<C>(mut self) -> Self
+    where
+        C: Send + 'static,
+    {
+        self.control = Some(Control::new::<C>());
+        self
+    }
+
+    /// The time `start()` may run before it is aborted.
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    /// Create the reference, mailbox and receiver of a new task.
+    ///
+    /// `name` is used unless the task was named explicitly.
+    pub fn channel<T>(
+        &self,
+        name: &'static str,
+    ) -> (TaskRef<T>, Mailbox<T>, UnboundedReceiver<Envelope<T>>)
+    where
+        T: Send + 'static,
+    {
+        let (sender, receiver) = runtime::channel();
+        let id = TaskId::next();
+        let mut mailbox = Mailbox::with_config(
+            self.mailbox.unwrap_or_default(),
+            self.overflow.unwrap_or_default(),
+            self.middleware.resolve(self.name.unwrap_or(name)),
+        );
+        #[cfg(feature = "testing")]
+        {
+            mailbox.gate = crate::testing::scheduler::Gate::current(id);
+        }
+        mailbox.audit = self.audit.clone();
+        mailbox.watchdog = self.watchdog.clone();
+        mailbox.budget = self.budget;
+        #[cfg(feature = "spsc")]
+        if let Some(capacity) = self.single_producer {
+            mailbox.capacity = None;
+            mailbox.ring = Some(Arc::new(Ring::new(capacity)));
+        }
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            mailbox.capacity = None;
+            mailbox.spill = spill.downcast();
+            #[cfg(feature = "spsc")]
+            {
+                mailbox.ring = None;
+            }
+        }
+        if let Some(resumed) = self
+            .incarnation
+            .as_ref()
+            .and_then(|incarnation| incarnation.resume(mailbox.clone()))
+        {
+            return resumed;
+        }
+        let task = TaskRef::with_identity(sender, id, self.name.unwrap_or(name))
+            .with_capacity(mailbox.capacity.clone())
+            .with_control(self.control.clone());
+        #[cfg(feature = "spill")]
+        let task = task.with_spill(mailbox.spill.clone());
+        #[cfg(feature = "spsc")]
+        let task = task.with_ring(mailbox.ring.clone());
+
+        (task, mailbox, receiver)
+    }
+
+    /// Spawn the future running the task referenced by `task`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    pub fn spawn<T, F>(self, task: TaskRef<T>, mailbox: &Mailbox<T>, future: F) -> TaskHandle<T>
+    where
+        T: Send + 'static,
+        F: Future<Output = TerminateReason> + Send + 'static,
+    {
+        // Wake the waiters only once the task's state, including its mailbox,
+        // is dropped. Mapping doesn't store the future twice like an async
+        // block would, which overflows the stack of some runtimes in debug builds.
+        let running = super::id::running(task.id());
+        let future = future.map(move |reason| {
+            drop(running);
+            reason
+        });
+
+        #[cfg(feature = "metrics")]
+        let future = {
+            let name = task.name();
+            async move {
+                let reason = future.await;
+                crate::metrics::terminated(name, &reason);
+                reason
+            }
+        };
+
+        #[cfg(feature = "tokio-metrics")]
+        let monitor = tokio_metrics::TaskMonitor::new();
+        #[cfg(feature = "tokio-metrics")]
+        let future = monitor.instrument(future);
+
+        #[cfg(feature = "inspector")]
+        let future = {
+            crate::inspector::enter(task.id(), task.name(), mailbox);
+            let exit = crate::inspector::Exit(task.id());
+            async move {
+                let _exit = exit;
+                future.await
+            }
+        };
+
+        let future = super::id::scope(task.id(), correlation::scope(future));
+        let join = if self.blocking {
+            runtime::spawn_blocking(task.name(), future)
+        } else {
+            runtime::spawn(task.name(), future)
+        };
+
+        #[cfg(feature = "inspector")]
+        crate::inspector::attach(task.id(), join.abort_handle());
+        let restart = self
+            .restartable
+            .map(|policy| Restart::new(self.clone(), policy, mailbox));
+        let mut handle = TaskHandle::new(task, mailbox, join);
+        handle.restart = restart;
+
+        if let Some(idle) = self.idle_timeout {
+            handle.passivate_after(idle);
+        }
+        if let Some(timeout) = self.shutdown_timeout {
+            handle.shutdown_timeout = timeout;
+        }
+        #[cfg(feature = "tokio-metrics")]
+        {
+            handle.monitor = Some(monitor);
+        }
+
+        handle
+    }
+}
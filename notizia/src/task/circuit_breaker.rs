@@ -0,0 +1,256 @@
+//! Circuit breaker for request-response calls.
+
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::broadcast;
+
+use super::TaskRef;
+use crate::core::errors::{CallError, CallResult};
+use crate::runtime::Instant;
+
+/// Default number of consecutive failures after which a breaker opens.
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+
+/// Default time an open breaker waits before letting a trial call through.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// State of a [`CircuitBreaker`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls pass through and failures are counted
+    Closed,
+    /// Calls fail fast with [`CallError::CircuitOpen`]
+    Open,
+    /// A single trial call is let through to probe the task
+    HalfOpen,
+}
+
+struct Inner {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    trial_in_flight: bool,
+}
+
+/// Guards calls to a task that may be failing.
+///
+/// The breaker counts consecutive failed calls (timeouts, closed reply
+/// channels, failed sends). Once the [threshold](Self::failure_threshold)
+/// is reached, it opens and further calls fail immediately with
+/// [`CallError::CircuitOpen`] instead of waiting for their timeout. After
+/// the [cooldown](Self::cooldown), a single trial call is let through: if it
+/// succeeds, the breaker closes again, otherwise it re-opens. Calls that
+/// were already in flight when the breaker opened don't change its state.
+///
+/// State changes are published to [subscribers](Self::subscribe). Clones
+/// share the same state.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::{call, message};
+/// # use notizia::task::CircuitBreaker;
+/// # use std::time::Duration;
+/// # #[message]
+/// # #[derive(Debug)]
+/// # enum Msg {
+/// #     #[request(reply = u32)]
+/// #     GetStatus,
+/// # }
+/// # #[derive(Task)]
+/// # #[task(message = Msg)]
+/// # struct Worker;
+/// # impl Runnable<Msg> for Worker { async fn start(&self) {} }
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), CallError> {
+/// let handle = spawn!(Worker);
+///
+/// let breaker = CircuitBreaker::new(handle.this())
+///     .failure_threshold(3)
+///     .cooldown(Duration::from_secs(10));
+///
+/// let status = breaker
+///     .call(|worker| async move { call!(worker, Msg::GetStatus, timeout = 500).await })
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CircuitBreaker<T> {
+    task: TaskRef<T>,
+    inner: Arc<Mutex<Inner>>,
+    events: broadcast::Sender<CircuitState>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+// Manual Clone implementation to avoid requiring T: Clone
+impl<T> Clone for CircuitBreaker<T> {
+    fn clone(&self) -> Self {
+        CircuitBreaker {
+            task: self.task.clone(),
+            inner: self.inner.clone(),
+            events: self.events.clone(),
+            failure_threshold: self.failure_threshold,
+            cooldown: self.cooldown,
+        }
+    }
+}
+
+impl<T> CircuitBreaker<T> {
+    /// Create a closed breaker guarding calls to `task`.
+    pub fn new(task: TaskRef<T>) -> Self {
+        CircuitBreaker {
+            task,
+            inner: Arc::new(Mutex::new(Inner {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+                trial_in_flight: false,
+            })),
+            events: broadcast::channel(16).0,
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Set the number of consecutive failures that open the breaker.
+    ///
+    /// A threshold of zero is treated as one.
+    pub fn failure_threshold(mut self, threshold: u32) -> Self {
+        self.failure_threshold = threshold.max(1);
+        self
+    }
+
+    /// Set how long the breaker stays open before a trial call.
+    pub fn cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Perform a call through the breaker.
+    ///
+    /// `call` receives a reference to the guarded task and typically wraps
+    /// [`call!`](crate::call!). Its result is recorded and returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`CallError::CircuitOpen`] without invoking `call` while the
+    /// breaker is open, and the call's own error otherwise.
+    pub async fn call<R, F, Fut>(&self, call: F) -> CallResult<R>
+    where
+        F: FnOnce(TaskRef<T>) -> Fut,
+        Fut: Future<Output = CallResult<R>>,
+    {
+        let trial = self.acquire()?;
+
+        // Counts as a failure if the call is dropped before it completes
+        let mut attempt = Attempt {
+            breaker: self,
+            trial,
+            success: false,
+        };
+
+        let result = call(self.task.clone()).await;
+        attempt.success = result.is_ok();
+        result
+    }
+
+    /// Current state of the breaker.
+    pub fn state(&self) -> CircuitState {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.state == CircuitState::Open && inner.opened_at.elapsed() >= self.cooldown {
+            self.transition(&mut inner, CircuitState::HalfOpen);
+        }
+        inner.state
+    }
+
+    /// Subscribe to state changes.
+    pub fn subscribe(&self) -> broadcast::Receiver<CircuitState> {
+        self.events.subscribe()
+    }
+
+    /// Close the breaker and forget all failures.
+    pub fn reset(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.failures = 0;
+        inner.trial_in_flight = false;
+        self.transition(&mut inner, CircuitState::Closed);
+    }
+
+    /// The guarded task.
+    pub fn task(&self) -> &TaskRef<T> {
+        &self.task
+    }
+
+    /// Let a call through, returning whether it is the trial call.
+    fn acquire(&self) -> CallResult<bool> {
+        let mut inner = self.inner.lock().unwrap();
+
+        match inner.state {
+            CircuitState::Closed => Ok(false),
+            CircuitState::Open if inner.opened_at.elapsed() < self.cooldown => {
+                Err(CallError::CircuitOpen)
+            }
+            CircuitState::HalfOpen if inner.trial_in_flight => Err(CallError::CircuitOpen),
+            CircuitState::Open | CircuitState::HalfOpen => {
+                inner.trial_in_flight = true;
+                self.transition(&mut inner, CircuitState::HalfOpen);
+                Ok(true)
+            }
+        }
+    }
+
+    fn record(&self, trial: bool, success: bool) {
+        let mut inner = self.inner.lock().unwrap();
+
+        match inner.state {
+            CircuitState::Closed if success => inner.failures = 0,
+            CircuitState::Closed => {
+                inner.failures += 1;
+                if inner.failures >= self.failure_threshold {
+                    self.open(&mut inner);
+                }
+            }
+            // Only the trial call decides whether the breaker closes again
+            CircuitState::HalfOpen if trial => {
+                inner.trial_in_flight = false;
+                if success {
+                    inner.failures = 0;
+                    self.transition(&mut inner, CircuitState::Closed);
+                } else {
+                    self.open(&mut inner);
+                }
+            }
+            // Calls started before the breaker opened
+            CircuitState::Open | CircuitState::HalfOpen => {}
+        }
+    }
+
+    fn open(&self, inner: &mut Inner) {
+        inner.opened_at = Instant::now();
+        self.transition(inner, CircuitState::Open);
+    }
+
+    fn transition(&self, inner: &mut Inner, state: CircuitState) {
+        if inner.state != state {
+            inner.state = state;
+            let _ = self.events.send(state);
+        }
+    }
+}
+
+/// Records the outcome of a call when dropped.
+struct Attempt<'a, T> {
+    breaker: &'a CircuitBreaker<T>,
+    trial: bool,
+    success: bool,
+}
+
+impl<T> Drop for Attempt<'_, T> {
+    fn drop(&mut self) {
+        self.breaker.record(self.trial, self.success);
+    }
+}
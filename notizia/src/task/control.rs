@@ -0,0 +1,196 @@
+//! Control mailboxes for multi-protocol tasks.
+//!
+//! A task declared with `#[task(message = DataMsg, control = CtrlMsg)]` has
+//! a second, typed mailbox for control-plane messages. Control messages are
+//! sent with [`TaskRef::send_control`](super::TaskRef::send_control) and
+//! received with [`recv_ctrl!`](crate::recv_ctrl!), so they are not stuck
+//! behind a large backlog of data messages. [`recv_any!`](crate::recv_any!)
+//! waits for either kind of message and prefers control messages.
+//!
+//! The control mailbox stays open while the task runs. The task stops once
+//! its data mailbox is closed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::task::Incoming;
+//! use notizia::recv_any;
+//!
+//! #[derive(Debug)]
+//! enum DataMsg {
+//!     Chunk(Vec<u8>),
+//! }
+//!
+//! #[derive(Debug)]
+//! enum CtrlMsg {
+//!     Pause,
+//!     Resume,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = DataMsg, control = CtrlMsg)]
+//! struct Ingest;
+//!
+//! impl Runnable<DataMsg> for Ingest {
+//!     async fn start(&self) {
+//!         let mut paused = false;
+//!         while let Ok(incoming) = recv_any!(self) {
+//!             match incoming {
+//!                 Incoming::Control(CtrlMsg::Pause) => paused = true,
+//!                 Incoming::Control(CtrlMsg::Resume) => paused = false,
+//!                 Incoming::Data(DataMsg::Chunk(chunk)) if !paused => {
+//!                     println!("{} bytes", chunk.len());
+//!                 }
+//!                 Incoming::Data(_) => {}
+//!             }
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let handle = spawn!(Ingest);
+//! handle.send(DataMsg::Chunk(vec![0; 1024])).unwrap();
+//! handle.send_control(CtrlMsg::Pause).unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+use super::Task;
+use crate::core::errors::{RecvError, RecvResult, SendResult};
+use crate::core::mailbox::Received;
+
+/// A message received by [`recv_any!`](crate::recv_any!).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incoming<T, C> {
+    /// A message from the control mailbox
+    Control(C),
+    /// A message from the data mailbox
+    Data(T),
+}
+
+/// Type-erased control mailbox of a task.
+///
+/// This is an implementation detail of the generated code.
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct Control {
+    channel: Arc<dyn Any + Send + Sync>,
+}
+
+struct Channel<C> {
+    sender: UnboundedSender<C>,
+    receiver: Mutex<UnboundedReceiver<C>>,
+}
+
+impl Control {
+    /// Create a control mailbox for messages of type `C`.
+    pub fn new<C>() -> Self
+    where
+        C: Send + 'static,
+    {
+        let (sender, receiver) = unbounded_channel::<C>();
+
+        Control {
+            channel: Arc::new(Channel {
+                sender,
+                receiver: Mutex::new(receiver),
+            }),
+        }
+    }
+
+    fn channel<C: 'static>(&self) -> Option<&Channel<C>> {
+        self.channel.downcast_ref()
+    }
+
+    /// Send `msg` if this is a control mailbox for `C`.
+    pub(crate) fn send<C: 'static>(&self, msg: C) -> SendResult<C> {
+        match self.channel::<C>() {
+            Some(channel) => channel.sender.send(msg),
+            None => Err(SendError(msg)),
+        }
+    }
+
+    /// Receive the next control message.
+    ///
+    /// Unlike [`Mailbox::recv`](crate::Mailbox::recv), this is cancel-safe,
+    /// so it can be raced against other futures.
+    ///
+    /// # Panics
+    ///
+    /// Panics if this is not a control mailbox for `C`.
+    pub async fn recv<C: 'static>(&self) -> RecvResult<C> {
+        let channel = self
+            .channel::<C>()
+            .expect("control mailbox has a different message type");
+
+        channel
+            .receiver
+            .lock()
+            .await
+            .recv()
+            .await
+            .ok_or(RecvError::Closed)
+    }
+}
+
+impl std::fmt::Debug for Control {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Control").finish_non_exhaustive()
+    }
+}
+
+/// Tasks with a control mailbox.
+///
+/// Implemented by `#[derive(Task)]` for tasks declared with
+/// `#[task(message = T, control = C)]`.
+pub trait Controlled {
+    /// The type of control messages
+    type Control: Send + 'static;
+
+    /// Internal access to the control mailbox (do not call directly).
+    #[doc(hidden)]
+    fn __control(&self) -> Control;
+}
+
+/// Receive the next control message of `task`.
+///
+/// This is typically called by [`recv_ctrl!`](crate::recv_ctrl!) and not by user code directly.
+#[doc(hidden)]
+pub async fn recv_control<S>(task: &S) -> RecvResult<S::Control>
+where
+    S: Controlled,
+{
+    task.__control().recv().await
+}
+
+/// Receive the next message of `task` from either mailbox.
+///
+/// This is typically called by [`recv_any!`](crate::recv_any!) and not by user code directly.
+#[doc(hidden)]
+pub async fn recv_any<S, T>(task: &S) -> RecvResult<Incoming<T, S::Control>>
+where
+    S: Task<T> + Controlled,
+    T: Send,
+{
+    let control = task.__control();
+    let interrupt = async {
+        match control.recv::<S::Control>().await {
+            Ok(msg) => msg,
+            // The control mailbox never closes while the task runs
+            Err(_) => std::future::pending().await,
+        }
+    };
+
+    match task.mailbox().recv_or(interrupt).await? {
+        Received::Message(msg) => Ok(Incoming::Data(msg)),
+        Received::Interrupted(msg) => Ok(Incoming::Control(msg)),
+    }
+}
@@ -0,0 +1,172 @@
+//! Deduplication of messages by idempotency key.
+//!
+//! [`Dedup`] is a [`Middleware`] dropping messages whose
+//! [idempotency key](Idempotent::idempotency_key) was already processed by
+//! the task within a configurable window. A key counts as processed once
+//! the task has handled its message, so a message that was lost because the
+//! task panicked is handled again when it is redelivered.
+//!
+//! Together with [`send_reliable!`](crate::send_reliable!), which may
+//! deliver a message more than once, this gives effectively-once processing
+//! of keyed messages: duplicates are acknowledged without reaching the task.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use notizia::prelude::*;
+//! use notizia::task::{Dedup, Idempotent};
+//!
+//! #[derive(Debug, Clone)]
+//! enum Payment {
+//!     Charge { id: u64, cents: u64 },
+//!     Audit,
+//! }
+//!
+//! impl Idempotent for Payment {
+//!     type Key = u64;
+//!
+//!     fn idempotency_key(&self) -> Option<u64> {
+//!         match self {
+//!             Payment::Charge { id, .. } => Some(*id),
+//!             Payment::Audit => None,
+//!         }
+//!     }
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = Payment)]
+//! struct Payments;
+//!
+//! impl Runnable<Payment> for Payments {
+//!     async fn start(&self) {
+//!         while let Ok(payment) = recv!(self) {
+//!             println!("{payment:?}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let handle = Payments
+//!     .builder()
+//!     .middleware(Dedup::new(Duration::from_secs(60)))
+//!     .spawn();
+//!
+//! // The second charge is dropped
+//! handle.send(Payment::Charge { id: 1, cents: 500 }).unwrap();
+//! handle.send(Payment::Charge { id: 1, cents: 500 }).unwrap();
+//! # }
+//! ```
+
+use std::collections::{HashMap, VecDeque};
+use std::hash::Hash;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::Middleware;
+use crate::runtime::Instant;
+
+/// A message that may carry an idempotency key.
+pub trait Idempotent {
+    /// The type of the key.
+    type Key: Eq + Hash + Clone + Send + Sync;
+
+    /// The key identifying this message, if it should be deduplicated.
+    ///
+    /// Messages returning `None` are always delivered.
+    fn idempotency_key(&self) -> Option<Self::Key>;
+}
+
+/// Middleware dropping messages whose key was processed within a window.
+///
+/// The keys seen are kept per spawn: register a separate `Dedup` for every
+/// task. See the [module documentation](self).
+pub struct Dedup<M>
+where
+    M: Idempotent,
+{
+    window: Duration,
+    state: Mutex<State<M::Key>>,
+}
+
+struct State<K> {
+    /// When each key was processed
+    processed: HashMap<K, Instant>,
+    /// Processed keys, oldest first
+    order: VecDeque<(Instant, K)>,
+    /// Key of the message being handled
+    current: Option<K>,
+}
+
+impl<M> Dedup<M>
+where
+    M: Idempotent,
+{
+    /// Drop messages whose key was processed less than `window` ago.
+    pub fn new(window: Duration) -> Self {
+        Dedup {
+            window,
+            state: Mutex::new(State {
+                processed: HashMap::new(),
+                order: VecDeque::new(),
+                current: None,
+            }),
+        }
+    }
+
+    /// The deduplication window.
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+}
+
+impl<K> State<K>
+where
+    K: Eq + Hash,
+{
+    /// Forget keys processed before `window`.
+    fn expire(&mut self, window: Duration) {
+        while let Some((processed, _)) = self.order.front() {
+            if processed.elapsed() < window {
+                break;
+            }
+
+            let (_, key) = self.order.pop_front().unwrap();
+            self.processed.remove(&key);
+        }
+    }
+}
+
+impl<M> Middleware<M> for Dedup<M>
+where
+    M: Idempotent,
+{
+    fn before_handle(&self, msg: &M) -> bool {
+        let key = msg.idempotency_key();
+        let mut state = self.state.lock().unwrap();
+        // A message dropped by later middleware is never handled
+        state.current = None;
+
+        let Some(key) = key else {
+            return true;
+        };
+
+        state.expire(self.window);
+        if state.processed.contains_key(&key) {
+            return false;
+        }
+
+        state.current = Some(key);
+        true
+    }
+
+    fn after_handle(&self, _elapsed: Duration) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(key) = state.current.take() {
+            let now = Instant::now();
+            state.processed.insert(key.clone(), now);
+            state.order.push_back((now, key));
+        }
+    }
+}
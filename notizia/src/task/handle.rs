@@ -1,11 +1,26 @@
 //! Task handle for controlling spawned tasks.
 
+use std::fmt;
 use std::time::Duration;
 
-use tokio::sync::mpsc::UnboundedSender;
-use tokio::task::JoinHandle;
+#[cfg(not(target_arch = "wasm32"))]
+use tokio::runtime::Handle;
 
-use crate::core::errors::SendResult;
+use super::health::HealthStatus;
+use super::respawn::{RespawnError, Restart};
+use super::timer::TimerHandle;
+use super::watchdog::Watchdog;
+use super::{Task, TaskId, TaskRef};
+use crate::core::IntoTimeout;
+use crate::core::SystemSignal;
+use crate::core::correlation::CorrelationId;
+use crate::core::delivery::Ack;
+use crate::core::errors::{CallError, CallResult, SendResult};
+use crate::core::lifecycle::DEFAULT_SHUTDOWN_TIMEOUT;
+use crate::core::mailbox::{Activity, Gauge, Mailbox, Passivation};
+use crate::core::reply::{self, ReplySender};
+use crate::runtime::{self, Instant, JoinError, JoinHandle};
+use crate::system::TaskStatus;
 use crate::{ShutdownError, ShutdownResult, TerminateReason};
 
 /// Handle for a spawned task.
@@ -15,7 +30,9 @@ use crate::{ShutdownError, ShutdownResult, TerminateReason};
 /// - Wait for the task to complete
 /// - Abort the task
 ///
-/// The handle owns the sender side of the channel and the Tokio join handle.
+/// The handle owns a [`TaskRef`] to the task and the Tokio join handle.
+/// Dropping the handle closes the task's mailbox once no [`TaskRef`](super::TaskRef)
+/// to the task remains, so the task receives `RecvError::Closed`.
 ///
 /// # Example
 ///
@@ -45,8 +62,18 @@ pub struct TaskHandle<T>
 where
     T: 'static,
 {
-    sender: UnboundedSender<T>,
+    task: TaskRef<T>,
+    passivation: Passivation,
+    activity: Activity,
+    gauge: Gauge<T>,
+    watchdog: Option<Watchdog>,
     handle: JoinHandle<TerminateReason>,
+    #[cfg(not(target_arch = "wasm32"))]
+    runtime: Option<Handle>,
+    pub(crate) shutdown_timeout: Duration,
+    pub(crate) restart: Option<Restart<T>>,
+    #[cfg(feature = "tokio-metrics")]
+    pub(crate) monitor: Option<tokio_metrics::TaskMonitor>,
 }
 
 impl<T> TaskHandle<T>
@@ -57,8 +84,90 @@ where
     ///
     /// This is typically called by the generated code and not by user code directly.
     #[doc(hidden)]
-    pub fn new(sender: UnboundedSender<T>, handle: JoinHandle<TerminateReason>) -> Self {
-        TaskHandle { sender, handle }
+    pub fn new(
+        task: TaskRef<T>,
+        mailbox: &Mailbox<T>,
+        handle: JoinHandle<TerminateReason>,
+    ) -> Self {
+        TaskHandle {
+            task,
+            passivation: mailbox.passivation.clone(),
+            activity: mailbox.activity.clone(),
+            gauge: mailbox.gauge(),
+            watchdog: mailbox.watchdog.clone(),
+            handle,
+            #[cfg(not(target_arch = "wasm32"))]
+            runtime: Handle::try_current().ok(),
+            shutdown_timeout: DEFAULT_SHUTDOWN_TIMEOUT,
+            restart: None,
+            #[cfg(feature = "tokio-metrics")]
+            monitor: None,
+        }
+    }
+
+    /// Passivate the task once it has received nothing for `idle`.
+    ///
+    /// When the idle timeout expires while the task waits on its mailbox, the
+    /// mailbox stops accepting messages, delivers the ones already queued, and
+    /// then reports [`RecvError::Closed`](crate::core::errors::RecvError::Closed).
+    /// The task stops gracefully and its `terminate()` hook is called with
+    /// [`TerminateReason::Idle`]. Sending to a passivated task fails, so the
+    /// owner can re-activate it on the next message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use std::time::Duration;
+    /// # #[derive(Task)]
+    /// # #[task(message = Signal)]
+    /// # struct Worker;
+    /// # impl Runnable<Signal> for Worker {
+    /// #     async fn start(&self) {
+    /// #         while let Ok(_) = recv!(self) {}
+    /// #     }
+    /// # }
+    /// # #[derive(Clone)]
+    /// # enum Signal { Ping }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let handle = spawn!(Worker);
+    /// handle.passivate_after(Duration::from_secs(30));
+    ///
+    /// // Without messages, the worker stops after 30 seconds
+    /// let reason = handle.join().await.unwrap();
+    /// assert_eq!(reason, TerminateReason::Idle);
+    /// # }
+    /// ```
+    pub fn passivate_after(&self, idle: Duration) {
+        self.passivation.set_idle_timeout(Some(idle));
+    }
+
+    /// Check whether the task was passivated because it was idle.
+    pub fn is_passivated(&self) -> bool {
+        self.passivation.is_passivated()
+    }
+
+    /// Check whether the task has been processing a single message for
+    /// longer than its [watchdog](super::SpawnBuilder::watchdog) allows.
+    ///
+    /// Always `false` for tasks without a watchdog.
+    pub fn is_stalled(&self) -> bool {
+        self.watchdog.as_ref().is_some_and(Watchdog::is_stalled)
+    }
+
+    /// Report the status of the task without keeping it alive.
+    pub(crate) fn status(&self) -> impl Fn() -> TaskStatus + Send + Sync + 'static {
+        let abort = self.handle.abort_handle();
+        let passivation = self.passivation.clone();
+        let watchdog = self.watchdog.clone();
+
+        move || match abort.is_finished() {
+            true if passivation.is_passivated() => TaskStatus::Passivated,
+            true => TaskStatus::Terminated,
+            false if watchdog.as_ref().is_some_and(Watchdog::is_stalled) => TaskStatus::Stalled,
+            false => TaskStatus::Running,
+        }
     }
 
     /// Wait for the task to complete without signaling shutdown.
@@ -74,7 +183,7 @@ where
     ///
     /// # Errors
     ///
-    /// Returns a [`JoinError`](tokio::task::JoinError) if the task was
+    /// Returns a [`JoinError`] if the task was
     /// aborted or an unexpected error occurred (rare).
     ///
     /// # Example
@@ -92,7 +201,7 @@ where
     /// # #[derive(Clone)]
     /// # enum Signal {}
     /// # #[tokio::main]
-    /// # async fn main() -> Result<(), tokio::task::JoinError> {
+    /// # async fn main() -> Result<(), notizia::runtime::JoinError> {
     /// let worker = Worker;
     /// let handle = spawn!(worker);
     ///
@@ -103,7 +212,9 @@ where
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn join(self) -> Result<TerminateReason, tokio::task::JoinError> {
+    pub async fn join(self) -> Result<TerminateReason, JoinError> {
+        // Keep the channel open while waiting, so the task is not signaled to stop
+        let _task = self.task;
         self.handle.await
     }
 
@@ -142,13 +253,259 @@ where
     /// # }
     /// ```
     pub fn send(&self, msg: T) -> SendResult<T> {
-        self.sender.send(msg)
+        self.task.send(msg)
+    }
+
+    /// Pass on a message to the task on behalf of its sender.
+    ///
+    /// See [`TaskRef::forward`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`send`](Self::send).
+    pub fn forward(&self, msg: T) -> SendResult<T> {
+        self.task.forward(msg)
+    }
+
+    /// Send a message to the task once `delay` has passed.
+    ///
+    /// See [`TaskRef::send_after`].
+    pub fn send_after(&self, delay: Duration, msg: T) -> TimerHandle
+    where
+        T: Send,
+    {
+        self.task.send_after(delay, msg)
+    }
+
+    /// Send a message made by `make` to the task every `period`.
+    ///
+    /// See [`TaskRef::send_interval`].
+    pub fn send_interval<F>(&self, period: Duration, make: F) -> TimerHandle
+    where
+        T: Send,
+        F: FnMut() -> T + Send + 'static,
+    {
+        self.task.send_interval(period, make)
+    }
+
+    /// Send a message, waiting for room in a full
+    /// [bounded](crate::core::bounded) mailbox.
+    ///
+    /// See [`TaskRef::feed`] and [`feed!`](crate::feed!).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated and the receiver has been dropped.
+    pub async fn feed(&self, msg: T) -> SendResult<T> {
+        self.task.feed(msg).await
+    }
+
+    /// Check that the task is alive and receiving messages, returning the
+    /// round-trip time.
+    ///
+    /// See [`TaskRef::ping`].
+    ///
+    /// # Errors
+    ///
+    /// Fails like [`TaskRef::ping`].
+    pub async fn ping(&self, timeout: impl IntoTimeout) -> CallResult<Duration> {
+        self.task.ping(timeout).await
+    }
+
+    /// Check the health of the task.
+    ///
+    /// Combines a [heartbeat](Self::ping) waiting at most `timeout` with
+    /// the depth of the task's mailbox and the time it last received a
+    /// message. See [`task::health`](crate::task::health).
+    pub async fn health(&self, timeout: impl IntoTimeout) -> HealthStatus {
+        let mailbox_depth = self.gauge.depth();
+
+        HealthStatus {
+            round_trip: self.ping(timeout).await.ok(),
+            mailbox_depth,
+            last_activity: self.activity.last(),
+        }
+    }
+
+    /// Send a message that the task acknowledges once it has processed it.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    /// Use [`send_reliable!`](crate::send_reliable!) instead.
+    #[doc(hidden)]
+    pub fn send_acked(&self, msg: T, ack: Ack) -> SendResult<T> {
+        self.task.send_acked(msg, ack)
+    }
+
+    /// Send a message to the task from synchronous code.
+    ///
+    /// Safe to call from any thread, including
+    /// [`spawn_blocking`](tokio::task::spawn_blocking) workers and FFI
+    /// callbacks running outside of the runtime.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated and the receiver has been dropped.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking_send(&self, msg: T) -> SendResult<T> {
+        let _guard = self.runtime.as_ref().map(Handle::enter);
+        self.task.send(msg)
+    }
+
+    /// Call the task from synchronous code, blocking until it replies.
+    ///
+    /// This is the blocking counterpart of [`call!`](crate::call!) with the
+    /// closure syntax: `builder` receives the reply sender and returns the
+    /// request. The call runs on the runtime the task was spawned on, so it
+    /// can be used from [`spawn_blocking`](tokio::task::spawn_blocking)
+    /// workers and from threads outside of the runtime.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the same errors as [`call!`](crate::call!).
+    ///
+    /// # Panics
+    ///
+    /// Panics if called from asynchronous code, since blocking there would
+    /// stall the runtime. Use `call!` instead.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use notizia::message;
+    /// # #[message]
+    /// # enum Msg {
+    /// #     #[request(reply = u32)]
+    /// #     Get { key: u32 },
+    /// # }
+    /// # #[derive(Task)]
+    /// # #[task(message = Msg)]
+    /// # struct Store;
+    /// # impl Runnable<Msg> for Store { async fn start(&self) {} }
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let handle = spawn!(Store);
+    ///
+    /// let value = tokio::task::spawn_blocking(move || {
+    ///     handle.blocking_call(|reply_to| Msg::Get { key: 1, reply_to }, 1000)
+    /// })
+    /// .await
+    /// .unwrap();
+    /// # }
+    /// ```
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn blocking_call<S, F>(
+        &self,
+        builder: F,
+        timeout: impl IntoTimeout,
+    ) -> CallResult<S::Output>
+    where
+        T: Send,
+        S: ReplySender,
+        F: FnOnce(S) -> T,
+    {
+        let timeout = timeout.into_timeout();
+
+        let call = async {
+            CallError::check_self_call(self.id(), self.name())?;
+            let correlation = CorrelationId::current_or_next();
+            let (reply_to, receiver) = reply::channel(correlation);
+            let started = Instant::now();
+            self.send(builder(reply_to))
+                .map_err(|err| CallError::send_failed(self.id(), self.name(), err))?;
+
+            runtime::timeout(timeout, receiver)
+                .await
+                .map_err(|_| CallError::timed_out(self.id(), self.name(), correlation, started))?
+        };
+
+        match &self.runtime {
+            Some(runtime) => runtime.block_on(call),
+            None => futures::executor::block_on(call),
+        }
+    }
+
+    /// Send a system signal to the task.
+    ///
+    /// See [`TaskRef::signal`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated or stopped accepting messages.
+    pub fn signal(&self, signal: SystemSignal) -> SendResult<SystemSignal> {
+        self.task.signal(signal)
+    }
+
+    /// Send a message to the task's control mailbox.
+    ///
+    /// See [`TaskRef::send_control`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated or has no control mailbox for messages of type `C`.
+    pub fn send_control<C>(&self, msg: C) -> SendResult<C>
+    where
+        C: Send + 'static,
+    {
+        self.task.send_control(msg)
+    }
+
+    /// Check whether the task has finished.
+    ///
+    /// Returns `true` once the task has terminated, either normally, by
+    /// panicking, or because it was aborted.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// A handle that aborts the task without consuming this handle.
+    ///
+    /// See [`KillSwitch`](super::KillSwitch) for aborting groups of tasks.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn abort_handle(&self) -> runtime::AbortHandle {
+        self.handle.abort_handle()
+    }
+
+    /// Unique identifier of the task.
+    pub fn id(&self) -> TaskId {
+        self.task.id()
+    }
+
+    /// Name of the task, i.e., the name of the type deriving `Task`.
+    pub fn name(&self) -> &'static str {
+        self.task.name()
+    }
+
+    /// Module path of the task's type, e.g. `app::workers::Worker`.
+    pub fn path(&self) -> &'static str {
+        self.task.path()
+    }
+
+    /// Runtime metrics of the task, such as the time it spent being polled,
+    /// idle, or waiting to be scheduled.
+    ///
+    /// Tasks that are polled for long stretches starve the runtime; tasks
+    /// that wait long to be scheduled suffer from such neighbours. The
+    /// metrics are cumulative since the task was spawned.
+    ///
+    /// Returns `None` for tasks not spawned through `#[derive(Task)]`.
+    ///
+    /// Requires the `tokio-metrics` feature.
+    #[cfg(feature = "tokio-metrics")]
+    pub fn runtime_metrics(&self) -> Option<super::TaskMetrics> {
+        self.monitor.as_ref().map(|monitor| monitor.cumulative())
     }
 
     /// Abort the task immediately.
     ///
     /// This method forcefully terminates the task. The task will not have
     /// an opportunity to clean up resources or finish processing messages.
+    /// Tasks running on the blocking pool cannot be interrupted and only
+    /// stop once their mailbox closes.
     ///
     /// # Example
     ///
@@ -171,6 +528,7 @@ where
     /// handle.kill();
     /// # }
     /// ```
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn kill(self) {
         self.handle.abort();
     }
@@ -238,6 +596,8 @@ where
     /// match handle.shutdown(Duration::from_secs(5)).await {
     ///     Ok(TerminateReason::Normal) => println!("Clean shutdown"),
     ///     Ok(TerminateReason::Panic(msg)) => eprintln!("Task panicked: {}", msg),
+    ///     Ok(TerminateReason::Idle) => println!("Task was already passivated"),
+    ///     Ok(TerminateReason::DeadlineExceeded) => println!("Task ran out of time"),
     ///     Err(ShutdownError::Timeout) => eprintln!("Shutdown timed out"),
     ///     Err(e) => eprintln!("Shutdown error: {}", e),
     /// }
@@ -248,14 +608,14 @@ where
         // Step 1: Close the channel to signal shutdown
         // When the sender is dropped, receivers get RecvError::Closed
         // Note: If TaskRef clones exist, they keep the channel alive
-        drop(self.sender);
+        drop(self.task);
 
         // Step 2: Wait for the task to complete with timeout
         // The task will:
         //   - Complete start() (normally or with panic)
         //   - Call terminate(reason)
         //   - Return TerminateReason
-        match tokio::time::timeout(timeout, self.handle).await {
+        match runtime::timeout(timeout, self.handle).await {
             // Timeout succeeded, join succeeded - task completed
             Ok(Ok(reason)) => Ok(reason),
 
@@ -268,6 +628,92 @@ where
         }
     }
 
+    /// Start a new incarnation of the terminated task, built by `factory`.
+    ///
+    /// The new incarnation takes over the mailbox of the task, so it keeps
+    /// its [`TaskId`] and every [`TaskRef`] to the task stays valid. The
+    /// task's [`MailboxPolicy`](super::respawn::MailboxPolicy) decides
+    /// whether it receives the messages that were still queued. The handle
+    /// then controls the new incarnation; see [`task::respawn`](super::respawn).
+    ///
+    /// Returns how the previous incarnation terminated.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RespawnError::NotRestartable`] if the task was not spawned
+    /// with [`SpawnBuilder::restartable`](super::SpawnBuilder::restartable),
+    /// [`RespawnError::Running`] if it has not terminated yet, and
+    /// [`RespawnError::Closed`] if its mailbox was closed, e.g. because it
+    /// was stopped or aborted while waiting for a message.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # #[derive(Task)]
+    /// # #[task(message = Signal)]
+    /// # struct Worker;
+    /// # impl Runnable<Signal> for Worker {
+    /// #     async fn start(&self) {}
+    /// # }
+    /// # #[derive(Clone)]
+    /// # enum Signal {}
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let mut handle = Worker.builder().restartable().spawn();
+    ///
+    /// # while !handle.is_finished() { tokio::task::yield_now().await; }
+    /// if handle.is_finished() {
+    ///     let previous = handle.respawn(|| Worker).await.unwrap();
+    ///     println!("restarted after {previous:?}");
+    /// }
+    /// # }
+    /// ```
+    pub async fn respawn<S, F>(
+        &mut self,
+        factory: F,
+    ) -> Result<Result<TerminateReason, JoinError>, RespawnError>
+    where
+        S: Task<T>,
+        F: FnOnce() -> S,
+        T: Send,
+    {
+        let restart = self.restart.as_ref().ok_or(RespawnError::NotRestartable)?;
+        if !self.handle.is_finished() {
+            return Err(RespawnError::Running);
+        }
+
+        restart.prepare(&self.task).await?;
+
+        let options = restart.next(self.task.clone());
+        let previous = std::mem::replace(self, factory().__spawn(options));
+        Ok(previous.handle.await)
+    }
+
+    /// Split the handle into its task reference and join handle.
+    pub(crate) fn into_parts(self) -> (TaskRef<T>, JoinHandle<TerminateReason>) {
+        (self.task, self.handle)
+    }
+
+    /// Gracefully shut the task down with its configured shutdown timeout.
+    ///
+    /// Equivalent to [`shutdown`](Self::shutdown) with the timeout set via
+    /// [`SpawnBuilder::shutdown_timeout`](super::SpawnBuilder::shutdown_timeout),
+    /// or [`DEFAULT_SHUTDOWN_TIMEOUT`] if none was set.
+    ///
+    /// # Errors
+    ///
+    /// Fails with the same errors as [`shutdown`](Self::shutdown).
+    pub async fn stop(self) -> ShutdownResult {
+        let timeout = self.shutdown_timeout;
+        self.shutdown(timeout).await
+    }
+
+    /// The timeout used by [`stop`](Self::stop).
+    pub fn shutdown_timeout(&self) -> Duration {
+        self.shutdown_timeout
+    }
+
     /// Get a reference to this task.
     ///
     /// Returns a [`TaskRef`](super::TaskRef) that can be used to send messages to this task.
@@ -294,7 +740,31 @@ where
     /// let task_ref = handle.this();
     /// # }
     /// ```
-    pub fn this(&self) -> super::TaskRef<T> {
-        super::TaskRef::new(self.sender.clone())
+    pub fn this(&self) -> TaskRef<T> {
+        self.task.clone()
+    }
+}
+
+impl<T> fmt::Debug for TaskHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskHandle")
+            .field("name", &self.name())
+            .field("id", &self.id())
+            .field("path", &self.path())
+            .field("finished", &self.is_finished())
+            .finish()
+    }
+}
+
+/// Displays as the name and id of the task and whether it has finished,
+/// e.g. `Worker#7 (running)`.
+impl<T> fmt::Display for TaskHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let liveness = if self.is_finished() {
+            "finished"
+        } else {
+            "running"
+        };
+        write!(f, "{}#{} ({liveness})", self.name(), self.id())
     }
 }
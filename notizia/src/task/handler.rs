@@ -0,0 +1,572 @@
+//! Handling messages one at a time.
+//!
+//! Instead of writing the receive loop in [`Runnable::start`](super::Runnable::start),
+//! a task declared with `#[task(message = M, handler)]` implements
+//! [`Handler<M>`]. The derive generates the loop: it receives messages,
+//! passes each one to [`Handler::handle`] with exclusive access to the task,
+//! and stops once the mailbox is closed or the handler calls
+//! [`Context::stop`]. Request variants are answered through their
+//! `reply_to` field as usual; a request the handler does not reply to fails
+//! the caller's `call!` instead of leaving it waiting.
+//!
+//! Since the task handles one message at a time, a handler awaiting slow I/O
+//! holds up every following message. [`Context::pipe_to`] runs such work in
+//! the background instead and delivers its result as a message of its own.
+//! Likewise, [`Context::subscribe`] turns the events of a Tokio
+//! [`broadcast`] channel into messages, and [`Context::watch`] the changes
+//! of a [`watch`] channel.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::{call, message};
+//!
+//! #[message]
+//! #[derive(Debug)]
+//! enum CounterMsg {
+//!     Increment,
+//!     #[request(reply = u32)]
+//!     GetCount,
+//!     Stop,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = CounterMsg, handler)]
+//! struct Counter {
+//!     count: u32,
+//! }
+//!
+//! impl Handler<CounterMsg> for Counter {
+//!     async fn handle(&mut self, msg: CounterMsg, ctx: &mut Context<CounterMsg>) {
+//!         match msg {
+//!             CounterMsg::Increment => self.count += 1,
+//!             CounterMsg::GetCount { reply_to } => {
+//!                 let _ = reply_to.reply(self.count);
+//!             }
+//!             CounterMsg::Stop => ctx.stop(),
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() -> Result<(), CallError> {
+//! let counter = Counter { count: 0 }.run();
+//! counter.send(CounterMsg::Increment).unwrap();
+//! assert_eq!(call!(counter, CounterMsg::GetCount).await?, 1);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! # Switching behaviors
+//!
+//! A task going through protocol phases can swap the code handling its
+//! messages instead of matching on a mode flag. [`Context::r#become`] pushes
+//! a [`Behavior`] that handles all following messages, and
+//! [`Context::unbecome`] pops it again, returning to the previous behavior
+//! or, at the bottom of the stack, to [`Handler::handle`]. Since `become` is
+//! a reserved keyword, it is called as `ctx.r#become(...)`.
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::task::Behavior;
+//!
+//! #[derive(Debug)]
+//! enum Frame {
+//!     Hello,
+//!     Data(Vec<u8>),
+//!     Bye,
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = Frame, handler)]
+//! struct Connection {
+//!     received: usize,
+//! }
+//!
+//! // Handshaking: wait for the greeting
+//! impl Handler<Frame> for Connection {
+//!     async fn handle(&mut self, msg: Frame, ctx: &mut Context<Frame>) {
+//!         if let Frame::Hello = msg {
+//!             ctx.r#become(Streaming);
+//!         }
+//!     }
+//! }
+//!
+//! struct Streaming;
+//!
+//! impl Behavior<Connection, Frame> for Streaming {
+//!     async fn handle(&mut self, conn: &mut Connection, msg: Frame, ctx: &mut Context<Frame>) {
+//!         match msg {
+//!             Frame::Data(bytes) => conn.received += bytes.len(),
+//!             Frame::Bye => ctx.unbecome(),
+//!             Frame::Hello => {}
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::any::Any;
+use std::future::Future;
+
+use futures::future::BoxFuture;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+
+use super::audit::Outcome;
+use super::{Scope, TaskId, TaskRef};
+use crate::TerminateReason;
+use crate::core::trace;
+use crate::core::{Sender, TaskState};
+use crate::runtime::{self, AbortHandle};
+
+/// Message handler of a task declared with `#[task(message = M, handler)]`.
+///
+/// The generated receive loop calls [`handle`](Self::handle) for every
+/// message, in order, and [`terminate`](Self::terminate) once the loop has
+/// ended.
+pub trait Handler<M>: Send {
+    /// Handle a single message.
+    fn handle(&mut self, msg: M, ctx: &mut Context<M>) -> impl Future<Output = ()> + Send;
+
+    /// Cleanup hook called when the task is terminating.
+    ///
+    /// Like [`Runnable::terminate`](super::Runnable::terminate), this is
+    /// called regardless of why the task stops. The default implementation
+    /// does nothing.
+    fn terminate(&mut self, reason: TerminateReason) -> impl Future<Output = ()> + Send {
+        async move {
+            let _ = reason;
+        }
+    }
+}
+
+/// A message handler that temporarily replaces [`Handler::handle`].
+///
+/// Pushed with [`Context::r#become`] and popped with [`Context::unbecome`].
+/// `S` is the task type, whose state the behavior can access.
+pub trait Behavior<S, M>: Send + 'static {
+    /// Handle a single message on behalf of `task`.
+    fn handle(
+        &mut self,
+        task: &mut S,
+        msg: M,
+        ctx: &mut Context<M>,
+    ) -> impl Future<Output = ()> + Send;
+}
+
+/// Object-safe form of [`Behavior`], so behaviors of different types can
+/// share a stack.
+trait DynBehavior<S, M>: Send {
+    fn handle_boxed<'a>(
+        &'a mut self,
+        task: &'a mut S,
+        msg: M,
+        ctx: &'a mut Context<M>,
+    ) -> BoxFuture<'a, ()>;
+}
+
+impl<S, M, B> DynBehavior<S, M> for B
+where
+    B: Behavior<S, M>,
+    S: Send,
+    M: Send + 'static,
+{
+    fn handle_boxed<'a>(
+        &'a mut self,
+        task: &'a mut S,
+        msg: M,
+        ctx: &'a mut Context<M>,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(self.handle(task, msg, ctx))
+    }
+}
+
+/// A behavior on the stack of a [`Context`], holding a
+/// `Box<dyn DynBehavior<S, M>>` for the task type `S`.
+type Erased = Box<dyn Any + Send>;
+
+/// The running task, as seen by a [`Handler`].
+pub struct Context<M> {
+    state: TaskState<M>,
+    stopped: bool,
+    /// Pushed behaviors; the slot of the one handling the current message
+    /// is empty until it is done
+    behaviors: Vec<Option<Erased>>,
+    /// Where a persistent task appends its events
+    #[cfg(feature = "persistence")]
+    pub(crate) journal: Option<Box<dyn Any + Send>>,
+    /// Timings reported to the inspector
+    #[cfg(feature = "inspector")]
+    samples: std::sync::Arc<std::sync::Mutex<crate::inspector::Samples>>,
+}
+
+impl<M> Context<M> {
+    /// Create the context of the running task.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn new(state: TaskState<M>) -> Self {
+        Context {
+            #[cfg(feature = "inspector")]
+            samples: crate::inspector::samples(state.id),
+            state,
+            stopped: false,
+            behaviors: Vec::new(),
+            #[cfg(feature = "persistence")]
+            journal: None,
+        }
+    }
+
+    /// Handle `msg` with the active behavior, or with `task` itself if no
+    /// behavior has been pushed.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub async fn __dispatch<S>(&mut self, task: &mut S, msg: M)
+    where
+        S: Handler<M> + 'static,
+        M: Send + 'static,
+    {
+        let audit = self
+            .state
+            .mailbox
+            .audit
+            .as_ref()
+            .map(|auditor| auditor.start::<M>(self.id(), self.name()));
+        let watchdog = self.state.mailbox.watchdog.clone();
+        let state = watchdog
+            .as_ref()
+            .and_then(|watchdog| watchdog.capture(&*task));
+        let (id, name) = (self.id(), self.name());
+        #[cfg(feature = "inspector")]
+        let started = crate::runtime::Instant::now();
+
+        let dispatch = trace::dispatch::<M, _>(self.dispatch(task, msg));
+        match &watchdog {
+            Some(watchdog) => watchdog.guard::<M, _>(id, name, state, dispatch).await,
+            None => dispatch.await,
+        }
+
+        #[cfg(feature = "inspector")]
+        self.samples.lock().unwrap().record(started.elapsed());
+        if let Some(audit) = audit {
+            audit.finish(if self.stopped {
+                Outcome::Stopped
+            } else {
+                Outcome::Handled
+            });
+        }
+    }
+
+    async fn dispatch<S>(&mut self, task: &mut S, msg: M)
+    where
+        S: Handler<M> + 'static,
+        M: Send + 'static,
+    {
+        let index = self.behaviors.len().checked_sub(1);
+        let Some(mut behavior) = index.and_then(|index| self.behaviors[index].take()) else {
+            return task.handle(msg, self).await;
+        };
+
+        behavior
+            .downcast_mut::<Box<dyn DynBehavior<S, M>>>()
+            .expect("behavior was pushed for a different task type")
+            .handle_boxed(task, msg, self)
+            .await;
+
+        // Put it back, unless it was popped while handling the message
+        if let Some(slot @ None) = index.and_then(|index| self.behaviors.get_mut(index)) {
+            *slot = Some(behavior);
+        }
+    }
+
+    /// Get a reference to the running task.
+    pub fn this(&self) -> TaskRef<M> {
+        self.state.task_ref()
+    }
+
+    /// Unique identifier of the running task.
+    pub fn id(&self) -> TaskId {
+        self.state.id
+    }
+
+    /// Name of the running task.
+    pub fn name(&self) -> &'static str {
+        self.state.name
+    }
+
+    /// The task that sent the message being handled, if it was sent from
+    /// within a task.
+    ///
+    /// Lets the handler answer the sender, e.g. with
+    /// `ctx.sender().and_then(|sender| sender.task_ref::<Pong>())`, without
+    /// the message carrying a [`TaskRef`] itself.
+    pub fn sender(&self) -> Option<Sender> {
+        self.state.mailbox.sender()
+    }
+
+    /// Stop the task once the current message has been handled.
+    ///
+    /// The task terminates with
+    /// [`TerminateReason::Normal`](crate::TerminateReason::Normal); messages
+    /// still queued are dropped.
+    pub fn stop(&mut self) {
+        self.stopped = true;
+    }
+
+    /// Check whether [`stop`](Self::stop) has been called.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Run helper futures concurrently and wait for all of them.
+    ///
+    /// `f` starts the helpers with [`Scope::spawn`]; the returned future
+    /// resolves to their outputs in the order they were started. See
+    /// [`task::scope`](super::scope).
+    pub fn scope<'a, T, F>(&self, f: F) -> impl Future<Output = Vec<T>> + Send + 'a
+    where
+        F: FnOnce(&mut Scope<'a, T>),
+        T: Send + 'a,
+    {
+        super::scope::run(f)
+    }
+
+    /// Run `future` in the background and send its output, wrapped into a
+    /// message by `wrap`, to the running task.
+    ///
+    /// The handler returns without awaiting `future`, so the task keeps
+    /// handling messages in the meantime. `future` does not keep the task's
+    /// mailbox open; if the task has stopped receiving by the time it
+    /// completes, the output is dropped. The returned handle aborts it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use std::time::Duration;
+    /// enum Msg {
+    ///     Fetch(String),
+    ///     Fetched(String, usize),
+    /// }
+    ///
+    /// #[derive(Task)]
+    /// #[task(message = Msg, handler)]
+    /// struct Crawler;
+    ///
+    /// impl Handler<Msg> for Crawler {
+    ///     async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+    ///         match msg {
+    ///             Msg::Fetch(url) => {
+    ///                 let fetch = async move {
+    ///                     tokio::time::sleep(Duration::from_secs(1)).await;
+    ///                     url.len()
+    ///                 };
+    ///                 ctx.pipe_to(fetch, |len| Msg::Fetched(String::from("page"), len));
+    ///             }
+    ///             Msg::Fetched(page, len) => println!("{page}: {len} bytes"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn pipe_to<F, W>(&self, future: F, wrap: W) -> AbortHandle
+    where
+        F: Future + Send + 'static,
+        F::Output: Send,
+        W: FnOnce(F::Output) -> M + Send + 'static,
+        M: Send + 'static,
+    {
+        let task = self.this().weak();
+        runtime::spawn(self.name(), async move {
+            let output = future.await;
+            if let Some(task) = task.upgrade() {
+                let _ = task.send(wrap(output));
+            }
+        })
+        .abort_handle()
+    }
+
+    /// Send every event of a [`broadcast`] channel, wrapped into a message
+    /// by `wrap`, to the running task.
+    ///
+    /// Events the task fell too far behind to receive are skipped; see
+    /// [`subscribe_with_lag`](Self::subscribe_with_lag) to be told about
+    /// them. The subscription does not keep the task's mailbox open. It ends
+    /// when the channel closes, or with the first event after the task has
+    /// stopped receiving; the returned handle ends it earlier.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # use tokio::sync::broadcast;
+    /// #[derive(Clone, Debug)]
+    /// enum AppEvent {
+    ///     ConfigChanged,
+    /// }
+    ///
+    /// enum Msg {
+    ///     Listen(broadcast::Receiver<AppEvent>),
+    ///     Event(AppEvent),
+    /// }
+    ///
+    /// #[derive(Task)]
+    /// #[task(message = Msg, handler)]
+    /// struct Listener;
+    ///
+    /// impl Handler<Msg> for Listener {
+    ///     async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+    ///         match msg {
+    ///             Msg::Listen(events) => {
+    ///                 ctx.subscribe(events, Msg::Event);
+    ///             }
+    ///             Msg::Event(event) => println!("{event:?}"),
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub fn subscribe<E, W>(&self, events: broadcast::Receiver<E>, wrap: W) -> AbortHandle
+    where
+        E: Clone + Send + 'static,
+        W: FnMut(E) -> M + Send + 'static,
+        M: Send + 'static,
+    {
+        self.subscribe_with_lag(events, wrap, |_| None)
+    }
+
+    /// Send every event of a [`broadcast`] channel to the running task like
+    /// [`subscribe`](Self::subscribe), reporting skipped events.
+    ///
+    /// When the task falls behind and the channel drops events, `lagged` is
+    /// called with the number of skipped events and the message it returns,
+    /// if any, is sent in their place. Tasks that keep state derived from
+    /// the events can use it to resynchronize.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    pub fn subscribe_with_lag<E, W, L>(
+        &self,
+        mut events: broadcast::Receiver<E>,
+        mut wrap: W,
+        mut lagged: L,
+    ) -> AbortHandle
+    where
+        E: Clone + Send + 'static,
+        W: FnMut(E) -> M + Send + 'static,
+        L: FnMut(u64) -> Option<M> + Send + 'static,
+        M: Send + 'static,
+    {
+        let task = self.this().weak();
+        runtime::spawn(self.name(), async move {
+            loop {
+                let msg = match events.recv().await {
+                    Ok(event) => wrap(event),
+                    Err(RecvError::Lagged(skipped)) => match lagged(skipped) {
+                        Some(gap) => gap,
+                        None => continue,
+                    },
+                    Err(RecvError::Closed) => break,
+                };
+                let Some(task) = task.upgrade() else {
+                    break;
+                };
+                if task.send(msg).is_err() {
+                    break;
+                }
+            }
+        })
+        .abort_handle()
+    }
+
+    /// Send every change of a [`watch`] channel, wrapped into a message by
+    /// `wrap`, to the running task.
+    ///
+    /// A watch channel only holds its latest value, so values replaced
+    /// before they were sent on are skipped and the task receives the latest
+    /// one. The current value is sent right away if `values` has not seen it
+    /// yet. Like [`subscribe`](Self::subscribe), this does not keep the
+    /// task's mailbox open and ends when the channel closes, with the first
+    /// change after the task has stopped receiving, or through the returned
+    /// handle.
+    ///
+    /// See [`ConfigTask`](crate::config::ConfigTask) for publishing
+    /// configuration changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    pub fn watch<T, W>(&self, mut values: watch::Receiver<T>, mut wrap: W) -> AbortHandle
+    where
+        T: Clone + Send + Sync + 'static,
+        W: FnMut(T) -> M + Send + 'static,
+        M: Send + 'static,
+    {
+        let task = self.this().weak();
+        runtime::spawn(self.name(), async move {
+            while values.changed().await.is_ok() {
+                let value = values.borrow_and_update().clone();
+                let Some(task) = task.upgrade() else {
+                    break;
+                };
+                if task.send(wrap(value)).is_err() {
+                    break;
+                }
+            }
+        })
+        .abort_handle()
+    }
+
+    /// Handle the following messages with `behavior`.
+    ///
+    /// The behavior is pushed on top of the current one, which takes over
+    /// again after [`unbecome`](Self::unbecome). Takes effect with the next
+    /// message.
+    pub fn r#become<S, B>(&mut self, behavior: B)
+    where
+        B: Behavior<S, M>,
+        S: Send + 'static,
+        M: Send + 'static,
+    {
+        let behavior: Box<dyn DynBehavior<S, M>> = Box::new(behavior);
+        self.behaviors.push(Some(Box::new(behavior)));
+    }
+
+    /// Return to the behavior that was active before the last
+    /// [`become`](Self::r#become).
+    ///
+    /// Does nothing if no behavior has been pushed, i.e. while
+    /// [`Handler::handle`] is active.
+    pub fn unbecome(&mut self) {
+        self.behaviors.pop();
+    }
+
+    /// Number of behaviors pushed on top of [`Handler::handle`].
+    pub fn behaviors(&self) -> usize {
+        self.behaviors.len()
+    }
+}
+
+impl<M> std::fmt::Debug for Context<M> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("id", &self.state.id)
+            .field("name", &self.state.name)
+            .field("stopped", &self.stopped)
+            .field("behaviors", &self.behaviors.len())
+            .finish()
+    }
+}
@@ -0,0 +1,72 @@
+//! Health checks of running tasks.
+//!
+//! [`TaskHandle::health`](super::TaskHandle::health) combines a
+//! [heartbeat](super::TaskRef::ping) with the depth of the task's mailbox
+//! and the time it last received a message into a [`HealthStatus`]. Mapped
+//! to an HTTP status, it backs liveness and readiness probes, e.g. those of
+//! Kubernetes.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use std::time::Duration;
+//!
+//! #[derive(Task)]
+//! #[task(message = u32)]
+//! struct Worker;
+//!
+//! impl Runnable<u32> for Worker {
+//!     async fn start(&self) {
+//!         while let Ok(job) = recv!(self) {
+//!             println!("job {job}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let worker = spawn!(Worker);
+//!
+//! let health = worker.health(Duration::from_millis(500)).await;
+//! let status = match health.is_ready(1_000) {
+//!     true => 200,
+//!     false => 503,
+//! };
+//! # }
+//! ```
+
+use std::time::Duration;
+
+use crate::runtime::Instant;
+
+/// The health of a task, as reported by
+/// [`TaskHandle::health`](super::TaskHandle::health).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthStatus {
+    /// Round-trip time of the heartbeat, or `None` if the task did not
+    /// answer in time
+    pub round_trip: Option<Duration>,
+    /// Approximate number of messages waiting in the task's mailbox
+    pub mailbox_depth: usize,
+    /// When the task last received a message, or `None` if it never did
+    pub last_activity: Option<Instant>,
+}
+
+impl HealthStatus {
+    /// Check whether the task answered the heartbeat.
+    pub fn is_alive(&self) -> bool {
+        self.round_trip.is_some()
+    }
+
+    /// Check whether the task is alive with at most `max_depth` messages
+    /// waiting.
+    pub fn is_ready(&self, max_depth: usize) -> bool {
+        self.is_alive() && self.mailbox_depth <= max_depth
+    }
+
+    /// Time since the task last received a message.
+    pub fn idle(&self) -> Option<Duration> {
+        self.last_activity.map(|at| at.elapsed())
+    }
+}
@@ -0,0 +1,137 @@
+//! Task identifiers.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+use tokio::sync::oneshot;
+
+tokio::task_local! {
+    static CURRENT: TaskId;
+}
+
+/// Spawned tasks that have not terminated yet, with the senders waking
+/// those [waiting](terminated) for them.
+static RUNNING: LazyLock<Mutex<HashMap<TaskId, Vec<oneshot::Sender<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// Unique identifier of a spawned task.
+///
+/// Every task receives a fresh identifier when it is spawned. Identifiers are
+/// unique for the lifetime of the process and are shared by the task's
+/// [`TaskHandle`](super::TaskHandle) and all of its [`TaskRef`](super::TaskRef)s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TaskId(u64);
+
+impl TaskId {
+    /// Allocate a fresh identifier.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn next() -> Self {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        TaskId(NEXT.fetch_add(1, Ordering::Relaxed))
+    }
+
+    /// The identifier of the task running the caller, or `None` outside of
+    /// tasks.
+    pub fn current() -> Option<Self> {
+        CURRENT.try_with(|id| *id).ok()
+    }
+
+    /// The numeric value of the identifier.
+    pub fn as_u64(&self) -> u64 {
+        self.0
+    }
+}
+
+impl fmt::Display for TaskId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Run `future` as the task identified by `id`.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn scope<F>(id: TaskId, future: F) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    CURRENT.scope(id, future)
+}
+
+/// Record that the task identified by `id` runs until the returned guard is
+/// dropped.
+pub(crate) fn running(id: TaskId) -> Running {
+    RUNNING.lock().unwrap().entry(id).or_default();
+    Running(id)
+}
+
+/// Marks a task as terminated when dropped, i.e. when its future completes
+/// or is aborted.
+pub(crate) struct Running(TaskId);
+
+impl Drop for Running {
+    fn drop(&mut self) {
+        // Never panic while the task is being dropped. Dropping the senders
+        // wakes the waiters.
+        if let Ok(mut running) = RUNNING.lock() {
+            running.remove(&self.0);
+        }
+    }
+}
+
+/// Wait for the task identified by `id` to terminate.
+///
+/// Returns `None` if no such task is running, because it has already
+/// terminated or was never spawned.
+pub(crate) fn terminated(id: TaskId) -> Option<impl Future<Output = ()> + Send + 'static> {
+    let (wake, woken) = oneshot::channel();
+    let mut running = RUNNING.lock().unwrap();
+    let waiters = running.get_mut(&id)?;
+    waiters.retain(|waiter| !waiter.is_closed());
+    waiters.push(wake);
+
+    Some(async move {
+        let _ = woken.await;
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn identifiers_are_unique() {
+        let first = TaskId::next();
+        let second = TaskId::next();
+
+        assert_ne!(first, second);
+        assert!(second > first);
+    }
+
+    #[tokio::test]
+    async fn current_identifier_is_scoped() {
+        let id = TaskId::next();
+
+        assert_eq!(TaskId::current(), None);
+        scope(id, async move { assert_eq!(TaskId::current(), Some(id)) }).await;
+    }
+
+    #[tokio::test]
+    async fn termination_wakes_waiters() {
+        let id = TaskId::next();
+        assert!(terminated(id).is_none());
+
+        let running = running(id);
+        let waiter = tokio::spawn(terminated(id).unwrap());
+        drop(running);
+
+        waiter.await.unwrap();
+        assert!(terminated(id).is_none());
+    }
+}
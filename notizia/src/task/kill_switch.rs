@@ -0,0 +1,110 @@
+//! Aborting groups of tasks at once.
+
+use std::sync::{Arc, Mutex};
+
+use super::TaskHandle;
+use crate::runtime::AbortHandle;
+
+#[derive(Default)]
+struct Inner {
+    triggered: bool,
+    handles: Vec<AbortHandle>,
+}
+
+/// Aborts a whole group of tasks with a single trigger.
+///
+/// Tasks are [registered](Self::register) with the switch, e.g. all helper
+/// tasks spawned for one request. [`trigger`](Self::trigger) aborts every
+/// registered task, like [`TaskHandle::kill`]. Tasks registered after the
+/// switch was triggered are aborted right away, so no task of the group
+/// survives a trigger.
+///
+/// Clones share the same group, so a switch can be handed to the tasks that
+/// spawn further helpers.
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use notizia::task::KillSwitch;
+/// # #[derive(Task)]
+/// # #[task(message = Job)]
+/// # struct Helper;
+/// # impl Runnable<Job> for Helper { async fn start(&self) {} }
+/// # enum Job {}
+/// # #[tokio::main]
+/// # async fn main() {
+/// let switch = KillSwitch::new();
+///
+/// let first = spawn!(Helper);
+/// let second = spawn!(Helper);
+/// switch.register(&first);
+/// switch.register(&second);
+///
+/// // The request was cancelled, abort all of its helpers
+/// switch.trigger();
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct KillSwitch {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl KillSwitch {
+    /// Create a switch without registered tasks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the task behind `handle` with the switch.
+    ///
+    /// The handle stays usable; the switch only keeps a way to abort it.
+    pub fn register<T>(&self, handle: &TaskHandle<T>) {
+        self.register_abort(handle.abort_handle());
+    }
+
+    /// Register any task with the switch, such as a Tokio task by its
+    /// [`tokio::task::AbortHandle`].
+    pub fn register_abort(&self, handle: impl Into<AbortHandle>) {
+        let handle = handle.into();
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.triggered {
+            handle.abort();
+            return;
+        }
+
+        inner.handles.retain(|handle| !handle.is_finished());
+        inner.handles.push(handle);
+    }
+
+    /// Abort all registered tasks.
+    ///
+    /// Triggering an already triggered switch has no effect.
+    pub fn trigger(&self) {
+        let handles = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.triggered = true;
+            std::mem::take(&mut inner.handles)
+        };
+
+        for handle in handles {
+            handle.abort();
+        }
+    }
+
+    /// Check whether the switch has been triggered.
+    pub fn is_triggered(&self) -> bool {
+        self.inner.lock().unwrap().triggered
+    }
+}
+
+impl std::fmt::Debug for KillSwitch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("KillSwitch")
+            .field("triggered", &inner.triggered)
+            .field("registered", &inner.handles.len())
+            .finish()
+    }
+}
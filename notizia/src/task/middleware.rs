@@ -0,0 +1,201 @@
+//! Interceptors running around every message a task receives.
+//!
+//! A [`Middleware`] is registered when spawning a task with
+//! [`SpawnBuilder::middleware`](super::SpawnBuilder::middleware) and sees
+//! every message before the task does. This allows cross-cutting logging,
+//! timing and filtering without touching the task's code.
+//!
+//! [`before_handle`](Middleware::before_handle) is called when a message is
+//! received and may drop it. [`after_handle`](Middleware::after_handle) is
+//! called once the task has handled the message, i.e. when it asks for its
+//! next one, with the time handling took. Middleware run in the order they
+//! were registered before a message is handled, and in reverse order after.
+//!
+//! [`Dedup`](super::Dedup) is a ready-made middleware dropping messages with
+//! an idempotency key that was already processed.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use notizia::prelude::*;
+//! use notizia::task::Middleware;
+//!
+//! #[derive(Debug)]
+//! enum Job {
+//!     Work(u32),
+//!     Ping,
+//! }
+//!
+//! struct LoggingLayer;
+//!
+//! impl Middleware<Job> for LoggingLayer {
+//!     fn before_handle(&self, msg: &Job) -> bool {
+//!         println!("handling {msg:?}");
+//!         // Pings are not worth waking the task up for
+//!         !matches!(msg, Job::Ping)
+//!     }
+//!
+//!     fn after_handle(&self, elapsed: Duration) {
+//!         println!("handled in {elapsed:?}");
+//!     }
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = Job)]
+//! struct Worker;
+//!
+//! impl Runnable<Job> for Worker {
+//!     async fn start(&self) {
+//!         while let Ok(job) = recv!(self) {
+//!             println!("{job:?}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let handle = Worker.builder().middleware(LoggingLayer).spawn();
+//! handle.send(Job::Work(1)).unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::runtime::Instant;
+
+/// An interceptor running around every message of a task.
+///
+/// See the [module documentation](self) for when the hooks are called.
+pub trait Middleware<M>: Send + Sync {
+    /// Called when `msg` was received, before the task handles it.
+    ///
+    /// Returning `false` drops the message: neither the task nor the
+    /// remaining middleware see it. The default implementation lets every
+    /// message through.
+    fn before_handle(&self, msg: &M) -> bool {
+        let _ = msg;
+        true
+    }
+
+    /// Called once the task has handled a message, with the time it took.
+    ///
+    /// The message itself has been consumed by the task at this point;
+    /// middleware needing details of it record them in
+    /// [`before_handle`](Self::before_handle). The default implementation
+    /// does nothing.
+    fn after_handle(&self, elapsed: Duration) {
+        let _ = elapsed;
+    }
+}
+
+/// Middleware registered for a spawn, with their message type erased.
+#[derive(Clone, Default)]
+pub(crate) struct Layers(Vec<Arc<dyn Any + Send + Sync>>);
+
+impl Layers {
+    pub(crate) fn push<M>(&mut self, layer: impl Middleware<M> + 'static)
+    where
+        M: 'static,
+    {
+        let layer: Arc<dyn Middleware<M>> = Arc::new(layer);
+        self.0.push(Arc::new(layer));
+    }
+
+    /// The middleware for messages of type `M` of the task named `task`.
+    pub(crate) fn resolve<M>(&self, task: &'static str) -> Chain<M>
+    where
+        M: 'static,
+    {
+        let layers = self
+            .0
+            .iter()
+            .map(|layer| {
+                layer
+                    .downcast_ref::<Arc<dyn Middleware<M>>>()
+                    .expect("middleware was registered for a different message type")
+                    .clone()
+            })
+            .collect();
+
+        Chain {
+            layers,
+            task,
+            started: None,
+        }
+    }
+}
+
+impl fmt::Debug for Layers {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Layers")
+            .field("len", &self.0.len())
+            .finish()
+    }
+}
+
+/// The middleware of a running task, tracking the message being handled.
+pub(crate) struct Chain<M> {
+    layers: Arc<[Arc<dyn Middleware<M>>]>,
+    /// Name of the task, labelling its metrics
+    #[cfg_attr(not(feature = "metrics"), allow(dead_code))]
+    task: &'static str,
+    /// When the message currently being handled was delivered
+    started: Option<Instant>,
+}
+
+impl<M> Chain<M> {
+    /// Run the `before_handle` hooks, returning whether to deliver `msg`.
+    pub(crate) fn admit(&mut self, msg: &M) -> bool {
+        if !self.layers.iter().all(|layer| layer.before_handle(msg)) {
+            return false;
+        }
+
+        if !self.layers.is_empty() || cfg!(feature = "metrics") {
+            self.started = Some(Instant::now());
+        }
+        true
+    }
+
+    /// Name of the task the middleware runs for.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn task(&self) -> &'static str {
+        self.task
+    }
+
+    /// Run the `after_handle` hooks for the message being handled, if any.
+    pub(crate) fn finish(&mut self) {
+        if let Some(started) = self.started.take() {
+            let elapsed = started.elapsed();
+            #[cfg(feature = "metrics")]
+            crate::metrics::handled(self.task, elapsed);
+
+            for layer in self.layers.iter().rev() {
+                layer.after_handle(elapsed);
+            }
+        }
+    }
+}
+
+impl<M> Clone for Chain<M> {
+    fn clone(&self) -> Self {
+        Chain {
+            layers: self.layers.clone(),
+            task: self.task,
+            started: None,
+        }
+    }
+}
+
+impl<M> Default for Chain<M> {
+    fn default() -> Self {
+        Chain {
+            layers: Arc::new([]),
+            task: "",
+            started: None,
+        }
+    }
+}
@@ -3,13 +3,65 @@
 //! This module contains the core abstractions for working with tasks:
 //! - [`Task`] - Trait automatically implemented by `#[derive(Task)]`
 //! - [`Runnable`] - User-facing trait for task logic
+//! - [`Handler`] - Alternative to [`Runnable`] handling one message at a time
+//! - [`Scope`] - Structured concurrency inside handlers
 //! - [`TaskHandle`] - Handle for controlling spawned tasks
+//! - [`respawn`] - Restarting crashed tasks in place
+//! - [`HealthStatus`] - Liveness and load of a task for health probes
+//! - [`SpawnBuilder`] - Spawning tasks with per-spawn options
+//! - [`Middleware`] - Interceptors running around every message
+//! - [`AuditSink`] - Audit log of the messages a handler task processed
+//! - [`watchdog`] - Detection of handlers stuck on a single message
+//! - [`Dedup`] - Middleware dropping messages with a processed idempotency key
 //! - [`TaskRef`] - Lightweight reference for sending messages
+//! - [`TaskId`] - Unique identifier of a spawned task
+//! - [`TaskSet`] - Joining groups of tasks as they finish
+//! - [`KillSwitch`] - Aborting groups of tasks at once
+//! - [`Controlled`] - Tasks with a second mailbox for control messages
+//! - [`Throttled`] - Rate-limited task reference
+//! - [`CircuitBreaker`] - Fail-fast guard for calls to failing tasks
 
+pub mod audit;
+pub mod builder;
+pub mod circuit_breaker;
+pub mod control;
+pub mod dedup;
 pub mod handle;
+pub mod handler;
+pub mod health;
+pub mod id;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod kill_switch;
+pub mod middleware;
 pub mod reference;
+pub mod respawn;
+pub mod scope;
+pub mod set;
+pub mod throttled;
+pub mod timer;
 pub mod traits;
+pub mod watchdog;
 
+pub use audit::{AuditRecord, AuditSink, Outcome};
+pub use builder::{SpawnBuilder, SpawnOptions};
+pub use circuit_breaker::{CircuitBreaker, CircuitState};
+pub use control::{Control, Controlled, Incoming};
+pub use dedup::{Dedup, Idempotent};
 pub use handle::TaskHandle;
+pub use handler::{Behavior, Context, Handler};
+pub use health::HealthStatus;
+pub use id::TaskId;
+#[cfg(not(target_arch = "wasm32"))]
+pub use kill_switch::KillSwitch;
+pub use middleware::Middleware;
 pub use reference::TaskRef;
+pub use respawn::{MailboxPolicy, RespawnError};
+pub use scope::Scope;
+pub use set::TaskSet;
+pub use throttled::Throttled;
+pub use timer::TimerHandle;
 pub use traits::{Runnable, Task};
+
+/// Runtime metrics of a task, see [`TaskHandle::runtime_metrics`].
+#[cfg(feature = "tokio-metrics")]
+pub use tokio_metrics::TaskMetrics;
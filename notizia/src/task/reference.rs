@@ -1,8 +1,26 @@
 //! Lightweight reference to a task.
 
-use tokio::sync::mpsc::UnboundedSender;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
 
-use crate::core::errors::SendResult;
+use tokio::sync::mpsc::error::SendError;
+use tokio::sync::mpsc::{UnboundedSender, WeakUnboundedSender, unbounded_channel};
+
+use super::timer::{self, TimerHandle};
+use super::{Control, TaskId, Throttled};
+use crate::core::correlation::CorrelationId;
+use crate::core::delivery::Ack;
+use crate::core::envelope::{self, Envelope, Sender, SystemSignal};
+use crate::core::errors::{CallError, CallResult, SendResult};
+use crate::core::mailbox::{Admission, Capacity, Overflow};
+#[cfg(feature = "spill")]
+use crate::core::spill::Spill;
+#[cfg(feature = "spsc")]
+use crate::core::spsc::Ring;
+use crate::core::time::IntoTimeout;
+use crate::runtime::{self, Instant};
 
 /// A lightweight reference to a task for sending messages.
 ///
@@ -14,10 +32,17 @@ use crate::core::errors::SendResult;
 ///
 /// Unlike `TaskHandle`, a `TaskRef` cannot join or kill the task.
 ///
+/// References compare equal and hash alike if they refer to the same task,
+/// i.e. have the same [`TaskId`], so they can be kept in sets or used as
+/// map keys.
+///
+/// A reference displays as the name and id of the task and whether it still
+/// accepts messages, e.g. `Worker#7 (alive)`; its `Debug` output adds the
+/// module path of the task type.
+///
 /// # Example
 ///
-/// ```ignore
-/// # TODO: Re-enable once derive macro hygiene is fixed
+/// ```
 /// # use notizia::prelude::*;
 /// # #[derive(Task)]
 /// # #[task(message = PingMsg)]
@@ -32,18 +57,191 @@ use crate::core::errors::SendResult;
 /// # #[derive(Clone)]
 /// # struct PingMsg;
 /// ```
-#[derive(Debug, Clone)]
 pub struct TaskRef<T> {
-    sender: UnboundedSender<T>,
+    sender: UnboundedSender<Envelope<T>>,
+    id: TaskId,
+    name: &'static str,
+    path: &'static str,
+    capacity: Option<Arc<Capacity>>,
+    #[cfg(feature = "spill")]
+    spill: Option<Arc<Spill<T>>>,
+    #[cfg(feature = "spsc")]
+    ring: Option<Arc<Ring<T>>>,
+    control: Option<Control>,
+}
+
+// Manual Clone implementation to avoid requiring T: Clone
+impl<T> Clone for TaskRef<T> {
+    fn clone(&self) -> Self {
+        TaskRef {
+            sender: self.sender.clone(),
+            id: self.id,
+            name: self.name,
+            path: self.path,
+            capacity: self.capacity.clone(),
+            #[cfg(feature = "spill")]
+            spill: self.spill.clone(),
+            #[cfg(feature = "spsc")]
+            ring: self.ring.clone(),
+            control: self.control.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for TaskRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TaskRef")
+            .field("name", &self.name)
+            .field("id", &self.id)
+            .field("path", &self.path)
+            .field("alive", &!self.is_closed())
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for TaskRef<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let liveness = if self.is_closed() { "closed" } else { "alive" };
+        write!(f, "{}#{} ({liveness})", self.name, self.id)
+    }
+}
+
+impl<T> PartialEq for TaskRef<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.same_task(other)
+    }
+}
+
+impl<T> Eq for TaskRef<T> {}
+
+impl<T> Hash for TaskRef<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
 }
 
 impl<T> TaskRef<T> {
     /// Create a new task reference.
     ///
     /// This is typically called by the generated code and not by user code directly.
+    /// The reference receives a fresh [`TaskId`] and the name `"anonymous"`.
     #[doc(hidden)]
-    pub fn new(sender: UnboundedSender<T>) -> Self {
-        TaskRef { sender }
+    pub fn new(sender: UnboundedSender<Envelope<T>>) -> Self {
+        Self::with_identity(sender, TaskId::next(), "anonymous")
+    }
+
+    /// Create a new task reference for a task with a known identity.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    /// The path of the reference is its name until set with
+    /// [`with_path`](Self::with_path).
+    #[doc(hidden)]
+    pub fn with_identity(
+        sender: UnboundedSender<Envelope<T>>,
+        id: TaskId,
+        name: &'static str,
+    ) -> Self {
+        TaskRef {
+            sender,
+            id,
+            name,
+            path: name,
+            capacity: None,
+            #[cfg(feature = "spill")]
+            spill: None,
+            #[cfg(feature = "spsc")]
+            ring: None,
+            control: None,
+        }
+    }
+
+    /// Set the module path of the task type.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    #[doc(hidden)]
+    pub fn with_path(mut self, path: &'static str) -> Self {
+        self.path = path;
+        self
+    }
+
+    /// Limit the number of queued messages to the given capacity.
+    pub(crate) fn with_capacity(mut self, capacity: Option<Arc<Capacity>>) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    /// Spill messages beyond the mailbox's threshold to disk.
+    #[cfg(feature = "spill")]
+    pub(crate) fn with_spill(mut self, spill: Option<Arc<Spill<T>>>) -> Self {
+        self.spill = spill;
+        self
+    }
+
+    /// Send messages through the ring buffer of a single-producer mailbox.
+    #[cfg(feature = "spsc")]
+    pub(crate) fn with_ring(mut self, ring: Option<Arc<Ring<T>>>) -> Self {
+        self.ring = ring;
+        self
+    }
+
+    /// Attach the control mailbox of a multi-protocol task.
+    pub(crate) fn with_control(mut self, control: Option<Control>) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// The control mailbox of a multi-protocol task.
+    pub(crate) fn control(&self) -> Option<&Control> {
+        self.control.as_ref()
+    }
+
+    /// The capacity of a bounded mailbox.
+    pub(crate) fn capacity(&self) -> Option<&Arc<Capacity>> {
+        self.capacity.as_ref()
+    }
+
+    /// The spilling state of a mailbox spilling to disk.
+    #[cfg(feature = "spill")]
+    pub(crate) fn spill(&self) -> Option<&Arc<Spill<T>>> {
+        self.spill.as_ref()
+    }
+
+    /// The ring buffer of a single-producer mailbox.
+    #[cfg(feature = "spsc")]
+    pub(crate) fn ring(&self) -> Option<&Arc<Ring<T>>> {
+        self.ring.as_ref()
+    }
+
+    /// Unique identifier of the referenced task.
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// Name of the referenced task.
+    ///
+    /// For tasks spawned from `#[derive(Task)]` types, this is the name of
+    /// the type.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Module path of the referenced task's type, e.g. `app::workers::Worker`.
+    pub fn path(&self) -> &'static str {
+        self.path
+    }
+
+    /// Check whether `other` refers to the same task.
+    ///
+    /// References are compared by their [`TaskId`], which a
+    /// [respawned](super::TaskHandle::respawn) task keeps.
+    pub fn same_task(&self, other: &TaskRef<T>) -> bool {
+        self.id == other.id
+    }
+
+    /// Check whether the referenced task is the task calling this method,
+    /// e.g. to detect that a message would be sent to the sender itself.
+    pub fn is_current(&self) -> bool {
+        TaskId::current() == Some(self.id)
     }
 
     /// Send a message to the referenced task.
@@ -54,12 +252,18 @@ impl<T> TaskRef<T> {
     /// # Errors
     ///
     /// Returns [`SendError`](crate::core::errors::SendError) if the task has
-    /// terminated and the receiver has been dropped.
+    /// terminated and the receiver has been dropped, or if the task's
+    /// [bounded](crate::core::bounded) mailbox is full and rejects overflowing
+    /// messages. With [`Overflow::DropNewest`], a message sent to a full
+    /// mailbox is discarded and `Ok(())` is returned. For a mailbox
+    /// [spilling to disk](crate::core::spill), an error is also returned if
+    /// the message cannot be written to the spill file, and for a
+    /// [single-producer mailbox](crate::core::spsc) if its ring buffer is
+    /// full.
     ///
     /// # Example
     ///
-    /// ```ignore
-    /// # TODO: Re-enable once derive macro hygiene is fixed
+    /// ```
     /// # use notizia::prelude::*;
     /// # #[derive(Clone)]
     /// # enum Signal { Ping }
@@ -75,6 +279,300 @@ impl<T> TaskRef<T> {
     /// # }
     /// ```
     pub fn send(&self, msg: T) -> SendResult<T> {
-        self.sender.send(msg)
+        self.send_with(msg, None)
+    }
+
+    /// Pass on a message to the referenced task on behalf of its sender.
+    ///
+    /// Sends like [`send`](Self::send), but the message keeps the
+    /// [sender](crate::core::Sender) of the message the running task is
+    /// handling, so the referenced task answers the original sender. A
+    /// request keeps its `reply_to`, so the caller is answered by the
+    /// referenced task directly. See [`forward!`](crate::forward!).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`send`](Self::send). The error contains
+    /// the message, `reply_to` included, so it can be forwarded elsewhere.
+    pub fn forward(&self, msg: T) -> SendResult<T> {
+        envelope::forwarding(|| self.send(msg))
+    }
+
+    /// Send a message to the referenced task once `delay` has passed.
+    ///
+    /// The returned [`TimerHandle`] cancels or reschedules the send. It is
+    /// canceled automatically if the referenced task stops receiving
+    /// messages or, when called from within a task, once that task ends.
+    /// See [`task::timer`](crate::task::timer).
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    pub fn send_after(&self, delay: Duration, msg: T) -> TimerHandle
+    where
+        T: Send + 'static,
+    {
+        let mut msg = Some(msg);
+        timer::schedule(self, delay, None, move || {
+            msg.take().expect("a delayed message is sent once")
+        })
+    }
+
+    /// Send a message made by `make` to the referenced task every `period`,
+    /// starting one period from now.
+    ///
+    /// Like [`send_after`](Self::send_after), the returned [`TimerHandle`]
+    /// controls the timer, which stops with either task. Ticks missed while
+    /// the runtime was busy are skipped, and a tick that finds a full
+    /// [bounded](crate::core::bounded) mailbox is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a Tokio runtime and no other
+    /// [runtime](crate::runtime) backend is enabled.
+    pub fn send_interval<F>(&self, period: Duration, make: F) -> TimerHandle
+    where
+        T: Send + 'static,
+        F: FnMut() -> T + Send + 'static,
+    {
+        timer::schedule(self, period, Some(period), make)
+    }
+
+    /// Send a message, waiting for room in a full mailbox.
+    ///
+    /// For a [bounded](crate::core::bounded) mailbox, this waits until the
+    /// task has received enough messages to make room, whatever the
+    /// mailbox's [`Overflow`] policy. Producers feeding a task this way slow
+    /// down to the pace of the task instead of racing ahead of it. For other
+    /// mailboxes, it behaves like [`send`](Self::send). See
+    /// [`feed!`](crate::feed!).
+    ///
+    /// The wait is not limited; wrap the future in a timeout to bound it.
+    /// Dropping the future before it completes sends nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated and the receiver has been dropped, including while waiting.
+    pub async fn feed(&self, msg: T) -> SendResult<T> {
+        #[cfg(feature = "spsc")]
+        if let Some(ring) = &self.ring {
+            let (mut msg, mut space) = (msg, None);
+            loop {
+                match self.send_user(msg, None) {
+                    Err(SendError(rejected)) if !self.is_closed() => msg = rejected,
+                    sent => return sent,
+                }
+
+                // Only wait once registered, so no room made in between is missed
+                match space.take() {
+                    None => space = Some(ring.space()),
+                    Some(space) => tokio::select! {
+                        () = space => {}
+                        () = self.sender.closed() => {}
+                    },
+                }
+            }
+        }
+
+        let Some(capacity) = &self.capacity else {
+            return self.send(msg);
+        };
+
+        tokio::select! {
+            () = capacity.reserve() => {}
+            () = self.sender.closed() => return Err(SendError(msg)),
+        }
+
+        self.send_user(msg, None)
+            .inspect_err(|_| capacity.cancel(Admission::Accepted))
+    }
+
+    /// Send a message that the task acknowledges once it has processed it.
+    ///
+    /// This is typically called by the generated code and not by user code directly.
+    /// Use [`send_reliable!`](crate::send_reliable!) instead.
+    #[doc(hidden)]
+    pub fn send_acked(&self, msg: T, ack: Ack) -> SendResult<T> {
+        self.send_with(msg, Some(ack))
+    }
+
+    fn send_with(&self, msg: T, ack: Option<Ack>) -> SendResult<T> {
+        #[cfg(feature = "spill")]
+        if let Some(spill) = &self.spill {
+            if self.is_closed() {
+                return Err(SendError(msg));
+            }
+            return match ack {
+                None => spill.send(msg, |msg| self.send_user(msg, None)),
+                Some(ack) => spill.send_unspilled(msg, |msg| self.send_user(msg, Some(ack))),
+            };
+        }
+
+        let Some(capacity) = &self.capacity else {
+            return self.send_user(msg, ack);
+        };
+
+        let admission = capacity.acquire();
+        match admission {
+            Admission::Full(Overflow::DropNewest) => Ok(()),
+            Admission::Full(_) => Err(SendError(msg)),
+            _ => self
+                .send_user(msg, ack)
+                .inspect_err(|_| capacity.cancel(admission)),
+        }
+    }
+
+    fn send_user(&self, msg: T, ack: Option<Ack>) -> SendResult<T> {
+        let envelope = match (Sender::current(), ack) {
+            (Some(sender), ack) => Envelope::Sent(msg, sender, ack),
+            (None, Some(ack)) => Envelope::Reliable(msg, ack),
+            (None, None) => Envelope::User(msg),
+        };
+
+        #[cfg(feature = "spsc")]
+        if let Some(ring) = &self.ring {
+            if self.is_closed() {
+                return Err(SendError(into_message(envelope)));
+            }
+            return ring
+                .push(envelope)
+                .map_err(|envelope| SendError(into_message(envelope)));
+        }
+
+        self.sender
+            .send(envelope)
+            .map_err(|SendError(envelope)| SendError(into_message(envelope)))
+    }
+
+    /// Send a system signal to the referenced task.
+    ///
+    /// Signals are handled by the task's mailbox once the messages sent
+    /// before them have been received; see [`core::envelope`](crate::core::envelope).
+    /// They do not count towards the capacity of a bounded mailbox.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated or stopped accepting messages.
+    pub fn signal(&self, signal: SystemSignal) -> SendResult<SystemSignal> {
+        self.sender
+            .send(Envelope::System(signal))
+            .map_err(|_| SendError(signal))
+    }
+
+    /// Check that the referenced task is alive and receiving messages.
+    ///
+    /// The ping is answered by the task's mailbox, without the task's code
+    /// seeing it, the next time the task receives after the messages sent
+    /// before the ping. Returns the round-trip time. A task busy with a
+    /// message, or one that never receives, does not answer.
+    ///
+    /// # Errors
+    ///
+    /// - [`CallError::SendError`] if the task has stopped accepting messages
+    /// - [`CallError::ChannelClosed`] if the task terminated before answering
+    /// - [`CallError::Timeout`] if no answer arrived within `timeout`
+    /// - [`CallError::WouldDeadlock`] if a task pings itself
+    pub async fn ping(&self, timeout: impl IntoTimeout) -> CallResult<Duration> {
+        CallError::check_self_call(self.id, self.name)?;
+
+        let correlation = CorrelationId::current_or_next();
+        let (pong, answered) = Ack::channel();
+        let started = Instant::now();
+        self.sender
+            .send(Envelope::Ping(pong, correlation))
+            .map_err(|_| CallError::send_failed(self.id, self.name, SendError(())))?;
+
+        match runtime::timeout(timeout.into_timeout(), answered).await {
+            Ok(Ok(())) => Ok(started.elapsed()),
+            Ok(Err(_)) => Err(CallError::ChannelClosed),
+            Err(_) => Err(CallError::timed_out(
+                self.id,
+                self.name,
+                correlation,
+                started,
+            )),
+        }
+    }
+
+    /// Send a message to the control mailbox of the referenced task.
+    ///
+    /// Control messages bypass the task's data mailbox, so they are received
+    /// even while a large backlog of data messages is queued. See
+    /// [`task::control`](crate::task::control).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated or has no control mailbox for messages of type `C`.
+    pub fn send_control<C>(&self, msg: C) -> SendResult<C>
+    where
+        C: Send + 'static,
+    {
+        match &self.control {
+            Some(control) if !self.is_closed() => control.send(msg),
+            _ => Err(SendError(msg)),
+        }
+    }
+
+    /// Check whether the referenced task has stopped receiving messages.
+    ///
+    /// Once this returns `true`, every send through this reference fails.
+    pub fn is_closed(&self) -> bool {
+        self.sender.is_closed()
+    }
+
+    /// Limit the rate of sends through this reference.
+    ///
+    /// Returns a [`Throttled`] reference allowing `rate` messages per second
+    /// on average, with bursts of up to `burst` messages. Both values are at
+    /// least one.
+    pub fn throttled(self, rate: u32, burst: u32) -> Throttled<T> {
+        Throttled::new(self, rate, burst)
+    }
+
+    /// Downgrade to a weak sender that does not keep the task's mailbox open.
+    pub(crate) fn downgrade(&self) -> WeakUnboundedSender<Envelope<T>> {
+        self.sender.downgrade()
+    }
+
+    /// Downgrade to a reference that does not keep the task's mailbox open.
+    pub(crate) fn weak(&self) -> WeakTaskRef<T> {
+        let (closed, _) = unbounded_channel();
+        WeakTaskRef {
+            sender: self.downgrade(),
+            task: TaskRef {
+                sender: closed,
+                ..self.clone()
+            },
+        }
+    }
+}
+
+/// A [`TaskRef`] that does not keep the task's mailbox open.
+pub(crate) struct WeakTaskRef<T> {
+    sender: WeakUnboundedSender<Envelope<T>>,
+    /// The reference, disconnected from the task
+    task: TaskRef<T>,
+}
+
+impl<T> WeakTaskRef<T> {
+    /// The reference, unless the task has stopped receiving messages.
+    pub(crate) fn upgrade(&self) -> Option<TaskRef<T>> {
+        let sender = self.sender.upgrade().filter(|sender| !sender.is_closed())?;
+        Some(TaskRef {
+            sender,
+            ..self.task.clone()
+        })
+    }
+}
+
+/// The message of an envelope that carries one.
+fn into_message<T>(envelope: Envelope<T>) -> T {
+    match envelope {
+        Envelope::User(msg) | Envelope::Reliable(msg, _) | Envelope::Sent(msg, ..) => msg,
+        Envelope::System(_) | Envelope::Ping(..) => unreachable!("a user message was sent"),
     }
 }
@@ -0,0 +1,206 @@
+//! Restarting crashed tasks in place.
+//!
+//! A task spawned with [`SpawnBuilder::restartable`](super::SpawnBuilder::restartable)
+//! keeps its mailbox open after it terminates. [`TaskHandle::respawn`](super::TaskHandle::respawn)
+//! then starts a new incarnation of the task on the same mailbox: it keeps
+//! the [`TaskId`](super::TaskId) of the task, and every [`TaskRef`] handed
+//! out before stays valid.
+//!
+//! A [`MailboxPolicy`] decides what happens to the messages still queued
+//! from the previous incarnation. By default, they are put into the
+//! [dead-letter queue](crate::core::dead_letter) and the new incarnation
+//! starts with an empty mailbox; with [`MailboxPolicy::PreserveMailbox`],
+//! it receives them instead, so no message is lost. The message the task
+//! was handling when it crashed is not delivered again either way.
+//!
+//! This restarts a single task without building a full supervisor.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::task::respawn::MailboxPolicy;
+//!
+//! #[derive(Task)]
+//! #[task(message = u32, handler)]
+//! struct Divider;
+//!
+//! impl Handler<u32> for Divider {
+//!     async fn handle(&mut self, divisor: u32, _ctx: &mut Context<u32>) {
+//!         println!("{}", 100 / divisor);
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let mut divider = Divider
+//!     .builder()
+//!     .restartable_with(MailboxPolicy::PreserveMailbox)
+//!     .spawn();
+//! let divider_ref = divider.this();
+//!
+//! // Panics, terminating the task
+//! divider_ref.send(0).unwrap();
+//! # while !divider.is_finished() { tokio::task::yield_now().await; }
+//!
+//! divider.respawn(|| Divider).await.unwrap();
+//! divider_ref.send(4).unwrap();
+//! # }
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::sync::Arc;
+
+use tokio::sync::Mutex;
+
+use super::{SpawnOptions, TaskRef};
+use crate::core::Mailbox;
+use crate::core::dead_letter::{self, DeadLetter};
+use crate::core::envelope::Inbox;
+use crate::runtime::{self, Receiver};
+
+/// Errors when [respawning](super::TaskHandle::respawn) a task.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum RespawnError {
+    /// The task was not spawned with
+    /// [`SpawnBuilder::restartable`](super::SpawnBuilder::restartable)
+    #[error("task is not restartable")]
+    NotRestartable,
+    /// The current incarnation of the task is still running
+    #[error("task is still running")]
+    Running,
+    /// The mailbox of the task was closed, e.g. by
+    /// [`SystemSignal::Stop`](crate::core::SystemSignal::Stop) or passivation
+    #[error("mailbox closed")]
+    Closed,
+}
+
+/// What a respawned task does with the messages queued for its previous
+/// incarnation.
+///
+/// Passed to [`SpawnBuilder::restartable_with`](super::SpawnBuilder::restartable_with).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MailboxPolicy {
+    /// Deliver the queued messages to the new incarnation
+    PreserveMailbox,
+    /// Put the queued messages into the [dead-letter queue](crate::core::dead_letter)
+    #[default]
+    FlushToDeadLetters,
+}
+
+/// The mailbox of a restartable task, kept by its handle.
+pub(crate) struct Restart<T> {
+    pub(crate) options: SpawnOptions,
+    pub(crate) policy: MailboxPolicy,
+    pub(crate) inbox: Arc<Mutex<Option<Inbox<T>>>>,
+}
+
+impl<T> Restart<T>
+where
+    T: Send + 'static,
+{
+    pub(crate) fn new(
+        mut options: SpawnOptions,
+        policy: MailboxPolicy,
+        mailbox: &Mailbox<T>,
+    ) -> Self {
+        options.incarnation = None;
+        Restart {
+            options,
+            policy,
+            inbox: mailbox.receiver.clone(),
+        }
+    }
+
+    /// Prepare the mailbox of `task` for its next incarnation.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RespawnError::Closed`] if the mailbox was closed.
+    pub(crate) async fn prepare(&self, task: &TaskRef<T>) -> Result<(), RespawnError> {
+        let mut slot = self.inbox.lock().await;
+        let inbox = slot
+            .as_mut()
+            .filter(|inbox| !inbox.is_closed())
+            .ok_or(RespawnError::Closed)?;
+
+        // The previous incarnation did not finish its last message
+        inbox.abandon();
+        if self.policy == MailboxPolicy::FlushToDeadLetters {
+            for msg in inbox.drain() {
+                dead_letter::push(DeadLetter::new(
+                    task.id(),
+                    task.name(),
+                    1,
+                    &"task restarted",
+                    msg,
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Options spawning the next incarnation of `task` on the same mailbox.
+    pub(crate) fn next(&self, task: TaskRef<T>) -> SpawnOptions {
+        let mut options = self.options.clone();
+        options.incarnation = Some(AnyIncarnation::new(task, self.inbox.clone()));
+        options
+    }
+}
+
+/// The mailbox a new incarnation of a task takes over.
+struct Incarnation<T> {
+    task: TaskRef<T>,
+    inbox: Arc<Mutex<Option<Inbox<T>>>>,
+}
+
+/// An [`Incarnation`] of any message type, carried by [`SpawnOptions`].
+#[derive(Clone)]
+pub(crate) struct AnyIncarnation(Arc<dyn Any + Send + Sync>);
+
+impl AnyIncarnation {
+    /// The mailbox of `task`, connected to `inbox`.
+    pub(crate) fn new<T>(task: TaskRef<T>, inbox: Arc<Mutex<Option<Inbox<T>>>>) -> Self
+    where
+        T: Send + 'static,
+    {
+        AnyIncarnation(Arc::new(Incarnation { task, inbox }))
+    }
+
+    /// Connect `mailbox` to the inbox of the previous incarnation.
+    ///
+    /// Returns `None` if the previous incarnation had a different message
+    /// type.
+    pub(crate) fn resume<T>(
+        &self,
+        mut mailbox: Mailbox<T>,
+    ) -> Option<(TaskRef<T>, Mailbox<T>, Receiver<T>)>
+    where
+        T: Send + 'static,
+    {
+        let incarnation = self.0.downcast_ref::<Incarnation<T>>()?;
+        let task = incarnation.task.clone();
+
+        mailbox.receiver = incarnation.inbox.clone();
+        mailbox.reattached = true;
+        mailbox.capacity = task.capacity().cloned();
+        #[cfg(feature = "spill")]
+        {
+            mailbox.spill = task.spill().cloned();
+        }
+        #[cfg(feature = "spsc")]
+        {
+            mailbox.ring = task.ring().cloned();
+        }
+
+        // The receiver is never used, since the mailbox keeps its inbox
+        Some((task, mailbox, runtime::channel().1))
+    }
+}
+
+impl fmt::Debug for AnyIncarnation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AnyIncarnation").finish_non_exhaustive()
+    }
+}
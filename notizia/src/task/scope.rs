@@ -0,0 +1,89 @@
+//! Structured concurrency inside message handlers.
+//!
+//! [`Context::scope`](super::Context::scope) runs helper futures
+//! concurrently and only completes once all of them have completed, so no
+//! helper outlives the message that started it. The helpers run inside the
+//! task itself rather than being spawned, so they may borrow the task's
+//! state. If the handler is cancelled, e.g. because the task is killed,
+//! the helpers are dropped with it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//!
+//! #[derive(Task)]
+//! #[task(message = Vec<String>, handler)]
+//! struct Crawler {
+//!     pages: usize,
+//! }
+//!
+//! async fn fetch(url: &str) -> usize {
+//!     url.len()
+//! }
+//!
+//! impl Handler<Vec<String>> for Crawler {
+//!     async fn handle(&mut self, urls: Vec<String>, ctx: &mut Context<Vec<String>>) {
+//!         let sizes = ctx
+//!             .scope(|s| {
+//!                 for url in &urls {
+//!                     s.spawn(fetch(url));
+//!                 }
+//!             })
+//!             .await;
+//!
+//!         self.pages += sizes.len();
+//!     }
+//! }
+//! ```
+
+use std::future::Future;
+
+use futures::future::{BoxFuture, join_all};
+
+/// Helper futures started within [`Context::scope`](super::Context::scope).
+pub struct Scope<'a, T> {
+    futures: Vec<BoxFuture<'a, T>>,
+}
+
+impl<'a, T> Scope<'a, T> {
+    /// Run `future` concurrently with the other futures of the scope.
+    pub fn spawn<F>(&mut self, future: F)
+    where
+        F: Future<Output = T> + Send + 'a,
+    {
+        self.futures.push(Box::pin(future));
+    }
+
+    /// Number of futures started in the scope.
+    pub fn len(&self) -> usize {
+        self.futures.len()
+    }
+
+    /// Check whether no future has been started in the scope.
+    pub fn is_empty(&self) -> bool {
+        self.futures.is_empty()
+    }
+}
+
+/// Start the futures of a scope with `f` and wait for all of them.
+pub(crate) fn run<'a, T, F>(f: F) -> impl Future<Output = Vec<T>> + Send + 'a
+where
+    F: FnOnce(&mut Scope<'a, T>),
+    T: Send + 'a,
+{
+    let mut scope = Scope {
+        futures: Vec::new(),
+    };
+    f(&mut scope);
+
+    join_all(scope.futures)
+}
+
+impl<T> std::fmt::Debug for Scope<'_, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scope")
+            .field("futures", &self.futures.len())
+            .finish()
+    }
+}
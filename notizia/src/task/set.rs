@@ -0,0 +1,145 @@
+//! Joining groups of tasks.
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use futures::StreamExt;
+use futures::future::BoxFuture;
+use futures::stream::FuturesUnordered;
+
+use super::{TaskHandle, TaskId};
+use crate::runtime::{self, Instant, JoinError};
+use crate::{ShutdownError, ShutdownResult, TerminateReason};
+
+/// A collection of running tasks, joined as they finish.
+///
+/// A `TaskSet` owns the [`TaskHandle`]s of tasks with any message type.
+/// [`join_next`](Self::join_next) yields tasks in the order they terminate,
+/// so callers no longer depend on joining handles in the right order.
+///
+/// Like a [`TaskHandle`], the set keeps its tasks' mailboxes open until the
+/// tasks are joined or the set is [shut down](Self::shutdown_all).
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # use std::time::Duration;
+/// # #[derive(Task)]
+/// # #[task(message = Job)]
+/// # struct Worker;
+/// # impl Runnable<Job> for Worker { async fn start(&self) {} }
+/// # #[derive(Task)]
+/// # #[task(message = Report)]
+/// # struct Reporter;
+/// # impl Runnable<Report> for Reporter { async fn start(&self) {} }
+/// # enum Job {}
+/// # enum Report {}
+/// # #[tokio::main]
+/// # async fn main() {
+/// let mut set = TaskSet::new();
+/// set.insert(spawn!(Worker));
+/// set.insert(spawn!(Reporter));
+///
+/// while let Some((id, reason)) = set.join_next().await {
+///     println!("task {id} finished: {reason:?}");
+/// }
+///
+/// // Or stop everything that is still running
+/// let results = set.shutdown_all(Duration::from_secs(5)).await;
+/// # }
+/// ```
+#[derive(Default)]
+pub struct TaskSet {
+    /// References keeping the mailboxes of unjoined tasks open
+    refs: HashMap<TaskId, Box<dyn Send>>,
+    running: FuturesUnordered<BoxFuture<'static, (TaskId, Result<TerminateReason, JoinError>)>>,
+}
+
+impl TaskSet {
+    /// Create an empty set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a task to the set, returning its id.
+    pub fn insert<T>(&mut self, handle: TaskHandle<T>) -> TaskId
+    where
+        T: Send + 'static,
+    {
+        let id = handle.id();
+        let (task, join) = handle.into_parts();
+
+        self.refs.insert(id, Box::new(task));
+        self.running.push(Box::pin(async move { (id, join.await) }));
+
+        id
+    }
+
+    /// Check whether the task with `id` is in the set and not yet joined.
+    pub fn contains(&self, id: TaskId) -> bool {
+        self.refs.contains_key(&id)
+    }
+
+    /// Number of tasks that have not been joined yet.
+    pub fn len(&self) -> usize {
+        self.running.len()
+    }
+
+    /// Check whether all tasks have been joined.
+    pub fn is_empty(&self) -> bool {
+        self.running.is_empty()
+    }
+
+    /// Wait for the next task to terminate.
+    ///
+    /// Returns `None` once the set is empty. Like
+    /// [`TaskHandle::join`], this does not signal the tasks to stop.
+    pub async fn join_next(&mut self) -> Option<(TaskId, Result<TerminateReason, JoinError>)> {
+        let (id, result) = self.running.next().await?;
+        self.refs.remove(&id);
+
+        Some((id, result))
+    }
+
+    /// Wait for all tasks to terminate, in the order they finish.
+    pub async fn join_all(mut self) -> Vec<(TaskId, Result<TerminateReason, JoinError>)> {
+        let mut results = Vec::with_capacity(self.len());
+        while let Some(result) = self.join_next().await {
+            results.push(result);
+        }
+
+        results
+    }
+
+    /// Gracefully shut down all tasks within `timeout`.
+    ///
+    /// Closes the mailboxes of all tasks, like [`TaskHandle::shutdown`], and
+    /// waits for them to terminate. Tasks that are still running once the
+    /// timeout has elapsed are reported with [`ShutdownError::Timeout`].
+    pub async fn shutdown_all(mut self, timeout: Duration) -> Vec<(TaskId, ShutdownResult)> {
+        let mut pending: HashSet<TaskId> = self.refs.drain().map(|(id, _)| id).collect();
+        let deadline = Instant::now() + timeout;
+
+        let mut results = Vec::with_capacity(pending.len());
+        while let Ok(Some((id, result))) = runtime::timeout_at(deadline, self.running.next()).await
+        {
+            pending.remove(&id);
+            results.push((id, result.map_err(ShutdownError::from)));
+        }
+
+        results.extend(
+            pending
+                .into_iter()
+                .map(|id| (id, Err(ShutdownError::Timeout))),
+        );
+
+        results
+    }
+}
+
+impl std::fmt::Debug for TaskSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskSet").field("len", &self.len()).finish()
+    }
+}
@@ -0,0 +1,139 @@
+//! Rate-limited task references.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::TaskRef;
+use crate::core::errors::{SendResult, ThrottleError};
+use crate::runtime::Instant;
+
+/// A [`TaskRef`] that limits how fast messages can be sent.
+///
+/// Sends are limited by a token bucket: the bucket holds up to `burst`
+/// tokens and is refilled at `rate` tokens per second. Every send takes one
+/// token. [`send`](Self::send) waits until a token is available, while
+/// [`try_send`](Self::try_send) rejects the message instead.
+///
+/// Clones share the same bucket, so the limit applies to all of them
+/// together.
+///
+/// Created with [`TaskRef::throttled`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use notizia::prelude::*;
+/// # #[derive(Task)]
+/// # #[task(message = Signal)]
+/// # struct Worker;
+/// # impl Runnable<Signal> for Worker {
+/// #     async fn start(&self) {}
+/// # }
+/// # #[derive(Debug, Clone)]
+/// # enum Signal { Ping }
+/// # #[tokio::main]
+/// # async fn main() {
+/// let handle = spawn!(Worker);
+///
+/// // At most 100 messages per second, in bursts of up to 10
+/// let worker = handle.this().throttled(100, 10);
+///
+/// for _ in 0..1000 {
+///     worker.send(Signal::Ping).await.unwrap();
+/// }
+/// # }
+/// ```
+pub struct Throttled<T> {
+    task: TaskRef<T>,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+// Manual Clone implementation to avoid requiring T: Clone
+impl<T> Clone for Throttled<T> {
+    fn clone(&self) -> Self {
+        Throttled {
+            task: self.task.clone(),
+            bucket: self.bucket.clone(),
+        }
+    }
+}
+
+struct Bucket {
+    tokens: f64,
+    capacity: f64,
+    per_second: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Take a token, or return how long to wait until one is available.
+    fn take(&mut self) -> Result<(), Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.per_second).min(self.capacity);
+        self.updated = now;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.per_second,
+            ))
+        }
+    }
+}
+
+impl<T> Throttled<T> {
+    pub(crate) fn new(task: TaskRef<T>, rate: u32, burst: u32) -> Self {
+        let capacity = f64::from(burst.max(1));
+
+        Throttled {
+            task,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: capacity,
+                capacity,
+                per_second: f64::from(rate.max(1)),
+                updated: Instant::now(),
+            })),
+        }
+    }
+
+    /// Send a message, waiting until the rate limit allows it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the task has
+    /// terminated and the receiver has been dropped.
+    pub async fn send(&self, msg: T) -> SendResult<T> {
+        loop {
+            let wait = self.bucket.lock().unwrap().take();
+            match wait {
+                Ok(()) => return self.task.send(msg),
+                Err(wait) => crate::runtime::sleep(wait).await,
+            }
+        }
+    }
+
+    /// Send a message if the rate limit allows it right now.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ThrottleError::RateLimited`] if no token is available and
+    /// [`ThrottleError::Closed`] if the task has terminated. Both return the
+    /// message.
+    pub fn try_send(&self, msg: T) -> Result<(), ThrottleError<T>> {
+        if self.bucket.lock().unwrap().take().is_err() {
+            return Err(ThrottleError::RateLimited(msg));
+        }
+
+        self.task
+            .send(msg)
+            .map_err(|err| ThrottleError::Closed(err.0))
+    }
+
+    /// The underlying, unthrottled task reference.
+    pub fn task(&self) -> &TaskRef<T> {
+        &self.task
+    }
+}
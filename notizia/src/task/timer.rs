@@ -0,0 +1,247 @@
+//! Messages sent on a schedule.
+//!
+//! [`TaskRef::send_after`] sends a message once a delay has passed and
+//! [`TaskRef::send_interval`] sends one every period. A task declared with
+//! `tick = "100ms" => Msg::Tick` in its `#[task(...)]` attribute receives
+//! `Msg::Tick` every 100 milliseconds, so polling workers need no second
+//! task driving a timer:
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//!
+//! enum Msg {
+//!     Tick,
+//!     Job(u32),
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = Msg, handler, tick = "100ms" => Msg::Tick)]
+//! struct Poller {
+//!     pending: Vec<u32>,
+//! }
+//!
+//! impl Handler<Msg> for Poller {
+//!     async fn handle(&mut self, msg: Msg, _ctx: &mut Context<Msg>) {
+//!         match msg {
+//!             Msg::Job(job) => self.pending.push(job),
+//!             // Flush the batch every 100ms
+//!             Msg::Tick => self.pending.clear(),
+//!         }
+//!     }
+//! }
+//! ```
+//!
+//! The first tick arrives one period after the task has started. A task
+//! that falls behind is not sent the ticks it missed, and a tick that finds
+//! a [bounded](crate::core::bounded) mailbox full is dropped.
+//!
+//! Every scheduled send is controlled through a [`TimerHandle`], which
+//! [cancels](TimerHandle::cancel) or [reschedules](TimerHandle::reschedule)
+//! it; a task gets the handles of its ticks from [`ticks`]. Timers do not
+//! outlive their endpoints: a timer stops once the receiving task stops
+//! receiving messages, and timers started from within a task are canceled
+//! when that task ends.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+
+use tokio::sync::Notify;
+
+use super::TaskRef;
+use crate::runtime::{self, Instant};
+
+crate::runtime::task_local! {
+    static OWNED: Owned;
+}
+
+/// The timers started by the running task.
+#[derive(Default)]
+struct Owned {
+    timers: RefCell<Vec<Weak<Shared>>>,
+    ticks: RefCell<Vec<TimerHandle>>,
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        for timer in self.timers.get_mut().drain(..) {
+            if let Some(timer) = timer.upgrade() {
+                timer.cancel();
+            }
+        }
+    }
+}
+
+/// Run `future` as a task whose timers are canceled when it completes or
+/// is dropped.
+pub(crate) fn owning_timers<F>(future: F) -> impl Future<Output = F::Output>
+where
+    F: Future,
+{
+    OWNED.scope(Owned::default(), future)
+}
+
+/// Handles of the ticks declared on the running task, in the order of the
+/// `#[task(...)]` attribute.
+///
+/// Returns an empty list outside of tasks and for tasks without ticks.
+pub fn ticks() -> Vec<TimerHandle> {
+    OWNED
+        .try_with(|owned| owned.ticks.borrow().clone())
+        .unwrap_or_default()
+}
+
+/// When a timer fires next.
+struct Schedule {
+    /// `None` once the timer is canceled or has sent its only message
+    next: Option<Instant>,
+    /// The period of a repeating timer
+    period: Option<Duration>,
+}
+
+/// The state shared between a timer and its handles.
+struct Shared {
+    schedule: Mutex<Schedule>,
+    changed: Notify,
+}
+
+impl Shared {
+    fn cancel(&self) {
+        self.schedule.lock().unwrap().next = None;
+        self.changed.notify_one();
+    }
+}
+
+/// Controls a scheduled send.
+///
+/// Returned by [`TaskRef::send_after`] and [`TaskRef::send_interval`], and
+/// by [`ticks`] for the ticks of a task. Clones control the same timer.
+/// Dropping the handle does not cancel the timer.
+#[derive(Clone)]
+pub struct TimerHandle {
+    shared: Arc<Shared>,
+}
+
+impl TimerHandle {
+    /// Stop the timer. Messages not yet sent are not sent anymore.
+    pub fn cancel(&self) {
+        self.shared.cancel();
+    }
+
+    /// Restart the timer, firing once `delay` has passed.
+    ///
+    /// A repeating timer keeps firing every `delay` from then on. A timer
+    /// that was canceled or has already sent its only message is not
+    /// restarted.
+    pub fn reschedule(&self, delay: Duration) {
+        let mut schedule = self.shared.schedule.lock().unwrap();
+        if schedule.next.is_some() {
+            schedule.next = Instant::now().checked_add(delay);
+            if let Some(period) = &mut schedule.period {
+                *period = delay;
+            }
+        }
+        drop(schedule);
+        self.shared.changed.notify_one();
+    }
+
+    /// Check whether the timer may still send messages.
+    pub fn is_active(&self) -> bool {
+        self.shared.schedule.lock().unwrap().next.is_some()
+    }
+}
+
+impl fmt::Debug for TimerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let schedule = self.shared.schedule.lock().unwrap();
+        f.debug_struct("TimerHandle")
+            .field("active", &schedule.next.is_some())
+            .field("period", &schedule.period)
+            .finish()
+    }
+}
+
+/// Send the message made by `make` to `task` after `delay`, and then every
+/// `period`, if any.
+pub(crate) fn schedule<T, F>(
+    task: &TaskRef<T>,
+    delay: Duration,
+    period: Option<Duration>,
+    mut make: F,
+) -> TimerHandle
+where
+    T: Send + 'static,
+    F: FnMut() -> T + Send + 'static,
+{
+    let shared = Arc::new(Shared {
+        schedule: Mutex::new(Schedule {
+            next: Instant::now().checked_add(delay),
+            period,
+        }),
+        changed: Notify::new(),
+    });
+    let _ = OWNED.try_with(|owned| {
+        let mut timers = owned.timers.borrow_mut();
+        timers.retain(|timer| timer.strong_count() > 0);
+        timers.push(Arc::downgrade(&shared));
+    });
+
+    let name = task.name();
+    let task = task.weak();
+    let timer = shared.clone();
+    runtime::spawn(name, async move {
+        loop {
+            let Some(next) = timer.schedule.lock().unwrap().next else {
+                break;
+            };
+            tokio::select! {
+                () = runtime::sleep_until(next) => {}
+                // Canceled or rescheduled
+                () = timer.changed.notified() => continue,
+            }
+
+            let mut schedule = timer.schedule.lock().unwrap();
+            if schedule.next != Some(next) {
+                continue;
+            }
+            // Skip the ticks missed while the runtime was busy
+            schedule.next = schedule.period.and_then(|period| {
+                let now = Instant::now();
+                match next + period <= now {
+                    true => now.checked_add(period),
+                    false => Some(next + period),
+                }
+            });
+            drop(schedule);
+
+            let Some(task) = task.upgrade() else {
+                timer.cancel();
+                break;
+            };
+            // A full mailbox drops the message
+            if task.send(make()).is_err() && task.is_closed() {
+                timer.cancel();
+                break;
+            }
+        }
+    });
+
+    TimerHandle { shared }
+}
+
+/// Send the message made by `tick` to `task` every `period`, until the task
+/// stops receiving messages.
+///
+/// This is typically called by the generated code and not by user code directly.
+#[doc(hidden)]
+pub fn __tick<T, F>(task: &TaskRef<T>, period: Duration, tick: F) -> TimerHandle
+where
+    T: Send + 'static,
+    F: FnMut() -> T + Send + 'static,
+{
+    let tick = schedule(task, period, Some(period), tick);
+    let _ = OWNED.try_with(|owned| owned.ticks.borrow_mut().push(tick.clone()));
+    tick
+}
@@ -1,13 +1,12 @@
 //! Core traits for task behavior.
 
 use std::future::Future;
-
-use tokio::sync::mpsc::UnboundedReceiver;
+use std::time::Duration;
 
 use crate::core::errors::RecvResult;
 use crate::{TerminateReason, core::Mailbox};
 
-use super::{TaskHandle, TaskRef};
+use super::{SpawnBuilder, SpawnOptions, TaskHandle, TaskRef};
 
 /// User-facing trait for implementing task logic.
 ///
@@ -17,8 +16,7 @@ use super::{TaskHandle, TaskRef};
 ///
 /// # Example
 ///
-/// ```ignore
-/// # TODO: Re-enable once derive macro hygiene is fixed
+/// ```
 /// use notizia::prelude::*;
 /// # #[derive(Debug, Clone)]
 /// # enum Signal { Stop }
@@ -61,7 +59,7 @@ pub trait Runnable<T>: Send + Sync {
     ///
     /// # Arguments
     ///
-    /// * `reason` - Why the task is terminating ([`Normal`](crate::TerminateReason::Normal) or [`Panic`](crate::TerminateReason::Panic))
+    /// * `reason` - Why the task is terminating ([`Normal`](crate::TerminateReason::Normal), [`Panic`](crate::TerminateReason::Panic), [`Idle`](crate::TerminateReason::Idle), or [`DeadlineExceeded`](crate::TerminateReason::DeadlineExceeded))
     ///
     /// # Panics
     ///
@@ -119,9 +117,12 @@ pub trait Runnable<T>: Send + Sync {
 /// and should not be implemented manually. It provides the infrastructure
 /// for task spawning, message passing, and lifecycle management.
 ///
-/// The trait combines the user-facing [`Runnable`] trait with internal
+/// The trait combines the user-facing [`Runnable`] trait, or [`Handler`]
+/// for tasks declared with `#[task(message = T, handler)]`, with internal
 /// machinery for channel setup and task-local state management.
-pub trait Task<T>: Runnable<T>
+///
+/// [`Handler`]: super::Handler
+pub trait Task<T>: Send
 where
     T: Send,
 {
@@ -131,9 +132,12 @@ where
     /// and start the task logic.
     #[doc(hidden)]
     fn __setup(
-        &self,
-        receiver: UnboundedReceiver<T>,
-    ) -> impl Future<Output = TerminateReason> + Send;
+        self,
+        receiver: crate::runtime::Receiver<T>,
+        deadline: Option<Duration>,
+    ) -> impl Future<Output = TerminateReason> + Send
+    where
+        Self: Sized;
 
     /// Get the mailbox for this task.
     ///
@@ -141,6 +145,15 @@ where
     /// to receive messages.
     fn mailbox(&self) -> Mailbox<T>;
 
+    /// Internal spawn method (do not call directly).
+    ///
+    /// This method is called by [`run`](Self::run) and [`SpawnBuilder`] to
+    /// spawn the task with the given options.
+    #[doc(hidden)]
+    fn __spawn(self, options: SpawnOptions) -> TaskHandle<T>
+    where
+        Self: Sized;
+
     /// Run the task, returning a handle.
     ///
     /// This method spawns the task on the Tokio runtime and returns a
@@ -168,7 +181,41 @@ where
     /// handle.join().await;
     /// # }
     /// ```
-    fn run(self) -> TaskHandle<T>;
+    fn run(self) -> TaskHandle<T>
+    where
+        Self: Sized,
+    {
+        self.__spawn(SpawnOptions::default())
+    }
+
+    /// Configure how the task is spawned.
+    ///
+    /// Returns a [`SpawnBuilder`] collecting spawn-time options like the
+    /// task's name and mailbox; [`SpawnBuilder::spawn`] then spawns the task.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use notizia::prelude::*;
+    /// # #[derive(Task)]
+    /// # #[task(message = Signal)]
+    /// # struct Worker;
+    /// # impl Runnable<Signal> for Worker {
+    /// #     async fn start(&self) {}
+    /// # }
+    /// # #[derive(Clone)]
+    /// # enum Signal {}
+    /// # #[tokio::main]
+    /// # async fn main() {
+    /// let handle = Worker.builder().name("primary").spawn();
+    /// # }
+    /// ```
+    fn builder(self) -> SpawnBuilder<Self, T>
+    where
+        Self: Sized,
+    {
+        SpawnBuilder::new(self)
+    }
 
     /// Alias for [`run`](Self::run). Spawns the task and returns a handle.
     ///
@@ -217,8 +264,7 @@ where
     ///
     /// # Example
     ///
-    /// ```ignore
-    /// # TODO: Re-enable once derive macro hygiene is fixed
+    /// ```
     /// # use notizia::prelude::*;
     /// # #[derive(Clone)]
     /// # enum Signal { Stop }
@@ -236,7 +282,8 @@ where
     /// }
     /// ```
     fn recv(&self) -> impl Future<Output = RecvResult<T>> + Send {
-        async move { self.mailbox().recv().await }
+        let mailbox = self.mailbox();
+        async move { mailbox.recv().await }
     }
 
     /// Get a reference to this task.
@@ -246,8 +293,7 @@ where
     ///
     /// # Example
     ///
-    /// ```ignore
-    /// # TODO: Re-enable once derive macro hygiene is fixed
+    /// ```
     /// # use notizia::prelude::*;
     /// # #[derive(Clone)]
     /// # enum Signal {}
@@ -0,0 +1,159 @@
+//! Detection of stalled handlers.
+//!
+//! A handler that blocks on a lost lock, a hung connection or an endless
+//! loop keeps its task alive while no message gets through anymore. A task
+//! spawned with [`SpawnBuilder::watchdog`](super::SpawnBuilder::watchdog)
+//! is flagged as stalled once a single message has been processing for
+//! longer than the given bound: a warning naming the task and the type of
+//! the message is emitted (a `tracing` event with the `tracing` feature)
+//! and [`TaskHandle::is_stalled`](super::TaskHandle::is_stalled) reports
+//! `true` until the message is done.
+//!
+//! With [`SpawnBuilder::watchdog_with_state`](super::SpawnBuilder::watchdog_with_state),
+//! the warning also contains the [`Debug`](std::fmt::Debug) output of the
+//! task as it was before the stalled message. Since the handler holds the
+//! task while it runs, that state is captured before every message.
+//!
+//! Like [audits](super::audit), the watchdog only covers tasks declared with
+//! `#[task(message = M, handler)]`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use std::time::Duration;
+//!
+//! #[derive(Task, Debug, Default)]
+//! #[task(message = String, handler)]
+//! struct Resolver {
+//!     resolved: usize,
+//! }
+//!
+//! impl Handler<String> for Resolver {
+//!     async fn handle(&mut self, host: String, _ctx: &mut Context<String>) {
+//!         let _ = tokio::net::lookup_host((host, 443)).await;
+//!         self.resolved += 1;
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let resolver = Resolver::default()
+//!     .builder()
+//!     .watchdog_with_state(Duration::from_secs(5))
+//!     .spawn();
+//!
+//! resolver.send("example.com".to_string()).unwrap();
+//!
+//! if resolver.is_stalled() {
+//!     println!("resolver is stuck");
+//! }
+//! # }
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use super::TaskId;
+use crate::core::trace;
+use crate::runtime::{self, Instant};
+
+/// Renders the state of a task, given as `&dyn Any`.
+type Dump = fn(&dyn Any) -> String;
+
+/// The watchdog of a task, shared between its mailbox and handle.
+#[derive(Clone)]
+pub(crate) struct Watchdog {
+    bound: Duration,
+    dump: Option<Dump>,
+    stalled: Arc<AtomicBool>,
+}
+
+impl Watchdog {
+    pub(crate) fn new(bound: Duration) -> Self {
+        Watchdog {
+            bound,
+            dump: None,
+            stalled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Also report the state of the task, of type `S`.
+    pub(crate) fn dumping<S>(mut self) -> Self
+    where
+        S: fmt::Debug + 'static,
+    {
+        self.dump = Some(|task| match task.downcast_ref::<S>() {
+            Some(task) => format!("{task:#?}"),
+            None => String::from("<unknown>"),
+        });
+        self
+    }
+
+    /// Whether a message has been processing for longer than the bound.
+    pub(crate) fn is_stalled(&self) -> bool {
+        self.stalled.load(Ordering::Acquire)
+    }
+
+    /// Capture the state of `task` before it handles a message.
+    pub(crate) fn capture(&self, task: &dyn Any) -> Option<String> {
+        self.dump.map(|dump| dump(task))
+    }
+
+    /// Run `future`, handling a message of type `M`, and report it once it
+    /// exceeds the bound.
+    pub(crate) async fn guard<M, F>(
+        &self,
+        id: TaskId,
+        name: &'static str,
+        state: Option<String>,
+        future: F,
+    ) -> F::Output
+    where
+        F: Future,
+    {
+        let started = Instant::now();
+        let mut future = pin!(future);
+
+        tokio::select! {
+            output = &mut future => return output,
+            _ = runtime::sleep(self.bound) => {}
+        }
+
+        self.stalled.store(true, Ordering::Release);
+        trace::handler_stalled(
+            id,
+            name,
+            std::any::type_name::<M>(),
+            started.elapsed(),
+            state.as_deref(),
+        );
+
+        // Clear the flag even if the handler panics or is cancelled
+        let _stalled = Stalled(&self.stalled);
+        future.await
+    }
+}
+
+impl fmt::Debug for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Watchdog")
+            .field("bound", &self.bound)
+            .field("stalled", &self.stalled)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Clears the stalled flag when dropped.
+struct Stalled<'a>(&'a AtomicBool);
+
+impl Drop for Stalled<'_> {
+    fn drop(&mut self) {
+        self.0.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,127 @@
+//! Stub tasks answering messages with canned responses.
+//!
+//! A [`MockTask`] stands in for a collaborator of the task under test. Each
+//! rule registered with [`on`](MockTask::on) pairs a matcher with a
+//! response; a message is answered by the first rule matching it. Messages
+//! no rule matches go to the [`otherwise`](MockTask::otherwise) response, or
+//! are dropped, which cancels the caller's request.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::{call, message};
+//! use notizia::testing::MockTask;
+//!
+//! #[message]
+//! #[derive(Debug)]
+//! enum CounterMsg {
+//!     Increment,
+//!     #[request(reply = u32)]
+//!     GetCount,
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let counter = MockTask::new()
+//!     .on(
+//!         |msg| matches!(msg, CounterMsg::GetCount { .. }),
+//!         |msg| {
+//!             if let CounterMsg::GetCount { reply_to } = msg {
+//!                 let _ = reply_to.reply(42);
+//!             }
+//!         },
+//!     )
+//!     .spawn();
+//!
+//! assert_eq!(call!(counter, CounterMsg::GetCount).await.unwrap(), 42);
+//! # }
+//! ```
+
+use std::sync::Mutex;
+
+use crate::task::Runnable;
+
+type Matcher<M> = Box<dyn Fn(&M) -> bool + Send + Sync>;
+type Response<M> = Mutex<Box<dyn FnMut(M) + Send>>;
+
+/// A task answering messages according to registered rules.
+///
+/// See the [module documentation](self) for an example.
+#[derive(crate::Task)]
+#[task(message = M)]
+pub struct MockTask<M>
+where
+    M: Send + 'static,
+{
+    rules: Vec<(Matcher<M>, Response<M>)>,
+    otherwise: Option<Response<M>>,
+}
+
+impl<M> Default for MockTask<M>
+where
+    M: Send + 'static,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M> MockTask<M>
+where
+    M: Send + 'static,
+{
+    /// Create a mock without rules, dropping every message.
+    pub fn new() -> Self {
+        MockTask {
+            rules: Vec::new(),
+            otherwise: None,
+        }
+    }
+
+    /// Answer messages for which `matches` returns `true` with `respond`.
+    ///
+    /// Rules are tried in the order they were registered.
+    pub fn on<F, R>(mut self, matches: F, respond: R) -> Self
+    where
+        F: Fn(&M) -> bool + Send + Sync + 'static,
+        R: FnMut(M) + Send + 'static,
+    {
+        self.rules
+            .push((Box::new(matches), Mutex::new(Box::new(respond))));
+        self
+    }
+
+    /// Answer messages no rule matches with `respond`.
+    pub fn otherwise<R>(mut self, respond: R) -> Self
+    where
+        R: FnMut(M) + Send + 'static,
+    {
+        self.otherwise = Some(Mutex::new(Box::new(respond)));
+        self
+    }
+
+    fn respond(&self, msg: M) {
+        let response = self
+            .rules
+            .iter()
+            .find(|(matches, _)| matches(&msg))
+            .map(|(_, respond)| respond)
+            .or(self.otherwise.as_ref());
+
+        if let Some(respond) = response {
+            (respond.lock().unwrap())(msg);
+        }
+    }
+}
+
+impl<M> Runnable<M> for MockTask<M>
+where
+    M: Send + 'static,
+{
+    async fn start(&self) {
+        while let Ok(msg) = crate::recv!(self) {
+            self.respond(msg);
+        }
+    }
+}
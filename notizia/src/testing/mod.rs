@@ -0,0 +1,19 @@
+//! Utilities for testing tasks.
+//!
+//! - [`MockTask`] - Stub task answering messages with canned responses
+//! - [`Scheduler`] - Seeded, step-by-step control over message delivery
+//! - [`TestProbe`] - Stand-in task asserting the messages sent to it
+//! - [`time`] - Virtual time, moved forward with [`advance`]
+//!
+//! This module requires the `testing` feature, which is meant to be enabled
+//! for dev-dependencies only.
+
+pub mod mock;
+pub mod probe;
+pub mod scheduler;
+pub mod time;
+
+pub use mock::MockTask;
+pub use probe::TestProbe;
+pub use scheduler::{Scheduler, SchedulerGuard};
+pub use time::{advance, pause, resume};
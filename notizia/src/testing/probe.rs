@@ -0,0 +1,224 @@
+//! Asserting the messages a task sends.
+//!
+//! A [`TestProbe`] stands in for a task: its [`TaskRef`] is handed to the
+//! code under test, and the test then asserts on the messages that arrive,
+//! each with a deadline. This replaces counting messages in shared atomics
+//! and sleeping for a fixed time before checking the count.
+//!
+//! Failed expectations panic, like `assert!`, with the message that arrived
+//! instead or the time waited.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::expect_msg;
+//! use notizia::testing::TestProbe;
+//!
+//! #[derive(Debug, PartialEq)]
+//! enum Event {
+//!     Saved { id: u32 },
+//!     Deleted { id: u32 },
+//! }
+//!
+//! #[derive(Task)]
+//! #[task(message = u32)]
+//! struct Store {
+//!     events: TaskRef<Event>,
+//! }
+//!
+//! impl Runnable<u32> for Store {
+//!     async fn start(&self) {
+//!         while let Ok(id) = recv!(self) {
+//!             let _ = self.events.send(Event::Saved { id });
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let probe = TestProbe::new();
+//! let store = Store { events: probe.this() };
+//! let store = spawn!(store);
+//!
+//! store.send(1).unwrap();
+//! store.send(2).unwrap();
+//!
+//! probe.expect_msg(Event::Saved { id: 1 }, 100).await;
+//! expect_msg!(probe, Event::Saved { .. }, within = 100);
+//! probe.expect_no_msg(50).await;
+//! # }
+//! ```
+
+use std::fmt::Debug;
+use std::time::Duration;
+
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::core::errors::RecvError;
+use crate::core::mailbox::Mailbox;
+use crate::core::time::IntoTimeout;
+use crate::task::{TaskId, TaskRef};
+
+/// A stand-in task recording the messages sent to it.
+///
+/// See the [module documentation](self) for an example.
+pub struct TestProbe<T> {
+    task: TaskRef<T>,
+    mailbox: Mailbox<T>,
+}
+
+impl<T> Default for TestProbe<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> TestProbe<T> {
+    /// Create a probe named `"probe"`.
+    pub fn new() -> Self {
+        Self::named("probe")
+    }
+
+    /// Create a probe with the given name.
+    pub fn named(name: &'static str) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        TestProbe {
+            task: TaskRef::with_identity(sender, TaskId::next(), name),
+            mailbox: Mailbox::from_receiver(receiver),
+        }
+    }
+
+    /// A reference to the probe, to be handed to the code under test.
+    pub fn this(&self) -> TaskRef<T> {
+        self.task.clone()
+    }
+
+    /// The probe's identifier.
+    pub fn id(&self) -> TaskId {
+        self.task.id()
+    }
+
+    /// Receive the next message, waiting at most `within`.
+    ///
+    /// Returns `None` if no message arrived in time.
+    pub async fn receive(&self, within: impl IntoTimeout) -> Option<T> {
+        match self.mailbox.recv_timeout(within.into_timeout()).await {
+            Ok(msg) => Some(msg),
+            Err(RecvError::Timeout) => None,
+            // The probe holds a reference to itself, so its mailbox stays open
+            Err(err) => panic!("probe mailbox failed: {err}"),
+        }
+    }
+
+    /// Expect the next message to match `predicate`, returning it.
+    ///
+    /// The [`expect_msg!`](crate::expect_msg!) macro wraps this to match
+    /// against a pattern.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no message arrives within `within` or the message does not
+    /// match.
+    #[track_caller]
+    pub fn expect_msg_where(
+        &self,
+        within: impl IntoTimeout,
+        predicate: impl FnOnce(&T) -> bool,
+    ) -> impl Future<Output = T>
+    where
+        T: Debug,
+    {
+        let within = within.into_timeout();
+        let caller = std::panic::Location::caller();
+
+        async move {
+            let msg = self.expect_any(within, caller).await;
+            assert!(
+                predicate(&msg),
+                "{caller}: probe received unexpected message {msg:?}"
+            );
+            msg
+        }
+    }
+
+    /// Expect the next message to equal `expected`, returning it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no message arrives within `within` or the message differs.
+    #[track_caller]
+    pub fn expect_msg(&self, expected: T, within: impl IntoTimeout) -> impl Future<Output = T>
+    where
+        T: Debug + PartialEq,
+    {
+        let within = within.into_timeout();
+        let caller = std::panic::Location::caller();
+
+        async move {
+            let msg = self.expect_any(within, caller).await;
+            assert_eq!(msg, expected, "{caller}: probe received unexpected message");
+            msg
+        }
+    }
+
+    /// Expect the next messages to equal `expected`, in order.
+    ///
+    /// `within` applies to each message separately.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a message does not arrive in time or differs from the
+    /// expected one.
+    #[track_caller]
+    pub fn expect_ordered<I>(
+        &self,
+        expected: I,
+        within: impl IntoTimeout,
+    ) -> impl Future<Output = ()>
+    where
+        I: IntoIterator<Item = T>,
+        T: Debug + PartialEq,
+    {
+        let expected = expected.into_iter();
+        let within = within.into_timeout();
+        let caller = std::panic::Location::caller();
+
+        async move {
+            for (index, expected) in expected.enumerate() {
+                let msg = self.expect_any(within, caller).await;
+                assert_eq!(
+                    msg, expected,
+                    "{caller}: probe received unexpected message at position {index}"
+                );
+            }
+        }
+    }
+
+    /// Expect no message to arrive within `within`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a message arrives.
+    #[track_caller]
+    pub fn expect_no_msg(&self, within: impl IntoTimeout) -> impl Future<Output = ()>
+    where
+        T: Debug,
+    {
+        let within = within.into_timeout();
+        let caller = std::panic::Location::caller();
+
+        async move {
+            if let Some(msg) = self.receive(within).await {
+                panic!("{caller}: probe received unexpected message {msg:?}");
+            }
+        }
+    }
+
+    async fn expect_any(&self, within: Duration, caller: &std::panic::Location<'_>) -> T {
+        match self.receive(within).await {
+            Some(msg) => msg,
+            None => panic!("{caller}: probe received no message within {within:?}"),
+        }
+    }
+}
@@ -0,0 +1,319 @@
+//! Deterministic delivery order across tasks.
+//!
+//! Tasks spawned while a [`Scheduler`] is [entered](Scheduler::enter) do not
+//! receive messages on their own. A message sent to such a task waits in its
+//! mailbox until the scheduler delivers it with [`step`](Scheduler::step),
+//! which picks one of the tasks with a waiting message using a seeded random
+//! number generator. The same seed always yields the same interleaving, so a
+//! bug that depends on the order messages arrive in across tasks can be
+//! reproduced and asserted, and looping over seeds explores other orders.
+//!
+//! Messages to a single task are still delivered in the order they were
+//! sent, and [system signals](crate::core::SystemSignal) are never held back.
+//! Only tasks spawned through `#[derive(Task)]` are scheduled.
+//!
+//! The scheduler requires a single-threaded runtime, which is what
+//! `#[tokio::test]` uses by default.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::prelude::*;
+//! use notizia::testing::Scheduler;
+//!
+//! #[derive(Task)]
+//! #[task(message = &'static str)]
+//! struct Printer;
+//!
+//! impl Runnable<&'static str> for Printer {
+//!     async fn start(&self) {
+//!         while let Ok(msg) = recv!(self) {
+//!             println!("{msg}");
+//!         }
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let scheduler = Scheduler::new(42);
+//! let _guard = scheduler.enter();
+//!
+//! let a = spawn!(Printer);
+//! let b = spawn!(Printer);
+//! a.send("a").unwrap();
+//! b.send("b").unwrap();
+//!
+//! // Delivers both messages, in an order decided by the seed
+//! let order = scheduler.run().await;
+//! assert_eq!(order.len(), 2);
+//! # }
+//! ```
+
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex};
+
+use tokio::runtime::{Handle, RuntimeFlavor};
+use tokio::sync::Notify;
+
+use crate::task::TaskId;
+
+/// Maximum number of times [`Scheduler::settle`] yields to other tasks.
+const SETTLE_ROUNDS: usize = 64;
+
+thread_local! {
+    static CURRENT: RefCell<Option<Arc<Shared>>> = const { RefCell::new(None) };
+}
+
+/// State shared between a scheduler and the gates of its tasks.
+#[derive(Default)]
+struct Shared {
+    slots: Mutex<Slots>,
+}
+
+#[derive(Default)]
+struct Slots {
+    tasks: BTreeMap<TaskId, Slot>,
+    /// Incremented whenever a task changes its state
+    events: u64,
+}
+
+struct Slot {
+    /// The task holds a message and waits for the scheduler to deliver it
+    waiting: bool,
+    /// The scheduler delivered the message, the task has yet to take it
+    permitted: bool,
+    /// The task is running and has not asked for its next message yet
+    busy: bool,
+    notify: Arc<Notify>,
+}
+
+impl Shared {
+    fn events(&self) -> u64 {
+        self.slots.lock().unwrap().events
+    }
+}
+
+/// The connection of a scheduled task's mailbox to its scheduler.
+pub(crate) struct Gate {
+    id: TaskId,
+    shared: Arc<Shared>,
+}
+
+impl Gate {
+    /// Schedule the task `id` with the scheduler entered on this thread, if any.
+    pub(crate) fn current(id: TaskId) -> Option<Arc<Gate>> {
+        let shared = CURRENT.with(|current| current.borrow().clone())?;
+        shared.slots.lock().unwrap().tasks.insert(
+            id,
+            // A new task runs until it asks for its first message
+            Slot {
+                waiting: false,
+                permitted: false,
+                busy: true,
+                notify: Arc::default(),
+            },
+        );
+
+        Some(Arc::new(Gate { id, shared }))
+    }
+
+    /// Wait until the scheduler delivers the message the task holds.
+    ///
+    /// Cancel-safe: a delivery is kept until the next call.
+    pub(crate) async fn acquire(&self) {
+        loop {
+            let notify = match self.update(|slot| slot.permitted) {
+                Some(notify) => notify,
+                None => return,
+            };
+            notify.notified().await;
+        }
+    }
+
+    /// Take the delivery of the message the task holds, if the scheduler
+    /// already made it.
+    pub(crate) fn try_acquire(&self) -> bool {
+        self.update(|slot| slot.permitted).is_none()
+    }
+
+    /// Record that the task asked for its next message.
+    pub(crate) fn idle(&self) {
+        let mut slots = self.shared.slots.lock().unwrap();
+        let Slots { tasks, events } = &mut *slots;
+        if let Some(slot) = tasks.get_mut(&self.id).filter(|slot| slot.busy) {
+            slot.busy = false;
+            *events += 1;
+        }
+    }
+
+    /// Take a delivery if `delivered`, otherwise mark the task as waiting
+    /// and return what to wait on.
+    fn update(&self, delivered: impl FnOnce(&Slot) -> bool) -> Option<Arc<Notify>> {
+        let mut slots = self.shared.slots.lock().unwrap();
+        let Slots { tasks, events } = &mut *slots;
+        let slot = tasks.get_mut(&self.id)?;
+
+        if delivered(slot) {
+            slot.permitted = false;
+            slot.busy = true;
+            *events += 1;
+            return None;
+        }
+
+        if !slot.waiting {
+            slot.waiting = true;
+            *events += 1;
+        }
+        Some(slot.notify.clone())
+    }
+}
+
+impl Drop for Gate {
+    fn drop(&mut self) {
+        if let Ok(mut slots) = self.shared.slots.lock() {
+            slots.tasks.remove(&self.id);
+            slots.events += 1;
+        }
+    }
+}
+
+/// Seeded scheduler deciding the order messages are delivered in.
+///
+/// See the [module documentation](self) for details.
+pub struct Scheduler {
+    seed: u64,
+    rng: Mutex<u64>,
+    shared: Arc<Shared>,
+}
+
+impl Scheduler {
+    /// Create a scheduler picking tasks based on `seed`.
+    pub fn new(seed: u64) -> Self {
+        Scheduler {
+            seed,
+            rng: Mutex::new(seed),
+            shared: Arc::default(),
+        }
+    }
+
+    /// The seed the scheduler was created with.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Schedule all tasks spawned on this thread until the guard is dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if called outside of a single-threaded Tokio runtime.
+    pub fn enter(&self) -> SchedulerGuard<'_> {
+        let flavor = Handle::try_current().map(|handle| handle.runtime_flavor());
+        assert!(
+            matches!(flavor, Ok(RuntimeFlavor::CurrentThread)),
+            "the deterministic scheduler requires a single-threaded Tokio runtime"
+        );
+
+        let previous = CURRENT.with(|current| current.replace(Some(self.shared.clone())));
+        SchedulerGuard {
+            previous,
+            _scheduler: PhantomData,
+        }
+    }
+
+    /// Tasks holding a message that waits to be delivered, ordered by id.
+    pub fn pending(&self) -> Vec<TaskId> {
+        let slots = self.shared.slots.lock().unwrap();
+        slots
+            .tasks
+            .iter()
+            .filter(|(_, slot)| slot.waiting && !slot.permitted)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    /// Deliver the next message to one of the [pending](Self::pending) tasks
+    /// and let the tasks run until they settle.
+    ///
+    /// Returns the task the message was delivered to, or `None` if no task
+    /// had a message waiting.
+    pub async fn step(&self) -> Option<TaskId> {
+        self.settle().await;
+
+        let id = {
+            let pending = self.pending();
+            if pending.is_empty() {
+                return None;
+            }
+            let id = pending[(self.next() % pending.len() as u64) as usize];
+
+            let mut slots = self.shared.slots.lock().unwrap();
+            let Slots { tasks, events } = &mut *slots;
+            let slot = tasks.get_mut(&id)?;
+            slot.waiting = false;
+            slot.permitted = true;
+            slot.notify.notify_one();
+            *events += 1;
+            id
+        };
+
+        self.settle().await;
+        Some(id)
+    }
+
+    /// [Step](Self::step) until no task has a message waiting.
+    ///
+    /// Returns the tasks messages were delivered to, in delivery order.
+    pub async fn run(&self) -> Vec<TaskId> {
+        let mut delivered = Vec::new();
+        while let Some(id) = self.step().await {
+            delivered.push(id);
+        }
+
+        delivered
+    }
+
+    /// Yield to the scheduled tasks until none of them is running.
+    ///
+    /// Tasks waiting on something other than their mailbox, e.g. a timer,
+    /// are given a bounded number of rounds.
+    async fn settle(&self) {
+        for _ in 0..SETTLE_ROUNDS {
+            let before = self.shared.events();
+            tokio::task::yield_now().await;
+
+            let slots = self.shared.slots.lock().unwrap();
+            let running = slots.tasks.values().any(|slot| slot.busy || slot.permitted);
+            if slots.events == before && !running {
+                break;
+            }
+        }
+    }
+
+    /// Advance the random number generator (SplitMix64).
+    fn next(&self) -> u64 {
+        let mut state = self.rng.lock().unwrap();
+        *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+
+        let mut z = *state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Keeps a [`Scheduler`] entered, see [`Scheduler::enter`].
+#[must_use = "tasks are only scheduled while the guard is alive"]
+pub struct SchedulerGuard<'a> {
+    previous: Option<Arc<Shared>>,
+    // Ties the guard to the thread and the scheduler it was entered on
+    _scheduler: PhantomData<(&'a Scheduler, *const ())>,
+}
+
+impl Drop for SchedulerGuard<'_> {
+    fn drop(&mut self) {
+        let previous = self.previous.take();
+        CURRENT.with(|current| *current.borrow_mut() = previous);
+    }
+}
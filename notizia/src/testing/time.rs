@@ -0,0 +1,87 @@
+//! Virtual time for tests.
+//!
+//! Timeouts in notizia, such as those of [`call!`](crate::call!) and idle
+//! passivation, run on Tokio's clock. Once the clock is [paused](pause), a
+//! test moves it forward with [`advance`] instead of sleeping, so a test of
+//! a five second timeout completes instantly and does not depend on how
+//! busy the machine is. While paused, Tokio also skips ahead to the next
+//! timer whenever all tasks are waiting, so sleeps complete immediately.
+//!
+//! Paused time requires a single-threaded runtime. Instead of calling
+//! [`pause`], a test may start paused with
+//! `#[tokio::test(start_paused = true)]`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use notizia::prelude::*;
+//! use notizia::testing;
+//!
+//! #[derive(Task)]
+//! #[task(message = ())]
+//! struct Idle;
+//!
+//! impl Runnable<()> for Idle {
+//!     async fn start(&self) {
+//!         while recv!(self).is_ok() {}
+//!     }
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! testing::pause();
+//!
+//! let handle = spawn!(Idle);
+//! handle.passivate_after(Duration::from_secs(60));
+//!
+//! // Passes a minute without waiting for it
+//! testing::advance(Duration::from_secs(60)).await;
+//! assert!(handle.is_finished());
+//! # }
+//! ```
+
+use std::time::Duration;
+
+/// Number of times [`advance`] yields to the tasks it woke up.
+const SETTLE_ROUNDS: usize = 16;
+
+/// Freeze the clock of the current runtime.
+///
+/// # Panics
+///
+/// Panics if the clock is already paused or the runtime is not
+/// single-threaded.
+pub fn pause() {
+    tokio::time::pause();
+}
+
+/// Resume the clock of the current runtime.
+///
+/// # Panics
+///
+/// Panics if the clock is not paused.
+pub fn resume() {
+    tokio::time::resume();
+}
+
+/// Move the paused clock forward by `duration` and let the tasks woken on
+/// the way run.
+///
+/// Timers fire in order: a task that sleeps again after being woken is
+/// woken a second time if its new deadline also lies within `duration`.
+///
+/// # Panics
+///
+/// Panics if the clock is not [paused](pause).
+pub async fn advance(duration: Duration) {
+    // Fails unless paused, so an unpaused clock is never slept on for real
+    tokio::time::advance(Duration::ZERO).await;
+
+    // Tokio skips ahead to each pending timer in turn while all tasks wait
+    tokio::time::sleep(duration).await;
+
+    for _ in 0..SETTLE_ROUNDS {
+        tokio::task::yield_now().await;
+    }
+}
@@ -0,0 +1,246 @@
+//! Virtual (grain-style) actors with on-demand activation.
+//!
+//! A virtual actor is addressed purely by its identity. Sending a message to
+//! an identity activates the actor if it isn't running yet, loading its state
+//! through [`VirtualActor::activate`]. Callers never spawn, look up, or
+//! restart virtual actors explicitly; they only ever talk to identities.
+//!
+//! Active actors are hosted in a [`ShardRegion`], so placement, eviction,
+//! and rebalancing work exactly as they do for sharded entities.
+//!
+//! # Lifecycle
+//!
+//! 1. The first message for an identity spawns a host task, which calls
+//!    [`activate`](VirtualActor::activate) to load the actor's state.
+//! 2. Messages queued in the meantime are handled in order by
+//!    [`handle`](VirtualActor::handle).
+//! 3. When the actor is deactivated (explicitly, by eviction, after being
+//!    idle, or because the region shuts down), [`deactivate`](VirtualActor::deactivate) is called
+//!    so the actor can persist its state.
+//! 4. The next message for the identity activates it again.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use notizia::virtual_actors::{VirtualActor, VirtualActors};
+//!
+//! struct Account {
+//!     id: u64,
+//!     balance: i64,
+//! }
+//!
+//! enum AccountMsg {
+//!     Deposit(i64),
+//! }
+//!
+//! impl VirtualActor for Account {
+//!     type Id = u64;
+//!     type Message = AccountMsg;
+//!
+//!     async fn activate(id: u64) -> Self {
+//!         // Load the balance from storage...
+//!         Account { id, balance: 0 }
+//!     }
+//!
+//!     async fn handle(&mut self, msg: AccountMsg) {
+//!         match msg {
+//!             AccountMsg::Deposit(amount) => self.balance += amount,
+//!         }
+//!     }
+//!
+//!     async fn deactivate(&mut self) {
+//!         // Persist the balance...
+//!         println!("account {} has {}", self.id, self.balance);
+//!     }
+//! }
+//!
+//! # #[tokio::main]
+//! # async fn main() {
+//! let accounts = VirtualActors::<Account>::new();
+//!
+//! // Activates account 42 on demand
+//! accounts.send(42, AccountMsg::Deposit(100)).unwrap();
+//! # }
+//! ```
+
+use std::future::Future;
+use std::hash::Hash;
+use std::panic::AssertUnwindSafe;
+use std::time::Duration;
+
+use futures::FutureExt;
+use tokio::sync::mpsc::unbounded_channel;
+
+use crate::core::errors::SendResult;
+use crate::core::lifecycle::panic_message;
+use crate::core::mailbox::Mailbox;
+use crate::runtime;
+use crate::sharding::{Placement, ShardRegion};
+use crate::task::{TaskHandle, TaskId, TaskRef};
+use crate::{ShutdownResult, TerminateReason};
+
+/// An actor that is activated on demand by its identity.
+///
+/// See the [module documentation](self) for the activation lifecycle.
+pub trait VirtualActor: Send + Sized + 'static {
+    /// Identity of an actor instance.
+    type Id: Hash + Eq + Clone + Send + Sync + 'static;
+
+    /// Message type handled by the actor.
+    type Message: Send + 'static;
+
+    /// Load the actor's state when it is activated.
+    fn activate(id: Self::Id) -> impl Future<Output = Self> + Send;
+
+    /// Handle a single message.
+    fn handle(&mut self, msg: Self::Message) -> impl Future<Output = ()> + Send;
+
+    /// Hook called before the actor is deactivated.
+    ///
+    /// Use this to persist state. The default implementation does nothing.
+    fn deactivate(&mut self) -> impl Future<Output = ()> + Send {
+        async {}
+    }
+}
+
+/// A space of virtual actors, addressed by identity.
+pub struct VirtualActors<A>
+where
+    A: VirtualActor,
+{
+    region: ShardRegion<A::Id, A::Message>,
+}
+
+impl<A> Default for VirtualActors<A>
+where
+    A: VirtualActor,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A> VirtualActors<A>
+where
+    A: VirtualActor,
+{
+    /// Create an empty space of virtual actors.
+    pub fn new() -> Self {
+        VirtualActors {
+            region: ShardRegion::with_spawner(|id: &A::Id| activate::<A>(id.clone())),
+        }
+    }
+
+    /// Set the strategy deciding which shard an actor is placed on.
+    pub fn placement<P>(mut self, placement: P) -> Self
+    where
+        P: Placement<A::Id>,
+    {
+        self.region = self.region.placement(placement);
+        self
+    }
+
+    /// Set the number of shards active actors are distributed over.
+    pub fn with_shards(mut self, shards: usize) -> Self {
+        self.region = self.region.with_shards(shards);
+        self
+    }
+
+    /// Limit the number of concurrently active actors.
+    ///
+    /// Once the limit is exceeded, the least recently used actor is
+    /// deactivated.
+    pub fn max_active(mut self, max: usize) -> Self {
+        self.region = self.region.max_entities(max);
+        self
+    }
+
+    /// Set the timeout for deactivating evicted actors.
+    pub fn deactivation_timeout(mut self, timeout: Duration) -> Self {
+        self.region = self.region.eviction_timeout(timeout);
+        self
+    }
+
+    /// Deactivate actors that received nothing for `idle`.
+    ///
+    /// The next message for an identity activates it again.
+    pub fn passivate_after(mut self, idle: Duration) -> Self {
+        self.region = self.region.passivate_after(idle);
+        self
+    }
+
+    /// Send a message to the actor with the given identity.
+    ///
+    /// The actor is activated if it isn't running.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`SendError`](crate::core::errors::SendError) if the actor
+    /// terminated before it could receive the message.
+    pub fn send(&self, id: A::Id, msg: A::Message) -> SendResult<A::Message> {
+        self.region.send(id, msg)
+    }
+
+    /// Get a reference to the actor with the given identity.
+    ///
+    /// The actor is activated if it isn't running. Note that the actor can
+    /// only be deactivated gracefully once all references are dropped.
+    pub fn get(&self, id: A::Id) -> TaskRef<A::Message> {
+        self.region.entity(id)
+    }
+
+    /// Check whether the actor with the given identity is active.
+    pub fn is_active(&self, id: &A::Id) -> bool {
+        self.region.contains_key(id)
+    }
+
+    /// Number of currently active actors.
+    pub fn active_count(&self) -> usize {
+        self.region.prune();
+        self.region.len()
+    }
+
+    /// Gracefully deactivate the actor with the given identity.
+    ///
+    /// Returns `None` if the actor is not active.
+    pub async fn deactivate(&self, id: &A::Id, timeout: Duration) -> Option<ShutdownResult> {
+        self.region.evict(id, timeout).await
+    }
+
+    /// Deactivate all actors, returning each actor's result.
+    pub async fn shutdown(self, timeout: Duration) -> Vec<(A::Id, ShutdownResult)> {
+        self.region.shutdown(timeout).await
+    }
+}
+
+/// Spawn the host task for a single activation of a virtual actor.
+fn activate<A>(id: A::Id) -> TaskHandle<A::Message>
+where
+    A: VirtualActor,
+{
+    let (sender, receiver) = unbounded_channel();
+    let mailbox = Mailbox::from_receiver(receiver);
+    let mb = mailbox.clone();
+
+    let name = std::any::type_name::<A>();
+    let handle = runtime::spawn(name, async move {
+        let run = AssertUnwindSafe(async {
+            let mut actor = A::activate(id).await;
+
+            while let Ok(msg) = mb.recv().await {
+                actor.handle(msg).await;
+            }
+
+            actor.deactivate().await;
+        });
+
+        match run.catch_unwind().await {
+            Ok(()) if mb.is_passivated() => TerminateReason::Idle,
+            Ok(()) => TerminateReason::Normal,
+            Err(payload) => TerminateReason::Panic(panic_message(&*payload)),
+        }
+    });
+
+    let task = TaskRef::with_identity(sender, TaskId::next(), name);
+    TaskHandle::new(task, &mailbox, handle)
+}
@@ -0,0 +1,93 @@
+//! Integration tests for the audit log of handler tasks.
+
+use std::sync::{Arc, Mutex};
+
+use notizia::prelude::*;
+use notizia::task::{AuditRecord, Outcome};
+use notizia::{call, message};
+
+#[message]
+#[derive(Debug)]
+enum Command {
+    Deposit(u64),
+    #[request(reply = u64)]
+    Balance,
+    Crash,
+    Close,
+}
+
+#[derive(Task)]
+#[task(message = Command, handler)]
+struct Account {
+    balance: u64,
+}
+
+impl Handler<Command> for Account {
+    async fn handle(&mut self, msg: Command, ctx: &mut Context<Command>) {
+        match msg {
+            Command::Deposit(amount) => self.balance += amount,
+            Command::Balance { reply_to } => {
+                let _ = reply_to.reply(self.balance);
+            }
+            Command::Crash => panic!("account crashed"),
+            Command::Close => ctx.stop(),
+        }
+    }
+}
+
+fn spawn_audited() -> (TaskHandle<Command>, Arc<Mutex<Vec<AuditRecord>>>) {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let sink = records.clone();
+    let handle = Account { balance: 0 }
+        .builder()
+        .audit(move |record: &AuditRecord| sink.lock().unwrap().push(record.clone()))
+        .spawn();
+
+    (handle, records)
+}
+
+fn outcomes(records: &Mutex<Vec<AuditRecord>>) -> Vec<Outcome> {
+    records
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|record| record.outcome)
+        .collect()
+}
+
+#[tokio::test]
+async fn every_handled_message_is_recorded() {
+    let (handle, records) = spawn_audited();
+
+    handle.send(Command::Deposit(10)).unwrap();
+    assert_eq!(call!(handle, Command::Balance).await.unwrap(), 10);
+    handle.send(Command::Close).unwrap();
+    let id = handle.id();
+    handle.join().await.unwrap();
+
+    assert_eq!(
+        outcomes(&records),
+        vec![Outcome::Handled, Outcome::Handled, Outcome::Stopped]
+    );
+
+    let records = records.lock().unwrap();
+    assert!(records.iter().all(|record| record.task == id));
+    assert!(records.iter().all(|record| record.name == "Account"));
+    assert!(records[0].message.ends_with("Command"));
+    assert!(records[0].correlation.is_none());
+    assert!(records[1].correlation.is_some());
+    assert!(records[0].at <= records[1].at);
+}
+
+#[tokio::test]
+async fn panicking_handlers_are_recorded() {
+    let (handle, records) = spawn_audited();
+
+    handle.send(Command::Crash).unwrap();
+    assert!(matches!(
+        handle.join().await.unwrap(),
+        TerminateReason::Panic(_)
+    ));
+
+    assert_eq!(outcomes(&records), vec![Outcome::Panicked]);
+}
@@ -0,0 +1,201 @@
+//! Integration tests for serving HTTP from tasks with axum.
+
+#![cfg(feature = "axum")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use axum::Router;
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::response::IntoResponse;
+use axum::routing::{get, post};
+use notizia::core::errors::CallError;
+use notizia::prelude::*;
+use notizia::{call, message};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::oneshot;
+
+#[message]
+enum CounterMsg {
+    Add,
+    #[request(reply = u64)]
+    Get,
+    #[request(reply = u64)]
+    Stall,
+    Crash,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter;
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        let mut count = 0;
+        let mut stalled = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::Add => count += 1,
+                CounterMsg::Get { reply_to } => {
+                    let _ = reply_to.reply(count);
+                }
+                CounterMsg::Stall { reply_to } => stalled.push(reply_to),
+                CounterMsg::Crash => return,
+            }
+        }
+    }
+}
+
+async fn add(State(counter): State<TaskRef<CounterMsg>>) -> StatusCode {
+    match counter.send(CounterMsg::Add) {
+        Ok(()) => StatusCode::ACCEPTED,
+        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
+    }
+}
+
+async fn count(State(counter): State<TaskRef<CounterMsg>>) -> Result<String, CallError> {
+    let count = call!(counter, CounterMsg::Get).await?;
+    Ok(count.to_string())
+}
+
+async fn stall(State(counter): State<TaskRef<CounterMsg>>) -> Result<String, CallError> {
+    let count = call!(counter, CounterMsg::Stall, timeout = 50).await?;
+    Ok(count.to_string())
+}
+
+async fn crash(State(counter): State<TaskRef<CounterMsg>>) -> StatusCode {
+    let _ = counter.send(CounterMsg::Crash);
+    StatusCode::ACCEPTED
+}
+
+fn app(counter: TaskRef<CounterMsg>) -> Router {
+    Router::new()
+        .route("/add", post(add))
+        .route("/count", get(count))
+        .route("/stall", get(stall))
+        .route("/crash", post(crash))
+        .with_state(counter)
+}
+
+/// Send a bodiless request and return the status code and body.
+async fn request(addr: SocketAddr, method: &str, path: &str) -> (u16, String) {
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let request = format!(
+        "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await.unwrap();
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+
+    let status = response[9..12].parse().unwrap();
+    let body = response
+        .split("\r\n\r\n")
+        .nth(1)
+        .unwrap_or_default()
+        .to_string();
+    (status, body)
+}
+
+async fn listener() -> (TcpListener, SocketAddr) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    (listener, addr)
+}
+
+#[test]
+fn call_errors_map_to_http_statuses() {
+    let timeout = CallError::Timeout {
+        task: TaskId::next(),
+        name: "counter",
+        correlation: CorrelationId::next(),
+        elapsed: Duration::from_secs(5),
+    };
+    assert_eq!(timeout.status_code(), StatusCode::GATEWAY_TIMEOUT);
+    assert_eq!(
+        CallError::CircuitOpen.into_response().status(),
+        StatusCode::SERVICE_UNAVAILABLE
+    );
+    assert_eq!(
+        CallError::NoReply.into_response().status(),
+        StatusCode::INTERNAL_SERVER_ERROR
+    );
+}
+
+#[tokio::test]
+async fn handlers_call_tasks_from_state() {
+    let counter = spawn!(Counter);
+    let (listener, addr) = listener().await;
+    let app = app(counter.this());
+    tokio::spawn(async move { axum::serve(listener, app).await });
+
+    assert_eq!(request(addr, "POST", "/add").await.0, 202);
+    assert_eq!(request(addr, "POST", "/add").await.0, 202);
+    assert_eq!(request(addr, "GET", "/count").await, (200, "2".to_string()));
+
+    let (status, body) = request(addr, "GET", "/stall").await;
+    assert_eq!(status, 504);
+    assert!(body.contains("timed out"), "{body}");
+
+    assert_eq!(request(addr, "POST", "/crash").await.0, 202);
+    counter.join().await.unwrap();
+    assert_eq!(request(addr, "GET", "/count").await.0, 503);
+}
+
+#[tokio::test]
+async fn serve_shuts_down_tasks_after_the_server() {
+    let counter = spawn!(Counter);
+    let (listener, addr) = listener().await;
+    let app = app(counter.this());
+    let mut tasks = TaskSet::new();
+    let id = tasks.insert(counter);
+
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(notizia::axum::serve(
+        listener,
+        app,
+        tasks,
+        async {
+            let _ = stopped.await;
+        },
+        Duration::from_secs(1),
+    ));
+
+    assert_eq!(request(addr, "POST", "/add").await.0, 202);
+    stop.send(()).unwrap();
+
+    let results = server.await.unwrap().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, id);
+    assert!(results[0].1.is_ok());
+    assert!(TcpStream::connect(addr).await.is_err());
+}
+
+#[tokio::test]
+async fn serve_stops_when_a_task_terminates() {
+    let counter = spawn!(Counter);
+    let (listener, addr) = listener().await;
+    let app = app(counter.this());
+    let mut tasks = TaskSet::new();
+    tasks.insert(counter);
+
+    let server = tokio::spawn(notizia::axum::serve(
+        listener,
+        app,
+        tasks,
+        std::future::pending(),
+        Duration::from_secs(1),
+    ));
+
+    assert_eq!(request(addr, "POST", "/crash").await.0, 202);
+
+    let results = tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    assert_eq!(results.len(), 1);
+}
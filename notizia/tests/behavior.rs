@@ -0,0 +1,125 @@
+//! Integration tests for switching behaviors with `become`/`unbecome`.
+
+use notizia::call;
+use notizia::message;
+use notizia::prelude::*;
+use notizia::task::Behavior;
+
+#[message]
+#[derive(Debug)]
+enum Phase {
+    Hello,
+    Data(u32),
+    Pause,
+    Bye,
+    #[request(reply = (String, u32, usize))]
+    Status,
+}
+
+#[derive(Task)]
+#[task(message = Phase, handler)]
+struct Session {
+    total: u32,
+}
+
+impl Handler<Phase> for Session {
+    async fn handle(&mut self, msg: Phase, ctx: &mut Context<Phase>) {
+        match msg {
+            Phase::Hello => ctx.r#become(Streaming),
+            Phase::Status { reply_to } => {
+                let _ = reply_to.reply(("handshaking".into(), self.total, ctx.behaviors()));
+            }
+            _ => {}
+        }
+    }
+}
+
+struct Streaming;
+
+impl Behavior<Session, Phase> for Streaming {
+    async fn handle(&mut self, session: &mut Session, msg: Phase, ctx: &mut Context<Phase>) {
+        match msg {
+            Phase::Data(n) => session.total += n,
+            Phase::Pause => ctx.r#become(Paused { dropped: 0 }),
+            Phase::Bye => ctx.unbecome(),
+            Phase::Status { reply_to } => {
+                let _ = reply_to.reply(("streaming".into(), session.total, ctx.behaviors()));
+            }
+            Phase::Hello => {}
+        }
+    }
+}
+
+/// Drops data until resumed with `Hello`, keeping its own state.
+struct Paused {
+    dropped: u32,
+}
+
+impl Behavior<Session, Phase> for Paused {
+    async fn handle(&mut self, session: &mut Session, msg: Phase, ctx: &mut Context<Phase>) {
+        match msg {
+            Phase::Data(_) => self.dropped += 1,
+            Phase::Hello => ctx.unbecome(),
+            Phase::Status { reply_to } => {
+                let state = format!("paused after dropping {}", self.dropped);
+                let _ = reply_to.reply((state, session.total, ctx.behaviors()));
+            }
+            _ => {}
+        }
+    }
+}
+
+#[tokio::test]
+async fn behaviors_are_pushed_and_popped() {
+    let handle = Session { total: 0 }.run();
+
+    // Data is ignored while handshaking
+    handle.send(Phase::Data(100)).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("handshaking".into(), 0, 0));
+
+    handle.send(Phase::Hello).unwrap();
+    handle.send(Phase::Data(1)).unwrap();
+    handle.send(Phase::Data(2)).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("streaming".into(), 3, 1));
+
+    handle.send(Phase::Pause).unwrap();
+    handle.send(Phase::Data(5)).unwrap();
+    handle.send(Phase::Data(5)).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("paused after dropping 2".into(), 3, 2));
+
+    handle.send(Phase::Hello).unwrap();
+    handle.send(Phase::Data(4)).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("streaming".into(), 7, 1));
+
+    handle.send(Phase::Bye).unwrap();
+    handle.send(Phase::Data(100)).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("handshaking".into(), 7, 0));
+}
+
+#[tokio::test]
+async fn behavior_state_survives_between_messages() {
+    let handle = Session { total: 0 }.run();
+
+    handle.send(Phase::Hello).unwrap();
+    handle.send(Phase::Pause).unwrap();
+    for _ in 0..3 {
+        handle.send(Phase::Data(1)).unwrap();
+    }
+
+    let (state, _, _) = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(state, "paused after dropping 3");
+}
+
+#[tokio::test]
+async fn unbecome_without_behavior_does_nothing() {
+    let handle = Session { total: 0 }.run();
+
+    handle.send(Phase::Bye).unwrap();
+    let status = call!(handle, Phase::Status).await.unwrap();
+    assert_eq!(status, ("handshaking".into(), 0, 0));
+}
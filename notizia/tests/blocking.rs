@@ -0,0 +1,108 @@
+//! Integration tests for calling and sending to tasks from synchronous code.
+
+use notizia::message;
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::Duration;
+
+#[message]
+#[derive(Debug)]
+enum StoreMsg {
+    Add(u32),
+    #[request(reply = u32)]
+    Total,
+    #[request(reply = ())]
+    Ignore,
+}
+
+#[derive(Task)]
+#[task(message = StoreMsg)]
+struct Store {
+    total: Arc<AtomicU32>,
+}
+
+impl Runnable<StoreMsg> for Store {
+    async fn start(&self) {
+        let mut ignored = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                StoreMsg::Add(n) => {
+                    self.total.fetch_add(n, Ordering::SeqCst);
+                }
+                StoreMsg::Total { reply_to } => {
+                    let _ = reply_to.reply(self.total.load(Ordering::SeqCst));
+                }
+                StoreMsg::Ignore { reply_to } => ignored.push(reply_to),
+            }
+        }
+    }
+}
+
+fn store() -> TaskHandle<StoreMsg> {
+    Store {
+        total: Arc::new(AtomicU32::new(0)),
+    }
+    .run()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_call_from_spawn_blocking() {
+    let handle = Arc::new(store());
+
+    let total = tokio::task::spawn_blocking({
+        let handle = handle.clone();
+        move || {
+            handle.blocking_send(StoreMsg::Add(3)).unwrap();
+            handle.blocking_send(StoreMsg::Add(4)).unwrap();
+            handle.blocking_call(|reply_to| StoreMsg::Total { reply_to }, 1000)
+        }
+    })
+    .await
+    .unwrap();
+
+    assert_eq!(total.unwrap(), 7);
+}
+
+#[tokio::test]
+async fn blocking_call_from_plain_thread_on_current_thread_runtime() {
+    let handle = Arc::new(store());
+
+    let thread = std::thread::spawn({
+        let handle = handle.clone();
+        move || {
+            handle.blocking_send(StoreMsg::Add(5)).unwrap();
+            handle.blocking_call(|reply_to| StoreMsg::Total { reply_to }, 1000)
+        }
+    });
+
+    // Keep the runtime running while the thread blocks on it
+    while !thread.is_finished() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    assert_eq!(thread.join().unwrap().unwrap(), 5);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_call_times_out() {
+    let handle = Arc::new(store());
+
+    let result = tokio::task::spawn_blocking({
+        let handle = handle.clone();
+        move || handle.blocking_call(|reply_to| StoreMsg::Ignore { reply_to }, 10)
+    })
+    .await
+    .unwrap();
+
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+}
+
+#[tokio::test]
+#[should_panic]
+async fn blocking_call_from_async_code_panics() {
+    let handle = store();
+
+    let _ = handle.blocking_call(|reply_to| StoreMsg::Total { reply_to }, 1000);
+}
@@ -0,0 +1,88 @@
+//! Integration tests for tasks running on the blocking pool.
+
+use notizia::call;
+use notizia::message;
+use notizia::prelude::*;
+use std::time::{Duration, Instant};
+
+#[message]
+#[derive(Debug)]
+enum CruncherMsg {
+    #[request(reply = u64)]
+    Crunch { millis: u64 },
+}
+
+/// Answer crunch requests with busy work that never yields to the runtime.
+async fn crunch(task: &impl Task<CruncherMsg>) {
+    while let Ok(CruncherMsg::Crunch { millis, reply_to }) = recv!(task) {
+        let started = Instant::now();
+        let mut spins = 0;
+        while started.elapsed() < Duration::from_millis(millis) {
+            spins += 1;
+        }
+        let _ = reply_to.reply(spins);
+    }
+}
+
+#[derive(Task)]
+#[task(message = CruncherMsg, blocking)]
+struct Cruncher;
+
+impl Runnable<CruncherMsg> for Cruncher {
+    async fn start(&self) {
+        crunch(self).await;
+    }
+}
+
+#[derive(Task)]
+#[task(message = CruncherMsg)]
+struct Regular;
+
+impl Runnable<CruncherMsg> for Regular {
+    async fn start(&self) {
+        crunch(self).await;
+    }
+}
+
+/// Crunch for 200ms while measuring how long a 10ms sleep takes.
+async fn tick_while_crunching(task: &TaskHandle<CruncherMsg>) -> Duration {
+    let crunch = call!(task, |tx| CruncherMsg::Crunch {
+        millis: 200,
+        reply_to: tx
+    });
+    let ticker = async {
+        let started = Instant::now();
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        started.elapsed()
+    };
+
+    let (spins, ticked_after) = tokio::join!(crunch, ticker);
+    assert!(spins.unwrap() > 0);
+
+    ticked_after
+}
+
+#[tokio::test]
+async fn blocking_task_does_not_starve_runtime() {
+    let cruncher = spawn!(Cruncher);
+
+    assert!(tick_while_crunching(&cruncher).await < Duration::from_millis(150));
+}
+
+#[tokio::test]
+async fn builder_moves_regular_task_to_blocking_pool() {
+    let regular = Regular.run();
+    let blocking = Regular.builder().blocking().spawn();
+
+    assert!(tick_while_crunching(&blocking).await < Duration::from_millis(150));
+    assert!(tick_while_crunching(&regular).await >= Duration::from_millis(200));
+}
+
+#[tokio::test]
+async fn blocking_task_shuts_down_gracefully() {
+    let cruncher = spawn!(Cruncher);
+
+    let reason = cruncher.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(reason, TerminateReason::Normal);
+}
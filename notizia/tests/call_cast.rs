@@ -113,7 +113,7 @@ async fn call_returns_timeout_error_when_deadline_exceeded() {
 
     assert!(result.is_err(), "Call should timeout");
     match result {
-        Err(CallError::Timeout) => {
+        Err(CallError::Timeout { .. }) => {
             // Expected
         }
         Err(e) => panic!("Expected Timeout error, got: {:?}", e),
@@ -317,7 +317,7 @@ async fn oneshot_channels_cleaned_up_properly() {
         Err(CallError::ChannelClosed) => {
             // Expected - the sender was dropped without sending
         }
-        Err(CallError::Timeout) => {
+        Err(CallError::Timeout { .. }) => {
             // This is also acceptable in this test, as the task might not
             // process the message quickly enough before the drop
         }
@@ -405,3 +405,70 @@ async fn call_returns_send_error_when_task_killed() {
     // expected behavior rather than testing it
     // In practice, sending to a killed task returns SendError
 }
+
+#[tokio::test]
+async fn send_error_returns_request_and_target() {
+    let counter = Counter {
+        count: Arc::new(AtomicU32::new(0)),
+    };
+    let handle = spawn!(counter);
+    let task = handle.this();
+    let id = handle.id();
+
+    handle.kill();
+    sleep(Duration::from_millis(50)).await;
+
+    let err = call!(task, |tx| CounterMsg::GetCount { reply_to: tx })
+        .await
+        .unwrap_err();
+
+    match &err {
+        CallError::SendError { task, name, .. } => {
+            assert_eq!(*task, id);
+            assert_eq!(*name, "Counter");
+        }
+        other => panic!("Expected SendError, got: {:?}", other),
+    }
+    assert!(matches!(
+        err.into_message::<CounterMsg>(),
+        Some(CounterMsg::GetCount { .. })
+    ));
+}
+
+#[tokio::test]
+async fn timeout_error_reports_target_and_elapsed_time() {
+    let responder = SlowResponder;
+    let handle = spawn!(responder);
+
+    let err = call!(
+        handle,
+        |tx| SlowMsg::SlowRequest { reply_to: tx },
+        timeout = 50
+    )
+    .await
+    .unwrap_err();
+
+    match err {
+        CallError::Timeout {
+            task,
+            name,
+            elapsed,
+            ..
+        } => {
+            assert_eq!(task, handle.id());
+            assert_eq!(name, "SlowResponder");
+            assert!(elapsed >= Duration::from_millis(50));
+        }
+        other => panic!("Expected Timeout, got: {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn handle_and_references_share_identity() {
+    let first = spawn!(SlowResponder);
+    let second = spawn!(SlowResponder);
+
+    assert_eq!(first.id(), first.this().id());
+    assert_eq!(first.this().name(), "SlowResponder");
+    assert_ne!(first.id(), second.id());
+}
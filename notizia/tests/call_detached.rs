@@ -0,0 +1,99 @@
+//! Integration tests for `call_detached!`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::{call_detached, message};
+use tokio::time::sleep;
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = u32)]
+    Square { n: u32 },
+    #[request(reply = u32)]
+    Slow,
+}
+
+/// Answers requests, noting whether slow ones were canceled.
+#[derive(Task)]
+#[task(message = Msg)]
+struct Service {
+    canceled: Arc<AtomicBool>,
+}
+
+impl Runnable<Msg> for Service {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                Msg::Square { n, reply_to } => {
+                    let _ = reply_to.reply(n * n);
+                }
+                Msg::Slow { reply_to } => {
+                    sleep(Duration::from_millis(50)).await;
+                    self.canceled
+                        .store(reply_to.is_canceled(), Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
+
+fn service() -> (TaskHandle<Msg>, Arc<AtomicBool>) {
+    let canceled = Arc::new(AtomicBool::new(false));
+    let handle = Service {
+        canceled: canceled.clone(),
+    }
+    .run();
+    (handle, canceled)
+}
+
+#[tokio::test]
+async fn detached_calls_are_sent_before_being_awaited() {
+    let (handle, _) = service();
+
+    let first = call_detached!(handle, |reply_to| Msg::Square { n: 3, reply_to });
+    let second = call_detached!(handle, |reply_to| Msg::Square { n: 4, reply_to });
+    handle.signal(SystemSignal::Stop).unwrap();
+
+    // Both requests were queued ahead of the stop signal
+    assert_eq!(second.await.unwrap(), 16);
+    assert_eq!(first.await.unwrap(), 9);
+}
+
+#[tokio::test]
+async fn detached_calls_time_out_from_when_they_were_sent() {
+    let (handle, _) = service();
+
+    let slow = call_detached!(handle, Msg::Slow, timeout = 20);
+    sleep(Duration::from_millis(30)).await;
+    let started = tokio::time::Instant::now();
+    assert!(matches!(slow.await, Err(CallError::Timeout { .. })));
+    assert!(started.elapsed() < Duration::from_millis(10));
+}
+
+#[tokio::test]
+async fn canceled_detached_calls_are_seen_by_the_task() {
+    let (handle, canceled) = service();
+
+    let slow = call_detached!(handle, Msg::Slow);
+    sleep(Duration::from_millis(10)).await;
+    slow.cancel();
+    sleep(Duration::from_millis(60)).await;
+    assert!(canceled.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn detached_calls_to_stopped_tasks_fail_when_awaited() {
+    let (handle, _) = service();
+    let task = handle.this();
+    handle.signal(SystemSignal::Stop).unwrap();
+    handle.join().await.unwrap();
+
+    let call = call_detached!(task, |reply_to| Msg::Square { n: 2, reply_to });
+    let err = call.await.unwrap_err();
+    assert!(matches!(err, CallError::SendError { .. }));
+}
@@ -0,0 +1,119 @@
+//! Integration tests for `call_hedged!`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::{call_hedged, message};
+use tokio::time::{Instant, sleep};
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = &'static str)]
+    Get,
+}
+
+/// Answers with its name after a delay, noting how it was called.
+#[derive(Task)]
+#[task(message = Msg)]
+struct Replica {
+    name: &'static str,
+    delay: Duration,
+    served: Arc<AtomicUsize>,
+    canceled: Arc<AtomicBool>,
+}
+
+impl Runnable<Msg> for Replica {
+    async fn start(&self) {
+        while let Ok(Msg::Get { reply_to }) = recv!(self) {
+            self.served.fetch_add(1, Ordering::SeqCst);
+            sleep(self.delay).await;
+            self.canceled
+                .store(reply_to.is_canceled(), Ordering::SeqCst);
+            let _ = reply_to.reply(self.name);
+        }
+    }
+}
+
+struct Replicas {
+    handles: Vec<TaskHandle<Msg>>,
+    served: Vec<Arc<AtomicUsize>>,
+    canceled: Vec<Arc<AtomicBool>>,
+}
+
+fn replicas(delays: &[(&'static str, u64)]) -> Replicas {
+    let mut replicas = Replicas {
+        handles: Vec::new(),
+        served: Vec::new(),
+        canceled: Vec::new(),
+    };
+    for &(name, delay) in delays {
+        let served = Arc::new(AtomicUsize::new(0));
+        let canceled = Arc::new(AtomicBool::new(false));
+        let replica = Replica {
+            name,
+            delay: Duration::from_millis(delay),
+            served: served.clone(),
+            canceled: canceled.clone(),
+        };
+        replicas.handles.push(replica.run());
+        replicas.served.push(served);
+        replicas.canceled.push(canceled);
+    }
+    replicas
+}
+
+#[tokio::test]
+async fn fast_replicas_are_not_hedged() {
+    let replicas = replicas(&[("first", 0), ("second", 0)]);
+
+    let answer = call_hedged!(&replicas.handles, Msg::Get, hedge_after = 50).await;
+    assert_eq!(answer.unwrap(), "first");
+    sleep(Duration::from_millis(60)).await;
+    assert_eq!(replicas.served[1].load(Ordering::SeqCst), 0);
+}
+
+#[tokio::test]
+async fn slow_replicas_are_hedged_and_canceled() {
+    let replicas = replicas(&[("slow", 100), ("fast", 0)]);
+
+    let started = Instant::now();
+    let answer = call_hedged!(
+        &replicas.handles,
+        |reply_to| Msg::Get { reply_to },
+        hedge_after = Duration::from_millis(20)
+    )
+    .await;
+    assert_eq!(answer.unwrap(), "fast");
+    assert!(started.elapsed() < Duration::from_millis(80));
+
+    // The slow replica finds its request abandoned
+    sleep(Duration::from_millis(100)).await;
+    assert!(replicas.canceled[0].load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn failed_replicas_are_hedged_right_away() {
+    let replicas = replicas(&[("stopped", 0), ("second", 0)]);
+    replicas.handles[0].signal(SystemSignal::Stop).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    let started = Instant::now();
+    let answer = call_hedged!(&replicas.handles, Msg::Get, hedge_after = 1000).await;
+    assert_eq!(answer.unwrap(), "second");
+    assert!(started.elapsed() < Duration::from_millis(500));
+}
+
+#[tokio::test]
+async fn hedged_calls_report_the_last_error() {
+    let replicas = replicas(&[("slow", 100), ("slower", 200)]);
+    let answer = call_hedged!(&replicas.handles, Msg::Get, hedge_after = 10, timeout = 50).await;
+    assert!(matches!(answer, Err(CallError::Timeout { .. })));
+
+    let none: Vec<TaskRef<Msg>> = Vec::new();
+    let answer = call_hedged!(&none, Msg::Get, hedge_after = 10).await;
+    assert!(matches!(answer, Err(CallError::NoReplicas)));
+}
@@ -33,7 +33,7 @@ impl Runnable<CounterMsg> for Counter {
             match recv!(self) {
                 Ok(CounterMsg::GetCount { reply_to }) => {
                     let count = self.count.load(Ordering::SeqCst);
-                    let _ = reply_to.send(count);
+                    let _ = reply_to.reply(count);
                 }
                 Ok(CounterMsg::Increment) => {
                     self.count.fetch_add(1, Ordering::SeqCst);
@@ -117,17 +117,17 @@ impl Runnable<MultiMsg> for MultiCounter {
         loop {
             match recv!(self) {
                 Ok(MultiMsg::GetCount { reply_to }) => {
-                    let _ = reply_to.send(self.count.load(Ordering::SeqCst));
+                    let _ = reply_to.reply(self.count.load(Ordering::SeqCst));
                 }
                 Ok(MultiMsg::GetStats { reply_to }) => {
                     let stats = Stats {
                         count: self.count.load(Ordering::SeqCst),
                         operations: self.ops.load(Ordering::SeqCst),
                     };
-                    let _ = reply_to.send(stats);
+                    let _ = reply_to.reply(stats);
                 }
                 Ok(MultiMsg::GetStatus { reply_to }) => {
-                    let _ = reply_to.send("Running".to_string());
+                    let _ = reply_to.reply("Running".to_string());
                 }
                 Ok(MultiMsg::Increment) => {
                     self.count.fetch_add(1, Ordering::SeqCst);
@@ -184,7 +184,7 @@ impl Runnable<EchoMsg> for EchoServer {
         loop {
             match recv!(self) {
                 Ok(EchoMsg::Echo { id, reply_to }) => {
-                    let _ = reply_to.send(id * 2);
+                    let _ = reply_to.reply(id * 2);
                 }
                 Ok(EchoMsg::Stop) => break,
                 Err(_) => break,
@@ -269,3 +269,101 @@ async fn can_mix_both_syntaxes() {
     // Both should give same result
     assert_eq!(count, count2);
 }
+
+// =============================================================================
+// Test 6: Timeouts as Duration and absolute deadlines
+// =============================================================================
+
+#[tokio::test]
+async fn timeout_accepts_duration() {
+    let counter = Counter {
+        count: Arc::new(AtomicU32::new(4)),
+    };
+    let handle = spawn!(counter);
+
+    let timeout = tokio::time::Duration::from_secs(1);
+    let count = call!(handle, CounterMsg::GetCount, timeout = timeout)
+        .await
+        .unwrap();
+    let count2 = call!(
+        handle,
+        |tx| CounterMsg::GetCount { reply_to: tx },
+        timeout = tokio::time::Duration::from_millis(500)
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(count, 4);
+    assert_eq!(count2, 4);
+}
+
+#[tokio::test]
+async fn deadline_is_shared_between_calls() {
+    let counter = Counter {
+        count: Arc::new(AtomicU32::new(6)),
+    };
+    let handle = spawn!(counter);
+
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(1);
+    let count = call!(handle, CounterMsg::GetCount, deadline = deadline)
+        .await
+        .unwrap();
+    let count2 = call!(
+        handle,
+        |tx| CounterMsg::GetCount { reply_to: tx },
+        deadline = std::time::Instant::now() + std::time::Duration::from_secs(1)
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(count, 6);
+    assert_eq!(count2, 6);
+}
+
+#[tokio::test]
+async fn expired_deadline_times_out() {
+    // Never answers, but keeps the reply channel open
+    let (sender, mut receiver) =
+        tokio::sync::mpsc::unbounded_channel::<notizia::core::Envelope<CounterMsg>>();
+    let task = notizia::TaskRef::new(sender);
+    let keep = tokio::spawn(async move {
+        let mut pending = Vec::new();
+        while let Some(msg) = receiver.recv().await {
+            pending.push(msg);
+        }
+    });
+
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_millis(20);
+    let result = call!(task, CounterMsg::GetCount, deadline = deadline).await;
+
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+    keep.abort();
+}
+
+mod without_imports {
+    // `call!` is not imported here, so the macro must not rely on it
+    use super::{Counter, CounterMsg};
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test]
+    async fn call_works_when_only_reachable_by_path() {
+        let counter = Counter {
+            count: Arc::new(AtomicU32::new(3)),
+        };
+        let handle = notizia::spawn!(counter);
+
+        let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(1);
+        let counts = [
+            notizia::call!(handle, CounterMsg::GetCount).await.unwrap(),
+            notizia::call!(handle, CounterMsg::GetCount, deadline = deadline)
+                .await
+                .unwrap(),
+            notizia::call!(handle, |tx| CounterMsg::GetCount { reply_to: tx })
+                .await
+                .unwrap(),
+        ];
+
+        assert_eq!(counts, [3, 3, 3]);
+    }
+}
@@ -0,0 +1,137 @@
+//! Integration tests for streaming replies with `call_stream!`.
+
+use notizia::futures::StreamExt;
+use notizia::prelude::*;
+use notizia::{call_stream, message};
+use tokio::time::{Duration, sleep};
+
+#[message(client = StoreClient)]
+#[derive(Debug)]
+enum StoreMsg {
+    #[request(stream = Vec<u32>)]
+    Pages { size: usize },
+    #[request(stream = u32)]
+    Broken,
+    #[request(stream = u32)]
+    Forgotten,
+    #[request(stream = u32)]
+    Endless,
+}
+
+#[derive(Task)]
+#[task(message = StoreMsg)]
+struct Store {
+    items: Vec<u32>,
+}
+
+impl Runnable<StoreMsg> for Store {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                StoreMsg::Pages { size, reply_to } => {
+                    for page in self.items.chunks(size) {
+                        if reply_to.send(page.to_vec()).await.is_err() {
+                            break;
+                        }
+                    }
+                    reply_to.finish().await;
+                }
+                StoreMsg::Broken { reply_to } => {
+                    let _ = reply_to.send(1).await;
+                    reply_to.fail("disk on fire").await;
+                }
+                StoreMsg::Forgotten { reply_to } => drop(reply_to),
+                StoreMsg::Endless { reply_to } => {
+                    let mut i = 0;
+                    while reply_to.send(i).await.is_ok() {
+                        i += 1;
+                    }
+                    assert!(reply_to.is_closed());
+                }
+            }
+        }
+    }
+}
+
+fn store() -> TaskHandle<StoreMsg> {
+    let store = Store {
+        items: (1..=7).collect(),
+    };
+    spawn!(store)
+}
+
+#[tokio::test]
+async fn stream_yields_pages_until_finished() {
+    let handle = store();
+
+    let mut pages = call_stream!(handle, |tx| StoreMsg::Pages {
+        size: 3,
+        reply_to: tx
+    })
+    .unwrap();
+
+    let received: Vec<_> = pages.by_ref().collect().await;
+
+    assert_eq!(received, vec![vec![1, 2, 3], vec![4, 5, 6], vec![7]]);
+    assert_eq!(pages.end(), Some(&StreamEnd::Finished));
+}
+
+#[tokio::test]
+async fn failed_stream_reports_reason() {
+    let handle = store();
+
+    let mut stream = call_stream!(handle, StoreMsg::Broken, buffer = 1).unwrap();
+
+    assert_eq!(stream.next().await, Some(1));
+    assert_eq!(stream.next().await, None);
+    assert_eq!(
+        stream.end(),
+        Some(&StreamEnd::Failed("disk on fire".to_string()))
+    );
+}
+
+#[tokio::test]
+async fn dropped_stream_reply_ends_stream() {
+    let handle = store();
+
+    let mut stream = call_stream!(handle, StoreMsg::Forgotten).unwrap();
+
+    assert_eq!(stream.next().await, None);
+    assert_eq!(stream.end(), Some(&StreamEnd::Dropped));
+}
+
+#[tokio::test]
+async fn dropping_the_stream_stops_the_producer() {
+    let handle = store();
+
+    let stream = call_stream!(handle, StoreMsg::Endless, buffer = 2).unwrap();
+    let first: Vec<_> = stream.take(5).collect().await;
+    assert_eq!(first, vec![0, 1, 2, 3, 4]);
+
+    // The task notices the closed stream and serves the next request
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(handle.broken().unwrap().next().await, Some(1));
+}
+
+#[tokio::test]
+async fn stream_to_stopped_task_fails() {
+    let handle = store();
+    let task = handle.this();
+    handle.kill();
+    sleep(Duration::from_millis(20)).await;
+
+    assert!(matches!(
+        call_stream!(task, StoreMsg::Forgotten),
+        Err(CallError::SendError { .. })
+    ));
+}
+
+#[tokio::test]
+async fn client_streams_replies() {
+    let handle = store();
+    let client = StoreClient::new(handle.this());
+
+    let pages: Vec<_> = client.pages(4).unwrap().collect().await;
+
+    assert_eq!(pages, vec![vec![1, 2, 3, 4], vec![5, 6, 7]]);
+}
@@ -0,0 +1,75 @@
+//! Integration tests for casting a message to many tasks with `cast_all!`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use notizia::cast_all;
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Signal {
+    Bump(usize),
+}
+
+#[derive(Task)]
+#[task(message = Signal)]
+struct Counter {
+    total: Arc<AtomicUsize>,
+}
+
+impl Runnable<Signal> for Counter {
+    async fn start(&self) {
+        while let Ok(Signal::Bump(n)) = recv!(self) {
+            self.total.fetch_add(n, Ordering::SeqCst);
+        }
+    }
+}
+
+#[tokio::test]
+async fn every_target_receives_the_message() {
+    let total = Arc::new(AtomicUsize::new(0));
+    let handles: Vec<_> = (0..3)
+        .map(|_| {
+            Counter {
+                total: total.clone(),
+            }
+            .run()
+        })
+        .collect();
+
+    let results = cast_all!(&handles, Signal::Bump(2));
+
+    let ids: Vec<_> = handles.iter().map(|handle| handle.id()).collect();
+    assert_eq!(results.iter().map(|(id, _)| *id).collect::<Vec<_>>(), ids);
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+    for handle in handles {
+        handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    }
+    assert_eq!(total.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+async fn failed_sends_are_reported_per_target() {
+    let total = Arc::new(AtomicUsize::new(0));
+    let refs: Vec<_> = (0..2)
+        .map(|_| {
+            Counter {
+                total: total.clone(),
+            }
+            .run()
+            .this()
+        })
+        .collect();
+    refs[0].signal(SystemSignal::Stop).unwrap();
+    while !refs[0].is_closed() {
+        tokio::task::yield_now().await;
+    }
+
+    let results = cast_all!(refs.clone(), Signal::Bump(1));
+
+    assert_eq!(results[0].1.as_ref().unwrap_err().0, Signal::Bump(1));
+    assert!(results[1].1.is_ok());
+}
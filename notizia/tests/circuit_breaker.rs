@@ -0,0 +1,148 @@
+//! Integration tests for the circuit breaker.
+
+use notizia::prelude::*;
+use notizia::task::{CircuitBreaker, CircuitState};
+use notizia::{call, message};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = u32)]
+    Get,
+}
+
+/// Answers requests only while healthy.
+#[derive(Task)]
+#[task(message = Msg)]
+struct Flaky {
+    healthy: Arc<AtomicBool>,
+}
+
+impl Runnable<Msg> for Flaky {
+    async fn start(&self) {
+        while let Ok(Msg::Get { reply_to }) = recv!(self) {
+            if self.healthy.load(Ordering::SeqCst) {
+                let _ = reply_to.reply(42);
+            }
+        }
+    }
+}
+
+fn breaker(healthy: &Arc<AtomicBool>) -> (TaskHandle<Msg>, CircuitBreaker<Msg>) {
+    let task = Flaky {
+        healthy: healthy.clone(),
+    };
+    let handle = spawn!(task);
+    let breaker = CircuitBreaker::new(handle.this())
+        .failure_threshold(2)
+        .cooldown(Duration::from_millis(50));
+    (handle, breaker)
+}
+
+async fn get(breaker: &CircuitBreaker<Msg>) -> CallResult<u32> {
+    breaker
+        .call(|task| async move { call!(task, Msg::Get, timeout = 20).await })
+        .await
+}
+
+#[tokio::test]
+async fn successful_calls_keep_breaker_closed() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let (_handle, breaker) = breaker(&healthy);
+
+    for _ in 0..3 {
+        assert_eq!(get(&breaker).await.unwrap(), 42);
+    }
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn breaker_opens_after_threshold_and_fails_fast() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (_handle, breaker) = breaker(&healthy);
+
+    assert!(get(&breaker).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(get(&breaker).await.is_err());
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    let result = breaker
+        .call(|_| async { panic!("call must not run while open") as CallResult<u32> })
+        .await;
+    assert!(matches!(result, Err(CallError::CircuitOpen)));
+}
+
+#[tokio::test]
+async fn successful_trial_closes_breaker() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (_handle, breaker) = breaker(&healthy);
+    let mut events = breaker.subscribe();
+
+    let _ = get(&breaker).await;
+    let _ = get(&breaker).await;
+
+    healthy.store(true, Ordering::SeqCst);
+    sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(get(&breaker).await.unwrap(), 42);
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    assert_eq!(events.recv().await.unwrap(), CircuitState::Open);
+    assert_eq!(events.recv().await.unwrap(), CircuitState::HalfOpen);
+    assert_eq!(events.recv().await.unwrap(), CircuitState::Closed);
+}
+
+#[tokio::test]
+async fn failed_trial_reopens_breaker() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (_handle, breaker) = breaker(&healthy);
+
+    let _ = get(&breaker).await;
+    let _ = get(&breaker).await;
+    sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(matches!(get(&breaker).await, Err(CallError::NoReply)));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(matches!(get(&breaker).await, Err(CallError::CircuitOpen)));
+}
+
+#[tokio::test]
+async fn calls_in_flight_when_the_breaker_opens_do_not_close_it() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (_handle, breaker) = breaker(&healthy);
+    let (release, released) = oneshot::channel();
+
+    let slow = breaker.call(|_| async move {
+        let _ = released.await;
+        Ok(0)
+    });
+    let open = async {
+        let _ = get(&breaker).await;
+        let _ = get(&breaker).await;
+        assert_eq!(breaker.state(), CircuitState::Open);
+        release.send(()).unwrap();
+    };
+
+    let (result, ()) = tokio::join!(slow, open);
+    assert_eq!(result.unwrap(), 0);
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(matches!(get(&breaker).await, Err(CallError::CircuitOpen)));
+}
+
+#[tokio::test]
+async fn reset_closes_open_breaker() {
+    let healthy = Arc::new(AtomicBool::new(false));
+    let (_handle, breaker) = breaker(&healthy);
+
+    let _ = get(&breaker).await;
+    let _ = get(&breaker).await;
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    breaker.reset();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
@@ -0,0 +1,121 @@
+//! Tests shared by the runtime backends.
+
+use std::time::Duration;
+
+use notizia::core::errors::RecvError;
+use notizia::message;
+use notizia::prelude::*;
+
+#[message]
+pub enum CounterMsg {
+    Add,
+    #[request(reply = u64)]
+    Get,
+    #[request(reply = u64)]
+    Stall,
+    Crash,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+pub struct Counter;
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        let mut count = 0;
+        let mut stalled = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::Add => count += 1,
+                CounterMsg::Get { reply_to } => {
+                    let _ = reply_to.reply(count);
+                }
+                CounterMsg::Stall { reply_to } => stalled.push(reply_to),
+                CounterMsg::Crash => panic!("crashed"),
+            }
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+pub struct Idle;
+
+impl Runnable<CounterMsg> for Idle {
+    async fn start(&self) {
+        let result = self.mailbox().recv_timeout(Duration::from_millis(20)).await;
+        assert!(matches!(result, Err(RecvError::Timeout)));
+    }
+}
+
+/// Generate the runtime tests, driving them with `$block_on`.
+macro_rules! runtime_tests {
+    ($block_on:path) => {
+        use std::time::Duration;
+
+        use notizia::call;
+        use notizia::core::errors::CallError;
+        use notizia::prelude::*;
+
+        use common::{Counter, CounterMsg, Idle};
+
+        #[test]
+        fn spawn_and_call() {
+            $block_on(async {
+                let counter = spawn!(Counter);
+                counter.send(CounterMsg::Add).unwrap();
+                counter.send(CounterMsg::Add).unwrap();
+                assert_eq!(call!(counter, CounterMsg::Get).await.unwrap(), 2);
+
+                let reason = counter.shutdown(Duration::from_secs(1)).await.unwrap();
+                assert!(matches!(reason, TerminateReason::Normal));
+            });
+        }
+
+        #[test]
+        fn timers_fire() {
+            $block_on(async {
+                let counter = spawn!(Counter);
+                let result = call!(counter, CounterMsg::Stall, timeout = 20).await;
+                assert!(matches!(result, Err(CallError::Timeout { .. })));
+
+                let idle = spawn!(Idle);
+                assert!(matches!(
+                    idle.join().await.unwrap(),
+                    TerminateReason::Normal
+                ));
+            });
+        }
+
+        #[test]
+        fn panics_are_caught() {
+            $block_on(async {
+                let counter = spawn!(Counter);
+                counter.send(CounterMsg::Crash).unwrap();
+                assert!(matches!(
+                    counter.join().await.unwrap(),
+                    TerminateReason::Panic(_)
+                ));
+            });
+        }
+
+        #[test]
+        fn aborted_tasks_are_cancelled() {
+            $block_on(async {
+                let counter = spawn!(Counter);
+                counter.abort_handle().abort();
+                assert!(counter.join().await.unwrap_err().is_cancelled());
+            });
+        }
+
+        #[test]
+        fn blocking_call_outside_of_any_runtime() {
+            let counter = $block_on(async { spawn!(Counter) });
+            counter.blocking_send(CounterMsg::Add).unwrap();
+
+            let count = counter.blocking_call(|reply_to| CounterMsg::Get { reply_to }, 1000);
+            assert_eq!(count.unwrap(), 1);
+        }
+    };
+}
@@ -0,0 +1,110 @@
+//! Integration tests for `ConfigTask` and `Context::watch`.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::call;
+use notizia::config::{ConfigMsg, ConfigTask};
+use notizia::prelude::*;
+use tokio::sync::watch;
+use tokio::time::sleep;
+
+#[derive(Clone, Debug, PartialEq)]
+struct Config {
+    level: u32,
+}
+
+enum Msg {
+    Watch(watch::Receiver<Config>),
+    ConfigChanged(Config),
+}
+
+/// Records the configurations it is told about.
+#[derive(Task)]
+#[task(message = Msg, handler)]
+struct Subscriber {
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Handler<Msg> for Subscriber {
+    async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+        match msg {
+            Msg::Watch(config) => {
+                ctx.watch(config, Msg::ConfigChanged);
+            }
+            Msg::ConfigChanged(config) => self.seen.lock().unwrap().push(config.level),
+        }
+    }
+}
+
+fn subscriber() -> (TaskHandle<Msg>, Arc<Mutex<Vec<u32>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Subscriber { seen: seen.clone() }.run();
+    (handle, seen)
+}
+
+#[tokio::test]
+async fn config_changes_reach_subscribers() {
+    let source = Arc::new(AtomicU32::new(1));
+    let loaded = source.clone();
+    let config = ConfigTask::new(move || {
+        Ok::<_, std::io::Error>(Config {
+            level: loaded.load(Ordering::SeqCst),
+        })
+    })
+    .unwrap()
+    .run();
+    let (subscriber, seen) = subscriber();
+
+    let receiver = call!(config, ConfigMsg::Subscribe).await.unwrap();
+    subscriber.send(Msg::Watch(receiver)).unwrap();
+
+    source.store(2, Ordering::SeqCst);
+    call!(config, ConfigMsg::Reload).await.unwrap().unwrap();
+    sleep(Duration::from_millis(10)).await;
+    // Unchanged reloads are not published
+    call!(config, ConfigMsg::Reload).await.unwrap().unwrap();
+    config.send(ConfigMsg::Set(Config { level: 3 })).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(*seen.lock().unwrap(), [2, 3]);
+    assert_eq!(call!(config, ConfigMsg::Get).await.unwrap().level, 3);
+}
+
+#[tokio::test]
+async fn failed_reloads_keep_the_config() {
+    let attempts = Arc::new(AtomicU32::new(0));
+    let counted = attempts.clone();
+    let config = ConfigTask::new(move || match counted.fetch_add(1, Ordering::SeqCst) {
+        0 => Ok(Config { level: 1 }),
+        _ => Err("config file is malformed"),
+    })
+    .unwrap()
+    .run();
+
+    let err = call!(config, ConfigMsg::Reload).await.unwrap().unwrap_err();
+    assert!(err.to_string().contains("config file is malformed"));
+    assert_eq!(call!(config, ConfigMsg::Get).await.unwrap().level, 1);
+
+    let err = ConfigTask::<Config>::new(|| Err("missing")).err().unwrap();
+    assert_eq!(err.to_string(), "failed to load configuration: missing");
+}
+
+#[tokio::test]
+async fn watched_changes_are_coalesced() {
+    let config = ConfigTask::with_config(Config { level: 0 });
+    let receiver = config.subscribe();
+    let config = config.run();
+    let (subscriber, seen) = subscriber();
+
+    // Set before the subscriber watches, so only the latest is delivered
+    for level in 1..=5 {
+        config.send(ConfigMsg::Set(Config { level })).unwrap();
+    }
+    sleep(Duration::from_millis(10)).await;
+    subscriber.send(Msg::Watch(receiver)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(*seen.lock().unwrap(), [5]);
+}
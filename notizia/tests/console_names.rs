@@ -0,0 +1,80 @@
+//! Integration tests for the `console` feature.
+//!
+//! Only built with `RUSTFLAGS="--cfg tokio_unstable"`, which Tokio requires
+//! for task names.
+
+#![cfg(all(tokio_unstable, feature = "console", feature = "tracing"))]
+
+use notizia::prelude::*;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Subscriber remembering the fields of Tokio's spawn spans
+#[derive(Default)]
+struct Spawns(Mutex<Vec<String>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!("{}={:?} ", field.name(), value));
+    }
+}
+
+impl Subscriber for Spawns {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut spawns = self.0.lock().unwrap();
+        if attrs.metadata().name() == "runtime.spawn" {
+            let mut fields = Fields(String::new());
+            attrs.record(&mut fields);
+            spawns.push(fields.0);
+        }
+        Id::from_u64(spawns.len() as u64 + 1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[derive(Task)]
+#[task(message = ())]
+struct Named;
+
+impl Runnable<()> for Named {
+    async fn start(&self) {}
+}
+
+#[tokio::test]
+async fn tasks_are_spawned_with_their_name() {
+    let spawns = Arc::new(Spawns::default());
+    let _guard = tracing::subscriber::set_default(spawns.clone());
+
+    spawn!(Named).join().await.unwrap();
+    Named.builder().name("custom").spawn().join().await.unwrap();
+
+    let spawns = spawns.0.lock().unwrap();
+    assert!(
+        spawns
+            .iter()
+            .any(|fields| fields.contains("task.name=Named "))
+    );
+    assert!(
+        spawns
+            .iter()
+            .any(|fields| fields.contains("task.name=custom "))
+    );
+}
@@ -0,0 +1,133 @@
+//! Integration tests for tasks with a control mailbox.
+
+use notizia::prelude::*;
+use notizia::task::Incoming;
+use notizia::{recv_any, recv_ctrl, recv_data};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, PartialEq)]
+enum DataMsg {
+    Chunk(u32),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CtrlMsg {
+    Pause,
+    Resume,
+}
+
+#[derive(Task)]
+#[task(message = DataMsg, control = CtrlMsg)]
+struct Ingest {
+    /// Held until the test has queued its messages
+    gate: Arc<Notify>,
+    log: Arc<Mutex<Vec<Incoming<DataMsg, CtrlMsg>>>>,
+}
+
+impl Runnable<DataMsg> for Ingest {
+    async fn start(&self) {
+        self.gate.notified().await;
+        while let Ok(incoming) = recv_any!(self) {
+            self.log.lock().unwrap().push(incoming);
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = DataMsg, control = CtrlMsg)]
+struct Split {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Runnable<DataMsg> for Split {
+    async fn start(&self) {
+        let ctrl = recv_ctrl!(self).unwrap();
+        self.log.lock().unwrap().push(format!("{ctrl:?}"));
+
+        while let Ok(data) = recv_data!(self) {
+            self.log.lock().unwrap().push(format!("{data:?}"));
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = DataMsg)]
+struct Plain;
+
+impl Runnable<DataMsg> for Plain {
+    async fn start(&self) {
+        while recv!(self).is_ok() {}
+    }
+}
+
+#[tokio::test]
+async fn control_messages_overtake_data_backlog() {
+    let gate = Arc::new(Notify::new());
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let task = Ingest {
+        gate: gate.clone(),
+        log: log.clone(),
+    };
+    let handle = spawn!(task);
+
+    for i in 0..100 {
+        handle.send(DataMsg::Chunk(i)).unwrap();
+    }
+    handle.send_control(CtrlMsg::Pause).unwrap();
+    handle.send_control(CtrlMsg::Resume).unwrap();
+    gate.notify_one();
+
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    let log = log.lock().unwrap();
+    assert_eq!(log.len(), 102);
+    assert_eq!(log[0], Incoming::Control(CtrlMsg::Pause));
+    assert_eq!(log[1], Incoming::Control(CtrlMsg::Resume));
+    assert_eq!(log[2], Incoming::Data(DataMsg::Chunk(0)));
+    assert_eq!(log[101], Incoming::Data(DataMsg::Chunk(99)));
+}
+
+#[tokio::test]
+async fn mailboxes_can_be_received_from_separately() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let task = Split { log: log.clone() };
+    let handle = spawn!(task);
+
+    handle.send(DataMsg::Chunk(1)).unwrap();
+    handle.this().send_control(CtrlMsg::Resume).unwrap();
+
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec!["Resume", "Chunk(1)"]);
+}
+
+#[tokio::test]
+async fn control_sends_fail_without_matching_mailbox() {
+    let plain = spawn!(Plain);
+    assert_eq!(
+        plain.send_control(CtrlMsg::Pause).unwrap_err().0,
+        CtrlMsg::Pause
+    );
+
+    let task = Split {
+        log: Arc::new(Mutex::new(Vec::new())),
+    };
+    let split = spawn!(task);
+    assert!(split.send_control("wrong type").is_err());
+}
+
+#[tokio::test]
+async fn control_sends_fail_after_termination() {
+    let task = Split {
+        log: Arc::new(Mutex::new(Vec::new())),
+    };
+    let handle = spawn!(task);
+    let task_ref = handle.this();
+
+    handle.kill();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    assert!(task_ref.send_control(CtrlMsg::Pause).is_err());
+}
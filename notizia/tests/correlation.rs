@@ -0,0 +1,123 @@
+//! Integration tests for correlation ids across request chains.
+
+use notizia::prelude::*;
+use notizia::tokio::sync::oneshot;
+use notizia::tokio::time::Duration;
+use notizia::{call, message};
+
+#[message]
+#[derive(Debug)]
+enum BackMsg {
+    #[request(reply = (Option<CorrelationId>, CorrelationId))]
+    Ids,
+    #[request(reply = ())]
+    Stall,
+}
+
+#[derive(Task)]
+#[task(message = BackMsg)]
+struct Back;
+
+impl Runnable<BackMsg> for Back {
+    async fn start(&self) {
+        let mut stalled = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                BackMsg::Ids { reply_to } => {
+                    let ids = (CorrelationId::current(), reply_to.correlation_id());
+                    let _ = reply_to.reply(ids);
+                }
+                BackMsg::Stall { reply_to } => stalled.push(reply_to),
+            }
+        }
+    }
+}
+
+#[message]
+#[derive(Debug)]
+enum FrontMsg {
+    #[request(reply = (CorrelationId, CorrelationId))]
+    Forward,
+    Ping(oneshot::Sender<Option<CorrelationId>>),
+    #[request(reply = Option<CorrelationId>)]
+    Current,
+}
+
+#[derive(Task)]
+#[task(message = FrontMsg)]
+struct Front {
+    back: TaskRef<BackMsg>,
+}
+
+impl Runnable<FrontMsg> for Front {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                FrontMsg::Forward { reply_to } => {
+                    let (_, downstream) = call!(self.back, BackMsg::Ids).await.unwrap();
+                    let upstream = reply_to.correlation_id();
+                    let _ = reply_to.reply((upstream, downstream));
+                }
+                FrontMsg::Ping(seen) => {
+                    let _ = seen.send(CorrelationId::current());
+                }
+                FrontMsg::Current { reply_to } => {
+                    let _ = reply_to.reply(CorrelationId::current());
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn handler_sees_correlation_id_of_request() {
+    let back = spawn!(Back);
+
+    let (current, reply) = call!(back, BackMsg::Ids).await.unwrap();
+
+    assert_eq!(current, Some(reply));
+}
+
+#[tokio::test]
+async fn nested_calls_reuse_correlation_id() {
+    let back = spawn!(Back);
+    let front = Front { back: back.this() }.run();
+
+    let (first, first_downstream) = call!(front, FrontMsg::Forward).await.unwrap();
+    let (second, second_downstream) = call!(front, FrontMsg::Forward).await.unwrap();
+
+    assert_eq!(first, first_downstream);
+    assert_eq!(second, second_downstream);
+    assert_ne!(first, second);
+}
+
+#[tokio::test]
+async fn casts_clear_current_correlation_id() {
+    let back = spawn!(Back);
+    let front = Front { back: back.this() }.run();
+
+    let current = call!(front, FrontMsg::Current).await.unwrap();
+    assert!(current.is_some());
+
+    let (seen, during_cast) = oneshot::channel();
+    front.send(FrontMsg::Ping(seen)).unwrap();
+    assert_eq!(during_cast.await.unwrap(), None);
+
+    // Outside of tasks there is no current id
+    assert_eq!(CorrelationId::current(), None);
+}
+
+#[tokio::test]
+async fn timeout_reports_correlation_id() {
+    let back = spawn!(Back);
+
+    let err = call!(back, BackMsg::Stall, timeout = Duration::from_millis(10))
+        .await
+        .unwrap_err();
+
+    let CallError::Timeout { correlation, .. } = err else {
+        panic!("expected timeout, got {err:?}");
+    };
+    assert!(err.to_string().contains(&format!("call {correlation} ")));
+}
@@ -0,0 +1,80 @@
+//! Integration tests for whole-task deadlines.
+
+use notizia::prelude::*;
+use notizia::spawn_with_deadline;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+struct Tick;
+
+#[derive(Task)]
+#[task(message = Tick)]
+struct Forever {
+    reason: Arc<Mutex<Option<TerminateReason>>>,
+}
+
+impl Runnable<Tick> for Forever {
+    async fn start(&self) {
+        std::future::pending::<()>().await;
+    }
+
+    async fn terminate(&self, reason: TerminateReason) {
+        *self.reason.lock().unwrap() = Some(reason);
+    }
+}
+
+#[derive(Task)]
+#[task(message = Tick)]
+struct Quick;
+
+impl Runnable<Tick> for Quick {
+    async fn start(&self) {}
+}
+
+#[tokio::test]
+async fn start_is_aborted_after_deadline() {
+    let reason = Arc::new(Mutex::new(None));
+    let task = Forever {
+        reason: reason.clone(),
+    };
+
+    let handle = task.builder().deadline(Duration::from_millis(20)).spawn();
+
+    assert_eq!(
+        handle.join().await.unwrap(),
+        TerminateReason::DeadlineExceeded
+    );
+    assert_eq!(
+        *reason.lock().unwrap(),
+        Some(TerminateReason::DeadlineExceeded)
+    );
+}
+
+#[tokio::test]
+async fn macro_spawns_with_deadline() {
+    let task = Forever {
+        reason: Arc::new(Mutex::new(None)),
+    };
+
+    let handle = spawn_with_deadline!(task, Duration::from_millis(20));
+
+    assert_eq!(
+        handle.join().await.unwrap(),
+        TerminateReason::DeadlineExceeded
+    );
+}
+
+#[tokio::test]
+async fn tasks_finishing_in_time_terminate_normally() {
+    let handle = spawn_with_deadline!(Quick, Duration::from_secs(5));
+
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Normal);
+}
+
+#[test]
+fn deadline_exceeded_is_displayed() {
+    assert_eq!(
+        TerminateReason::DeadlineExceeded.to_string(),
+        "deadline exceeded"
+    );
+}
@@ -0,0 +1,100 @@
+//! Integration tests for detecting calls a task makes to itself.
+
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::{call, call_stream, message};
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::{Duration, Instant};
+
+#[message]
+enum LoopMsg {
+    #[request(reply = u32)]
+    Value,
+    #[request(stream = u32)]
+    Values,
+    CallSelf(oneshot::Sender<CallResult<u32>>),
+    CallViaRegistry(Arc<Registry>, oneshot::Sender<CallResult<u32>>),
+    StreamSelf(oneshot::Sender<bool>),
+}
+
+#[derive(Task)]
+#[task(message = LoopMsg)]
+struct Looper;
+
+impl Runnable<LoopMsg> for Looper {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                LoopMsg::Value { reply_to } => {
+                    let _ = reply_to.reply(7);
+                }
+                LoopMsg::Values { reply_to } => reply_to.finish().await,
+                LoopMsg::CallSelf(result) => {
+                    let _ = result.send(call!(self.this(), LoopMsg::Value).await);
+                }
+                LoopMsg::CallViaRegistry(registry, result) => {
+                    let me = registry.whereis::<LoopMsg>("looper").unwrap();
+                    let _ = result.send(call!(me, LoopMsg::Value).await);
+                }
+                LoopMsg::StreamSelf(result) => {
+                    let stream = call_stream!(self.this(), LoopMsg::Values);
+                    let _ = result.send(matches!(stream, Err(CallError::WouldDeadlock { .. })));
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn call_to_self_fails_fast() {
+    let looper = spawn!(Looper);
+    let (result, receiver) = oneshot::channel();
+
+    let started = Instant::now();
+    looper.send(LoopMsg::CallSelf(result)).unwrap();
+    let err = receiver.await.unwrap().unwrap_err();
+
+    assert!(started.elapsed() < Duration::from_secs(1));
+    match err {
+        CallError::WouldDeadlock { task, name } => {
+            assert_eq!(task, looper.id());
+            assert_eq!(name, "Looper");
+        }
+        err => panic!("expected WouldDeadlock, got {err:?}"),
+    }
+}
+
+#[tokio::test]
+async fn call_to_self_through_registry_fails_fast() {
+    let registry = Arc::new(Registry::new());
+    let looper = spawn!(Looper);
+    registry.register("looper", &looper.this());
+
+    let (result, receiver) = oneshot::channel();
+    looper
+        .send(LoopMsg::CallViaRegistry(registry.clone(), result))
+        .unwrap();
+
+    assert!(matches!(
+        receiver.await.unwrap(),
+        Err(CallError::WouldDeadlock { .. })
+    ));
+}
+
+#[tokio::test]
+async fn stream_from_self_fails_fast() {
+    let looper = spawn!(Looper);
+    let (result, receiver) = oneshot::channel();
+
+    looper.send(LoopMsg::StreamSelf(result)).unwrap();
+
+    assert!(receiver.await.unwrap());
+}
+
+#[tokio::test]
+async fn calls_from_other_tasks_are_unaffected() {
+    let looper = spawn!(Looper);
+
+    assert_eq!(call!(looper, LoopMsg::Value).await.unwrap(), 7);
+}
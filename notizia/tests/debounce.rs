@@ -0,0 +1,110 @@
+//! Integration tests for debouncing and conflation.
+
+use notizia::core::Debounced;
+use notizia::prelude::*;
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, Clone, PartialEq)]
+enum StateMsg {
+    Set { key: &'static str, value: u32 },
+    Marker(u32),
+}
+
+#[derive(Task)]
+#[task(message = StateMsg)]
+struct Mirror {
+    seen: Arc<Mutex<Vec<StateMsg>>>,
+    window: Duration,
+}
+
+impl Runnable<StateMsg> for Mirror {
+    async fn start(&self) {
+        let mut updates = Debounced::new(self.mailbox(), |msg: &StateMsg| match msg {
+            StateMsg::Set { key, .. } => Some(*key),
+            StateMsg::Marker(_) => None,
+        })
+        .window(self.window);
+
+        while let Ok(msg) = updates.recv().await {
+            self.seen.lock().unwrap().push(msg);
+        }
+    }
+}
+
+fn set(key: &'static str, value: u32) -> StateMsg {
+    StateMsg::Set { key, value }
+}
+
+#[tokio::test]
+async fn burst_within_window_is_collapsed_to_latest() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let task = Mirror {
+        seen: seen.clone(),
+        window: Duration::from_millis(50),
+    };
+    let handle = spawn!(task);
+
+    for value in 0..5 {
+        handle.send(set("a", value)).unwrap();
+        sleep(Duration::from_millis(5)).await;
+    }
+    handle.send(set("b", 1)).unwrap();
+    handle.send(StateMsg::Marker(7)).unwrap();
+
+    sleep(Duration::from_millis(100)).await;
+    handle.send(set("a", 9)).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![set("a", 4), set("b", 1), StateMsg::Marker(7), set("a", 9)]
+    );
+}
+
+#[tokio::test]
+async fn messages_without_key_are_never_conflated() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let task = Mirror {
+        seen: seen.clone(),
+        window: Duration::from_millis(20),
+    };
+    let handle = spawn!(task);
+
+    for n in 0..3 {
+        handle.send(StateMsg::Marker(n)).unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            StateMsg::Marker(0),
+            StateMsg::Marker(1),
+            StateMsg::Marker(2)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn recv_timeout_keeps_mailbox_usable() {
+    let (sender, receiver) = notizia::tokio::sync::mpsc::unbounded_channel();
+    let mailbox = Mailbox::new();
+    mailbox.set_receiver(receiver).await;
+
+    let result = mailbox.recv_timeout(Duration::from_millis(10)).await;
+    assert!(matches!(result, Err(RecvError::Timeout)));
+
+    sender.send(notizia::core::Envelope::User(1u32)).unwrap();
+    assert_eq!(
+        mailbox
+            .recv_timeout(Duration::from_millis(10))
+            .await
+            .unwrap(),
+        1
+    );
+    assert!(matches!(mailbox.try_recv().await, Ok(None)));
+
+    drop(sender);
+    assert!(matches!(mailbox.try_recv().await, Err(RecvError::Closed)));
+}
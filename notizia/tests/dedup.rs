@@ -0,0 +1,122 @@
+//! Integration tests for deduplication by idempotency key.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::prelude::*;
+use notizia::task::{Dedup, Idempotent, Middleware};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Event {
+    Keyed(u32),
+    Unkeyed,
+}
+
+impl Idempotent for Event {
+    type Key = u32;
+
+    fn idempotency_key(&self) -> Option<u32> {
+        match self {
+            Event::Keyed(key) => Some(*key),
+            Event::Unkeyed => None,
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = Event)]
+struct Recorder {
+    seen: Arc<Mutex<Vec<Event>>>,
+}
+
+impl Runnable<Event> for Recorder {
+    async fn start(&self) {
+        while let Ok(event) = recv!(self) {
+            self.seen.lock().unwrap().push(event);
+        }
+    }
+}
+
+/// Drops every message carrying key 0.
+struct DropZero;
+
+impl Middleware<Event> for DropZero {
+    fn before_handle(&self, msg: &Event) -> bool {
+        *msg != Event::Keyed(0)
+    }
+}
+
+async fn wait_for(seen: &Arc<Mutex<Vec<Event>>>, len: usize) {
+    while seen.lock().unwrap().len() < len {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[tokio::test]
+async fn duplicate_keys_are_dropped() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Recorder { seen: seen.clone() }
+        .builder()
+        .middleware(Dedup::new(Duration::from_secs(60)))
+        .spawn();
+
+    for event in [
+        Event::Keyed(1),
+        Event::Keyed(1),
+        Event::Unkeyed,
+        Event::Keyed(2),
+        Event::Unkeyed,
+        Event::Keyed(1),
+    ] {
+        handle.send(event).unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            Event::Keyed(1),
+            Event::Unkeyed,
+            Event::Keyed(2),
+            Event::Unkeyed
+        ]
+    );
+}
+
+#[tokio::test]
+async fn keys_are_accepted_again_after_the_window() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Recorder { seen: seen.clone() }
+        .builder()
+        .middleware(Dedup::new(Duration::from_millis(50)))
+        .spawn();
+
+    handle.send(Event::Keyed(1)).unwrap();
+    wait_for(&seen, 1).await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    handle.send(Event::Keyed(1)).unwrap();
+    wait_for(&seen, 2).await;
+}
+
+#[tokio::test]
+async fn keys_dropped_by_later_middleware_are_not_processed() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Recorder { seen: seen.clone() }
+        .builder()
+        .middleware(Dedup::new(Duration::from_secs(60)))
+        .middleware(DropZero)
+        .spawn();
+
+    handle.send(Event::Keyed(0)).unwrap();
+    handle.send(Event::Unkeyed).unwrap();
+    handle.send(Event::Unkeyed).unwrap();
+    handle.send(Event::Keyed(1)).unwrap();
+    handle.send(Event::Keyed(1)).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![Event::Unkeyed, Event::Unkeyed, Event::Keyed(1)]
+    );
+}
@@ -0,0 +1,134 @@
+//! Integration tests for the deterministic test scheduler.
+
+#![cfg(feature = "testing")]
+
+use notizia::prelude::*;
+use notizia::testing::Scheduler;
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex};
+
+type Log = Arc<Mutex<Vec<(char, u32)>>>;
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Writer {
+    tag: char,
+    log: Log,
+}
+
+impl Runnable<u32> for Writer {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.log.lock().unwrap().push((self.tag, msg));
+        }
+    }
+}
+
+/// Deliver three messages to each of two tasks, returning the order they
+/// were handled in
+async fn interleave(seed: u64) -> Vec<(char, u32)> {
+    let log = Log::default();
+    let scheduler = Scheduler::new(seed);
+    let _guard = scheduler.enter();
+
+    let a = Writer {
+        tag: 'a',
+        log: log.clone(),
+    };
+    let a = spawn!(a);
+    let b = Writer {
+        tag: 'b',
+        log: log.clone(),
+    };
+    let b = spawn!(b);
+    for msg in 0..3 {
+        a.send(msg).unwrap();
+        b.send(msg).unwrap();
+    }
+
+    let delivered = scheduler.run().await;
+    let log = log.lock().unwrap().clone();
+    assert_eq!(delivered.len(), 6);
+
+    // The scheduler reports the tasks in the order they handled messages
+    let tags: Vec<_> = log.iter().map(|(tag, _)| *tag).collect();
+    let ids: Vec<_> = delivered
+        .iter()
+        .map(|id| if *id == a.id() { 'a' } else { 'b' })
+        .collect();
+    assert_eq!(tags, ids);
+    log
+}
+
+#[tokio::test]
+async fn messages_wait_for_the_scheduler() {
+    let log = Log::default();
+    let scheduler = Scheduler::new(0);
+    let _guard = scheduler.enter();
+
+    let writer = Writer {
+        tag: 'a',
+        log: log.clone(),
+    };
+    let handle = spawn!(writer);
+    handle.send(1).unwrap();
+    handle.send(2).unwrap();
+
+    for _ in 0..10 {
+        tokio::task::yield_now().await;
+    }
+    assert!(log.lock().unwrap().is_empty());
+    assert_eq!(scheduler.pending(), vec![handle.id()]);
+
+    assert_eq!(scheduler.step().await, Some(handle.id()));
+    assert_eq!(*log.lock().unwrap(), vec![('a', 1)]);
+
+    assert_eq!(scheduler.step().await, Some(handle.id()));
+    assert_eq!(scheduler.step().await, None);
+    assert_eq!(*log.lock().unwrap(), vec![('a', 1), ('a', 2)]);
+}
+
+#[tokio::test]
+async fn same_seed_yields_same_interleaving() {
+    for seed in 0..10 {
+        assert_eq!(interleave(seed).await, interleave(seed).await);
+    }
+}
+
+#[tokio::test]
+async fn seeds_explore_different_interleavings() {
+    let mut orders = HashSet::new();
+    for seed in 0..20 {
+        let order = interleave(seed).await;
+
+        // Messages to one task keep their order
+        for tag in ['a', 'b'] {
+            let msgs: Vec<_> = order.iter().filter(|(t, _)| *t == tag).collect();
+            assert!(msgs.iter().map(|(_, msg)| *msg).eq(0..3));
+        }
+        orders.insert(order);
+    }
+
+    assert!(orders.len() > 1);
+}
+
+#[tokio::test]
+async fn tasks_spawned_outside_the_scheduler_run_freely() {
+    let log = Log::default();
+    let scheduler = Scheduler::new(0);
+
+    let writer = Writer {
+        tag: 'a',
+        log: log.clone(),
+    };
+    let handle = spawn!(writer);
+    let _guard = scheduler.enter();
+    handle.send(1).unwrap();
+    handle
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec![('a', 1)]);
+    assert!(scheduler.pending().is_empty());
+}
@@ -0,0 +1,73 @@
+//! Integration tests for deriving `Task` on enums.
+
+use notizia::call;
+use notizia::message;
+use notizia::prelude::*;
+
+#[message]
+#[derive(Debug)]
+enum ConnectionMsg {
+    Open,
+    Close,
+    #[request(reply = &'static str)]
+    Status,
+}
+
+#[derive(Task)]
+#[task(message = ConnectionMsg)]
+enum Connection {
+    Connecting,
+    Ready,
+    Closed,
+}
+
+impl Connection {
+    fn status(&self) -> &'static str {
+        match self {
+            Connection::Connecting => "connecting",
+            Connection::Ready => "ready",
+            Connection::Closed => "closed",
+        }
+    }
+}
+
+impl Runnable<ConnectionMsg> for Connection {
+    async fn start(&self) {
+        let mut status = self.status();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                ConnectionMsg::Open => status = Connection::Ready.status(),
+                ConnectionMsg::Close => status = Connection::Closed.status(),
+                ConnectionMsg::Status { reply_to } => {
+                    let _ = reply_to.reply(status);
+                }
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn enum_task_handles_messages() {
+    let connection = Connection::Connecting;
+    let handle = spawn!(connection);
+
+    let status = call!(handle, ConnectionMsg::Status).await.unwrap();
+    assert_eq!(status, "connecting");
+
+    handle.send(ConnectionMsg::Open).unwrap();
+    assert_eq!(call!(handle, ConnectionMsg::Status).await.unwrap(), "ready");
+
+    handle.send(ConnectionMsg::Close).unwrap();
+    assert_eq!(
+        call!(handle, ConnectionMsg::Status).await.unwrap(),
+        "closed"
+    );
+}
+
+#[tokio::test]
+async fn enum_task_reports_its_name() {
+    let handle = Connection::Ready.run();
+
+    assert_eq!(handle.name(), "Connection");
+}
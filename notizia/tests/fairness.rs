@@ -0,0 +1,87 @@
+//! Integration tests for the cooperative fairness budget of tasks.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use notizia::prelude::*;
+
+const BACKLOG: usize = 100;
+
+#[derive(Debug, Clone)]
+struct Work;
+
+#[derive(Task)]
+#[task(message = Work)]
+struct Busy {
+    processed: Arc<AtomicUsize>,
+}
+
+impl Runnable<Work> for Busy {
+    async fn start(&self) {
+        while recv!(self).is_ok() {
+            self.processed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = Work, budget = 4)]
+struct Polite {
+    processed: Arc<AtomicUsize>,
+}
+
+impl Runnable<Work> for Polite {
+    async fn start(&self) {
+        while recv!(self).is_ok() {
+            self.processed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Feed `handle` a backlog, then report how many messages it had processed
+/// when a sibling task first got to run.
+async fn processed_when_sibling_ran(
+    handle: TaskHandle<Work>,
+    processed: Arc<AtomicUsize>,
+) -> usize {
+    for _ in 0..BACKLOG {
+        handle.send(Work).unwrap();
+    }
+
+    let sibling = tokio::spawn({
+        let processed = processed.clone();
+        async move { processed.load(Ordering::SeqCst) }
+    });
+    let seen = sibling.await.unwrap();
+
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(processed.load(Ordering::SeqCst), BACKLOG);
+    seen
+}
+
+#[tokio::test]
+async fn budgeted_tasks_let_siblings_run() {
+    let processed = Arc::new(AtomicUsize::new(0));
+    let handle = Busy {
+        processed: processed.clone(),
+    }
+    .builder()
+    .budget(8)
+    .spawn();
+
+    let seen = processed_when_sibling_ran(handle, processed).await;
+    assert!(seen <= 8, "sibling ran after {seen} messages");
+}
+
+#[tokio::test]
+async fn budget_can_be_set_in_the_attribute() {
+    let processed = Arc::new(AtomicUsize::new(0));
+    let handle = Polite {
+        processed: processed.clone(),
+    }
+    .run();
+
+    let seen = processed_when_sibling_ran(handle, processed).await;
+    assert!(seen <= 4, "sibling ran after {seen} messages");
+}
@@ -0,0 +1,91 @@
+//! Integration tests for backpressured sends with `feed!`.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use notizia::core::{Overflow, bounded};
+use notizia::feed;
+use notizia::prelude::*;
+use tokio::sync::Notify;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Item(u32);
+
+/// Receives nothing until `go` is notified, then collects all items.
+#[derive(Task)]
+#[task(message = Item)]
+struct Consumer {
+    go: Arc<Notify>,
+    received: tokio::sync::mpsc::UnboundedSender<u32>,
+}
+
+impl Runnable<Item> for Consumer {
+    async fn start(&self) {
+        self.go.notified().await;
+        while let Ok(Item(n)) = recv!(self) {
+            let _ = self.received.send(n);
+        }
+    }
+}
+
+fn spawn_consumer(
+    overflow: Overflow,
+) -> (
+    TaskHandle<Item>,
+    Arc<Notify>,
+    tokio::sync::mpsc::UnboundedReceiver<u32>,
+) {
+    let go = Arc::new(Notify::new());
+    let (tx, rx) = tokio::sync::mpsc::unbounded_channel();
+    let handle = Consumer {
+        go: go.clone(),
+        received: tx,
+    }
+    .builder()
+    .mailbox(bounded(2))
+    .overflow(overflow)
+    .spawn();
+
+    (handle, go, rx)
+}
+
+#[tokio::test]
+async fn feed_waits_for_room_in_a_full_mailbox() {
+    for overflow in [Overflow::Reject, Overflow::DropNewest, Overflow::DropOldest] {
+        let (handle, go, mut received) = spawn_consumer(overflow);
+
+        feed!(handle, Item(1)).await.unwrap();
+        feed!(handle, Item(2)).await.unwrap();
+
+        let blocked = tokio::time::timeout(Duration::from_millis(50), feed!(handle, Item(3))).await;
+        assert!(blocked.is_err(), "feeding a full mailbox should wait");
+
+        go.notify_one();
+        feed!(handle, Item(3)).await.unwrap();
+        feed!(handle, Item(4)).await.unwrap();
+
+        let mut items = Vec::new();
+        while items.len() < 4 {
+            items.push(received.recv().await.unwrap());
+        }
+        assert_eq!(items, vec![1, 2, 3, 4], "{overflow:?}");
+    }
+}
+
+#[tokio::test]
+async fn feed_fails_once_the_task_terminated() {
+    let (handle, _go, _received) = spawn_consumer(Overflow::Reject);
+    let task = handle.this();
+
+    feed!(task, Item(1)).await.unwrap();
+    feed!(task, Item(2)).await.unwrap();
+
+    let waiting = tokio::spawn({
+        let task = task.clone();
+        async move { feed!(task, Item(3)).await }
+    });
+    handle.kill();
+
+    let err = waiting.await.unwrap().unwrap_err();
+    assert_eq!(err.0, Item(3));
+}
@@ -0,0 +1,138 @@
+//! Integration tests for `forward!`.
+
+use std::time::Duration;
+
+use notizia::prelude::*;
+use notizia::{call, forward, message, reply};
+
+#[message]
+#[derive(Debug)]
+enum Query {
+    #[request(reply = String)]
+    Get {
+        key: String,
+    },
+    Double(u32),
+}
+
+/// Answers queries itself.
+#[derive(Task)]
+#[task(message = Query, handler)]
+struct Shard {
+    name: &'static str,
+}
+
+impl Handler<Query> for Shard {
+    async fn handle(&mut self, msg: Query, ctx: &mut Context<Query>) {
+        match msg {
+            Query::Get { key, reply_to } => {
+                reply!(reply_to, format!("{}:{key}", self.name));
+            }
+            Query::Double(n) => {
+                reply!(ctx, u64::from(n) * 2);
+            }
+        }
+    }
+}
+
+/// Passes every query on to its shard.
+#[derive(Task)]
+#[task(message = Query, handler)]
+struct Router {
+    shards: Vec<TaskRef<Query>>,
+}
+
+impl Handler<Query> for Router {
+    async fn handle(&mut self, msg: Query, _ctx: &mut Context<Query>) {
+        let shard = match &msg {
+            Query::Get { key, .. } => &self.shards[key.len() % 2],
+            Query::Double(_) => &self.shards[0],
+        };
+        forward!(shard, msg).unwrap();
+    }
+}
+
+fn router() -> (TaskHandle<Query>, [TaskHandle<Query>; 2]) {
+    let shards = [Shard { name: "even" }.run(), Shard { name: "odd" }.run()];
+    let router = Router {
+        shards: shards.iter().map(TaskHandle::this).collect(),
+    }
+    .run();
+    (router, shards)
+}
+
+#[tokio::test]
+async fn forwarded_requests_are_answered_by_the_target() {
+    let (router, _shards) = router();
+
+    let answer = call!(router, |reply_to| Query::Get {
+        key: "ab".into(),
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert_eq!(answer, "even:ab");
+
+    let answer = call!(router, |reply_to| Query::Get {
+        key: "abc".into(),
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert_eq!(answer, "odd:abc");
+}
+
+/// Asks the router to double a number and waits for the answer.
+#[derive(Task)]
+#[task(message = u64)]
+struct Client {
+    router: TaskRef<Query>,
+    shard: TaskId,
+}
+
+impl Runnable<u64> for Client {
+    async fn start(&self) {
+        self.router.send(Query::Double(21)).unwrap();
+        assert_eq!(recv!(self).unwrap(), 42);
+        assert_eq!(self.mailbox().sender().unwrap().id(), self.shard);
+    }
+}
+
+#[tokio::test]
+async fn forwarded_messages_keep_their_sender() {
+    let (router, shards) = router();
+    let client = Client {
+        router: router.this(),
+        shard: shards[0].id(),
+    }
+    .run();
+
+    let reason = tokio::time::timeout(Duration::from_secs(1), client.join())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(matches!(reason, TerminateReason::Normal));
+}
+
+#[tokio::test]
+async fn failed_forward_returns_the_request() {
+    let shard = Shard { name: "gone" }.run();
+    let target = shard.this();
+    target.signal(notizia::core::SystemSignal::Stop).unwrap();
+    shard.join().await.unwrap();
+
+    let (reply_to, answer) = Reply::channel();
+    let err = forward!(
+        target,
+        Query::Get {
+            key: "a".into(),
+            reply_to
+        }
+    )
+    .unwrap_err();
+    let Query::Get { reply_to, .. } = err.0 else {
+        panic!("unexpected message");
+    };
+    reply_to.reply("fallback".into()).unwrap();
+    assert_eq!(answer.await.unwrap(), "fallback");
+}
@@ -0,0 +1,155 @@
+//! Integration tests for state machine tasks.
+
+use notizia::fsm::{self, Event, StateMachine, Transition};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Conn {
+    Idle,
+    Connecting,
+    Connected,
+}
+
+enum ConnMsg {
+    Connect,
+    Established,
+    Drop,
+    Shutdown,
+    State(oneshot::Sender<Conn>),
+}
+
+struct Connection {
+    log: Arc<Mutex<Vec<String>>>,
+    connect_timeout: Duration,
+}
+
+impl StateMachine for Connection {
+    type State = Conn;
+    type Message = ConnMsg;
+
+    fn initial_state(&self) -> Conn {
+        Conn::Idle
+    }
+
+    fn timeout(&self, state: &Conn) -> Option<Duration> {
+        (*state == Conn::Connecting).then_some(self.connect_timeout)
+    }
+
+    async fn handle(&mut self, state: &Conn, event: Event<ConnMsg>) -> Transition<Conn> {
+        match (state, event) {
+            (_, Event::Message(ConnMsg::State(reply))) => {
+                let _ = reply.send(state.clone());
+                Transition::Stay
+            }
+            (_, Event::Message(ConnMsg::Shutdown)) => Transition::Stop,
+            (Conn::Idle, Event::Message(ConnMsg::Connect)) => Transition::Goto(Conn::Connecting),
+            (Conn::Connecting, Event::Message(ConnMsg::Established)) => {
+                Transition::Goto(Conn::Connected)
+            }
+            (Conn::Connecting, Event::Timeout) => {
+                self.log.lock().unwrap().push("timeout".into());
+                Transition::Goto(Conn::Idle)
+            }
+            (Conn::Connected, Event::Message(ConnMsg::Drop)) => Transition::Goto(Conn::Idle),
+            _ => Transition::Stay,
+        }
+    }
+
+    async fn on_enter(&mut self, state: &Conn) {
+        self.log.lock().unwrap().push(format!("enter {state:?}"));
+    }
+
+    async fn on_exit(&mut self, state: &Conn) {
+        self.log.lock().unwrap().push(format!("exit {state:?}"));
+    }
+}
+
+fn connection(connect_timeout: Duration) -> (Connection, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let machine = Connection {
+        log: log.clone(),
+        connect_timeout,
+    };
+    (machine, log)
+}
+
+async fn state(handle: &notizia::TaskHandle<ConnMsg>) -> Conn {
+    let (reply, state) = oneshot::channel();
+    handle.send(ConnMsg::State(reply)).unwrap();
+    state.await.unwrap()
+}
+
+#[tokio::test]
+async fn transitions_run_entry_and_exit_hooks() {
+    let (machine, log) = connection(Duration::from_secs(60));
+    let handle = fsm::spawn(machine);
+
+    handle.send(ConnMsg::Connect).unwrap();
+    handle.send(ConnMsg::Established).unwrap();
+    assert_eq!(state(&handle).await, Conn::Connected);
+
+    // Not a valid transition from `Connected`
+    handle.send(ConnMsg::Connect).unwrap();
+    assert_eq!(state(&handle).await, Conn::Connected);
+
+    handle.send(ConnMsg::Drop).unwrap();
+    handle.send(ConnMsg::Shutdown).unwrap();
+    assert_eq!(
+        handle.join().await.unwrap(),
+        notizia::TerminateReason::Normal
+    );
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "enter Idle",
+            "exit Idle",
+            "enter Connecting",
+            "exit Connecting",
+            "enter Connected",
+            "exit Connected",
+            "enter Idle",
+            "exit Idle",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn states_time_out_once() {
+    let (machine, log) = connection(Duration::from_millis(20));
+    let handle = fsm::spawn(machine);
+
+    handle.send(ConnMsg::Connect).unwrap();
+    tokio::time::sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(state(&handle).await, Conn::Idle);
+    let log = log.lock().unwrap();
+    assert_eq!(log.iter().filter(|entry| *entry == "timeout").count(), 1);
+}
+
+#[tokio::test]
+async fn handled_messages_do_not_restart_the_timeout() {
+    let (machine, _log) = connection(Duration::from_millis(50));
+    let handle = fsm::spawn(machine);
+
+    handle.send(ConnMsg::Connect).unwrap();
+    for _ in 0..4 {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        handle.send(ConnMsg::Drop).unwrap();
+    }
+
+    assert_eq!(state(&handle).await, Conn::Idle);
+}
+
+#[tokio::test]
+async fn closing_the_mailbox_exits_the_current_state() {
+    let (machine, log) = connection(Duration::from_secs(60));
+    let handle = fsm::spawn(machine);
+
+    handle.send(ConnMsg::Connect).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(log.lock().unwrap().last().unwrap(), "exit Connecting");
+}
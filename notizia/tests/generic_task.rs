@@ -0,0 +1,121 @@
+//! Integration tests for deriving `Task` on generic types.
+
+use notizia::call;
+use notizia::message;
+use notizia::prelude::*;
+use std::collections::HashMap;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+
+trait Store: Send + Sync {
+    fn put(&self, key: u32, value: String);
+    fn get(&self, key: u32) -> Option<String>;
+}
+
+#[derive(Default)]
+struct MemoryStore(Mutex<HashMap<u32, String>>);
+
+impl Store for MemoryStore {
+    fn put(&self, key: u32, value: String) {
+        self.0.lock().unwrap().insert(key, value);
+    }
+
+    fn get(&self, key: u32) -> Option<String> {
+        self.0.lock().unwrap().get(&key).cloned()
+    }
+}
+
+#[message]
+#[derive(Debug)]
+enum StoreMsg {
+    Put(u32, String),
+    #[request(reply = Option<String>)]
+    Get {
+        key: u32,
+    },
+}
+
+#[derive(Task)]
+#[task(message = StoreMsg)]
+struct Worker<S: Store> {
+    store: S,
+}
+
+impl<S: Store + 'static> Runnable<StoreMsg> for Worker<S> {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                StoreMsg::Put(key, value) => self.store.put(key, value),
+                StoreMsg::Get { key, reply_to } => {
+                    let _ = reply_to.reply(self.store.get(key));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = M)]
+struct Collector<M>
+where
+    M: Debug + Send + Sync + 'static,
+{
+    seen: Arc<Mutex<Vec<M>>>,
+}
+
+impl<M> Runnable<M> for Collector<M>
+where
+    M: Debug + Send + Sync + 'static,
+{
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.lock().unwrap().push(msg);
+        }
+    }
+}
+
+#[tokio::test]
+async fn generic_task_struct_runs() {
+    let worker = Worker {
+        store: MemoryStore::default(),
+    };
+    let handle = spawn!(worker);
+
+    handle.send(StoreMsg::Put(1, "one".into())).unwrap();
+    let value = call!(handle, |reply_to| StoreMsg::Get { key: 1, reply_to })
+        .await
+        .unwrap();
+
+    assert_eq!(value.as_deref(), Some("one"));
+}
+
+#[tokio::test]
+async fn message_type_can_be_a_generic_parameter() {
+    let numbers = Arc::new(Mutex::new(Vec::new()));
+    let words = Arc::new(Mutex::new(Vec::new()));
+
+    let first = Collector {
+        seen: numbers.clone(),
+    }
+    .run();
+    let second = Collector {
+        seen: words.clone(),
+    }
+    .run();
+
+    first.send(1u32).unwrap();
+    first.send(2u32).unwrap();
+    second.send("hello").unwrap();
+
+    first
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    second
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+
+    assert_eq!(*numbers.lock().unwrap(), vec![1, 2]);
+    assert_eq!(*words.lock().unwrap(), vec!["hello"]);
+}
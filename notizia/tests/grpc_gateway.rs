@@ -0,0 +1,218 @@
+//! Integration tests for exposing tasks as gRPC services.
+
+#![cfg(feature = "grpc")]
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use http::uri::PathAndQuery;
+use notizia::grpc::{Empty, GrpcGateway, JsonCodec};
+use notizia::message;
+use notizia::prelude::*;
+use serde::{Deserialize, Serialize};
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Channel;
+use tonic::{Code, Request};
+
+#[message]
+enum CounterMsg {
+    Add {
+        amount: u64,
+    },
+    #[request(reply = u64)]
+    Get,
+    #[request(reply = u64)]
+    Stall,
+    #[request(stream = u64)]
+    Count {
+        to: u64,
+    },
+    #[request(stream = u64)]
+    Broken,
+}
+
+#[derive(Serialize, Deserialize)]
+struct AddRequest {
+    amount: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CountRequest {
+    to: u64,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter;
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        let mut count = 0;
+        let mut stalled = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::Add { amount } => count += amount,
+                CounterMsg::Get { reply_to } => {
+                    let _ = reply_to.reply(count);
+                }
+                CounterMsg::Stall { reply_to } => stalled.push(reply_to),
+                CounterMsg::Count { to, reply_to } => {
+                    for i in 1..=to {
+                        let _ = reply_to.send(i).await;
+                    }
+                    reply_to.finish().await;
+                }
+                CounterMsg::Broken { reply_to } => {
+                    let _ = reply_to.send(1).await;
+                    reply_to.fail("counter broke").await;
+                }
+            }
+        }
+    }
+}
+
+async fn serve(counter: &TaskHandle<CounterMsg>) -> SocketAddr {
+    let routes = GrpcGateway::new("test.Counter", counter.this())
+        .timeout(Duration::from_millis(100))
+        .cast("Add", |req: AddRequest| CounterMsg::Add {
+            amount: req.amount,
+        })
+        .unary("Get", |(): (), reply_to| CounterMsg::Get { reply_to })
+        .unary("Stall", |(): (), reply_to| CounterMsg::Stall { reply_to })
+        .server_streaming("Count", |req: CountRequest, reply_to| CounterMsg::Count {
+            to: req.to,
+            reply_to,
+        })
+        .server_streaming("Broken", |(): (), reply_to| CounterMsg::Broken { reply_to })
+        .into_routes();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(
+        tonic::transport::Server::builder()
+            .add_routes(routes)
+            .serve_with_incoming(TcpListenerStream::new(listener)),
+    );
+    addr
+}
+
+async fn client(addr: SocketAddr) -> tonic::client::Grpc<Channel> {
+    let channel = Channel::from_shared(format!("http://{addr}"))
+        .unwrap()
+        .connect()
+        .await
+        .unwrap();
+    tonic::client::Grpc::new(channel)
+}
+
+async fn unary<Req, Res>(
+    client: &mut tonic::client::Grpc<Channel>,
+    path: &'static str,
+    req: Req,
+) -> Result<Res, tonic::Status>
+where
+    Req: Serialize + Send + Sync + 'static,
+    Res: for<'de> Deserialize<'de> + Send + Sync + 'static,
+{
+    client.ready().await.unwrap();
+    client
+        .unary(
+            Request::new(req),
+            PathAndQuery::from_static(path),
+            JsonCodec::<Req, Res>::default(),
+        )
+        .await
+        .map(tonic::Response::into_inner)
+}
+
+async fn streaming<Req>(
+    client: &mut tonic::client::Grpc<Channel>,
+    path: &'static str,
+    req: Req,
+) -> (Vec<u64>, Option<tonic::Status>)
+where
+    Req: Serialize + Send + Sync + 'static,
+{
+    client.ready().await.unwrap();
+    let mut stream = client
+        .server_streaming(
+            Request::new(req),
+            PathAndQuery::from_static(path),
+            JsonCodec::<Req, u64>::default(),
+        )
+        .await
+        .unwrap()
+        .into_inner();
+
+    let mut items = Vec::new();
+    loop {
+        match stream.message().await {
+            Ok(Some(item)) => items.push(item),
+            Ok(None) => return (items, None),
+            Err(status) => return (items, Some(status)),
+        }
+    }
+}
+
+#[tokio::test]
+async fn unary_methods_call_the_task() {
+    let counter = spawn!(Counter);
+    let mut client = client(serve(&counter).await).await;
+
+    let empty: Empty = unary(&mut client, "/test.Counter/Add", AddRequest { amount: 3 })
+        .await
+        .unwrap();
+    assert_eq!(empty, Empty {});
+    unary::<_, Empty>(&mut client, "/test.Counter/Add", AddRequest { amount: 4 })
+        .await
+        .unwrap();
+
+    let count: u64 = unary(&mut client, "/test.Counter/Get", ()).await.unwrap();
+    assert_eq!(count, 7);
+}
+
+#[tokio::test]
+async fn timed_out_calls_exceed_the_deadline() {
+    let counter = spawn!(Counter);
+    let mut client = client(serve(&counter).await).await;
+
+    let status = unary::<_, u64>(&mut client, "/test.Counter/Stall", ())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::DeadlineExceeded);
+}
+
+#[tokio::test]
+async fn invalid_requests_are_rejected() {
+    let counter = spawn!(Counter);
+    let mut client = client(serve(&counter).await).await;
+
+    let status = unary::<_, Empty>(&mut client, "/test.Counter/Add", "three")
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    let status = unary::<_, Empty>(&mut client, "/test.Counter/Missing", ())
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unimplemented);
+}
+
+#[tokio::test]
+async fn server_streaming_methods_stream_the_reply() {
+    let counter = spawn!(Counter);
+    let mut client = client(serve(&counter).await).await;
+
+    let (items, status) =
+        streaming(&mut client, "/test.Counter/Count", CountRequest { to: 3 }).await;
+    assert_eq!(items, vec![1, 2, 3]);
+    assert!(status.is_none());
+
+    let (items, status) = streaming(&mut client, "/test.Counter/Broken", ()).await;
+    assert_eq!(items, vec![1]);
+    let status = status.unwrap();
+    assert_eq!(status.code(), Code::Aborted);
+    assert_eq!(status.message(), "counter broke");
+}
@@ -0,0 +1,127 @@
+//! Integration tests for tasks implementing `Handler`.
+
+use notizia::prelude::*;
+use notizia::{call, message};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[message]
+#[derive(Debug)]
+enum CounterMsg {
+    Increment,
+    Add(u32),
+    #[request(reply = u32)]
+    GetCount,
+    #[request(reply = u32)]
+    Forget,
+    Stop,
+}
+
+/// Final count and termination reason, recorded by `terminate`
+type Outcome = Arc<Mutex<Option<(u32, TerminateReason)>>>;
+
+#[derive(Task)]
+#[task(message = CounterMsg, handler)]
+struct Counter {
+    count: u32,
+    reason: Outcome,
+}
+
+impl Handler<CounterMsg> for Counter {
+    async fn handle(&mut self, msg: CounterMsg, ctx: &mut Context<CounterMsg>) {
+        match msg {
+            CounterMsg::Increment => self.count += 1,
+            CounterMsg::Add(n) => self.count += n,
+            CounterMsg::GetCount { reply_to } => {
+                let _ = reply_to.reply(self.count);
+            }
+            CounterMsg::Forget { .. } => {}
+            CounterMsg::Stop => ctx.stop(),
+        }
+    }
+
+    async fn terminate(&mut self, reason: TerminateReason) {
+        *self.reason.lock().unwrap() = Some((self.count, reason));
+    }
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg, handler)]
+struct Echo;
+
+impl Handler<CounterMsg> for Echo {
+    async fn handle(&mut self, msg: CounterMsg, ctx: &mut Context<CounterMsg>) {
+        if let CounterMsg::Increment = msg {
+            // Messages sent to ourselves are handled after the current one
+            ctx.this().send(CounterMsg::Stop).unwrap();
+        } else if let CounterMsg::Stop = msg {
+            assert_eq!(ctx.name(), "Echo");
+            ctx.stop();
+        }
+    }
+}
+
+fn counter() -> (Counter, Outcome) {
+    let reason = Arc::new(Mutex::new(None));
+    let counter = Counter {
+        count: 0,
+        reason: reason.clone(),
+    };
+
+    (counter, reason)
+}
+
+#[tokio::test]
+async fn handler_mutates_state_and_replies() {
+    let (counter, _) = counter();
+    let handle = spawn!(counter);
+
+    handle.send(CounterMsg::Increment).unwrap();
+    handle.send(CounterMsg::Add(41)).unwrap();
+
+    assert_eq!(call!(handle, CounterMsg::GetCount).await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn unanswered_requests_fail_the_call() {
+    let (counter, _) = counter();
+    let handle = spawn!(counter);
+
+    let result = call!(handle, CounterMsg::Forget).await;
+
+    assert!(matches!(result, Err(CallError::NoReply)));
+}
+
+#[tokio::test]
+async fn closed_mailbox_stops_the_loop() {
+    let (counter, reason) = counter();
+    let handle = spawn!(counter);
+    handle.send(CounterMsg::Increment).unwrap();
+
+    let result = handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(result, TerminateReason::Normal);
+    assert_eq!(*reason.lock().unwrap(), Some((1, TerminateReason::Normal)));
+}
+
+#[tokio::test]
+async fn stop_ends_the_task_after_the_current_message() {
+    let (counter, reason) = counter();
+    let handle = spawn!(counter);
+    let task = handle.this();
+
+    task.send(CounterMsg::Increment).unwrap();
+    task.send(CounterMsg::Stop).unwrap();
+    task.send(CounterMsg::Increment).unwrap();
+
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Normal);
+    assert_eq!(*reason.lock().unwrap(), Some((1, TerminateReason::Normal)));
+}
+
+#[tokio::test]
+async fn context_refers_to_the_running_task() {
+    let handle = spawn!(Echo);
+    handle.send(CounterMsg::Increment).unwrap();
+
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Normal);
+}
@@ -0,0 +1,159 @@
+//! Integration tests for mapping message variants to methods with `#[handlers]`.
+
+use notizia::futures::StreamExt;
+use notizia::prelude::*;
+use notizia::{call, call_stream, handlers, message};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[message]
+#[derive(Debug)]
+enum CounterMsg {
+    Increment,
+    Add {
+        amount: u32,
+    },
+    #[request(reply = u32)]
+    GetCount,
+    #[request(reply = bool)]
+    Exceeds {
+        limit: u32,
+    },
+    #[request(stream = u32)]
+    CountDown,
+    Stop,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg, handler)]
+struct Counter {
+    count: u32,
+    reason: Arc<Mutex<Option<TerminateReason>>>,
+}
+
+#[handlers(message = CounterMsg)]
+impl Counter {
+    async fn increment(&mut self) {
+        self.count += 1;
+    }
+
+    fn add(&mut self, amount: u32) {
+        self.count += amount;
+    }
+
+    async fn get_count(&self) -> u32 {
+        self.count
+    }
+
+    async fn exceeds(&self, limit: u32) -> bool {
+        self.count > limit
+    }
+
+    async fn count_down(&self, reply_to: StreamReply<u32>) {
+        for i in (0..self.count).rev() {
+            let _ = reply_to.send(i).await;
+        }
+        reply_to.finish().await;
+    }
+
+    async fn stop(&mut self, ctx: &mut Context<CounterMsg>) {
+        ctx.stop();
+    }
+
+    async fn terminate(&mut self, reason: TerminateReason) {
+        *self.reason.lock().unwrap() = Some(reason);
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Generic<T> {
+    Push(T),
+}
+
+#[derive(Task)]
+#[task(message = Generic<u32>, handler)]
+struct Sink {
+    items: Arc<Mutex<Vec<u32>>>,
+}
+
+#[handlers(message = Generic<u32>)]
+impl Sink {
+    async fn push(&mut self, arg0: u32) {
+        self.items.lock().unwrap().push(arg0);
+    }
+}
+
+fn counter() -> (Counter, Arc<Mutex<Option<TerminateReason>>>) {
+    let reason = Arc::new(Mutex::new(None));
+    let counter = Counter {
+        count: 0,
+        reason: reason.clone(),
+    };
+
+    (counter, reason)
+}
+
+#[tokio::test]
+async fn variants_are_dispatched_to_methods() {
+    let (counter, _) = counter();
+    let handle = spawn!(counter);
+
+    handle.send(CounterMsg::Increment).unwrap();
+    handle.send(CounterMsg::Add { amount: 2 }).unwrap();
+
+    assert_eq!(call!(handle, CounterMsg::GetCount).await.unwrap(), 3);
+    assert!(
+        call!(handle, |reply_to| CounterMsg::Exceeds {
+            limit: 2,
+            reply_to
+        })
+        .await
+        .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn methods_taking_reply_to_reply_themselves() {
+    let (counter, _) = counter();
+    let handle = spawn!(counter);
+    handle.send(CounterMsg::Add { amount: 3 }).unwrap();
+
+    let items: Vec<u32> = call_stream!(handle, CounterMsg::CountDown)
+        .unwrap()
+        .collect()
+        .await;
+
+    assert_eq!(items, vec![2, 1, 0]);
+}
+
+#[tokio::test]
+async fn context_and_terminate_are_forwarded() {
+    let (counter, reason) = counter();
+    let handle = spawn!(counter);
+
+    handle.send(CounterMsg::Stop).unwrap();
+
+    assert_eq!(
+        tokio::time::timeout(Duration::from_secs(1), handle.join())
+            .await
+            .unwrap()
+            .unwrap(),
+        TerminateReason::Normal
+    );
+    assert_eq!(*reason.lock().unwrap(), Some(TerminateReason::Normal));
+}
+
+#[tokio::test]
+async fn tuple_fields_of_generic_messages_are_bound_positionally() {
+    let items = Arc::new(Mutex::new(Vec::new()));
+    let sink = Sink {
+        items: items.clone(),
+    };
+    let handle = spawn!(sink);
+
+    handle.send(Generic::Push(1)).unwrap();
+    handle.send(Generic::Push(2)).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(*items.lock().unwrap(), vec![1, 2]);
+}
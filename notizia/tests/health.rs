@@ -0,0 +1,58 @@
+//! Integration tests for `TaskHandle::health`.
+
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+
+#[derive(Task)]
+#[task(message = Duration, handler)]
+struct Napper;
+
+impl Handler<Duration> for Napper {
+    async fn handle(&mut self, nap: Duration, _ctx: &mut Context<Duration>) {
+        tokio::time::sleep(nap).await;
+    }
+}
+
+#[tokio::test]
+async fn idle_task_is_alive_and_ready() {
+    let handle = spawn!(Napper);
+
+    let health = handle.health(Duration::from_secs(1)).await;
+    assert!(health.is_alive());
+    assert!(health.is_ready(0));
+    assert_eq!(health.mailbox_depth, 0);
+    assert_eq!(health.last_activity, None);
+
+    handle.send(Duration::ZERO).unwrap();
+    let health = handle.health(Duration::from_secs(1)).await;
+    assert!(health.last_activity.is_some());
+    assert!(health.idle().unwrap() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn busy_task_reports_its_backlog() {
+    let handle = spawn!(Napper);
+    handle.send(Duration::from_secs(5)).unwrap();
+    handle.send(Duration::ZERO).unwrap();
+    handle.send(Duration::ZERO).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let health = handle.health(Duration::from_millis(30)).await;
+    assert!(!health.is_alive());
+    assert_eq!(health.mailbox_depth, 2);
+    assert!(health.last_activity.is_some());
+    assert!(!health.is_ready(10));
+}
+
+#[tokio::test]
+async fn terminated_task_is_not_alive() {
+    let handle = spawn!(Napper);
+    handle.signal(SystemSignal::Stop).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let health = handle.health(Duration::from_secs(1)).await;
+    assert!(!health.is_alive());
+    assert_eq!(health.mailbox_depth, 0);
+}
@@ -0,0 +1,67 @@
+//! Integration tests for liveness checks with `ping`.
+
+use std::time::Duration;
+
+use notizia::CallError;
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+
+#[derive(Task)]
+#[task(message = Duration, handler)]
+struct Napper;
+
+impl Handler<Duration> for Napper {
+    async fn handle(&mut self, nap: Duration, _ctx: &mut Context<Duration>) {
+        tokio::time::sleep(nap).await;
+    }
+}
+
+#[tokio::test]
+async fn receiving_task_answers_pings() {
+    let handle = spawn!(Napper);
+
+    let rtt = handle.ping(Duration::from_secs(1)).await.unwrap();
+    assert!(rtt < Duration::from_secs(1));
+
+    // Suspended tasks still receive, so they still answer
+    handle.signal(SystemSignal::Suspend).unwrap();
+    assert!(handle.this().ping(Duration::from_secs(1)).await.is_ok());
+}
+
+#[tokio::test]
+async fn busy_task_does_not_answer() {
+    let handle = spawn!(Napper);
+    handle.send(Duration::from_secs(5)).unwrap();
+
+    let err = handle.ping(Duration::from_millis(30)).await.unwrap_err();
+    assert!(matches!(err, CallError::Timeout { .. }));
+}
+
+#[tokio::test]
+async fn stopped_task_fails_the_ping() {
+    let handle = spawn!(Napper);
+    let task = handle.this();
+    handle.signal(SystemSignal::Stop).unwrap();
+    handle.join().await.unwrap();
+
+    let err = task.ping(Duration::from_secs(1)).await.unwrap_err();
+    assert!(matches!(err, CallError::SendError { .. }));
+}
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Deaf;
+
+impl Runnable<u32> for Deaf {
+    async fn start(&self) {
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn task_terminating_before_receiving_closes_the_ping() {
+    let handle = spawn!(Deaf);
+
+    let err = handle.ping(Duration::from_secs(1)).await.unwrap_err();
+    assert!(matches!(err, CallError::ChannelClosed));
+}
@@ -0,0 +1,154 @@
+//! Integration tests for the `inspector` feature.
+
+#![cfg(feature = "inspector")]
+
+use notizia::call;
+use notizia::inspector::{InspectorMsg, InspectorTask};
+use notizia::prelude::*;
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+#[derive(Task)]
+#[task(message = oneshot::Sender<()>)]
+struct Sleeper;
+
+impl Runnable<oneshot::Sender<()>> for Sleeper {
+    async fn start(&self) {
+        // Report the first message, then keep the rest queued
+        if let Ok(started) = recv!(self) {
+            let _ = started.send(());
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[tokio::test]
+async fn lists_and_describes_running_tasks() {
+    let inspector = spawn!(InspectorTask);
+    let sleeper = spawn!(Sleeper);
+
+    let (tx, rx) = oneshot::channel();
+    sleeper.send(tx).unwrap();
+    rx.await.unwrap();
+    sleeper.send(oneshot::channel().0).unwrap();
+    sleeper.send(oneshot::channel().0).unwrap();
+
+    let tasks = call!(inspector, InspectorMsg::ListTasks).await.unwrap();
+    assert!(tasks.iter().any(|task| task.id == inspector.id()));
+    let task = tasks.iter().find(|task| task.id == sleeper.id()).unwrap();
+    assert_eq!(task.name, "Sleeper");
+    assert_eq!(task.mailbox_depth, 2);
+
+    let id = sleeper.id();
+    let detail = call!(inspector, |reply_to| InspectorMsg::TaskDetail {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert_eq!(detail.map(|task| task.mailbox_depth), Some(2));
+
+    let depths = call!(inspector, InspectorMsg::DumpMailboxDepths)
+        .await
+        .unwrap();
+    assert!(depths.contains(&(id, 2)));
+}
+
+#[tokio::test]
+async fn kills_tasks_by_id() {
+    let inspector = spawn!(InspectorTask);
+    let sleeper = spawn!(Sleeper);
+    let id = sleeper.id();
+
+    let killed = call!(inspector, |reply_to| InspectorMsg::KillTask {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert!(killed);
+    assert!(sleeper.join().await.unwrap_err().is_cancelled());
+
+    // Finished tasks leave the directory
+    let detail = call!(inspector, |reply_to| InspectorMsg::TaskDetail {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert_eq!(detail, None);
+
+    let killed = call!(inspector, |reply_to| InspectorMsg::KillTask {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert!(!killed);
+}
+
+#[tokio::test]
+async fn uptime_grows() {
+    let inspector = spawn!(InspectorTask);
+    let id = inspector.id();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let detail = call!(inspector, |reply_to| InspectorMsg::TaskDetail {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap()
+    .unwrap();
+    assert!(detail.uptime >= Duration::from_millis(20));
+}
+
+#[derive(Task)]
+#[task(message = Duration, handler)]
+struct Napper;
+
+impl Handler<Duration> for Napper {
+    async fn handle(&mut self, nap: Duration, _ctx: &mut Context<Duration>) {
+        tokio::time::sleep(nap).await;
+    }
+}
+
+#[tokio::test]
+async fn reports_handler_latency_and_throughput() {
+    let inspector = spawn!(InspectorTask);
+    let napper = spawn!(Napper);
+    let id = napper.id();
+
+    for nap in [1, 1, 1, 1, 1, 1, 1, 1, 1, 20] {
+        napper.send(Duration::from_millis(nap)).unwrap();
+    }
+    let stats = loop {
+        let stats = call!(inspector, |reply_to| InspectorMsg::HandlerStats {
+            id,
+            reply_to
+        })
+        .await
+        .unwrap();
+        if let Some(stats) = stats.filter(|stats| stats.handled == 10) {
+            break stats;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    };
+    assert_eq!(stats.handled, 10);
+    assert!(stats.p50 >= Duration::from_millis(1));
+    assert!(stats.p50 < Duration::from_millis(20));
+    assert!(stats.p99 >= Duration::from_millis(20));
+    assert!(stats.p95 <= stats.p99);
+    assert!(stats.per_second > 0.0);
+
+    // Tasks without a generated dispatch have no handler stats
+    let sleeper = spawn!(Sleeper);
+    let id = sleeper.id();
+    let stats = call!(inspector, |reply_to| InspectorMsg::HandlerStats {
+        id,
+        reply_to
+    })
+    .await
+    .unwrap();
+    assert_eq!(stats, None);
+}
@@ -0,0 +1,68 @@
+//! Integration tests for aborting groups of tasks with `KillSwitch`.
+
+use notizia::prelude::*;
+use notizia::task::KillSwitch;
+
+struct Tick;
+
+#[derive(Task)]
+#[task(message = Tick)]
+struct Forever;
+
+impl Runnable<Tick> for Forever {
+    async fn start(&self) {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[tokio::test]
+async fn trigger_aborts_all_registered_tasks() {
+    let switch = KillSwitch::new();
+    let first = spawn!(Forever);
+    let second = spawn!(Forever);
+    switch.register(&first);
+    switch.register(&second);
+
+    assert!(!switch.is_triggered());
+    switch.trigger();
+    assert!(switch.is_triggered());
+
+    assert!(first.join().await.unwrap_err().is_cancelled());
+    assert!(second.join().await.unwrap_err().is_cancelled());
+}
+
+#[tokio::test]
+async fn tasks_registered_after_trigger_are_aborted() {
+    let switch = KillSwitch::new();
+    switch.trigger();
+
+    let late = spawn!(Forever);
+    switch.clone().register(&late);
+
+    assert!(late.join().await.unwrap_err().is_cancelled());
+}
+
+#[tokio::test]
+async fn unregistered_tasks_keep_running() {
+    let switch = KillSwitch::new();
+    let registered = spawn!(Forever);
+    let bystander = spawn!(Forever);
+    switch.register(&registered);
+
+    switch.trigger();
+    assert!(registered.join().await.unwrap_err().is_cancelled());
+
+    tokio::task::yield_now().await;
+    assert!(!bystander.is_finished());
+}
+
+#[tokio::test]
+async fn plain_tokio_tasks_can_be_registered() {
+    let switch = KillSwitch::new();
+    let helper = tokio::spawn(std::future::pending::<()>());
+    switch.register_abort(helper.abort_handle());
+
+    switch.trigger();
+
+    assert!(helper.await.unwrap_err().is_cancelled());
+}
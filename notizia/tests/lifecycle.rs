@@ -345,15 +345,9 @@ async fn shutdown_timeout_aborts_task() {
 
 #[tokio::test]
 async fn shutdown_closes_channel() {
-    // This test verifies the intended behavior: shutdown() should signal
-    // the task to stop. However, due to the current implementation where
-    // tasks hold an internal sender (for this() method), the channel won't
-    // actually close until the task exits. This test documents the current
-    // behavior: shutdown() works by timing out and waiting for task completion,
-    // not by closing the channel.
-    //
-    // For proper shutdown signaling, tasks should use explicit stop messages
-    // rather than relying on channel closure.
+    // shutdown() waits for a task that was told to stop with an explicit
+    // stop message. Tasks that are not, stop once the channel closes, see
+    // shutdown_closes_channel_without_stop_message.
 
     let terminate_called = Arc::new(AtomicBool::new(false));
     let terminate_reason_holder = Arc::new(Mutex::new(None));
@@ -387,6 +381,63 @@ async fn shutdown_closes_channel() {
     assert_eq!(*reason, Some(TerminateReason::Normal));
 }
 
+#[tokio::test]
+async fn shutdown_closes_channel_without_stop_message() {
+    // Tasks only hold a weak sender to themselves (for this()), so once the
+    // handle's sender is dropped, the task observes RecvError::Closed.
+
+    let terminate_called = Arc::new(AtomicBool::new(false));
+    let terminate_reason_holder = Arc::new(Mutex::new(None));
+
+    let task = TerminateTrackingTask {
+        terminate_called: terminate_called.clone(),
+        terminate_reason: terminate_reason_holder.clone(),
+    };
+
+    let handle = spawn!(task);
+
+    // Give the task time to start, without sending an explicit stop message
+    sleep(Duration::from_millis(10)).await;
+
+    // shutdown() closes the channel, so the task exits and terminate() is called
+    let result = handle.shutdown(Duration::from_secs(1)).await;
+
+    assert!(result.is_ok(), "shutdown() should succeed");
+    assert_eq!(result.unwrap(), TerminateReason::Normal);
+
+    // Verify terminate was called
+    assert!(
+        terminate_called.load(Ordering::SeqCst),
+        "terminate() should have been called"
+    );
+
+    let reason = terminate_reason_holder.lock().await;
+    assert_eq!(*reason, Some(TerminateReason::Normal));
+}
+
+#[tokio::test]
+async fn dropping_handle_closes_mailbox() {
+    let terminate_called = Arc::new(AtomicBool::new(false));
+    let terminate_reason_holder = Arc::new(Mutex::new(None));
+
+    let task = TerminateTrackingTask {
+        terminate_called: terminate_called.clone(),
+        terminate_reason: terminate_reason_holder.clone(),
+    };
+
+    // Without a handle or any TaskRef, nothing can send to the task anymore
+    drop(spawn!(task));
+    sleep(Duration::from_millis(50)).await;
+
+    assert!(
+        terminate_called.load(Ordering::SeqCst),
+        "terminate() should have been called"
+    );
+
+    let reason = terminate_reason_holder.lock().await;
+    assert_eq!(*reason, Some(TerminateReason::Normal));
+}
+
 #[tokio::test]
 async fn kill_skips_terminate_hook() {
     let terminate_called = Arc::new(AtomicBool::new(false));
@@ -509,8 +560,8 @@ async fn shutdown_after_panic_in_start() {
         TerminateReason::Panic(msg) => {
             assert_eq!(msg, "deliberate panic", "panic message should match");
         }
-        TerminateReason::Normal => {
-            panic!("Expected Panic reason, got Normal");
+        other => {
+            panic!("Expected Panic reason, got {other:?}");
         }
     }
 
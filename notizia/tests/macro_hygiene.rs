@@ -0,0 +1,97 @@
+//! Integration tests for the hygiene of `#[derive(Task)]`.
+//!
+//! The derive must not depend on names in scope at the call site, such as a
+//! `tokio` crate or the traits of the prelude.
+
+#![allow(dead_code)]
+
+use std::sync::{Arc, Mutex};
+
+mod isolated {
+    // Neither the prelude nor `Runnable` is in scope here, and `tokio` does
+    // not name the crate
+    mod tokio {}
+
+    #[derive(notizia::Task)]
+    #[task(message = u32, handler)]
+    pub struct Summer {
+        pub total: std::sync::Arc<std::sync::Mutex<u32>>,
+    }
+
+    impl notizia::task::Handler<u32> for Summer {
+        async fn handle(&mut self, msg: u32, _ctx: &mut notizia::task::Context<u32>) {
+            *self.total.lock().unwrap() += msg;
+        }
+    }
+}
+
+#[derive(notizia::Task)]
+#[task(message = String)]
+struct Echo {
+    seen: Arc<Mutex<Vec<String>>>,
+}
+
+impl notizia::Runnable<String> for Echo {
+    async fn start(&self) {
+        while let Ok(msg) = notizia::recv!(self) {
+            self.seen.lock().unwrap().push(msg);
+        }
+    }
+}
+
+#[::tokio::test]
+async fn derive_does_not_depend_on_call_site_names() {
+    use notizia::Task as _;
+
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Echo { seen: seen.clone() }.run();
+    handle.send("hello".to_string()).unwrap();
+    handle
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec!["hello"]);
+
+    let total = Arc::new(Mutex::new(0));
+    let handle = isolated::Summer {
+        total: total.clone(),
+    }
+    .run();
+    handle.send(1).unwrap();
+    handle.send(2).unwrap();
+    handle
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(*total.lock().unwrap(), 3);
+}
+
+#[::tokio::test]
+async fn derive_works_on_types_declared_in_functions() {
+    use notizia::Task as _;
+
+    struct Local(u32);
+
+    #[derive(notizia::Task)]
+    #[task(message = Local)]
+    struct Doubler {
+        out: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl notizia::Runnable<Local> for Doubler {
+        async fn start(&self) {
+            while let Ok(Local(n)) = notizia::recv!(self) {
+                self.out.lock().unwrap().push(n * 2);
+            }
+        }
+    }
+
+    let out = Arc::new(Mutex::new(Vec::new()));
+    let handle = Doubler { out: out.clone() }.run();
+    handle.send(Local(21)).unwrap();
+    handle
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert_eq!(*out.lock().unwrap(), vec![42]);
+}
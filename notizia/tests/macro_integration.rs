@@ -175,50 +175,83 @@ async fn derive_macro_works_with_multi_field_struct() {
     let _ = handle.join().await;
 }
 
-// TODO: Test with generic message type
-// Currently commented out because the derive macro parser doesn't support
-// generic types in #[task(message = ...)] attribute yet.
-//
-// #[derive(Debug, Clone)]
-// struct GenericMsg<T: Clone> {
-//     data: T,
-// }
-//
-// #[derive(Task)]
-// #[task(message = GenericMsg<u32>)]
-// struct ConcreteGenericTask {
-//     sum: Arc<AtomicU32>,
-// }
-//
-// impl Runnable<GenericMsg<u32>> for ConcreteGenericTask {
-//     async fn start(&self) {
-//         loop {
-//             match recv!(self) {
-//                 Ok(msg) => {
-//                     self.sum.fetch_add(msg.data, Ordering::SeqCst);
-//                 }
-//                 Err(_) => break,
-//             }
-//         }
-//     }
-// }
-//
-// #[tokio::test]
-// async fn derive_macro_works_with_generic_messages() {
-//     let sum = Arc::new(AtomicU32::new(0));
-//     let task = ConcreteGenericTask { sum: sum.clone() };
-//     let handle = spawn!(task);
-//
-//     handle.send(GenericMsg { data: 5 }).unwrap();
-//     handle.send(GenericMsg { data: 10 }).unwrap();
-//     handle.send(GenericMsg { data: 15 }).unwrap();
-//
-//     sleep(Duration::from_millis(10)).await;
-//
-//     assert_eq!(sum.load(Ordering::SeqCst), 30);
-//
-//     drop(handle);
-// }
+// Test with generic message type
+#[derive(Debug, Clone)]
+struct GenericMsg<T: Clone> {
+    data: T,
+}
+
+#[derive(Task)]
+#[task(message = GenericMsg<u32>)]
+struct ConcreteGenericTask {
+    sum: Arc<AtomicU32>,
+}
+
+impl Runnable<GenericMsg<u32>> for ConcreteGenericTask {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.sum.fetch_add(msg.data, Ordering::SeqCst);
+        }
+    }
+}
+
+#[tokio::test]
+async fn derive_macro_works_with_generic_messages() {
+    let sum = Arc::new(AtomicU32::new(0));
+    let task = ConcreteGenericTask { sum: sum.clone() };
+    let handle = spawn!(task);
+
+    handle.send(GenericMsg { data: 5 }).unwrap();
+    handle.send(GenericMsg { data: 10 }).unwrap();
+    handle.send(GenericMsg { data: 15 }).unwrap();
+
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(sum.load(Ordering::SeqCst), 30);
+
+    drop(handle);
+}
+
+// Test with a fully qualified message type
+trait Protocol {
+    type Message;
+}
+
+struct Counting;
+
+impl Protocol for Counting {
+    type Message = GenericMsg<u32>;
+}
+
+#[derive(Task)]
+#[task(message = <Counting as Protocol>::Message)]
+struct QualifiedTask {
+    sum: Arc<AtomicU32>,
+}
+
+impl Runnable<<Counting as Protocol>::Message> for QualifiedTask {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.sum.fetch_add(msg.data, Ordering::SeqCst);
+        }
+    }
+}
+
+#[tokio::test]
+async fn derive_macro_works_with_qualified_message_types() {
+    let sum = Arc::new(AtomicU32::new(0));
+    let task = QualifiedTask { sum: sum.clone() };
+    let handle = spawn!(task);
+
+    handle.send(GenericMsg { data: 7 }).unwrap();
+    handle.send(GenericMsg { data: 8 }).unwrap();
+
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(sum.load(Ordering::SeqCst), 15);
+
+    drop(handle);
+}
 
 // Test with nested enum
 #[derive(Debug, Clone)]
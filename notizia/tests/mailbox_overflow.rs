@@ -0,0 +1,136 @@
+//! Integration tests for mailbox configuration in the task attribute.
+
+use notizia::core::{Overflow, bounded};
+use notizia::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::Notify;
+
+/// Waits for `gate` before receiving, so messages pile up in the mailbox.
+macro_rules! gated_task {
+    ($name:ident, $($option:tt)*) => {
+        #[derive(Task)]
+        #[task(message = u32, $($option)*)]
+        struct $name {
+            gate: Arc<Notify>,
+            seen: Arc<Mutex<Vec<u32>>>,
+        }
+
+        impl Runnable<u32> for $name {
+            async fn start(&self) {
+                self.gate.notified().await;
+                while let Ok(msg) = recv!(self) {
+                    self.seen.lock().unwrap().push(msg);
+                }
+            }
+        }
+
+        impl $name {
+            fn new() -> (Self, Arc<Notify>, Arc<Mutex<Vec<u32>>>) {
+                let gate = Arc::new(Notify::new());
+                let seen = Arc::new(Mutex::new(Vec::new()));
+                let task = $name {
+                    gate: gate.clone(),
+                    seen: seen.clone(),
+                };
+                (task, gate, seen)
+            }
+        }
+    };
+}
+
+gated_task!(Rejecting, mailbox = bounded(2));
+gated_task!(DropNewest, mailbox = bounded(2), overflow = drop_newest);
+gated_task!(DropOldest, mailbox = bounded(2), overflow = drop_oldest);
+gated_task!(Unbounded, mailbox = unbounded);
+
+async fn drain(handle: TaskHandle<u32>, gate: Arc<Notify>, seen: Arc<Mutex<Vec<u32>>>) -> Vec<u32> {
+    gate.notify_one();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    let seen = seen.lock().unwrap();
+    seen.clone()
+}
+
+#[tokio::test]
+async fn bounded_mailbox_rejects_by_default() {
+    let (task, gate, seen) = Rejecting::new();
+    let handle = task.run();
+
+    handle.send(1).unwrap();
+    handle.send(2).unwrap();
+    assert_eq!(handle.send(3).unwrap_err().0, 3);
+
+    assert_eq!(drain(handle, gate, seen).await, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn drop_newest_discards_overflowing_messages() {
+    let (task, gate, seen) = DropNewest::new();
+    let handle = task.run();
+
+    for i in 1..=4 {
+        handle.send(i).unwrap();
+    }
+
+    assert_eq!(drain(handle, gate, seen).await, vec![1, 2]);
+}
+
+#[tokio::test]
+async fn drop_oldest_keeps_the_latest_messages() {
+    let (task, gate, seen) = DropOldest::new();
+    let handle = task.run();
+
+    for i in 1..=5 {
+        handle.send(i).unwrap();
+    }
+
+    assert_eq!(drain(handle, gate, seen).await, vec![4, 5]);
+}
+
+#[tokio::test]
+async fn drop_oldest_frees_slots_as_messages_are_received() {
+    let (task, gate, seen) = DropOldest::new();
+    let handle = task.run();
+
+    for i in 1..=3 {
+        handle.send(i).unwrap();
+    }
+    gate.notify_one();
+    while seen.lock().unwrap().len() < 2 {
+        tokio::task::yield_now().await;
+    }
+    handle.send(4).unwrap();
+    handle.send(5).unwrap();
+
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(*seen.lock().unwrap(), vec![2, 3, 4, 5]);
+}
+
+#[tokio::test]
+async fn unbounded_mailbox_accepts_everything() {
+    let (task, gate, seen) = Unbounded::new();
+    let handle = task.run();
+
+    for i in 0..100 {
+        handle.send(i).unwrap();
+    }
+
+    assert_eq!(drain(handle, gate, seen).await.len(), 100);
+}
+
+#[tokio::test]
+async fn builder_overrides_the_attribute() {
+    let (task, gate, seen) = DropOldest::new();
+    let handle = task
+        .builder()
+        .mailbox(bounded(3))
+        .overflow(Overflow::Reject)
+        .spawn();
+
+    for i in 1..=3 {
+        handle.send(i).unwrap();
+    }
+    assert!(handle.send(4).is_err());
+
+    assert_eq!(drain(handle, gate, seen).await, vec![1, 2, 3]);
+}
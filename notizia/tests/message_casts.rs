@@ -0,0 +1,85 @@
+//! Integration tests for cast enums generated by `#[message(casts = ...)]`.
+
+use notizia::message;
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Messages of the counter
+#[message(casts = CounterCast)]
+#[derive(Debug)]
+enum CounterMsg {
+    #[request(reply = u32)]
+    GetCount,
+    Increment,
+    Add(u32),
+    Set {
+        value: u32,
+    },
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter {
+    count: Arc<AtomicU32>,
+}
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::GetCount { reply_to } => {
+                    let _ = reply_to.reply(self.count.load(Ordering::SeqCst));
+                }
+                CounterMsg::Increment => {
+                    self.count.fetch_add(1, Ordering::SeqCst);
+                }
+                CounterMsg::Add(n) => {
+                    self.count.fetch_add(n, Ordering::SeqCst);
+                }
+                CounterMsg::Set { value } => {
+                    self.count.store(value, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn cast_enum_converts_into_message() {
+    let casts = [
+        CounterCast::Increment,
+        CounterCast::Add(2),
+        CounterCast::Set { value: 3 },
+    ];
+
+    let messages: Vec<CounterMsg> = casts.iter().cloned().map(Into::into).collect();
+
+    assert!(matches!(messages[0], CounterMsg::Increment));
+    assert!(matches!(messages[1], CounterMsg::Add(2)));
+    assert!(matches!(messages[2], CounterMsg::Set { value: 3 }));
+    assert_eq!(format!("{:?}", casts[1]), "Add(2)");
+}
+
+#[tokio::test]
+async fn cast_can_be_broadcast() -> Result<(), CallError> {
+    let counters: Vec<_> = (0..3)
+        .map(|_| {
+            let counter = Counter {
+                count: Arc::new(AtomicU32::new(0)),
+            };
+            spawn!(counter)
+        })
+        .collect();
+
+    let msg = CounterCast::Add(5);
+    for counter in &counters {
+        counter.send(msg.clone().into()).unwrap();
+    }
+
+    for counter in &counters {
+        assert_eq!(counter.get_count().await?, 5);
+    }
+
+    Ok(())
+}
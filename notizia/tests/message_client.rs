@@ -0,0 +1,112 @@
+//! Integration tests for clients generated by `#[message(client = ...)]`.
+
+use notizia::message;
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, sleep};
+
+#[message(client = CounterClient)]
+#[derive(Debug)]
+enum CounterMsg {
+    #[request(reply = u32)]
+    GetCount,
+    #[request(reply = u32)]
+    AddAndGet {
+        amount: u32,
+    },
+    Increment,
+    Add(u32, u32),
+    Set {
+        value: u32,
+    },
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter {
+    count: Arc<AtomicU32>,
+}
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::GetCount { reply_to } => {
+                    let _ = reply_to.reply(self.count.load(Ordering::SeqCst));
+                }
+                CounterMsg::AddAndGet { amount, reply_to } => {
+                    let _ = reply_to.reply(self.count.fetch_add(amount, Ordering::SeqCst) + amount);
+                }
+                CounterMsg::Increment => {
+                    self.count.fetch_add(1, Ordering::SeqCst);
+                }
+                CounterMsg::Add(a, b) => {
+                    self.count.fetch_add(a + b, Ordering::SeqCst);
+                }
+                CounterMsg::Set { value } => {
+                    self.count.store(value, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
+
+fn counter() -> TaskHandle<CounterMsg> {
+    let counter = Counter {
+        count: Arc::new(AtomicU32::new(0)),
+    };
+    spawn!(counter)
+}
+
+/// Takes the task's API as a concrete type
+async fn bump_twice(client: CounterClient) -> CallResult<u32> {
+    client.increment().unwrap();
+    client.increment().unwrap();
+    client.get_count().await
+}
+
+#[tokio::test]
+async fn client_sends_casts_and_calls() -> Result<(), CallError> {
+    let handle = counter();
+    let client = CounterClient::new(handle.this());
+
+    client.set(10).unwrap();
+    client.add(1, 2).unwrap();
+    client.increment().unwrap();
+
+    assert_eq!(client.get_count().await?, 14);
+    assert_eq!(client.add_and_get(6).await?, 20);
+    assert_eq!(
+        client
+            .add_and_get_with_timeout(1, Duration::from_secs(1))
+            .await?,
+        21
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn client_can_be_passed_around() {
+    let handle = counter();
+    let client: CounterClient = handle.this().into();
+
+    assert_eq!(bump_twice(client.clone()).await.unwrap(), 2);
+    assert_eq!(client.task().id(), handle.id());
+}
+
+#[tokio::test]
+async fn client_reports_stopped_task() {
+    let handle = counter();
+    let client = CounterClient::new(handle.this());
+
+    handle.kill();
+    sleep(Duration::from_millis(20)).await;
+
+    assert!(client.increment().is_err());
+    assert!(matches!(
+        client.get_count().await,
+        Err(CallError::SendError { .. })
+    ));
+}
@@ -3,8 +3,7 @@
 //! This test suite validates the #[message] attribute macro that automatically
 //! injects reply_to fields for request variants.
 
-use notizia::message;
-use tokio::sync::oneshot;
+use notizia::{Reply, message};
 
 #[test]
 fn message_macro_injects_reply_to_field() {
@@ -19,7 +18,7 @@ fn message_macro_injects_reply_to_field() {
     }
 
     // This would not compile if reply_to wasn't injected
-    let (tx, _rx) = oneshot::channel();
+    let (tx, _rx) = Reply::channel();
     let _msg = TestMsg::GetValue { reply_to: tx };
 }
 
@@ -58,7 +57,7 @@ fn message_macro_works_with_existing_fields() {
     }
 
     // Should inject reply_to alongside existing fields
-    let (tx, _rx) = oneshot::channel();
+    let (tx, _rx) = Reply::channel();
     let _msg = TestMsg::Echo {
         id: 42,
         reply_to: tx,
@@ -89,13 +88,13 @@ fn message_macro_works_with_multiple_requests() {
         Increment,
     }
 
-    let (tx1, _rx1) = oneshot::channel();
+    let (tx1, _rx1) = Reply::channel();
     let _msg1 = TestMsg::GetCount { reply_to: tx1 };
 
-    let (tx2, _rx2) = oneshot::channel();
+    let (tx2, _rx2) = Reply::channel();
     let _msg2 = TestMsg::GetStats { reply_to: tx2 };
 
-    let (tx3, _rx3) = oneshot::channel();
+    let (tx3, _rx3) = Reply::channel();
     let _msg3 = TestMsg::GetStatus { reply_to: tx3 };
 }
 
@@ -137,7 +136,7 @@ fn issue5_exact_syntax_works() {
     }
 
     // Verify it compiles and works
-    let (tx, _rx) = oneshot::channel();
+    let (tx, _rx) = Reply::channel();
     let _msg = Msg::GetStatus { reply_to: tx };
     let _msg2 = Msg::Increment;
 }
@@ -0,0 +1,94 @@
+//! Integration tests for the schemas `#[message]` generates.
+
+use notizia::core::schema::{Describe, FieldSchema, ReplySchema, Shape};
+use notizia::message;
+use std::collections::HashMap;
+
+#[message]
+enum StoreMsg {
+    Clear,
+    Put(String, Vec<u8>),
+    Tag {
+        key: String,
+        tags: HashMap<String, Option<u32>>,
+    },
+    #[request(reply = Option<Vec<u8>>)]
+    Get {
+        key: String,
+    },
+    #[request(stream = (String, usize))]
+    Scan,
+}
+
+#[test]
+fn variants_are_described_in_order() {
+    let schema = StoreMsg::SCHEMA;
+    assert_eq!(schema.name, "StoreMsg");
+    assert_eq!(schema.version, None);
+    assert!(!schema.serde);
+
+    let names: Vec<_> = schema.variants.iter().map(|variant| variant.name).collect();
+    assert_eq!(names, ["Clear", "Put", "Tag", "Get", "Scan"]);
+
+    let clear = schema.variant("Clear").unwrap();
+    assert_eq!(clear.shape, Shape::Unit);
+    assert!(clear.fields.is_empty());
+    assert_eq!(clear.reply, None);
+}
+
+#[test]
+fn field_types_are_recorded_as_written() {
+    let put = StoreMsg::SCHEMA.variant("Put").unwrap();
+    assert_eq!(put.shape, Shape::Tuple);
+    assert_eq!(
+        put.fields,
+        [
+            FieldSchema {
+                name: None,
+                ty: "String"
+            },
+            FieldSchema {
+                name: None,
+                ty: "Vec<u8>"
+            },
+        ]
+    );
+
+    let tag = StoreMsg::SCHEMA.variant("Tag").unwrap();
+    assert_eq!(tag.shape, Shape::Struct);
+    assert_eq!(tag.fields[1].name, Some("tags"));
+    assert_eq!(tag.fields[1].ty, "HashMap<String, Option<u32>>");
+}
+
+#[test]
+fn requests_record_their_reply_without_the_reply_channel() {
+    let get = StoreMsg::SCHEMA.variant("Get").unwrap();
+    assert_eq!(get.fields.len(), 1);
+    assert_eq!(get.reply, Some(ReplySchema::Single("Option<Vec<u8>>")));
+
+    let scan = StoreMsg::SCHEMA.variant("Scan").unwrap();
+    assert_eq!(scan.shape, Shape::Unit);
+    assert_eq!(scan.reply, Some(ReplySchema::Stream("(String, usize)")));
+}
+
+#[test]
+fn schemas_render_as_json() {
+    #[message]
+    enum PingMsg {
+        Ping(u64),
+        #[request(reply = &'static str)]
+        Echo {
+            text: String,
+        },
+    }
+
+    assert_eq!(
+        PingMsg::SCHEMA.to_json(),
+        concat!(
+            r#"{"name":"PingMsg","version":null,"serde":false,"variants":["#,
+            r#"{"name":"Ping","shape":"tuple","fields":[{"name":null,"type":"u64"}],"reply":null},"#,
+            r#"{"name":"Echo","shape":"struct","fields":[{"name":"text","type":"String"}],"#,
+            r#""reply":{"kind":"single","type":"&'static str"}}]}"#,
+        )
+    );
+}
@@ -0,0 +1,162 @@
+//! Integration tests for schema versions of serialized messages.
+
+#![cfg(feature = "serde")]
+
+use notizia::core::Versioned;
+use notizia::core::schema::Describe;
+use notizia::message;
+use notizia::serde::{Deserialize, Deserializer};
+use serde_json::json;
+
+/// The current protocol
+#[message(version = 2, compat = upcast)]
+#[derive(Debug, PartialEq)]
+enum OrderMsg {
+    Place { id: u32, quantity: u32 },
+}
+
+fn upcast<'de, D>(version: Option<u32>, payload: D) -> Result<OrderMsg, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    match version {
+        Some(1) => match OrderMsgV1::deserialize(payload)? {
+            OrderMsgV1::Place { id } => Ok(OrderMsg::Place { id, quantity: 1 }),
+        },
+        Some(version) => Err(notizia::core::version::unsupported(version, 2)),
+        None => Err(notizia::serde::de::Error::custom("untagged")),
+    }
+}
+
+/// The protocol before quantities were added
+#[message(version = 1)]
+#[derive(Debug, PartialEq)]
+enum OrderMsgV1 {
+    Place { id: u32 },
+}
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum Unversioned {
+    Ping,
+}
+
+#[test]
+fn versions_are_declared_by_the_attribute() {
+    assert_eq!(OrderMsg::VERSION, Some(2));
+    assert_eq!(OrderMsgV1::VERSION, Some(1));
+    assert_eq!(Unversioned::VERSION, None);
+
+    // And recorded in the schema
+    assert_eq!(OrderMsg::SCHEMA.version, Some(2));
+    const { assert!(OrderMsg::SCHEMA.serde) };
+}
+
+#[test]
+fn current_versions_decode_directly() {
+    let payload = json!({"Place": {"id": 7, "quantity": 3}});
+    assert_eq!(
+        OrderMsg::upcast(Some(2), payload).unwrap(),
+        OrderMsg::Place { id: 7, quantity: 3 }
+    );
+}
+
+#[test]
+fn older_versions_go_through_compat() {
+    let payload = json!({"Place": {"id": 7}});
+    assert_eq!(
+        OrderMsg::upcast(Some(1), payload).unwrap(),
+        OrderMsg::Place { id: 7, quantity: 1 }
+    );
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let payload = json!({"Place": {"id": 7, "quantity": 3}});
+    let err = OrderMsg::upcast(Some(3), payload).unwrap_err();
+    assert_eq!(err.to_string(), "unsupported message version 3, expected 2");
+}
+
+#[test]
+fn other_versions_are_rejected_without_compat() {
+    // Would decode fine, but was written by another schema
+    let payload = json!({"Place": {"id": 7}});
+    assert!(OrderMsgV1::upcast(Some(0), payload.clone()).is_err());
+    let err = OrderMsgV1::upcast(None, payload).unwrap_err();
+    assert_eq!(err.to_string(), "message without version, expected 1");
+}
+
+#[test]
+fn unversioned_messages_ignore_tags() {
+    assert_eq!(
+        Unversioned::upcast(Some(4), json!("Ping")).unwrap(),
+        Unversioned::Ping
+    );
+}
+
+#[cfg(feature = "remote")]
+mod remote {
+    use super::*;
+    use notizia::prelude::*;
+    use notizia::registry::Registry;
+    use notizia::remote::{RemoteListener, RemoteRef};
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    /// A protocol from the future
+    #[message(version = 3)]
+    #[derive(Debug)]
+    enum OrderMsgV3 {
+        Place { id: u32, quantity: u32 },
+    }
+
+    #[derive(Task)]
+    #[task(message = OrderMsg)]
+    struct Orders {
+        seen: mpsc::UnboundedSender<OrderMsg>,
+    }
+
+    impl Runnable<OrderMsg> for Orders {
+        async fn start(&self) {
+            while let Ok(msg) = recv!(self) {
+                self.seen.send(msg).unwrap();
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn frames_are_upcast_or_dropped_by_version() {
+        let registry = Registry::new();
+        let (seen, mut rx) = mpsc::unbounded_channel();
+        let orders = Orders { seen };
+        let orders = spawn!(orders);
+        registry.register("orders", &orders.this());
+
+        let listener = RemoteListener::bind("127.0.0.1:0", &registry)
+            .await
+            .unwrap()
+            .expose::<OrderMsg>("orders");
+        let addr = listener.local_addr().unwrap();
+        let _listener = listener.spawn();
+
+        // Nodes running each version of the protocol
+        RemoteRef::<OrderMsgV1>::new(addr, "orders")
+            .send(OrderMsgV1::Place { id: 1 })
+            .unwrap();
+        RemoteRef::<OrderMsgV3>::new(addr, "orders")
+            .send(OrderMsgV3::Place { id: 3, quantity: 3 })
+            .unwrap();
+        RemoteRef::<OrderMsg>::new(addr, "orders")
+            .send(OrderMsg::Place { id: 2, quantity: 2 })
+            .unwrap();
+
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(2), rx.recv())
+                .await
+                .ok()
+                .flatten()
+        };
+        assert_eq!(next().await, Some(OrderMsg::Place { id: 1, quantity: 1 }));
+        assert_eq!(next().await, Some(OrderMsg::Place { id: 2, quantity: 2 }));
+    }
+}
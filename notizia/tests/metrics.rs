@@ -0,0 +1,187 @@
+//! Integration tests for the `metrics` feature.
+
+#![cfg(feature = "metrics")]
+
+use metrics::{
+    Counter, CounterFn, Gauge, GaugeFn, Histogram, HistogramFn, Key, KeyName, Metadata, Recorder,
+    SharedString, Unit,
+};
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+type Values = Arc<Mutex<HashMap<String, Vec<f64>>>>;
+
+/// Recorder remembering every value reported for a metric
+#[derive(Default)]
+struct Store(Values);
+
+/// A single metric, identified by its name and `task` label
+struct Cell {
+    key: String,
+    values: Values,
+}
+
+impl Cell {
+    fn push(&self, value: f64) {
+        let mut values = self.values.lock().unwrap();
+        values.entry(self.key.clone()).or_default().push(value);
+    }
+}
+
+impl CounterFn for Cell {
+    fn increment(&self, value: u64) {
+        self.push(value as f64);
+    }
+
+    fn absolute(&self, value: u64) {
+        self.push(value as f64);
+    }
+}
+
+impl GaugeFn for Cell {
+    fn increment(&self, value: f64) {
+        self.push(value);
+    }
+
+    fn decrement(&self, value: f64) {
+        self.push(-value);
+    }
+
+    fn set(&self, value: f64) {
+        self.push(value);
+    }
+}
+
+impl HistogramFn for Cell {
+    fn record(&self, value: f64) {
+        self.push(value);
+    }
+}
+
+impl Store {
+    fn cell(&self, key: &Key) -> Arc<Cell> {
+        let task = key
+            .labels()
+            .find(|label| label.key() == "task")
+            .map(|label| label.value().to_string())
+            .unwrap_or_default();
+
+        Arc::new(Cell {
+            key: format!("{}{{{}}}", key.name(), task),
+            values: self.0.clone(),
+        })
+    }
+}
+
+impl Recorder for Store {
+    fn describe_counter(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_gauge(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn describe_histogram(&self, _: KeyName, _: Option<Unit>, _: SharedString) {}
+
+    fn register_counter(&self, key: &Key, _: &Metadata<'_>) -> Counter {
+        Counter::from_arc(self.cell(key))
+    }
+
+    fn register_gauge(&self, key: &Key, _: &Metadata<'_>) -> Gauge {
+        Gauge::from_arc(self.cell(key))
+    }
+
+    fn register_histogram(&self, key: &Key, _: &Metadata<'_>) -> Histogram {
+        Histogram::from_arc(self.cell(key))
+    }
+}
+
+/// Values reported for `metric` of `task` by any test
+fn values(metric: &str, task: &str) -> Vec<f64> {
+    static VALUES: OnceLock<Values> = OnceLock::new();
+    let values = VALUES.get_or_init(|| {
+        let store = Store::default();
+        let values = store.0.clone();
+        metrics::set_global_recorder(store).unwrap();
+        values
+    });
+
+    let values = values.lock().unwrap();
+    values
+        .get(&format!("{metric}{{{task}}}"))
+        .cloned()
+        .unwrap_or_default()
+}
+
+#[derive(Task)]
+#[task(message = u64)]
+struct Sleeper {
+    done: mpsc::UnboundedSender<()>,
+}
+
+impl Runnable<u64> for Sleeper {
+    async fn start(&self) {
+        while let Ok(millis) = recv!(self) {
+            tokio::time::sleep(Duration::from_millis(millis)).await;
+            self.done.send(()).unwrap();
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = ())]
+struct Crasher;
+
+impl Runnable<()> for Crasher {
+    async fn start(&self) {
+        panic!("boom");
+    }
+}
+
+#[tokio::test]
+async fn messages_are_counted_and_timed() {
+    // Install the recorder before spawning
+    values("", "");
+    let (tx, mut done) = mpsc::unbounded_channel();
+    let handle = Sleeper { done: tx }.builder().name("sleeper").spawn();
+
+    handle.send(20).unwrap();
+    handle.send(0).unwrap();
+    handle.send(0).unwrap();
+    for _ in 0..3 {
+        done.recv().await.unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    let processed = values("notizia_messages_processed_total", "sleeper");
+    assert_eq!(processed.iter().sum::<f64>(), 3.0);
+
+    // Two messages were still queued behind the first one
+    let depth = values("notizia_mailbox_depth", "sleeper");
+    assert_eq!(depth.first(), Some(&2.0));
+    assert_eq!(depth.last(), Some(&0.0));
+
+    let durations = values("notizia_handler_duration_seconds", "sleeper");
+    assert_eq!(durations.len(), 3);
+    assert!(durations[0] >= 0.02);
+}
+
+#[tokio::test]
+async fn panics_and_restarts_are_counted() {
+    // Install the recorder before spawning
+    values("", "");
+    let registry = Registry::new();
+
+    for _ in 0..3 {
+        let handle = Crasher.builder().name("crasher").spawn();
+        registry.register("crasher", &handle.this());
+        assert!(matches!(handle.join().await, Ok(TerminateReason::Panic(_))));
+    }
+
+    let panics = values("notizia_task_panics_total", "crasher");
+    assert_eq!(panics.iter().sum::<f64>(), 3.0);
+
+    let restarts = values("notizia_task_restarts_total", "crasher");
+    assert_eq!(restarts.iter().sum::<f64>(), 2.0);
+}
@@ -0,0 +1,149 @@
+//! Integration tests for message middleware.
+
+use notizia::prelude::*;
+use notizia::task::Middleware;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// Records every hook call, tagged with its name
+struct Recording {
+    tag: &'static str,
+    log: Log,
+}
+
+impl Middleware<u32> for Recording {
+    fn before_handle(&self, msg: &u32) -> bool {
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} before {msg}", self.tag));
+        true
+    }
+
+    fn after_handle(&self, elapsed: Duration) {
+        let millis = elapsed.as_millis();
+        self.log
+            .lock()
+            .unwrap()
+            .push(format!("{} after {}", self.tag, millis >= 20));
+    }
+}
+
+/// Drops odd numbers
+struct EvenOnly;
+
+impl Middleware<u32> for EvenOnly {
+    fn before_handle(&self, msg: &u32) -> bool {
+        msg.is_multiple_of(2)
+    }
+}
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Sleeper {
+    handled: mpsc::UnboundedSender<u32>,
+}
+
+impl Runnable<u32> for Sleeper {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            tokio::time::sleep(Duration::from_millis(msg as u64)).await;
+            self.handled.send(msg).unwrap();
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = u32, handler)]
+struct Collector {
+    handled: mpsc::UnboundedSender<u32>,
+}
+
+impl Handler<u32> for Collector {
+    async fn handle(&mut self, msg: u32, _ctx: &mut Context<u32>) {
+        self.handled.send(msg).unwrap();
+    }
+}
+
+#[tokio::test]
+async fn hooks_run_around_each_message_in_order() {
+    let log = Log::default();
+    let (tx, mut handled) = mpsc::unbounded_channel();
+
+    let handle = Sleeper { handled: tx }
+        .builder()
+        .middleware(Recording {
+            tag: "outer",
+            log: log.clone(),
+        })
+        .middleware(Recording {
+            tag: "inner",
+            log: log.clone(),
+        })
+        .spawn();
+
+    handle.send(30).unwrap();
+    handle.send(0).unwrap();
+    assert_eq!(handled.recv().await, Some(30));
+    assert_eq!(handled.recv().await, Some(0));
+
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        vec![
+            "outer before 30",
+            "inner before 30",
+            "inner after true",
+            "outer after true",
+            "outer before 0",
+            "inner before 0",
+            "inner after false",
+            "outer after false",
+        ]
+    );
+}
+
+#[tokio::test]
+async fn rejected_messages_are_dropped() {
+    let log = Log::default();
+    let (tx, mut handled) = mpsc::unbounded_channel();
+
+    let handle = Collector { handled: tx }
+        .builder()
+        .middleware(EvenOnly)
+        .middleware(Recording {
+            tag: "log",
+            log: log.clone(),
+        })
+        .spawn();
+
+    for msg in 1..=4 {
+        handle.send(msg).unwrap();
+    }
+    drop(handle);
+
+    let mut received = Vec::new();
+    while let Some(msg) = handled.recv().await {
+        received.push(msg);
+    }
+    assert_eq!(received, vec![2, 4]);
+
+    // Later layers never see rejected messages
+    let log = log.lock().unwrap();
+    assert_eq!(log.iter().filter(|line| line.contains("before")).count(), 2);
+    assert_eq!(log.iter().filter(|line| line.contains("after")).count(), 2);
+}
+
+#[tokio::test]
+async fn tasks_without_middleware_are_unaffected() {
+    let (tx, mut handled) = mpsc::unbounded_channel();
+    let collector = Collector { handled: tx };
+    let handle = spawn!(collector);
+
+    handle.send(1).unwrap();
+    assert_eq!(handled.recv().await, Some(1));
+}
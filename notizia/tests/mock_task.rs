@@ -0,0 +1,81 @@
+//! Integration tests for `MockTask`.
+
+#![cfg(feature = "testing")]
+
+use notizia::prelude::*;
+use notizia::testing::{MockTask, TestProbe};
+use notizia::{call, message};
+
+#[message]
+#[derive(Debug)]
+enum CounterMsg {
+    Increment(u32),
+    #[request(reply = u32)]
+    GetCount,
+    #[request(reply = String)]
+    Describe,
+}
+
+fn count(value: u32) -> impl FnMut(CounterMsg) + Send + 'static {
+    move |msg| {
+        if let CounterMsg::GetCount { reply_to } = msg {
+            let _ = reply_to.reply(value);
+        }
+    }
+}
+
+#[tokio::test]
+async fn first_matching_rule_answers() {
+    let counter = MockTask::new()
+        .on(|msg| matches!(msg, CounterMsg::GetCount { .. }), count(42))
+        .on(|msg| matches!(msg, CounterMsg::GetCount { .. }), count(7))
+        .spawn();
+
+    assert_eq!(call!(counter, CounterMsg::GetCount).await.unwrap(), 42);
+    assert_eq!(call!(counter, CounterMsg::GetCount).await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn responses_keep_state_between_messages() {
+    let probe = TestProbe::new();
+    let seen = probe.this();
+    let mut total = 0;
+
+    let counter = MockTask::new()
+        .on(
+            |msg| matches!(msg, CounterMsg::Increment(_)),
+            move |msg| {
+                if let CounterMsg::Increment(n) = msg {
+                    total += n;
+                    seen.send(total).unwrap();
+                }
+            },
+        )
+        .spawn();
+
+    counter.send(CounterMsg::Increment(2)).unwrap();
+    counter.send(CounterMsg::Increment(3)).unwrap();
+    probe.expect_ordered([2, 5], 100).await;
+}
+
+#[tokio::test]
+async fn unmatched_messages_use_the_fallback() {
+    let counter = MockTask::new()
+        .on(|msg| matches!(msg, CounterMsg::GetCount { .. }), count(1))
+        .otherwise(|msg| {
+            if let CounterMsg::Describe { reply_to } = msg {
+                let _ = reply_to.reply("mock".to_string());
+            }
+        })
+        .spawn();
+
+    assert_eq!(call!(counter, CounterMsg::Describe).await.unwrap(), "mock");
+}
+
+#[tokio::test]
+async fn unmatched_messages_are_dropped_without_fallback() {
+    let counter = MockTask::new().spawn();
+
+    let result = call!(counter, CounterMsg::GetCount, timeout = 1000).await;
+    assert!(matches!(result, Err(CallError::NoReply)));
+}
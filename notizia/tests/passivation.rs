@@ -0,0 +1,170 @@
+//! Integration tests for idle passivation.
+
+use notizia::prelude::*;
+use notizia::sharding::ShardRegion;
+use notizia::virtual_actors::{VirtualActor, VirtualActors};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, Clone)]
+enum Msg {
+    Work(u32),
+}
+
+#[derive(Task)]
+#[task(message = Msg)]
+struct Worker {
+    handled: Arc<AtomicU32>,
+    reasons: Arc<Mutex<Vec<TerminateReason>>>,
+}
+
+impl Runnable<Msg> for Worker {
+    async fn start(&self) {
+        while let Ok(Msg::Work(n)) = recv!(self) {
+            self.handled.fetch_add(n, Ordering::SeqCst);
+        }
+    }
+
+    async fn terminate(&self, reason: TerminateReason) {
+        self.reasons.lock().unwrap().push(reason);
+    }
+}
+
+fn worker() -> (Worker, Arc<AtomicU32>, Arc<Mutex<Vec<TerminateReason>>>) {
+    let handled = Arc::new(AtomicU32::new(0));
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let worker = Worker {
+        handled: handled.clone(),
+        reasons: reasons.clone(),
+    };
+    (worker, handled, reasons)
+}
+
+#[tokio::test]
+async fn idle_task_is_passivated() {
+    let (task, _, reasons) = worker();
+    let handle = spawn!(task);
+
+    // The task is already waiting on its mailbox when the timeout is set
+    sleep(Duration::from_millis(10)).await;
+    handle.passivate_after(Duration::from_millis(20));
+
+    let reason = handle.join().await.unwrap();
+
+    assert_eq!(reason, TerminateReason::Idle);
+    assert_eq!(*reasons.lock().unwrap(), vec![TerminateReason::Idle]);
+}
+
+#[tokio::test]
+async fn messages_reset_the_idle_timeout() {
+    let (task, handled, _) = worker();
+    let handle = spawn!(task);
+    handle.passivate_after(Duration::from_millis(50));
+
+    for _ in 0..4 {
+        sleep(Duration::from_millis(20)).await;
+        handle.send(Msg::Work(1)).unwrap();
+    }
+
+    sleep(Duration::from_millis(10)).await;
+    assert!(!handle.is_finished());
+    assert!(!handle.is_passivated());
+    assert_eq!(handled.load(Ordering::SeqCst), 4);
+
+    sleep(Duration::from_millis(100)).await;
+    assert!(handle.is_passivated());
+    assert!(handle.send(Msg::Work(1)).is_err());
+}
+
+#[tokio::test]
+async fn task_without_idle_timeout_is_not_passivated() {
+    let (task, _, _) = worker();
+    let handle = spawn!(task);
+
+    sleep(Duration::from_millis(50)).await;
+
+    assert!(!handle.is_finished());
+    assert!(!handle.is_passivated());
+    assert_eq!(
+        handle.shutdown(Duration::from_secs(1)).await.unwrap(),
+        TerminateReason::Normal
+    );
+}
+
+#[tokio::test]
+async fn passivated_entity_is_reactivated_on_next_message() {
+    let handled = Arc::new(AtomicU32::new(0));
+    let reasons = Arc::new(Mutex::new(Vec::new()));
+    let spawned = Arc::new(AtomicU32::new(0));
+
+    let region = {
+        let handled = handled.clone();
+        let reasons = reasons.clone();
+        let spawned = spawned.clone();
+        ShardRegion::new(move |_: &u32| {
+            spawned.fetch_add(1, Ordering::SeqCst);
+            Worker {
+                handled: handled.clone(),
+                reasons: reasons.clone(),
+            }
+        })
+        .passivate_after(Duration::from_millis(20))
+    };
+
+    region.send(1, Msg::Work(1)).unwrap();
+    sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(*reasons.lock().unwrap(), vec![TerminateReason::Idle]);
+    assert_eq!(region.prune(), 1);
+
+    region.send(1, Msg::Work(2)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(spawned.load(Ordering::SeqCst), 2);
+    assert_eq!(handled.load(Ordering::SeqCst), 3);
+}
+
+static SESSIONS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Session {
+    id: u32,
+}
+
+impl VirtualActor for Session {
+    type Id = u32;
+    type Message = ();
+
+    async fn activate(id: u32) -> Self {
+        SESSIONS.lock().unwrap().push(format!("activate {id}"));
+        Session { id }
+    }
+
+    async fn handle(&mut self, _msg: ()) {}
+
+    async fn deactivate(&mut self) {
+        SESSIONS
+            .lock()
+            .unwrap()
+            .push(format!("deactivate {}", self.id));
+    }
+}
+
+#[tokio::test]
+async fn idle_virtual_actor_is_deactivated() {
+    let sessions = VirtualActors::<Session>::new().passivate_after(Duration::from_millis(20));
+
+    sessions.send(1, ()).unwrap();
+    sleep(Duration::from_millis(60)).await;
+
+    assert_eq!(sessions.active_count(), 0);
+
+    sessions.send(1, ()).unwrap();
+    sleep(Duration::from_millis(5)).await;
+
+    assert!(sessions.is_active(&1));
+    assert_eq!(
+        *SESSIONS.lock().unwrap(),
+        vec!["activate 1", "deactivate 1", "activate 1"]
+    );
+}
@@ -0,0 +1,270 @@
+//! Integration tests for event-sourced tasks.
+
+#![cfg(feature = "persistence")]
+
+use std::time::Duration;
+
+use notizia::persistence::{
+    FileJournal, Journal, JournalBackend, JournalError, MemoryJournal, Persistent, Snapshots,
+};
+use notizia::prelude::*;
+use notizia::{call, message};
+use serde::{Deserialize, Serialize};
+
+#[message]
+enum AccountMsg {
+    Deposit(u64),
+    #[request(reply = u64)]
+    Balance,
+    Crash,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum AccountEvent {
+    Deposited(u64),
+}
+
+#[derive(Task)]
+#[task(message = AccountMsg, handler, persistent)]
+struct Account {
+    journal: Journal<AccountEvent>,
+    balance: u64,
+}
+
+impl Account {
+    fn new(journal: &Journal<AccountEvent>) -> Self {
+        Account {
+            journal: journal.clone(),
+            balance: 0,
+        }
+    }
+}
+
+impl Persistent for Account {
+    type Event = AccountEvent;
+
+    fn journal(&self) -> Journal<AccountEvent> {
+        self.journal.clone()
+    }
+
+    fn apply(&mut self, event: AccountEvent) {
+        match event {
+            AccountEvent::Deposited(amount) => self.balance += amount,
+        }
+    }
+}
+
+impl Handler<AccountMsg> for Account {
+    async fn handle(&mut self, msg: AccountMsg, ctx: &mut Context<AccountMsg>) {
+        match msg {
+            AccountMsg::Deposit(amount) => {
+                let event = AccountEvent::Deposited(amount);
+                ctx.persist(&event).await.unwrap();
+                self.apply(event);
+            }
+            AccountMsg::Balance { reply_to } => {
+                let _ = reply_to.reply(self.balance);
+            }
+            AccountMsg::Crash => panic!("crashed"),
+        }
+    }
+}
+
+async fn deposit(account: &TaskHandle<AccountMsg>, amounts: &[u64]) -> u64 {
+    for amount in amounts {
+        account.send(AccountMsg::Deposit(*amount)).unwrap();
+    }
+    call!(account, AccountMsg::Balance).await.unwrap()
+}
+
+#[message]
+enum LedgerMsg {
+    Add(u64),
+    /// The total, and the number of events applied since the task started
+    #[request(reply = (u64, usize))]
+    Stats,
+}
+
+/// An account that snapshots its total
+#[derive(Task)]
+#[task(message = LedgerMsg, handler, persistent, snapshots)]
+struct Ledger {
+    journal: Journal<AccountEvent>,
+    total: u64,
+    applied: usize,
+}
+
+impl Ledger {
+    fn new(journal: &Journal<AccountEvent>) -> Self {
+        Ledger {
+            journal: journal.clone(),
+            total: 0,
+            applied: 0,
+        }
+    }
+}
+
+impl Persistent for Ledger {
+    type Event = AccountEvent;
+
+    fn journal(&self) -> Journal<AccountEvent> {
+        self.journal.clone()
+    }
+
+    fn apply(&mut self, event: AccountEvent) {
+        let AccountEvent::Deposited(amount) = event;
+        self.total += amount;
+        self.applied += 1;
+    }
+}
+
+impl Snapshots for Ledger {
+    type Snapshot = u64;
+
+    fn snapshot(&self) -> u64 {
+        self.total
+    }
+
+    fn restore(&mut self, total: u64) {
+        self.total = total;
+    }
+}
+
+impl Handler<LedgerMsg> for Ledger {
+    async fn handle(&mut self, msg: LedgerMsg, ctx: &mut Context<LedgerMsg>) {
+        match msg {
+            LedgerMsg::Add(amount) => {
+                let event = AccountEvent::Deposited(amount);
+                ctx.persist(&event).await.unwrap();
+                self.apply(event);
+            }
+            LedgerMsg::Stats { reply_to } => {
+                let _ = reply_to.reply((self.total, self.applied));
+            }
+        }
+    }
+}
+
+async fn restart_ledger(journal: &Journal<AccountEvent>, amounts: &[u64]) -> (u64, usize) {
+    let ledger = Ledger::new(journal).run();
+    for amount in amounts {
+        ledger.send(LedgerMsg::Add(*amount)).unwrap();
+    }
+    let stats = call!(ledger, LedgerMsg::Stats).await.unwrap();
+    ledger.shutdown(Duration::from_secs(1)).await.unwrap();
+    stats
+}
+
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("notizia-{}-{name}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[tokio::test]
+async fn restarted_task_replays_its_events() {
+    let journal = Journal::new(MemoryJournal::new(), "account-1");
+
+    let account = Account::new(&journal).run();
+    assert_eq!(deposit(&account, &[10, 20]).await, 30);
+    account.send(AccountMsg::Crash).unwrap();
+    assert!(matches!(
+        account.join().await.unwrap(),
+        TerminateReason::Panic(_)
+    ));
+
+    let account = Account::new(&journal).run();
+    assert_eq!(deposit(&account, &[5]).await, 35);
+    account.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    let events = journal.read(1).await.unwrap();
+    assert_eq!(
+        events,
+        vec![
+            (1, AccountEvent::Deposited(20)),
+            (2, AccountEvent::Deposited(5))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn journals_are_separated_by_id() {
+    let backend = MemoryJournal::new();
+    let first = Account::new(&Journal::new(backend.clone(), "first")).run();
+    let second = Account::new(&Journal::new(backend.clone(), "second")).run();
+
+    assert_eq!(deposit(&first, &[1, 2]).await, 3);
+    assert_eq!(deposit(&second, &[7]).await, 7);
+}
+
+#[tokio::test]
+async fn file_journal_survives_reopening() {
+    let dir = temp_dir("file-journal");
+
+    let journal = Journal::new(FileJournal::open(&dir).unwrap(), "account/7");
+    let account = Account::new(&journal).run();
+    assert_eq!(deposit(&account, &[100, 50]).await, 150);
+    account.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    let journal = Journal::new(FileJournal::open(&dir).unwrap(), "account/7");
+    let account = Account::new(&journal).run();
+    assert_eq!(deposit(&account, &[1]).await, 151);
+    account.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn backends_reject_conflicting_appends() {
+    let dir = temp_dir("conflicts");
+    let backends: [Box<dyn JournalBackend>; 2] = [
+        Box::new(MemoryJournal::new()),
+        Box::new(FileJournal::open(&dir).unwrap()),
+    ];
+
+    for backend in backends {
+        backend.append("id", 0, b"1".to_vec()).await.unwrap();
+        let conflict = backend.append("id", 0, b"2".to_vec()).await;
+        assert!(matches!(
+            conflict,
+            Err(JournalError::Conflict { seq: 0, .. })
+        ));
+
+        let gap = backend.append("id", 2, b"3".to_vec()).await;
+        assert!(matches!(gap, Err(JournalError::Conflict { seq: 2, .. })));
+        assert_eq!(
+            backend.read("id", 0).await.unwrap(),
+            vec![(0, b"1".to_vec())]
+        );
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn recovery_replays_events_after_the_latest_snapshot() {
+    let dir = temp_dir("snapshots");
+    let journals = [
+        Journal::new(MemoryJournal::new(), "ledger").snapshot_every(2),
+        Journal::new(FileJournal::open(&dir).unwrap(), "ledger").snapshot_every(2),
+    ];
+
+    for journal in journals {
+        // Snapshots after the second and fourth event
+        assert_eq!(restart_ledger(&journal, &[1, 2, 3, 4, 5]).await, (15, 5));
+
+        // Restores the total of the first four events, then replays the fifth
+        assert_eq!(restart_ledger(&journal, &[]).await, (15, 1));
+        assert_eq!(journal.read(0).await.unwrap().len(), 5);
+    }
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[tokio::test]
+async fn tasks_without_snapshots_replay_all_events() {
+    let journal = Journal::new(MemoryJournal::new(), "ledger");
+
+    assert_eq!(restart_ledger(&journal, &[1, 2, 3]).await, (6, 3));
+    assert_eq!(restart_ledger(&journal, &[]).await, (6, 3));
+}
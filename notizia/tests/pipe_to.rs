@@ -0,0 +1,68 @@
+//! Integration tests for `Context::pipe_to`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use tokio::sync::oneshot;
+
+enum Msg {
+    Fetch(u32, oneshot::Receiver<u32>),
+    Fetched(u32, u32),
+    Note(&'static str),
+}
+
+/// Fetches in the background, recording what it handles.
+#[derive(Task, Default)]
+#[task(message = Msg, handler)]
+struct Fetcher {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Handler<Msg> for Fetcher {
+    async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+        let entry = match msg {
+            Msg::Fetch(id, response) => {
+                ctx.pipe_to(async move { response.await.unwrap() }, move |value| {
+                    Msg::Fetched(id, value)
+                });
+                format!("fetch {id}")
+            }
+            Msg::Fetched(id, value) => format!("fetched {id}: {value}"),
+            Msg::Note(note) => note.to_string(),
+        };
+        self.log.lock().unwrap().push(entry);
+    }
+}
+
+#[tokio::test]
+async fn piped_results_arrive_as_messages() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let fetcher = Fetcher { log: log.clone() }.run();
+
+    let (respond, response) = oneshot::channel();
+    fetcher.send(Msg::Fetch(1, response)).unwrap();
+    // Handled while the fetch is still running
+    fetcher.send(Msg::Note("busy")).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    respond.send(42).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    fetcher.signal(SystemSignal::Stop).unwrap();
+    fetcher.join().await.unwrap();
+    assert_eq!(*log.lock().unwrap(), ["fetch 1", "busy", "fetched 1: 42"]);
+}
+
+#[tokio::test]
+async fn pending_pipes_do_not_keep_the_task_alive() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let fetcher = Fetcher { log: log.clone() }.run();
+
+    let (_respond, response) = oneshot::channel();
+    fetcher.send(Msg::Fetch(1, response)).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    let result = fetcher.shutdown(Duration::from_secs(1)).await;
+    assert!(matches!(result, Ok(TerminateReason::Normal)));
+}
@@ -0,0 +1,200 @@
+//! Integration tests for staged processing pipelines.
+
+use notizia::pipeline::{Pipeline, Stage};
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::sync::Mutex;
+use tokio::time::{Duration, sleep};
+
+struct Parse;
+
+impl Stage for Parse {
+    type In = String;
+    type Out = u32;
+
+    async fn process(&self, input: String) -> u32 {
+        input.parse().expect("invalid number")
+    }
+}
+
+struct Square;
+
+impl Stage for Square {
+    type In = u32;
+    type Out = u64;
+
+    async fn process(&self, input: u32) -> u64 {
+        u64::from(input) * u64::from(input)
+    }
+}
+
+struct Format;
+
+impl Stage for Format {
+    type In = u64;
+    type Out = String;
+
+    async fn process(&self, input: u64) -> String {
+        format!("={input}")
+    }
+}
+
+#[tokio::test]
+async fn values_flow_through_all_stages_in_order() {
+    let pipeline = Pipeline::new()
+        .stage(Parse, 1)
+        .stage(Square, 1)
+        .stage(Format, 1)
+        .build();
+
+    assert_eq!(pipeline.stages(), 3);
+
+    for i in 1..=5 {
+        pipeline.send(i.to_string()).unwrap();
+    }
+
+    for i in 1u64..=5 {
+        assert_eq!(pipeline.recv().await.unwrap(), format!("={}", i * i));
+    }
+
+    let reason = pipeline.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(reason, TerminateReason::Normal);
+}
+
+#[tokio::test]
+async fn empty_pipeline_passes_values_through() {
+    let pipeline = Pipeline::<u32, u32>::new().build();
+
+    pipeline.send(7).unwrap();
+
+    assert_eq!(pipeline.recv().await.unwrap(), 7);
+    assert_eq!(pipeline.stages(), 0);
+}
+
+struct Slow {
+    active: Arc<AtomicU32>,
+    peak: Arc<AtomicU32>,
+}
+
+impl Stage for Slow {
+    type In = u32;
+    type Out = u32;
+
+    async fn process(&self, input: u32) -> u32 {
+        let active = self.active.fetch_add(1, Ordering::SeqCst) + 1;
+        self.peak.fetch_max(active, Ordering::SeqCst);
+        sleep(Duration::from_millis(50)).await;
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        input
+    }
+}
+
+#[tokio::test]
+async fn stage_workers_process_concurrently() {
+    let peak = Arc::new(AtomicU32::new(0));
+    let pipeline = Pipeline::new()
+        .stage(
+            Slow {
+                active: Arc::new(AtomicU32::new(0)),
+                peak: peak.clone(),
+            },
+            4,
+        )
+        .build();
+
+    for i in 0..8 {
+        pipeline.send(i).unwrap();
+    }
+
+    let mut results = Vec::new();
+    for _ in 0..8 {
+        results.push(pipeline.recv().await.unwrap());
+    }
+    results.sort();
+
+    assert_eq!(results, (0..8).collect::<Vec<_>>());
+    assert_eq!(peak.load(Ordering::SeqCst), 4);
+}
+
+struct Record {
+    name: &'static str,
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Stage for Record {
+    type In = u32;
+    type Out = u32;
+
+    async fn process(&self, input: u32) -> u32 {
+        sleep(Duration::from_millis(10)).await;
+        self.log
+            .lock()
+            .await
+            .push(format!("{}:{}", self.name, input));
+        input
+    }
+}
+
+#[tokio::test]
+async fn shutdown_drains_stages_downstream() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let pipeline = Pipeline::new()
+        .stage(
+            Record {
+                name: "first",
+                log: log.clone(),
+            },
+            1,
+        )
+        .stage(
+            Record {
+                name: "second",
+                log: log.clone(),
+            },
+            1,
+        )
+        .build();
+
+    pipeline.send(1).unwrap();
+    pipeline.send(2).unwrap();
+
+    let reason = pipeline.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(reason, TerminateReason::Normal);
+
+    let log = log.lock().await;
+    assert_eq!(log.len(), 4, "all queued values should be processed");
+    assert!(log.contains(&"second:1".to_string()));
+    assert!(log.contains(&"second:2".to_string()));
+    assert_eq!(log.last().unwrap(), "second:2");
+}
+
+#[tokio::test]
+async fn shutdown_times_out_on_stuck_stage() {
+    let pipeline = Pipeline::new()
+        .stage(
+            Slow {
+                active: Arc::new(AtomicU32::new(0)),
+                peak: Arc::new(AtomicU32::new(0)),
+            },
+            1,
+        )
+        .build();
+
+    for i in 0..10 {
+        pipeline.send(i).unwrap();
+    }
+
+    let result = pipeline.shutdown(Duration::from_millis(60)).await;
+    assert!(matches!(result, Err(ShutdownError::Timeout)));
+}
+
+#[tokio::test]
+async fn shutdown_reports_panicking_stage() {
+    let pipeline = Pipeline::new().stage(Parse, 1).build();
+
+    pipeline.send("not a number".to_string()).unwrap();
+
+    let reason = pipeline.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert!(matches!(reason, TerminateReason::Panic(msg) if msg.contains("invalid number")));
+}
@@ -0,0 +1,94 @@
+//! Integration tests for recording and replaying message traces.
+
+#![cfg(feature = "record")]
+
+use notizia::prelude::*;
+use notizia::record::{Recorder, ReplayError, Trace};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+enum OrderMsg {
+    Place { id: u32 },
+    Cancel { id: u32 },
+}
+
+#[derive(Task)]
+#[task(message = OrderMsg)]
+struct Orders {
+    seen: mpsc::UnboundedSender<OrderMsg>,
+}
+
+impl Runnable<OrderMsg> for Orders {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+fn trace_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("notizia-{}-{name}.trace", std::process::id()))
+}
+
+fn messages() -> Vec<OrderMsg> {
+    vec![
+        OrderMsg::Place { id: 1 },
+        OrderMsg::Place { id: 2 },
+        OrderMsg::Cancel { id: 1 },
+    ]
+}
+
+async fn record(path: &Path, recorder: Recorder<OrderMsg>) {
+    let (seen, _rx) = mpsc::unbounded_channel();
+    let handle = Orders { seen }.builder().middleware(recorder).spawn();
+    for msg in messages() {
+        handle.send(msg).unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn recorded_trace_replays_in_order() {
+    let path = trace_path("replay");
+    record(&path, Recorder::serialized(&path).unwrap()).await;
+
+    let trace = Trace::load(&path).unwrap();
+    let entries = trace.entries();
+    assert_eq!(entries.len(), 3);
+    assert!(entries.iter().map(|entry| entry.seq).eq(0..3));
+    assert!(entries[0].message_type.ends_with("OrderMsg"));
+    assert!(entries[0].timestamp_micros <= entries[2].timestamp_micros);
+
+    let (seen, mut rx) = mpsc::unbounded_channel();
+    let orders = Orders { seen };
+    let handle = spawn!(orders);
+    trace.replay(&handle.this()).unwrap();
+
+    let mut replayed = Vec::new();
+    for _ in 0..3 {
+        replayed.push(rx.recv().await.unwrap());
+    }
+    assert_eq!(replayed, messages());
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn traces_without_payload_cannot_be_replayed() {
+    let path = trace_path("opaque");
+    record(&path, Recorder::new(&path).unwrap()).await;
+
+    let trace = Trace::load(&path).unwrap();
+    assert_eq!(trace.entries().len(), 3);
+    assert!(trace.entries().iter().all(|entry| entry.payload.is_none()));
+    assert!(matches!(
+        trace.messages::<OrderMsg>(),
+        Err(ReplayError::MissingPayload(0))
+    ));
+
+    std::fs::remove_file(path).unwrap();
+}
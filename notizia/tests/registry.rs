@@ -0,0 +1,257 @@
+//! Integration tests for the named task registry.
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::registry::{Conflict, NameTaken, Registry, RegistryEvent};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, Clone)]
+enum CountMsg {
+    Add(u32),
+}
+
+#[derive(Task)]
+#[task(message = CountMsg)]
+struct Counter {
+    total: Arc<AtomicU32>,
+}
+
+impl Runnable<CountMsg> for Counter {
+    async fn start(&self) {
+        while let Ok(CountMsg::Add(n)) = recv!(self) {
+            self.total.fetch_add(n, Ordering::SeqCst);
+        }
+    }
+}
+
+fn counter(total: &Arc<AtomicU32>) -> TaskHandle<CountMsg> {
+    let task = Counter {
+        total: total.clone(),
+    };
+    spawn!(task)
+}
+
+#[tokio::test]
+async fn registered_task_can_be_looked_up_by_name() {
+    let registry = Registry::new();
+    let total = Arc::new(AtomicU32::new(0));
+    let handle = counter(&total);
+
+    registry.register("counter", &handle.this());
+
+    let counter = registry.whereis::<CountMsg>("counter").unwrap();
+    counter.send(CountMsg::Add(3)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(total.load(Ordering::SeqCst), 3);
+    assert_eq!(registry.names(), vec!["counter".to_string()]);
+}
+
+#[tokio::test]
+async fn lookup_fails_for_unknown_name_or_wrong_type() {
+    let registry = Registry::new();
+    let handle = counter(&Arc::new(AtomicU32::new(0)));
+    registry.register("counter", &handle.this());
+
+    assert!(registry.whereis::<CountMsg>("missing").is_none());
+    assert!(registry.whereis::<String>("counter").is_none());
+}
+
+#[tokio::test]
+async fn registration_does_not_prevent_shutdown() {
+    let registry = Registry::new();
+    let handle = counter(&Arc::new(AtomicU32::new(0)));
+    registry.register("counter", &handle.this());
+
+    let result = handle.shutdown(Duration::from_secs(1)).await;
+
+    assert!(matches!(result, Ok(TerminateReason::Normal)));
+    assert!(registry.whereis::<CountMsg>("counter").is_none());
+}
+
+#[tokio::test]
+async fn reregistering_replaces_previous_task() {
+    let registry = Registry::new();
+    let first = Arc::new(AtomicU32::new(0));
+    let second = Arc::new(AtomicU32::new(0));
+
+    let old = counter(&first);
+    registry.register("counter", &old.this());
+
+    let new = counter(&second);
+    registry.register("counter", &new.this());
+
+    registry
+        .whereis::<CountMsg>("counter")
+        .unwrap()
+        .send(CountMsg::Add(1))
+        .unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    assert_eq!(first.load(Ordering::SeqCst), 0);
+    assert_eq!(second.load(Ordering::SeqCst), 1);
+
+    assert!(registry.unregister("counter"));
+    assert!(!registry.unregister("counter"));
+    assert!(registry.whereis::<CountMsg>("counter").is_none());
+}
+
+#[tokio::test]
+async fn conflicting_registration_can_be_rejected() {
+    let registry = Registry::new();
+    let mut events = registry.subscribe();
+    let total = Arc::new(AtomicU32::new(0));
+    let first = counter(&total);
+    let second = counter(&total);
+
+    registry
+        .register_with("counter", &first.this(), Conflict::Error)
+        .unwrap();
+    let err = registry
+        .register_with("counter", &second.this(), Conflict::Error)
+        .unwrap_err();
+    assert_eq!(
+        err,
+        NameTaken {
+            name: "counter".into(),
+            holder: first.id()
+        }
+    );
+    assert_eq!(
+        registry.whereis::<CountMsg>("counter").unwrap().id(),
+        first.id()
+    );
+
+    // Re-registering the holder is no conflict
+    registry
+        .register_with("counter", &first.this(), Conflict::Error)
+        .unwrap();
+
+    assert!(
+        matches!(events.recv().await.unwrap(), RegistryEvent::Registered { task, .. } if task == first.id())
+    );
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        RegistryEvent::Rejected { task, holder, .. } if task == second.id() && holder == first.id()
+    ));
+}
+
+#[tokio::test]
+async fn replacing_registration_stops_the_holder() {
+    let registry = Registry::new();
+    let mut events = registry.subscribe();
+    let total = Arc::new(AtomicU32::new(0));
+    let first = counter(&total);
+    let first_id = first.id();
+    let second = counter(&total);
+
+    registry.register("counter", &first.this());
+    registry
+        .register_with("counter", &second.this(), Conflict::Replace)
+        .unwrap();
+
+    assert_eq!(
+        registry.whereis::<CountMsg>("counter").unwrap().id(),
+        second.id()
+    );
+    tokio::time::timeout(Duration::from_secs(1), first.join())
+        .await
+        .unwrap()
+        .unwrap();
+
+    events.recv().await.unwrap();
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        RegistryEvent::Replaced { task, previous, .. } if task == second.id() && previous == first_id
+    ));
+    assert!(
+        matches!(events.recv().await.unwrap(), RegistryEvent::Registered { task, .. } if task == second.id())
+    );
+}
+
+#[tokio::test]
+async fn queued_registration_waits_for_the_holder_to_exit() {
+    let registry = Registry::new();
+    let mut events = registry.subscribe();
+    let total = Arc::new(AtomicU32::new(0));
+    let first = counter(&total);
+    let first_id = first.id();
+    let second = counter(&total);
+
+    registry.register("counter", &first.this());
+    registry
+        .register_with("counter", &second.this(), Conflict::QueueBehind)
+        .unwrap();
+    sleep(Duration::from_millis(30)).await;
+    assert_eq!(
+        registry.whereis::<CountMsg>("counter").unwrap().id(),
+        first_id
+    );
+
+    first.signal(SystemSignal::Stop).unwrap();
+    first.join().await.unwrap();
+    sleep(Duration::from_millis(30)).await;
+    assert_eq!(
+        registry.whereis::<CountMsg>("counter").unwrap().id(),
+        second.id()
+    );
+
+    events.recv().await.unwrap();
+    assert!(matches!(
+        events.recv().await.unwrap(),
+        RegistryEvent::Queued { task, holder, .. } if task == second.id() && holder == first_id
+    ));
+    assert!(
+        matches!(events.recv().await.unwrap(), RegistryEvent::Registered { task, .. } if task == second.id())
+    );
+}
+
+#[tokio::test]
+async fn queued_registrations_take_over_in_order() {
+    let registry = Registry::new();
+    let total = Arc::new(AtomicU32::new(0));
+    let holder = counter(&total);
+    let queued: Vec<_> = (0..3).map(|_| counter(&total)).collect();
+
+    registry.register("counter", &holder.this());
+    // Queue in reverse spawn order, so the order is not that of the ids
+    for task in queued.iter().rev() {
+        registry
+            .register_with("counter", &task.this(), Conflict::QueueBehind)
+            .unwrap();
+    }
+
+    let mut previous = holder;
+    for task in queued.into_iter().rev() {
+        let id = task.id();
+        previous.signal(SystemSignal::Stop).unwrap();
+        previous.join().await.unwrap();
+        sleep(Duration::from_millis(5)).await;
+
+        assert_eq!(registry.whereis::<CountMsg>("counter").unwrap().id(), id);
+        previous = task;
+    }
+}
+
+#[tokio::test]
+async fn queued_registration_takes_over_an_unregistered_name() {
+    let registry = Registry::new();
+    let total = Arc::new(AtomicU32::new(0));
+    let first = counter(&total);
+    let second = counter(&total);
+
+    registry.register("counter", &first.this());
+    registry
+        .register_with("counter", &second.this(), Conflict::QueueBehind)
+        .unwrap();
+    sleep(Duration::from_millis(5)).await;
+
+    registry.unregister("counter");
+    sleep(Duration::from_millis(5)).await;
+    assert_eq!(
+        registry.whereis::<CountMsg>("counter").unwrap().id(),
+        second.id()
+    );
+}
@@ -0,0 +1,103 @@
+//! Integration tests for acknowledged delivery with `send_reliable!`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+use notizia::core::{RetryPolicy, SystemSignal, dead_letter};
+use notizia::prelude::*;
+use notizia::{CallError, send_reliable};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Charge(u64),
+}
+
+#[derive(Task)]
+#[task(message = Command, handler)]
+struct Billing {
+    charged: Arc<AtomicUsize>,
+}
+
+impl Handler<Command> for Billing {
+    async fn handle(&mut self, msg: Command, _ctx: &mut Context<Command>) {
+        let Command::Charge(amount) = msg;
+        self.charged.fetch_add(amount as usize, Ordering::SeqCst);
+    }
+}
+
+/// Takes longer than the acknowledgement timeout for its first message.
+#[derive(Task)]
+#[task(message = Command)]
+struct Slow {
+    received: Arc<AtomicUsize>,
+}
+
+impl Runnable<Command> for Slow {
+    async fn start(&self) {
+        while recv!(self).is_ok() {
+            if self.received.fetch_add(1, Ordering::SeqCst) == 0 {
+                tokio::time::sleep(Duration::from_millis(200)).await;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn handler_tasks_acknowledge_processed_messages() {
+    let charged = Arc::new(AtomicUsize::new(0));
+    let handle = Billing {
+        charged: charged.clone(),
+    }
+    .run();
+
+    send_reliable!(handle, Command::Charge(10)).await.unwrap();
+    send_reliable!(handle.this(), Command::Charge(5), timeout = 1000)
+        .await
+        .unwrap();
+
+    assert_eq!(charged.load(Ordering::SeqCst), 15);
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_are_sent_again() {
+    let received = Arc::new(AtomicUsize::new(0));
+    let handle = Slow {
+        received: received.clone(),
+    }
+    .run();
+    let policy = RetryPolicy::new(3).backoff(Duration::from_millis(10));
+
+    send_reliable!(handle, Command::Charge(1), retry = policy, timeout = 150)
+        .await
+        .unwrap();
+
+    assert_eq!(received.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn undeliverable_messages_go_to_the_dead_letter_queue() {
+    let handle = Billing {
+        charged: Arc::new(AtomicUsize::new(0)),
+    }
+    .run();
+    let task = handle.this();
+    handle.signal(SystemSignal::Stop).unwrap();
+    while !task.is_closed() {
+        tokio::task::yield_now().await;
+    }
+    let policy = RetryPolicy::new(2).backoff(Duration::from_millis(1));
+
+    let err = send_reliable!(task, Command::Charge(7), retry = policy)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, CallError::SendError { .. }));
+
+    let letter = dead_letter::drain()
+        .into_iter()
+        .find(|letter| letter.task() == task.id())
+        .unwrap();
+    assert_eq!(letter.name(), "Billing");
+    assert_eq!(letter.attempts(), 2);
+    assert_eq!(letter.downcast::<Command>().unwrap(), Command::Charge(7));
+}
@@ -0,0 +1,174 @@
+//! Integration tests for serializable task addresses.
+
+#![cfg(feature = "remote")]
+
+use notizia::message;
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::remote::membership::{Gossip, MEMBERSHIP};
+use notizia::remote::{Address, Membership, RemoteError, RemoteListener, RemoteRef};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[message(serde)]
+#[derive(Debug)]
+enum PriceMsg {
+    Quote {
+        item: String,
+        reply_to: Address<QuoteMsg>,
+    },
+}
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum QuoteMsg {
+    Price { item: String, cents: u64 },
+}
+
+#[derive(Task)]
+#[task(message = PriceMsg)]
+struct Pricer;
+
+impl Runnable<PriceMsg> for Pricer {
+    async fn start(&self) {
+        while let Ok(PriceMsg::Quote { item, reply_to }) = recv!(self) {
+            let cents = item.len() as u64 * 100;
+            reply_to.send(QuoteMsg::Price { item, cents }).unwrap();
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = QuoteMsg)]
+struct Quotes {
+    seen: mpsc::UnboundedSender<QuoteMsg>,
+}
+
+impl Runnable<QuoteMsg> for Quotes {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+async fn next(rx: &mut mpsc::UnboundedReceiver<QuoteMsg>) -> Option<QuoteMsg> {
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+/// A node running a `Pricer` as "pricer" and a `Quotes` as "quotes"
+struct Node {
+    addr: SocketAddr,
+    quotes: Address<QuoteMsg>,
+    seen: mpsc::UnboundedReceiver<QuoteMsg>,
+    _tasks: (TaskHandle<PriceMsg>, TaskHandle<QuoteMsg>),
+    _membership: Option<TaskHandle<Gossip>>,
+    _listener: TaskHandle<Infallible>,
+}
+
+async fn node(id: &str, with_membership: bool) -> Node {
+    let registry = Registry::new();
+    let pricer = spawn!(Pricer);
+    registry.register("pricer", &pricer.this());
+    let (seen, rx) = mpsc::unbounded_channel();
+    let quotes = Quotes { seen };
+    let quotes = spawn!(quotes);
+    registry.register("quotes", &quotes.this());
+
+    let listener = RemoteListener::bind("127.0.0.1:0", &registry)
+        .await
+        .unwrap()
+        .expose::<PriceMsg>("pricer")
+        .expose::<QuoteMsg>("quotes")
+        .expose::<Gossip>(MEMBERSHIP);
+    let addr = listener.local_addr().unwrap();
+
+    let membership = Membership::new(id, addr, &registry);
+    let address = membership.address("quotes");
+    let membership = with_membership.then(|| membership.spawn());
+    // Hosted once the membership task runs
+    while with_membership && !address.is_local() {
+        tokio::task::yield_now().await;
+    }
+
+    Node {
+        addr,
+        quotes: address,
+        seen: rx,
+        _tasks: (pricer, quotes),
+        _membership: membership,
+        _listener: listener.spawn(),
+    }
+}
+
+#[test]
+fn addresses_serialize_as_node_and_name() {
+    let address = Address::<QuoteMsg>::new("a", "10.0.0.1:7400".parse().unwrap(), "quotes");
+    let json = serde_json::to_string(&address).unwrap();
+    assert_eq!(
+        json,
+        r#"{"node":"a","addr":"10.0.0.1:7400","name":"quotes"}"#
+    );
+
+    let decoded: Address<QuoteMsg> = serde_json::from_str(&json).unwrap();
+    assert_eq!(decoded, address);
+    assert_eq!(decoded.to_string(), "a/quotes");
+}
+
+#[tokio::test]
+async fn local_addresses_send_to_the_mailbox() {
+    let mut a = node("local-a", true).await;
+    assert!(a.quotes.is_local());
+
+    a.quotes
+        .send(QuoteMsg::Price {
+            item: "tea".into(),
+            cents: 1,
+        })
+        .unwrap();
+    assert_eq!(
+        next(&mut a.seen).await,
+        Some(QuoteMsg::Price {
+            item: "tea".into(),
+            cents: 1
+        })
+    );
+
+    let missing = Address::<QuoteMsg>::new("local-a", a.addr, "missing");
+    assert!(matches!(
+        missing.send(QuoteMsg::Price {
+            item: "tea".into(),
+            cents: 1
+        }),
+        Err(RemoteError::NotRunning(name)) if name == "missing"
+    ));
+}
+
+#[tokio::test]
+async fn addresses_in_messages_reach_the_remote_task() {
+    // Neither node is hosted, so both directions go over the wire
+    let a = node("wire-a", false).await;
+    let mut b = node("wire-b", false).await;
+    assert!(!b.quotes.is_local());
+
+    let pricer = RemoteRef::<PriceMsg>::new(a.addr, "pricer");
+    pricer
+        .send(PriceMsg::Quote {
+            item: "coffee".into(),
+            reply_to: b.quotes.clone(),
+        })
+        .unwrap();
+
+    assert_eq!(
+        next(&mut b.seen).await,
+        Some(QuoteMsg::Price {
+            item: "coffee".into(),
+            cents: 600
+        })
+    );
+}
@@ -0,0 +1,144 @@
+//! Integration tests for gossip membership between nodes.
+
+#![cfg(feature = "remote")]
+
+use notizia::message;
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::remote::membership::{Gossip, MEMBERSHIP, Reachability};
+use notizia::remote::{Membership, NodeId, RemoteError, RemoteListener, RemoteRegistry};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum ChatMsg {
+    Say { text: String },
+}
+
+#[derive(Task)]
+#[task(message = ChatMsg)]
+struct Room {
+    seen: mpsc::UnboundedSender<ChatMsg>,
+}
+
+impl Runnable<ChatMsg> for Room {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+/// A listener and membership, optionally with a `Room` registered as "room"
+struct Node {
+    addr: SocketAddr,
+    peers: RemoteRegistry,
+    seen: mpsc::UnboundedReceiver<ChatMsg>,
+    _room: Option<TaskHandle<ChatMsg>>,
+    _membership: TaskHandle<Gossip>,
+    _listener: TaskHandle<Infallible>,
+}
+
+async fn node(id: &str, seeds: &[SocketAddr], with_room: bool) -> Node {
+    let registry = Registry::new();
+    let (seen, rx) = mpsc::unbounded_channel();
+    let room = with_room.then(|| {
+        let room = Room { seen };
+        let room = spawn!(room);
+        registry.register("room", &room.this());
+        room
+    });
+
+    let listener = RemoteListener::bind("127.0.0.1:0", &registry)
+        .await
+        .unwrap()
+        .expose::<ChatMsg>("room")
+        .expose::<Gossip>(MEMBERSHIP);
+    let addr = listener.local_addr().unwrap();
+
+    let membership = seeds.iter().fold(
+        Membership::new(id, addr, &registry)
+            .heartbeat(Duration::from_millis(20))
+            .unreachable_after(Duration::from_millis(200)),
+        |membership, seed| membership.seed(*seed),
+    );
+    let peers = membership.remote_registry();
+
+    Node {
+        addr,
+        peers,
+        seen: rx,
+        _room: room,
+        _membership: membership.spawn(),
+        _listener: listener.spawn(),
+    }
+}
+
+/// Wait until `check` holds, for up to two seconds.
+async fn eventually(mut check: impl FnMut() -> bool) -> bool {
+    for _ in 0..100 {
+        if check() {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    false
+}
+
+fn say(text: &str) -> ChatMsg {
+    ChatMsg::Say { text: text.into() }
+}
+
+#[tokio::test]
+async fn nodes_learn_each_others_names() {
+    let mut a = node("a", &[], true).await;
+    let b = node("b", &[a.addr], false).await;
+
+    assert!(eventually(|| b.peers.lookup::<ChatMsg>("room").is_some()).await);
+    let member = b.peers.member(&NodeId::new("a")).unwrap();
+    assert_eq!(member.addr, a.addr);
+    assert_eq!(member.names, vec!["room".to_string()]);
+    assert!(eventually(|| a.peers.member(&NodeId::new("b")).is_some()).await);
+
+    let room = b.peers.lookup::<ChatMsg>("room").unwrap();
+    room.send(say("hello")).unwrap();
+    let received = tokio::time::timeout(Duration::from_secs(2), a.seen.recv()).await;
+    assert_eq!(received.unwrap(), Some(say("hello")));
+}
+
+#[tokio::test]
+async fn peers_are_learned_through_gossip() {
+    let a = node("a", &[], true).await;
+    let b = node("b", &[a.addr], false).await;
+    // Only knows about `b`
+    let c = node("c", &[b.addr], false).await;
+
+    assert!(eventually(|| c.peers.lookup::<ChatMsg>("room").is_some()).await);
+    assert!(eventually(|| a.peers.members().len() == 2).await);
+    drop(b);
+}
+
+#[tokio::test]
+async fn silent_peers_become_unreachable() {
+    let a = node("a", &[], true).await;
+    let b = node("b", &[a.addr], false).await;
+    assert!(eventually(|| b.peers.lookup::<ChatMsg>("room").is_some()).await);
+    let room = b.peers.lookup::<ChatMsg>("room").unwrap();
+    assert!(!room.is_stale());
+
+    drop(a);
+
+    let id = NodeId::new("a");
+    assert!(
+        eventually(|| b.peers.member(&id).unwrap().reachability == Reachability::Unreachable).await
+    );
+    assert!(room.is_stale());
+    assert!(matches!(
+        room.send(say("anyone?")),
+        Err(RemoteError::Unreachable(node)) if node == id
+    ));
+    assert!(b.peers.lookup::<ChatMsg>("room").is_none());
+}
@@ -0,0 +1,161 @@
+//! Integration tests for the QUIC transport of remote messaging.
+
+#![cfg(feature = "remote-quic")]
+
+use notizia::message;
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::remote::quic::{ClientConfig, ServerConfig};
+use notizia::remote::{Endpoint, QuicClient, RemoteListener};
+use quinn::rustls::RootCertStore;
+use quinn::rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum ChatMsg {
+    Say { text: String },
+}
+
+#[derive(Task)]
+#[task(message = ChatMsg)]
+struct Room {
+    seen: mpsc::UnboundedSender<ChatMsg>,
+}
+
+impl Runnable<ChatMsg> for Room {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+/// A listener exposing two rooms, and a client trusting its certificate
+struct Node {
+    addr: SocketAddr,
+    client: QuicClient,
+    lobby: mpsc::UnboundedReceiver<ChatMsg>,
+    garden: mpsc::UnboundedReceiver<ChatMsg>,
+    _rooms: [TaskHandle<ChatMsg>; 2],
+    _listener: TaskHandle<std::convert::Infallible>,
+}
+
+fn configs() -> (ServerConfig, ClientConfig) {
+    let key = rcgen::generate_simple_self_signed(vec!["localhost".into()]).unwrap();
+    let cert = CertificateDer::from(key.cert);
+    let private = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.signing_key.serialize_der()));
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert.clone()).unwrap();
+
+    (
+        ServerConfig::with_single_cert(vec![cert], private).unwrap(),
+        ClientConfig::with_root_certificates(Arc::new(roots)).unwrap(),
+    )
+}
+
+fn room(
+    registry: &Registry,
+    name: &str,
+) -> (TaskHandle<ChatMsg>, mpsc::UnboundedReceiver<ChatMsg>) {
+    let (seen, rx) = mpsc::unbounded_channel();
+    let room = Room { seen };
+    let room = spawn!(room);
+    registry.register(name, &room.this());
+    (room, rx)
+}
+
+async fn node() -> Node {
+    let registry = Registry::new();
+    let (lobby, lobby_rx) = room(&registry, "lobby");
+    let (garden, garden_rx) = room(&registry, "garden");
+
+    let (server, client) = configs();
+    let listener = RemoteListener::bind_quic("127.0.0.1:0".parse().unwrap(), server, &registry)
+        .unwrap()
+        .expose::<ChatMsg>("lobby")
+        .expose::<ChatMsg>("garden");
+
+    Node {
+        addr: listener.local_addr().unwrap(),
+        client: QuicClient::new(client).unwrap(),
+        lobby: lobby_rx,
+        garden: garden_rx,
+        _rooms: [lobby, garden],
+        _listener: listener.spawn(),
+    }
+}
+
+async fn next(rx: &mut mpsc::UnboundedReceiver<ChatMsg>) -> Option<ChatMsg> {
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+fn say(text: &str) -> ChatMsg {
+    ChatMsg::Say { text: text.into() }
+}
+
+#[tokio::test]
+async fn remote_refs_send_over_quic() {
+    let mut node = node().await;
+    let lobby = node
+        .client
+        .remote::<ChatMsg>(node.addr, "localhost", "lobby");
+    assert!(matches!(lobby.endpoint(), Endpoint::Quic(target) if target.addr() == node.addr));
+
+    lobby.send(say("hello")).unwrap();
+    lobby.send(say("world")).unwrap();
+
+    assert_eq!(next(&mut node.lobby).await, Some(say("hello")));
+    assert_eq!(next(&mut node.lobby).await, Some(say("world")));
+}
+
+#[tokio::test]
+async fn every_task_gets_its_own_stream() {
+    let mut node = node().await;
+    let lobby = node
+        .client
+        .remote::<ChatMsg>(node.addr, "localhost", "lobby");
+    let garden = node
+        .client
+        .remote::<ChatMsg>(node.addr, "localhost", "garden");
+    // References through the same client share the connection
+    assert_eq!(lobby.endpoint(), garden.endpoint());
+
+    for i in 0..10 {
+        lobby.send(say(&format!("lobby {i}"))).unwrap();
+        garden.send(say(&format!("garden {i}"))).unwrap();
+    }
+
+    for i in 0..10 {
+        assert_eq!(
+            next(&mut node.lobby).await,
+            Some(say(&format!("lobby {i}")))
+        );
+        assert_eq!(
+            next(&mut node.garden).await,
+            Some(say(&format!("garden {i}")))
+        );
+    }
+}
+
+#[tokio::test]
+async fn untrusted_listeners_receive_nothing() {
+    let mut node = node().await;
+    // A client trusting another certificate
+    let (_, other) = configs();
+    let client = QuicClient::new(other).unwrap();
+
+    client
+        .remote::<ChatMsg>(node.addr, "localhost", "lobby")
+        .send(say("hello"))
+        .unwrap();
+
+    assert_eq!(next(&mut node.lobby).await, None);
+}
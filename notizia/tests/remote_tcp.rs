@@ -0,0 +1,129 @@
+//! Integration tests for remote messaging over TCP.
+
+#![cfg(feature = "remote")]
+
+use notizia::message;
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::remote::{RemoteListener, RemoteRef};
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+use tokio::sync::mpsc;
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum OrderMsg {
+    Place { id: u32 },
+    Cancel { id: u32 },
+}
+
+#[derive(Task)]
+#[task(message = OrderMsg)]
+struct Orders {
+    seen: mpsc::UnboundedSender<OrderMsg>,
+}
+
+impl Runnable<OrderMsg> for Orders {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+/// Spawn an `Orders` task exposed as "orders" on a fresh listener
+async fn node() -> (
+    SocketAddr,
+    TaskHandle<std::convert::Infallible>,
+    TaskHandle<OrderMsg>,
+    mpsc::UnboundedReceiver<OrderMsg>,
+) {
+    let registry = Registry::new();
+    let (seen, rx) = mpsc::unbounded_channel();
+    let orders = Orders { seen };
+    let orders = spawn!(orders);
+    registry.register("orders", &orders.this());
+
+    let listener = RemoteListener::bind("127.0.0.1:0", &registry)
+        .await
+        .unwrap()
+        .expose::<OrderMsg>("orders");
+    let addr = listener.local_addr().unwrap();
+    (addr, listener.spawn(), orders, rx)
+}
+
+async fn next(rx: &mut mpsc::UnboundedReceiver<OrderMsg>) -> Option<OrderMsg> {
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+fn raw_frame(json: &str) -> Vec<u8> {
+    let mut frame = (json.len() as u32).to_be_bytes().to_vec();
+    frame.extend_from_slice(json.as_bytes());
+    frame
+}
+
+#[tokio::test]
+async fn messages_reach_the_named_task_in_order() {
+    let (addr, _listener, _orders, mut rx) = node().await;
+    let orders = RemoteRef::<OrderMsg>::new(addr, "orders");
+
+    for id in 0..20 {
+        orders.send(OrderMsg::Place { id }).unwrap();
+    }
+    orders.clone().send(OrderMsg::Cancel { id: 3 }).unwrap();
+
+    for id in 0..20 {
+        assert_eq!(next(&mut rx).await, Some(OrderMsg::Place { id }));
+    }
+    assert_eq!(next(&mut rx).await, Some(OrderMsg::Cancel { id: 3 }));
+}
+
+#[tokio::test]
+async fn undeliverable_frames_are_dropped() {
+    let (addr, _listener, _orders, mut rx) = node().await;
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+
+    // Unknown name, then a payload of the wrong shape, then a valid frame
+    stream
+        .write_all(&raw_frame(
+            r#"{"to":"billing","payload":{"Place":{"id":1}}}"#,
+        ))
+        .await
+        .unwrap();
+    stream
+        .write_all(&raw_frame(r#"{"to":"orders","payload":{"Refund":{}}}"#))
+        .await
+        .unwrap();
+    stream
+        .write_all(&raw_frame(
+            r#"{"to":"orders","payload":{"Place":{"id":2}}}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(next(&mut rx).await, Some(OrderMsg::Place { id: 2 }));
+}
+
+#[tokio::test]
+async fn messages_to_stopped_tasks_are_dropped() {
+    let (addr, _listener, orders, mut rx) = node().await;
+    orders.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    RemoteRef::<OrderMsg>::new(addr, "orders")
+        .send(OrderMsg::Place { id: 1 })
+        .unwrap();
+    assert_eq!(next(&mut rx).await, None);
+}
+
+#[tokio::test]
+async fn shut_down_listener_stops_accepting() {
+    let (addr, listener, _orders, _rx) = node().await;
+    listener.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert!(TcpStream::connect(addr).await.is_err());
+}
@@ -0,0 +1,108 @@
+//! Integration tests for the WebSocket transport of remote messaging.
+
+#![cfg(feature = "remote-ws")]
+
+use futures::SinkExt;
+use notizia::message;
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::remote::{Endpoint, RemoteListener, RemoteRef};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::Message;
+
+#[message(serde)]
+#[derive(Debug, PartialEq)]
+enum ChatMsg {
+    Say { text: String },
+}
+
+#[derive(Task)]
+#[task(message = ChatMsg)]
+struct Room {
+    seen: mpsc::UnboundedSender<ChatMsg>,
+}
+
+impl Runnable<ChatMsg> for Room {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            self.seen.send(msg).unwrap();
+        }
+    }
+}
+
+/// A listener exposing a `Room` as "room"
+struct Node {
+    url: String,
+    seen: mpsc::UnboundedReceiver<ChatMsg>,
+    _room: TaskHandle<ChatMsg>,
+    _listener: TaskHandle<std::convert::Infallible>,
+}
+
+async fn node() -> Node {
+    let registry = Registry::new();
+    let (seen, rx) = mpsc::unbounded_channel();
+    let room = Room { seen };
+    let room = spawn!(room);
+    registry.register("room", &room.this());
+
+    let listener = RemoteListener::bind_ws("127.0.0.1:0", &registry)
+        .await
+        .unwrap()
+        .expose::<ChatMsg>("room");
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+    Node {
+        url,
+        seen: rx,
+        _room: room,
+        _listener: listener.spawn(),
+    }
+}
+
+async fn next(rx: &mut mpsc::UnboundedReceiver<ChatMsg>) -> Option<ChatMsg> {
+    tokio::time::timeout(Duration::from_secs(2), rx.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+fn say(text: &str) -> ChatMsg {
+    ChatMsg::Say { text: text.into() }
+}
+
+#[tokio::test]
+async fn remote_refs_send_over_websockets() {
+    let mut node = node().await;
+    let room = RemoteRef::<ChatMsg>::websocket(node.url.clone(), "room");
+    assert_eq!(room.endpoint(), &Endpoint::WebSocket(node.url.clone()));
+
+    room.send(say("hello")).unwrap();
+    room.send(say("world")).unwrap();
+
+    assert_eq!(next(&mut node.seen).await, Some(say("hello")));
+    assert_eq!(next(&mut node.seen).await, Some(say("world")));
+}
+
+#[tokio::test]
+async fn text_messages_carry_frames_too() {
+    let mut node = node().await;
+    let (mut socket, _) = tokio_tungstenite::connect_async(node.url.as_str())
+        .await
+        .unwrap();
+
+    // A browser would send JSON text messages
+    socket
+        .send(Message::text(
+            r#"{"to":"lobby","payload":{"Say":{"text":"lost"}}}"#,
+        ))
+        .await
+        .unwrap();
+    socket
+        .send(Message::text(
+            r#"{"to":"room","payload":{"Say":{"text":"hi"}}}"#,
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(next(&mut node.seen).await, Some(say("hi")));
+}
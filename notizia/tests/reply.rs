@@ -0,0 +1,186 @@
+//! Integration tests for `Reply<T>`.
+
+use notizia::core::SystemSignal;
+use notizia::core::errors::CallError;
+use notizia::prelude::*;
+use notizia::{call, message, reply};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::time::{Duration, Instant, sleep};
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = u32)]
+    Answer,
+    #[request(reply = u32)]
+    Ignore,
+    #[request(reply = u32)]
+    Slow,
+    #[request(reply = u32)]
+    Crash,
+}
+
+#[derive(Task)]
+#[task(message = Msg)]
+struct Service {
+    canceled: Arc<AtomicBool>,
+}
+
+impl Runnable<Msg> for Service {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                Msg::Answer { reply_to } => {
+                    let _ = reply_to.reply(42);
+                }
+                Msg::Ignore { reply_to } => drop(reply_to),
+                Msg::Slow { reply_to } => {
+                    sleep(Duration::from_millis(50)).await;
+                    self.canceled
+                        .store(reply_to.is_canceled(), Ordering::SeqCst);
+                    assert_eq!(reply_to.reply(1), Err(1));
+                }
+                Msg::Crash {
+                    reply_to: _reply_to,
+                } => panic!("handler crashed"),
+            }
+        }
+    }
+}
+
+fn service() -> (Service, Arc<AtomicBool>) {
+    let canceled = Arc::new(AtomicBool::new(false));
+    let service = Service {
+        canceled: canceled.clone(),
+    };
+    (service, canceled)
+}
+
+#[tokio::test]
+async fn reply_answers_the_call() {
+    let (task, _) = service();
+    let handle = spawn!(task);
+
+    assert_eq!(call!(handle, Msg::Answer).await.unwrap(), 42);
+}
+
+#[tokio::test]
+async fn dropped_reply_fails_the_call_immediately() {
+    let (task, _) = service();
+    let handle = spawn!(task);
+
+    let started = Instant::now();
+    let result = call!(handle, Msg::Ignore, timeout = Duration::from_secs(5)).await;
+
+    assert!(matches!(result, Err(CallError::NoReply)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+}
+
+#[tokio::test]
+async fn panicking_handler_fails_the_call_immediately() {
+    let (task, _) = service();
+    let handle = spawn!(task);
+
+    let started = Instant::now();
+    let result = call!(handle, Msg::Crash, timeout = Duration::from_secs(5)).await;
+
+    assert!(matches!(result, Err(CallError::NoReply)));
+    assert!(started.elapsed() < Duration::from_secs(1));
+    assert!(matches!(
+        handle.join().await.unwrap(),
+        TerminateReason::Panic(_)
+    ));
+}
+
+#[tokio::test]
+async fn reply_observes_caller_timeout() {
+    let (task, canceled) = service();
+    let handle = spawn!(task);
+
+    let result = call!(handle, Msg::Slow, timeout = 10).await;
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+
+    sleep(Duration::from_millis(100)).await;
+    assert!(canceled.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn reply_macro_reports_whether_the_answer_was_delivered() {
+    let (reply_to, receiver) = Reply::channel();
+    assert!(reply!(reply_to, 7));
+    assert_eq!(receiver.await.unwrap(), 7);
+
+    let (reply_to, receiver) = Reply::<u32>::channel();
+    drop(receiver);
+    assert!(!reply!(reply_to, 7));
+}
+
+#[message]
+#[derive(Debug)]
+enum Job {
+    #[request(reply = u32)]
+    Run {
+        input: u32,
+    },
+    Done(u32, u32),
+}
+
+/// Answers each job once its background work completes.
+#[derive(Task, Default)]
+#[task(message = Job, handler)]
+struct Jobs {
+    next: u32,
+    pending: HashMap<u32, Reply<u32>>,
+}
+
+impl Handler<Job> for Jobs {
+    async fn handle(&mut self, msg: Job, ctx: &mut Context<Job>) {
+        match msg {
+            Job::Run { input, reply_to } => {
+                let job = self.next;
+                self.next += 1;
+                self.pending.insert(job, reply_to);
+
+                // Later jobs finish first
+                let delay = Duration::from_millis(u64::from(60 - input * 20));
+                ctx.pipe_to(sleep(delay), move |()| Job::Done(job, input * 10));
+            }
+            Job::Done(job, output) => {
+                let reply_to = self.pending.remove(&job).unwrap();
+                reply!(reply_to, output);
+            }
+        }
+    }
+}
+
+#[test]
+fn replies_can_be_stored_and_moved_across_tasks() {
+    fn assert_send_static<T: Send + 'static>() {}
+    assert_send_static::<Reply<u32>>();
+}
+
+#[tokio::test]
+async fn stashed_replies_are_answered_from_later_messages() {
+    let jobs = Jobs::default().run();
+
+    let first = call!(jobs, |reply_to| Job::Run { input: 1, reply_to });
+    let second = call!(jobs, |reply_to| Job::Run { input: 2, reply_to });
+    let (first, second) = tokio::join!(first, second);
+    assert_eq!(first.unwrap(), 10);
+    assert_eq!(second.unwrap(), 20);
+}
+
+#[tokio::test]
+async fn stashed_replies_fail_their_calls_when_the_task_stops() {
+    let jobs = Jobs::default().run();
+    let task = jobs.this();
+
+    let pending =
+        tokio::spawn(async move { call!(task, |reply_to| Job::Run { input: 1, reply_to }).await });
+    sleep(Duration::from_millis(10)).await;
+    jobs.signal(SystemSignal::Stop).unwrap();
+
+    assert!(matches!(pending.await.unwrap(), Err(CallError::NoReply)));
+}
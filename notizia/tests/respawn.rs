@@ -0,0 +1,129 @@
+//! Integration tests for `TaskHandle::respawn`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::{SystemSignal, dead_letter};
+use notizia::prelude::*;
+use notizia::task::{MailboxPolicy, RespawnError};
+
+/// Records the divisors it receives, panicking on zero.
+#[derive(Task, Default)]
+#[task(message = u32, handler)]
+struct Divider {
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Handler<u32> for Divider {
+    async fn handle(&mut self, divisor: u32, _ctx: &mut Context<u32>) {
+        assert_ne!(divisor, 0, "division by zero");
+        self.seen.lock().unwrap().push(divisor);
+    }
+}
+
+async fn finished(handle: &TaskHandle<u32>) {
+    while !handle.is_finished() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+#[tokio::test]
+async fn respawned_task_keeps_its_references() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let divider = Divider { seen: seen.clone() };
+    let mut handle = divider.builder().restartable().spawn();
+    let task = handle.this();
+    let id = handle.id();
+
+    task.send(1).unwrap();
+    task.send(0).unwrap();
+    finished(&handle).await;
+
+    let previous = handle
+        .respawn(|| Divider { seen: seen.clone() })
+        .await
+        .unwrap();
+    assert!(matches!(previous, Ok(TerminateReason::Panic(_))));
+    assert_eq!(handle.id(), id);
+
+    task.send(2).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*seen.lock().unwrap(), [1, 2]);
+}
+
+#[tokio::test]
+async fn queued_messages_go_to_dead_letters() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let divider = Divider { seen: seen.clone() };
+    let mut handle = divider.builder().restartable().spawn();
+
+    handle.send(0).unwrap();
+    finished(&handle).await;
+
+    // Accepted while the task is down, but not delivered to the new one
+    handle.send(3).unwrap();
+    handle
+        .respawn(|| Divider { seen: seen.clone() })
+        .await
+        .unwrap()
+        .unwrap();
+    handle.send(4).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*seen.lock().unwrap(), [4]);
+
+    let letters: Vec<_> = dead_letter::drain()
+        .into_iter()
+        .filter(|letter| letter.task() == handle.id())
+        .collect();
+    assert_eq!(letters.len(), 1);
+    assert_eq!(letters[0].reason(), "task restarted");
+    let letter = letters.into_iter().next().unwrap();
+    assert_eq!(letter.downcast::<u32>().unwrap(), 3);
+}
+
+#[tokio::test]
+async fn preserved_mailbox_delivers_queued_messages() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let divider = Divider { seen: seen.clone() };
+    let mut handle = divider
+        .builder()
+        .restartable_with(MailboxPolicy::PreserveMailbox)
+        .spawn();
+
+    handle.send(0).unwrap();
+    handle.send(3).unwrap();
+    finished(&handle).await;
+
+    handle
+        .respawn(|| Divider { seen: seen.clone() })
+        .await
+        .unwrap()
+        .unwrap();
+    handle.send(4).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    assert_eq!(*seen.lock().unwrap(), [3, 4]);
+}
+
+#[tokio::test]
+async fn respawn_requires_a_terminated_restartable_task() {
+    let mut handle = Divider::default().run();
+    handle.send(0).unwrap();
+    finished(&handle).await;
+    assert_eq!(
+        handle.respawn(Divider::default).await.unwrap_err(),
+        RespawnError::NotRestartable
+    );
+
+    let mut handle = Divider::default().builder().restartable().spawn();
+    assert_eq!(
+        handle.respawn(Divider::default).await.unwrap_err(),
+        RespawnError::Running
+    );
+
+    handle.signal(SystemSignal::Stop).unwrap();
+    finished(&handle).await;
+    assert_eq!(
+        handle.respawn(Divider::default).await.unwrap_err(),
+        RespawnError::Closed
+    );
+}
@@ -0,0 +1,126 @@
+//! Integration tests for retrying calls.
+
+use notizia::core::RetryPolicy;
+use notizia::prelude::*;
+use notizia::{call_with_retry, message};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, Instant};
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = u32)]
+    Get,
+    #[request(reply = String)]
+    Echo { text: String },
+}
+
+/// Ignores the first `failures` requests before answering.
+#[derive(Task)]
+#[task(message = Msg)]
+struct Unreliable {
+    failures: u32,
+    requests: Arc<AtomicU32>,
+}
+
+impl Runnable<Msg> for Unreliable {
+    async fn start(&self) {
+        let mut pending = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            let n = self.requests.fetch_add(1, Ordering::SeqCst);
+            if n < self.failures {
+                // Keep the reply channel open, so the caller times out
+                pending.push(msg);
+                continue;
+            }
+
+            match msg {
+                Msg::Get { reply_to } => {
+                    let _ = reply_to.reply(n);
+                }
+                Msg::Echo { text, reply_to } => {
+                    let _ = reply_to.reply(text);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_unreliable(failures: u32) -> (TaskHandle<Msg>, Arc<AtomicU32>) {
+    let requests = Arc::new(AtomicU32::new(0));
+    let task = Unreliable {
+        failures,
+        requests: requests.clone(),
+    };
+    (spawn!(task), requests)
+}
+
+fn fast_policy(attempts: u32) -> RetryPolicy {
+    RetryPolicy::new(attempts).backoff(Duration::from_millis(5))
+}
+
+#[tokio::test]
+async fn transient_timeouts_are_retried() {
+    let (handle, requests) = spawn_unreliable(2);
+
+    let result = call_with_retry!(handle, Msg::Get, retry = fast_policy(3), timeout = 20).await;
+
+    assert_eq!(result.unwrap(), 2);
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn last_error_is_returned_when_attempts_are_used_up() {
+    let (handle, requests) = spawn_unreliable(10);
+
+    let result = call_with_retry!(handle, Msg::Get, retry = fast_policy(3), timeout = 20).await;
+
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+    assert_eq!(requests.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn errors_rejected_by_predicate_are_not_retried() {
+    let (handle, requests) = spawn_unreliable(10);
+    let policy = fast_policy(5).retry_on(|err| matches!(err, CallError::SendError { .. }));
+
+    let result = call_with_retry!(handle, Msg::Get, retry = policy, timeout = 20).await;
+
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+    assert_eq!(requests.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn closure_syntax_rebuilds_message_per_attempt() {
+    let (handle, _) = spawn_unreliable(1);
+    let text = String::from("hello");
+
+    let result = call_with_retry!(
+        handle,
+        |tx| Msg::Echo {
+            text: text.clone(),
+            reply_to: tx
+        },
+        retry = fast_policy(2),
+        timeout = 20
+    )
+    .await;
+
+    assert_eq!(result.unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn backoff_is_applied_between_attempts() {
+    let (handle, _) = spawn_unreliable(2);
+    let policy = RetryPolicy::new(3).backoff(Duration::from_millis(30));
+
+    let started = Instant::now();
+    call_with_retry!(handle, Msg::Get, retry = policy, timeout = 10)
+        .await
+        .unwrap();
+
+    // Two timeouts of 10ms, then backoffs of 30ms and 60ms
+    assert!(started.elapsed() >= Duration::from_millis(110));
+}
@@ -0,0 +1,8 @@
+//! Integration tests for running tasks on async-std.
+
+#![cfg(feature = "runtime-async-std")]
+
+#[macro_use]
+mod common;
+
+runtime_tests!(async_std::task::block_on);
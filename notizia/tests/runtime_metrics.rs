@@ -0,0 +1,35 @@
+//! Integration tests for the `tokio-metrics` feature.
+
+#![cfg(feature = "tokio-metrics")]
+
+use notizia::prelude::*;
+use std::time::Duration;
+
+#[derive(Task)]
+#[task(message = u64)]
+struct Worker;
+
+impl Runnable<u64> for Worker {
+    async fn start(&self) {
+        while let Ok(millis) = recv!(self) {
+            // Hog the runtime instead of yielding
+            std::thread::sleep(Duration::from_millis(millis));
+        }
+    }
+}
+
+#[tokio::test]
+async fn poll_durations_are_tracked_per_task() {
+    let busy = spawn!(Worker);
+    let idle = spawn!(Worker);
+
+    busy.send(30).unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let busy_metrics = busy.runtime_metrics().unwrap();
+    let idle_metrics = idle.runtime_metrics().unwrap();
+
+    assert!(busy_metrics.total_poll_duration >= Duration::from_millis(30));
+    assert!(idle_metrics.total_poll_duration < Duration::from_millis(30));
+    assert_eq!(busy_metrics.instrumented_count, 1);
+}
@@ -0,0 +1,8 @@
+//! Integration tests for running tasks on smol.
+
+#![cfg(feature = "runtime-smol")]
+
+#[macro_use]
+mod common;
+
+runtime_tests!(smol::block_on);
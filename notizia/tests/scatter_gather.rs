@@ -0,0 +1,154 @@
+//! Integration tests for scatter-gather calls across many tasks (`scatter!` and `call_all!`).
+
+use notizia::prelude::*;
+use notizia::{call_all, message, scatter};
+use tokio::time::{Duration, sleep};
+
+#[message]
+#[derive(Debug)]
+enum ReplicaMsg {
+    #[request(reply = usize)]
+    GetId,
+
+    #[request(reply = usize)]
+    Multiply { factor: usize },
+}
+
+#[derive(Task)]
+#[task(message = ReplicaMsg)]
+struct Replica {
+    id: usize,
+    delay: Duration,
+}
+
+impl Runnable<ReplicaMsg> for Replica {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            sleep(self.delay).await;
+            match msg {
+                ReplicaMsg::GetId { reply_to } => {
+                    let _ = reply_to.reply(self.id);
+                }
+                ReplicaMsg::Multiply { factor, reply_to } => {
+                    let _ = reply_to.reply(self.id * factor);
+                }
+            }
+        }
+    }
+}
+
+fn spawn_replicas(delays: &[u64]) -> Vec<TaskRef<ReplicaMsg>> {
+    delays
+        .iter()
+        .enumerate()
+        .map(|(id, delay)| {
+            Replica {
+                id,
+                delay: Duration::from_millis(*delay),
+            }
+            .run()
+            .this()
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn scatter_collects_replies_in_order() {
+    let replicas = spawn_replicas(&[30, 0, 10]);
+
+    let results = scatter!(&replicas, ReplicaMsg::GetId).await;
+
+    let ids: Vec<_> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(ids, vec![0, 1, 2]);
+}
+
+#[tokio::test]
+async fn scatter_builds_message_per_callee() {
+    let replicas = spawn_replicas(&[0, 0, 0, 0]);
+    let factor = 10;
+
+    let results = scatter!(&replicas, |tx| ReplicaMsg::Multiply {
+        factor,
+        reply_to: tx
+    })
+    .await;
+
+    let products: Vec<_> = results.into_iter().map(|r| r.unwrap()).collect();
+    assert_eq!(products, vec![0, 10, 20, 30]);
+}
+
+#[tokio::test]
+async fn scatter_timeout_applies_per_callee() {
+    let replicas = spawn_replicas(&[0, 500, 0]);
+
+    let results = scatter!(&replicas, ReplicaMsg::GetId, timeout = 100).await;
+
+    assert_eq!(results.len(), 3);
+    assert_eq!(*results[0].as_ref().unwrap(), 0);
+    assert!(matches!(results[1], Err(CallError::Timeout { .. })));
+    assert_eq!(*results[2].as_ref().unwrap(), 2);
+}
+
+#[tokio::test]
+async fn scatter_reports_send_error_for_dead_callee() {
+    let alive = Replica {
+        id: 0,
+        delay: Duration::ZERO,
+    }
+    .run();
+    let dead = Replica {
+        id: 1,
+        delay: Duration::ZERO,
+    }
+    .run();
+    let dead_ref = dead.this();
+    dead.kill();
+    sleep(Duration::from_millis(10)).await;
+
+    let targets = vec![alive.this(), dead_ref];
+    let results = scatter!(targets, ReplicaMsg::GetId, timeout = 100).await;
+
+    assert_eq!(*results[0].as_ref().unwrap(), 0);
+    assert!(matches!(results[1], Err(CallError::SendError { .. })));
+}
+
+#[tokio::test]
+async fn scatter_over_empty_collection_is_empty() {
+    let replicas: Vec<TaskRef<ReplicaMsg>> = Vec::new();
+
+    let results = scatter!(&replicas, ReplicaMsg::GetId).await;
+
+    assert!(results.is_empty());
+}
+
+#[tokio::test]
+async fn call_all_pairs_results_with_task_ids() {
+    let replicas = spawn_replicas(&[0, 500, 0]);
+
+    let results = call_all!(&replicas, ReplicaMsg::GetId, timeout = 100).await;
+
+    let ids: Vec<_> = results.iter().map(|(id, _)| *id).collect();
+    let expected: Vec<_> = replicas.iter().map(TaskRef::id).collect();
+    assert_eq!(ids, expected);
+
+    assert_eq!(*results[0].1.as_ref().unwrap(), 0);
+    assert!(matches!(results[1].1, Err(CallError::Timeout { .. })));
+    assert_eq!(*results[2].1.as_ref().unwrap(), 2);
+}
+
+#[tokio::test]
+async fn call_all_with_closure_syntax() {
+    let replicas = spawn_replicas(&[0, 0]);
+
+    let results = call_all!(&replicas, |tx| ReplicaMsg::Multiply {
+        factor: 3,
+        reply_to: tx
+    })
+    .await;
+
+    let products: Vec<_> = results
+        .into_iter()
+        .map(|(id, result)| (id, result.unwrap()))
+        .collect();
+    assert_eq!(products, vec![(replicas[0].id(), 0), (replicas[1].id(), 3)]);
+}
@@ -0,0 +1,110 @@
+//! Integration tests for cron-style scheduling.
+#![cfg(feature = "scheduler")]
+
+use notizia::prelude::*;
+use notizia::registry::Registry;
+use notizia::scheduler::{ScheduleError, Scheduler};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, Clone)]
+enum TickMsg {
+    Tick,
+}
+
+#[derive(Task)]
+#[task(message = TickMsg)]
+struct Ticker {
+    ticks: Arc<AtomicU32>,
+}
+
+impl Runnable<TickMsg> for Ticker {
+    async fn start(&self) {
+        while let Ok(TickMsg::Tick) = recv!(self) {
+            self.ticks.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn spawn_ticker(ticks: &Arc<AtomicU32>) -> TaskHandle<TickMsg> {
+    let task = Ticker {
+        ticks: ticks.clone(),
+    };
+    spawn!(task)
+}
+
+#[tokio::test]
+async fn invalid_expression_is_rejected() {
+    let scheduler = Scheduler::new(Registry::new());
+
+    let result = scheduler.schedule("not a cron expression", "ticker", || TickMsg::Tick);
+
+    assert!(matches!(
+        result,
+        Err(ScheduleError::InvalidExpression { .. })
+    ));
+}
+
+#[tokio::test]
+async fn job_sends_messages_to_registered_task() {
+    let registry = Registry::new();
+    let ticks = Arc::new(AtomicU32::new(0));
+    let ticker = spawn_ticker(&ticks);
+    registry.register("ticker", &ticker.this());
+
+    let scheduler = Scheduler::new(registry.clone());
+    scheduler
+        .schedule("* * * * * *", "ticker", || TickMsg::Tick)
+        .unwrap();
+
+    sleep(Duration::from_millis(2100)).await;
+
+    assert!(ticks.load(Ordering::SeqCst) >= 2);
+    scheduler.shutdown().await;
+}
+
+#[tokio::test]
+async fn job_survives_target_restart() {
+    let registry = Registry::new();
+    let first = Arc::new(AtomicU32::new(0));
+    let second = Arc::new(AtomicU32::new(0));
+
+    let ticker = spawn_ticker(&first);
+    registry.register("ticker", &ticker.this());
+
+    let scheduler = Scheduler::new(registry.clone());
+    scheduler
+        .schedule("* * * * * *", "ticker", || TickMsg::Tick)
+        .unwrap();
+
+    sleep(Duration::from_millis(1100)).await;
+    ticker.kill();
+
+    // The restarted task registers itself under the same name
+    let restarted = spawn_ticker(&second);
+    registry.register("ticker", &restarted.this());
+
+    sleep(Duration::from_millis(1100)).await;
+
+    assert!(first.load(Ordering::SeqCst) >= 1);
+    assert!(second.load(Ordering::SeqCst) >= 1);
+}
+
+#[tokio::test]
+async fn cancelled_job_stops_firing() {
+    let registry = Registry::new();
+    let ticks = Arc::new(AtomicU32::new(0));
+    let ticker = spawn_ticker(&ticks);
+    registry.register("ticker", &ticker.this());
+
+    let scheduler = Scheduler::new(registry);
+    let job = scheduler
+        .schedule("* * * * * *", "ticker", || TickMsg::Tick)
+        .unwrap();
+    scheduler.cancel(job);
+
+    sleep(Duration::from_millis(1100)).await;
+
+    assert_eq!(ticks.load(Ordering::SeqCst), 0);
+}
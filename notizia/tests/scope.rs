@@ -0,0 +1,119 @@
+//! Integration tests for structured concurrency with `ctx.scope`.
+
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+
+enum Job {
+    /// Sleep for each duration concurrently and report the total time
+    Sleep(Vec<u64>, oneshot::Sender<(Vec<u64>, Duration)>),
+    /// Sum the prefix of `values` of every length, borrowing the task state
+    Prefixes(oneshot::Sender<Vec<u32>>),
+    /// Start a helper that never finishes
+    Hang(Arc<DropFlag>, oneshot::Sender<()>),
+}
+
+#[derive(Default)]
+struct DropFlag(AtomicBool);
+
+struct SetOnDrop(Arc<DropFlag>);
+
+impl Drop for SetOnDrop {
+    fn drop(&mut self) {
+        self.0.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[derive(Task)]
+#[task(message = Job, handler)]
+struct Worker {
+    values: Vec<u32>,
+}
+
+impl Handler<Job> for Worker {
+    async fn handle(&mut self, msg: Job, ctx: &mut Context<Job>) {
+        match msg {
+            Job::Sleep(delays, reply) => {
+                let started = Instant::now();
+                let done = ctx
+                    .scope(|s| {
+                        for delay in delays {
+                            s.spawn(async move {
+                                tokio::time::sleep(Duration::from_millis(delay)).await;
+                                delay
+                            });
+                        }
+                    })
+                    .await;
+                let _ = reply.send((done, started.elapsed()));
+            }
+            Job::Prefixes(reply) => {
+                let values = &self.values;
+                let sums = ctx
+                    .scope(|s| {
+                        for len in 1..=values.len() {
+                            s.spawn(async move { values[..len].iter().sum() });
+                        }
+                    })
+                    .await;
+                let _ = reply.send(sums);
+            }
+            Job::Hang(flag, started) => {
+                ctx.scope(|s| {
+                    s.spawn(async move {
+                        let _guard = SetOnDrop(flag);
+                        let _ = started.send(());
+                        std::future::pending::<()>().await;
+                    });
+                })
+                .await;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn helpers_run_concurrently_and_keep_their_order() {
+    let handle = Worker { values: vec![] }.run();
+
+    let (reply, result) = oneshot::channel();
+    handle.send(Job::Sleep(vec![60, 20, 40], reply)).unwrap();
+    let (done, elapsed) = result.await.unwrap();
+
+    assert_eq!(done, vec![60, 20, 40]);
+    assert!(elapsed < Duration::from_millis(110), "took {elapsed:?}");
+}
+
+#[tokio::test]
+async fn helpers_can_borrow_task_state() {
+    let handle = Worker {
+        values: vec![1, 2, 3, 4],
+    }
+    .run();
+
+    let (reply, result) = oneshot::channel();
+    handle.send(Job::Prefixes(reply)).unwrap();
+
+    assert_eq!(result.await.unwrap(), vec![1, 3, 6, 10]);
+}
+
+#[tokio::test]
+async fn helpers_do_not_outlive_a_killed_task() {
+    let handle = Worker { values: vec![] }.run();
+    let flag = Arc::new(DropFlag::default());
+
+    let (started, running) = oneshot::channel();
+    handle.send(Job::Hang(flag.clone(), started)).unwrap();
+    running.await.unwrap();
+    assert!(!flag.0.load(Ordering::SeqCst));
+
+    let abort = handle.abort_handle();
+    handle.kill();
+    while !abort.is_finished() {
+        tokio::task::yield_now().await;
+    }
+
+    assert!(flag.0.load(Ordering::SeqCst));
+}
@@ -0,0 +1,157 @@
+//! Integration tests for the sender recorded in envelopes.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::reply;
+
+/// Answers every number with its double, to whoever sent it.
+#[derive(Task)]
+#[task(message = u32, handler)]
+struct Doubler;
+
+impl Handler<u32> for Doubler {
+    async fn handle(&mut self, n: u32, ctx: &mut Context<u32>) {
+        if let Some(sender) = ctx.sender().and_then(|sender| sender.task_ref::<u64>()) {
+            sender.send(u64::from(n) * 2).unwrap();
+        }
+    }
+}
+
+/// Sends numbers to a doubler and records the answers.
+#[derive(Task)]
+#[task(message = u64)]
+struct Client {
+    doubler: TaskRef<u32>,
+    answers: Arc<Mutex<Vec<u64>>>,
+}
+
+impl Runnable<u64> for Client {
+    async fn start(&self) {
+        self.doubler.send(1).unwrap();
+        self.doubler.send(2).unwrap();
+        while let Ok(answer) = recv!(self) {
+            let sender = self.mailbox().sender().unwrap();
+            assert_eq!(sender.id(), self.doubler.id());
+            assert_eq!(sender.name(), self.doubler.name());
+
+            let mut answers = self.answers.lock().unwrap();
+            answers.push(answer);
+            if answers.len() == 2 {
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn handlers_can_answer_the_sender() {
+    let doubler = spawn!(Doubler);
+    let answers = Arc::new(Mutex::new(Vec::new()));
+    let client = Client {
+        doubler: doubler.this(),
+        answers: answers.clone(),
+    }
+    .run();
+
+    tokio::time::timeout(Duration::from_secs(1), client.join())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(*answers.lock().unwrap(), [2, 4]);
+}
+
+/// Records the sender of every message.
+#[derive(Task, Default)]
+#[task(message = u32, handler)]
+struct Recorder {
+    senders: Arc<Mutex<Vec<Option<TaskId>>>>,
+}
+
+impl Handler<u32> for Recorder {
+    async fn handle(&mut self, _n: u32, ctx: &mut Context<u32>) {
+        let sender = ctx.sender().map(|sender| sender.id());
+        self.senders.lock().unwrap().push(sender);
+    }
+}
+
+#[tokio::test]
+async fn messages_sent_outside_of_tasks_have_no_sender() {
+    let senders = Arc::new(Mutex::new(Vec::new()));
+    let recorder = Recorder {
+        senders: senders.clone(),
+    }
+    .run();
+
+    recorder.send(1).unwrap();
+    recorder.signal(SystemSignal::Stop).unwrap();
+    recorder.join().await.unwrap();
+    assert_eq!(*senders.lock().unwrap(), [None]);
+}
+
+/// Asks a doubler once, then receives until its mailbox is closed.
+#[derive(Task)]
+#[task(message = u64)]
+struct Asker {
+    doubler: TaskRef<u32>,
+}
+
+impl Runnable<u64> for Asker {
+    async fn start(&self) {
+        self.doubler.send(1).unwrap();
+        while recv!(self).is_ok() {}
+    }
+}
+
+#[tokio::test]
+async fn sender_does_not_keep_its_mailbox_open() {
+    let doubler = spawn!(Doubler);
+    let asker = Asker {
+        doubler: doubler.this(),
+    }
+    .run();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+
+    // The doubler still remembers the asker as the sender of its last message
+    let result = asker.shutdown(Duration::from_secs(1)).await;
+    assert!(matches!(result, Ok(TerminateReason::Normal)));
+}
+
+/// Answers every number with its square, to whoever sent it.
+#[derive(Task, Default)]
+#[task(message = u32, handler)]
+struct Squarer {
+    delivered: Arc<Mutex<Vec<bool>>>,
+}
+
+impl Handler<u32> for Squarer {
+    async fn handle(&mut self, n: u32, ctx: &mut Context<u32>) {
+        let delivered = reply!(ctx, u64::from(n * n));
+        self.delivered.lock().unwrap().push(delivered);
+    }
+}
+
+#[tokio::test]
+async fn reply_macro_answers_the_sender() {
+    let delivered = Arc::new(Mutex::new(Vec::new()));
+    let squarer = Squarer {
+        delivered: delivered.clone(),
+    }
+    .run();
+    let answers = Arc::new(Mutex::new(Vec::new()));
+    let client = Client {
+        doubler: squarer.this(),
+        answers: answers.clone(),
+    }
+    .run();
+    client.join().await.unwrap();
+    assert_eq!(*answers.lock().unwrap(), [1, 4]);
+
+    // Without a sender, the answer cannot be delivered
+    squarer.send(3).unwrap();
+    squarer.signal(SystemSignal::Stop).unwrap();
+    squarer.join().await.unwrap();
+    assert_eq!(*delivered.lock().unwrap(), [true, true, false]);
+}
@@ -0,0 +1,51 @@
+//! Integration tests for serializable message types.
+
+#![cfg(feature = "serde")]
+
+use notizia::message;
+use notizia::prelude::*;
+
+#[message(casts = CounterCast, serde)]
+#[derive(Debug)]
+enum CounterMsg {
+    Increment,
+    Add {
+        amount: u32,
+    },
+    Rename(String),
+    #[request(reply = u32)]
+    GetCount,
+}
+
+#[test]
+fn cast_variants_round_trip() {
+    for msg in [
+        CounterMsg::Increment,
+        CounterMsg::Add { amount: 3 },
+        CounterMsg::Rename("total".into()),
+    ] {
+        let json = serde_json::to_string(&msg).unwrap();
+        let decoded: CounterMsg = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{decoded:?}"), format!("{msg:?}"));
+    }
+
+    assert_eq!(
+        serde_json::to_string(&CounterMsg::Add { amount: 3 }).unwrap(),
+        r#"{"Add":{"amount":3}}"#
+    );
+}
+
+#[test]
+fn request_variants_are_skipped() {
+    let (reply_to, _receiver) = Reply::channel();
+    assert!(serde_json::to_string(&CounterMsg::GetCount { reply_to }).is_err());
+    assert!(serde_json::from_str::<CounterMsg>(r#""GetCount""#).is_err());
+    assert!(serde_json::from_str::<CounterMsg>(r#"{"GetCount":{}}"#).is_err());
+}
+
+#[test]
+fn cast_enum_is_serializable() {
+    let json = serde_json::to_string(&CounterCast::Rename("x".into())).unwrap();
+    let decoded: CounterCast = serde_json::from_str(&json).unwrap();
+    assert!(matches!(CounterMsg::from(decoded), CounterMsg::Rename(name) if name == "x"));
+}
@@ -0,0 +1,108 @@
+//! Integration tests for sessions between tasks.
+
+use notizia::prelude::*;
+use notizia::session::Session;
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, PartialEq)]
+enum Request {
+    Add(u32),
+    Done,
+}
+
+#[derive(Debug, PartialEq)]
+enum Response {
+    Sum(u32),
+}
+
+enum AdderMsg {
+    Open(Session<Response, Request>),
+    Crash,
+}
+
+#[derive(Task)]
+#[task(message = AdderMsg)]
+struct Adder;
+
+impl Runnable<AdderMsg> for Adder {
+    async fn start(&self) {
+        // Keep sessions alive until the task terminates
+        let mut open = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                AdderMsg::Open(mut session) => {
+                    let mut sum = 0;
+                    while let Ok(Request::Add(n)) = session.recv().await {
+                        sum += n;
+                        let _ = session.send(Response::Sum(sum));
+                    }
+                    open.push(session);
+                }
+                AdderMsg::Crash => break,
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn session_carries_a_conversation() {
+    let adder = spawn!(Adder);
+
+    let mut session = Session::open(&adder.this(), AdderMsg::Open).unwrap();
+
+    for n in [1, 2, 3] {
+        session.send(Request::Add(n)).unwrap();
+    }
+
+    assert_eq!(session.recv().await.unwrap(), Response::Sum(1));
+    assert_eq!(session.recv().await.unwrap(), Response::Sum(3));
+    assert_eq!(session.recv().await.unwrap(), Response::Sum(6));
+    assert_eq!(session.try_recv().unwrap(), None);
+}
+
+#[tokio::test]
+async fn session_is_torn_down_when_peer_terminates() {
+    let adder = spawn!(Adder);
+    let task = adder.this();
+
+    let mut session = Session::open(&task, AdderMsg::Open).unwrap();
+    session.send(Request::Add(5)).unwrap();
+    session.send(Request::Done).unwrap();
+    assert_eq!(session.recv().await.unwrap(), Response::Sum(5));
+
+    // The adder keeps its side open until it stops
+    sleep(Duration::from_millis(10)).await;
+    assert!(!session.is_closed());
+
+    task.send(AdderMsg::Crash).unwrap();
+    adder.join().await.unwrap();
+
+    assert!(session.is_closed());
+    assert!(matches!(session.recv().await, Err(RecvError::Closed)));
+    assert!(session.send(Request::Add(1)).is_err());
+}
+
+#[tokio::test]
+async fn peer_sees_closed_session_when_opener_drops_it() {
+    let (mine, mut theirs) = Session::<u8, u8>::pair();
+
+    mine.send(1).unwrap();
+    drop(mine);
+
+    assert_eq!(theirs.recv().await.unwrap(), 1);
+    assert!(matches!(theirs.recv().await, Err(RecvError::Closed)));
+    assert!(theirs.is_closed());
+}
+
+#[tokio::test]
+async fn opening_session_with_stopped_task_fails() {
+    let adder = spawn!(Adder);
+    let task = adder.this();
+    adder.kill();
+    sleep(Duration::from_millis(20)).await;
+
+    let result = Session::<Request, Response>::open(&task, AdderMsg::Open);
+
+    assert!(matches!(result, Err(CallError::SendError { .. })));
+}
@@ -0,0 +1,237 @@
+//! Integration tests for entity sharding.
+
+use notizia::prelude::*;
+use notizia::sharding::ShardRegion;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::time::{Duration, sleep};
+
+#[derive(Debug, Clone)]
+enum DeviceMsg {
+    Reading(u32),
+    Stop,
+}
+
+type Totals = Arc<Mutex<HashMap<u32, u32>>>;
+
+#[derive(Task)]
+#[task(message = DeviceMsg)]
+struct Device {
+    id: u32,
+    totals: Totals,
+    stopped: Arc<AtomicU32>,
+}
+
+impl Runnable<DeviceMsg> for Device {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                DeviceMsg::Reading(value) => {
+                    *self.totals.lock().unwrap().entry(self.id).or_default() += value;
+                }
+                DeviceMsg::Stop => break,
+            }
+        }
+    }
+
+    async fn terminate(&self, _reason: TerminateReason) {
+        self.stopped.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+struct Fixture {
+    totals: Totals,
+    spawned: Arc<AtomicU32>,
+    stopped: Arc<AtomicU32>,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        Fixture {
+            totals: Arc::new(Mutex::new(HashMap::new())),
+            spawned: Arc::new(AtomicU32::new(0)),
+            stopped: Arc::new(AtomicU32::new(0)),
+        }
+    }
+
+    fn region(&self) -> ShardRegion<u32, DeviceMsg> {
+        let totals = self.totals.clone();
+        let spawned = self.spawned.clone();
+        let stopped = self.stopped.clone();
+
+        ShardRegion::new(move |id: &u32| {
+            spawned.fetch_add(1, Ordering::SeqCst);
+            Device {
+                id: *id,
+                totals: totals.clone(),
+                stopped: stopped.clone(),
+            }
+        })
+    }
+}
+
+#[tokio::test]
+async fn entities_are_spawned_lazily_per_key() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    assert!(region.is_empty());
+
+    region.send(1, DeviceMsg::Reading(10)).unwrap();
+    region.send(2, DeviceMsg::Reading(5)).unwrap();
+    region.send(1, DeviceMsg::Reading(7)).unwrap();
+
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(region.len(), 2);
+    assert_eq!(fixture.spawned.load(Ordering::SeqCst), 2);
+
+    let totals = fixture.totals.lock().unwrap();
+    assert_eq!(totals[&1], 17);
+    assert_eq!(totals[&2], 5);
+}
+
+#[tokio::test]
+async fn terminated_entity_is_respawned_on_next_message() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    region.send(1, DeviceMsg::Stop).unwrap();
+    sleep(Duration::from_millis(20)).await;
+
+    region.send(1, DeviceMsg::Reading(3)).unwrap();
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(fixture.spawned.load(Ordering::SeqCst), 2);
+    assert_eq!(fixture.totals.lock().unwrap()[&1], 3);
+}
+
+#[tokio::test]
+async fn broadcast_reaches_known_entities_without_spawning() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    region.send(1, DeviceMsg::Reading(1)).unwrap();
+    region.send(2, DeviceMsg::Reading(2)).unwrap();
+
+    let mut results = region.broadcast(DeviceMsg::Reading(10));
+    results.sort_by_key(|(key, _)| *key);
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(
+        results.iter().map(|(key, _)| *key).collect::<Vec<_>>(),
+        [1, 2]
+    );
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+    assert_eq!(fixture.spawned.load(Ordering::SeqCst), 2);
+
+    let totals = fixture.totals.lock().unwrap();
+    assert_eq!(totals[&1], 11);
+    assert_eq!(totals[&2], 12);
+}
+
+#[tokio::test]
+async fn prune_removes_finished_entities() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    region.send(1, DeviceMsg::Stop).unwrap();
+    region.send(2, DeviceMsg::Reading(1)).unwrap();
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(region.prune(), 1);
+    assert!(!region.contains_key(&1));
+    assert!(region.contains_key(&2));
+}
+
+#[tokio::test]
+async fn evict_gracefully_stops_entity() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    region.send(1, DeviceMsg::Reading(1)).unwrap();
+
+    let result = region.evict(&1, Duration::from_secs(1)).await;
+    assert!(matches!(result, Some(Ok(TerminateReason::Normal))));
+    assert_eq!(fixture.stopped.load(Ordering::SeqCst), 1);
+    assert!(!region.contains_key(&1));
+
+    assert!(region.evict(&1, Duration::from_secs(1)).await.is_none());
+}
+
+#[tokio::test]
+async fn least_recently_used_entity_is_evicted_over_limit() {
+    let fixture = Fixture::new();
+    let region = fixture.region().max_entities(2);
+
+    region.send(1, DeviceMsg::Reading(1)).unwrap();
+    sleep(Duration::from_millis(5)).await;
+    region.send(2, DeviceMsg::Reading(1)).unwrap();
+    sleep(Duration::from_millis(5)).await;
+    region.send(1, DeviceMsg::Reading(1)).unwrap();
+    sleep(Duration::from_millis(5)).await;
+    region.send(3, DeviceMsg::Reading(1)).unwrap();
+
+    sleep(Duration::from_millis(20)).await;
+
+    let mut keys = region.keys();
+    keys.sort();
+    assert_eq!(keys, vec![1, 3]);
+    assert_eq!(fixture.stopped.load(Ordering::SeqCst), 1);
+}
+
+#[tokio::test]
+async fn rebalance_keeps_running_entities() {
+    let fixture = Fixture::new();
+    let region = fixture.region().with_shards(2);
+
+    for id in 0..10 {
+        region.send(id, DeviceMsg::Reading(1)).unwrap();
+    }
+
+    region.rebalance(5);
+
+    assert_eq!(region.shard_count(), 5);
+    assert_eq!(region.len(), 10);
+    for id in 0..10 {
+        assert!(region.shard_of(&id) < 5);
+        region.send(id, DeviceMsg::Reading(1)).unwrap();
+    }
+
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(fixture.spawned.load(Ordering::SeqCst), 10);
+    assert!(fixture.totals.lock().unwrap().values().all(|v| *v == 2));
+}
+
+#[tokio::test]
+async fn entity_ref_reaches_same_task() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    let entity = region.entity(4);
+    entity.send(DeviceMsg::Reading(2)).unwrap();
+    region.send(4, DeviceMsg::Reading(3)).unwrap();
+
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(fixture.spawned.load(Ordering::SeqCst), 1);
+    assert_eq!(fixture.totals.lock().unwrap()[&4], 5);
+}
+
+#[tokio::test]
+async fn shutdown_stops_all_entities() {
+    let fixture = Fixture::new();
+    let region = fixture.region();
+
+    for id in 0..3 {
+        region.send(id, DeviceMsg::Reading(1)).unwrap();
+    }
+
+    let results = region.shutdown(Duration::from_secs(1)).await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+    assert_eq!(fixture.stopped.load(Ordering::SeqCst), 3);
+}
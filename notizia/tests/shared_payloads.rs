@@ -0,0 +1,61 @@
+//! Integration tests for sharing large payloads with `ArcMessage`.
+
+use std::time::Duration;
+
+use notizia::cast_all;
+use notizia::core::ArcMessage;
+use notizia::prelude::*;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone)]
+enum BlobMsg {
+    Blob(ArcMessage<Vec<u8>>),
+}
+
+#[derive(Task)]
+#[task(message = BlobMsg)]
+struct Reader {
+    seen: mpsc::UnboundedSender<ArcMessage<Vec<u8>>>,
+}
+
+impl Runnable<BlobMsg> for Reader {
+    async fn start(&self) {
+        while let Ok(BlobMsg::Blob(blob)) = recv!(self) {
+            let _ = self.seen.send(blob);
+        }
+    }
+}
+
+#[tokio::test]
+async fn broadcast_receivers_share_one_allocation() {
+    let (seen, mut received) = mpsc::unbounded_channel();
+    let handles: Vec<_> = (0..4)
+        .map(|_| Reader { seen: seen.clone() }.run())
+        .collect();
+
+    let blob = ArcMessage::new(vec![7u8; 1 << 20]);
+    let results = cast_all!(&handles, BlobMsg::Blob(blob.clone()));
+    assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+    for _ in 0..4 {
+        let copy = received.recv().await.unwrap();
+        assert!(ArcMessage::ptr_eq(&copy, &blob));
+        assert_eq!(copy.len(), 1 << 20);
+    }
+
+    for handle in handles {
+        handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    }
+}
+
+#[test]
+fn payload_is_unwrapped_once_unshared() {
+    let blob = ArcMessage::new(String::from("snapshot"));
+    let copy = blob.clone();
+    assert_eq!(ArcMessage::copies(&blob), 2);
+
+    let blob = ArcMessage::try_unwrap(blob).unwrap_err();
+    drop(copy);
+
+    assert_eq!(ArcMessage::try_unwrap(blob).unwrap(), "snapshot");
+}
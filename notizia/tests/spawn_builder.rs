@@ -0,0 +1,136 @@
+//! Integration tests for spawning tasks through `SpawnBuilder`.
+
+use notizia::core::{bounded, unbounded};
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{Notify, oneshot};
+
+enum GateMsg {
+    Hold,
+    Ping(oneshot::Sender<()>),
+}
+
+#[derive(Task)]
+#[task(message = GateMsg)]
+struct Gate {
+    open: Arc<Notify>,
+}
+
+impl Runnable<GateMsg> for Gate {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                GateMsg::Hold => self.open.notified().await,
+                GateMsg::Ping(done) => {
+                    let _ = done.send(());
+                }
+            }
+        }
+    }
+}
+
+enum Never {}
+
+#[derive(Task)]
+#[task(message = Never)]
+struct Stubborn;
+
+impl Runnable<Never> for Stubborn {
+    async fn start(&self) {
+        while recv!(self).is_ok() {}
+    }
+
+    async fn terminate(&self, _reason: TerminateReason) {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+fn gate() -> (Gate, Arc<Notify>) {
+    let open = Arc::new(Notify::new());
+    (Gate { open: open.clone() }, open)
+}
+
+#[tokio::test]
+async fn defaults_match_run() {
+    let (task, _open) = gate();
+    let handle = task.builder().spawn();
+
+    assert_eq!(handle.name(), "Gate");
+    assert_eq!(
+        handle.shutdown_timeout(),
+        notizia::core::lifecycle::DEFAULT_SHUTDOWN_TIMEOUT
+    );
+}
+
+#[tokio::test]
+async fn name_is_used_by_handle_and_refs() {
+    let (task, _open) = gate();
+    let handle = task.builder().name("gate-1").spawn();
+
+    assert_eq!(handle.name(), "gate-1");
+    assert_eq!(handle.this().name(), "gate-1");
+}
+
+#[tokio::test]
+async fn bounded_mailbox_rejects_messages_when_full() {
+    let (task, open) = gate();
+    let handle = task.builder().mailbox(bounded(2)).spawn();
+
+    handle.send(GateMsg::Hold).unwrap();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+
+    // The task is stuck on `Hold`, so two messages fill the mailbox
+    let (first, first_done) = oneshot::channel();
+    let (second, second_done) = oneshot::channel();
+    handle.send(GateMsg::Ping(first)).unwrap();
+    handle.this().send(GateMsg::Ping(second)).unwrap();
+
+    let (third, _) = oneshot::channel();
+    assert!(handle.send(GateMsg::Ping(third)).is_err());
+
+    open.notify_one();
+    first_done.await.unwrap();
+    second_done.await.unwrap();
+
+    // Received messages free their slots again
+    let (fourth, fourth_done) = oneshot::channel();
+    handle.send(GateMsg::Ping(fourth)).unwrap();
+    fourth_done.await.unwrap();
+}
+
+#[tokio::test]
+async fn unbounded_mailbox_accepts_many_messages() {
+    let (task, open) = gate();
+    let handle = task.builder().mailbox(unbounded()).spawn();
+
+    handle.send(GateMsg::Hold).unwrap();
+    for _ in 0..1000 {
+        let (done, _) = oneshot::channel();
+        handle.send(GateMsg::Ping(done)).unwrap();
+    }
+
+    open.notify_one();
+}
+
+#[tokio::test]
+async fn stop_uses_configured_shutdown_timeout() {
+    let handle = Stubborn
+        .builder()
+        .shutdown_timeout(Duration::from_millis(20))
+        .spawn();
+
+    assert_eq!(handle.shutdown_timeout(), Duration::from_millis(20));
+    assert!(matches!(handle.stop().await, Err(ShutdownError::Timeout)));
+}
+
+#[tokio::test]
+async fn passivate_after_is_applied() {
+    let (task, _open) = gate();
+    let handle = task
+        .builder()
+        .passivate_after(Duration::from_millis(20))
+        .spawn();
+
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Idle);
+}
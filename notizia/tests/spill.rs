@@ -0,0 +1,140 @@
+//! Integration tests for mailboxes spilling to disk.
+
+#![cfg(feature = "spill")]
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::core::spill::SpillFile;
+use notizia::prelude::*;
+use tokio::sync::Notify;
+
+/// Waits for `gate` before receiving, so messages pile up in the mailbox.
+#[derive(Task)]
+#[task(message = u32)]
+struct Gated {
+    gate: Arc<Notify>,
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Runnable<u32> for Gated {
+    async fn start(&self) {
+        self.gate.notified().await;
+        while let Ok(msg) = recv!(self) {
+            self.seen.lock().unwrap().push(msg);
+        }
+    }
+}
+
+fn spawn_gated(
+    file: SpillFile,
+    threshold: usize,
+) -> (TaskHandle<u32>, Arc<Notify>, Arc<Mutex<Vec<u32>>>) {
+    let gate = Arc::new(Notify::new());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Gated {
+        gate: gate.clone(),
+        seen: seen.clone(),
+    }
+    .builder()
+    .spill(file, threshold)
+    .spawn();
+
+    (handle, gate, seen)
+}
+
+async fn drain(handle: TaskHandle<u32>, gate: Arc<Notify>, seen: Arc<Mutex<Vec<u32>>>) -> Vec<u32> {
+    gate.notify_one();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+    let seen = seen.lock().unwrap();
+    seen.clone()
+}
+
+fn temp_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("notizia-{}-{name}.spill", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    path
+}
+
+fn spilled(path: &PathBuf) -> usize {
+    std::fs::read_to_string(path).unwrap().lines().count()
+}
+
+#[tokio::test]
+async fn messages_beyond_the_threshold_are_spilled_and_delivered_in_order() {
+    let path = temp_file("order");
+    let (handle, gate, seen) = spawn_gated(SpillFile::open(&path).unwrap(), 10);
+
+    for i in 0..100 {
+        handle.send(i).unwrap();
+    }
+    assert_eq!(spilled(&path), 90);
+
+    assert_eq!(
+        drain(handle, gate, seen).await,
+        (0..100).collect::<Vec<_>>()
+    );
+    assert_eq!(spilled(&path), 0);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn memory_is_used_again_once_the_spill_file_is_drained() {
+    let path = temp_file("drained");
+    let (handle, gate, seen) = spawn_gated(SpillFile::open(&path).unwrap(), 2);
+
+    for i in 0..4 {
+        handle.send(i).unwrap();
+    }
+    gate.notify_one();
+    while seen.lock().unwrap().len() < 4 {
+        tokio::task::yield_now().await;
+    }
+
+    handle.send(4).unwrap();
+    assert_eq!(spilled(&path), 0);
+
+    assert_eq!(
+        drain(handle, Arc::new(Notify::new()), seen).await,
+        vec![0, 1, 2, 3, 4]
+    );
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn leftover_spilled_messages_are_delivered_first() {
+    let path = temp_file("leftover");
+    // The last message was cut off while being written
+    std::fs::write(&path, "1\n2\n3").unwrap();
+
+    let file = SpillFile::open(&path).unwrap();
+    assert_eq!(file.len(), 2);
+    let (handle, gate, seen) = spawn_gated(file, 10);
+
+    handle.send(10).unwrap();
+    handle.send(11).unwrap();
+
+    assert_eq!(drain(handle, gate, seen).await, vec![1, 2, 10, 11]);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[tokio::test]
+async fn sends_to_a_stopped_task_fail() {
+    let path = temp_file("stopped");
+    let (handle, gate, _) = spawn_gated(SpillFile::open(&path).unwrap(), 1);
+
+    handle.signal(SystemSignal::Stop).unwrap();
+    gate.notify_one();
+    while !handle.this().is_closed() {
+        tokio::task::yield_now().await;
+    }
+
+    assert_eq!(handle.send(1).unwrap_err().0, 1);
+    assert_eq!(handle.send(2).unwrap_err().0, 2);
+    assert_eq!(spilled(&path), 0);
+
+    std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,115 @@
+//! Integration tests for single-producer mailboxes.
+
+#![cfg(feature = "spsc")]
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::feed;
+use notizia::prelude::*;
+use tokio::sync::Notify;
+
+/// Waits for `gate` before receiving, so messages pile up in the ring.
+#[derive(Task)]
+#[task(message = u32)]
+struct Gated {
+    gate: Arc<Notify>,
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Runnable<u32> for Gated {
+    async fn start(&self) {
+        self.gate.notified().await;
+        while let Ok(msg) = recv!(self) {
+            self.seen.lock().unwrap().push(msg);
+        }
+    }
+}
+
+fn spawn_gated(capacity: usize) -> (TaskHandle<u32>, Arc<Notify>, Arc<Mutex<Vec<u32>>>) {
+    let gate = Arc::new(Notify::new());
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Gated {
+        gate: gate.clone(),
+        seen: seen.clone(),
+    }
+    .builder()
+    .single_producer(capacity)
+    .spawn();
+
+    (handle, gate, seen)
+}
+
+#[tokio::test]
+async fn messages_are_received_in_order() {
+    let (handle, gate, seen) = spawn_gated(64);
+    gate.notify_one();
+
+    for i in 0..1000 {
+        feed!(handle, i).await.unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), (0..1000).collect::<Vec<_>>());
+}
+
+#[tokio::test]
+async fn sending_to_a_full_ring_fails() {
+    let (handle, gate, seen) = spawn_gated(2);
+
+    handle.send(1).unwrap();
+    handle.send(2).unwrap();
+    assert_eq!(handle.send(3).unwrap_err().0, 3);
+
+    let blocked = tokio::time::timeout(Duration::from_millis(50), feed!(handle, 3)).await;
+    assert!(blocked.is_err(), "feeding a full ring should wait");
+
+    gate.notify_one();
+    feed!(handle, 3).await.unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(*seen.lock().unwrap(), vec![1, 2, 3]);
+}
+
+#[tokio::test]
+async fn signals_are_handled_after_earlier_messages() {
+    let (handle, gate, seen) = spawn_gated(8);
+    let task = handle.this();
+
+    task.send(1).unwrap();
+    task.send(2).unwrap();
+    task.signal(SystemSignal::Stop).unwrap();
+    gate.notify_one();
+
+    while !task.is_closed() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(task.send(3).unwrap_err().0, 3);
+    assert_eq!(*seen.lock().unwrap(), vec![1, 2]);
+}
+
+#[tokio::test]
+async fn several_producers_are_serialized() {
+    let (handle, gate, seen) = spawn_gated(16);
+    gate.notify_one();
+
+    let producers: Vec<_> = (0..4)
+        .map(|producer| {
+            let task = handle.this();
+            tokio::spawn(async move {
+                for i in 0..100 {
+                    feed!(task, producer * 100 + i).await.unwrap();
+                }
+            })
+        })
+        .collect();
+    for producer in producers {
+        producer.await.unwrap();
+    }
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_unstable();
+    assert_eq!(seen, (0..400).collect::<Vec<_>>());
+}
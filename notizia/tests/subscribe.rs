@@ -0,0 +1,96 @@
+//! Integration tests for `Context::subscribe`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use tokio::sync::broadcast;
+use tokio::time::sleep;
+
+enum Msg {
+    Listen(broadcast::Receiver<u32>),
+    ListenWithGaps(broadcast::Receiver<u32>),
+    Event(u32),
+    Gap(u64),
+}
+
+/// Subscribes to the channels it is given, recording what arrives.
+#[derive(Task, Default)]
+#[task(message = Msg, handler)]
+struct Listener {
+    log: Arc<Mutex<Vec<String>>>,
+}
+
+impl Handler<Msg> for Listener {
+    async fn handle(&mut self, msg: Msg, ctx: &mut Context<Msg>) {
+        match msg {
+            Msg::Listen(events) => {
+                ctx.subscribe(events, Msg::Event);
+            }
+            Msg::ListenWithGaps(events) => {
+                ctx.subscribe_with_lag(events, Msg::Event, |skipped| Some(Msg::Gap(skipped)));
+            }
+            Msg::Event(event) => self.log.lock().unwrap().push(format!("event {event}")),
+            Msg::Gap(skipped) => self.log.lock().unwrap().push(format!("gap {skipped}")),
+        }
+    }
+}
+
+fn listener() -> (TaskHandle<Msg>, Arc<Mutex<Vec<String>>>) {
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let handle = Listener { log: log.clone() }.run();
+    (handle, log)
+}
+
+#[tokio::test]
+async fn broadcast_events_arrive_as_messages() {
+    let (handle, log) = listener();
+    let (events, receiver) = broadcast::channel(8);
+    handle.send(Msg::Listen(receiver)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    events.send(1).unwrap();
+    events.send(2).unwrap();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(*log.lock().unwrap(), ["event 1", "event 2"]);
+
+    // Closing the channel ends the subscription
+    drop(events);
+    handle.signal(SystemSignal::Stop).unwrap();
+    handle.join().await.unwrap();
+}
+
+#[tokio::test]
+async fn lagged_subscriptions_report_skipped_events() {
+    let (handle, log) = listener();
+    let (events, receiver) = broadcast::channel(2);
+    for event in 0..5 {
+        events.send(event).unwrap();
+    }
+
+    handle.send(Msg::ListenWithGaps(receiver)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(*log.lock().unwrap(), ["gap 3", "event 3", "event 4"]);
+
+    // Without a gap message, skipped events go unnoticed
+    let (events, receiver) = broadcast::channel(2);
+    for event in 0..5 {
+        events.send(event).unwrap();
+    }
+    log.lock().unwrap().clear();
+    handle.send(Msg::Listen(receiver)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(*log.lock().unwrap(), ["event 3", "event 4"]);
+}
+
+#[tokio::test]
+async fn subscriptions_do_not_keep_the_task_alive() {
+    let (handle, _) = listener();
+    let (_events, receiver) = broadcast::channel::<u32>(8);
+    handle.send(Msg::Listen(receiver)).unwrap();
+    sleep(Duration::from_millis(10)).await;
+
+    let result = handle.shutdown(Duration::from_secs(1)).await;
+    assert!(matches!(result, Ok(TerminateReason::Normal)));
+}
@@ -0,0 +1,313 @@
+//! Integration tests for task hierarchies in a `System`.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::system::{Spec, StartError, System, TaskStatus};
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Leaf;
+
+impl Runnable<u32> for Leaf {
+    async fn start(&self) {
+        while recv!(self).is_ok() {}
+    }
+}
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Parent {
+    system: System,
+}
+
+impl Runnable<u32> for Parent {
+    async fn start(&self) {
+        // Keep the child's handle until the parent is done
+        let _child = self.system.spawn("child", Leaf);
+        while recv!(self).is_ok() {}
+    }
+}
+
+async fn settle() {
+    tokio::time::sleep(Duration::from_millis(20)).await;
+}
+
+#[tokio::test]
+async fn tasks_spawned_by_system_tasks_are_their_children() {
+    let system = System::new();
+    let parent = system.spawn(
+        "parent",
+        Parent {
+            system: system.clone(),
+        },
+    );
+    settle().await;
+
+    let tasks = system.tasks();
+    assert_eq!(
+        tasks
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect::<Vec<_>>(),
+        ["child", "parent"]
+    );
+    assert_eq!(tasks[0].parent.as_deref(), Some("parent"));
+    assert_eq!(tasks[0].task, "Leaf");
+    assert_eq!(tasks[1].parent, None);
+    assert_eq!(tasks[1].id, parent.id());
+    assert!(tasks.iter().all(|task| task.status == TaskStatus::Running));
+    assert!(system.registry().whereis::<u32>("child").is_some());
+}
+
+#[tokio::test]
+async fn replacing_a_task_counts_as_restart() {
+    let system = System::new();
+    let first = system.spawn("leaf", Leaf);
+    first.signal(SystemSignal::Stop).unwrap();
+    settle().await;
+    assert_eq!(system.tasks()[0].status, TaskStatus::Terminated);
+
+    system.spawn("leaf", Leaf);
+    let tasks = system.tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].restarts, 1);
+    assert_eq!(tasks[0].status, TaskStatus::Running);
+}
+
+#[tokio::test]
+async fn exports_dot_and_json() {
+    let system = System::new();
+    let parent = system.spawn(
+        "parent",
+        Parent {
+            system: system.clone(),
+        },
+    );
+    settle().await;
+    let (parent_id, child_id) = (parent.id(), system.tasks()[0].id);
+
+    let dot = system.export_dot();
+    assert!(dot.starts_with("digraph system {"));
+    assert!(dot.contains(&format!(
+        r#""child" [label="child\nLeaf #{child_id}\nrunning, 0 restarts"];"#
+    )));
+    assert!(dot.contains(r#""parent" -> "child";"#));
+
+    assert_eq!(
+        system.export_json(),
+        format!(
+            concat!(
+                r#"[{{"name":"child","task":"Leaf","id":{},"parent":"parent","status":"running","restarts":0}},"#,
+                r#"{{"name":"parent","task":"Parent","id":{},"parent":null,"status":"running","restarts":0}}]"#,
+            ),
+            child_id, parent_id
+        )
+    );
+}
+
+/// Records its name once initialized.
+#[derive(Task)]
+#[task(message = u32)]
+struct Service {
+    name: &'static str,
+    started: Arc<Mutex<Vec<&'static str>>>,
+}
+
+impl Runnable<u32> for Service {
+    async fn start(&self) {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        self.started.lock().unwrap().push(self.name);
+        while recv!(self).is_ok() {}
+    }
+}
+
+fn service(name: &'static str, started: &Arc<Mutex<Vec<&'static str>>>) -> Spec {
+    Spec::new(
+        name,
+        Service {
+            name,
+            started: started.clone(),
+        },
+    )
+}
+
+#[tokio::test]
+async fn start_spawns_tasks_after_their_dependencies() {
+    let system = System::new();
+    let started = Arc::new(Mutex::new(Vec::new()));
+    system.declare(service("api", &started).after("cache").after("db"));
+    system.declare(service("cache", &started).after("db"));
+    system.declare(service("db", &started));
+
+    system.start().await.unwrap();
+    assert_eq!(*started.lock().unwrap(), ["db", "cache", "api"]);
+    assert!(system.registry().whereis::<u32>("api").is_some());
+    assert_eq!(system.tasks().len(), 3);
+}
+
+#[tokio::test]
+async fn start_fails_on_cycles_without_spawning() {
+    let system = System::new();
+    let started = Arc::new(Mutex::new(Vec::new()));
+    system.declare(service("a", &started).after("c"));
+    system.declare(service("b", &started).after("a"));
+    system.declare(service("c", &started).after("b"));
+    system.declare(service("d", &started));
+
+    let err = system.start().await.unwrap_err();
+    assert!(matches!(&err, StartError::Cycle(cycle) if cycle == &["a", "c", "b", "a"]));
+    assert_eq!(err.to_string(), "dependency cycle: a -> c -> b -> a");
+    assert!(system.tasks().is_empty());
+}
+
+#[tokio::test]
+async fn start_fails_on_unknown_dependencies() {
+    let system = System::new();
+    let started = Arc::new(Mutex::new(Vec::new()));
+    let _db = system.spawn("db", Leaf);
+    system.declare(service("api", &started).after("db"));
+    system.declare(service("cache", &started).after("redis"));
+
+    let err = system.start().await.unwrap_err();
+    assert!(matches!(
+        err,
+        StartError::UnknownDependency { task, dependency } if task == "cache" && dependency == "redis"
+    ));
+}
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Stuck;
+
+impl Runnable<u32> for Stuck {
+    async fn start(&self) {
+        tokio::time::sleep(Duration::from_secs(60)).await;
+    }
+}
+
+#[tokio::test]
+async fn start_stops_at_tasks_that_do_not_become_ready() {
+    let system = System::new();
+    let started = Arc::new(Mutex::new(Vec::new()));
+    system.declare(Spec::new("stuck", Stuck).ready_timeout(Duration::from_millis(50)));
+    system.declare(service("api", &started).after("stuck"));
+
+    let err = system.start().await.unwrap_err();
+    assert!(matches!(err, StartError::NotReady { task, .. } if task == "stuck"));
+    assert!(started.lock().unwrap().is_empty());
+}
+
+/// Counts the messages it receives.
+#[derive(Task)]
+#[task(message = u32)]
+struct Counter {
+    received: Arc<AtomicUsize>,
+}
+
+impl Runnable<u32> for Counter {
+    async fn start(&self) {
+        while recv!(self).is_ok() {
+            self.received.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+#[tokio::test]
+async fn lazy_tasks_spawn_on_their_first_message() {
+    let system = System::new();
+    let built = Arc::new(AtomicUsize::new(0));
+    let received = Arc::new(AtomicUsize::new(0));
+    let cache = system.register_lazy("cache", {
+        let built = built.clone();
+        let received = received.clone();
+        move || {
+            built.fetch_add(1, Ordering::SeqCst);
+            Counter { received }
+        }
+    });
+
+    cache.ping(Duration::from_secs(1)).await.unwrap();
+    assert_eq!(built.load(Ordering::SeqCst), 0);
+    assert!(system.tasks().is_empty());
+    assert_eq!(
+        system.registry().whereis::<u32>("cache").unwrap().id(),
+        cache.id()
+    );
+
+    cache.send(1).unwrap();
+    cache.send(2).unwrap();
+    settle().await;
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+    assert_eq!(received.load(Ordering::SeqCst), 2);
+
+    let tasks = system.tasks();
+    assert_eq!(tasks.len(), 1);
+    assert_eq!(tasks[0].id, cache.id());
+    assert_eq!(tasks[0].task, "Counter");
+}
+
+#[tokio::test]
+async fn lazy_tasks_without_references_are_never_spawned() {
+    let system = System::new();
+    let built = Arc::new(AtomicUsize::new(0));
+    let cache = system.register_lazy("cache", {
+        let built = built.clone();
+        move || {
+            built.fetch_add(1, Ordering::SeqCst);
+            Counter {
+                received: Arc::default(),
+            }
+        }
+    });
+
+    drop(cache);
+    settle().await;
+    assert_eq!(built.load(Ordering::SeqCst), 0);
+    assert!(system.registry().whereis::<u32>("cache").is_none());
+}
+
+#[tokio::test]
+async fn singletons_are_spawned_once() {
+    let system = System::new();
+    let built = Arc::new(AtomicUsize::new(0));
+    let factory = || {
+        built.fetch_add(1, Ordering::SeqCst);
+        Counter {
+            received: Arc::default(),
+        }
+    };
+
+    let first = system.singleton(factory);
+    let second = system.singleton(factory);
+    assert_eq!(first.id(), second.id());
+    assert_eq!(built.load(Ordering::SeqCst), 1);
+    assert_eq!(system.tasks()[0].name, "Counter");
+
+    first.signal(SystemSignal::Stop).unwrap();
+    settle().await;
+    let third = system.singleton(factory);
+    assert_ne!(third.id(), first.id());
+    assert_eq!(built.load(Ordering::SeqCst), 2);
+    assert_eq!(system.tasks()[0].restarts, 1);
+}
+
+#[tokio::test]
+async fn singleton_factories_may_use_the_system() {
+    let system = System::new();
+    let factory = || {
+        system.singleton(|| Leaf);
+        Counter {
+            received: Arc::default(),
+        }
+    };
+
+    let counter = system.singleton(factory);
+    let mut names: Vec<_> = system.tasks().into_iter().map(|task| task.name).collect();
+    names.sort();
+    assert_eq!(names, ["Counter", "Leaf"]);
+    assert_eq!(system.singleton(factory).id(), counter.id());
+}
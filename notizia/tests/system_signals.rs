@@ -0,0 +1,124 @@
+//! Integration tests for system signals handled by the mailbox.
+
+use notizia::core::{SystemSignal, bounded};
+use notizia::prelude::*;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Recorder {
+    seen: mpsc::UnboundedSender<u32>,
+}
+
+impl Runnable<u32> for Recorder {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            let _ = self.seen.send(msg);
+        }
+    }
+}
+
+fn recorder() -> (Recorder, mpsc::UnboundedReceiver<u32>) {
+    let (seen, received) = mpsc::unbounded_channel();
+    (Recorder { seen }, received)
+}
+
+async fn next(received: &mut mpsc::UnboundedReceiver<u32>) -> Option<u32> {
+    tokio::time::timeout(Duration::from_millis(50), received.recv())
+        .await
+        .ok()
+        .flatten()
+}
+
+#[tokio::test]
+async fn suspend_holds_messages_until_resume() {
+    let (task, mut received) = recorder();
+    let handle = task.run();
+
+    handle.send(1).unwrap();
+    handle.signal(SystemSignal::Suspend).unwrap();
+    handle.send(2).unwrap();
+    handle.send(3).unwrap();
+
+    assert_eq!(next(&mut received).await, Some(1));
+    assert_eq!(next(&mut received).await, None);
+
+    handle.signal(SystemSignal::Resume).unwrap();
+    handle.send(4).unwrap();
+
+    assert_eq!(next(&mut received).await, Some(2));
+    assert_eq!(next(&mut received).await, Some(3));
+    assert_eq!(next(&mut received).await, Some(4));
+}
+
+#[tokio::test]
+async fn stop_closes_the_mailbox_after_earlier_messages() {
+    let (task, mut received) = recorder();
+    let handle = task.run();
+    let task_ref = handle.this();
+
+    handle.send(1).unwrap();
+    handle.signal(SystemSignal::Stop).unwrap();
+
+    assert_eq!(next(&mut received).await, Some(1));
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Normal);
+    assert!(task_ref.send(2).is_err());
+    assert!(task_ref.signal(SystemSignal::Resume).is_err());
+}
+
+#[tokio::test]
+async fn held_messages_are_delivered_when_the_mailbox_closes() {
+    let (task, mut received) = recorder();
+    let handle = task.run();
+
+    handle.signal(SystemSignal::Suspend).unwrap();
+    handle.send(1).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(next(&mut received).await, Some(1));
+}
+
+#[tokio::test]
+async fn held_messages_count_towards_capacity() {
+    let (task, mut received) = recorder();
+    let handle = task.builder().mailbox(bounded(2)).spawn();
+
+    handle.signal(SystemSignal::Suspend).unwrap();
+    handle.send(1).unwrap();
+    handle.send(2).unwrap();
+    tokio::task::yield_now().await;
+
+    assert!(handle.send(3).is_err());
+    // Signals bypass the capacity
+    handle.signal(SystemSignal::Resume).unwrap();
+
+    assert_eq!(next(&mut received).await, Some(1));
+    assert_eq!(next(&mut received).await, Some(2));
+}
+
+#[tokio::test]
+async fn signals_are_handled_without_the_task_seeing_them() {
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    #[derive(Task)]
+    #[task(message = u32, handler)]
+    struct Counter {
+        log: Arc<Mutex<Vec<u32>>>,
+    }
+
+    impl Handler<u32> for Counter {
+        async fn handle(&mut self, msg: u32, _ctx: &mut Context<u32>) {
+            self.log.lock().unwrap().push(msg);
+        }
+    }
+
+    let handle = Counter { log: log.clone() }.run();
+    handle.signal(SystemSignal::Suspend).unwrap();
+    handle.signal(SystemSignal::Resume).unwrap();
+    handle.send(7).unwrap();
+    handle.shutdown(Duration::from_secs(1)).await.unwrap();
+
+    assert_eq!(*log.lock().unwrap(), vec![7]);
+}
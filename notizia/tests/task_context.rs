@@ -0,0 +1,85 @@
+//! Integration tests for the task context of log output.
+
+use notizia::core::trace::TaskContext;
+use notizia::prelude::*;
+use tokio::sync::oneshot;
+
+#[derive(Task)]
+#[task(message = oneshot::Sender<Option<TaskContext>>)]
+struct Reporter;
+
+impl Runnable<oneshot::Sender<Option<TaskContext>>> for Reporter {
+    async fn start(&self) {
+        while let Ok(reply) = recv!(self) {
+            let _ = reply.send(TaskContext::current());
+        }
+    }
+}
+
+#[tokio::test]
+async fn context_identifies_the_running_task() {
+    assert_eq!(TaskContext::current(), None);
+
+    let handle = spawn!(Reporter);
+    let (tx, rx) = oneshot::channel();
+    handle.send(tx).unwrap();
+
+    let context = rx.await.unwrap().unwrap();
+    assert_eq!(context.id, handle.id());
+    assert_eq!(context.name, "Reporter");
+    assert_eq!(context.path, "task_context::Reporter");
+    assert_eq!(context.to_string(), format!("Reporter#{}", handle.id()));
+}
+
+#[cfg(feature = "log")]
+#[tokio::test]
+async fn task_logger_prefixes_records_inside_tasks() {
+    use notizia::core::trace::TaskLogger;
+    use std::sync::Mutex;
+
+    static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+    struct Capture;
+
+    impl log::Log for Capture {
+        fn enabled(&self, _metadata: &log::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn log(&self, record: &log::Record<'_>) {
+            LINES.lock().unwrap().push(record.args().to_string());
+        }
+
+        fn flush(&self) {}
+    }
+
+    #[derive(Task)]
+    #[task(message = oneshot::Sender<()>)]
+    struct Logger;
+
+    impl Runnable<oneshot::Sender<()>> for Logger {
+        async fn start(&self) {
+            while let Ok(done) = recv!(self) {
+                log::info!("inside");
+                let _ = done.send(());
+            }
+        }
+    }
+
+    TaskLogger::new(Capture).install().unwrap();
+    log::set_max_level(log::LevelFilter::Info);
+
+    log::info!("outside");
+    let handle = spawn!(Logger);
+    let (tx, rx) = oneshot::channel();
+    handle.send(tx).unwrap();
+    rx.await.unwrap();
+
+    assert_eq!(
+        *LINES.lock().unwrap(),
+        [
+            "outside".to_string(),
+            format!("[Logger#{}] inside", handle.id())
+        ]
+    );
+}
@@ -301,3 +301,78 @@ async fn task_ref_send_fails_when_task_terminated() {
     // Sending should fail
     assert!(task_ref.send(QuickMsg).is_err());
 }
+
+#[derive(Task)]
+#[task(message = u32)]
+struct SelfCheck {
+    is_self: Arc<AtomicBool>,
+}
+
+impl Runnable<u32> for SelfCheck {
+    async fn start(&self) {
+        self.is_self
+            .store(self.this().is_current(), Ordering::SeqCst);
+    }
+}
+
+#[derive(Task)]
+#[task(message = u32)]
+struct Sink;
+
+impl Runnable<u32> for Sink {
+    async fn start(&self) {
+        while recv!(self).is_ok() {}
+    }
+}
+
+#[tokio::test]
+async fn task_refs_compare_by_task() {
+    let first = spawn!(Sink);
+    let second = spawn!(Sink);
+
+    assert!(first.this().same_task(&first.this()));
+    assert!(!first.this().same_task(&second.this()));
+    assert_eq!(first.this(), first.this());
+    assert_ne!(first.this(), second.this());
+
+    // Only the id is hashed, so the sender's interior mutability is harmless
+    #[allow(clippy::mutable_key_type)]
+    let subscribers: std::collections::HashSet<_> = [first.this(), second.this(), first.this()]
+        .into_iter()
+        .collect();
+    assert_eq!(subscribers.len(), 2);
+    assert!(!first.this().is_current());
+}
+
+#[tokio::test]
+async fn task_ref_detects_the_current_task() {
+    let is_self = Arc::new(AtomicBool::new(false));
+    let handle = SelfCheck {
+        is_self: is_self.clone(),
+    }
+    .run();
+
+    assert!(!handle.this().is_current());
+    handle.join().await.unwrap();
+    assert!(is_self.load(Ordering::SeqCst));
+}
+
+#[tokio::test]
+async fn task_refs_and_handles_display_their_task() {
+    let handle = spawn!(Sink);
+    let task = handle.this();
+    let id = handle.id();
+
+    assert_eq!(task.path(), "task_ref::Sink");
+    assert_eq!(task.to_string(), format!("Sink#{id} (alive)"));
+    assert_eq!(handle.to_string(), format!("Sink#{id} (running)"));
+    assert_eq!(
+        format!("{task:?}"),
+        format!(r#"TaskRef {{ name: "Sink", id: {id:?}, path: "task_ref::Sink", alive: true }}"#)
+    );
+    assert!(format!("{handle:?}").contains("finished: false"));
+
+    task.signal(notizia::core::SystemSignal::Stop).unwrap();
+    handle.join().await.unwrap();
+    assert_eq!(task.to_string(), format!("Sink#{id} (closed)"));
+}
@@ -0,0 +1,124 @@
+//! Integration tests for joining groups of tasks with `TaskSet`.
+
+use notizia::prelude::*;
+use std::time::Duration;
+
+struct Tick;
+
+enum Never {}
+
+#[derive(Task)]
+#[task(message = Tick)]
+struct Sleeper {
+    millis: u64,
+}
+
+impl Runnable<Tick> for Sleeper {
+    async fn start(&self) {
+        tokio::time::sleep(Duration::from_millis(self.millis)).await;
+    }
+}
+
+#[derive(Task)]
+#[task(message = Never)]
+struct Listener;
+
+impl Runnable<Never> for Listener {
+    async fn start(&self) {
+        // Runs until the mailbox is closed
+        while recv!(self).is_ok() {}
+    }
+}
+
+#[derive(Task)]
+#[task(message = Tick)]
+struct Stubborn;
+
+impl Runnable<Tick> for Stubborn {
+    async fn start(&self) {
+        std::future::pending::<()>().await;
+    }
+}
+
+#[tokio::test]
+async fn tasks_are_joined_in_completion_order() {
+    let mut set = TaskSet::new();
+
+    let slow = set.insert(Sleeper { millis: 60 }.run());
+    let fast = set.insert(Sleeper { millis: 10 }.run());
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(slow));
+
+    let (first, reason) = set.join_next().await.unwrap();
+    assert_eq!(first, fast);
+    assert_eq!(reason.unwrap(), TerminateReason::Normal);
+    assert!(!set.contains(fast));
+
+    let (second, _) = set.join_next().await.unwrap();
+    assert_eq!(second, slow);
+
+    assert!(set.is_empty());
+    assert!(set.join_next().await.is_none());
+}
+
+#[tokio::test]
+async fn set_holds_tasks_with_different_message_types() {
+    let mut set = TaskSet::new();
+    set.insert(Sleeper { millis: 5 }.run());
+    let listener = set.insert(spawn!(Listener));
+
+    let (id, _) = set.join_next().await.unwrap();
+    assert_ne!(id, listener);
+
+    // The set keeps the listener's mailbox open
+    let pending = tokio::time::timeout(Duration::from_millis(30), set.join_next()).await;
+    assert!(pending.is_err());
+    assert!(set.contains(listener));
+}
+
+#[tokio::test]
+async fn shutdown_all_closes_mailboxes() {
+    let mut set = TaskSet::new();
+    let a = set.insert(spawn!(Listener));
+    let b = set.insert(spawn!(Listener));
+
+    let results = set.shutdown_all(Duration::from_secs(1)).await;
+
+    assert_eq!(results.len(), 2);
+    for (id, result) in results {
+        assert!(id == a || id == b);
+        assert_eq!(result.unwrap(), TerminateReason::Normal);
+    }
+}
+
+#[tokio::test]
+async fn shutdown_all_reports_tasks_exceeding_timeout() {
+    let mut set = TaskSet::new();
+    let listener = set.insert(spawn!(Listener));
+    let stubborn = set.insert(spawn!(Stubborn));
+
+    let results = set.shutdown_all(Duration::from_millis(50)).await;
+
+    assert_eq!(results.len(), 2);
+    for (id, result) in results {
+        if id == listener {
+            assert_eq!(result.unwrap(), TerminateReason::Normal);
+        } else {
+            assert_eq!(id, stubborn);
+            assert!(matches!(result, Err(ShutdownError::Timeout)));
+        }
+    }
+}
+
+#[tokio::test]
+async fn join_all_collects_every_task() {
+    let mut set = TaskSet::new();
+    for millis in [30, 10, 20] {
+        set.insert(Sleeper { millis }.run());
+    }
+
+    let results = set.join_all().await;
+
+    assert_eq!(results.len(), 3);
+    assert!(results.iter().all(|(_, reason)| reason.is_ok()));
+}
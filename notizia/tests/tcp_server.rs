@@ -0,0 +1,131 @@
+//! Integration tests for serving TCP connections with a task per connection.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use notizia::call;
+use notizia::core::Received;
+use notizia::net::{ServerMsg, TcpServerTask};
+use notizia::prelude::*;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use tokio::sync::Mutex;
+
+enum EchoMsg {}
+
+/// Greets the peer, then echoes its lines until it disconnects
+#[derive(Task)]
+#[task(message = EchoMsg)]
+struct Echo {
+    stream: Mutex<TcpStream>,
+}
+
+impl Runnable<EchoMsg> for Echo {
+    async fn start(&self) {
+        let mailbox = self.mailbox();
+        let mut stream = self.stream.lock().await;
+        let (read, mut write) = stream.split();
+        let mut lines = BufReader::new(read).lines();
+        write.write_all(b"hello\n").await.unwrap();
+
+        loop {
+            match mailbox.recv_or(lines.next_line()).await {
+                Ok(Received::Interrupted(Ok(Some(line)))) => {
+                    let _ = write.write_all(format!("{line}\n").as_bytes()).await;
+                }
+                Ok(Received::Message(never)) => match never {},
+                // Disconnected, or the server shuts down
+                _ => break,
+            }
+        }
+    }
+}
+
+struct Client {
+    lines: tokio::io::Lines<BufReader<OwnedReadHalf>>,
+    write: OwnedWriteHalf,
+}
+
+impl Client {
+    async fn connect(addr: SocketAddr) -> Self {
+        let (read, write) = TcpStream::connect(addr).await.unwrap().into_split();
+        Client {
+            lines: BufReader::new(read).lines(),
+            write,
+        }
+    }
+
+    async fn line(&mut self) -> Option<String> {
+        tokio::time::timeout(Duration::from_secs(5), self.lines.next_line())
+            .await
+            .unwrap()
+            .unwrap()
+    }
+}
+
+async fn server(max: Option<usize>) -> (TaskHandle<ServerMsg>, SocketAddr) {
+    let server = TcpServerTask::bind("127.0.0.1:0", |stream, _, _| Echo {
+        stream: Mutex::new(stream),
+    })
+    .await
+    .unwrap();
+    let server = match max {
+        Some(max) => server.max_connections(max),
+        None => server,
+    };
+    let addr = server.local_addr().unwrap();
+    (spawn!(server), addr)
+}
+
+#[tokio::test]
+async fn every_connection_gets_its_own_task() {
+    let (server, addr) = server(None).await;
+
+    let mut first = Client::connect(addr).await;
+    let mut second = Client::connect(addr).await;
+    assert_eq!(first.line().await.unwrap(), "hello");
+    assert_eq!(second.line().await.unwrap(), "hello");
+
+    first.write.write_all(b"one\n").await.unwrap();
+    second.write.write_all(b"two\n").await.unwrap();
+    assert_eq!(second.line().await.unwrap(), "two");
+    assert_eq!(first.line().await.unwrap(), "one");
+
+    let count = call!(server, |reply_to| ServerMsg::Connections { reply_to })
+        .await
+        .unwrap();
+    assert_eq!(count, 2);
+}
+
+#[tokio::test]
+async fn connection_limit_defers_accepting() {
+    let (_server, addr) = server(Some(1)).await;
+
+    let mut first = Client::connect(addr).await;
+    assert_eq!(first.line().await.unwrap(), "hello");
+
+    // Waits in the backlog until the first connection closes
+    let mut second = Client::connect(addr).await;
+    let greeting = tokio::time::timeout(Duration::from_millis(100), second.lines.next_line()).await;
+    assert!(greeting.is_err());
+
+    drop(first);
+    assert_eq!(second.line().await.unwrap(), "hello");
+}
+
+#[tokio::test]
+async fn shutdown_closes_all_connections() {
+    let (server, addr) = server(None).await;
+
+    let mut first = Client::connect(addr).await;
+    let mut second = Client::connect(addr).await;
+    assert_eq!(first.line().await.unwrap(), "hello");
+    assert_eq!(second.line().await.unwrap(), "hello");
+
+    server.send(ServerMsg::Shutdown).unwrap();
+    assert_eq!(first.line().await, None);
+    assert_eq!(second.line().await, None);
+
+    server.join().await.unwrap();
+}
@@ -0,0 +1,113 @@
+//! Integration tests for `TestProbe`.
+
+#![cfg(feature = "testing")]
+
+use notizia::expect_msg;
+use notizia::prelude::*;
+use notizia::testing::TestProbe;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Event {
+    Saved { id: u32 },
+    Deleted { id: u32 },
+}
+
+#[derive(Task)]
+#[task(message = i32)]
+struct Store {
+    events: TaskRef<Event>,
+}
+
+impl Runnable<i32> for Store {
+    async fn start(&self) {
+        while let Ok(id) = recv!(self) {
+            let event = match id {
+                id if id < 0 => Event::Deleted {
+                    id: id.unsigned_abs(),
+                },
+                id => Event::Saved { id: id as u32 },
+            };
+            self.events.send(event).unwrap();
+        }
+    }
+}
+
+fn store(probe: &TestProbe<Event>) -> TaskHandle<i32> {
+    let store = Store {
+        events: probe.this(),
+    };
+    spawn!(store)
+}
+
+#[tokio::test]
+async fn expects_messages_by_value_and_pattern() {
+    let probe = TestProbe::new();
+    let store = store(&probe);
+
+    store.send(1).unwrap();
+    store.send(-2).unwrap();
+    store.send(3).unwrap();
+
+    assert_eq!(
+        probe.expect_msg(Event::Saved { id: 1 }, 100).await,
+        Event::Saved { id: 1 }
+    );
+    let deleted = expect_msg!(probe, Event::Deleted { .. }, within = 100);
+    assert_eq!(deleted, Event::Deleted { id: 2 });
+    expect_msg!(probe, Event::Saved { id } if *id == 3);
+    probe.expect_no_msg(20).await;
+}
+
+#[tokio::test]
+async fn expects_messages_in_order() {
+    let probe = TestProbe::new();
+    let store = store(&probe);
+
+    for id in [4, -4, 5] {
+        store.send(id).unwrap();
+    }
+
+    probe
+        .expect_ordered(
+            [
+                Event::Saved { id: 4 },
+                Event::Deleted { id: 4 },
+                Event::Saved { id: 5 },
+            ],
+            100,
+        )
+        .await;
+}
+
+#[tokio::test]
+async fn probe_has_its_own_identity() {
+    let probe = TestProbe::<Event>::named("events");
+    let task = probe.this();
+
+    assert_eq!(task.id(), probe.id());
+    assert_eq!(task.name(), "events");
+    assert_eq!(probe.receive(10).await, None);
+}
+
+#[tokio::test]
+#[should_panic(expected = "probe received no message")]
+async fn missing_message_fails() {
+    let probe = TestProbe::<Event>::new();
+    probe.expect_msg(Event::Saved { id: 1 }, 10).await;
+}
+
+#[tokio::test]
+#[should_panic(expected = "probe received unexpected message")]
+async fn unexpected_message_fails() {
+    let probe = TestProbe::new();
+    probe.this().send(Event::Deleted { id: 1 }).unwrap();
+    expect_msg!(probe, Event::Saved { .. }, within = 10);
+}
+
+#[tokio::test]
+#[should_panic(expected = "probe received unexpected message")]
+async fn message_during_silence_fails() {
+    let probe = TestProbe::new();
+    probe.this().send(Event::Saved { id: 1 }).unwrap();
+    probe.expect_no_msg(10).await;
+}
@@ -0,0 +1,106 @@
+//! Integration tests for rate-limited task references.
+
+use notizia::core::errors::ThrottleError;
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, Instant, sleep};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Msg {
+    Hit(u32),
+}
+
+#[derive(Task)]
+#[task(message = Msg)]
+struct Sink {
+    hits: Arc<AtomicU32>,
+}
+
+impl Runnable<Msg> for Sink {
+    async fn start(&self) {
+        while let Ok(Msg::Hit(_)) = recv!(self) {
+            self.hits.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+}
+
+fn spawn_sink(hits: &Arc<AtomicU32>) -> TaskHandle<Msg> {
+    let task = Sink { hits: hits.clone() };
+    spawn!(task)
+}
+
+#[tokio::test]
+async fn try_send_rejects_messages_beyond_burst() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let handle = spawn_sink(&hits);
+    let sink = handle.this().throttled(10, 3);
+
+    for n in 0..3 {
+        sink.try_send(Msg::Hit(n)).unwrap();
+    }
+    let rejected = sink.try_send(Msg::Hit(3));
+
+    assert_eq!(rejected, Err(ThrottleError::RateLimited(Msg::Hit(3))));
+
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(hits.load(Ordering::SeqCst), 3);
+}
+
+#[tokio::test]
+async fn tokens_are_refilled_over_time() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let handle = spawn_sink(&hits);
+    let sink = handle.this().throttled(50, 1);
+
+    sink.try_send(Msg::Hit(0)).unwrap();
+    assert!(sink.try_send(Msg::Hit(1)).is_err());
+
+    sleep(Duration::from_millis(30)).await;
+    assert!(sink.try_send(Msg::Hit(2)).is_ok());
+}
+
+#[tokio::test]
+async fn send_waits_for_rate_limit() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let handle = spawn_sink(&hits);
+    let sink = handle.this().throttled(100, 1);
+
+    let started = Instant::now();
+    for n in 0..6 {
+        sink.send(Msg::Hit(n)).await.unwrap();
+    }
+
+    // One message from the burst, five more at 10ms each
+    assert!(started.elapsed() >= Duration::from_millis(45));
+
+    sleep(Duration::from_millis(10)).await;
+    assert_eq!(hits.load(Ordering::SeqCst), 6);
+}
+
+#[tokio::test]
+async fn clones_share_the_limit() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let handle = spawn_sink(&hits);
+    let first = handle.this().throttled(1, 2);
+    let second = first.clone();
+
+    first.try_send(Msg::Hit(0)).unwrap();
+    second.try_send(Msg::Hit(1)).unwrap();
+
+    assert!(first.try_send(Msg::Hit(2)).is_err());
+    assert!(second.try_send(Msg::Hit(3)).is_err());
+}
+
+#[tokio::test]
+async fn try_send_to_terminated_task_returns_message() {
+    let hits = Arc::new(AtomicU32::new(0));
+    let handle = spawn_sink(&hits);
+    let sink = handle.this().throttled(10, 10);
+
+    handle.kill();
+    sleep(Duration::from_millis(10)).await;
+
+    let result = sink.try_send(Msg::Hit(1));
+    assert_eq!(result, Err(ThrottleError::Closed(Msg::Hit(1))));
+}
@@ -0,0 +1,126 @@
+//! Integration tests for ticks declared with `#[task(tick = ...)]`.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::task::timer;
+use tokio::time::sleep;
+
+enum Msg {
+    Tick,
+    Flush,
+}
+
+/// Counts the ticks of two different periods.
+#[derive(Task)]
+#[task(message = Msg, handler, tick = "20ms" => Msg::Tick, tick = "1h" => Msg::Flush)]
+struct Poller {
+    ticks: Arc<AtomicU32>,
+    flushes: Arc<AtomicU32>,
+}
+
+impl Handler<Msg> for Poller {
+    async fn handle(&mut self, msg: Msg, _ctx: &mut Context<Msg>) {
+        let counter = match msg {
+            Msg::Tick => &self.ticks,
+            Msg::Flush => &self.flushes,
+        };
+        counter.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+#[tokio::test]
+async fn ticks_are_sent_every_period() {
+    let ticks = Arc::new(AtomicU32::new(0));
+    let flushes = Arc::new(AtomicU32::new(0));
+    let poller = Poller {
+        ticks: ticks.clone(),
+        flushes: flushes.clone(),
+    }
+    .run();
+
+    sleep(Duration::from_millis(110)).await;
+    let sent = ticks.load(Ordering::SeqCst);
+    assert!((3..=5).contains(&sent), "{sent} ticks");
+    assert_eq!(flushes.load(Ordering::SeqCst), 0);
+
+    poller.signal(SystemSignal::Stop).unwrap();
+    poller.join().await.unwrap();
+}
+
+/// Stops itself after the third tick.
+#[derive(Task)]
+#[task(message = u32, tick = "10ms" => 1)]
+struct Countdown {
+    ticks: Arc<AtomicU32>,
+}
+
+impl Runnable<u32> for Countdown {
+    async fn start(&self) {
+        while let Ok(tick) = recv!(self) {
+            if self.ticks.fetch_add(tick, Ordering::SeqCst) == 2 {
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn ticks_stop_with_the_task() {
+    let ticks = Arc::new(AtomicU32::new(0));
+    let countdown = Countdown {
+        ticks: ticks.clone(),
+    }
+    .run();
+    let task = countdown.this();
+
+    countdown.join().await.unwrap();
+    sleep(Duration::from_millis(100)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), 3);
+    // The ticks held no reference keeping the mailbox open
+    assert!(task.is_closed());
+}
+
+/// Stops its tick when told to, reporting the handles it found.
+#[derive(Task)]
+#[task(message = Msg, handler, tick = "10ms" => Msg::Tick)]
+struct Pausable {
+    ticks: Arc<AtomicU32>,
+}
+
+impl Handler<Msg> for Pausable {
+    async fn handle(&mut self, msg: Msg, _ctx: &mut Context<Msg>) {
+        match msg {
+            Msg::Tick => {
+                self.ticks.fetch_add(1, Ordering::SeqCst);
+            }
+            Msg::Flush => {
+                let ticks = timer::ticks();
+                assert_eq!(ticks.len(), 1);
+                ticks[0].cancel();
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn tasks_control_their_ticks() {
+    let ticks = Arc::new(AtomicU32::new(0));
+    let pausable = Pausable {
+        ticks: ticks.clone(),
+    }
+    .run();
+
+    sleep(Duration::from_millis(35)).await;
+    pausable.send(Msg::Flush).unwrap();
+    sleep(Duration::from_millis(10)).await;
+    let sent = ticks.load(Ordering::SeqCst);
+    assert!(sent > 0);
+
+    sleep(Duration::from_millis(50)).await;
+    assert_eq!(ticks.load(Ordering::SeqCst), sent);
+    assert!(timer::ticks().is_empty());
+}
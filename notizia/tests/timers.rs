@@ -0,0 +1,126 @@
+//! Integration tests for `send_after`, `send_interval` and `TimerHandle`.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notizia::core::SystemSignal;
+use notizia::prelude::*;
+use notizia::task::TimerHandle;
+use tokio::time::sleep;
+
+/// Records the numbers it receives.
+#[derive(Task)]
+#[task(message = u32, handler)]
+struct Recorder {
+    seen: Arc<Mutex<Vec<u32>>>,
+}
+
+impl Handler<u32> for Recorder {
+    async fn handle(&mut self, n: u32, _ctx: &mut Context<u32>) {
+        self.seen.lock().unwrap().push(n);
+    }
+}
+
+fn recorder() -> (TaskHandle<u32>, Arc<Mutex<Vec<u32>>>) {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let handle = Recorder { seen: seen.clone() }.run();
+    (handle, seen)
+}
+
+#[tokio::test]
+async fn delayed_messages_are_sent_once() {
+    let (recorder, seen) = recorder();
+
+    let timer = recorder.send_after(Duration::from_millis(20), 1);
+    assert!(timer.is_active());
+    sleep(Duration::from_millis(10)).await;
+    assert!(seen.lock().unwrap().is_empty());
+
+    sleep(Duration::from_millis(30)).await;
+    assert_eq!(*seen.lock().unwrap(), [1]);
+    assert!(!timer.is_active());
+}
+
+#[tokio::test]
+async fn timers_can_be_canceled_and_rescheduled() {
+    let (recorder, seen) = recorder();
+
+    let canceled = recorder.send_after(Duration::from_millis(20), 1);
+    canceled.cancel();
+    let delayed = recorder.send_after(Duration::from_millis(20), 2);
+    delayed.reschedule(Duration::from_millis(60));
+
+    sleep(Duration::from_millis(40)).await;
+    assert!(seen.lock().unwrap().is_empty());
+    sleep(Duration::from_millis(40)).await;
+    assert_eq!(*seen.lock().unwrap(), [2]);
+
+    // Finished timers stay finished
+    delayed.reschedule(Duration::from_millis(10));
+    sleep(Duration::from_millis(20)).await;
+    assert_eq!(*seen.lock().unwrap(), [2]);
+}
+
+#[tokio::test]
+async fn intervals_repeat_until_canceled() {
+    let (recorder, seen) = recorder();
+
+    let mut next = 0;
+    let interval = recorder.send_interval(Duration::from_millis(10), move || {
+        next += 1;
+        next
+    });
+    sleep(Duration::from_millis(55)).await;
+    interval.cancel();
+    let sent = seen.lock().unwrap().clone();
+    assert!(sent.len() >= 3, "{sent:?}");
+    assert_eq!(sent, (1..=sent.len() as u32).collect::<Vec<_>>());
+
+    sleep(Duration::from_millis(30)).await;
+    assert_eq!(seen.lock().unwrap().len(), sent.len());
+}
+
+#[tokio::test]
+async fn timers_stop_with_the_receiver() {
+    let (recorder, _) = recorder();
+
+    let interval = recorder.send_interval(Duration::from_millis(10), || 0);
+    recorder.signal(SystemSignal::Stop).unwrap();
+    recorder.join().await.unwrap();
+
+    sleep(Duration::from_millis(30)).await;
+    assert!(!interval.is_active());
+}
+
+/// Schedules a message to the recorder, then ends.
+#[derive(Task)]
+#[task(message = ())]
+struct Scheduler {
+    recorder: TaskRef<u32>,
+    timer: Arc<Mutex<Option<TimerHandle>>>,
+}
+
+impl Runnable<()> for Scheduler {
+    async fn start(&self) {
+        let timer = self.recorder.send_after(Duration::from_millis(20), 7);
+        *self.timer.lock().unwrap() = Some(timer);
+    }
+}
+
+#[tokio::test]
+async fn timers_stop_with_the_task_that_started_them() {
+    let (recorder, seen) = recorder();
+    let timer = Arc::new(Mutex::new(None));
+
+    let scheduler = Scheduler {
+        recorder: recorder.this(),
+        timer: timer.clone(),
+    }
+    .run();
+    scheduler.join().await.unwrap();
+
+    let timer = timer.lock().unwrap().take().unwrap();
+    assert!(!timer.is_active());
+    sleep(Duration::from_millis(40)).await;
+    assert!(seen.lock().unwrap().is_empty());
+}
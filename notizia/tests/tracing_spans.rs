@@ -0,0 +1,153 @@
+//! Integration tests for the `tracing` feature.
+
+#![cfg(feature = "tracing")]
+
+use notizia::prelude::*;
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use tokio::sync::oneshot;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+#[derive(Debug)]
+struct SpanRecord {
+    name: &'static str,
+    fields: String,
+    parent: Option<u64>,
+}
+
+/// Subscriber remembering every span and event
+#[derive(Default)]
+struct Recorder {
+    spans: Mutex<Vec<SpanRecord>>,
+    stack: Mutex<Vec<u64>>,
+    events: Mutex<Vec<(String, Option<u64>)>>,
+}
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0.push_str(&format!("{}={:?} ", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let mut fields = Fields(String::new());
+        attrs.record(&mut fields);
+
+        let parent = match attrs.parent() {
+            Some(parent) => Some(parent.into_u64()),
+            None if attrs.is_contextual() => self.stack.lock().unwrap().last().copied(),
+            None => None,
+        };
+
+        let mut spans = self.spans.lock().unwrap();
+        spans.push(SpanRecord {
+            name: attrs.metadata().name(),
+            fields: fields.0,
+            parent,
+        });
+        Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields(String::new());
+        event.record(&mut fields);
+        let current = self.stack.lock().unwrap().last().copied();
+        self.events.lock().unwrap().push((fields.0, current));
+    }
+
+    fn enter(&self, span: &Id) {
+        self.stack.lock().unwrap().push(span.into_u64());
+    }
+
+    fn exit(&self, _: &Id) {
+        self.stack.lock().unwrap().pop();
+    }
+}
+
+#[derive(Task)]
+#[task(message = oneshot::Sender<()>, handler)]
+struct Echo;
+
+impl Handler<oneshot::Sender<()>> for Echo {
+    async fn handle(
+        &mut self,
+        reply: oneshot::Sender<()>,
+        _ctx: &mut Context<oneshot::Sender<()>>,
+    ) {
+        let _ = reply.send(());
+    }
+}
+
+#[derive(Task)]
+#[task(message = ())]
+struct Crashing;
+
+impl Runnable<()> for Crashing {
+    async fn start(&self) {}
+
+    async fn terminate(&self, _reason: TerminateReason) {
+        panic!("cleanup failed");
+    }
+}
+
+#[tokio::test]
+async fn task_runs_in_span_with_identity() {
+    let recorder = Arc::new(Recorder::default());
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let handle = spawn!(Echo);
+    let id = handle.id();
+    let (tx, rx) = oneshot::channel();
+    handle.send(tx).unwrap();
+    rx.await.unwrap();
+    handle.kill();
+
+    let spans = recorder.spans.lock().unwrap();
+    let task = spans
+        .iter()
+        .position(|span| span.name == "task")
+        .expect("task span");
+    assert!(spans[task].fields.contains(&format!("id={id}")));
+    assert!(spans[task].fields.contains("name=\"Echo\""));
+    assert!(spans[task].fields.contains("path=\"tracing_spans::Echo\""));
+
+    let message = spans
+        .iter()
+        .find(|span| span.name == "message")
+        .expect("message span");
+    assert_eq!(message.parent, Some(task as u64 + 1));
+    assert!(message.fields.contains("oneshot::Sender<()>"));
+}
+
+#[tokio::test]
+async fn terminate_panic_is_reported_as_event() {
+    let recorder = Arc::new(Recorder::default());
+    let _guard = tracing::subscriber::set_default(recorder.clone());
+
+    let handle = spawn!(Crashing);
+    handle.join().await.unwrap();
+
+    let spans = recorder.spans.lock().unwrap();
+    let task = spans.iter().position(|span| span.name == "task").unwrap() as u64 + 1;
+
+    let events = recorder.events.lock().unwrap();
+    let (fields, span) = events
+        .iter()
+        .find(|(fields, _)| fields.contains("terminate() hook panicked"))
+        .expect("warning event");
+    assert!(fields.contains("panic=\"cleanup failed\""));
+    assert_eq!(*span, Some(task));
+}
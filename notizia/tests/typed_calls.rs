@@ -0,0 +1,116 @@
+//! Integration tests for the typed call methods generated by `#[message]`.
+
+use notizia::message;
+use notizia::prelude::*;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+use tokio::time::{Duration, sleep};
+
+#[message]
+#[derive(Debug)]
+enum CounterMsg {
+    #[request(reply = u32)]
+    GetCount,
+    #[request(reply = u32)]
+    AddAndGet {
+        amount: u32,
+    },
+    #[request(reply = String)]
+    Describe {
+        prefix: String,
+        suffix: String,
+    },
+    #[request(reply = bool)]
+    Stall,
+    Increment,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter {
+    count: Arc<AtomicU32>,
+}
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::GetCount { reply_to } => {
+                    let _ = reply_to.reply(self.count.load(Ordering::SeqCst));
+                }
+                CounterMsg::AddAndGet { amount, reply_to } => {
+                    let _ = reply_to.reply(self.count.fetch_add(amount, Ordering::SeqCst) + amount);
+                }
+                CounterMsg::Describe {
+                    prefix,
+                    suffix,
+                    reply_to,
+                } => {
+                    let count = self.count.load(Ordering::SeqCst);
+                    let _ = reply_to.reply(format!("{prefix}{count}{suffix}"));
+                }
+                CounterMsg::Stall { reply_to } => {
+                    sleep(Duration::from_millis(200)).await;
+                    let _ = reply_to.reply(true);
+                }
+                CounterMsg::Increment => {
+                    self.count.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        }
+    }
+}
+
+fn counter(start: u32) -> TaskHandle<CounterMsg> {
+    let counter = Counter {
+        count: Arc::new(AtomicU32::new(start)),
+    };
+    spawn!(counter)
+}
+
+#[tokio::test]
+async fn typed_calls_on_handle() -> Result<(), CallError> {
+    let handle = counter(3);
+
+    assert_eq!(handle.get_count().await?, 3);
+    assert_eq!(handle.add_and_get(4).await?, 7);
+    assert_eq!(
+        handle.describe("<".to_string(), ">".to_string()).await?,
+        "<7>"
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn typed_calls_on_task_ref() -> Result<(), CallError> {
+    let handle = counter(1);
+    let task = handle.this();
+
+    handle.send(CounterMsg::Increment).unwrap();
+
+    assert_eq!(task.get_count().await?, 2);
+    assert_eq!(task.add_and_get_with_timeout(1, 1000).await?, 3);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn typed_call_with_timeout_times_out() {
+    let handle = counter(0);
+
+    let result = handle.stall_with_timeout(Duration::from_millis(20)).await;
+
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+}
+
+#[tokio::test]
+async fn typed_calls_can_be_spawned() {
+    let task = counter(5).this();
+
+    let result = tokio::spawn(async move { task.get_count().await })
+        .await
+        .unwrap();
+
+    assert_eq!(result.unwrap(), 5);
+}
@@ -0,0 +1,216 @@
+//! Integration tests for virtual actors with on-demand activation.
+
+use notizia::call;
+use notizia::prelude::*;
+use notizia::sharding::Placement;
+use notizia::virtual_actors::{VirtualActor, VirtualActors};
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Mutex};
+use tokio::sync::oneshot;
+use tokio::time::{Duration, sleep};
+
+/// Simulated persistent storage shared by all accounts
+static STORE: LazyLock<Mutex<HashMap<String, i64>>> = LazyLock::new(Default::default);
+
+/// Activation log, to observe the lifecycle
+static EVENTS: LazyLock<Mutex<Vec<String>>> = LazyLock::new(Default::default);
+
+fn events_for(prefix: &str) -> Vec<String> {
+    EVENTS
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|e| e.contains(prefix))
+        .cloned()
+        .collect()
+}
+
+#[derive(Debug)]
+enum AccountMsg {
+    Deposit(i64),
+    Balance { reply_to: oneshot::Sender<i64> },
+}
+
+struct Account {
+    id: String,
+    balance: i64,
+}
+
+impl VirtualActor for Account {
+    type Id = String;
+    type Message = AccountMsg;
+
+    async fn activate(id: String) -> Self {
+        EVENTS.lock().unwrap().push(format!("activate {id}"));
+        let balance = STORE.lock().unwrap().get(&id).copied().unwrap_or(0);
+        Account { id, balance }
+    }
+
+    async fn handle(&mut self, msg: AccountMsg) {
+        match msg {
+            AccountMsg::Deposit(amount) => self.balance += amount,
+            AccountMsg::Balance { reply_to } => {
+                let _ = reply_to.send(self.balance);
+            }
+        }
+    }
+
+    async fn deactivate(&mut self) {
+        EVENTS
+            .lock()
+            .unwrap()
+            .push(format!("deactivate {}", self.id));
+        STORE.lock().unwrap().insert(self.id.clone(), self.balance);
+    }
+}
+
+#[tokio::test]
+async fn sending_to_identity_activates_actor() {
+    let accounts = VirtualActors::<Account>::new();
+
+    accounts
+        .send("alice".to_string(), AccountMsg::Deposit(10))
+        .unwrap();
+    accounts
+        .send("alice".to_string(), AccountMsg::Deposit(5))
+        .unwrap();
+
+    let alice = accounts.get("alice".to_string());
+    let balance = call!(alice, |tx| AccountMsg::Balance { reply_to: tx })
+        .await
+        .unwrap();
+
+    assert_eq!(balance, 15);
+    assert!(accounts.is_active(&"alice".to_string()));
+    assert_eq!(events_for("alice"), vec!["activate alice"]);
+}
+
+#[tokio::test]
+async fn deactivation_persists_and_reactivation_restores_state() {
+    let accounts = VirtualActors::<Account>::new();
+    let id = "bob".to_string();
+
+    accounts.send(id.clone(), AccountMsg::Deposit(42)).unwrap();
+
+    let result = accounts.deactivate(&id, Duration::from_secs(1)).await;
+    assert!(matches!(result, Some(Ok(TerminateReason::Normal))));
+    assert!(!accounts.is_active(&id));
+
+    let bob = accounts.get(id.clone());
+    let balance = call!(bob, |tx| AccountMsg::Balance { reply_to: tx })
+        .await
+        .unwrap();
+
+    assert_eq!(balance, 42);
+    assert_eq!(
+        events_for("bob"),
+        vec!["activate bob", "deactivate bob", "activate bob"]
+    );
+}
+
+#[tokio::test]
+async fn least_recently_used_actor_is_deactivated_over_limit() {
+    let accounts = VirtualActors::<Account>::new().max_active(1);
+
+    accounts
+        .send("carol".to_string(), AccountMsg::Deposit(1))
+        .unwrap();
+    sleep(Duration::from_millis(5)).await;
+    accounts
+        .send("dave".to_string(), AccountMsg::Deposit(1))
+        .unwrap();
+    sleep(Duration::from_millis(20)).await;
+
+    assert_eq!(accounts.active_count(), 1);
+    assert!(accounts.is_active(&"dave".to_string()));
+    assert_eq!(
+        events_for("carol"),
+        vec!["activate carol", "deactivate carol"]
+    );
+    assert_eq!(STORE.lock().unwrap()["carol"], 1);
+}
+
+#[tokio::test]
+async fn shutdown_deactivates_all_actors() {
+    let accounts = VirtualActors::<Account>::new();
+
+    for id in ["erin", "frank"] {
+        accounts
+            .send(id.to_string(), AccountMsg::Deposit(3))
+            .unwrap();
+    }
+
+    let results = accounts.shutdown(Duration::from_secs(1)).await;
+
+    assert_eq!(results.len(), 2);
+    assert!(results.iter().all(|(_, r)| r.is_ok()));
+    assert_eq!(events_for("deactivate erin").len(), 1);
+    assert_eq!(events_for("deactivate frank").len(), 1);
+}
+
+struct FirstShard(Arc<Mutex<Vec<u32>>>);
+
+impl Placement<u32> for FirstShard {
+    fn place(&self, key: &u32, _shards: usize) -> usize {
+        self.0.lock().unwrap().push(*key);
+        0
+    }
+}
+
+struct Echo;
+
+impl VirtualActor for Echo {
+    type Id = u32;
+    type Message = oneshot::Sender<()>;
+
+    async fn activate(_id: u32) -> Self {
+        Echo
+    }
+
+    async fn handle(&mut self, reply_to: oneshot::Sender<()>) {
+        let _ = reply_to.send(());
+    }
+}
+
+#[tokio::test]
+async fn custom_placement_is_used() {
+    let placed = Arc::new(Mutex::new(Vec::new()));
+    let echoes = VirtualActors::<Echo>::new()
+        .with_shards(4)
+        .placement(FirstShard(placed.clone()));
+
+    let (tx, rx) = oneshot::channel();
+    echoes.send(7, tx).unwrap();
+    rx.await.unwrap();
+
+    assert!(placed.lock().unwrap().contains(&7));
+}
+
+struct Faulty;
+
+impl VirtualActor for Faulty {
+    type Id = u32;
+    type Message = ();
+
+    async fn activate(id: u32) -> Self {
+        if id == 0 {
+            panic!("failed to load state");
+        }
+        Faulty
+    }
+
+    async fn handle(&mut self, _msg: ()) {}
+}
+
+#[tokio::test]
+async fn failed_activation_is_reported_as_panic() {
+    let actors = VirtualActors::<Faulty>::new();
+
+    let _ = actors.send(0, ());
+    sleep(Duration::from_millis(10)).await;
+
+    let result = actors.deactivate(&0, Duration::from_secs(1)).await;
+    assert!(
+        matches!(result, Some(Ok(TerminateReason::Panic(msg))) if msg == "failed to load state")
+    );
+}
@@ -0,0 +1,93 @@
+//! Integration tests for virtual time in tests.
+
+#![cfg(feature = "testing")]
+
+use notizia::prelude::*;
+use notizia::testing::{self, TestProbe};
+use notizia::{call, message};
+use std::time::Duration;
+
+#[message]
+#[derive(Debug)]
+enum Msg {
+    #[request(reply = u32)]
+    Slow,
+}
+
+#[derive(Task)]
+#[task(message = Msg)]
+struct Sluggish;
+
+impl Runnable<Msg> for Sluggish {
+    async fn start(&self) {
+        while let Ok(Msg::Slow { reply_to }) = recv!(self) {
+            tokio::time::sleep(Duration::from_secs(30)).await;
+            let _ = reply_to.reply(1);
+        }
+    }
+}
+
+#[derive(Task)]
+#[task(message = ())]
+struct Ticker {
+    ticks: TaskRef<u32>,
+}
+
+impl Runnable<()> for Ticker {
+    async fn start(&self) {
+        for tick in 1.. {
+            tokio::time::sleep(Duration::from_secs(10)).await;
+            if self.ticks.send(tick).is_err() {
+                break;
+            }
+        }
+    }
+}
+
+#[tokio::test]
+async fn call_timeouts_elapse_instantly() {
+    testing::pause();
+    let started = std::time::Instant::now();
+    let handle = spawn!(Sluggish);
+
+    let result = call!(handle, Msg::Slow, timeout = Duration::from_secs(5)).await;
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+
+    // The reply arrives after 30 virtual seconds
+    assert_eq!(call!(handle, Msg::Slow, timeout = 60_000).await.unwrap(), 1);
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn idle_timeouts_fire_on_advance() {
+    testing::pause();
+    let handle = spawn!(Sluggish);
+    handle.passivate_after(Duration::from_secs(60));
+    testing::advance(Duration::from_millis(1)).await;
+
+    testing::advance(Duration::from_secs(59)).await;
+    assert!(!handle.is_finished());
+
+    testing::advance(Duration::from_secs(1)).await;
+    assert!(handle.is_finished());
+    assert_eq!(handle.join().await.unwrap(), TerminateReason::Idle);
+}
+
+#[tokio::test(start_paused = true)]
+async fn advance_fires_timers_in_order() {
+    let probe = TestProbe::new();
+    let ticker = Ticker {
+        ticks: probe.this(),
+    };
+    let _handle = spawn!(ticker);
+    testing::advance(Duration::ZERO).await;
+
+    testing::advance(Duration::from_secs(9)).await;
+    assert_eq!(probe.receive(Duration::ZERO).await, None);
+
+    testing::advance(Duration::from_secs(21)).await;
+    assert_eq!(probe.receive(Duration::ZERO).await, Some(1));
+    assert_eq!(probe.receive(Duration::ZERO).await, Some(2));
+    assert_eq!(probe.receive(Duration::ZERO).await, Some(3));
+    assert_eq!(probe.receive(Duration::ZERO).await, None);
+}
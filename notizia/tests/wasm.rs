@@ -0,0 +1,63 @@
+//! Integration tests for running tasks in the browser.
+//!
+//! Run with `wasm-pack test --headless --firefox notizia`.
+
+#![cfg(target_arch = "wasm32")]
+
+use notizia::core::errors::CallError;
+use notizia::prelude::*;
+use notizia::{call, message};
+use wasm_bindgen_test::{wasm_bindgen_test, wasm_bindgen_test_configure};
+
+wasm_bindgen_test_configure!(run_in_browser);
+
+#[message]
+enum CounterMsg {
+    Add,
+    #[request(reply = u64)]
+    Get,
+    #[request(reply = u64)]
+    Stall,
+}
+
+#[derive(Task)]
+#[task(message = CounterMsg)]
+struct Counter;
+
+impl Runnable<CounterMsg> for Counter {
+    async fn start(&self) {
+        let mut count = 0;
+        let mut stalled = Vec::new();
+
+        while let Ok(msg) = recv!(self) {
+            match msg {
+                CounterMsg::Add => count += 1,
+                CounterMsg::Get { reply_to } => {
+                    let _ = reply_to.reply(count);
+                }
+                CounterMsg::Stall { reply_to } => stalled.push(reply_to),
+            }
+        }
+    }
+}
+
+#[wasm_bindgen_test]
+async fn tasks_run_on_the_event_loop() {
+    let counter = spawn!(Counter);
+    counter.send(CounterMsg::Add).unwrap();
+    counter.send(CounterMsg::Add).unwrap();
+    assert_eq!(call!(counter, CounterMsg::Get).await.unwrap(), 2);
+
+    let reason = counter
+        .shutdown(std::time::Duration::from_secs(1))
+        .await
+        .unwrap();
+    assert!(matches!(reason, TerminateReason::Normal));
+}
+
+#[wasm_bindgen_test]
+async fn timers_run_on_the_event_loop() {
+    let counter = spawn!(Counter);
+    let result = call!(counter, CounterMsg::Stall, timeout = 20).await;
+    assert!(matches!(result, Err(CallError::Timeout { .. })));
+}
@@ -0,0 +1,65 @@
+//! Integration tests for the slow-handler watchdog.
+
+use std::time::Duration;
+
+use notizia::prelude::*;
+use tokio::time::sleep;
+
+#[derive(Task, Debug, Default)]
+#[task(message = Duration, handler)]
+struct Napper {
+    naps: usize,
+}
+
+impl Handler<Duration> for Napper {
+    async fn handle(&mut self, nap: Duration, _ctx: &mut Context<Duration>) {
+        sleep(nap).await;
+        self.naps += 1;
+    }
+}
+
+#[tokio::test]
+async fn slow_message_flags_the_task_until_it_is_done() {
+    let napper = Napper::default()
+        .builder()
+        .watchdog(Duration::from_millis(20))
+        .spawn();
+
+    napper.send(Duration::from_millis(1)).unwrap();
+    sleep(Duration::from_millis(30)).await;
+    assert!(!napper.is_stalled());
+
+    napper.send(Duration::from_millis(150)).unwrap();
+    sleep(Duration::from_millis(60)).await;
+    assert!(napper.is_stalled());
+
+    sleep(Duration::from_millis(150)).await;
+    assert!(!napper.is_stalled());
+
+    napper.shutdown(Duration::from_secs(1)).await.unwrap();
+}
+
+#[tokio::test]
+async fn state_is_captured_for_the_warning() {
+    let napper = Napper::default()
+        .builder()
+        .watchdog_with_state(Duration::from_millis(10))
+        .spawn();
+
+    napper.send(Duration::from_millis(1)).unwrap();
+    napper.send(Duration::from_millis(60)).unwrap();
+    sleep(Duration::from_millis(40)).await;
+    assert!(napper.is_stalled());
+
+    napper.shutdown(Duration::from_secs(1)).await.unwrap();
+}
+
+#[tokio::test]
+async fn tasks_without_watchdog_are_never_stalled() {
+    let napper = Napper::default().run();
+    napper.send(Duration::from_millis(100)).unwrap();
+    sleep(Duration::from_millis(30)).await;
+
+    assert!(!napper.is_stalled());
+    napper.shutdown(Duration::from_secs(1)).await.unwrap();
+}
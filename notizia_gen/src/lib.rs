@@ -1,18 +1,69 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
 use syn::{
-    Attribute, DeriveInput, Error, Expr, Field, Fields, ItemEnum, Meta, MetaNameValue, Result,
-    Type, Variant, parse_macro_input,
+    Attribute, DeriveInput, Error, Expr, ExprLit, ExprPath, Field, Fields, Ident, ItemEnum,
+    ItemImpl, Lit, LitInt, Meta, Result, Token, Type, Variant, parse_macro_input, parse_quote,
 };
 
 /// Derive macro for implementing the Task trait.
 ///
 /// This macro requires a `#[task(message = T)]` attribute to specify the message type.
+/// `T` can be any type, including generic types such as `Msg<u32>` and
+/// fully qualified paths such as `<Job as Protocol>::Message`.
+///
+/// Adding `control = C`, as in `#[task(message = T, control = C)]`, gives
+/// the task a second mailbox for control messages of type `C`, received with
+/// `recv_ctrl!` or `recv_any!`.
+///
+/// Adding `handler`, as in `#[task(message = T, handler)]`, generates the
+/// receive loop: instead of `Runnable`, the task implements `Handler<T>`,
+/// whose `handle(&mut self, msg, ctx)` is called for every message.
+///
+/// Adding `persistent` to a handler task, as in
+/// `#[task(message = T, handler, persistent)]`, rebuilds its state from its
+/// journal before the first message, replaying its events into
+/// `Persistent::apply`. Adding `snapshots` as well restores the latest
+/// snapshot first and saves new ones, see `Snapshots`.
+///
+/// Adding `mailbox = bounded(n)` limits the mailbox to `n` queued messages,
+/// and `overflow = reject | drop_newest | drop_oldest` decides what happens to
+/// messages sent while it is full, as in
+/// `#[task(message = T, mailbox = bounded(128), overflow = drop_oldest)]`.
+/// Both can be overridden per spawn with `builder()`.
+///
+/// Adding `budget = n`, as in `#[task(message = T, budget = 64)]`, makes the
+/// task yield to the runtime after every `n` received messages, so a large
+/// backlog does not starve other tasks on the same worker thread. `n` must
+/// be a positive integer literal.
+///
+/// Adding `tick = "100ms" => M::Tick`, as in
+/// `#[task(message = M, tick = "100ms" => M::Tick)]`, sends the message
+/// `M::Tick` to the task every 100 milliseconds while it runs. The period is
+/// a whole number with one of the units `ns`, `us`, `ms`, `s`, `m` or `h`.
+/// A task may declare several ticks; `notizia::task::timer::ticks()`
+/// returns their handles from within the task.
+///
+/// Adding `blocking`, as in `#[task(message = T, blocking)]`, runs `start()`
+/// on a thread that may block, such as Tokio's blocking pool, so CPU-heavy
+/// work does not starve the async runtime.
+///
+/// The generated code refers to everything through `::notizia::` paths,
+/// so the derive works in crates that do not depend on `tokio` directly,
+/// without the prelude in scope, and on types declared inside functions.
+///
+/// The task type can be a struct or an enum, e.g. a connection that is
+/// either `Connecting`, `Ready` or `Closed`.
+///
+/// The task type may be generic, e.g. `struct Worker<S: Store>`, and its
+/// parameters may appear in the message type. Since spawned tasks must be
+/// `'static`, the `Task` impl requires the task and message types to be
+/// `'static`, so the `Runnable` impl needs the same bounds.
 ///
 /// # Example
 ///
 /// ```rust,ignore
-/// # TODO: Re-enable once derive macro hygiene is fixed
 /// use notizia::prelude::*;
 ///
 /// #[derive(Task)]
@@ -43,106 +94,282 @@ fn impl_task_derive(input: &DeriveInput) -> Result<quote::__private::TokenStream
     let name = &input.ident;
 
     // Parse the #[task(message = T)] attribute
-    let message_type = parse_task_attribute(&input.attrs)?;
+    let options = parse_task_attribute(&input.attrs)?;
+    let message_type = &options.message;
+    let blocking = if options.blocking {
+        quote! { let options = options.blocking(); }
+    } else {
+        quote! {}
+    };
+    let mailbox = options.mailbox.as_ref().map(|config| {
+        quote! { let options = options.default_mailbox(#config); }
+    });
+    let overflow = options.overflow.as_ref().map(|overflow| {
+        quote! { let options = options.default_overflow(#overflow); }
+    });
+    let budget = options.budget.as_ref().map(|budget| {
+        quote! { let options = options.default_budget(#budget); }
+    });
+
+    // The generated items live in an anonymous `const _` block, so the
+    // task-local state is private to the derive and resolves the same
+    // names as the task type, even when it is declared inside a function.
+    // A task-local static cannot depend on the generic parameters of the
+    // task, so generic tasks keep their state in notizia's type-erased slot.
+    let mut generics = input.generics.clone();
+    let (state, scope_state, state_static) = if generics.params.is_empty() {
+        (
+            quote! { __NOTIZIA_TASK_STATE.get() },
+            quote! { __NOTIZIA_TASK_STATE.scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future) },
+            quote! {
+                ::notizia::runtime::task_local! {
+                    static __NOTIZIA_TASK_STATE: ::notizia::TaskState<#message_type>;
+                }
+            },
+        )
+    } else {
+        let where_clause = generics.make_where_clause();
+        where_clause.predicates.push(parse_quote! { Self: 'static });
+        where_clause
+            .predicates
+            .push(parse_quote! { #message_type: 'static });
+
+        (
+            quote! { ::notizia::TaskState::<#message_type>::generic() },
+            quote! { ::notizia::TaskState::new(mailbox.clone(), &task_ref).scope_generic(future) },
+            quote! {},
+        )
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Tasks with `control = C` get a second mailbox for control messages
+    let (control, control_impl) = match &options.control {
+        Some(control_type) => (
+            quote! { let options = options.control::<#control_type>(); },
+            quote! {
+                impl #impl_generics ::notizia::task::Controlled for #name #ty_generics #where_clause {
+                    type Control = #control_type;
+
+                    fn __control(&self) -> ::notizia::task::Control {
+                        #state.control()
+                    }
+                }
+            },
+        ),
+        None => (quote! {}, quote! {}),
+    };
+
+    // Persistent tasks rebuild their state before handling messages
+    let (recover, snapshot) = if options.snapshots {
+        (
+            quote! { ::notizia::persistence::__recover_snapshot(&mut task, &mut ctx).await; },
+            quote! { ::notizia::persistence::__snapshot(&task, &mut ctx).await; },
+        )
+    } else if options.persistent {
+        (
+            quote! { ::notizia::persistence::__recover(&mut task, &mut ctx).await; },
+            quote! {},
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    // Ticks are sent from when the mailbox is set up until `start()` returns
+    let (start_ticks, stop_ticks) = if options.ticks.is_empty() {
+        (quote! {}, quote! {})
+    } else {
+        let ticks = options.ticks.iter().map(|(period, tick)| {
+            quote! {
+                ::notizia::task::timer::__tick(
+                    &::notizia::Task::<#message_type>::this(&task),
+                    #period,
+                    || #tick,
+                )
+            }
+        });
+        (
+            quote! { let ticks = [#(#ticks),*]; },
+            quote! {
+                for tick in &ticks {
+                    tick.cancel();
+                }
+            },
+        )
+    };
 
-    // Generate the module name for task-local storage
-    let mod_name = format_ident!("__{name}_gen");
-    let task_state = format_ident!("{name}State");
+    // Handler tasks get a generated receive loop instead of `start()`
+    let (prepare, start, terminate) = if options.handler {
+        (
+            quote! {
+                let mut task = self;
+                let mut ctx = ::notizia::task::Context::new(#state);
+            },
+            quote! {
+                async {
+                    #recover
+
+                    while !ctx.is_stopped() {
+                        let ::std::result::Result::Ok(msg) = ::notizia::Task::<#message_type>::recv(&task).await else {
+                            break;
+                        };
+
+                        {
+                            #[allow(unused_imports)]
+                            use ::notizia::core::correlation::{ViaAny as _, ViaCorrelated as _};
+                            ::notizia::core::correlation::enter(
+                                (&::notizia::core::correlation::Probe(&msg)).correlation_id(),
+                            );
+                        }
+
+                        ::notizia::task::Context::__dispatch(&mut ctx, &mut task, msg).await;
+                        #snapshot
+                        ::notizia::Task::<#message_type>::mailbox(&task).ack();
+                    }
+                }
+            },
+            quote! { ::notizia::task::Handler::<#message_type>::terminate(&mut task, ::std::clone::Clone::clone(&reason)) },
+        )
+    } else {
+        (
+            quote! { let task = self; },
+            quote! { ::notizia::Runnable::<#message_type>::start(&task) },
+            quote! { ::notizia::Runnable::<#message_type>::terminate(&task, ::std::clone::Clone::clone(&reason)) },
+        )
+    };
 
     // Generate the Task trait implementation
     let generated = quote! {
-        impl notizia::Task<#message_type> for #name {
+        impl #impl_generics ::notizia::Task<#message_type> for #name #ty_generics #where_clause {
             fn __setup(
-                &self,
-                receiver: notizia::tokio::sync::mpsc::UnboundedReceiver<#message_type>,
-            ) -> impl std::future::Future<Output = notizia::TerminateReason> + Send {
-                async move {
+                self,
+                receiver: ::notizia::runtime::Receiver<#message_type>,
+                deadline: ::std::option::Option<::std::time::Duration>,
+            ) -> impl ::std::future::Future<Output = ::notizia::TerminateReason> + ::std::marker::Send {
+                let this = ::notizia::Task::<#message_type>::this(&self);
+                let path = ::std::concat!(::std::module_path!(), "::", ::std::stringify!(#name));
+
+                ::notizia::core::trace::instrument(&this, path, async move {
+                    #prepare
+
                     // Set up mailbox
-                    let mb = self.mailbox();
+                    let mb = ::notizia::Task::<#message_type>::mailbox(&task);
                     mb.set_receiver(receiver).await;
+                    #start_ticks
 
-                    // Execute start() and catch panics
-                    let start_result = notizia::futures::FutureExt::catch_unwind(
-                        std::panic::AssertUnwindSafe(self.start())
+                    // Execute start() until the deadline and catch panics
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                        ::std::panic::AssertUnwindSafe(
+                            ::notizia::core::lifecycle::with_deadline(deadline, #start)
+                        )
                     ).await;
+                    #stop_ticks
 
                     // Determine termination reason
                     let reason = match start_result {
-                        Ok(()) => notizia::TerminateReason::Normal,
-                        Err(panic_payload) => {
+                        ::std::result::Result::Ok(::std::option::Option::None) => ::notizia::TerminateReason::DeadlineExceeded,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => ::notizia::TerminateReason::Normal,
+                        ::std::result::Result::Err(panic_payload) => {
                             // Extract panic message
-                            let msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
-                                s.to_string()
-                            } else if let Some(s) = panic_payload.downcast_ref::<String>() {
-                                s.clone()
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload.downcast_ref::<&str>() {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload.downcast_ref::<::std::string::String>() {
+                                ::std::clone::Clone::clone(s)
                             } else {
-                                "unknown panic".to_string()
+                                ::std::string::ToString::to_string("unknown panic")
                             };
-                            notizia::TerminateReason::Panic(msg)
+                            ::notizia::TerminateReason::Panic(msg)
                         }
                     };
 
                     // Call terminate hook, also catch panics
-                    let terminate_result  = notizia::futures::FutureExt::catch_unwind(
-                        std::panic::AssertUnwindSafe(self.terminate(reason.clone()))
+                    let terminate_result  = ::notizia::futures::FutureExt::catch_unwind(
+                        ::std::panic::AssertUnwindSafe(#terminate)
                     ).await;
 
                     // Log if terminate() panicked
-                    if let Err(terminate_panic) = terminate_result {
-                        let msg = if let Some(s) = terminate_panic.downcast_ref::<&str>() {
-                            s.to_string()
-                        } else if let Some(s) = terminate_panic.downcast_ref::<String>() {
-                            s.clone()
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic.downcast_ref::<&str>() {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic.downcast_ref::<::std::string::String>() {
+                            ::std::clone::Clone::clone(s)
                         } else {
-                            "unknown panic".to_string()
+                            ::std::string::ToString::to_string("unknown panic")
                         };
-                        eprintln!("Warning: terminate() hook panicked: {}", msg);
+                        ::notizia::core::trace::terminate_panicked(&msg);
                     }
 
                     // Return the original termination reason
                     reason
-                }
+                })
             }
 
-            fn mailbox(&self) -> notizia::Mailbox<#message_type> {
-                #mod_name::#task_state.get().mailbox
+            fn mailbox(&self) -> ::notizia::Mailbox<#message_type> {
+                #state.mailbox
             }
 
-            fn run(self) -> notizia::TaskHandle<#message_type> {
-                let (sender, receiver) = notizia::tokio::sync::mpsc::unbounded_channel::<#message_type>();
+            fn __spawn(self, options: ::notizia::task::SpawnOptions) -> ::notizia::TaskHandle<#message_type> {
+                #blocking
+                #mailbox
+                #overflow
+                #budget
+                #control
+                let (task_ref, mailbox, receiver) = options.channel::<#message_type>(::std::stringify!(#name));
+                let task_ref = task_ref.with_path(::std::concat!(::std::module_path!(), "::", ::std::stringify!(#name)));
+                let deadline = options.deadline();
 
-                let task = #mod_name::#task_state.scope(notizia::TaskState {
-                    mailbox: notizia::Mailbox::new(),
-                    sender: sender.clone(),
-                }, async move {
-                    let handle = self.__setup(receiver);
+                let future = async move {
+                    let handle = ::notizia::Task::<#message_type>::__setup(self, receiver, deadline);
                     handle.await
-                });
+                };
+                let task = #scope_state;
 
-                let handle = notizia::tokio::spawn(task);
-
-                notizia::TaskHandle::new(sender, handle)
+                options.spawn(task_ref, &mailbox, task)
             }
 
-            fn this(&self) -> notizia::TaskRef<#message_type> {
-                notizia::TaskRef::new(#mod_name::#task_state.get().sender)
+            fn this(&self) -> ::notizia::TaskRef<#message_type> {
+                #state.task_ref()
             }
         }
 
-        mod #mod_name {
-            use super::*;
-
-            tokio::task_local! {
-                pub static #task_state: notizia::TaskState<#message_type>;
-            }
-        }
+        #control_impl
     };
 
-    Ok(generated)
+    Ok(quote! {
+        const _: () = {
+            #state_static
+
+            #generated
+        };
+    })
+}
+
+/// Options of the `#[task(...)]` attribute.
+struct TaskOptions {
+    /// The message type, from `message = T`
+    message: Type,
+    /// Whether `start()` runs on the blocking pool, from `blocking`
+    blocking: bool,
+    /// The type of control messages, from `control = C`
+    control: Option<Type>,
+    /// Whether the task implements `Handler` instead of `Runnable`, from `handler`
+    handler: bool,
+    /// Whether the task is rebuilt from its journal, from `persistent`
+    persistent: bool,
+    /// Whether the task saves snapshots of its state, from `snapshots`
+    snapshots: bool,
+    /// The mailbox configuration, from `mailbox = bounded(n)`
+    mailbox: Option<quote::__private::TokenStream>,
+    /// The overflow policy, from `overflow = drop_oldest`
+    overflow: Option<quote::__private::TokenStream>,
+    /// Messages received before yielding, from `budget = n`
+    budget: Option<LitInt>,
+    /// Periods and messages of the ticks, from `tick = "100ms" => M::Tick`
+    ticks: Vec<(quote::__private::TokenStream, Expr)>,
 }
 
-/// Parse the #[task(message = T)] attribute to extract the message type.
-fn parse_task_attribute(attrs: &[Attribute]) -> Result<Type> {
+/// Parse the #[task(message = T)] attribute to extract the message type and options.
+fn parse_task_attribute(attrs: &[Attribute]) -> Result<TaskOptions> {
     // Find the #[task(...)] attribute
     let task_attr = attrs
         .iter()
@@ -156,41 +383,113 @@ fn parse_task_attribute(attrs: &[Attribute]) -> Result<Type> {
             )
         })?;
 
-    // Parse the attribute as a list: #[task(message = T)]
+    // Parse the attribute as a list: #[task(message = T, blocking)]
     let meta = &task_attr.meta;
 
     match meta {
         Meta::List(list) => {
-            // Parse the nested meta items
-            let nested: MetaNameValue = syn::parse2(list.tokens.clone()).map_err(|_| {
-                Error::new_spanned(
-                    meta,
-                    "Expected #[task(message = Type)].\n\
-                     The task attribute must be in the form: #[task(message = YourMessageType)]",
-                )
-            })?;
+            // Parse the nested items, keeping the errors of individual items
+            let items =
+                list.parse_args_with(Punctuated::<TaskItem, Token![,]>::parse_terminated)?;
+
+            let mut message = None;
+            let mut blocking = false;
+            let mut control = None;
+            let mut handler = false;
+            let mut persistent = None;
+            let mut snapshots = None;
+            let mut mailbox = None;
+            let mut overflow = None;
+            let mut budget = None;
+            let mut ticks = Vec::new();
+
+            for item in items {
+                match item {
+                    TaskItem::Message(ty) => message = Some(ty),
+                    TaskItem::Control(ty) => control = Some(ty),
+                    TaskItem::Mailbox(config) => mailbox = Some(config),
+                    TaskItem::Overflow(name, policy) => overflow = Some((name, policy)),
+                    TaskItem::Budget(messages) => budget = Some(messages),
+                    TaskItem::Tick(period, tick) => ticks.push((period, tick)),
+                    TaskItem::Flag(flag) if flag == "blocking" => blocking = true,
+                    TaskItem::Flag(flag) if flag == "handler" => handler = true,
+                    TaskItem::Flag(flag) if flag == "persistent" => persistent = Some(flag),
+                    TaskItem::Flag(flag) if flag == "snapshots" => snapshots = Some(flag),
+                    TaskItem::Flag(flag) if flag == "message" => {
+                        return Err(Error::new_spanned(
+                            meta,
+                            "Expected #[task(message = Type)].\n\
+                             The task attribute must be in the form: #[task(message = YourMessageType)]",
+                        ));
+                    }
+                    TaskItem::Flag(flag) => {
+                        return Err(Error::new_spanned(
+                            flag,
+                            "Unknown task option.\n\
+                             Supported options: message = T, control = C, mailbox = bounded(n), \
+                             overflow = P, budget = n, tick = \"100ms\" => M::Tick, blocking, handler, \
+                             persistent, snapshots",
+                        ));
+                    }
+                }
+            }
 
-            // Check that the name is "message"
-            if !nested.path.is_ident("message") {
+            let overflow = match (overflow, &mailbox) {
+                (Some((_, policy)), Some(MailboxItem::Bounded(_))) => Some(policy),
+                (Some((name, _)), _) => {
+                    return Err(Error::new_spanned(
+                        name,
+                        "The overflow policy requires a bounded mailbox.\n\
+                         Use: #[task(message = T, mailbox = bounded(128), overflow = drop_oldest)]",
+                    ));
+                }
+                (None, _) => None,
+            };
+            if let Some(flag) = &persistent
+                && !handler
+            {
                 return Err(Error::new_spanned(
-                    &nested.path,
-                    "Expected 'message' parameter.\n\
-                     Use: #[task(message = YourMessageType)]",
+                    flag,
+                    "Persistent tasks handle their messages with `Handler`.\n\
+                     Use: #[task(message = T, handler, persistent)]",
                 ));
             }
 
-            // Extract the type from the value
-            match &nested.value {
-                syn::Expr::Path(expr_path) => Ok(Type::Path(syn::TypePath {
-                    qself: None,
-                    path: expr_path.path.clone(),
-                })),
-                _ => Err(Error::new_spanned(
-                    &nested.value,
-                    "Expected a type for the message parameter.\n\
-                     Example: #[task(message = MyMessage)]",
-                )),
+            if let Some(flag) = &snapshots
+                && persistent.is_none()
+            {
+                return Err(Error::new_spanned(
+                    flag,
+                    "Snapshots are taken of persistent tasks.\n\
+                     Use: #[task(message = T, handler, persistent, snapshots)]",
+                ));
             }
+
+            let mailbox = mailbox.map(|config| match config {
+                MailboxItem::Bounded(capacity) => quote! { ::notizia::core::bounded(#capacity) },
+                MailboxItem::Unbounded => quote! { ::notizia::core::unbounded() },
+            });
+
+            let message = message.ok_or_else(|| {
+                Error::new_spanned(
+                    meta,
+                    "Expected 'message' parameter.\n\
+                     Use: #[task(message = YourMessageType)]",
+                )
+            })?;
+
+            Ok(TaskOptions {
+                message,
+                blocking,
+                control,
+                handler,
+                persistent: persistent.is_some(),
+                snapshots: snapshots.is_some(),
+                mailbox,
+                overflow,
+                budget,
+                ticks,
+            })
         }
         Meta::Path(_) => Err(Error::new_spanned(
             meta,
@@ -205,10 +504,202 @@ fn parse_task_attribute(attrs: &[Attribute]) -> Result<Type> {
     }
 }
 
+/// A single item of the `#[task(...)]` attribute.
+enum TaskItem {
+    /// `message = T`
+    Message(Type),
+    /// `control = C`
+    Control(Type),
+    /// `mailbox = bounded(n)` or `mailbox = unbounded`
+    Mailbox(MailboxItem),
+    /// `overflow = drop_oldest`, with the option name kept for error spans
+    Overflow(Ident, quote::__private::TokenStream),
+    /// `budget = n`
+    Budget(LitInt),
+    /// `tick = "100ms" => M::Tick`, with the period as a `Duration` expression
+    Tick(quote::__private::TokenStream, Expr),
+    /// A flag without value, e.g. `blocking`
+    Flag(Ident),
+}
+
+/// The value of `mailbox = ...` in the `#[task(...)]` attribute.
+enum MailboxItem {
+    /// `bounded(n)`
+    Bounded(Expr),
+    /// `unbounded`
+    Unbounded,
+}
+
+impl Parse for TaskItem {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let name: Ident = input.parse()?;
+
+        if !input.peek(Token![=]) {
+            return Ok(TaskItem::Flag(name));
+        }
+
+        if name == "mailbox" {
+            input.parse::<Token![=]>()?;
+            return parse_mailbox_config(input).map(TaskItem::Mailbox);
+        }
+
+        if name == "overflow" {
+            input.parse::<Token![=]>()?;
+            let policy = parse_overflow_policy(input)?;
+            return Ok(TaskItem::Overflow(name, policy));
+        }
+
+        if name == "budget" {
+            input.parse::<Token![=]>()?;
+            return parse_budget(input).map(TaskItem::Budget);
+        }
+
+        if name == "tick" {
+            input.parse::<Token![=]>()?;
+            let period = parse_tick_period(&input.parse()?)?;
+            input.parse::<Token![=>]>()?;
+            return Ok(TaskItem::Tick(period, input.parse()?));
+        }
+
+        if name != "message" && name != "control" {
+            return Err(Error::new_spanned(
+                name,
+                "Expected 'message' parameter.\n\
+                 Use: #[task(message = YourMessageType)]",
+            ));
+        }
+
+        input.parse::<Token![=]>()?;
+        let ty = parse_message_type(input)?;
+
+        if name == "control" {
+            Ok(TaskItem::Control(ty))
+        } else {
+            Ok(TaskItem::Message(ty))
+        }
+    }
+}
+
+/// Parse the message type from the value of `message = T`.
+///
+/// Accepts any type, including generic types like `Msg<u32>` and fully
+/// qualified paths like `<Job as Work>::Message`.
+fn parse_message_type(input: ParseStream) -> Result<Type> {
+    if input.peek(syn::Lit) {
+        let value: syn::Lit = input.parse()?;
+        return Err(Error::new_spanned(
+            value,
+            "Expected a type for the message parameter.\n\
+             Example: #[task(message = MyMessage)]",
+        ));
+    }
+
+    input.parse()
+}
+
+/// Parse the value of `mailbox = ...`: either `bounded(n)` or `unbounded`.
+fn parse_mailbox_config(input: ParseStream) -> Result<MailboxItem> {
+    let kind: Ident = input.parse()?;
+
+    if kind == "unbounded" {
+        return Ok(MailboxItem::Unbounded);
+    }
+
+    if kind != "bounded" || !input.peek(syn::token::Paren) {
+        return Err(Error::new_spanned(
+            kind,
+            "Expected a mailbox configuration.\n\
+             Use: #[task(message = T, mailbox = bounded(128))] or mailbox = unbounded",
+        ));
+    }
+
+    let content;
+    syn::parenthesized!(content in input);
+    content.parse().map(MailboxItem::Bounded)
+}
+
+/// Parse the value of `budget = n` into a positive, unsuffixed integer.
+fn parse_budget(input: ParseStream) -> Result<LitInt> {
+    let value: Expr = input.parse()?;
+    let budget = match &value {
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(messages),
+            ..
+        }) => messages
+            .base10_parse::<usize>()
+            .ok()
+            .filter(|&budget| budget > 0)
+            .map(|budget| LitInt::new(&budget.to_string(), messages.span())),
+        _ => None,
+    };
+
+    budget.ok_or_else(|| {
+        Error::new_spanned(
+            value,
+            "Expected a positive number of messages for the budget.\n\
+             Example: #[task(message = T, budget = 64)]",
+        )
+    })
+}
+
+/// Parse the period of `tick = "100ms" => ...` into a `Duration` expression.
+fn parse_tick_period(period: &syn::LitStr) -> Result<quote::__private::TokenStream> {
+    let value = period.value();
+    let split = value
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(value.len());
+    let (amount, unit) = value.split_at(split);
+
+    let nanos_per_unit: u128 = match unit {
+        "ns" => 1,
+        "us" => 1_000,
+        "ms" => 1_000_000,
+        "s" => 1_000_000_000,
+        "m" => 60_000_000_000,
+        "h" => 3_600_000_000_000,
+        _ => 0,
+    };
+    let nanos = amount
+        .parse::<u128>()
+        .ok()
+        .and_then(|amount| amount.checked_mul(nanos_per_unit))
+        .and_then(|nanos| u64::try_from(nanos).ok())
+        .filter(|&nanos| nanos > 0);
+
+    match nanos {
+        Some(nanos) => Ok(quote! { ::std::time::Duration::from_nanos(#nanos) }),
+        None => Err(Error::new_spanned(
+            period,
+            "Expected a tick period like \"100ms\".\n\
+             Supported units: ns, us, ms, s, m, h",
+        )),
+    }
+}
+
+/// Parse the value of `overflow = ...` into a path to the `Overflow` variant.
+fn parse_overflow_policy(input: ParseStream) -> Result<quote::__private::TokenStream> {
+    let policy: Ident = input.parse()?;
+
+    let variant = match policy.to_string().as_str() {
+        "reject" => quote! { Reject },
+        "drop_newest" => quote! { DropNewest },
+        "drop_oldest" => quote! { DropOldest },
+        _ => {
+            return Err(Error::new_spanned(
+                policy,
+                "Unknown overflow policy.\n\
+                 Supported policies: reject, drop_newest, drop_oldest",
+            ));
+        }
+    };
+
+    Ok(quote! { ::notizia::core::Overflow::#variant })
+}
+
 /// Attribute macro for message enums that automatically injects reply_to fields.
 ///
 /// This macro allows marking enum variants with `#[request(reply = T)]` to automatically
-/// inject a `reply_to: tokio::sync::oneshot::Sender<T>` field into the variant.
+/// inject a `reply_to: notizia::Reply<T>` field into the variant.
 ///
 /// # Example
 ///
@@ -235,23 +726,202 @@ fn parse_task_attribute(attrs: &[Attribute]) -> Result<Type> {
 /// ```rust,ignore
 /// #[derive(Debug)]
 /// enum CounterMsg {
-///     GetCount { reply_to: tokio::sync::oneshot::Sender<u32> },
-///     GetStatus { reply_to: tokio::sync::oneshot::Sender<String> },
+///     GetCount { reply_to: notizia::Reply<u32> },
+///     GetStatus { reply_to: notizia::Reply<String> },
 ///     Increment,
 ///     Decrement,
 /// }
 /// ```
+///
+/// together with a `CounterMsgCalls` extension trait for `TaskHandle<CounterMsg>`
+/// and `TaskRef<CounterMsg>`, providing `get_count()`, `get_count_with_timeout(timeout)`,
+/// `get_status()`, and `get_status_with_timeout(timeout)`. Fields of a request
+/// variant become parameters of its methods. Inherent methods of the handle,
+/// such as `join` or `shutdown`, take precedence over generated methods of the
+/// same name.
+///
+/// # Streaming replies
+///
+/// `#[request(stream = T)]` injects a `reply_to: notizia::StreamReply<T>`
+/// field instead, for requests answered with many items. Callers receive a
+/// `notizia::ReplyStream<T>` from `call_stream!`, and the generated method
+/// returns it directly instead of a future.
+///
+/// # Client
+///
+/// `#[message(client = CounterClient)]` additionally generates a `CounterClient`
+/// struct wrapping a `TaskRef<CounterMsg>`, with one method per variant. Cast
+/// variants return a `SendResult`, request variants return a future of the
+/// reply. Tuple fields become parameters named `arg0`, `arg1`, and so on.
+///
+/// ```rust,ignore
+/// let client = CounterClient::new(handle.this());
+/// client.increment()?;
+/// let count = client.get_count().await?;
+/// ```
+///
+/// # Cloneable casts
+///
+/// Request variants hold a `Reply`, so the message enum cannot derive `Clone`.
+/// `#[message(casts = CounterCast)]` generates a `CounterCast` enum with only
+/// the cast variants. It carries the attributes of the message enum plus
+/// `#[derive(Clone)]`, and converts into the message enum with `From`, so
+/// fire-and-forget messages can be cloned and broadcast:
+///
+/// ```rust,ignore
+/// let msg = CounterCast::Increment;
+/// for worker in &workers {
+///     worker.send(msg.clone().into())?;
+/// }
+/// ```
+///
+/// # Schema
+///
+/// Every message enum implements `notizia::core::schema::Describe`, whose
+/// `SCHEMA` constant lists the variants, their field types and the reply
+/// types of requests, and renders them as JSON for tooling in other
+/// languages.
+///
+/// # Serialization
+///
+/// With notizia's `serde` feature enabled, `#[message(serde)]` derives
+/// `Serialize` and `Deserialize` for the message enum and the cast enum, if
+/// any. Request variants are skipped, since their reply channel only exists
+/// within the process: serializing one fails, and they never result from
+/// deserializing. The derives use notizia's re-export of serde, so the
+/// crate defining the messages does not need to depend on serde itself.
+///
+/// ```rust,ignore
+/// #[message(serde)]
+/// #[derive(Debug)]
+/// enum CounterMsg {
+///     Add { amount: u32 },
+///     #[request(reply = u32)]
+///     GetCount,
+/// }
+///
+/// let json = serde_json::to_string(&CounterMsg::Add { amount: 2 })?;
+/// ```
+///
+/// Serialized enums implement `notizia::core::Versioned`. `version = N`
+/// (which implies `serde`) tags them with a schema version, so remote nodes
+/// reject payloads written by another version of the protocol instead of
+/// decoding them into the wrong shape. `compat = path::to::fn` names a
+/// function `fn<'de, D: Deserializer<'de>>(Option<u32>, D) -> Result<Enum,
+/// D::Error>` decoding payloads of older or unknown versions:
+///
+/// ```rust,ignore
+/// #[message(version = 2, compat = upcast_v1)]
+/// enum CounterMsg {
+///     Add { amount: u64 },
+/// }
+/// ```
 #[proc_macro_attribute]
-pub fn message(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn message(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let options = parse_macro_input!(attr with MessageOptions::parse);
     let input = parse_macro_input!(item as ItemEnum);
 
-    match impl_message_macro(&input) {
+    match impl_message_macro(&input, &options) {
         Ok(tokens) => tokens.into(),
         Err(err) => err.to_compile_error().into(),
     }
 }
 
-fn impl_message_macro(input: &ItemEnum) -> Result<quote::__private::TokenStream> {
+/// Options of the `#[message(...)]` attribute.
+#[derive(Default)]
+struct MessageOptions {
+    /// Name of the client struct to generate
+    client: Option<Ident>,
+    /// Name of the cast-only enum to generate
+    casts: Option<Ident>,
+    /// Whether to derive `Serialize` and `Deserialize`
+    serde: bool,
+    /// Schema version of the serialized enum
+    version: Option<LitInt>,
+    /// Function deserializing payloads of older versions
+    compat: Option<ExprPath>,
+}
+
+impl MessageOptions {
+    fn parse(input: syn::parse::ParseStream) -> Result<Self> {
+        let mut options = MessageOptions::default();
+        let items = Punctuated::<Meta, Token![,]>::parse_terminated(input)?;
+
+        for item in items {
+            match &item {
+                Meta::NameValue(item) if item.path.is_ident("client") => {
+                    options.client = Some(parse_ident_value(&item.value)?);
+                }
+                Meta::NameValue(item) if item.path.is_ident("casts") => {
+                    options.casts = Some(parse_ident_value(&item.value)?);
+                }
+                Meta::Path(path) if path.is_ident("serde") => options.serde = true,
+                Meta::NameValue(item) if item.path.is_ident("version") => match &item.value {
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Int(version),
+                        ..
+                    }) => {
+                        version.base10_parse::<u32>()?;
+                        options.version = Some(version.clone());
+                    }
+                    value => return Err(Error::new_spanned(value, "Expected a version number")),
+                },
+                Meta::NameValue(item) if item.path.is_ident("compat") => match &item.value {
+                    Expr::Path(path) => options.compat = Some(path.clone()),
+                    value => return Err(Error::new_spanned(value, "Expected a function path")),
+                },
+                _ => {
+                    return Err(Error::new_spanned(
+                        item.path(),
+                        "Unknown message option.\n\
+                         Supported options: #[message(client = ClientName, casts = CastEnumName, serde, version = N, compat = upcast_fn)]",
+                    ));
+                }
+            }
+        }
+
+        if let (Some(compat), None) = (&options.compat, &options.version) {
+            return Err(Error::new_spanned(
+                compat,
+                "`compat` requires a `version` to upcast to",
+            ));
+        }
+        // Versions only make sense for serialized messages
+        options.serde |= options.version.is_some();
+
+        Ok(options)
+    }
+
+    /// Attributes deriving `Serialize` and `Deserialize` through notizia's
+    /// re-export of serde, if requested.
+    fn serde_derive(&self) -> quote::__private::TokenStream {
+        if !self.serde {
+            return quote! {};
+        }
+
+        quote! {
+            #[derive(::notizia::serde::Serialize, ::notizia::serde::Deserialize)]
+            #[serde(crate = "::notizia::serde")]
+        }
+    }
+}
+
+/// Parse the value of a `name = Ident` option.
+fn parse_ident_value(value: &Expr) -> Result<Ident> {
+    match value {
+        Expr::Path(expr_path) if expr_path.qself.is_none() => expr_path
+            .path
+            .get_ident()
+            .cloned()
+            .ok_or_else(|| Error::new_spanned(value, "Expected an identifier")),
+        _ => Err(Error::new_spanned(value, "Expected an identifier")),
+    }
+}
+
+fn impl_message_macro(
+    input: &ItemEnum,
+    options: &MessageOptions,
+) -> Result<quote::__private::TokenStream> {
     let enum_name = &input.ident;
     let vis = &input.vis;
     let attrs = &input.attrs;
@@ -261,22 +931,629 @@ fn impl_message_macro(input: &ItemEnum) -> Result<quote::__private::TokenStream>
     let variants = input
         .variants
         .iter()
-        .map(process_variant)
+        .map(|variant| process_variant(variant, options.serde))
         .collect::<Result<Vec<_>>>()?;
 
+    let calls = generate_calls_trait(input)?;
+    let client = match &options.client {
+        Some(client) => generate_client(input, client)?,
+        None => quote! {},
+    };
+    let casts = match &options.casts {
+        Some(casts) => generate_cast_enum(input, casts, &options.serde_derive())?,
+        None => quote! {},
+    };
+    let correlated = generate_correlated(input)?;
+    let serde_derive = options.serde_derive();
+    let versioned = generate_versioned(input, options);
+    let described = generate_describe(input, options)?;
+
     // Generate the enum
     let generated = quote! {
         #(#attrs)*
+        #serde_derive
         #vis enum #enum_name #generics {
             #(#variants),*
         }
+
+        #calls
+
+        #correlated
+
+        #versioned
+
+        #described
+
+        #client
+
+        #casts
     };
 
     Ok(generated)
 }
 
+/// Implement `Versioned` for serialized enums, rejecting payloads of other
+/// versions unless a `compat` function decodes them.
+fn generate_versioned(input: &ItemEnum, options: &MessageOptions) -> quote::__private::TokenStream {
+    if !options.serde {
+        return quote! {};
+    }
+
+    let enum_name = &input.ident;
+    let mut generics = input.generics.clone();
+    generics
+        .make_where_clause()
+        .predicates
+        .push(parse_quote!(Self: ::notizia::serde::de::DeserializeOwned));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let (version, upcast) = match &options.version {
+        None => (
+            quote! { None },
+            quote! {
+                let _ = version;
+                <Self as ::notizia::serde::Deserialize>::deserialize(payload)
+            },
+        ),
+        Some(version) => {
+            let older = match &options.compat {
+                Some(compat) => quote! { _ => #compat(version, payload), },
+                None => quote! {
+                    Some(version) => Err(::notizia::core::version::unsupported(version, #version)),
+                    None => Err(::notizia::core::version::untagged(#version)),
+                },
+            };
+            (
+                quote! { Some(#version) },
+                quote! {
+                    match version {
+                        Some(#version) => <Self as ::notizia::serde::Deserialize>::deserialize(payload),
+                        Some(version) if version > #version => {
+                            Err(::notizia::core::version::unsupported(version, #version))
+                        }
+                        #older
+                    }
+                },
+            )
+        }
+    };
+
+    quote! {
+        impl #impl_generics ::notizia::core::Versioned for #enum_name #ty_generics #where_clause {
+            const VERSION: Option<u32> = #version;
+
+            fn upcast<'de, D>(version: Option<u32>, payload: D) -> Result<Self, D::Error>
+            where
+                D: ::notizia::serde::Deserializer<'de>,
+            {
+                #upcast
+            }
+        }
+    }
+}
+
+/// Implement `Describe` for the enum, recording variants, field types and
+/// reply types as written.
+fn generate_describe(
+    input: &ItemEnum,
+    options: &MessageOptions,
+) -> Result<quote::__private::TokenStream> {
+    let enum_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut variants = Vec::new();
+    for variant in &input.variants {
+        let name = variant.ident.to_string();
+        let (shape, fields) = match &variant.fields {
+            Fields::Unit => (quote! { Unit }, Vec::new()),
+            Fields::Unnamed(fields) => (quote! { Tuple }, fields.unnamed.iter().collect()),
+            Fields::Named(fields) => (quote! { Struct }, fields.named.iter().collect()),
+        };
+        let fields = fields.into_iter().map(|field| {
+            let name = match &field.ident {
+                Some(ident) => {
+                    let ident = ident.to_string();
+                    quote! { Some(#ident) }
+                }
+                None => quote! { None },
+            };
+            let ty = type_name(&field.ty);
+            quote! { ::notizia::core::schema::FieldSchema { name: #name, ty: #ty } }
+        });
+        let reply = match parse_request_attribute(&variant.attrs)? {
+            Some(Request::Reply(ty)) => {
+                let ty = type_name(&ty);
+                quote! { Some(::notizia::core::schema::ReplySchema::Single(#ty)) }
+            }
+            Some(Request::Stream(ty)) => {
+                let ty = type_name(&ty);
+                quote! { Some(::notizia::core::schema::ReplySchema::Stream(#ty)) }
+            }
+            None => quote! { None },
+        };
+
+        variants.push(quote! {
+            ::notizia::core::schema::VariantSchema {
+                name: #name,
+                shape: ::notizia::core::schema::Shape::#shape,
+                fields: &[#(#fields),*],
+                reply: #reply,
+            }
+        });
+    }
+
+    let name = enum_name.to_string();
+    let version = match &options.version {
+        Some(version) => quote! { Some(#version) },
+        None => quote! { None },
+    };
+    let serde = options.serde;
+
+    Ok(quote! {
+        impl #impl_generics ::notizia::core::schema::Describe for #enum_name #ty_generics #where_clause {
+            const SCHEMA: ::notizia::core::schema::Schema = ::notizia::core::schema::Schema {
+                name: #name,
+                version: #version,
+                serde: #serde,
+                variants: &[#(#variants),*],
+            };
+        }
+    })
+}
+
+/// Render a type as written, without the spaces token streams put around
+/// punctuation.
+fn type_name(ty: &Type) -> String {
+    let tokens = quote!(#ty).to_string();
+    let chars: Vec<char> = tokens.chars().collect();
+    let word = |c: Option<&char>| c.is_some_and(|c| c.is_alphanumeric() || *c == '_');
+
+    let mut name = String::with_capacity(tokens.len());
+    for (i, c) in chars.iter().enumerate() {
+        if *c != ' ' {
+            name.push(*c);
+            if *c == ',' || *c == ';' {
+                name.push(' ');
+            }
+        } else if word(name.chars().last().as_ref()) && word(chars.get(i + 1)) {
+            // Keep the space in `dyn Trait` or `mut T`
+            name.push(' ');
+        }
+    }
+    name
+}
+
+/// Implement `Correlated` for the enum, reading the id from the `reply_to`
+/// field of request variants.
+///
+/// Nothing is generated for enums without request variants.
+fn generate_correlated(input: &ItemEnum) -> Result<quote::__private::TokenStream> {
+    let enum_name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut arms = Vec::new();
+    for variant in &input.variants {
+        if parse_request_attribute(&variant.attrs)?.is_some() {
+            let variant_name = &variant.ident;
+            arms.push(quote! {
+                #enum_name::#variant_name { reply_to, .. } => {
+                    ::notizia::core::Correlated::correlation_id(reply_to)
+                }
+            });
+        }
+    }
+
+    if arms.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let fallback = if arms.len() < input.variants.len() {
+        quote! { _ => None, }
+    } else {
+        quote! {}
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::notizia::core::Correlated for #enum_name #ty_generics #where_clause {
+            fn correlation_id(&self) -> Option<::notizia::core::CorrelationId> {
+                match self {
+                    #(#arms)*
+                    #fallback
+                }
+            }
+        }
+    })
+}
+
+/// Generate the `{Enum}Calls` extension trait with one method per request variant.
+///
+/// The trait is implemented for `TaskHandle` and `TaskRef` of the enum. No
+/// trait is generated for enums without request variants.
+fn generate_calls_trait(input: &ItemEnum) -> Result<quote::__private::TokenStream> {
+    let enum_name = &input.ident;
+    let vis = &input.vis;
+    let trait_name = format_ident!("{}Calls", enum_name);
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut signatures = Vec::new();
+    let mut bodies = Vec::new();
+
+    for variant in &input.variants {
+        let Some(request) = parse_request_attribute(&variant.attrs)? else {
+            continue;
+        };
+
+        let variant_name = &variant.ident;
+        let method = method_ident(variant_name);
+        let method_with_timeout = format_ident!("{}_with_timeout", method);
+
+        let (names, types): (Vec<_>, Vec<_>) = variant
+            .fields
+            .iter()
+            .filter_map(|field| field.ident.as_ref().map(|name| (name, &field.ty)))
+            .unzip();
+
+        let reply_type = match request {
+            Request::Reply(reply_type) => reply_type,
+            Request::Stream(item_type) => {
+                let output = quote! { ::notizia::CallResult<::notizia::ReplyStream<#item_type>> };
+                let doc = format!(
+                    "Send [`{enum_name}::{variant_name}`] to the task and stream its replies."
+                );
+
+                signatures.push(quote! {
+                    #[doc = #doc]
+                    fn #method(&self, #(#names: #types),*) -> #output;
+                });
+                bodies.push(quote! {
+                    fn #method(&self, #(#names: #types),*) -> #output {
+                        ::notizia::call_stream!(
+                            self,
+                            |reply_to| #enum_name::#variant_name { #(#names,)* reply_to }
+                        )
+                    }
+                });
+                continue;
+            }
+        };
+
+        let output = quote! {
+            impl ::std::future::Future<Output = ::notizia::CallResult<#reply_type>> + Send
+        };
+        let doc =
+            format!("Call the task with [`{enum_name}::{variant_name}`] and wait for the reply.");
+        let doc_with_timeout = format!(
+            "Call the task with [`{enum_name}::{variant_name}`], waiting at most `timeout` for the reply."
+        );
+
+        signatures.push(quote! {
+            #[doc = #doc]
+            fn #method(&self, #(#names: #types),*) -> #output;
+
+            #[doc = #doc_with_timeout]
+            fn #method_with_timeout(
+                &self,
+                #(#names: #types,)*
+                timeout: impl ::notizia::core::IntoTimeout,
+            ) -> #output;
+        });
+
+        bodies.push(quote! {
+            fn #method(&self, #(#names: #types),*) -> #output {
+                self.#method_with_timeout(#(#names,)* ::notizia::core::DEFAULT_CALL_TIMEOUT)
+            }
+
+            fn #method_with_timeout(
+                &self,
+                #(#names: #types,)*
+                timeout: impl ::notizia::core::IntoTimeout,
+            ) -> #output {
+                let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+                async move {
+                    ::notizia::call!(
+                        self,
+                        |reply_to| #enum_name::#variant_name { #(#names,)* reply_to },
+                        timeout = timeout
+                    )
+                    .await
+                }
+            }
+        });
+    }
+
+    if signatures.is_empty() {
+        return Ok(quote! {});
+    }
+
+    let doc = format!(
+        "Typed calls for the request variants of [`{enum_name}`].\n\n\
+         Implemented for `TaskHandle` and `TaskRef`, so requests can be sent \
+         like ordinary async method calls."
+    );
+    let bound = quote! { #enum_name #ty_generics: Send + 'static };
+    let where_clause = match where_clause {
+        Some(where_clause) => quote! { #where_clause, #bound },
+        None => quote! { where #bound },
+    };
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis trait #trait_name #impl_generics {
+            #(#signatures)*
+        }
+
+        impl #impl_generics #trait_name #ty_generics for ::notizia::TaskHandle<#enum_name #ty_generics>
+        #where_clause
+        {
+            #(#bodies)*
+        }
+
+        impl #impl_generics #trait_name #ty_generics for ::notizia::TaskRef<#enum_name #ty_generics>
+        #where_clause
+        {
+            #(#bodies)*
+        }
+    })
+}
+
+/// Generate a client struct wrapping a `TaskRef` with one method per variant.
+fn generate_client(input: &ItemEnum, client: &Ident) -> Result<quote::__private::TokenStream> {
+    let enum_name = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let mut methods = Vec::new();
+
+    for variant in &input.variants {
+        let variant_name = &variant.ident;
+        let method = method_ident(variant_name);
+
+        if let Some(Request::Stream(item_type)) = parse_request_attribute(&variant.attrs)? {
+            let names: Vec<_> = variant.fields.iter().map(|field| &field.ident).collect();
+            let types: Vec<_> = variant.fields.iter().map(|field| &field.ty).collect();
+            let doc =
+                format!("Send [`{enum_name}::{variant_name}`] to the task and stream its replies.");
+
+            methods.push(quote! {
+                #[doc = #doc]
+                pub fn #method(
+                    &self,
+                    #(#names: #types),*
+                ) -> ::notizia::CallResult<::notizia::ReplyStream<#item_type>> {
+                    ::notizia::call_stream!(
+                        self.task,
+                        |reply_to| #enum_name::#variant_name { #(#names,)* reply_to }
+                    )
+                }
+            });
+        } else if let Some(Request::Reply(reply_type)) = parse_request_attribute(&variant.attrs)? {
+            let method_with_timeout = format_ident!("{}_with_timeout", method);
+            let (names, types): (Vec<_>, Vec<_>) = variant
+                .fields
+                .iter()
+                .filter_map(|field| field.ident.as_ref().map(|name| (name, &field.ty)))
+                .unzip();
+            let doc = format!("Call the task with [`{enum_name}::{variant_name}`].");
+            let doc_with_timeout = format!(
+                "Call the task with [`{enum_name}::{variant_name}`], waiting at most `timeout` for the reply."
+            );
+
+            methods.push(quote! {
+                #[doc = #doc]
+                pub async fn #method(&self, #(#names: #types),*) -> ::notizia::CallResult<#reply_type> {
+                    self.#method_with_timeout(#(#names,)* ::notizia::core::DEFAULT_CALL_TIMEOUT)
+                        .await
+                }
+
+                #[doc = #doc_with_timeout]
+                pub async fn #method_with_timeout(
+                    &self,
+                    #(#names: #types,)*
+                    timeout: impl ::notizia::core::IntoTimeout,
+                ) -> ::notizia::CallResult<#reply_type> {
+                    ::notizia::call!(
+                        self.task,
+                        |reply_to| #enum_name::#variant_name { #(#names,)* reply_to },
+                        timeout = timeout
+                    )
+                    .await
+                }
+            });
+        } else {
+            let doc = format!("Send [`{enum_name}::{variant_name}`] to the task.");
+            let (params, construct) = match &variant.fields {
+                Fields::Named(fields) => {
+                    let names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                    let types: Vec<_> = fields.named.iter().map(|f| &f.ty).collect();
+                    (
+                        quote! { #(#names: #types),* },
+                        quote! { #enum_name::#variant_name { #(#names),* } },
+                    )
+                }
+                Fields::Unnamed(fields) => {
+                    let names: Vec<_> = (0..fields.unnamed.len())
+                        .map(|i| format_ident!("arg{}", i))
+                        .collect();
+                    let types: Vec<_> = fields.unnamed.iter().map(|f| &f.ty).collect();
+                    (
+                        quote! { #(#names: #types),* },
+                        quote! { #enum_name::#variant_name(#(#names),*) },
+                    )
+                }
+                Fields::Unit => (quote! {}, quote! { #enum_name::#variant_name }),
+            };
+
+            methods.push(quote! {
+                #[doc = #doc]
+                pub fn #method(&self, #params) -> ::notizia::SendResult<#enum_name #ty_generics> {
+                    self.task.send(#construct)
+                }
+            });
+        }
+    }
+
+    let doc =
+        format!("Client for tasks receiving [`{enum_name}`], with one method per message variant.");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #vis struct #client #impl_generics #where_clause {
+            task: ::notizia::TaskRef<#enum_name #ty_generics>,
+        }
+
+        impl #impl_generics #client #ty_generics #where_clause {
+            /// Create a client for `task`.
+            pub fn new(task: ::notizia::TaskRef<#enum_name #ty_generics>) -> Self {
+                Self { task }
+            }
+
+            /// The task this client sends to.
+            pub fn task(&self) -> &::notizia::TaskRef<#enum_name #ty_generics> {
+                &self.task
+            }
+
+            #(#methods)*
+        }
+
+        impl #impl_generics ::std::clone::Clone for #client #ty_generics #where_clause {
+            fn clone(&self) -> Self {
+                Self {
+                    task: self.task.clone(),
+                }
+            }
+        }
+
+        impl #impl_generics ::std::convert::From<::notizia::TaskRef<#enum_name #ty_generics>>
+            for #client #ty_generics #where_clause
+        {
+            fn from(task: ::notizia::TaskRef<#enum_name #ty_generics>) -> Self {
+                Self::new(task)
+            }
+        }
+    })
+}
+
+/// Generate a cloneable enum holding only the cast variants of the message enum.
+fn generate_cast_enum(
+    input: &ItemEnum,
+    casts: &Ident,
+    serde_derive: &quote::__private::TokenStream,
+) -> Result<quote::__private::TokenStream> {
+    let enum_name = &input.ident;
+    let vis = &input.vis;
+    let generics = &input.generics;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Documentation belongs to the message enum only
+    let attrs: Vec<_> = input
+        .attrs
+        .iter()
+        .filter(|attr| !attr.path().is_ident("doc"))
+        .collect();
+    let derives_clone = attrs.iter().any(|attr| {
+        attr.path().is_ident("derive")
+            && attr
+                .parse_args_with(Punctuated::<syn::Path, Token![,]>::parse_terminated)
+                .is_ok_and(|paths| paths.iter().any(|path| path.is_ident("Clone")))
+    });
+    let derive_clone = if derives_clone {
+        quote! {}
+    } else {
+        quote! { #[derive(Clone)] }
+    };
+
+    let mut variants = Vec::new();
+    let mut conversions = Vec::new();
+
+    for variant in &input.variants {
+        if parse_request_attribute(&variant.attrs)?.is_some() {
+            continue;
+        }
+
+        let variant_name = &variant.ident;
+        variants.push(variant);
+
+        conversions.push(match &variant.fields {
+            Fields::Named(fields) => {
+                let names: Vec<_> = fields.named.iter().map(|f| &f.ident).collect();
+                quote! {
+                    #casts::#variant_name { #(#names),* } => #enum_name::#variant_name { #(#names),* }
+                }
+            }
+            Fields::Unnamed(fields) => {
+                let names: Vec<_> = (0..fields.unnamed.len())
+                    .map(|i| format_ident!("arg{}", i))
+                    .collect();
+                quote! {
+                    #casts::#variant_name(#(#names),*) => #enum_name::#variant_name(#(#names),*)
+                }
+            }
+            Fields::Unit => quote! {
+                #casts::#variant_name => #enum_name::#variant_name
+            },
+        });
+    }
+
+    let doc = format!("The cast variants of [`{enum_name}`], which can be cloned and broadcast.");
+
+    Ok(quote! {
+        #[doc = #doc]
+        #(#attrs)*
+        #derive_clone
+        #serde_derive
+        #vis enum #casts #generics {
+            #(#variants),*
+        }
+
+        impl #impl_generics ::std::convert::From<#casts #ty_generics> for #enum_name #ty_generics
+        #where_clause
+        {
+            fn from(msg: #casts #ty_generics) -> Self {
+                match msg {
+                    #(#conversions),*
+                }
+            }
+        }
+    })
+}
+
+/// Convert a `CamelCase` variant name into a `snake_case` method name.
+fn to_snake_case(name: &str) -> String {
+    let mut snake = String::new();
+
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
+
+/// Create the method identifier for a variant, falling back to a raw identifier for keywords.
+fn method_ident(variant_name: &Ident) -> Ident {
+    let name = to_snake_case(&variant_name.to_string());
+    let span = variant_name.span();
+
+    match syn::parse_str::<Ident>(&name) {
+        Ok(mut ident) => {
+            ident.set_span(span);
+            ident
+        }
+        Err(_) => Ident::new_raw(&name, span),
+    }
+}
+
 /// Process a single enum variant, checking for #[request(reply = T)] attribute
-fn process_variant(variant: &Variant) -> Result<quote::__private::TokenStream> {
+///
+/// With `serde`, request variants are skipped when (de)serializing, as their
+/// reply channel cannot cross a process boundary.
+fn process_variant(variant: &Variant, serde: bool) -> Result<quote::__private::TokenStream> {
     let variant_name = &variant.ident;
     let variant_attrs: Vec<_> = variant
         .attrs
@@ -285,12 +1562,18 @@ fn process_variant(variant: &Variant) -> Result<quote::__private::TokenStream> {
         .collect();
 
     // Check for #[request(reply = T)] attribute
-    if let Some(reply_type) = parse_request_attribute(&variant.attrs)? {
+    if let Some(request) = parse_request_attribute(&variant.attrs)? {
         // Inject reply_to field
-        let fields = inject_reply_field(variant, &reply_type)?;
+        let fields = inject_reply_field(variant, &request)?;
+        let skip = if serde {
+            quote! { #[serde(skip)] }
+        } else {
+            quote! {}
+        };
 
         Ok(quote! {
             #(#variant_attrs)*
+            #skip
             #variant_name #fields
         })
     } else {
@@ -311,8 +1594,17 @@ fn process_variant(variant: &Variant) -> Result<quote::__private::TokenStream> {
     }
 }
 
-/// Parse the #[request(reply = T)] attribute to extract the reply type.
-fn parse_request_attribute(attrs: &[Attribute]) -> Result<Option<Type>> {
+/// The kind of reply a request variant expects.
+enum Request {
+    /// `#[request(reply = T)]`: a single reply of type `T`
+    Reply(Type),
+    /// `#[request(stream = T)]`: a stream of items of type `T`
+    Stream(Type),
+}
+
+/// Parse the #[request(reply = T)] or #[request(stream = T)] attribute to
+/// extract the reply type.
+fn parse_request_attribute(attrs: &[Attribute]) -> Result<Option<Request>> {
     // Find the #[request(...)] attribute
     let request_attr = attrs.iter().find(|attr| attr.path().is_ident("request"));
 
@@ -324,35 +1616,33 @@ fn parse_request_attribute(attrs: &[Attribute]) -> Result<Option<Type>> {
 
     match meta {
         Meta::List(list) => {
-            // Parse the nested meta items: #[request(reply = T)]
-            let nested: MetaNameValue = syn::parse2(list.tokens.clone()).map_err(|_| {
-                Error::new_spanned(
-                    meta,
-                    "Expected #[request(reply = Type)].\n\
-                     The request attribute must be in the form: #[request(reply = YourReplyType)]",
-                )
-            })?;
+            // Parse the nested items: #[request(reply = T)] or #[request(stream = T)]
+            let (name, ty) = list
+                .parse_args_with(|input: syn::parse::ParseStream| {
+                    let name: Ident = input.parse()?;
+                    input.parse::<Token![=]>()?;
+                    let ty: Type = input.parse()?;
+                    Ok((name, ty))
+                })
+                .map_err(|_| {
+                    Error::new_spanned(
+                        meta,
+                        "Expected #[request(reply = Type)] or #[request(stream = Type)].\n\
+                         The request attribute must be in the form: #[request(reply = YourReplyType)] \
+                         or #[request(stream = YourItemType)]",
+                    )
+                })?;
 
-            // Check that the name is "reply"
-            if !nested.path.is_ident("reply") {
-                return Err(Error::new_spanned(
-                    &nested.path,
+            if name == "reply" {
+                Ok(Some(Request::Reply(ty)))
+            } else if name == "stream" {
+                Ok(Some(Request::Stream(ty)))
+            } else {
+                Err(Error::new_spanned(
+                    &name,
                     "Expected 'reply' parameter.\n\
-                     Use: #[request(reply = YourReplyType)]",
-                ));
-            }
-
-            // Extract the type from the value
-            match &nested.value {
-                Expr::Path(expr_path) => Ok(Some(Type::Path(syn::TypePath {
-                    qself: None,
-                    path: expr_path.path.clone(),
-                }))),
-                _ => Err(Error::new_spanned(
-                    &nested.value,
-                    "Expected a type for the reply parameter.\n\
-                     Example: #[request(reply = u32)]",
-                )),
+                     Use: #[request(reply = YourReplyType)] or #[request(stream = YourItemType)]",
+                ))
             }
         }
         Meta::Path(_) => Err(Error::new_spanned(
@@ -371,15 +1661,20 @@ fn parse_request_attribute(attrs: &[Attribute]) -> Result<Option<Type>> {
 /// Inject reply_to field into the variant
 fn inject_reply_field(
     variant: &Variant,
-    reply_type: &Type,
+    request: &Request,
 ) -> Result<quote::__private::TokenStream> {
+    let reply_type = match request {
+        Request::Reply(reply_type) => quote! { ::notizia::Reply<#reply_type> },
+        Request::Stream(item_type) => quote! { ::notizia::StreamReply<#item_type> },
+    };
+
     match &variant.fields {
         Fields::Named(fields) => {
             // Add reply_to to existing named fields
             let mut new_fields = fields.named.clone();
 
             let reply_field: Field = syn::parse_quote! {
-                reply_to: ::notizia::tokio::sync::oneshot::Sender<#reply_type>
+                reply_to: #reply_type
             };
 
             new_fields.push(reply_field);
@@ -391,7 +1686,7 @@ fn inject_reply_field(
         Fields::Unit => {
             // Convert unit variant to struct variant with single field
             Ok(quote! {
-                { reply_to: ::notizia::tokio::sync::oneshot::Sender<#reply_type> }
+                { reply_to: #reply_type }
             })
         }
         Fields::Unnamed(_) => {
@@ -405,3 +1700,271 @@ fn inject_reply_field(
         }
     }
 }
+
+/// Attribute macro mapping the variants of a message enum to the methods of
+/// an impl block.
+///
+/// `#[handlers(message = CounterMsg)]` on an inherent impl block generates a
+/// `Handler<CounterMsg>` impl for tasks declared with
+/// `#[task(message = CounterMsg, handler)]`. Every method handles the
+/// variant with the same name in `CamelCase`, e.g. `get_count` handles
+/// `CounterMsg::GetCount`:
+///
+/// - Parameters are bound to the variant's fields of the same name. Fields
+///   of tuple variants are bound to parameters named `arg0`, `arg1`, and so on.
+/// - A parameter of type `&mut Context<CounterMsg>` receives the task's context.
+/// - If the method returns a value, the variant is treated as a request and
+///   the value is sent through its `reply_to` field. Methods can instead take
+///   `reply_to` as a parameter to reply themselves, e.g. for streams.
+/// - A method named `terminate` implements `Handler::terminate`.
+///
+/// The generated dispatch is exhaustive: a variant without a method fails to
+/// compile, as does a method without a variant.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// use notizia::prelude::*;
+/// use notizia::{handlers, message};
+///
+/// #[message]
+/// #[derive(Debug)]
+/// enum CounterMsg {
+///     Increment,
+///     Add { amount: u32 },
+///     #[request(reply = u32)]
+///     GetCount,
+/// }
+///
+/// #[derive(Task)]
+/// #[task(message = CounterMsg, handler)]
+/// struct Counter {
+///     count: u32,
+/// }
+///
+/// #[handlers(message = CounterMsg)]
+/// impl Counter {
+///     async fn increment(&mut self) {
+///         self.count += 1;
+///     }
+///
+///     async fn add(&mut self, amount: u32) {
+///         self.count += amount;
+///     }
+///
+///     async fn get_count(&self) -> u32 {
+///         self.count
+///     }
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn handlers(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let message = parse_macro_input!(attr with parse_handlers_attribute);
+    let input = parse_macro_input!(item as ItemImpl);
+
+    match impl_handlers_macro(&input, &message) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// Parse the `message = T` argument of `#[handlers(...)]`.
+fn parse_handlers_attribute(input: ParseStream) -> Result<syn::Path> {
+    let usage = "Expected #[handlers(message = YourMessageType)]";
+
+    if input.is_empty() {
+        return Err(Error::new(input.span(), usage));
+    }
+
+    let name: Ident = input.parse()?;
+    if name != "message" {
+        return Err(Error::new_spanned(name, usage));
+    }
+
+    input.parse::<Token![=]>()?;
+    let message = parse_message_type(input)?;
+    input.parse::<Option<Token![,]>>()?;
+
+    match message {
+        Type::Path(type_path) if type_path.qself.is_none() => {
+            // Variants of generic enums are named with a turbofish, `Msg::<T>::Variant`
+            let mut path = type_path.path;
+            if let Some(segment) = path.segments.last_mut()
+                && let syn::PathArguments::AngleBracketed(args) = &mut segment.arguments
+            {
+                args.colon2_token = Some(Default::default());
+            }
+
+            Ok(path)
+        }
+        other => Err(Error::new_spanned(
+            other,
+            "Expected the path of a message enum.\n\
+             Example: #[handlers(message = MyMessage)]",
+        )),
+    }
+}
+
+fn impl_handlers_macro(
+    input: &ItemImpl,
+    message: &syn::Path,
+) -> Result<quote::__private::TokenStream> {
+    if let Some((_, trait_path, _)) = &input.trait_ {
+        return Err(Error::new_spanned(
+            trait_path,
+            "#[handlers] must be applied to an inherent impl block.\n\
+             Example: #[handlers(message = MyMessage)] impl MyTask { ... }",
+        ));
+    }
+
+    let mut arms = Vec::new();
+    let mut terminate = quote! {};
+
+    for item in &input.items {
+        let syn::ImplItem::Fn(method) = item else {
+            continue;
+        };
+        let sig = &method.sig;
+        let method_name = &sig.ident;
+        let awaited = sig.asyncness.map(|_| quote! { .await });
+
+        if method_name == "terminate" {
+            terminate = quote! {
+                async fn terminate(&mut self, reason: ::notizia::TerminateReason) {
+                    self.terminate(reason) #awaited
+                }
+            };
+            continue;
+        }
+
+        let mut fields = Vec::new();
+        let mut args = Vec::new();
+        let mut replies_itself = false;
+
+        for input in sig.inputs.iter() {
+            let syn::FnArg::Typed(arg) = input else {
+                continue;
+            };
+
+            if is_context_type(&arg.ty) {
+                args.push(quote! { ctx });
+                continue;
+            }
+
+            let syn::Pat::Ident(pat) = &*arg.pat else {
+                return Err(Error::new_spanned(
+                    &arg.pat,
+                    "Handler parameters must be plain identifiers named like the variant's fields",
+                ));
+            };
+            let field = &pat.ident;
+
+            replies_itself |= field == "reply_to";
+            args.push(quote! { #field });
+
+            // Tuple fields are bound positionally from `arg0`, `arg1`, ...
+            match tuple_index(field) {
+                Some(index) => fields.push(quote! { #index: #field }),
+                None => fields.push(quote! { #field }),
+            }
+        }
+
+        let returns_value = match &sig.output {
+            syn::ReturnType::Default => false,
+            syn::ReturnType::Type(_, ty) => {
+                !matches!(&**ty, Type::Tuple(tuple) if tuple.elems.is_empty())
+            }
+        };
+
+        if returns_value && replies_itself {
+            return Err(Error::new_spanned(
+                &sig.output,
+                "A handler taking `reply_to` replies itself and must not return a value",
+            ));
+        }
+
+        let variant = variant_ident(method_name);
+        let call = quote! { self.#method_name(#(#args),*) #awaited };
+
+        arms.push(if returns_value {
+            quote! {
+                #message::#variant { #(#fields,)* reply_to } => {
+                    let reply = #call;
+                    let _ = reply_to.reply(reply);
+                }
+            }
+        } else {
+            quote! {
+                #message::#variant { #(#fields),* } => {
+                    #call;
+                }
+            }
+        });
+    }
+
+    let self_ty = &input.self_ty;
+    let (impl_generics, _, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        #input
+
+        impl #impl_generics ::notizia::task::Handler<#message> for #self_ty #where_clause {
+            async fn handle(&mut self, msg: #message, ctx: &mut ::notizia::task::Context<#message>) {
+                let _ = &ctx;
+
+                match msg {
+                    #(#arms)*
+                }
+            }
+
+            #terminate
+        }
+    })
+}
+
+/// Check whether `ty` is `&mut Context<M>`.
+fn is_context_type(ty: &Type) -> bool {
+    let Type::Reference(reference) = ty else {
+        return false;
+    };
+
+    match &*reference.elem {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "Context"),
+        _ => false,
+    }
+}
+
+/// The tuple field bound to a handler parameter named `arg<N>`.
+fn tuple_index(field: &Ident) -> Option<syn::Index> {
+    let index = field.to_string().strip_prefix("arg")?.parse::<u32>().ok()?;
+
+    Some(syn::Index {
+        index,
+        span: field.span(),
+    })
+}
+
+/// Convert a `snake_case` method name into a `CamelCase` variant name.
+fn to_camel_case(name: &str) -> String {
+    name.split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Create the variant identifier handled by a method.
+fn variant_ident(method_name: &Ident) -> Ident {
+    let name = method_name.to_string();
+    let name = name.strip_prefix("r#").unwrap_or(&name);
+
+    Ident::new(&to_camel_case(name), method_name.span())
+}
@@ -0,0 +1,10 @@
+use notizia_gen::Task;
+
+struct Msg;
+
+// Test a zero budget - should fail with "Expected a positive number of messages"
+#[derive(Task)]
+#[task(message = Msg, budget = 0)]
+struct MyTask;
+
+fn main() {}
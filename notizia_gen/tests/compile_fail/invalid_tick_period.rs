@@ -0,0 +1,12 @@
+use notizia_gen::Task;
+
+enum Msg {
+    Tick,
+}
+
+// Test a tick period without a unit - should fail with "Expected a tick period"
+#[derive(Task)]
+#[task(message = Msg, tick = "100" => Msg::Tick)]
+struct MyTask;
+
+fn main() {}
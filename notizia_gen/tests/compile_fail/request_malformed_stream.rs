@@ -0,0 +1,9 @@
+use notizia_gen::message;
+
+#[message]
+enum TestMsg {
+    #[request(stream)]
+    Watch,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use notizia_gen::Task;
+
+#[derive(Clone, Debug)]
+struct CrunchMessage;
+
+#[derive(Task)]
+#[task(message = CrunchMessage, blocking)]
+struct CrunchTask;
+
+fn main() {}
@@ -0,0 +1,198 @@
+use notizia_gen::Task;
+struct DataMessage;
+#[automatically_derived]
+impl ::core::clone::Clone for DataMessage {
+    #[inline]
+    fn clone(&self) -> DataMessage {
+        DataMessage
+    }
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for DataMessage {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::write_str(f, "DataMessage")
+    }
+}
+enum ControlMessage {
+    Pause,
+    Resume,
+}
+#[automatically_derived]
+impl ::core::clone::Clone for ControlMessage {
+    #[inline]
+    fn clone(&self) -> ControlMessage {
+        match self {
+            ControlMessage::Pause => ControlMessage::Pause,
+            ControlMessage::Resume => ControlMessage::Resume,
+        }
+    }
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for ControlMessage {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::write_str(
+            f,
+            match self {
+                ControlMessage::Pause => "Pause",
+                ControlMessage::Resume => "Resume",
+            },
+        )
+    }
+}
+#[task(message = DataMessage, control = ControlMessage)]
+struct IngestTask;
+const _: () = {
+    static __NOTIZIA_TASK_STATE: ::tokio::task::LocalKey<
+        ::notizia::TaskState<DataMessage>,
+    > = {
+        const __KEY: ::std::thread::LocalKey<
+            std::cell::RefCell<Option<::notizia::TaskState<DataMessage>>>,
+        > = {
+            const __RUST_STD_INTERNAL_INIT: std::cell::RefCell<
+                Option<::notizia::TaskState<DataMessage>>,
+            > = { std::cell::RefCell::new(None) };
+            unsafe {
+                ::std::thread::LocalKey::new(const {
+                    if ::std::mem::needs_drop::<
+                        std::cell::RefCell<Option<::notizia::TaskState<DataMessage>>>,
+                    >() {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: ::std::thread::local_impl::EagerStorage<
+                                std::cell::RefCell<
+                                    Option<::notizia::TaskState<DataMessage>>,
+                                >,
+                            > = ::std::thread::local_impl::EagerStorage::new(
+                                __RUST_STD_INTERNAL_INIT,
+                            );
+                            __RUST_STD_INTERNAL_VAL.get()
+                        }
+                    } else {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: std::cell::RefCell<
+                                Option<::notizia::TaskState<DataMessage>>,
+                            > = __RUST_STD_INTERNAL_INIT;
+                            &__RUST_STD_INTERNAL_VAL
+                        }
+                    }
+                })
+            }
+        };
+        ::tokio::task::LocalKey {
+            inner: __KEY,
+        }
+    };
+    impl ::notizia::Task<DataMessage> for IngestTask {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<DataMessage>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<DataMessage>::this(&self);
+            let path = "macrotest005::IngestTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
+                async move {
+                    let task = self;
+                    let mb = ::notizia::Task::<DataMessage>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    ::notizia::Runnable::<DataMessage>::start(&task),
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::Runnable::<
+                                    DataMessage,
+                                >::terminate(&task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
+                },
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<DataMessage> {
+            __NOTIZIA_TASK_STATE.get().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<DataMessage> {
+            let options = options.control::<ControlMessage>();
+            let (task_ref, mailbox, receiver) = options
+                .channel::<DataMessage>("IngestTask");
+            let task_ref = task_ref.with_path("macrotest005::IngestTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    DataMessage,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = __NOTIZIA_TASK_STATE
+                .scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<DataMessage> {
+            __NOTIZIA_TASK_STATE.get().task_ref()
+        }
+    }
+    impl ::notizia::task::Controlled for IngestTask {
+        type Control = ControlMessage;
+        fn __control(&self) -> ::notizia::task::Control {
+            __NOTIZIA_TASK_STATE.get().control()
+        }
+    }
+};
+fn main() {}
@@ -0,0 +1,16 @@
+use notizia_gen::Task;
+
+#[derive(Clone, Debug)]
+struct DataMessage;
+
+#[derive(Clone, Debug)]
+enum ControlMessage {
+    Pause,
+    Resume,
+}
+
+#[derive(Task)]
+#[task(message = DataMessage, control = ControlMessage)]
+struct IngestTask;
+
+fn main() {}
@@ -0,0 +1,170 @@
+use notizia_gen::Task;
+struct ConnectionMessage;
+#[automatically_derived]
+impl ::core::clone::Clone for ConnectionMessage {
+    #[inline]
+    fn clone(&self) -> ConnectionMessage {
+        ConnectionMessage
+    }
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for ConnectionMessage {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::write_str(f, "ConnectionMessage")
+    }
+}
+#[task(message = ConnectionMessage)]
+enum ConnectionTask {
+    Connecting,
+    Ready { peer: String },
+    Closed,
+}
+const _: () = {
+    static __NOTIZIA_TASK_STATE: ::tokio::task::LocalKey<
+        ::notizia::TaskState<ConnectionMessage>,
+    > = {
+        const __KEY: ::std::thread::LocalKey<
+            std::cell::RefCell<Option<::notizia::TaskState<ConnectionMessage>>>,
+        > = {
+            const __RUST_STD_INTERNAL_INIT: std::cell::RefCell<
+                Option<::notizia::TaskState<ConnectionMessage>>,
+            > = { std::cell::RefCell::new(None) };
+            unsafe {
+                ::std::thread::LocalKey::new(const {
+                    if ::std::mem::needs_drop::<
+                        std::cell::RefCell<
+                            Option<::notizia::TaskState<ConnectionMessage>>,
+                        >,
+                    >() {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: ::std::thread::local_impl::EagerStorage<
+                                std::cell::RefCell<
+                                    Option<::notizia::TaskState<ConnectionMessage>>,
+                                >,
+                            > = ::std::thread::local_impl::EagerStorage::new(
+                                __RUST_STD_INTERNAL_INIT,
+                            );
+                            __RUST_STD_INTERNAL_VAL.get()
+                        }
+                    } else {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: std::cell::RefCell<
+                                Option<::notizia::TaskState<ConnectionMessage>>,
+                            > = __RUST_STD_INTERNAL_INIT;
+                            &__RUST_STD_INTERNAL_VAL
+                        }
+                    }
+                })
+            }
+        };
+        ::tokio::task::LocalKey {
+            inner: __KEY,
+        }
+    };
+    impl ::notizia::Task<ConnectionMessage> for ConnectionTask {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<ConnectionMessage>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<ConnectionMessage>::this(&self);
+            let path = "macrotest007::ConnectionTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
+                async move {
+                    let task = self;
+                    let mb = ::notizia::Task::<ConnectionMessage>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    ::notizia::Runnable::<ConnectionMessage>::start(&task),
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::Runnable::<
+                                    ConnectionMessage,
+                                >::terminate(&task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
+                },
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<ConnectionMessage> {
+            __NOTIZIA_TASK_STATE.get().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<ConnectionMessage> {
+            let (task_ref, mailbox, receiver) = options
+                .channel::<ConnectionMessage>("ConnectionTask");
+            let task_ref = task_ref.with_path("macrotest007::ConnectionTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    ConnectionMessage,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = __NOTIZIA_TASK_STATE
+                .scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<ConnectionMessage> {
+            __NOTIZIA_TASK_STATE.get().task_ref()
+        }
+    }
+};
+fn main() {}
@@ -0,0 +1,14 @@
+use notizia_gen::Task;
+
+#[derive(Clone, Debug)]
+struct ConnectionMessage;
+
+#[derive(Task)]
+#[task(message = ConnectionMessage)]
+enum ConnectionTask {
+    Connecting,
+    Ready { peer: String },
+    Closed,
+}
+
+fn main() {}
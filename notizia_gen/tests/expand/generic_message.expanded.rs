@@ -29,4 +29,151 @@ impl<T: ::core::fmt::Debug> ::core::fmt::Debug for GenericMessage<T> {
 }
 #[task(message = GenericMessage<String>)]
 struct ProcessorTask;
+const _: () = {
+    static __NOTIZIA_TASK_STATE: ::tokio::task::LocalKey<
+        ::notizia::TaskState<GenericMessage<String>>,
+    > = {
+        const __KEY: ::std::thread::LocalKey<
+            std::cell::RefCell<Option<::notizia::TaskState<GenericMessage<String>>>>,
+        > = {
+            const __RUST_STD_INTERNAL_INIT: std::cell::RefCell<
+                Option<::notizia::TaskState<GenericMessage<String>>>,
+            > = { std::cell::RefCell::new(None) };
+            unsafe {
+                ::std::thread::LocalKey::new(const {
+                    if ::std::mem::needs_drop::<
+                        std::cell::RefCell<
+                            Option<::notizia::TaskState<GenericMessage<String>>>,
+                        >,
+                    >() {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: ::std::thread::local_impl::EagerStorage<
+                                std::cell::RefCell<
+                                    Option<::notizia::TaskState<GenericMessage<String>>>,
+                                >,
+                            > = ::std::thread::local_impl::EagerStorage::new(
+                                __RUST_STD_INTERNAL_INIT,
+                            );
+                            __RUST_STD_INTERNAL_VAL.get()
+                        }
+                    } else {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: std::cell::RefCell<
+                                Option<::notizia::TaskState<GenericMessage<String>>>,
+                            > = __RUST_STD_INTERNAL_INIT;
+                            &__RUST_STD_INTERNAL_VAL
+                        }
+                    }
+                })
+            }
+        };
+        ::tokio::task::LocalKey {
+            inner: __KEY,
+        }
+    };
+    impl ::notizia::Task<GenericMessage<String>> for ProcessorTask {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<GenericMessage<String>>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<GenericMessage<String>>::this(&self);
+            let path = "macrotest009::ProcessorTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
+                async move {
+                    let task = self;
+                    let mb = ::notizia::Task::<GenericMessage<String>>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    ::notizia::Runnable::<GenericMessage<String>>::start(&task),
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::Runnable::<
+                                    GenericMessage<String>,
+                                >::terminate(&task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
+                },
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<GenericMessage<String>> {
+            __NOTIZIA_TASK_STATE.get().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<GenericMessage<String>> {
+            let (task_ref, mailbox, receiver) = options
+                .channel::<GenericMessage<String>>("ProcessorTask");
+            let task_ref = task_ref.with_path("macrotest009::ProcessorTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    GenericMessage<String>,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = __NOTIZIA_TASK_STATE
+                .scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<GenericMessage<String>> {
+            __NOTIZIA_TASK_STATE.get().task_ref()
+        }
+    }
+};
 fn main() {}
@@ -0,0 +1,130 @@
+use notizia_gen::Task;
+struct StoreMessage;
+#[automatically_derived]
+impl ::core::clone::Clone for StoreMessage {
+    #[inline]
+    fn clone(&self) -> StoreMessage {
+        StoreMessage
+    }
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for StoreMessage {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        ::core::fmt::Formatter::write_str(f, "StoreMessage")
+    }
+}
+trait Store: Send + Sync {}
+#[task(message = StoreMessage)]
+struct StoreTask<S: Store> {
+    store: S,
+}
+const _: () = {
+    impl<S: Store> ::notizia::Task<StoreMessage> for StoreTask<S>
+    where
+        Self: 'static,
+        StoreMessage: 'static,
+    {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<StoreMessage>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<StoreMessage>::this(&self);
+            let path = "macrotest011::StoreTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
+                async move {
+                    let task = self;
+                    let mb = ::notizia::Task::<StoreMessage>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    ::notizia::Runnable::<StoreMessage>::start(&task),
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::Runnable::<
+                                    StoreMessage,
+                                >::terminate(&task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
+                },
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<StoreMessage> {
+            ::notizia::TaskState::<StoreMessage>::generic().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<StoreMessage> {
+            let (task_ref, mailbox, receiver) = options
+                .channel::<StoreMessage>("StoreTask");
+            let task_ref = task_ref.with_path("macrotest011::StoreTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    StoreMessage,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = ::notizia::TaskState::new(mailbox.clone(), &task_ref)
+                .scope_generic(future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<StoreMessage> {
+            ::notizia::TaskState::<StoreMessage>::generic().task_ref()
+        }
+    }
+};
+fn main() {}
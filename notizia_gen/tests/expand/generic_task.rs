@@ -0,0 +1,14 @@
+use notizia_gen::Task;
+
+#[derive(Clone, Debug)]
+struct StoreMessage;
+
+trait Store: Send + Sync {}
+
+#[derive(Task)]
+#[task(message = StoreMessage)]
+struct StoreTask<S: Store> {
+    store: S,
+}
+
+fn main() {}
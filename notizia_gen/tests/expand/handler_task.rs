@@ -0,0 +1,14 @@
+use notizia_gen::Task;
+
+#[derive(Clone, Debug)]
+enum CounterMessage {
+    Increment,
+}
+
+#[derive(Task)]
+#[task(message = CounterMessage, handler)]
+struct CounterTask {
+    count: u32,
+}
+
+fn main() {}
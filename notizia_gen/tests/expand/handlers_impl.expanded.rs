@@ -0,0 +1,229 @@
+use notizia_gen::Task;
+use notizia_gen::handlers;
+enum CounterMessage {
+    Increment,
+    Add { amount: u32 },
+}
+#[automatically_derived]
+impl ::core::fmt::Debug for CounterMessage {
+    #[inline]
+    fn fmt(&self, f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+        match self {
+            CounterMessage::Increment => {
+                ::core::fmt::Formatter::write_str(f, "Increment")
+            }
+            CounterMessage::Add { amount: __self_0 } => {
+                ::core::fmt::Formatter::debug_struct_field1_finish(
+                    f,
+                    "Add",
+                    "amount",
+                    &__self_0,
+                )
+            }
+        }
+    }
+}
+#[task(message = CounterMessage, handler)]
+struct CounterTask {
+    count: u32,
+}
+const _: () = {
+    static __NOTIZIA_TASK_STATE: ::tokio::task::LocalKey<
+        ::notizia::TaskState<CounterMessage>,
+    > = {
+        const __KEY: ::std::thread::LocalKey<
+            std::cell::RefCell<Option<::notizia::TaskState<CounterMessage>>>,
+        > = {
+            const __RUST_STD_INTERNAL_INIT: std::cell::RefCell<
+                Option<::notizia::TaskState<CounterMessage>>,
+            > = { std::cell::RefCell::new(None) };
+            unsafe {
+                ::std::thread::LocalKey::new(const {
+                    if ::std::mem::needs_drop::<
+                        std::cell::RefCell<Option<::notizia::TaskState<CounterMessage>>>,
+                    >() {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: ::std::thread::local_impl::EagerStorage<
+                                std::cell::RefCell<
+                                    Option<::notizia::TaskState<CounterMessage>>,
+                                >,
+                            > = ::std::thread::local_impl::EagerStorage::new(
+                                __RUST_STD_INTERNAL_INIT,
+                            );
+                            __RUST_STD_INTERNAL_VAL.get()
+                        }
+                    } else {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: std::cell::RefCell<
+                                Option<::notizia::TaskState<CounterMessage>>,
+                            > = __RUST_STD_INTERNAL_INIT;
+                            &__RUST_STD_INTERNAL_VAL
+                        }
+                    }
+                })
+            }
+        };
+        ::tokio::task::LocalKey {
+            inner: __KEY,
+        }
+    };
+    impl ::notizia::Task<CounterMessage> for CounterTask {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<CounterMessage>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<CounterMessage>::this(&self);
+            let path = "macrotest015::CounterTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
+                async move {
+                    let mut task = self;
+                    let mut ctx = ::notizia::task::Context::new(
+                        __NOTIZIA_TASK_STATE.get(),
+                    );
+                    let mb = ::notizia::Task::<CounterMessage>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    async {
+                                        while !ctx.is_stopped() {
+                                            let ::std::result::Result::Ok(msg) = ::notizia::Task::<
+                                                CounterMessage,
+                                            >::recv(&task)
+                                                .await else {
+                                                break;
+                                            };
+                                            {
+                                                #[allow(unused_imports)]
+                                                use ::notizia::core::correlation::{
+                                                    ViaAny as _, ViaCorrelated as _,
+                                                };
+                                                ::notizia::core::correlation::enter(
+                                                    (&::notizia::core::correlation::Probe(&msg))
+                                                        .correlation_id(),
+                                                );
+                                            }
+                                            ::notizia::task::Context::__dispatch(
+                                                    &mut ctx,
+                                                    &mut task,
+                                                    msg,
+                                                )
+                                                .await;
+                                            ::notizia::Task::<CounterMessage>::mailbox(&task).ack();
+                                        }
+                                    },
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::task::Handler::<
+                                    CounterMessage,
+                                >::terminate(&mut task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
+                },
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<CounterMessage> {
+            __NOTIZIA_TASK_STATE.get().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<CounterMessage> {
+            let (task_ref, mailbox, receiver) = options
+                .channel::<CounterMessage>("CounterTask");
+            let task_ref = task_ref.with_path("macrotest015::CounterTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    CounterMessage,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = __NOTIZIA_TASK_STATE
+                .scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<CounterMessage> {
+            __NOTIZIA_TASK_STATE.get().task_ref()
+        }
+    }
+};
+impl CounterTask {
+    async fn increment(&mut self) {
+        self.count += 1;
+    }
+    async fn add(&mut self, amount: u32) {
+        self.count += amount;
+    }
+}
+impl ::notizia::task::Handler<CounterMessage> for CounterTask {
+    async fn handle(
+        &mut self,
+        msg: CounterMessage,
+        ctx: &mut ::notizia::task::Context<CounterMessage>,
+    ) {
+        let _ = &ctx;
+        match msg {
+            CounterMessage::Increment {} => {
+                self.increment().await;
+            }
+            CounterMessage::Add { amount } => {
+                self.add(amount).await;
+            }
+        }
+    }
+}
+fn main() {}
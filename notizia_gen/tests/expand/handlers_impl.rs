@@ -0,0 +1,27 @@
+use notizia_gen::Task;
+use notizia_gen::handlers;
+
+#[derive(Debug)]
+enum CounterMessage {
+    Increment,
+    Add { amount: u32 },
+}
+
+#[derive(Task)]
+#[task(message = CounterMessage, handler)]
+struct CounterTask {
+    count: u32,
+}
+
+#[handlers(message = CounterMessage)]
+impl CounterTask {
+    async fn increment(&mut self) {
+        self.count += 1;
+    }
+
+    async fn add(&mut self, amount: u32) {
+        self.count += amount;
+    }
+}
+
+fn main() {}
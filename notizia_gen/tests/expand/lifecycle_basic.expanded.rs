@@ -18,80 +18,147 @@ impl ::core::fmt::Debug for Message {
 struct BasicLifecycleTask {
     id: usize,
 }
-impl notizia::Task<Message> for BasicLifecycleTask {
-    fn __setup(
-        &self,
-        receiver: notizia::tokio::sync::mpsc::UnboundedReceiver<Message>,
-    ) -> impl std::future::Future<Output = notizia::TerminateReason> + Send {
-        async move {
-            let mb = self.mailbox();
-            mb.set_receiver(receiver).await;
-            let start_result = notizia::futures::FutureExt::catch_unwind(
-                    std::panic::AssertUnwindSafe(self.start()),
-                )
-                .await;
-            let reason = match start_result {
-                Ok(()) => notizia::TerminateReason::Normal,
-                Err(panic_payload) => {
-                    let msg = if let Some(s) = panic_payload.downcast_ref::<&str>() {
-                        s.to_string()
-                    } else if let Some(s) = panic_payload.downcast_ref::<String>() {
-                        s.clone()
+const _: () = {
+    static __NOTIZIA_TASK_STATE: ::tokio::task::LocalKey<
+        ::notizia::TaskState<Message>,
+    > = {
+        const __KEY: ::std::thread::LocalKey<
+            std::cell::RefCell<Option<::notizia::TaskState<Message>>>,
+        > = {
+            const __RUST_STD_INTERNAL_INIT: std::cell::RefCell<
+                Option<::notizia::TaskState<Message>>,
+            > = { std::cell::RefCell::new(None) };
+            unsafe {
+                ::std::thread::LocalKey::new(const {
+                    if ::std::mem::needs_drop::<
+                        std::cell::RefCell<Option<::notizia::TaskState<Message>>>,
+                    >() {
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: ::std::thread::local_impl::EagerStorage<
+                                std::cell::RefCell<Option<::notizia::TaskState<Message>>>,
+                            > = ::std::thread::local_impl::EagerStorage::new(
+                                __RUST_STD_INTERNAL_INIT,
+                            );
+                            __RUST_STD_INTERNAL_VAL.get()
+                        }
                     } else {
-                        "unknown panic".to_string()
-                    };
-                    notizia::TerminateReason::Panic(msg)
-                }
-            };
-            let terminate_result = notizia::futures::FutureExt::catch_unwind(
-                    std::panic::AssertUnwindSafe(self.terminate(reason.clone())),
-                )
-                .await;
-            if let Err(terminate_panic) = terminate_result {
-                let msg = if let Some(s) = terminate_panic.downcast_ref::<&str>() {
-                    s.to_string()
-                } else if let Some(s) = terminate_panic.downcast_ref::<String>() {
-                    s.clone()
-                } else {
-                    "unknown panic".to_string()
-                };
-                {
-                    ::std::io::_eprint(
-                        format_args!("Warning: terminate() hook panicked: {0}\n", msg),
-                    );
-                };
+                        |_| {
+                            #[thread_local]
+                            static __RUST_STD_INTERNAL_VAL: std::cell::RefCell<
+                                Option<::notizia::TaskState<Message>>,
+                            > = __RUST_STD_INTERNAL_INIT;
+                            &__RUST_STD_INTERNAL_VAL
+                        }
+                    }
+                })
             }
-            reason
+        };
+        ::tokio::task::LocalKey {
+            inner: __KEY,
         }
-    }
-    fn mailbox(&self) -> notizia::Mailbox<Message> {
-        __BasicLifecycleTask_gen::BasicLifecycleTaskState.get().mailbox
-    }
-    fn run(self) -> notizia::TaskHandle<Message> {
-        let (sender, receiver) = notizia::tokio::sync::mpsc::unbounded_channel::<
-            Message,
-        >();
-        let task = __BasicLifecycleTask_gen::BasicLifecycleTaskState
-            .scope(
-                notizia::TaskState {
-                    mailbox: notizia::Mailbox::new(),
-                    sender: sender.clone(),
-                },
+    };
+    impl ::notizia::Task<Message> for BasicLifecycleTask {
+        fn __setup(
+            self,
+            receiver: ::notizia::runtime::Receiver<Message>,
+            deadline: ::std::option::Option<::std::time::Duration>,
+        ) -> impl ::std::future::Future<
+            Output = ::notizia::TerminateReason,
+        > + ::std::marker::Send {
+            let this = ::notizia::Task::<Message>::this(&self);
+            let path = "macrotest017::BasicLifecycleTask";
+            ::notizia::core::trace::instrument(
+                &this,
+                path,
                 async move {
-                    let handle = self.__setup(receiver);
-                    handle.await
+                    let task = self;
+                    let mb = ::notizia::Task::<Message>::mailbox(&task);
+                    mb.set_receiver(receiver).await;
+                    let start_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::core::lifecycle::with_deadline(
+                                    deadline,
+                                    ::notizia::Runnable::<Message>::start(&task),
+                                ),
+                            ),
+                        )
+                        .await;
+                    let reason = match start_result {
+                        ::std::result::Result::Ok(::std::option::Option::None) => {
+                            ::notizia::TerminateReason::DeadlineExceeded
+                        }
+                        ::std::result::Result::Ok(
+                            ::std::option::Option::Some(()),
+                        ) if mb.is_passivated() => ::notizia::TerminateReason::Idle,
+                        ::std::result::Result::Ok(::std::option::Option::Some(())) => {
+                            ::notizia::TerminateReason::Normal
+                        }
+                        ::std::result::Result::Err(panic_payload) => {
+                            let msg = if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<&str>()
+                            {
+                                ::std::string::ToString::to_string(*s)
+                            } else if let ::std::option::Option::Some(s) = panic_payload
+                                .downcast_ref::<::std::string::String>()
+                            {
+                                ::std::clone::Clone::clone(s)
+                            } else {
+                                ::std::string::ToString::to_string("unknown panic")
+                            };
+                            ::notizia::TerminateReason::Panic(msg)
+                        }
+                    };
+                    let terminate_result = ::notizia::futures::FutureExt::catch_unwind(
+                            ::std::panic::AssertUnwindSafe(
+                                ::notizia::Runnable::<
+                                    Message,
+                                >::terminate(&task, ::std::clone::Clone::clone(&reason)),
+                            ),
+                        )
+                        .await;
+                    if let ::std::result::Result::Err(terminate_panic) = terminate_result {
+                        let msg = if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<&str>()
+                        {
+                            ::std::string::ToString::to_string(*s)
+                        } else if let ::std::option::Option::Some(s) = terminate_panic
+                            .downcast_ref::<::std::string::String>()
+                        {
+                            ::std::clone::Clone::clone(s)
+                        } else {
+                            ::std::string::ToString::to_string("unknown panic")
+                        };
+                        ::notizia::core::trace::terminate_panicked(&msg);
+                    }
+                    reason
                 },
-            );
-        let handle = notizia::tokio::spawn(task);
-        notizia::TaskHandle::new(sender, handle)
-    }
-    fn this(&self) -> notizia::TaskRef<Message> {
-        notizia::TaskRef::new(
-            __BasicLifecycleTask_gen::BasicLifecycleTaskState.get().sender,
-        )
+            )
+        }
+        fn mailbox(&self) -> ::notizia::Mailbox<Message> {
+            __NOTIZIA_TASK_STATE.get().mailbox
+        }
+        fn __spawn(
+            self,
+            options: ::notizia::task::SpawnOptions,
+        ) -> ::notizia::TaskHandle<Message> {
+            let (task_ref, mailbox, receiver) = options
+                .channel::<Message>("BasicLifecycleTask");
+            let task_ref = task_ref.with_path("macrotest017::BasicLifecycleTask");
+            let deadline = options.deadline();
+            let future = async move {
+                let handle = ::notizia::Task::<
+                    Message,
+                >::__setup(self, receiver, deadline);
+                handle.await
+            };
+            let task = __NOTIZIA_TASK_STATE
+                .scope(::notizia::TaskState::new(mailbox.clone(), &task_ref), future);
+            options.spawn(task_ref, &mailbox, task)
+        }
+        fn this(&self) -> ::notizia::TaskRef<Message> {
+            __NOTIZIA_TASK_STATE.get().task_ref()
+        }
     }
-}
-mod __BasicLifecycleTask_gen {
-    use super::*;
-}
+};
 fn main() {}
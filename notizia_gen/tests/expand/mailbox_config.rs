@@ -0,0 +1,7 @@
+use notizia_gen::Task;
+
+#[derive(Task)]
+#[task(message = u32, mailbox = bounded(128), overflow = drop_oldest)]
+struct LatestTask;
+
+fn main() {}
@@ -28,8 +28,8 @@ impl ::core::clone::Clone for CounterStats {
     }
 }
 enum CounterMsg {
-    GetCount { reply_to: ::notizia::tokio::sync::oneshot::Sender<u32> },
-    GetStats { reply_to: ::notizia::tokio::sync::oneshot::Sender<CounterStats> },
+    GetCount { reply_to: ::notizia::Reply<u32> },
+    GetStats { reply_to: ::notizia::Reply<CounterStats> },
     Increment,
     Decrement,
     Add(u32),
@@ -63,4 +63,298 @@ impl ::core::fmt::Debug for CounterMsg {
         }
     }
 }
+/**Typed calls for the request variants of [`CounterMsg`].
+
+Implemented for `TaskHandle` and `TaskRef`, so requests can be sent like ordinary async method calls.*/
+trait CounterMsgCalls {
+    ///Call the task with [`CounterMsg::GetCount`] and wait for the reply.
+    fn get_count(
+        &self,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send;
+    ///Call the task with [`CounterMsg::GetCount`], waiting at most `timeout` for the reply.
+    fn get_count_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send;
+    ///Call the task with [`CounterMsg::GetStats`] and wait for the reply.
+    fn get_stats(
+        &self,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<CounterStats>> + Send;
+    ///Call the task with [`CounterMsg::GetStats`], waiting at most `timeout` for the reply.
+    fn get_stats_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<CounterStats>> + Send;
+}
+impl CounterMsgCalls for ::notizia::TaskHandle<CounterMsg>
+where
+    CounterMsg: Send + 'static,
+{
+    fn get_count(
+        &self,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        self.get_count_with_timeout(::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn get_count_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = CounterMsg::GetCount { reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+    fn get_stats(
+        &self,
+    ) -> impl ::std::future::Future<
+        Output = ::notizia::CallResult<CounterStats>,
+    > + Send {
+        self.get_stats_with_timeout(::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn get_stats_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<
+        Output = ::notizia::CallResult<CounterStats>,
+    > + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = CounterMsg::GetStats { reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+}
+impl CounterMsgCalls for ::notizia::TaskRef<CounterMsg>
+where
+    CounterMsg: Send + 'static,
+{
+    fn get_count(
+        &self,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        self.get_count_with_timeout(::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn get_count_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = CounterMsg::GetCount { reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+    fn get_stats(
+        &self,
+    ) -> impl ::std::future::Future<
+        Output = ::notizia::CallResult<CounterStats>,
+    > + Send {
+        self.get_stats_with_timeout(::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn get_stats_with_timeout(
+        &self,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<
+        Output = ::notizia::CallResult<CounterStats>,
+    > + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = CounterMsg::GetStats { reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+}
+impl ::notizia::core::Correlated for CounterMsg {
+    fn correlation_id(&self) -> Option<::notizia::core::CorrelationId> {
+        match self {
+            CounterMsg::GetCount { reply_to, .. } => {
+                ::notizia::core::Correlated::correlation_id(reply_to)
+            }
+            CounterMsg::GetStats { reply_to, .. } => {
+                ::notizia::core::Correlated::correlation_id(reply_to)
+            }
+            _ => None,
+        }
+    }
+}
+impl ::notizia::core::schema::Describe for CounterMsg {
+    const SCHEMA: ::notizia::core::schema::Schema = ::notizia::core::schema::Schema {
+        name: "CounterMsg",
+        version: None,
+        serde: false,
+        variants: &[
+            ::notizia::core::schema::VariantSchema {
+                name: "GetCount",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: Some(::notizia::core::schema::ReplySchema::Single("u32")),
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "GetStats",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: Some(::notizia::core::schema::ReplySchema::Single("CounterStats")),
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Increment",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Decrement",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Add",
+                shape: ::notizia::core::schema::Shape::Tuple,
+                fields: &[
+                    ::notizia::core::schema::FieldSchema {
+                        name: None,
+                        ty: "u32",
+                    },
+                ],
+                reply: None,
+            },
+        ],
+    };
+}
 fn main() {}
@@ -29,4 +29,31 @@ impl ::core::clone::Clone for SimpleMsg {
         }
     }
 }
+impl ::notizia::core::schema::Describe for SimpleMsg {
+    const SCHEMA: ::notizia::core::schema::Schema = ::notizia::core::schema::Schema {
+        name: "SimpleMsg",
+        version: None,
+        serde: false,
+        variants: &[
+            ::notizia::core::schema::VariantSchema {
+                name: "Increment",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Decrement",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Stop",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+        ],
+    };
+}
 fn main() {}
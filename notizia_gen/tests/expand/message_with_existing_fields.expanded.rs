@@ -1,6 +1,6 @@
 use notizia_gen::message;
 enum EchoMsg {
-    Echo { id: u32, reply_to: ::notizia::tokio::sync::oneshot::Sender<u32> },
+    Echo { id: u32, reply_to: ::notizia::Reply<u32> },
     Stop,
 }
 #[automatically_derived]
@@ -22,4 +22,168 @@ impl ::core::fmt::Debug for EchoMsg {
         }
     }
 }
+/**Typed calls for the request variants of [`EchoMsg`].
+
+Implemented for `TaskHandle` and `TaskRef`, so requests can be sent like ordinary async method calls.*/
+trait EchoMsgCalls {
+    ///Call the task with [`EchoMsg::Echo`] and wait for the reply.
+    fn echo(
+        &self,
+        id: u32,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send;
+    ///Call the task with [`EchoMsg::Echo`], waiting at most `timeout` for the reply.
+    fn echo_with_timeout(
+        &self,
+        id: u32,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send;
+}
+impl EchoMsgCalls for ::notizia::TaskHandle<EchoMsg>
+where
+    EchoMsg: Send + 'static,
+{
+    fn echo(
+        &self,
+        id: u32,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        self.echo_with_timeout(id, ::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn echo_with_timeout(
+        &self,
+        id: u32,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = EchoMsg::Echo { id, reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+}
+impl EchoMsgCalls for ::notizia::TaskRef<EchoMsg>
+where
+    EchoMsg: Send + 'static,
+{
+    fn echo(
+        &self,
+        id: u32,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        self.echo_with_timeout(id, ::notizia::core::DEFAULT_CALL_TIMEOUT)
+    }
+    fn echo_with_timeout(
+        &self,
+        id: u32,
+        timeout: impl ::notizia::core::IntoTimeout,
+    ) -> impl ::std::future::Future<Output = ::notizia::CallResult<u32>> + Send {
+        let timeout = ::notizia::core::IntoTimeout::into_timeout(timeout);
+        async move {
+            {
+                async {
+                    let __notizia_task = &self;
+                    ::notizia::core::errors::CallError::check_self_call(
+                        __notizia_task.id(),
+                        __notizia_task.name(),
+                    )?;
+                    let correlation = ::notizia::core::CorrelationId::current_or_next();
+                    let (reply_to, rx) = ::notizia::core::reply::channel(correlation);
+                    let msg = EchoMsg::Echo { id, reply_to };
+                    let started = ::notizia::runtime::Instant::now();
+                    __notizia_task
+                        .send(msg)
+                        .map_err(|err| {
+                            ::notizia::core::errors::CallError::send_failed(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                err,
+                            )
+                        })?;
+                    ::notizia::runtime::timeout(
+                            ::notizia::core::IntoTimeout::into_timeout(timeout),
+                            rx,
+                        )
+                        .await
+                        .map_err(|_| {
+                            ::notizia::core::errors::CallError::timed_out(
+                                __notizia_task.id(),
+                                __notizia_task.name(),
+                                correlation,
+                                started,
+                            )
+                        })?
+                }
+            }
+                .await
+        }
+    }
+}
+impl ::notizia::core::Correlated for EchoMsg {
+    fn correlation_id(&self) -> Option<::notizia::core::CorrelationId> {
+        match self {
+            EchoMsg::Echo { reply_to, .. } => {
+                ::notizia::core::Correlated::correlation_id(reply_to)
+            }
+            _ => None,
+        }
+    }
+}
+impl ::notizia::core::schema::Describe for EchoMsg {
+    const SCHEMA: ::notizia::core::schema::Schema = ::notizia::core::schema::Schema {
+        name: "EchoMsg",
+        version: None,
+        serde: false,
+        variants: &[
+            ::notizia::core::schema::VariantSchema {
+                name: "Echo",
+                shape: ::notizia::core::schema::Shape::Struct,
+                fields: &[
+                    ::notizia::core::schema::FieldSchema {
+                        name: Some("id"),
+                        ty: "u32",
+                    },
+                ],
+                reply: Some(::notizia::core::schema::ReplySchema::Single("u32")),
+            },
+            ::notizia::core::schema::VariantSchema {
+                name: "Stop",
+                shape: ::notizia::core::schema::Shape::Unit,
+                fields: &[],
+                reply: None,
+            },
+        ],
+    };
+}
 fn main() {}